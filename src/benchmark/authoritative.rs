@@ -0,0 +1,86 @@
+//! Name server discovery for `--authoritative` runs.
+
+use super::bootstrap::resolve_hostnames;
+use super::resolver::create_resolver;
+use super::socket::BenchResolver;
+use crate::config::Config;
+use crate::dns::{DnsServer, HostnameServer, LookupStrategy};
+use crate::error::{DnsError, Error};
+use crate::platform::get_system_dns_servers;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::proto::xfer::Protocol;
+use std::net::SocketAddr;
+
+/// Find the authoritative servers of the benchmark domain's zone
+///
+/// NS records are looked up through `config.bootstrap`, or the first system
+/// resolver, walking up from the domain until a zone is found. Each name
+/// server is then resolved to one server per address.
+pub async fn zone_servers(config: &Config) -> Result<Vec<DnsServer>, Error> {
+    let resolver = lookup_resolver(config).await?;
+    let domain = config.domain.trim_end_matches('.');
+    let names = zone_name_servers(&resolver, domain)
+        .await
+        .ok_or_else(|| DnsError::NoNameServers(domain.to_string()))?;
+
+    let hosts: Vec<_> = names
+        .iter()
+        .map(|ns| {
+            let host = ns.trim_end_matches('.').to_string();
+            HostnameServer {
+                name: host.clone(),
+                host,
+                port: 53,
+                protocol: None,
+                tls_name: None,
+                tags: Vec::new(),
+            }
+        })
+        .collect();
+    Ok(resolve_hostnames(&hosts, config).await)
+}
+
+/// Create a resolver for looking up name servers: `config.bootstrap`, or the first system resolver
+pub(super) async fn lookup_resolver(config: &Config) -> Result<BenchResolver, Error> {
+    let resolver_ip = match config.bootstrap {
+        Some(ip) => ip,
+        None => get_system_dns_servers(
+            config.name_server_ip,
+            config.detect_cache_dir().as_deref(),
+            &config.adapter_filter(),
+        )
+        .await
+        .ok()
+        .and_then(|servers| servers.first().map(DnsServer::ip))
+        .ok_or_else(|| {
+            Error::InvalidArgument("No resolver to look up name servers with; pass --bootstrap".to_string())
+        })?,
+    };
+    Ok(create_resolver(
+        SocketAddr::new(resolver_ip, 53),
+        Protocol::Udp,
+        config.timeout_ms(),
+        LookupStrategy::from(config.name_server_ip).into(),
+        &config.socket,
+    ))
+}
+
+/// Find the name servers of the closest zone containing `domain`
+pub async fn zone_name_servers(resolver: &BenchResolver, domain: &str) -> Option<Vec<String>> {
+    let mut zone = domain;
+    loop {
+        if let Ok(lookup) = resolver.lookup(format!("{zone}."), RecordType::NS).await {
+            let names: Vec<_> = lookup
+                .iter()
+                .filter_map(|data| match data {
+                    RData::NS(ns) => Some(ns.0.to_ascii()),
+                    _ => None,
+                })
+                .collect();
+            if !names.is_empty() {
+                return Some(names);
+            }
+        }
+        zone = zone.split_once('.')?.1;
+    }
+}
@@ -0,0 +1,116 @@
+//! Bootstrap resolution of hostname-based custom servers.
+
+use super::resolver::create_resolver;
+use super::socket::BenchResolver;
+use crate::config::Config;
+use crate::dns::{DnsServer, HostnameServer, IpVersion, LookupStrategy};
+use hickory_resolver::proto::xfer::Protocol;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use tokio::task::JoinSet;
+
+/// Resolve hostname entries into one server per address
+///
+/// Hosts are looked up through `config.bootstrap` when set, otherwise through
+/// the system resolver. Hosts that fail to resolve are reported as warnings.
+pub async fn resolve_hostnames(hosts: &[HostnameServer], config: &Config) -> Vec<DnsServer> {
+    let resolver = config.bootstrap.map(|ip| {
+        create_resolver(
+            SocketAddr::new(ip, 53),
+            Protocol::Udp,
+            config.timeout_ms(),
+            LookupStrategy::from(config.name_server_ip).into(),
+            &config.socket,
+        )
+    });
+
+    let mut tasks = JoinSet::new();
+    for (i, host) in hosts.iter().enumerate() {
+        let name = host.host.clone();
+        let resolver = resolver.clone();
+        let ip_version = config.name_server_ip;
+        tasks.spawn(async move { (i, lookup(&name, resolver.as_ref(), ip_version).await) });
+    }
+
+    let mut resolved: Vec<(usize, Vec<IpAddr>)> = Vec::with_capacity(hosts.len());
+    while let Some(joined) = tasks.join_next().await {
+        // A lookup task that panicked loses only its own host
+        let Ok((i, result)) = joined else {
+            continue;
+        };
+        match result {
+            Ok(ips) => resolved.push((i, ips)),
+            Err(e) => tracing::warn!("Failed to resolve {}: {e}", hosts[i].host),
+        }
+    }
+    resolved.sort_by_key(|(i, _)| *i);
+
+    resolved
+        .into_iter()
+        .flat_map(|(i, ips)| ips.into_iter().map(move |ip| hosts[i].to_server(ip)))
+        .collect()
+}
+
+/// Look up the addresses of a host matching the requested IP version
+async fn lookup(
+    host: &str,
+    resolver: Option<&BenchResolver>,
+    ip_version: IpVersion,
+) -> Result<Vec<IpAddr>, String> {
+    let ips: Vec<IpAddr> = match resolver {
+        Some(resolver) => resolver
+            .lookup_ip(format!("{host}."))
+            .await
+            .map_err(|e| e.to_string())?
+            .iter()
+            .collect(),
+        None => tokio::net::lookup_host((host, 0))
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+
+    let ips = matching_addresses(ips, ip_version);
+
+    if ips.is_empty() {
+        Err(format!("no {ip_version} addresses"))
+    } else {
+        Ok(ips)
+    }
+}
+
+/// Addresses of the requested IP version, each once, in the resolver's order
+///
+/// The system resolver can repeat an address, and not always next to the
+/// earlier copy, so a plain `dedup` isn't enough.
+fn matching_addresses(ips: Vec<IpAddr>, ip_version: IpVersion) -> Vec<IpAddr> {
+    let mut unique = HashSet::new();
+    ips.into_iter()
+        .filter(|ip| match ip_version {
+            IpVersion::V4 => ip.is_ipv4(),
+            IpVersion::V6 => ip.is_ipv6(),
+            IpVersion::Both => true,
+        })
+        .filter(|ip| unique.insert(*ip))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_addresses() {
+        let ips: Vec<IpAddr> = ["1.1.1.1", "2606:4700::1111", "1.0.0.1", "1.1.1.1", "2606:4700::1111"]
+            .iter()
+            .map(|ip| ip.parse().unwrap())
+            .collect();
+        let strings = |version| -> Vec<String> {
+            matching_addresses(ips.clone(), version).iter().map(ToString::to_string).collect()
+        };
+        assert_eq!(strings(IpVersion::Both), ["1.1.1.1", "2606:4700::1111", "1.0.0.1"]);
+        assert_eq!(strings(IpVersion::V4), ["1.1.1.1", "1.0.0.1"]);
+        assert_eq!(strings(IpVersion::V6), ["2606:4700::1111"]);
+    }
+}
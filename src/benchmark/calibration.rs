@@ -0,0 +1,135 @@
+//! Host timing calibration recorded with each run.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Clock reads used to estimate the cost of taking a timestamp
+#[cfg(feature = "engine")]
+const CLOCK_READS: u32 = 10_000;
+
+/// Loopback round trips used to estimate scheduling jitter
+#[cfg(feature = "engine")]
+const LOOPBACK_SAMPLES: usize = 200;
+
+/// Give up on a loopback round trip after this long
+#[cfg(feature = "engine")]
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How precisely this host can time queries
+///
+/// Latency differences below the noise floor are within the measurement
+/// error of the host and shouldn't be read as one server being faster.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimingCalibration {
+    /// Cost of reading the clock, in microseconds
+    pub timer_overhead_us: f64,
+    /// Median loopback UDP round trip through the async runtime, in microseconds
+    pub loopback_rtt_us: f64,
+    /// 95th percentile minus median of loopback round trips, in microseconds
+    pub jitter_us: f64,
+    /// Smallest latency difference worth trusting, in microseconds
+    pub noise_floor_us: f64,
+    /// Loopback round trips measured
+    pub samples: u32,
+}
+
+impl TimingCalibration {
+    /// Summarize a clock read cost and loopback round-trip times
+    pub fn from_samples(timer_overhead: Duration, round_trips: &mut [Duration]) -> Self {
+        round_trips.sort_unstable();
+        let us = |d: Duration| d.as_secs_f64() * 1_000_000.0;
+        let percentile = |p: usize| round_trips.get((round_trips.len() * p / 100).min(round_trips.len().saturating_sub(1)));
+
+        let median = percentile(50).copied().unwrap_or_default();
+        let p95 = percentile(95).copied().unwrap_or_default();
+        let timer_overhead_us = us(timer_overhead);
+        let jitter_us = us(p95.saturating_sub(median));
+
+        Self {
+            timer_overhead_us,
+            loopback_rtt_us: us(median),
+            jitter_us,
+            noise_floor_us: timer_overhead_us * 2.0 + jitter_us,
+            samples: round_trips.len() as u32,
+        }
+    }
+
+    /// Noise floor in milliseconds
+    pub fn noise_floor_ms(&self) -> f64 {
+        self.noise_floor_us / 1000.0
+    }
+}
+
+/// Measure timer overhead and scheduling jitter of this host
+///
+/// The jitter comes from round trips to a UDP echo task on loopback, which
+/// go through the same socket and runtime paths as real queries but involve
+/// no network.
+#[cfg(feature = "engine")]
+pub async fn calibrate() -> std::io::Result<TimingCalibration> {
+    use std::io;
+    use std::time::Instant;
+    use tokio::net::UdpSocket;
+
+    let start = Instant::now();
+    for _ in 0..CLOCK_READS {
+        std::hint::black_box(Instant::now());
+    }
+    let timer_overhead = start.elapsed() / CLOCK_READS;
+
+    let echo = UdpSocket::bind("127.0.0.1:0").await?;
+    let echo_addr = echo.local_addr()?;
+    let echo_task = tokio::spawn(async move {
+        let mut buf = [0u8; 64];
+        while let Ok((len, from)) = echo.recv_from(&mut buf).await {
+            let _ = echo.send_to(&buf[..len], from).await;
+        }
+    });
+
+    let client = UdpSocket::bind("127.0.0.1:0").await?;
+    client.connect(echo_addr).await?;
+
+    let mut round_trips = Vec::with_capacity(LOOPBACK_SAMPLES);
+    let mut buf = [0u8; 64];
+    let result = async {
+        for i in 0..LOOPBACK_SAMPLES as u32 {
+            let start = Instant::now();
+            client.send(&i.to_be_bytes()).await?;
+            tokio::time::timeout(LOOPBACK_TIMEOUT, client.recv(&mut buf))
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "loopback echo timed out"))??;
+            round_trips.push(start.elapsed());
+        }
+        Ok::<_, io::Error>(())
+    }
+    .await;
+    echo_task.abort();
+    result?;
+
+    Ok(TimingCalibration::from_samples(timer_overhead, &mut round_trips))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_samples() {
+        let mut round_trips: Vec<_> = (1..=100).map(Duration::from_micros).collect();
+        let calibration = TimingCalibration::from_samples(Duration::from_nanos(500), &mut round_trips);
+
+        assert_eq!(calibration.samples, 100);
+        assert_eq!(calibration.loopback_rtt_us, 51.0);
+        assert_eq!(calibration.jitter_us, 45.0);
+        assert_eq!(calibration.noise_floor_us, 46.0);
+    }
+
+    #[cfg(feature = "engine")]
+    #[tokio::test]
+    async fn test_calibrate_loopback() {
+        let calibration = calibrate().await.unwrap();
+        assert_eq!(calibration.samples, LOOPBACK_SAMPLES as u32);
+        assert!(calibration.loopback_rtt_us > 0.0);
+        assert!(calibration.noise_floor_us >= calibration.jitter_us);
+    }
+}
@@ -0,0 +1,400 @@
+//! Fault-injecting DNS server for robustness testing.
+//!
+//! `--chaos` benchmarks a local server instead of real resolvers. It answers
+//! every query itself with a fixed address, or forwards it to a lab resolver,
+//! and corrupts a share of its UDP answers: some are delayed (past the
+//! timeout, at worst), some are malformed, and some are truncated. TCP
+//! answers are always intact, so clients that fall back to TCP after a
+//! truncated answer recover. Comparing the benchmark's statistics to the
+//! faults that were actually injected shows how the engine, and its
+//! adaptive timeout, behave against an unreliable server.
+
+use hickory_resolver::proto::op::{Message, MessageType, ResponseCode};
+use hickory_resolver::proto::rr::rdata::{A, AAAA};
+use hickory_resolver::proto::rr::{RData, Record, RecordType};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::task::JoinHandle;
+
+/// Address the built-in mock answers A queries with (TEST-NET-1)
+pub const MOCK_ANSWER_V4: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+
+/// Address the built-in mock answers AAAA queries with (documentation prefix)
+pub const MOCK_ANSWER_V6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+/// TTL of the built-in mock's answers
+const MOCK_TTL: u32 = 300;
+
+/// Shortest injected delay
+const MIN_DELAY: Duration = Duration::from_millis(50);
+
+/// Longest injected delay, beyond the default query timeout
+const MAX_DELAY: Duration = Duration::from_secs(3);
+
+/// Give up on the lab resolver after this long
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest message accepted over UDP
+const MAX_UDP_BYTES: usize = 4096;
+
+/// Share of UDP answers hit by each fault, from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosRates {
+    /// Answers sent after a random delay
+    pub delayed: f64,
+    /// Answers replaced by an undecodable message with the query's ID
+    pub malformed: f64,
+    /// Answers stripped of their records and sent with the TC bit
+    pub truncated: f64,
+}
+
+impl Default for ChaosRates {
+    fn default() -> Self {
+        Self {
+            delayed: 0.1,
+            malformed: 0.1,
+            truncated: 0.1,
+        }
+    }
+}
+
+/// Fault injected into one answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    Delayed,
+    Malformed,
+    Truncated,
+}
+
+impl ChaosRates {
+    /// Rates of a server that answers every query correctly
+    pub const NONE: Self = Self {
+        delayed: 0.0,
+        malformed: 0.0,
+        truncated: 0.0,
+    };
+
+    /// Pick the fault for the next answer, if any
+    fn pick(&self) -> Option<Fault> {
+        let roll = random_fraction();
+        if roll < self.delayed {
+            Some(Fault::Delayed)
+        } else if roll < self.delayed + self.malformed {
+            Some(Fault::Malformed)
+        } else if roll < self.delayed + self.malformed + self.truncated {
+            Some(Fault::Truncated)
+        } else {
+            None
+        }
+    }
+}
+
+/// Queries answered and faults injected by a chaos server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ChaosStats {
+    /// UDP queries received
+    pub udp_queries: u64,
+    /// TCP queries received (e.g. retries after truncation)
+    pub tcp_queries: u64,
+    /// UDP answers sent late
+    pub delayed: u64,
+    /// UDP answers replaced by malformed messages
+    pub malformed: u64,
+    /// UDP answers truncated
+    pub truncated: u64,
+    /// Queries the lab resolver didn't answer
+    pub upstream_failures: u64,
+}
+
+impl fmt::Display for ChaosStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} UDP queries: {} delayed, {} malformed, {} truncated; {} TCP queries",
+            self.udp_queries, self.delayed, self.malformed, self.truncated, self.tcp_queries
+        )?;
+        if self.upstream_failures > 0 {
+            write!(f, "; {} unanswered by the upstream resolver", self.upstream_failures)?;
+        }
+        Ok(())
+    }
+}
+
+/// Live counters shared by the server tasks
+#[derive(Debug, Default)]
+struct Counters {
+    udp_queries: AtomicU64,
+    tcp_queries: AtomicU64,
+    delayed: AtomicU64,
+    malformed: AtomicU64,
+    truncated: AtomicU64,
+    upstream_failures: AtomicU64,
+}
+
+/// A local DNS server that injects faults into its answers
+///
+/// The server runs until dropped.
+#[derive(Debug)]
+pub struct ChaosServer {
+    addr: SocketAddr,
+    counters: Arc<Counters>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ChaosServer {
+    /// Start a server on a loopback port
+    ///
+    /// With an `upstream`, queries are forwarded to that lab resolver;
+    /// otherwise the server answers A and AAAA queries itself with
+    /// [`MOCK_ANSWER_V4`] and [`MOCK_ANSWER_V6`].
+    pub async fn start(upstream: Option<IpAddr>, rates: ChaosRates) -> io::Result<Self> {
+        let udp = Arc::new(UdpSocket::bind("127.0.0.1:0").await?);
+        let addr = udp.local_addr()?;
+        let tcp = TcpListener::bind(addr).await?;
+        let upstream = upstream.map(|ip| SocketAddr::new(ip, 53));
+        let counters = Arc::new(Counters::default());
+
+        let udp_task = {
+            let counters = Arc::clone(&counters);
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; MAX_UDP_BYTES];
+                while let Ok((len, from)) = udp.recv_from(&mut buf).await {
+                    let query = buf[..len].to_vec();
+                    let udp = Arc::clone(&udp);
+                    let counters = Arc::clone(&counters);
+                    tokio::spawn(async move {
+                        counters.udp_queries.fetch_add(1, Ordering::Relaxed);
+                        let Some(answer) = answer(&query, upstream, &counters).await else {
+                            return;
+                        };
+                        let answer = match rates.pick() {
+                            Some(Fault::Delayed) => {
+                                counters.delayed.fetch_add(1, Ordering::Relaxed);
+                                tokio::time::sleep(random_delay()).await;
+                                answer
+                            }
+                            Some(Fault::Malformed) => {
+                                counters.malformed.fetch_add(1, Ordering::Relaxed);
+                                malform(&answer)
+                            }
+                            Some(Fault::Truncated) => {
+                                counters.truncated.fetch_add(1, Ordering::Relaxed);
+                                truncate(&answer).unwrap_or(answer)
+                            }
+                            None => answer,
+                        };
+                        let _ = udp.send_to(&answer, from).await;
+                    });
+                }
+            })
+        };
+
+        let tcp_task = {
+            let counters = Arc::clone(&counters);
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = tcp.accept().await {
+                    let counters = Arc::clone(&counters);
+                    tokio::spawn(serve_tcp(stream, upstream, counters));
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            counters,
+            tasks: vec![udp_task, tcp_task],
+        })
+    }
+
+    /// Address the server listens on (UDP and TCP)
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Queries answered and faults injected so far
+    pub fn stats(&self) -> ChaosStats {
+        let c = &self.counters;
+        ChaosStats {
+            udp_queries: c.udp_queries.load(Ordering::Relaxed),
+            tcp_queries: c.tcp_queries.load(Ordering::Relaxed),
+            delayed: c.delayed.load(Ordering::Relaxed),
+            malformed: c.malformed.load(Ordering::Relaxed),
+            truncated: c.truncated.load(Ordering::Relaxed),
+            upstream_failures: c.upstream_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for ChaosServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Answer length-prefixed queries on one TCP connection, without faults
+async fn serve_tcp(mut stream: TcpStream, upstream: Option<SocketAddr>, counters: Arc<Counters>) {
+    loop {
+        let mut len = [0u8; 2];
+        if stream.read_exact(&mut len).await.is_err() {
+            return;
+        }
+        let mut query = vec![0u8; u16::from_be_bytes(len) as usize];
+        if stream.read_exact(&mut query).await.is_err() {
+            return;
+        }
+        counters.tcp_queries.fetch_add(1, Ordering::Relaxed);
+
+        let Some(answer) = answer(&query, upstream, &counters).await else {
+            return;
+        };
+        let mut framed = Vec::with_capacity(answer.len() + 2);
+        framed.extend_from_slice(&(answer.len() as u16).to_be_bytes());
+        framed.extend_from_slice(&answer);
+        if stream.write_all(&framed).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Get the intact answer to a query, from the lab resolver or the mock
+async fn answer(query: &[u8], upstream: Option<SocketAddr>, counters: &Counters) -> Option<Vec<u8>> {
+    match upstream {
+        Some(upstream) => {
+            let answer = forward(query, upstream).await;
+            if answer.is_none() {
+                counters.upstream_failures.fetch_add(1, Ordering::Relaxed);
+            }
+            answer
+        }
+        None => mock_answer(&Message::from_vec(query).ok()?).to_vec().ok(),
+    }
+}
+
+/// Relay a query to the lab resolver over UDP
+async fn forward(query: &[u8], upstream: SocketAddr) -> Option<Vec<u8>> {
+    let bind = if upstream.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(bind).await.ok()?;
+    socket.connect(upstream).await.ok()?;
+    socket.send(query).await.ok()?;
+
+    let mut buf = vec![0u8; MAX_UDP_BYTES];
+    let len = tokio::time::timeout(UPSTREAM_TIMEOUT, socket.recv(&mut buf)).await.ok()?.ok()?;
+    buf.truncate(len);
+    Some(buf)
+}
+
+/// Answer A and AAAA queries with fixed addresses, anything else with no records
+fn mock_answer(query: &Message) -> Message {
+    let mut response = Message::new();
+    response
+        .set_id(query.id())
+        .set_message_type(MessageType::Response)
+        .set_op_code(query.op_code())
+        .set_recursion_desired(query.recursion_desired())
+        .set_recursion_available(true)
+        .set_response_code(ResponseCode::NoError)
+        .add_queries(query.queries().to_vec());
+
+    for q in query.queries() {
+        let rdata = match q.query_type() {
+            RecordType::A => RData::A(A(MOCK_ANSWER_V4)),
+            RecordType::AAAA => RData::AAAA(AAAA(MOCK_ANSWER_V6)),
+            _ => continue,
+        };
+        response.add_answer(Record::from_rdata(q.name().clone(), MOCK_TTL, rdata));
+    }
+    response
+}
+
+/// Keep the header (so the ID matches) and follow it with an invalid name
+fn malform(answer: &[u8]) -> Vec<u8> {
+    let mut bytes = answer[..answer.len().min(12)].to_vec();
+    // A compression pointer past the end of the message
+    bytes.extend_from_slice(&[0xc0, 0xff, 0xff]);
+    bytes
+}
+
+/// Drop all records from an answer and set the TC bit
+fn truncate(answer: &[u8]) -> Option<Vec<u8>> {
+    let mut message = Message::from_vec(answer).ok()?;
+    message.take_answers();
+    message.take_name_servers();
+    message.take_additionals();
+    message.set_truncated(true);
+    message.to_vec().ok()
+}
+
+/// Random delay between [`MIN_DELAY`] and [`MAX_DELAY`]
+fn random_delay() -> Duration {
+    MIN_DELAY + (MAX_DELAY - MIN_DELAY).mul_f64(random_fraction())
+}
+
+/// Random number in `[0, 1)`
+fn random_fraction() -> f64 {
+    (RandomState::new().build_hasher().finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::message::RawQuery;
+    use super::super::raw::{raw_query, raw_tcp_query, SocketPool};
+    use super::*;
+    use crate::config::SocketOptions;
+
+    #[tokio::test]
+    async fn test_chaos_mock_answers() {
+        let server = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        let pool = SocketPool::new(1, SocketOptions::default());
+        let timeout = Duration::from_secs(2);
+
+        let response = raw_query(&pool, server.addr(), RawQuery::new("example.com."), timeout).await.unwrap();
+        assert_eq!(response.first_ip(), Some(IpAddr::V4(MOCK_ANSWER_V4)));
+
+        let query = RawQuery {
+            record_type: RecordType::AAAA,
+            ..RawQuery::new("example.com.")
+        };
+        let response = raw_tcp_query(server.addr(), query, timeout, &SocketOptions::default()).await.unwrap();
+        assert_eq!(response.first_ip(), Some(IpAddr::V6(MOCK_ANSWER_V6)));
+
+        let stats = server.stats();
+        assert_eq!((stats.udp_queries, stats.tcp_queries), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_chaos_faults() {
+        let pool = SocketPool::new(1, SocketOptions::default());
+        let timeout = Duration::from_millis(500);
+
+        let truncating = ChaosServer::start(None, ChaosRates { truncated: 1.0, ..ChaosRates::NONE }).await.unwrap();
+        let response = raw_query(&pool, truncating.addr(), RawQuery::new("example.com."), timeout).await.unwrap();
+        assert!(response.message.truncated());
+        assert_eq!(response.first_ip(), None);
+        assert_eq!(truncating.stats().truncated, 1);
+
+        // Undecodable answers are skipped until the query times out
+        let malforming = ChaosServer::start(None, ChaosRates { malformed: 1.0, ..ChaosRates::NONE }).await.unwrap();
+        let result = raw_query(&pool, malforming.addr(), RawQuery::new("example.com."), timeout).await;
+        assert!(result.is_err());
+        assert_eq!(malforming.stats().malformed, 1);
+    }
+
+    #[test]
+    fn test_chaos_rates_pick() {
+        assert_eq!(ChaosRates::NONE.pick(), None);
+        let always_delay = ChaosRates { delayed: 1.0, ..ChaosRates::NONE };
+        assert_eq!(always_delay.pick(), Some(Fault::Delayed));
+        assert!((0..100).map(|_| random_fraction()).all(|r| (0.0..1.0).contains(&r)));
+    }
+}
@@ -0,0 +1,260 @@
+//! Comparison of two stored benchmark runs.
+
+use super::{BenchmarkResult, NetworkFingerprint, SerializableResult};
+use crate::error::Error;
+use serde::Deserialize;
+use std::io::Read;
+use std::path::Path;
+
+/// A benchmark run loaded from JSON output
+#[derive(Debug, Clone, Deserialize)]
+pub struct StoredRun {
+    /// Run metadata
+    pub meta: StoredMeta,
+    /// Per-server results
+    pub results: Vec<SerializableResult>,
+}
+
+/// Metadata of a stored run
+#[derive(Debug, Clone, Deserialize)]
+pub struct StoredMeta {
+    /// Domain that was tested
+    pub domain: String,
+    /// Network the run was taken on
+    #[serde(default)]
+    pub fingerprint: Option<NetworkFingerprint>,
+}
+
+impl StoredRun {
+    /// Load a run from JSON output
+    pub fn from_json_reader(reader: impl Read) -> Result<Self, Error> {
+        serde_json::from_reader(reader)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid benchmark result: {e}")))
+    }
+
+    /// Take a run that just finished, as if it had been written as JSON
+    pub fn from_result(result: &BenchmarkResult) -> Self {
+        Self {
+            meta: StoredMeta {
+                domain: result.domain.clone(),
+                fingerprint: result.fingerprint.clone(),
+            },
+            results: result
+                .servers
+                .iter()
+                .map(|s| SerializableResult::from_benchmark(s, result))
+                .collect(),
+        }
+    }
+
+    /// Load a run from a JSON output file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Self::from_json_reader(std::io::BufReader::new(file)).map_err(|e| match e {
+            Error::InvalidArgument(msg) => Error::InvalidArgument(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+}
+
+/// Network check outcome when comparing two runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkMatch {
+    /// Both runs were taken on the same network
+    Same,
+    /// The runs were taken on different networks
+    Different(Vec<&'static str>),
+    /// At least one run has no usable fingerprint
+    Unknown,
+}
+
+/// Average latency of one server in both runs
+#[derive(Debug, Clone)]
+pub struct ServerDelta {
+    /// Server name
+    pub name: String,
+    /// Server IP
+    pub ip: String,
+    /// Average in the baseline run (ms)
+    pub base_ms: Option<f64>,
+    /// Average in the current run (ms)
+    pub current_ms: Option<f64>,
+}
+
+impl ServerDelta {
+    /// Latency difference in milliseconds (current − base)
+    pub fn delta_ms(&self) -> Option<f64> {
+        Some(self.current_ms? - self.base_ms?)
+    }
+
+    /// Relative change in percent
+    pub fn change_percent(&self) -> Option<f64> {
+        let base = self.base_ms?;
+        (base > 0.0).then(|| self.delta_ms().map(|d| d / base * 100.0)).flatten()
+    }
+
+    /// Whether the server got more than `threshold_percent` slower, or stopped answering
+    pub fn regressed(&self, threshold_percent: f64) -> bool {
+        match (self.base_ms, self.current_ms) {
+            (Some(_), None) => true,
+            _ => self.change_percent().is_some_and(|change| change > threshold_percent),
+        }
+    }
+}
+
+/// Servers a regression check covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegressionScope {
+    /// Every server present in both runs
+    #[default]
+    Any,
+    /// Only the fastest server of each run, which may differ between runs
+    Fastest,
+}
+
+/// Comparison of two runs
+#[derive(Debug, Clone)]
+pub struct RunComparison {
+    /// Whether both runs came from the same network
+    pub network: NetworkMatch,
+    /// Servers present in both runs, in current-run order
+    pub servers: Vec<ServerDelta>,
+    /// Fastest server of the current run, against the baseline's fastest
+    pub fastest: Option<ServerDelta>,
+}
+
+impl RunComparison {
+    /// Compare a current run against a baseline, matching servers by IP
+    pub fn new(base: &StoredRun, current: &StoredRun) -> Self {
+        let network = match (&base.meta.fingerprint, &current.meta.fingerprint) {
+            (Some(a), Some(b)) if a.same_network(b) => NetworkMatch::Same,
+            (Some(a), Some(b)) if !a.differences(b).is_empty() => NetworkMatch::Different(a.differences(b)),
+            _ => NetworkMatch::Unknown,
+        };
+
+        let servers = current
+            .results
+            .iter()
+            .filter_map(|cur| {
+                let old = base.results.iter().find(|b| b.ip == cur.ip)?;
+                Some(ServerDelta {
+                    name: cur.name.clone(),
+                    ip: cur.ip.clone(),
+                    base_ms: old.avg_ms,
+                    current_ms: cur.avg_ms,
+                })
+            })
+            .collect();
+
+        // Results are stored in whatever order they were shown (`--sort-by`, `--reverse`);
+        // a current run where nothing answered compares as the fastest server failing
+        let fastest = match (
+            fastest_result(&base.results).and_then(|r| r.avg_ms),
+            fastest_result(&current.results).or(current.results.first()),
+        ) {
+            (Some(base_ms), Some(cur)) => Some(ServerDelta {
+                name: cur.name.clone(),
+                ip: cur.ip.clone(),
+                base_ms: Some(base_ms),
+                current_ms: cur.avg_ms,
+            }),
+            _ => None,
+        };
+
+        Self {
+            network,
+            servers,
+            fastest,
+        }
+    }
+
+    /// Servers that got more than `threshold_percent` slower than the baseline
+    pub fn regressions(&self, threshold_percent: f64, scope: RegressionScope) -> Vec<&ServerDelta> {
+        match scope {
+            RegressionScope::Any => self.servers.iter().filter(|d| d.regressed(threshold_percent)).collect(),
+            RegressionScope::Fastest => self.fastest.iter().filter(|d| d.regressed(threshold_percent)).collect(),
+        }
+    }
+}
+
+/// Result with the lowest average latency, if any server answered
+fn fastest_result(results: &[SerializableResult]) -> Option<&SerializableResult> {
+    results
+        .iter()
+        .filter_map(|r| r.avg_ms.map(|ms| (r, ms)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(r, _)| r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(fingerprint: &str, results: &str) -> StoredRun {
+        let json = format!(
+            r#"{{"meta": {{"domain": "google.com", "requests_per_server": 10, "total_servers": 2,
+                "duration_ms": 1.0{fingerprint}}}, "results": [{results}]}}"#
+        );
+        StoredRun::from_json_reader(json.as_bytes()).unwrap()
+    }
+
+    const RESULTS_A: &str = r#"
+        {"name": "Cloudflare", "ip": "1.1.1.1", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 10.0},
+        {"name": "Google", "ip": "8.8.8.8", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 20.0}"#;
+    const RESULTS_B: &str = r#"
+        {"name": "Google", "ip": "8.8.8.8", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 25.0},
+        {"name": "Quad9", "ip": "9.9.9.9", "total_requests": 10, "successful_requests": 0, "success_rate": 0.0}"#;
+
+    #[test]
+    fn test_compare_runs() {
+        let fp = r#", "fingerprint": {"gateway_mac": "0123456789abcdef"}"#;
+        let cmp = RunComparison::new(&run(fp, RESULTS_A), &run(fp, RESULTS_B));
+
+        assert_eq!(cmp.network, NetworkMatch::Same);
+        assert_eq!(cmp.servers.len(), 1);
+        assert_eq!(cmp.servers[0].name, "Google");
+        assert_eq!(cmp.servers[0].delta_ms(), Some(5.0));
+        assert_eq!(cmp.servers[0].change_percent(), Some(25.0));
+    }
+
+    #[test]
+    fn test_regressions() {
+        const RESULTS_C: &str = r#"
+            {"name": "Cloudflare", "ip": "1.1.1.1", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 11.0},
+            {"name": "Google", "ip": "8.8.8.8", "total_requests": 10, "successful_requests": 0, "success_rate": 0.0}"#;
+        let cmp = RunComparison::new(&run("", RESULTS_A), &run("", RESULTS_C));
+
+        // Google stopped answering; Cloudflare is 10% slower
+        let names = |r: Vec<&ServerDelta>| r.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(cmp.regressions(20.0, RegressionScope::Any)), vec!["Google"]);
+        assert_eq!(names(cmp.regressions(5.0, RegressionScope::Any)), vec!["Cloudflare", "Google"]);
+        assert!(cmp.regressions(20.0, RegressionScope::Fastest).is_empty());
+        assert_eq!(names(cmp.regressions(5.0, RegressionScope::Fastest)), vec!["Cloudflare"]);
+
+        // The fastest server is compared even when it changed between runs
+        let cmp = RunComparison::new(&run("", RESULTS_A), &run("", RESULTS_B));
+        let fastest = cmp.fastest.as_ref().unwrap();
+        assert_eq!(fastest.name, "Google");
+        assert_eq!(fastest.change_percent(), Some(150.0));
+
+        // Runs shown by name or worst first still compare their fastest servers
+        const RESULTS_REVERSED: &str = r#"
+            {"name": "Google", "ip": "8.8.8.8", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 20.0},
+            {"name": "Cloudflare", "ip": "1.1.1.1", "total_requests": 10, "successful_requests": 10, "success_rate": 100.0, "avg_ms": 12.0}"#;
+        let cmp = RunComparison::new(&run("", RESULTS_REVERSED), &run("", RESULTS_REVERSED));
+        let fastest = cmp.fastest.as_ref().unwrap();
+        assert_eq!((fastest.name.as_str(), fastest.base_ms, fastest.current_ms), ("Cloudflare", Some(12.0), Some(12.0)));
+    }
+
+    #[test]
+    fn test_compare_network_mismatch() {
+        let home = r#", "fingerprint": {"gateway_mac": "0123456789abcdef", "public_prefix": "203.0.113.0/24"}"#;
+        let office = r#", "fingerprint": {"gateway_mac": "fedcba9876543210", "public_prefix": "203.0.113.0/24"}"#;
+
+        let cmp = RunComparison::new(&run(home, RESULTS_A), &run(office, RESULTS_B));
+        assert_eq!(cmp.network, NetworkMatch::Different(vec!["gateway"]));
+
+        let cmp = RunComparison::new(&run("", RESULTS_A), &run(office, RESULTS_B));
+        assert_eq!(cmp.network, NetworkMatch::Unknown);
+    }
+}
@@ -0,0 +1,57 @@
+//! DNSSEC on/off A-B latency comparison.
+
+use super::message::RawQuery;
+use super::raw::{raw_query, SocketPool};
+use super::result::DnssecAbResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+use hickory_resolver::proto::op::ResponseCode;
+use std::time::Duration;
+
+/// Measure the latency cost of DNSSEC validation on a server
+///
+/// Sends `config.requests` pairs of queries for the benchmark domain: one
+/// with the DO bit set (the resolver validates and returns signatures) and
+/// one with the CD bit set (validation skipped). The order within each pair
+/// alternates so neither mode consistently benefits from the other warming
+/// the cache.
+pub async fn measure_dnssec_overhead(
+    server: &DnsServer,
+    config: &Config,
+    pool: &SocketPool,
+    on_query: &(dyn Fn() + Sync),
+) -> DnssecAbResult {
+    let timeout = Duration::from_millis(config.timeout_ms());
+    let name = format!("{}.", config.domain.trim_end_matches('.'));
+    let validated_query = RawQuery {
+        dnssec_ok: true,
+        ..RawQuery::new(&name)
+    };
+    let unvalidated_query = RawQuery {
+        checking_disabled: true,
+        ..RawQuery::new(&name)
+    };
+
+    let mut validated = Vec::with_capacity(config.requests as usize);
+    let mut unvalidated = Vec::with_capacity(config.requests as usize);
+
+    for run in 0..config.requests {
+        let order = if run % 2 == 0 {
+            [(validated_query, &mut validated), (unvalidated_query, &mut unvalidated)]
+        } else {
+            [(unvalidated_query, &mut unvalidated), (validated_query, &mut validated)]
+        };
+
+        for (query, times) in order {
+            if let Ok(response) = raw_query(pool, server.addr, query, timeout).await
+                && !matches!(response.message.response_code(), ResponseCode::ServFail | ResponseCode::Refused)
+            {
+                times.push(response.elapsed);
+            }
+
+            on_query();
+        }
+    }
+
+    DnssecAbResult::from_timings(config.requests as u32, &validated, &unvalidated)
+}
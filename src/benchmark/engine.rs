@@ -1,18 +1,51 @@
 //! Async benchmark execution engine.
 
-use super::resolver::create_resolver;
-use super::result::{BenchmarkResult, ServerResult, TimingResult};
+use super::calibration::calibrate;
+use super::dnssec_ab::measure_dnssec_overhead;
+use super::negative_cache::measure_negative_cache;
+use super::split_horizon::check_internal_domains;
+use super::node_id::identify_node;
+use super::page_load::simulate_page_loads;
+use super::progress::{Progress, ProgressCallback, ProgressEvent, TerminalProgress};
+use super::message::{
+    answers_nodata, query_plan, raw_query_for, rcode_failure_kind, rcode_name, RawQuery, RawResponse,
+};
+use super::raw::{raw_query, raw_tcp_query, SocketPool};
+use super::resolver::{create_authoritative_resolver, create_resolver, create_search_resolver};
+use super::shuffle::{random_seed, SeededRng};
+use super::tls::{raw_doh_query, raw_dot_query};
+use super::result::{
+    AnycastNodes, BenchmarkResult, EcsResult, FailureBreakdown, FailureKind, PhaseTimings, ServerResult, TimingResult,
+    TtlResult,
+};
+use super::ttl::authoritative_ttl;
+use super::verify::{reference_answers, verify_answers};
 use crate::config::Config;
-use crate::dns::DnsServer;
-use crate::output::OutputFormat;
+use crate::dns::{DnsServer, DomainList, Protocol};
+use crate::error::{DnsError, Error};
+use crate::i18n::Localizer;
+use crate::platform::{detect_fingerprint, detect_search_domains, VpnRoutes};
+use crate::probe::{Probe, ProbeRegistry, ProbeResult};
 
 use console::style;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use fluent_bundle::FluentValue;
+use futures_core::Stream;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::proto::{ProtoError, ProtoErrorKind};
+use hickory_resolver::lookup::Lookup;
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::ResolveError;
 use parking_lot::Mutex;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
-use tokio::sync::Semaphore;
+use tokio::sync::{mpsc, Semaphore};
 use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
 /// Adaptive timeout configuration
 const REDUCE_TIMEOUT_AFTER_FAILURES: u32 = 8;
@@ -20,196 +53,1036 @@ const REDUCED_TIMEOUT_MS: u64 = 500;
 const MINIMIZE_TIMEOUT_AFTER_FAILURES: u32 = 16;
 const MINIMAL_TIMEOUT_MS: u64 = 100;
 
-/// Progress bar tick interval
-const PROGRESS_TICK_MS: u64 = 80;
+/// Workload queries or domains listed in the configuration summary
+const MAX_SUMMARY_ITEMS: usize = 6;
+
+/// Callback invoked with each server's result as soon as it's measured
+pub type ResultCallback = Arc<dyn Fn(&ServerResult) + Send + Sync>;
+
+/// Item of [`BenchmarkEngine::run_stream`]
+#[derive(Debug, Clone)]
+pub enum BenchmarkEvent {
+    /// A server is done, in the order servers finish; probe results aren't in yet
+    Server(Box<ServerResult>),
+    /// The whole run, ranked and with probe results; always the last item
+    Finished(Box<BenchmarkResult>),
+}
 
 /// Async benchmark engine
 pub struct BenchmarkEngine {
     config: Config,
     servers: Vec<DnsServer>,
+    probes: Vec<Arc<dyn Probe>>,
+    phases: PhaseTimings,
+    on_result: Option<ResultCallback>,
+    on_progress: Option<ProgressCallback>,
+    cancel: CancellationToken,
 }
 
 impl BenchmarkEngine {
     /// Create a new benchmark engine
+    ///
+    /// Probes named in `config.probes` are taken from the built-in registry;
+    /// unknown names are skipped with a warning, and the known ones still run.
+    /// Use [`with_probes`](Self::with_probes) to run custom probes.
     pub fn new(config: Config, servers: Vec<DnsServer>) -> Self {
-        Self { config, servers }
+        let probes = ProbeRegistry::builtin().select_known(&config.probes);
+        Self {
+            config,
+            servers,
+            probes,
+            phases: PhaseTimings::default(),
+            on_result: None,
+            on_progress: None,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Replace the probes run against each server
+    pub fn with_probes(mut self, probes: Vec<Arc<dyn Probe>>) -> Self {
+        self.probes = probes;
+        self
+    }
+
+    /// Carry over timings of phases run before the engine (server collection, detection)
+    ///
+    /// The engine adds its own phases and reports them all in the result.
+    pub fn with_phases(mut self, phases: PhaseTimings) -> Self {
+        self.phases = phases;
+        self
+    }
+
+    /// Call `f` with each server's result the moment that server is done
+    ///
+    /// Servers are reported in the order they finish, from the worker tasks.
+    /// Reported results don't have probe results yet, since probes only run
+    /// once every server has been measured.
+    pub fn with_on_result(mut self, f: impl Fn(&ServerResult) + Send + Sync + 'static) -> Self {
+        self.on_result = Some(Arc::new(f));
+        self
+    }
+
+    /// Call `f` with each step of the run instead of drawing progress bars
+    ///
+    /// Events arrive from the worker tasks as queries and probes finish, so
+    /// `f` should return quickly.
+    pub fn with_progress(mut self, f: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(f));
+        self
+    }
+
+    /// Stop the run early when `token` is cancelled
+    ///
+    /// Queries in flight are abandoned and no further ones are sent. The
+    /// result still lists every server that answered or failed at least one
+    /// query, with the queries sent so far; extra measurements and probes
+    /// that hadn't finished are left out.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Run the benchmark, yielding each server's result as it arrives
+    ///
+    /// Each server is yielded once it is measured, like
+    /// [`with_on_result`](Self::with_on_result) reports it, followed by the
+    /// complete result. The benchmark only makes progress while the stream
+    /// is polled, and stops when the stream is dropped.
+    pub fn run_stream(mut self) -> impl Stream<Item = BenchmarkEvent> + Send + Unpin {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let on_result = self.on_result.take();
+        self.on_result = Some(Arc::new(move |server: &ServerResult| {
+            if let Some(ref on_result) = on_result {
+                on_result(server);
+            }
+            let _ = tx.send(server.clone());
+        }));
+        BenchmarkStream {
+            run: Some(Box::pin(self.run())),
+            servers: rx,
+            result: None,
+        }
     }
 
     /// Run the benchmark
-    pub async fn run(self) -> BenchmarkResult {
+    pub async fn run(mut self) -> BenchmarkResult {
+        // Fill in the system's search list before the summary shows it
+        if self.config.search && self.config.search_domains.is_empty() {
+            let start = Instant::now();
+            match detect_search_domains().await {
+                Ok(domains) if !domains.is_empty() => self.config.search_domains = domains,
+                Ok(_) => tracing::warn!("No system search domains found, resolving {} as is", self.config.domain),
+                Err(e) => tracing::warn!("Failed to detect search domains: {e}"),
+            }
+            self.phases.detection = Some(self.phases.detection.unwrap_or_default() + start.elapsed());
+        }
+        // Likewise the shuffle seed, so the run's order can be repeated
+        if self.config.shuffle_servers && self.config.seed.is_none() {
+            self.config.seed = Some(random_seed());
+        }
+
+        // Measure every server once per protocol; probes still run once per server
+        let protocols = self.config.compared_protocols();
+        let variants = protocols.len().max(1);
+        let probed = if protocols.is_empty() {
+            None
+        } else {
+            let servers = per_protocol(&self.servers, &protocols, self.config.protocol);
+            Some(std::mem::replace(&mut self.servers, servers))
+        };
+
+        // Servers whose entry names another protocol get no client subnet or truncation count
+        if self.config.ecs.is_some() || self.config.edns_payload.is_some() {
+            let protocol = self.config.protocol;
+            let others = self.servers.iter().filter(|s| s.protocol.unwrap_or(protocol) != Protocol::Udp).count();
+            if others > 0 {
+                tracing::warn!(
+                    "EDNS options are only sent over UDP, so {others} server(s) won't report ECS or truncation"
+                );
+            }
+        }
+
         let start_time = Instant::now();
         let server_count = self.servers.len();
+        tracing::info!(
+            protocol = %self.config.protocol,
+            lookup = %self.config.lookup_ip,
+            timeout_ms = self.config.timeout_ms(),
+            adaptive_timeout = !self.config.disable_adaptive_timeout,
+            recursion_desired = !self.config.authoritative,
+            search = ?self.config.search_domains,
+            socket = ?self.config.socket,
+            "Resolver configuration"
+        );
+        tracing::info!(
+            servers = server_count,
+            requests = self.config.requests,
+            workers = self.config.workers,
+            slice = self.config.slice,
+            "Benchmarking {}",
+            self.config.domain
+        );
 
         // Print config summary for human-readable output
-        if self.config.format == OutputFormat::Table {
+        if self.config.show_banners() {
             self.print_config_summary();
         }
 
-        // Create multi-progress for per-server progress bars
-        let multi_progress = Arc::new(MultiProgress::new());
-        let results: Arc<Mutex<Vec<ServerResult>>> = Arc::new(Mutex::new(Vec::with_capacity(server_count)));
+        // Detect the network fingerprint alongside the benchmark
+        let fingerprint = (!self.config.skip_fingerprint).then(|| {
+            let timeout_ms = self.config.timeout_ms();
+            tokio::spawn(async move {
+                let start = Instant::now();
+                let fingerprint = detect_fingerprint(timeout_ms).await;
+                (fingerprint, start.elapsed())
+            })
+        });
 
-        // Semaphore to limit concurrent benchmarks
-        let semaphore = Arc::new(Semaphore::new(self.config.workers as usize));
+        // Measure how precisely this host can time queries before loading it
+        let calibration = if self.config.calibrate { calibrate().await.ok() } else { None };
 
-        // Spawn benchmark tasks
-        let mut tasks = JoinSet::new();
+        let results: Arc<Mutex<Vec<(usize, ServerResult)>>> =
+            Arc::new(Mutex::new(Vec::with_capacity(server_count)));
+
+        // Progress bars, unless the embedding application takes the events
+        let terminal = (self.on_progress.is_none() && self.config.show_progress())
+            .then(|| Arc::new(TerminalProgress::new(&self.config, &self.servers)));
+        let progress = Progress::new(self.on_progress.clone().or_else(|| {
+            terminal.clone().map(|terminal| Arc::new(move |event| terminal.handle(event)) as ProgressCallback)
+        }));
+        // Ranked table of every server so far, redrawn above the progress bar
+        let live = terminal.filter(|terminal| terminal.has_live_table());
+
+        let dnssec_queries = if self.config.dnssec_ab { self.config.requests as u64 * 2 } else { 0 };
+        let nx_queries = if self.config.negative_cache { self.config.requests as u64 + 1 } else { 0 };
+        let verify_queries = u64::from(self.config.verify_against.is_some());
+        let internal_queries = self.config.internal_domains.len() as u64;
+        let per_server = self.config.requests as u64
+            + self.config.page_loads as u64
+            + dnssec_queries
+            + nx_queries
+            + verify_queries
+            + internal_queries;
+        progress.emit(ProgressEvent::Started {
+            servers: server_count,
+            queries: server_count as u64 * per_server,
+        });
+
+        // Round-robin queue of servers with queries left; each worker takes
+        // the next server, runs one slice, and puts it back at the end
+        let mut states: Vec<_> = self
+            .servers
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(index, server)| ServerState::new(index, server, &self.config))
+            .collect();
+        if let Some(seed) = self.config.seed.filter(|_| self.config.shuffle_servers) {
+            SeededRng::new(seed).shuffle(&mut states);
+        }
+        let queue: Arc<Mutex<VecDeque<ServerState>>> = Arc::new(Mutex::new(states.into()));
+
+        // Answers every server's answers are checked against
+        let reference_answers = Arc::new(reference_answers(&self.config).await);
+        if let Some(ip) = self.config.verify_against
+            && reference_answers.is_none()
+        {
+            tracing::warn!("{ip} returned no answer for {}, skipping answer verification", self.config.domain);
+        }
 
-        for server in self.servers {
+        // UDP sockets for hand-built queries, shared by all workers
+        let socket_pool = Arc::new(SocketPool::new(self.config.workers as usize, self.config.socket.clone()));
+
+        // TTL every server's answer TTLs are compared to
+        let authoritative = if self.config.ttl_check {
+            let ttl = match self.config.bootstrap.or_else(|| self.servers.first().map(|s| s.ip())) {
+                Some(ip) => authoritative_ttl(&self.config, SocketAddr::new(ip, 53), &socket_pool).await,
+                None => None,
+            };
+            if ttl.is_none() {
+                tracing::warn!("No authoritative TTL found for {}, skipping TTL comparison", self.config.domain);
+            }
+            ttl
+        } else {
+            None
+        };
+
+        // Interfaces every server's route is checked against, if a VPN is up
+        let vpn_routes = if self.config.vpn_check {
+            match VpnRoutes::detect() {
+                Ok(routes) if !routes.tunnels().is_empty() => Some(Arc::new(routes)),
+                Ok(_) => {
+                    tracing::warn!("No VPN tunnel is up, skipping the VPN leak check");
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping the VPN leak check: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut tasks = JoinSet::new();
+        for _ in 0..(self.config.workers as usize).min(server_count) {
             let config = self.config.clone();
+            let vpn_routes = vpn_routes.clone();
+            let socket_pool = Arc::clone(&socket_pool);
+            let reference_answers = Arc::clone(&reference_answers);
+            let queue = Arc::clone(&queue);
             let results = Arc::clone(&results);
-            let semaphore = Arc::clone(&semaphore);
-            let mp = Arc::clone(&multi_progress);
+            let on_result = self.on_result.clone();
+            let progress = progress.clone();
+            let live = live.clone();
+            let cancel = self.cancel.clone();
 
             tasks.spawn(async move {
-                // Acquire semaphore permit
-                let _permit = semaphore.acquire().await.unwrap();
+                loop {
+                    let Some(mut state) = queue.lock().pop_front() else {
+                        break;
+                    };
 
-                // Create per-server progress bar
-                let pb = if config.format == OutputFormat::Table {
-                    let pb = mp.add(ProgressBar::new(config.requests as u64));
-                    pb.set_style(
-                        ProgressStyle::default_bar()
-                            .template("{spinner:.cyan} {msg:<40} [{bar:25.cyan/blue}] {pos}/{len}")
-                            .unwrap()
-                            .progress_chars("━━╸"),
-                    );
-                    pb.set_message(format!("{} ({})", server.name, server.ip()));
-                    pb.enable_steady_tick(Duration::from_millis(PROGRESS_TICK_MS));
-                    Some(pb)
-                } else {
-                    None
-                };
+                    if !cancel.is_cancelled() {
+                        state.run_slice(&config, &socket_pool, &progress, &cancel).await;
+                        if let Some(ref live) = live {
+                            live.update_live(state.index, state.snapshot(&config), &config);
+                        }
+                    }
 
-                // Run benchmark for this server
-                let server_result = benchmark_server(&server, &config, pb.as_ref()).await;
+                    // Once cancelled, servers keep the queries sent so far
+                    if state.remaining(&config) > 0 && !cancel.is_cancelled() {
+                        queue.lock().push_back(state);
+                        continue;
+                    }
+                    if state.measurements.is_empty() {
+                        continue;
+                    }
 
-                // Store result
-                results.lock().push(server_result);
+                    let index = state.index;
+                    let server = state.server.clone();
+                    let on_query = || progress.emit(ProgressEvent::Query { index });
+                    let mut server_result = state.finish(&config);
+                    if let Some(ref mut ttl) = server_result.ttl {
+                        ttl.authoritative = authoritative;
+                    }
+                    if let Some(ref routes) = vpn_routes {
+                        server_result.vpn_route = routes.route(server.ip());
+                    }
+
+                    // Extra measurements are dropped if the run is cancelled meanwhile
+                    cancel
+                        .run_until_cancelled(async {
+                            // Simulate browser page loads
+                            if config.page_loads > 0 {
+                                server_result.page_load = simulate_page_loads(&server, &config, &on_query).await;
+                            }
+
+                            // Compare latency with and without DNSSEC validation
+                            if config.dnssec_ab {
+                                server_result.dnssec_ab =
+                                    Some(measure_dnssec_overhead(&server, &config, &socket_pool, &on_query).await);
+                            }
+
+                            // Compare NXDOMAIN latency before and after negative caching
+                            if config.negative_cache {
+                                server_result.negative_cache =
+                                    Some(measure_negative_cache(&server, &config, &socket_pool, &on_query).await);
+                            }
+
+                            // Cross-check answers against the reference resolver
+                            if config.verify_against.is_some() {
+                                if let Some(ref reference) = *reference_answers {
+                                    server_result.correctness = verify_answers(&server, &config, reference).await;
+                                }
+                                on_query();
+                            }
 
-                // Finish and remove progress bar
-                if let Some(pb) = pb {
-                    pb.finish_and_clear();
+                            // Check which internal-only names the server answers
+                            if !config.internal_domains.is_empty() {
+                                server_result.split_horizon = check_internal_domains(&server, &config, &on_query).await;
+                            }
+                        })
+                        .await;
+
+                    if let Some(ref on_result) = on_result {
+                        on_result(&server_result);
+                    }
+                    if let Some(ref live) = live {
+                        live.update_live(index, server_result.clone(), &config);
+                    }
+                    results.lock().push((index, server_result));
                 }
             });
         }
 
-        // Wait for all tasks to complete
+        // Wait for all workers to drain the queue
         while tasks.join_next().await.is_some() {}
 
-        // Sort results by average time
-        let mut servers = Arc::try_unwrap(results)
+        progress.emit(ProgressEvent::Measured);
+
+        let mut results = Arc::try_unwrap(results)
             .expect("All tasks completed")
             .into_inner();
-        servers.sort_by_key(|r| r.sort_key());
 
+        let mut phases = self.phases;
+        phases.benchmarking = Some(start_time.elapsed());
+
+        // Run capability probes once latency measurement is done so they
+        // don't compete with it for bandwidth or resolver capacity
+        if !self.probes.is_empty() && !self.cancel.is_cancelled() {
+            let probe_start = Instant::now();
+            let servers = probed.as_deref().unwrap_or(&self.servers);
+            let probe_phase = run_probe_phase(servers, &self.probes, &self.config, &progress);
+            match self.cancel.run_until_cancelled(probe_phase).await {
+                Some(probe_results) => {
+                    for (index, server_result) in &mut results {
+                        server_result.probes = probe_results[*index / variants].clone();
+                    }
+                }
+                None => progress.emit(ProgressEvent::ProbesFinished),
+            }
+            phases.probing = Some(probe_start.elapsed());
+        }
+
+        let servers: Vec<ServerResult> = results.into_iter().map(|(_, r)| r).collect();
         let duration = start_time.elapsed();
 
+        let fingerprint = match fingerprint {
+            Some(handle) if self.cancel.is_cancelled() => {
+                handle.abort();
+                None
+            }
+            Some(handle) => handle.await.ok().and_then(|(fingerprint, elapsed)| {
+                phases.detection = Some(phases.detection.unwrap_or_default() + elapsed);
+                Some(fingerprint).filter(|f| !f.is_empty())
+            }),
+            None => None,
+        };
+
         BenchmarkResult {
-            servers,
-            duration,
-            domain: self.config.domain.clone(),
-            requests_per_server: self.config.requests as u32,
+            fingerprint,
+            calibration,
+            authoritative_ttl: authoritative,
+            phases,
+            seed: self.config.seed.filter(|_| self.config.shuffle_servers),
+            ..BenchmarkResult::ranked(&self.config, servers, duration)
         }
     }
 
     /// Print configuration summary
     fn print_config_summary(&self) {
-        println!(
-            "\n{} DNS benchmark\n",
-            style("Starting").cyan().bold()
-        );
+        let l = Localizer::new(self.config.lang);
+        let line = |label: &str, id: &str, args: Vec<(&str, FluentValue)>| {
+            println!("  {} {}", style(l.tr(label)).dim(), l.tr_args(id, args));
+        };
+        let yellow = |value: &dyn std::fmt::Display| FluentValue::from(style(value.to_string()).yellow().to_string());
+
+        println!("\n{}\n", style(l.tr("start-title")).cyan().bold());
         println!(
             "  {} {}",
-            style("Domain:").dim(),
+            style(l.tr("start-domain")).dim(),
             style(&self.config.domain).green()
         );
-        println!(
-            "  {} {} servers × {} requests = {} total",
-            style("Scope:").dim(),
-            style(self.servers.len()).yellow(),
-            style(self.config.requests).yellow(),
-            style(self.servers.len() * self.config.requests as usize).yellow().bold()
+        line(
+            "start-scope",
+            "start-scope-value",
+            vec![
+                ("servers", yellow(&self.servers.len())),
+                ("requests", yellow(&self.config.requests)),
+                (
+                    "total",
+                    style(self.servers.len() * self.config.requests as usize).yellow().bold().to_string().into(),
+                ),
+            ],
         );
-        println!(
-            "  {} {} workers, {}s timeout, {}",
-            style("Config:").dim(),
-            self.config.workers,
-            self.config.timeout,
-            self.config.protocol
+        if self.config.page_loads > 0 {
+            line(
+                "start-browser",
+                "start-browser-value",
+                vec![
+                    ("loads", yellow(&self.config.page_loads)),
+                    ("domains", super::DOMAINS_PER_PAGE.into()),
+                ],
+            );
+        }
+        if self.config.dnssec_ab {
+            line("start-dnssec", "start-dnssec-value", vec![("pairs", yellow(&self.config.requests))]);
+        }
+        if self.config.negative_cache {
+            line("start-negative-cache", "start-negative-cache-value", vec![("repeats", yellow(&self.config.requests))]);
+        }
+        if let Some(payload) = self.config.edns_payload {
+            line("start-edns", "start-edns-value", vec![("payload", yellow(&payload))]);
+        }
+        if let Some(ip) = self.config.verify_against {
+            line("start-verify", "start-verify-value", vec![("ip", yellow(&ip))]);
+        }
+        if self.config.authoritative {
+            line("start-authoritative", "start-authoritative-value", vec![]);
+        }
+        if self.config.search {
+            let domains = if self.config.search_domains.is_empty() {
+                "-".to_string()
+            } else {
+                self.config.search_domains.join(", ")
+            };
+            line("start-search", "start-search-value", vec![("domains", yellow(&domains))]);
+        }
+        if !self.config.internal_domains.is_empty() {
+            line(
+                "start-split-horizon",
+                "start-split-horizon-value",
+                vec![("names", yellow(&self.config.internal_domains.join(", ")))],
+            );
+        }
+        if self.config.vpn_check {
+            line("start-vpn", "start-vpn-value", vec![]);
+        }
+        if self.config.ttl_check {
+            line("start-ttl", "start-ttl-value", vec![]);
+        }
+        if self.config.node_id {
+            line("start-node-id", "start-node-id-value", vec![("slice", yellow(&self.config.slice))]);
+        }
+        if let Some(subnet) = self.config.ecs {
+            line("start-ecs", "start-ecs-value", vec![("subnet", yellow(&subnet))]);
+        }
+        if let Some(ref workload) = self.config.workload {
+            let mut shares: Vec<_> = workload.shares().map(|(q, share)| format!("{q} {share:.0}%")).collect();
+            // Workload files can list hundreds of queries
+            if shares.len() > MAX_SUMMARY_ITEMS {
+                shares.truncate(MAX_SUMMARY_ITEMS);
+                shares.push("…".to_string());
+            }
+            line("start-workload", "start-workload-value", vec![("mix", yellow(&shares.join(", ")))]);
+        }
+        match self.config.domains {
+            Some(DomainList::Popular(count)) => {
+                line("start-domains", "start-domains-popular", vec![("count", yellow(&count))]);
+            }
+            Some(DomainList::Names(ref names)) => {
+                let mut shown = names[..names.len().min(MAX_SUMMARY_ITEMS)].join(", ");
+                if names.len() > MAX_SUMMARY_ITEMS {
+                    shown.push_str(", …");
+                }
+                line("start-domains", "start-domains-names", vec![("names", yellow(&shown))]);
+            }
+            None => {}
+        }
+        if let Some(seed) = self.config.seed.filter(|_| self.config.shuffle_servers) {
+            line("start-order", "start-order-value", vec![("seed", yellow(&seed))]);
+        }
+        if self.config.compares_protocols() {
+            let protocols: Vec<_> = self.config.compared_protocols().iter().map(ToString::to_string).collect();
+            line("start-protocols", "start-protocols-value", vec![("protocols", yellow(&protocols.join(", ")))]);
+        }
+        if !self.probes.is_empty() {
+            let names: Vec<_> = self.probes.iter().map(|p| p.name()).collect();
+            line(
+                "start-probes",
+                "start-probes-value",
+                vec![
+                    ("probes", yellow(&names.join(", "))),
+                    ("budget", self.config.probe_timeout.into()),
+                ],
+            );
+        }
+        line(
+            "start-config",
+            "start-config-value",
+            vec![
+                ("workers", self.config.workers.into()),
+                ("slice", self.config.slice.into()),
+                ("timeout", self.config.timeout.into()),
+                ("protocol", self.config.protocol.to_string().into()),
+            ],
         );
         println!();
     }
 }
 
-/// Benchmark a single DNS server
-async fn benchmark_server(
-    server: &DnsServer,
-    config: &Config,
-    progress: Option<&ProgressBar>,
-) -> ServerResult {
-    let mut measurements = Vec::with_capacity(config.requests as usize);
-
-    // Adaptive timeout state
-    let base_timeout_ms = config.timeout_ms();
-    let mut current_timeout_ms = base_timeout_ms;
-    let mut consecutive_failures: u32 = 0;
-
-    for _ in 0..config.requests {
-        let resolver = create_resolver(
-            server.addr,
-            config.protocol.into(),
-            current_timeout_ms,
-            config.lookup_ip.into(),
-        );
+/// Benchmark `servers` from synchronous code
+///
+/// Starts a Tokio runtime for the run and blocks until it's done. Call it
+/// outside of any async runtime; async code should use [`BenchmarkEngine::run`].
+pub fn run_blocking(config: Config, servers: Vec<DnsServer>) -> Result<BenchmarkResult, Error> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    Ok(runtime.block_on(BenchmarkEngine::new(config, servers).run()))
+}
+
+/// Measurement state of one server between slices
+struct ServerState {
+    index: usize,
+    server: DnsServer,
+    measurements: Vec<TimingResult>,
+    current_timeout_ms: u64,
+    consecutive_failures: u32,
+    failures: FailureBreakdown,
+    /// Responses by RCODE name
+    rcodes: BTreeMap<String, u32>,
+    ecs: Option<EcsResult>,
+    truncated: Option<u32>,
+    /// Time each TCP retry of a truncated answer took, including failed retries
+    fallbacks: Vec<Duration>,
+    ttl: Option<TtlResult>,
+    nodes: Option<AnycastNodes>,
+    /// Domain and record type (if not the lookup strategy's) of each query,
+    /// from the workload and domain list; empty without either
+    plan: Vec<(String, Option<RecordType>)>,
+    /// Times of the answered queries, by record type
+    type_times: BTreeMap<String, Vec<Duration>>,
+}
+
+impl ServerState {
+    fn new(index: usize, server: DnsServer, config: &Config) -> Self {
+        Self {
+            index,
+            measurements: Vec::with_capacity(config.requests as usize),
+            current_timeout_ms: config.timeout_ms(),
+            consecutive_failures: 0,
+            failures: FailureBreakdown::default(),
+            rcodes: BTreeMap::new(),
+            ecs: None,
+            truncated: None,
+            fallbacks: Vec::new(),
+            ttl: config.ttl_check.then(TtlResult::default),
+            // Identification queries are hand-built (UDP only)
+            nodes: (config.node_id && server.protocol.unwrap_or(config.protocol) == Protocol::Udp)
+                .then(AnycastNodes::default),
+            plan: query_plan(config),
+            type_times: BTreeMap::new(),
+            server,
+        }
+    }
+
+    /// Number of queries still to send
+    fn remaining(&self, config: &Config) -> usize {
+        (config.requests as usize).saturating_sub(self.measurements.len())
+    }
+
+    /// Send up to `config.slice` queries
+    ///
+    /// Stops early, without recording the query in flight, once `cancel` is cancelled
+    async fn run_slice(
+        &mut self,
+        config: &Config,
+        pool: &SocketPool,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) {
+        progress.emit(ProgressEvent::Slice { index: self.index });
+
+        // Identify the anycast node once per slice to catch route changes
+        if let Some(ref mut nodes) = self.nodes {
+            let timeout = Duration::from_millis(self.current_timeout_ms);
+            nodes.record(identify_node(pool, self.server.addr, timeout).await);
+        }
+
+        let count = self.remaining(config).min(config.slice.max(1) as usize);
+        for _ in 0..count {
+            let Some(timing) = cancel.run_until_cancelled(self.query_once(config, pool)).await else {
+                return;
+            };
+            self.measurements.push(timing);
+            progress.emit(ProgressEvent::Query { index: self.index });
+        }
+    }
+
+    /// Send one query, updating the adaptive timeout
+    async fn query_once(&mut self, config: &Config, pool: &SocketPool) -> TimingResult {
+        let protocol = self.server.protocol.unwrap_or(config.protocol);
+        // The resolver can't attach EDNS options or report truncation, so
+        // such queries are hand-built (UDP only)
+        let raw = config.ecs.is_some() || config.edns_payload.is_some();
+        let (domain, record_type) = match self.plan.get(self.measurements.len()) {
+            Some((domain, record_type)) => (domain.clone(), *record_type),
+            None => (config.domain.clone(), None),
+        };
+        let timing = match protocol {
+            Protocol::Dot | Protocol::Doh => self.query_encrypted(config, protocol, &domain, record_type).await,
+            Protocol::Udp if raw => self.query_raw(config, pool, &domain, record_type).await,
+            Protocol::Udp | Protocol::Tcp => self.lookup(config, protocol, &domain, record_type).await,
+        };
+        if let (Some(record_type), TimingResult::Success { duration, .. }) = (record_type, &timing) {
+            self.type_times.entry(record_type.to_string()).or_default().push(*duration);
+        }
+
+        match timing {
+            TimingResult::Success { .. } => {
+                self.consecutive_failures = 0;
+                if !config.disable_adaptive_timeout {
+                    self.current_timeout_ms = config.timeout_ms(); // Reset timeout on success
+                }
+            }
+            // Adaptive timeout logic
+            TimingResult::Failure { .. } if !config.disable_adaptive_timeout && timing.is_timeout() => {
+                self.consecutive_failures += 1;
+
+                if self.consecutive_failures >= MINIMIZE_TIMEOUT_AFTER_FAILURES {
+                    self.current_timeout_ms = MINIMAL_TIMEOUT_MS;
+                } else if self.consecutive_failures >= REDUCE_TIMEOUT_AFTER_FAILURES {
+                    self.current_timeout_ms = self.current_timeout_ms.min(REDUCED_TIMEOUT_MS);
+                }
+            }
+            TimingResult::Failure { .. } => {}
+        }
+
+        match timing {
+            TimingResult::Success { duration, ip } => match ip {
+                Some(ip) => tracing::debug!(server = %self.server, %protocol, "Answered {ip} in {duration:.2?}"),
+                None => tracing::debug!(server = %self.server, %protocol, "Answered in {duration:.2?}"),
+            },
+            TimingResult::Failure { ref error } => tracing::debug!(
+                server = %self.server,
+                %protocol,
+                next_timeout_ms = self.current_timeout_ms,
+                "Query failed: {error}"
+            ),
+        }
+
+        timing
+    }
+
+    /// Resolve `domain` through the resolver, as addresses or as `record_type` records
+    async fn lookup(
+        &mut self,
+        config: &Config,
+        protocol: Protocol,
+        domain: &str,
+        record_type: Option<RecordType>,
+    ) -> TimingResult {
+        let protocol = match hickory_resolver::proto::xfer::Protocol::try_from(protocol) {
+            Ok(protocol) => protocol,
+            Err(e) => return TimingResult::Failure { error: e.to_string() },
+        };
+        let create = if config.authoritative {
+            create_authoritative_resolver
+        } else {
+            create_resolver
+        };
+        let resolver = if config.search {
+            create_search_resolver(
+                self.server.addr,
+                protocol,
+                self.current_timeout_ms,
+                config.lookup_ip.into(),
+                &config.socket,
+                &config.search_domains,
+            )
+        } else {
+            create(
+                self.server.addr,
+                protocol,
+                self.current_timeout_ms,
+                config.lookup_ip.into(),
+                &config.socket,
+            )
+        };
 
         let start = Instant::now();
-        let result = resolver.lookup_ip(config.domain.as_str()).await;
+        let result = match record_type {
+            Some(record_type) => resolver.lookup(domain, record_type).await,
+            None => resolver.lookup_ip(domain).await.map(Lookup::from),
+        };
         let duration = start.elapsed();
 
-        let timing = match result {
+        match result {
             Ok(lookup) => {
-                consecutive_failures = 0;
-                if !config.disable_adaptive_timeout {
-                    current_timeout_ms = base_timeout_ms; // Reset timeout on success
+                self.record_rcode(ResponseCode::NoError);
+                let ip = lookup.iter().find_map(RData::ip_addr);
+                if let Some(ref mut ttl) = self.ttl {
+                    let answer_ttl = lookup
+                        .record_iter()
+                        .filter(|r| r.data().ip_addr().is_some())
+                        .map(|r| r.ttl())
+                        .min();
+                    if let Some(answer_ttl) = answer_ttl {
+                        ttl.record(answer_ttl);
+                    }
                 }
-
-                let ip = lookup.iter().next().expect("At least one IP in response");
                 TimingResult::Success { duration, ip }
             }
             Err(e) => {
-                let error = e.to_string();
-                let timing = TimingResult::Failure { error };
-
-                // Adaptive timeout logic
-                if !config.disable_adaptive_timeout && timing.is_timeout() {
-                    consecutive_failures += 1;
-
-                    if consecutive_failures >= MINIMIZE_TIMEOUT_AFTER_FAILURES {
-                        current_timeout_ms = MINIMAL_TIMEOUT_MS;
-                    } else if consecutive_failures >= REDUCE_TIMEOUT_AFTER_FAILURES {
-                        current_timeout_ms = current_timeout_ms.min(REDUCED_TIMEOUT_MS);
+                // Answers without records still carry the server's RCODE
+                if let Some(ProtoErrorKind::NoRecordsFound { response_code, .. }) = e.proto().map(ProtoError::kind) {
+                    self.record_rcode(*response_code);
+                    if *response_code == ResponseCode::NoError && answers_nodata(record_type) {
+                        return TimingResult::Success { duration, ip: None };
                     }
                 }
+                self.failures.record(resolve_failure_kind(&e));
+                TimingResult::Failure { error: e.to_string() }
+            }
+        }
+    }
+
+    /// Resolve the domain with a hand-built query carrying the configured EDNS
+    /// payload size and ECS option
+    ///
+    /// Truncated answers are counted and retried over TCP, with the time of
+    /// both attempts reported and the retry's own time, failed or not, kept as
+    /// the fallback cost. Whether the server echoed ECS is recorded.
+    ///
+    /// Only these queries show truncation: the resolver used otherwise retries
+    /// over TCP on its own, without saying so.
+    async fn query_raw(
+        &mut self,
+        config: &Config,
+        pool: &SocketPool,
+        domain: &str,
+        record_type: Option<RecordType>,
+    ) -> TimingResult {
+        let name = format!("{}.", domain.trim_end_matches('.'));
+        let query = raw_query_for(config, &name, record_type);
+        let timeout = Duration::from_millis(self.current_timeout_ms);
 
-                timing
+        let mut response = match raw_query(pool, self.server.addr, query, timeout).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.failures.record(raw_failure_kind(&e));
+                return TimingResult::Failure { error: e.to_string() };
             }
         };
+        let mut duration = response.elapsed;
+
+        let truncated = self.truncated.get_or_insert(0);
+        if response.message.truncated() {
+            *truncated += 1;
+            let retry_start = Instant::now();
+            response = match raw_tcp_query(self.server.addr, query, timeout, &config.socket).await {
+                Ok(response) => response,
+                Err(e) => {
+                    // A failed retry costs the time spent on it all the same
+                    self.fallbacks.push(retry_start.elapsed());
+                    self.failures.record(raw_failure_kind(&e));
+                    return TimingResult::Failure { error: format!("TCP retry after truncation: {e}") };
+                }
+            };
+            duration += response.elapsed;
+            self.fallbacks.push(response.elapsed);
+        }
+
+        if config.ecs.is_some() {
+            self.ecs
+                .get_or_insert_default()
+                .record(response.client_subnet().map(|s| s.scope_prefix()));
+        }
+
+        self.answer(&response, duration, record_type)
+    }
+
+    /// Resolve the domain over DNS-over-TLS or DNS-over-HTTPS
+    ///
+    /// Each query opens a new connection, so the time includes the TCP and
+    /// TLS handshakes.
+    async fn query_encrypted(
+        &mut self,
+        config: &Config,
+        protocol: Protocol,
+        domain: &str,
+        record_type: Option<RecordType>,
+    ) -> TimingResult {
+        let name = format!("{}.", domain.trim_end_matches('.'));
+        // The client subnet is only sent over UDP
+        let query = RawQuery {
+            client_subnet: None,
+            ..raw_query_for(config, &name, record_type)
+        };
+        let timeout = Duration::from_millis(self.current_timeout_ms);
+
+        let result = if protocol == Protocol::Doh {
+            raw_doh_query(&self.server, query, timeout, &config.socket).await
+        } else {
+            raw_dot_query(&self.server, query, timeout, &config.socket).await
+        };
+        match result {
+            Ok(response) => self.answer(&response, response.elapsed, record_type),
+            Err(e) => {
+                self.failures.record(raw_failure_kind(&e));
+                TimingResult::Failure { error: e.to_string() }
+            }
+        }
+    }
+
+    /// Record what a hand-built query's response says and time it at `duration`
+    fn answer(&mut self, response: &RawResponse, duration: Duration, record_type: Option<RecordType>) -> TimingResult {
+        if let Some(ref mut ttl) = self.ttl
+            && let Some(answer_ttl) = response.answer_ttl()
+        {
+            ttl.record(answer_ttl);
+        }
+
+        self.record_rcode(response.message.response_code());
+        match response.outcome(record_type) {
+            Ok(ip) => TimingResult::Success { duration, ip },
+            Err((kind, error)) => {
+                self.failures.record(kind);
+                TimingResult::Failure { error }
+            }
+        }
+    }
+
+    /// Count a response by its RCODE
+    fn record_rcode(&mut self, code: ResponseCode) {
+        *self.rcodes.entry(rcode_name(code)).or_insert(0) += 1;
+    }
+
+    /// Result of the queries sent so far, for the live table
+    fn snapshot(&self, config: &Config) -> ServerResult {
+        let mut result = ServerResult::from_measurements(&self.server, self.measurements.clone());
+        result.protocol = self.server.protocol.unwrap_or(config.protocol);
+        result
+    }
+
+    /// Build the final result
+    fn finish(self, config: &Config) -> ServerResult {
+        let mut result = ServerResult::from_measurements(&self.server, self.measurements);
+        result.protocol = self.server.protocol.unwrap_or(config.protocol);
+        result.failures = Some(self.failures);
+        result.rcodes = self.rcodes;
+        result.ecs = self.ecs;
+        result.truncated = self.truncated;
+        if !self.fallbacks.is_empty() {
+            result.tcp_fallback = Some(self.fallbacks.iter().sum::<Duration>() / self.fallbacks.len() as u32);
+        }
+        result.ttl = self.ttl;
+        result.nodes = self.nodes;
+        if config.workload.is_some() {
+            let averages = self.type_times.into_iter().map(|(t, times)| {
+                let avg = times.iter().sum::<Duration>() / times.len() as u32;
+                (t, avg)
+            });
+            result.query_types = Some(averages.collect());
+        }
+        result
+    }
+}
+
+/// Stream returned by [`BenchmarkEngine::run_stream`]
+struct BenchmarkStream {
+    run: Option<Pin<Box<dyn Future<Output = BenchmarkResult> + Send>>>,
+    servers: mpsc::UnboundedReceiver<ServerResult>,
+    result: Option<Box<BenchmarkResult>>,
+}
+
+impl Stream for BenchmarkStream {
+    type Item = BenchmarkEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<BenchmarkEvent>> {
+        let this = &mut *self;
+        if let Some(ref mut run) = this.run
+            && let Poll::Ready(result) = run.as_mut().poll(cx)
+        {
+            this.run = None;
+            this.result = Some(Box::new(result));
+        }
 
-        measurements.push(timing);
+        // Servers reported before the run ended come first; the channel
+        // closes once the run, and with it the callback, is dropped
+        match this.servers.poll_recv(cx) {
+            Poll::Ready(Some(server)) => Poll::Ready(Some(BenchmarkEvent::Server(Box::new(server)))),
+            Poll::Ready(None) if this.run.is_none() => Poll::Ready(this.result.take().map(BenchmarkEvent::Finished)),
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Repeat each server once per protocol, a server's protocols in a row
+///
+/// Servers listed on their protocol's own port (like 853 for DoT) are moved
+/// to port 53, from which encrypted queries find their port again.
+fn per_protocol(servers: &[DnsServer], protocols: &[Protocol], default: Protocol) -> Vec<DnsServer> {
+    servers
+        .iter()
+        .flat_map(|server| {
+            let mut server = server.clone();
+            if server.addr.port() == server.protocol.unwrap_or(default).default_port() {
+                server.addr.set_port(53);
+            }
+            protocols.iter().map(move |&protocol| DnsServer {
+                protocol: Some(protocol),
+                ..server.clone()
+            })
+        })
+        .collect()
+}
 
-        if let Some(pb) = progress {
-            pb.inc(1);
+/// Classify a failed resolver lookup
+fn resolve_failure_kind(error: &ResolveError) -> FailureKind {
+    match error.proto().map(ProtoError::kind) {
+        Some(ProtoErrorKind::Timeout) => FailureKind::Timeout,
+        Some(ProtoErrorKind::NoRecordsFound { response_code, .. }) => rcode_failure_kind(*response_code),
+        Some(ProtoErrorKind::Io(_) | ProtoErrorKind::NoConnections | ProtoErrorKind::Busy) => FailureKind::Network,
+        // Some timeouts only surface in the message
+        _ if error.to_string().to_lowercase().contains("timed out") => FailureKind::Timeout,
+        _ => FailureKind::Other,
+    }
+}
+
+/// Classify a failed hand-built query; errors other than timeouts come from the socket
+fn raw_failure_kind(error: &DnsError) -> FailureKind {
+    match error {
+        DnsError::Timeout | DnsError::NoResponse => FailureKind::Timeout,
+        _ => FailureKind::Network,
+    }
+}
+
+/// Run every probe against every server concurrently
+///
+/// Each probe run is limited to `config.probe_timeout`; runs that exceed it
+/// are reported as inconclusive. Returns the results indexed like `servers`.
+async fn run_probe_phase(
+    servers: &[DnsServer],
+    probes: &[Arc<dyn Probe>],
+    config: &Config,
+    progress: &Progress,
+) -> Vec<BTreeMap<String, ProbeResult>> {
+    let budget = Duration::from_secs(config.probe_timeout);
+    let semaphore = Arc::new(Semaphore::new(config.workers as usize));
+    progress.emit(ProgressEvent::ProbesStarted {
+        runs: (servers.len() * probes.len()) as u64,
+    });
+
+    let mut tasks = JoinSet::new();
+    for (index, server) in servers.iter().enumerate() {
+        for probe in probes {
+            let server = server.clone();
+            let probe = Arc::clone(probe);
+            let config = config.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let progress = progress.clone();
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                let result = tokio::time::timeout(budget, probe.run(&server, &config))
+                    .await
+                    .unwrap_or_else(|_| ProbeResult::inconclusive(format!("timed out after {}s", budget.as_secs())));
+                progress.emit(ProgressEvent::Probe { index, probe: probe.name() });
+                (index, probe.name(), result)
+            });
         }
     }
 
-    ServerResult::from_measurements(server, measurements)
+    let mut results = vec![BTreeMap::new(); servers.len()];
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, name, result)) = joined {
+            results[index].insert(name.to_string(), result);
+        }
+    }
+
+    progress.emit(ProgressEvent::ProbesFinished);
+    results
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::benchmark::{ChaosRates, ChaosServer};
+    use crate::config::ConfigBuilder;
     use crate::dns::ServerSource;
+    use crate::output::OutputFormat;
+    use hickory_resolver::proto::op::{Message, MessageType};
     use std::net::IpAddr;
 
     fn make_test_config() -> Config {
@@ -220,6 +1093,23 @@ mod tests {
             .build()
     }
 
+    /// TCP queries to closed local ports, which fail immediately instead of waiting for the timeout
+    fn closed_ports(workers: u16, requests: u16, count: u16) -> (ConfigBuilder, Vec<DnsServer>) {
+        let config = Config::builder()
+            .workers(workers)
+            .requests(requests)
+            .timeout(1)
+            .protocol(crate::dns::Protocol::Tcp)
+            .skip_fingerprint(true);
+        let servers = (1..=count)
+            .map(|i| {
+                let addr = format!("127.0.0.1:{i}").parse().unwrap();
+                DnsServer::new(format!("Closed {i}"), addr, ServerSource::Custom)
+            })
+            .collect();
+        (config, servers)
+    }
+
     fn make_test_server(ip: &str) -> DnsServer {
         DnsServer::from_ip(
             "Test",
@@ -228,6 +1118,335 @@ mod tests {
         )
     }
 
+    struct SlowProbe;
+
+    impl Probe for SlowProbe {
+        fn name(&self) -> &'static str {
+            "slow"
+        }
+
+        fn description(&self) -> &'static str {
+            "Never finishes within the budget"
+        }
+
+        fn run<'a>(&'a self, _server: &'a DnsServer, _config: &'a Config) -> crate::probe::ProbeFuture<'a> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                ProbeResult::pass("finished")
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_phase_enforces_budget() {
+        let config = Config::builder()
+            .probe_timeout(1)
+            .format(OutputFormat::Json)
+            .build();
+        let servers = vec![make_test_server("192.0.2.1"), make_test_server("192.0.2.2")];
+        let probes: Vec<Arc<dyn Probe>> = vec![Arc::new(SlowProbe)];
+
+        let results = run_probe_phase(&servers, &probes, &config, &Progress::default()).await;
+
+        assert_eq!(results.len(), 2);
+        for result in results {
+            assert_eq!(result["slow"].status, crate::probe::ProbeStatus::Inconclusive);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_time_sliced_run_measures_every_server() {
+        let (config, servers) = closed_ports(2, 7, 3);
+        let config = config.slice(3).format(OutputFormat::Json).build();
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let result = {
+            let reported = Arc::clone(&reported);
+            BenchmarkEngine::new(config, servers)
+                .with_on_result(move |server| reported.lock().push((server.name.clone(), server.total_requests)))
+                .run()
+                .await
+        };
+
+        assert_eq!(result.servers.len(), 3);
+        assert!(result.servers.iter().all(|s| s.total_requests == 7));
+
+        // Every server is reported once, fully measured
+        let mut reported = reported.lock().clone();
+        reported.sort();
+        assert_eq!(reported, (1..=3).map(|i| (format!("Closed {i}"), 7)).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_progress_events() {
+        let (config, servers) = closed_ports(2, 7, 3);
+        let config = config.slice(3).build();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        {
+            let events = Arc::clone(&events);
+            BenchmarkEngine::new(config, servers)
+                .with_progress(move |event| events.lock().push(event))
+                .run()
+                .await;
+        }
+
+        let events = events.lock();
+        assert_eq!(events.first(), Some(&ProgressEvent::Started { servers: 3, queries: 21 }));
+        assert_eq!(events.last(), Some(&ProgressEvent::Measured));
+        let count = |f: fn(&ProgressEvent) -> bool| events.iter().filter(|e| f(e)).count();
+        assert_eq!(count(|e| matches!(e, ProgressEvent::Query { .. })), 21);
+        // 3 + 3 + 1 queries per server
+        assert_eq!(count(|e| matches!(e, ProgressEvent::Slice { .. })), 9);
+        assert_eq!(count(|e| matches!(e, ProgressEvent::Query { index: 2 })), 7);
+    }
+
+    #[tokio::test]
+    async fn test_shuffled_server_order() {
+        // Order in which one worker starts on each of 6 closed ports
+        async fn order(shuffle: bool, seed: Option<u64>) -> (Vec<usize>, Option<u64>) {
+            let (builder, servers) = closed_ports(1, 1, 6);
+            let mut builder = builder.shuffle_servers(shuffle);
+            if let Some(seed) = seed {
+                builder = builder.seed(seed);
+            }
+
+            let slices = Arc::new(Mutex::new(Vec::new()));
+            let result = {
+                let slices = Arc::clone(&slices);
+                BenchmarkEngine::new(builder.build(), servers)
+                    .with_progress(move |event| {
+                        if let ProgressEvent::Slice { index } = event {
+                            slices.lock().push(index);
+                        }
+                    })
+                    .run()
+                    .await
+            };
+            let slices = slices.lock().clone();
+            (slices, result.seed)
+        }
+
+        assert_eq!(order(false, Some(42)).await, ((0..6).collect(), None));
+        assert_eq!(order(true, Some(42)).await, (vec![4, 3, 0, 2, 5, 1], Some(42)));
+
+        // A random seed is reported so the order can be repeated
+        let (random, seed) = order(true, None).await;
+        assert_eq!(order(true, seed).await, (random, seed));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_keeps_partial_results() {
+        let (config, servers) = closed_ports(1, 7, 3);
+        let config = config.slice(3).build();
+
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let result = BenchmarkEngine::new(config, servers)
+            .with_cancellation(token)
+            .with_progress(move |event| {
+                if matches!(event, ProgressEvent::Query { .. }) {
+                    cancel.cancel();
+                }
+            })
+            .run()
+            .await;
+
+        // Only the first server got a query out before the cancellation
+        assert_eq!(result.servers.len(), 1);
+        assert_eq!(result.servers[0].name, "Closed 1");
+        assert_eq!(result.servers[0].total_requests, 1);
+    }
+
+    #[test]
+    fn test_run_blocking() {
+        let (config, servers) = closed_ports(2, 2, 2);
+        let config = config.format(OutputFormat::Json).build();
+
+        let result = run_blocking(config, servers).unwrap();
+        assert_eq!(result.servers.len(), 2);
+        assert!(result.servers.iter().all(|s| s.total_requests == 2 && s.all_failed()));
+        // Closed ports refuse the connection
+        let failures = result.servers[0].failures.unwrap();
+        assert_eq!((failures.network, failures.total()), (2, 2), "{failures:?}");
+        // ...so no response, and no RCODE, was received
+        assert!(result.servers[0].rcodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ecs_over_tcp() {
+        let chaos = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        for (protocol, reported) in [(Protocol::Udp, true), (Protocol::Tcp, false)] {
+            let config = Config::builder()
+                .workers(1)
+                .requests(2)
+                .timeout(2)
+                .protocol(protocol)
+                .ecs("203.0.113.0/24".parse().unwrap())
+                .skip_fingerprint(true)
+                .build();
+            let servers = vec![DnsServer::new("Chaos".to_string(), chaos.addr(), ServerSource::Custom)];
+
+            let result = BenchmarkEngine::new(config, servers).run().await;
+            let server = &result.servers[0];
+            assert_eq!(server.successful_requests, 2, "{protocol}");
+            // Only hand-built UDP queries carry the subnet
+            assert_eq!(server.ecs.is_some(), reported, "{protocol}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_fallback() {
+        let rates = ChaosRates {
+            truncated: 1.0,
+            ..ChaosRates::NONE
+        };
+        let chaos = ChaosServer::start(None, rates).await.unwrap();
+        let config = Config::builder()
+            .workers(1)
+            .requests(2)
+            .timeout(2)
+            .edns_payload(512)
+            .skip_fingerprint(true)
+            .build();
+        let servers = vec![DnsServer::new("Chaos".to_string(), chaos.addr(), ServerSource::Custom)];
+
+        let result = BenchmarkEngine::new(config, servers).run().await;
+        let server = &result.servers[0];
+        assert_eq!(server.successful_requests, 2);
+        assert_eq!((server.truncated, server.truncation_rate()), (Some(2), Some(100.0)));
+        // Both attempts are timed, the retry alone is the fallback cost
+        let fallback = server.tcp_fallback.unwrap();
+        assert!(fallback > Duration::ZERO && fallback <= server.avg_time.unwrap());
+        assert_eq!(chaos.stats().tcp_queries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_tcp_fallback() {
+        // Truncates every answer, with nothing listening for the TCP retry
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+                let mut response = Message::from_vec(&buf[..len]).unwrap();
+                response.set_message_type(MessageType::Response).set_truncated(true);
+                let _ = socket.send_to(&response.to_vec().unwrap(), from).await;
+            }
+        });
+        let config = Config::builder()
+            .workers(1)
+            .requests(2)
+            .timeout(1)
+            .edns_payload(512)
+            .skip_fingerprint(true)
+            .build();
+        let servers = vec![DnsServer::new("Truncating".to_string(), addr, ServerSource::Custom)];
+
+        let result = BenchmarkEngine::new(config, servers).run().await;
+        let server = &result.servers[0];
+        assert_eq!((server.successful_requests, server.truncated), (0, Some(2)));
+        // The failed retries still count towards the fallback cost
+        assert!(server.tcp_fallback.is_some());
+        assert_eq!(server.failures.unwrap().network, 2);
+    }
+
+    #[tokio::test]
+    async fn test_workload() {
+        let chaos = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        let servers = vec![DnsServer::new("Chaos".to_string(), chaos.addr(), ServerSource::Custom)];
+        for edns_payload in [None, Some(1232)] {
+            // The resolver and hand-built queries (with an EDNS option) alike
+            let mut builder = Config::builder()
+                .workers(1)
+                .requests(6)
+                .timeout(2)
+                .workload("A,AAAA,MX".parse().unwrap())
+                .skip_fingerprint(true);
+            if let Some(payload) = edns_payload {
+                builder = builder.edns_payload(payload);
+            }
+
+            let result = BenchmarkEngine::new(builder.build(), servers.clone()).run().await;
+            let server = &result.servers[0];
+            // The mock server has no MX records, which is a NODATA answer rather than a failure
+            assert_eq!(server.successful_requests, 6);
+            assert_eq!(server.rcodes["NOERROR"], 6);
+            assert!(server.resolved_ip.is_some());
+            let types: Vec<_> = server.query_types.as_ref().unwrap().keys().collect();
+            assert_eq!(types, ["A", "AAAA", "MX"]);
+        }
+    }
+
+    #[test]
+    fn test_unknown_probes_skipped() {
+        let config = Config::builder().probes(vec!["tcp".to_string(), "bogus".to_string()]).build();
+        let engine = BenchmarkEngine::new(config, Vec::new());
+        let names: Vec<_> = engine.probes.iter().map(|probe| probe.name()).collect();
+        assert_eq!(names, ["tcp"]);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_comparison() {
+        use crate::dns::Protocol;
+
+        let chaos = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        let config = Config::builder()
+            .workers(2)
+            .requests(2)
+            .timeout(2)
+            .protocols(vec![Protocol::Udp, Protocol::Tcp, Protocol::Udp])
+            .skip_fingerprint(true)
+            .build();
+        let servers = vec![DnsServer::new("Chaos".to_string(), chaos.addr(), ServerSource::Custom)];
+
+        let result = BenchmarkEngine::new(config, servers).run().await;
+        assert_eq!(result.servers.len(), 2);
+        assert!(result.servers.iter().all(|s| s.successful_requests == 2));
+        assert_eq!(chaos.stats().tcp_queries, 2);
+        let comparisons = result.by_protocol();
+        assert_eq!(comparisons.len(), 1);
+        assert!(comparisons[0].get(Protocol::Tcp).is_some() && comparisons[0].get(Protocol::Dot).is_none());
+    }
+
+    #[test]
+    fn test_per_protocol() {
+        use crate::dns::Protocol;
+
+        let mut dot = DnsServer::new("Quad9".to_string(), "9.9.9.9:853".parse().unwrap(), ServerSource::Custom);
+        dot.protocol = Some(Protocol::Dot);
+        let odd = DnsServer::new("Odd".to_string(), "192.0.2.1:5353".parse().unwrap(), ServerSource::Custom);
+
+        let servers = per_protocol(&[dot, odd], &[Protocol::Udp, Protocol::Doh], Protocol::Udp);
+        let described: Vec<_> = servers
+            .iter()
+            .map(|s| format!("{} {} {}", s.name, s.addr, s.protocol.unwrap()))
+            .collect();
+        assert_eq!(
+            described,
+            ["Quad9 9.9.9.9:53 udp", "Quad9 9.9.9.9:53 doh", "Odd 192.0.2.1:5353 udp", "Odd 192.0.2.1:5353 doh"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_stream() {
+        let (config, servers) = closed_ports(2, 2, 2);
+        let config = config.format(OutputFormat::Json).build();
+
+        let mut stream = BenchmarkEngine::new(config, servers).run_stream();
+        let mut events = Vec::new();
+        while let Some(event) = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            events.push(event);
+        }
+
+        assert_eq!(events.len(), 3);
+        assert!(events[..2].iter().all(|e| matches!(e, BenchmarkEvent::Server(s) if s.total_requests == 2)));
+        let BenchmarkEvent::Finished(ref result) = events[2] else {
+            panic!("the last event is the finished run");
+        };
+        assert_eq!(result.servers.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_benchmark_engine_creation() {
         let config = make_test_config();
@@ -0,0 +1,148 @@
+//! Network environment fingerprint recorded with each run.
+
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+
+/// Identifies the network a benchmark was run on
+///
+/// Local identifiers are hashed so that result files can be shared without
+/// revealing the router or Wi-Fi network name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkFingerprint {
+    /// Hash of the default gateway's MAC address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_mac: Option<String>,
+    /// Hash of the connected Wi-Fi SSID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+    /// Public IP prefix (/24 for IPv4, /48 for IPv6)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_prefix: Option<String>,
+}
+
+impl NetworkFingerprint {
+    /// Check if no component could be detected
+    pub fn is_empty(&self) -> bool {
+        self.gateway_mac.is_none() && self.ssid.is_none() && self.public_prefix.is_none()
+    }
+
+    /// Get the components that differ from another fingerprint
+    ///
+    /// Only components known on both sides are compared.
+    pub fn differences(&self, other: &Self) -> Vec<&'static str> {
+        let fields = [
+            ("gateway", &self.gateway_mac, &other.gateway_mac),
+            ("ssid", &self.ssid, &other.ssid),
+            ("public prefix", &self.public_prefix, &other.public_prefix),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(label, a, b)| match (a, b) {
+                (Some(a), Some(b)) if a != b => Some(label),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Check if another fingerprint has at least one component in common
+    /// with this one and no conflicting components
+    pub fn same_network(&self, other: &Self) -> bool {
+        let shared = [
+            self.gateway_mac.is_some() && other.gateway_mac.is_some(),
+            self.ssid.is_some() && other.ssid.is_some(),
+            self.public_prefix.is_some() && other.public_prefix.is_some(),
+        ];
+        shared.contains(&true) && self.differences(other).is_empty()
+    }
+}
+
+impl fmt::Display for NetworkFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let none = "-";
+        write!(
+            f,
+            "gateway={} ssid={} prefix={}",
+            self.gateway_mac.as_deref().unwrap_or(none),
+            self.ssid.as_deref().unwrap_or(none),
+            self.public_prefix.as_deref().unwrap_or(none)
+        )
+    }
+}
+
+/// Hash a local identifier (MAC address, SSID) for storage, to 16 hex digits
+///
+/// Identifiers are trimmed and lowercased, then hashed with HMAC-SHA256 keyed
+/// by the install's `salt`. There are few enough MAC addresses and common
+/// SSIDs that an unkeyed hash could be reversed by hashing them all.
+#[cfg(feature = "engine")]
+pub fn hash_identifier(value: &str, salt: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, salt);
+    let tag = ring::hmac::sign(&key, value.trim().to_lowercase().as_bytes());
+    tag.as_ref()[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Hash a string to 16 hex digits
+///
+/// Uses 64-bit FNV-1a so the value is stable across builds and platforms.
+pub fn stable_hash(value: &str) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = value
+        .bytes()
+        .fold(OFFSET, |h, b| (h ^ u64::from(b)).wrapping_mul(PRIME));
+    format!("{hash:016x}")
+}
+
+/// Truncate a public IP to its network prefix
+pub fn public_prefix(ip: IpAddr) -> String {
+    let len = if ip.is_ipv4() { 24 } else { 48 };
+    IpNet::new(ip, len)
+        .map(|net| net.trunc().to_string())
+        .unwrap_or_else(|_| ip.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(gw: Option<&str>, prefix: Option<&str>) -> NetworkFingerprint {
+        NetworkFingerprint {
+            gateway_mac: gw.map(str::to_string),
+            ssid: None,
+            public_prefix: prefix.map(str::to_string),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "engine")]
+    fn test_hash_identifier() {
+        let salt = [7; 32];
+        assert_eq!(hash_identifier("AA:BB:CC:DD:EE:FF", &salt), hash_identifier("aa:bb:cc:dd:ee:ff ", &salt));
+        assert_ne!(hash_identifier("home", &salt), hash_identifier("office", &salt));
+        assert_eq!(hash_identifier("", &salt).len(), 16);
+        // Another install's hash of the same network doesn't match
+        assert_ne!(hash_identifier("home", &salt), hash_identifier("home", &[8; 32]));
+    }
+
+    #[test]
+    fn test_public_prefix() {
+        assert_eq!(public_prefix("203.0.113.77".parse().unwrap()), "203.0.113.0/24");
+        assert_eq!(public_prefix("2001:db8:1:2::5".parse().unwrap()), "2001:db8:1::/48");
+    }
+
+    #[test]
+    fn test_same_network() {
+        let home = fingerprint(Some("aa:bb"), Some("203.0.113.0/24"));
+        let home_no_ip = fingerprint(Some("aa:bb"), None);
+        let office = fingerprint(Some("cc:dd"), Some("198.51.100.0/24"));
+
+        assert!(home.same_network(&home_no_ip));
+        assert!(!home.same_network(&office));
+        assert_eq!(home.differences(&office), vec!["gateway", "public prefix"]);
+        assert!(!home.same_network(&NetworkFingerprint::default()));
+    }
+}
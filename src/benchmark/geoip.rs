@@ -0,0 +1,109 @@
+//! GeoIP/ASN annotation from offline MaxMind DB files.
+
+use super::result::{BenchmarkResult, GeoInfo, ServerResult};
+use crate::error::Error;
+use maxminddb::Reader;
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// The fields read from any GeoIP2/GeoLite2 database (Country, City, or ASN)
+#[derive(Debug, Deserialize)]
+struct Record<'a> {
+    #[serde(borrow)]
+    country: Option<CountryRecord<'a>>,
+    autonomous_system_number: Option<u32>,
+    autonomous_system_organization: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountryRecord<'a> {
+    iso_code: Option<&'a str>,
+}
+
+impl From<Record<'_>> for GeoInfo {
+    fn from(r: Record<'_>) -> Self {
+        Self {
+            country: r.country.and_then(|c| c.iso_code).map(str::to_string),
+            asn: r.autonomous_system_number,
+            as_org: r.autonomous_system_organization.map(str::to_string),
+        }
+    }
+}
+
+/// One or more MMDB files queried together
+///
+/// Country and ASN data usually ship as separate databases (e.g.
+/// GeoLite2-Country and GeoLite2-ASN); results from all of them are merged.
+pub struct GeoIpDatabase {
+    readers: Vec<Reader<Vec<u8>>>,
+}
+
+impl GeoIpDatabase {
+    /// Open MMDB files
+    pub fn open<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Error> {
+        let readers = paths
+            .iter()
+            .map(|path| {
+                Reader::open_readfile(path).map_err(|e| Error::GeoIp {
+                    path: path.as_ref().to_path_buf(),
+                    message: e.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { readers })
+    }
+
+    /// Look up an IP address in every database
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoInfo> {
+        let mut info = GeoInfo::default();
+        for reader in &self.readers {
+            if let Ok(record) = reader.lookup::<Record>(ip) {
+                info.merge(record.into());
+            }
+        }
+        (!info.is_empty()).then_some(info)
+    }
+
+    /// Annotate each server and its resolved answer
+    pub fn annotate(&self, result: &mut BenchmarkResult) {
+        for server in &mut result.servers {
+            self.annotate_server(server);
+        }
+    }
+
+    /// Annotate a single server and its resolved address
+    pub fn annotate_server(&self, server: &mut ServerResult) {
+        server.geo = self.lookup(server.ip);
+        server.resolved_geo = server.resolved_ip.and_then(|ip| self.lookup(ip));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_missing_database() {
+        let err = GeoIpDatabase::open(&["/nonexistent/GeoLite2-ASN.mmdb"]).err().unwrap();
+        assert!(err.to_string().contains("GeoLite2-ASN.mmdb"));
+    }
+
+    #[test]
+    fn test_record_conversion() {
+        let record = Record {
+            country: Some(CountryRecord { iso_code: Some("US") }),
+            autonomous_system_number: None,
+            autonomous_system_organization: None,
+        };
+        let mut info = GeoInfo::from(record);
+        info.merge(GeoInfo {
+            country: Some("DE".into()),
+            asn: Some(15169),
+            as_org: Some("GOOGLE".into()),
+        });
+
+        assert_eq!(info.country.as_deref(), Some("US"));
+        assert_eq!(info.to_string(), "AS15169 US");
+    }
+}
@@ -0,0 +1,344 @@
+//! DNS messages the engine and the browser benchmark build and judge alike.
+//!
+//! Nothing here sends anything: the engine's sockets ([`raw`](super::raw),
+//! [`tls`](super::tls)) and the browser's `fetch()` ([`web`](super::web))
+//! carry the messages, so a query means the same and its answer counts the
+//! same whichever transport measured it.
+
+use super::result::FailureKind;
+use crate::config::Config;
+use crate::dns::{DomainList, LookupStrategy};
+use crate::error::DnsError;
+use hickory_proto::op::{Edns, Message, MessageType, OpCode, Query, ResponseCode};
+use hickory_proto::rr::rdata::opt::{ClientSubnet, EdnsCode, EdnsOption};
+#[cfg(feature = "engine")]
+use hickory_proto::rr::RData;
+use hickory_proto::rr::{DNSClass, Name, RecordType};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Default EDNS UDP payload size advertised in queries (DNS Flag Day 2020 value)
+pub const DEFAULT_EDNS_PAYLOAD: u16 = 1232;
+
+/// Media type of DNS messages in DoH requests and responses
+pub(super) const DNS_MESSAGE: &str = "application/dns-message";
+
+/// A single query with explicit header and EDNS flags
+#[derive(Debug, Clone, Copy)]
+pub struct RawQuery<'a> {
+    /// Name to query
+    pub name: &'a str,
+    /// Record type to query
+    pub record_type: RecordType,
+    /// Class to query (CH for server identification)
+    pub query_class: DNSClass,
+    /// Set the EDNS DO bit (request DNSSEC records)
+    pub dnssec_ok: bool,
+    /// Set the CD bit (ask the resolver to skip DNSSEC validation)
+    pub checking_disabled: bool,
+    /// Set the RD bit (clear it to query authoritative servers)
+    pub recursion_desired: bool,
+    /// EDNS Client Subnet option to attach
+    pub client_subnet: Option<ClientSubnet>,
+    /// Attach an empty NSID option (ask for the server's identifier)
+    pub nsid: bool,
+    /// Advertised EDNS UDP payload size
+    pub max_payload: u16,
+}
+
+impl<'a> RawQuery<'a> {
+    /// Create an A query with no special flags
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            record_type: RecordType::A,
+            query_class: DNSClass::IN,
+            dnssec_ok: false,
+            checking_disabled: false,
+            recursion_desired: true,
+            client_subnet: None,
+            nsid: false,
+            max_payload: DEFAULT_EDNS_PAYLOAD,
+        }
+    }
+
+    /// Build the wire-format message
+    pub(super) fn to_message(self, id: u16) -> Result<Message, DnsError> {
+        let name = Name::from_ascii(self.name).map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+
+        let mut edns = Edns::new();
+        edns.set_max_payload(self.max_payload);
+        edns.set_dnssec_ok(self.dnssec_ok);
+        if let Some(subnet) = self.client_subnet {
+            edns.options_mut().insert(EdnsOption::Subnet(subnet));
+        }
+        if self.nsid {
+            edns.options_mut().insert(EdnsOption::Unknown(EdnsCode::NSID.into(), Vec::new()));
+        }
+
+        let mut query = Query::query(name, self.record_type);
+        query.set_query_class(self.query_class);
+
+        let mut message = Message::new();
+        message
+            .set_id(id)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(self.recursion_desired)
+            .set_checking_disabled(self.checking_disabled)
+            .add_query(query)
+            .set_edns(edns);
+        Ok(message)
+    }
+}
+
+/// Response to a raw query
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    /// Decoded response
+    pub message: Message,
+    /// Time from sending the query to receiving the response
+    pub elapsed: Duration,
+}
+
+impl RawResponse {
+    /// First A or AAAA address in the answer section
+    pub fn first_ip(&self) -> Option<IpAddr> {
+        self.message.answers().iter().find_map(|r| r.data().ip_addr())
+    }
+
+    /// Check if the answer section holds a record of the queried type
+    pub fn has_answer(&self) -> bool {
+        let Some(query) = self.message.queries().first() else {
+            return false;
+        };
+        self.message.answers().iter().any(|r| r.record_type() == query.query_type())
+    }
+
+    /// Address the response answers with, or the failure it counts as and why
+    pub fn outcome(&self, record_type: Option<RecordType>) -> Result<Option<IpAddr>, (FailureKind, String)> {
+        match (self.message.response_code(), self.has_answer()) {
+            (ResponseCode::NoError, true) => Ok(self.first_ip()),
+            (ResponseCode::NoError, false) if answers_nodata(record_type) => Ok(None),
+            (ResponseCode::NoError, false) => Err((FailureKind::Other, "no records found".to_string())),
+            (code, _) => Err((rcode_failure_kind(code), format!("server responded with {code}"))),
+        }
+    }
+}
+
+/// Details only the engine's measurements read
+#[cfg(feature = "engine")]
+impl RawResponse {
+    /// Lowest TTL of the A or AAAA records in the answer section
+    pub fn answer_ttl(&self) -> Option<u32> {
+        self.message
+            .answers()
+            .iter()
+            .filter(|r| r.data().ip_addr().is_some())
+            .map(|r| r.ttl())
+            .min()
+    }
+
+    /// Text of the first TXT record in the answer section
+    pub fn first_txt(&self) -> Option<String> {
+        self.message.answers().iter().find_map(|r| match r.data() {
+            RData::TXT(txt) => Some(txt.iter().map(|s| String::from_utf8_lossy(s)).collect()),
+            _ => None,
+        })
+    }
+
+    /// NSID option returned by the server, if non-empty
+    pub fn nsid(&self) -> Option<&[u8]> {
+        match self.message.extensions().as_ref()?.option(EdnsCode::NSID)? {
+            EdnsOption::Unknown(_, data) if !data.is_empty() => Some(data),
+            _ => None,
+        }
+    }
+
+    /// EDNS Client Subnet option echoed back by the server
+    pub fn client_subnet(&self) -> Option<ClientSubnet> {
+        match self.message.extensions().as_ref()?.option(EdnsCode::Subnet)? {
+            EdnsOption::Subnet(subnet) => Some(*subnet),
+            _ => None,
+        }
+    }
+}
+
+/// Domain and record type of each query a server is sent
+///
+/// Workload entries without a domain, or every query without a workload,
+/// take turns through the domain list (just the benchmark domain without one).
+/// Empty when neither is configured, so queries use the plain lookup.
+pub(super) fn query_plan(config: &Config) -> Vec<(String, Option<RecordType>)> {
+    let names = config
+        .domains
+        .as_ref()
+        .map_or_else(|| vec![config.domain.as_str()], DomainList::names);
+    let mut names = names.iter().cycle().map(|name| name.to_string());
+    let len = config.requests as usize;
+
+    match config.workload {
+        Some(ref workload) => workload
+            .schedule(len)
+            .into_iter()
+            .map(|q| {
+                let record_type = q.record_type.parse().expect("Workload record types are known");
+                let domain = q.domain.clone().or_else(|| names.next()).expect("Domain lists are not empty");
+                (domain, Some(record_type))
+            })
+            .collect(),
+        None if config.domains.is_some() => names.take(len).map(|domain| (domain, None)).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Hand-built query for `name`, carrying the configured EDNS options
+///
+/// Asks for `record_type` records, or addresses of the family the lookup strategy prefers.
+pub(super) fn raw_query_for<'a>(config: &Config, name: &'a str, record_type: Option<RecordType>) -> RawQuery<'a> {
+    let record_type = record_type.unwrap_or(match config.lookup_ip {
+        LookupStrategy::V6 | LookupStrategy::PreferV6 => RecordType::AAAA,
+        _ => RecordType::A,
+    });
+    RawQuery {
+        record_type,
+        client_subnet: config.ecs.map(|net| ClientSubnet::new(net.network(), net.prefix_len(), 0)),
+        max_payload: config.edns_payload.unwrap_or(DEFAULT_EDNS_PAYLOAD),
+        recursion_desired: !config.authoritative,
+        ..RawQuery::new(name)
+    }
+}
+
+/// Whether a NOERROR answer without records counts as a success
+///
+/// Plenty of names have no MX or AAAA records, so NODATA is a valid answer
+/// for any type but the A queries that stand in for plain lookups.
+pub(super) fn answers_nodata(record_type: Option<RecordType>) -> bool {
+    record_type.is_some_and(|record_type| record_type != RecordType::A)
+}
+
+/// Classify an answer with an error RCODE, or without records
+pub(super) fn rcode_failure_kind(code: ResponseCode) -> FailureKind {
+    match code {
+        ResponseCode::ServFail => FailureKind::ServFail,
+        ResponseCode::Refused => FailureKind::Refused,
+        _ => FailureKind::Other,
+    }
+}
+
+/// Response code as printed by dig
+pub(super) fn rcode_name(code: ResponseCode) -> String {
+    match code {
+        ResponseCode::NoError => "NOERROR".to_string(),
+        ResponseCode::FormErr => "FORMERR".to_string(),
+        ResponseCode::ServFail => "SERVFAIL".to_string(),
+        ResponseCode::NXDomain => "NXDOMAIN".to_string(),
+        ResponseCode::NotImp => "NOTIMP".to_string(),
+        ResponseCode::Refused => "REFUSED".to_string(),
+        other => format!("RCODE{}", u16::from(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_proto::rr::Record;
+
+    #[test]
+    fn test_raw_query_flags() {
+        let query = RawQuery {
+            dnssec_ok: true,
+            checking_disabled: true,
+            ..RawQuery::new("example.com.")
+        };
+        let message = query.to_message(42).unwrap();
+        let decoded = Message::from_vec(&message.to_vec().unwrap()).unwrap();
+
+        assert_eq!(decoded.id(), 42);
+        assert!(decoded.checking_disabled());
+        assert!(decoded.recursion_desired());
+        assert!(decoded.extensions().as_ref().unwrap().flags().dnssec_ok);
+        assert_eq!(decoded.queries()[0].query_type(), RecordType::A);
+    }
+
+    #[test]
+    fn test_raw_query_chaos_nsid() {
+        let query = RawQuery {
+            record_type: RecordType::TXT,
+            query_class: DNSClass::CH,
+            nsid: true,
+            ..RawQuery::new("id.server.")
+        };
+        let message = query.to_message(7).unwrap();
+        let decoded = Message::from_vec(&message.to_vec().unwrap()).unwrap();
+
+        assert_eq!(decoded.queries()[0].query_class(), DNSClass::CH);
+        assert_eq!(decoded.queries()[0].query_type(), RecordType::TXT);
+        let edns = decoded.extensions().as_ref().unwrap();
+        assert_eq!(edns.option(EdnsCode::NSID), Some(&EdnsOption::Unknown(3, Vec::new())));
+    }
+
+    #[test]
+    fn test_query_plan() {
+        assert!(query_plan(&Config::builder().requests(3).build()).is_empty());
+
+        let builder = || Config::builder().requests(5).domains("a.example,b.example".parse().unwrap());
+        let plan = query_plan(&builder().build());
+        let domains: Vec<_> = plan.iter().map(|(domain, _)| domain.as_str()).collect();
+        assert_eq!(domains, ["a.example", "b.example", "a.example", "b.example", "a.example"]);
+        assert!(plan.iter().all(|(_, record_type)| record_type.is_none()));
+
+        // Workload entries with a domain keep it, the others take turns
+        let plan = query_plan(&builder().workload("AAAA,mail.example/MX".parse().unwrap()).build());
+        let queries: Vec<_> = plan.iter().map(|(domain, t)| format!("{domain}/{}", t.unwrap())).collect();
+        let expected = ["a.example/AAAA", "mail.example/MX", "b.example/AAAA", "mail.example/MX", "a.example/AAAA"];
+        assert_eq!(queries, expected);
+
+        let plan = query_plan(&Config::builder().requests(2).domains("popular:1".parse().unwrap()).build());
+        assert_eq!(plan, [("google.com".to_string(), None), ("google.com".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_outcome() {
+        let respond = |record_type, code, answer: Option<&str>| {
+            let mut message = RawQuery {
+                record_type,
+                ..RawQuery::new("example.com.")
+            }
+            .to_message(1)
+            .unwrap();
+            message.set_message_type(MessageType::Response).set_response_code(code);
+            if let Some(ip) = answer {
+                let name = Name::from_ascii("example.com.").unwrap();
+                let ip: std::net::Ipv4Addr = ip.parse().unwrap();
+                message.add_answer(Record::from_rdata(name, 60, hickory_proto::rr::RData::A(ip.into())));
+            }
+            RawResponse {
+                message,
+                elapsed: Duration::ZERO,
+            }
+        };
+
+        let answered = respond(RecordType::A, ResponseCode::NoError, Some("192.0.2.1"));
+        assert_eq!(answered.outcome(None), Ok(Some("192.0.2.1".parse().unwrap())));
+        assert_eq!(respond(RecordType::MX, ResponseCode::NoError, None).outcome(Some(RecordType::MX)), Ok(None));
+        let empty = respond(RecordType::A, ResponseCode::NoError, None).outcome(None);
+        assert_eq!(empty, Err((FailureKind::Other, "no records found".to_string())));
+        let refused = respond(RecordType::A, ResponseCode::Refused, None).outcome(None);
+        assert_eq!(refused.unwrap_err().0, FailureKind::Refused);
+    }
+
+    #[test]
+    fn test_answers_nodata() {
+        assert!(answers_nodata(Some(RecordType::MX)));
+        assert!(answers_nodata(Some(RecordType::AAAA)));
+        assert!(!answers_nodata(Some(RecordType::A)));
+        assert!(!answers_nodata(None));
+    }
+
+    #[test]
+    fn test_rcode_name() {
+        assert_eq!(rcode_name(ResponseCode::NXDomain), "NXDOMAIN");
+        assert_eq!(rcode_name(ResponseCode::BADCOOKIE), "RCODE23");
+    }
+}
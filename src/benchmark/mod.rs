@@ -1,27 +1,142 @@
 //! High-performance async DNS benchmarking engine.
 
+#[cfg(feature = "engine")]
+mod authoritative;
+#[cfg(feature = "engine")]
+mod bootstrap;
+mod calibration;
+#[cfg(feature = "engine")]
+mod chaos;
+mod compare;
+#[cfg(feature = "engine")]
+mod dnssec_ab;
+#[cfg(feature = "engine")]
 mod engine;
+mod fingerprint;
+#[cfg(feature = "engine")]
+mod geoip;
+#[cfg(any(feature = "engine", feature = "web"))]
+mod message;
+#[cfg(feature = "engine")]
+mod negative_cache;
+#[cfg(feature = "engine")]
+mod node_id;
+#[cfg(feature = "engine")]
+mod page_load;
+#[cfg(feature = "engine")]
+mod progress;
+#[cfg(feature = "engine")]
+mod raw;
 mod result;
+#[cfg(feature = "engine")]
+mod resolve;
+#[cfg(feature = "engine")]
 mod resolver;
+#[cfg(feature = "engine")]
+mod shuffle;
+#[cfg(feature = "engine")]
+mod socket;
+#[cfg(feature = "engine")]
+mod split_horizon;
+#[cfg(feature = "engine")]
+mod tls;
+#[cfg(feature = "engine")]
+mod trace;
+#[cfg(feature = "engine")]
+mod ttl;
+#[cfg(feature = "engine")]
+mod verify;
+#[cfg(feature = "web")]
+mod web;
 
-pub use engine::BenchmarkEngine;
-pub use result::{BenchmarkResult, ServerResult, TimingResult, SerializableResult};
+pub use calibration::TimingCalibration;
+#[cfg(feature = "engine")]
+pub use chaos::{ChaosRates, ChaosServer, ChaosStats, MOCK_ANSWER_V4, MOCK_ANSWER_V6};
+pub use compare::{NetworkMatch, RegressionScope, RunComparison, ServerDelta, StoredMeta, StoredRun};
+#[cfg(feature = "engine")]
+pub use engine::{run_blocking, BenchmarkEngine, BenchmarkEvent, ResultCallback};
+#[cfg(feature = "engine")]
+pub use fingerprint::hash_identifier;
+pub use fingerprint::{public_prefix, stable_hash, NetworkFingerprint};
+#[cfg(feature = "engine")]
+pub use geoip::GeoIpDatabase;
+pub use result::{
+    AnswerRecord, AnycastNodes, BenchmarkResult, Correctness, DnssecAbResult, DualStackComparison, EcsResult,
+    EcsSupport, FailureBreakdown, FailureKind, GeoInfo, NegativeCacheResult, NormalizedLatency, PageLoadResult,
+    PhaseTimings, ProtocolComparison, ProviderResult, ResolveResult, SerializableDualStack,
+    SerializableProtocolComparison, SerializableProvider, SerializableResult, ServerResult, SplitHorizon,
+    SplitHorizonResult, TimingResult, TraceHop, TraceOutcome, TraceResult, TtlResult, TtlVerdict, VpnRoute,
+    VpnRouteResult,
+};
+pub(crate) use result::is_bogon;
+#[cfg(feature = "engine")]
+pub use page_load::{DOMAINS_PER_PAGE, PAGE_LOAD_DOMAINS};
+#[cfg(feature = "engine")]
+pub use progress::{ProgressCallback, ProgressEvent};
+#[cfg(feature = "engine")]
+pub use resolve::resolve_once;
+#[cfg(feature = "engine")]
 pub(crate) use resolver::create_resolver;
+#[cfg(feature = "engine")]
+pub use socket::{check_socket_options, BenchResolver};
+#[cfg(feature = "engine")]
+pub use trace::run_trace;
+#[cfg(feature = "web")]
+pub use web::{doh_url, run_doh_benchmark};
 
+#[cfg(feature = "engine")]
 use crate::config::Config;
-use crate::dns::{get_builtin_servers, load_custom_servers, DnsServer};
+#[cfg(feature = "engine")]
+use crate::dns::{
+    fetch_server_list, get_builtin_servers, is_remote_list, load_custom_servers, parse_server_list, DnsServer,
+    IpVersion, ServerFilter, ServerSource,
+};
+#[cfg(feature = "engine")]
 use crate::error::Error;
-use crate::platform::{get_gateway_dns_server, get_system_dns_servers};
+#[cfg(feature = "engine")]
+use crate::platform::{get_dhcp_dns_servers, get_gateway_dns_servers, get_system_dns_servers, get_vpn_dns_servers};
+#[cfg(feature = "engine")]
 use std::collections::HashSet;
+#[cfg(feature = "engine")]
+use std::time::Instant;
 
 /// Collect all DNS servers to benchmark based on configuration
-pub fn collect_servers(config: &Config) -> Result<Vec<DnsServer>, Error> {
+///
+/// Hostname entries in a custom server list are resolved here, which is why
+/// this function is async.
+#[cfg(feature = "engine")]
+pub async fn collect_servers(config: &Config) -> Result<Vec<DnsServer>, Error> {
+    collect_servers_timed(config, &mut PhaseTimings::default()).await
+}
+
+/// Collect DNS servers, recording the time spent loading server lists
+/// (`collection`) and detecting system and gateway resolvers (`detection`)
+#[cfg(feature = "engine")]
+pub async fn collect_servers_timed(config: &Config, phases: &mut PhaseTimings) -> Result<Vec<DnsServer>, Error> {
+    let filter = ServerFilter::new(&config.only, &config.exclude)?;
     let mut servers = Vec::new();
     let mut seen_ips = HashSet::new();
+    let collection_start = Instant::now();
 
-    // 1. Load custom servers or builtin list
+    // 1. Load custom servers, the zone's name servers, or builtin list
     let base_servers = if let Some(ref path) = config.custom_servers {
-        load_custom_servers(path, config.name_server_ip)?
+        let custom = if is_remote_list(path) {
+            let url = path.to_string_lossy().into_owned();
+            let cache_dir = Config::cache_dir().ok();
+            let content = tokio::task::spawn_blocking(move || fetch_server_list(&url, cache_dir.as_deref()))
+                .await
+                .map_err(|e| Error::InvalidArgument(e.to_string()))??;
+            parse_server_list(&content, path, config.name_server_ip, config.servers_format)?
+        } else {
+            load_custom_servers(path, config.name_server_ip, config.servers_format)?
+        };
+        let mut servers = custom.servers;
+        if !custom.hostnames.is_empty() {
+            servers.extend(bootstrap::resolve_hostnames(&custom.hostnames, config).await);
+        }
+        servers
+    } else if config.authoritative {
+        authoritative::zone_servers(config).await?
     } else {
         get_builtin_servers(config.name_server_ip)
     };
@@ -32,55 +147,133 @@ pub fn collect_servers(config: &Config) -> Result<Vec<DnsServer>, Error> {
         }
     }
 
-    // 2. Add system DNS servers if enabled
-    if !config.skip_system {
-        match get_system_dns_servers(config.name_server_ip) {
-            Ok(system_servers) => {
-                for server in system_servers {
-                    if seen_ips.insert(server.ip()) {
-                        servers.push(server);
-                    }
+    phases.collection = Some(collection_start.elapsed());
+    let detection_start = Instant::now();
+
+    // 2-3. Detect system, gateway, DHCP, and VPN DNS concurrently, keeping whichever succeeds
+    // (they're recursive, so not in authoritative runs)
+    let cache_dir = config.detect_cache_dir();
+    let detect_system = async {
+        if config.skip_system || config.authoritative {
+            return Ok(Vec::new());
+        }
+        get_system_dns_servers(config.name_server_ip, cache_dir.as_deref(), &config.adapter_filter()).await
+    };
+    let detect_gateway = async {
+        if config.skip_gateway || config.authoritative {
+            return Ok(Vec::new());
+        }
+        get_gateway_dns_servers(config.name_server_ip, cache_dir.as_deref(), config.all_interfaces).await
+    };
+    let detect_dhcp = async {
+        if !config.dhcp_dns || config.authoritative {
+            return Ok(Vec::new());
+        }
+        get_dhcp_dns_servers(config.name_server_ip).await
+    };
+    let detect_vpn = async {
+        if !config.vpn_check || config.authoritative {
+            return Ok(Vec::new());
+        }
+        get_vpn_dns_servers(config.name_server_ip).await
+    };
+    let (system_servers, gateway_servers, dhcp_servers, vpn_servers) =
+        tokio::join!(detect_system, detect_gateway, detect_dhcp, detect_vpn);
+
+    // VPN servers first, so they keep that name when they're also system DNS servers
+    match vpn_servers {
+        Ok(vpn_servers) => {
+            for server in vpn_servers {
+                if seen_ips.insert(server.ip()) {
+                    servers.push(server);
                 }
             }
-            Err(e) => {
-                eprintln!("Warning: Failed to detect system DNS: {e}");
+        }
+        Err(e) => {
+            tracing::warn!("Failed to detect VPN DNS servers: {e}");
+        }
+    }
+
+    match system_servers {
+        Ok(system_servers) => {
+            for server in system_servers {
+                if seen_ips.insert(server.ip()) {
+                    servers.push(server);
+                }
             }
         }
+        Err(e) => {
+            tracing::warn!("Failed to detect system DNS: {e}");
+        }
     }
 
-    // 3. Add gateway DNS if enabled
-    if !config.skip_gateway {
-        match get_gateway_dns_server(config.name_server_ip) {
-            Ok(Some(server)) => {
+    match gateway_servers {
+        Ok(gateway_servers) => {
+            for server in gateway_servers {
                 if seen_ips.insert(server.ip()) {
                     servers.push(server);
                 }
             }
-            Ok(None) => {}
-            Err(e) => {
-                eprintln!("Warning: Failed to detect gateway: {e}");
+        }
+        Err(e) => {
+            tracing::warn!("Failed to detect gateway: {e}");
+        }
+    }
+
+    match dhcp_servers {
+        Ok(dhcp_servers) => {
+            for server in dhcp_servers {
+                if seen_ips.insert(server.ip()) {
+                    servers.push(server);
+                }
             }
         }
+        Err(e) => {
+            tracing::warn!("Failed to detect DHCP DNS servers: {e}");
+        }
+    }
+
+    if !config.authoritative && (!config.skip_system || !config.skip_gateway || config.dhcp_dns || config.vpn_check) {
+        phases.detection = Some(detection_start.elapsed());
+    }
+
+    // 4. Apply include/exclude filters
+    if !filter.is_empty() {
+        servers.retain(|s| filter.allows(s));
+    }
+
+    // 5. Make sure the reference server is measured alongside the others
+    if let Some(ip) = config.reference
+        && !servers.iter().any(|s| s.ip() == ip)
+    {
+        let server = get_builtin_servers(IpVersion::Both)
+            .into_iter()
+            .find(|s| s.ip() == ip)
+            .unwrap_or_else(|| DnsServer::from_ip("Reference", ip, ServerSource::Custom));
+        servers.push(server);
     }
 
     Ok(servers)
 }
 
 /// Check if a server is responsive (quick test)
+#[cfg(feature = "engine")]
 pub async fn is_server_responsive(
     server: &DnsServer,
     config: &Config,
     timeout_ms: u64,
 ) -> bool {
+    // The resolver can't query DoT and DoH servers
+    let Ok(protocol) = server.protocol.unwrap_or(config.protocol).try_into() else {
+        return false;
+    };
     let resolver = create_resolver(
         server.addr,
-        config.protocol.into(),
+        protocol,
         timeout_ms,
         config.lookup_ip.into(),
+        &config.socket,
     );
 
-    match resolver.lookup_ip("google.com").await {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+    resolver.lookup_ip("google.com").await.is_ok()
 }
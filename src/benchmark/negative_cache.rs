@@ -0,0 +1,52 @@
+//! Negative-cache (NXDOMAIN) latency measurement.
+
+use super::message::RawQuery;
+use super::raw::{raw_query, SocketPool};
+use super::result::NegativeCacheResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+use hickory_resolver::proto::op::ResponseCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Measure how fast a server answers a nonexistent name before and after
+/// caching the NXDOMAIN
+///
+/// A random name under the benchmark domain is queried once, which the
+/// server has to forward to the authoritative server, then `config.requests`
+/// more times, which a negative cache answers directly. Only NXDOMAIN
+/// answers are counted, so servers that rewrite NXDOMAIN report nothing.
+pub async fn measure_negative_cache(
+    server: &DnsServer,
+    config: &Config,
+    pool: &SocketPool,
+    on_query: &(dyn Fn() + Sync),
+) -> NegativeCacheResult {
+    let timeout = Duration::from_millis(config.timeout_ms());
+    let name = format!(
+        "dnsbench-nx-{:016x}.{}.",
+        RandomState::new().build_hasher().finish(),
+        config.domain.trim_end_matches('.')
+    );
+    let query = RawQuery::new(&name);
+
+    let mut first_miss = None;
+    let mut cached = Vec::with_capacity(config.requests as usize);
+
+    for run in 0..=config.requests {
+        let elapsed = match raw_query(pool, server.addr, query, timeout).await {
+            Ok(response) if response.message.response_code() == ResponseCode::NXDomain => Some(response.elapsed),
+            _ => None,
+        };
+        match (run, elapsed) {
+            (0, elapsed) => first_miss = elapsed,
+            (_, Some(elapsed)) => cached.push(elapsed),
+            (_, None) => {}
+        }
+
+        on_query();
+    }
+
+    NegativeCacheResult::from_timings(config.requests as u32, first_miss, &cached)
+}
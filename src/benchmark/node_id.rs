@@ -0,0 +1,59 @@
+//! Anycast node identification via CHAOS TXT and NSID.
+//!
+//! Most anycast resolvers name the site that answered when asked for
+//! `id.server` or `hostname.bind` in the CHAOS class, or when a query
+//! carries the NSID option (RFC 5001). Both are asked for in one query.
+
+use super::message::RawQuery;
+use super::raw::{raw_query, SocketPool};
+use hickory_resolver::proto::rr::{DNSClass, RecordType};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// CHAOS TXT names that identify a server, in the order tried
+const NODE_ID_NAMES: [&str; 2] = ["id.server.", "hostname.bind."];
+
+/// Ask a server which anycast node is answering
+///
+/// The CHAOS TXT answer is preferred over NSID, which some servers fill
+/// with an opaque value. Returns `None` if the server identified itself
+/// neither way.
+pub async fn identify_node(pool: &SocketPool, addr: SocketAddr, timeout: Duration) -> Option<String> {
+    let mut nsid = None;
+    for name in NODE_ID_NAMES {
+        let query = RawQuery {
+            record_type: RecordType::TXT,
+            query_class: DNSClass::CH,
+            nsid: true,
+            ..RawQuery::new(name)
+        };
+        let Ok(response) = raw_query(pool, addr, query, timeout).await else {
+            continue;
+        };
+        if let Some(txt) = response.first_txt().filter(|t| !t.is_empty()) {
+            return Some(txt);
+        }
+        nsid = nsid.or_else(|| response.nsid().map(format_nsid));
+    }
+    nsid
+}
+
+/// Show an NSID as text if it is printable ASCII, as hex otherwise
+fn format_nsid(nsid: &[u8]) -> String {
+    if nsid.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        String::from_utf8_lossy(nsid).into_owned()
+    } else {
+        nsid.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_nsid() {
+        assert_eq!(format_nsid(b"gpdns-ams"), "gpdns-ams");
+        assert_eq!(format_nsid(&[0x01, 0xab, 0xff]), "01abff");
+    }
+}
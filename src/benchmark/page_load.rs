@@ -0,0 +1,120 @@
+//! Browser-like page load simulation.
+
+use super::resolver::create_resolver;
+use super::result::PageLoadResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+use hickory_resolver::proto::xfer::Protocol;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Hostnames a typical page load touches (first-party, CDN, fonts, analytics, ads)
+pub const PAGE_LOAD_DOMAINS: &[&str] = &[
+    "www.google.com",
+    "fonts.googleapis.com",
+    "fonts.gstatic.com",
+    "www.gstatic.com",
+    "ajax.googleapis.com",
+    "www.googletagmanager.com",
+    "www.google-analytics.com",
+    "stats.g.doubleclick.net",
+    "pagead2.googlesyndication.com",
+    "securepubads.g.doubleclick.net",
+    "connect.facebook.net",
+    "static.xx.fbcdn.net",
+    "platform.twitter.com",
+    "abs.twimg.com",
+    "cdn.jsdelivr.net",
+    "cdnjs.cloudflare.com",
+    "unpkg.com",
+    "code.jquery.com",
+    "use.fontawesome.com",
+    "maxcdn.bootstrapcdn.com",
+    "i.ytimg.com",
+    "www.youtube.com",
+    "yt3.ggpht.com",
+    "s.yimg.com",
+    "assets.adobedtm.com",
+    "js.stripe.com",
+    "www.paypal.com",
+    "cdn.shopify.com",
+    "static.cloudflareinsights.com",
+    "snap.licdn.com",
+    "bat.bing.com",
+    "c.amazon-adsystem.com",
+    "images-na.ssl-images-amazon.com",
+    "upload.wikimedia.org",
+    "avatars.githubusercontent.com",
+    "github.githubassets.com",
+    "cdn.segment.com",
+    "js.hs-scripts.com",
+    "widget.intercom.io",
+    "static.hotjar.com",
+];
+
+/// Number of unique domains resolved per simulated page load
+pub const DOMAINS_PER_PAGE: usize = 15;
+
+/// Simulate `config.page_loads` page loads against a server
+///
+/// Each page load resolves a burst of [`DOMAINS_PER_PAGE`] unique domains
+/// concurrently, the way a browser does while parsing a page, and measures
+/// the wall time until the last answer arrives. Successive page loads use
+/// different windows of [`PAGE_LOAD_DOMAINS`]. DoT and DoH servers are
+/// skipped (`None`), as the resolver can't query them.
+pub async fn simulate_page_loads(
+    server: &DnsServer,
+    config: &Config,
+    on_query: &(dyn Fn() + Sync),
+) -> Option<PageLoadResult> {
+    let Ok(protocol) = server.protocol.unwrap_or(config.protocol).try_into() else {
+        (0..config.page_loads).for_each(|_| on_query());
+        return None;
+    };
+    let mut times = Vec::with_capacity(config.page_loads as usize);
+
+    for run in 0..config.page_loads as usize {
+        let domains = (0..DOMAINS_PER_PAGE)
+            .map(|i| PAGE_LOAD_DOMAINS[(run * DOMAINS_PER_PAGE + i) % PAGE_LOAD_DOMAINS.len()]);
+
+        if let Some(elapsed) = page_load(server, protocol, config, domains).await {
+            times.push(elapsed);
+        }
+
+        on_query();
+    }
+
+    Some(PageLoadResult::from_timings(config.page_loads as u32, &times))
+}
+
+/// Resolve a burst of domains concurrently, returning the wall time if all succeed
+async fn page_load<'a>(
+    server: &DnsServer,
+    protocol: Protocol,
+    config: &Config,
+    domains: impl Iterator<Item = &'a str>,
+) -> Option<Duration> {
+    let resolver = create_resolver(
+        server.addr,
+        protocol,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    );
+
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for domain in domains {
+        let resolver = resolver.clone();
+        let name = format!("{domain}.");
+        tasks.spawn(async move { resolver.lookup_ip(name).await.is_ok() });
+    }
+
+    let mut all_ok = true;
+    while let Some(ok) = tasks.join_next().await {
+        all_ok &= ok.unwrap_or(false);
+    }
+    let elapsed = start.elapsed();
+
+    all_ok.then_some(elapsed)
+}
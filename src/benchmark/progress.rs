@@ -0,0 +1,231 @@
+//! Progress reporting of the benchmark engine.
+//!
+//! The engine reports what it is doing as [`ProgressEvent`]s. Applications
+//! embedding the library receive them through
+//! [`BenchmarkEngine::with_progress`](super::BenchmarkEngine::with_progress);
+//! otherwise the command line draws them as progress bars on stderr.
+
+use super::result::ServerResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+use crate::i18n::{Lang, Localizer};
+use crate::output::live_table;
+use console::Term;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Progress bar tick interval
+const PROGRESS_TICK_MS: u64 = 80;
+
+/// Terminal lines the live table leaves for its borders, the header, and the progress bar
+const LIVE_TABLE_RESERVED_LINES: usize = 8;
+
+/// Shortest time between redraws of the live table
+const LIVE_TABLE_REDRAW: Duration = Duration::from_millis(100);
+
+/// A step of a benchmark run
+///
+/// Servers are identified by their index in the list the engine was
+/// created with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Latency measurement starts; `queries` counts every query it will send
+    Started { servers: usize, queries: u64 },
+    /// A worker starts sending a slice of queries to a server
+    Slice { index: usize },
+    /// A query to a server finished, answered or not
+    Query { index: usize },
+    /// Every server is measured
+    Measured,
+    /// Capability probes start; `runs` counts probe runs over all servers
+    ProbesStarted { runs: u64 },
+    /// A probe finished running against a server
+    Probe { index: usize, probe: &'static str },
+    /// Every probe has run
+    ProbesFinished,
+}
+
+/// Callback invoked with each progress event, from the worker tasks
+pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Where the engine sends progress events; nowhere by default
+#[derive(Clone, Default)]
+pub(crate) struct Progress(Option<ProgressCallback>);
+
+impl Progress {
+    pub fn new(callback: Option<ProgressCallback>) -> Self {
+        Self(callback)
+    }
+
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(ref callback) = self.0 {
+            callback(event);
+        }
+    }
+}
+
+/// Progress bars on stderr, and the live table with `--live`
+pub(crate) struct TerminalProgress {
+    multi: MultiProgress,
+    /// Bar message for each server, `name (ip)`
+    labels: Vec<String>,
+    lang: Lang,
+    /// Bar of the phase in progress
+    bar: Mutex<Option<ProgressBar>>,
+    live: Option<LiveTable>,
+}
+
+impl TerminalProgress {
+    pub fn new(config: &Config, servers: &[DnsServer]) -> Self {
+        let multi = MultiProgress::new();
+        // Added first, so it stays above the progress bar
+        let live = config.show_live_table().then(|| LiveTable::new(&multi, servers.len()));
+        Self {
+            multi,
+            labels: servers.iter().map(|s| format!("{} ({})", s.name, s.ip())).collect(),
+            lang: config.lang,
+            bar: Mutex::new(None),
+            live,
+        }
+    }
+
+    /// Draw an event
+    pub fn handle(&self, event: ProgressEvent) {
+        let mut bar = self.bar.lock();
+        match event {
+            // Servers are measured in interleaved slices, so per-server bars
+            // would all be active at once; one bar covers the whole phase
+            ProgressEvent::Started { servers, queries } => {
+                let l = Localizer::new(self.lang);
+                *bar = Some(self.start_bar(queries, l.tr_args("progress-benchmarking", [("servers", servers.into())])));
+            }
+            ProgressEvent::ProbesStarted { runs } => {
+                *bar = Some(self.start_bar(runs, "Running probes".to_string()));
+            }
+            ProgressEvent::Slice { index } => {
+                if let Some(ref bar) = *bar
+                    && let Some(label) = self.labels.get(index)
+                {
+                    bar.set_message(label.clone());
+                }
+            }
+            ProgressEvent::Query { .. } | ProgressEvent::Probe { .. } => {
+                if let Some(ref bar) = *bar {
+                    bar.inc(1);
+                }
+            }
+            ProgressEvent::Measured | ProgressEvent::ProbesFinished => {
+                if let Some(bar) = bar.take() {
+                    bar.finish_and_clear();
+                }
+                if let Some(ref live) = self.live {
+                    live.bar.finish_and_clear();
+                }
+            }
+        }
+    }
+
+    /// Replace a server's row of the live table, if shown
+    pub fn update_live(&self, index: usize, result: ServerResult, config: &Config) {
+        if let Some(ref live) = self.live {
+            live.update(index, result, config);
+        }
+    }
+
+    /// Check if the live table is shown
+    pub fn has_live_table(&self) -> bool {
+        self.live.is_some()
+    }
+
+    fn start_bar(&self, len: u64, message: String) -> ProgressBar {
+        let bar = self.multi.add(ProgressBar::new(len));
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.cyan} {msg:<40} [{bar:25.cyan/blue}] {pos}/{len}")
+                .unwrap()
+                .progress_chars("━━╸"),
+        );
+        bar.set_message(message);
+        bar.enable_steady_tick(Duration::from_millis(PROGRESS_TICK_MS));
+        bar
+    }
+}
+
+/// Results table redrawn while the benchmark runs
+struct LiveTable {
+    bar: ProgressBar,
+    /// Latest result of each server, indexed like the engine's servers
+    rows: Mutex<Vec<Option<ServerResult>>>,
+    /// When the table was last drawn
+    drawn_at: Mutex<Option<Instant>>,
+    /// Most servers that fit on screen
+    max_rows: usize,
+}
+
+impl LiveTable {
+    fn new(multi: &MultiProgress, server_count: usize) -> Self {
+        let bar = multi.add(ProgressBar::new_spinner());
+        bar.set_style(ProgressStyle::with_template("{msg}").unwrap());
+        let height = Term::stderr().size().0 as usize;
+        Self {
+            bar,
+            rows: Mutex::new(vec![None; server_count]),
+            drawn_at: Mutex::new(None),
+            max_rows: height.saturating_sub(LIVE_TABLE_RESERVED_LINES).max(1),
+        }
+    }
+
+    /// Replace a server's row, and redraw unless the table was drawn moments ago
+    ///
+    /// Every worker updates the table after each slice, so with many workers
+    /// redrawing on every update would spend more time ranking and rendering
+    /// than measuring. The rows are kept either way, for the next redraw.
+    fn update(&self, index: usize, result: ServerResult, config: &Config) {
+        let mut rows = self.rows.lock();
+        rows[index] = Some(result);
+
+        let mut drawn_at = self.drawn_at.lock();
+        let now = Instant::now();
+        if drawn_at.is_some_and(|at| now.duration_since(at) < LIVE_TABLE_REDRAW) {
+            return;
+        }
+        *drawn_at = Some(now);
+        let servers: Vec<_> = rows.iter().flatten().collect();
+        self.bar.set_message(live_table(&servers, config, self.max_rows));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::ServerSource;
+
+    #[test]
+    fn test_live_table_redraws_throttled() {
+        let result = |name: &str| {
+            let server = DnsServer::from_ip(name, "192.0.2.1".parse().unwrap(), ServerSource::Custom);
+            let measurements = vec![TimingResult::Success {
+                duration: Duration::from_millis(10),
+                ip: None,
+            }];
+            ServerResult::from_measurements(&server, measurements)
+        };
+        let multi = MultiProgress::with_draw_target(indicatif::ProgressDrawTarget::hidden());
+        let live = LiveTable::new(&multi, 2);
+        let config = Config::builder().plain(true).build();
+
+        live.update(0, result("First"), &config);
+        assert!(live.bar.message().contains("First"));
+        // Kept, but not drawn until the next redraw is due
+        live.update(1, result("Second"), &config);
+        assert!(!live.bar.message().contains("Second"));
+        assert!(live.rows.lock()[1].is_some());
+
+        *live.drawn_at.lock() = Some(Instant::now() - LIVE_TABLE_REDRAW);
+        live.update(0, result("First"), &config);
+        assert!(live.bar.message().contains("Second"));
+    }
+}
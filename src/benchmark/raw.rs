@@ -0,0 +1,290 @@
+//! Hand-built DNS queries for measurements the resolver API can't express
+//! (header flags, EDNS options).
+//!
+//! Queries go out through a [`SocketPool`] of bound UDP sockets that are
+//! reused across queries and workers, which avoids a bind per query and
+//! keeps large runs from exhausting ephemeral ports. Truncated answers can
+//! be retried over TCP with [`raw_tcp_query`].
+
+use super::message::{RawQuery, RawResponse};
+use super::socket::{bind_udp, connect_tcp};
+use crate::config::SocketOptions;
+use crate::error::DnsError;
+use hickory_resolver::proto::op::Message;
+use parking_lot::Mutex;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UdpSocket;
+
+/// Largest UDP response accepted
+const MAX_RESPONSE_BYTES: usize = 4096;
+
+/// Pool of bound UDP sockets shared by concurrent queries
+///
+/// Each socket serves one query at a time. Sockets are bound on first use
+/// and kept for reuse, up to `capacity` per address family.
+pub struct SocketPool {
+    v4: Mutex<Vec<UdpSocket>>,
+    v6: Mutex<Vec<UdpSocket>>,
+    capacity: usize,
+    options: SocketOptions,
+}
+
+impl SocketPool {
+    /// Create an empty pool keeping up to `capacity` idle sockets per address family
+    pub fn new(capacity: usize, options: SocketOptions) -> Self {
+        Self {
+            v4: Mutex::new(Vec::with_capacity(capacity)),
+            v6: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+            options,
+        }
+    }
+
+    /// Take an idle socket able to reach `addr`, binding a new one if none is idle
+    pub async fn acquire(&self, addr: SocketAddr) -> io::Result<PooledSocket<'_>> {
+        let idle = self.idle(addr).lock().pop();
+        let socket = match idle {
+            Some(socket) => socket,
+            None => {
+                let bind: SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                bind_udp(bind, &self.options)?
+            }
+        };
+
+        Ok(PooledSocket {
+            socket: Some(socket),
+            pool: self,
+            ipv4: addr.is_ipv4(),
+        })
+    }
+
+    fn idle(&self, addr: SocketAddr) -> &Mutex<Vec<UdpSocket>> {
+        if addr.is_ipv4() { &self.v4 } else { &self.v6 }
+    }
+}
+
+/// A socket checked out of a [`SocketPool`], returned to it on drop
+pub struct PooledSocket<'a> {
+    socket: Option<UdpSocket>,
+    pool: &'a SocketPool,
+    ipv4: bool,
+}
+
+impl Deref for PooledSocket<'_> {
+    type Target = UdpSocket;
+
+    fn deref(&self) -> &UdpSocket {
+        self.socket.as_ref().expect("socket present until drop")
+    }
+}
+
+impl Drop for PooledSocket<'_> {
+    fn drop(&mut self) {
+        let Some(socket) = self.socket.take() else {
+            return;
+        };
+        let idle = if self.ipv4 { &self.pool.v4 } else { &self.pool.v6 };
+        let mut idle = idle.lock();
+        if idle.len() < self.pool.capacity {
+            idle.push(socket);
+        }
+    }
+}
+
+/// Send a query over UDP and wait for the matching response
+///
+/// Late answers to earlier queries on a reused socket are skipped by
+/// checking the source address and query ID.
+pub async fn raw_query(
+    pool: &SocketPool,
+    addr: SocketAddr,
+    query: RawQuery<'_>,
+    timeout: Duration,
+) -> Result<RawResponse, DnsError> {
+    let io_error = |e: io::Error| DnsError::ResolutionFailed(e.to_string());
+
+    let id = random_id();
+    let bytes = query
+        .to_message(id)?
+        .to_vec()
+        .map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+
+    let socket = pool.acquire(addr).await.map_err(io_error)?;
+
+    let start = Instant::now();
+    socket.send_to(&bytes, addr).await.map_err(io_error)?;
+
+    let mut buf = vec![0u8; MAX_RESPONSE_BYTES];
+    loop {
+        let remaining = timeout.checked_sub(start.elapsed()).ok_or(DnsError::Timeout)?;
+        let (len, from) = tokio::time::timeout(remaining, socket.recv_from(&mut buf))
+            .await
+            .map_err(|_| DnsError::Timeout)?
+            .map_err(io_error)?;
+        let elapsed = start.elapsed();
+
+        // Ignore stray, late, or spoofed datagrams
+        if from != addr {
+            continue;
+        }
+        match Message::from_vec(&buf[..len]) {
+            Ok(message) if message.id() == id => return Ok(RawResponse { message, elapsed }),
+            _ => continue,
+        }
+    }
+}
+
+/// Send a query over TCP on a fresh connection and wait for the response
+///
+/// Used to retry queries whose UDP answer was truncated; the elapsed time
+/// includes the connection handshake, as it would for a real client.
+pub async fn raw_tcp_query(
+    addr: SocketAddr,
+    query: RawQuery<'_>,
+    timeout: Duration,
+    options: &SocketOptions,
+) -> Result<RawResponse, DnsError> {
+    let id = random_id();
+    let bytes = query
+        .to_message(id)?
+        .to_vec()
+        .map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+
+    let start = Instant::now();
+    let exchange = async {
+        let mut stream = connect_tcp(addr, None, timeout, options).await?;
+
+        // Messages over TCP are prefixed with their length
+        let mut framed = Vec::with_capacity(bytes.len() + 2);
+        framed.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        framed.extend_from_slice(&bytes);
+        stream.write_all(&framed).await?;
+
+        let mut len = [0u8; 2];
+        stream.read_exact(&mut len).await?;
+        let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+        stream.read_exact(&mut buf).await?;
+        Ok::<_, io::Error>(buf)
+    };
+    let buf = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| DnsError::Timeout)?
+        .map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+    let elapsed = start.elapsed();
+
+    let message = Message::from_vec(&buf).map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+    if message.id() != id {
+        return Err(DnsError::ResolutionFailed("response ID does not match the query".to_string()));
+    }
+    Ok(RawResponse { message, elapsed })
+}
+
+/// Random query ID
+pub(super) fn random_id() -> u16 {
+    RandomState::new().build_hasher().finish() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::MessageType;
+    use hickory_resolver::proto::rr::rdata::opt::ClientSubnet;
+    use std::net::IpAddr;
+
+    /// Answer `count` queries on a loopback socket with empty NOERROR responses
+    async fn echo_server(count: usize) -> SocketAddr {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            for _ in 0..count {
+                let (len, from) = server.recv_from(&mut buf).await.unwrap();
+                let mut response = Message::from_vec(&buf[..len]).unwrap();
+                response.set_message_type(MessageType::Response);
+                server.send_to(&response.to_vec().unwrap(), from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_raw_query_reuses_pooled_socket() {
+        let addr = echo_server(2).await;
+        let pool = SocketPool::new(4, SocketOptions::default());
+        let timeout = Duration::from_secs(2);
+
+        raw_query(&pool, addr, RawQuery::new("example.com."), timeout).await.unwrap();
+        let first_port = pool.acquire(addr).await.unwrap().local_addr().unwrap();
+        raw_query(&pool, addr, RawQuery::new("example.com."), timeout).await.unwrap();
+        let second_port = pool.acquire(addr).await.unwrap().local_addr().unwrap();
+
+        assert_eq!(first_port, second_port);
+    }
+
+    #[tokio::test]
+    async fn test_raw_query_client_subnet() {
+        // The echo server mirrors the query, so the option comes back as sent
+        let addr = echo_server(1).await;
+        let pool = SocketPool::new(1, SocketOptions::default());
+        let subnet: ClientSubnet = "192.0.2.0/24".parse().unwrap();
+        let query = RawQuery {
+            client_subnet: Some(subnet),
+            ..RawQuery::new("example.com.")
+        };
+
+        let response = raw_query(&pool, addr, query, Duration::from_secs(2)).await.unwrap();
+
+        let echoed = response.client_subnet().unwrap();
+        assert_eq!(echoed.addr(), "192.0.2.0".parse::<IpAddr>().unwrap());
+        assert_eq!(echoed.source_prefix(), 24);
+        assert_eq!(response.first_ip(), None);
+    }
+
+    #[tokio::test]
+    async fn test_raw_query_timeout() {
+        // Nothing listens on the discard port; the query must time out
+        // (or fail) rather than hang
+        let pool = SocketPool::new(1, SocketOptions::default());
+        let addr = "127.0.0.1:9".parse().unwrap();
+        let result = raw_query(&pool, addr, RawQuery::new("example.com."), Duration::from_millis(200)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_raw_tcp_query() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut len = [0u8; 2];
+            stream.read_exact(&mut len).await.unwrap();
+            let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+            stream.read_exact(&mut buf).await.unwrap();
+
+            let mut response = Message::from_vec(&buf).unwrap();
+            response.set_message_type(MessageType::Response);
+            let bytes = response.to_vec().unwrap();
+            stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+            stream.write_all(&bytes).await.unwrap();
+        });
+
+        let query = RawQuery {
+            max_payload: 512,
+            ..RawQuery::new("example.com.")
+        };
+        let response = raw_tcp_query(addr, query, Duration::from_secs(2), &SocketOptions::default()).await.unwrap();
+
+        assert_eq!(response.message.message_type(), MessageType::Response);
+        assert_eq!(response.message.extensions().as_ref().unwrap().max_payload(), 512);
+    }
+}
@@ -0,0 +1,105 @@
+//! One-shot timed lookups (`resolve` subcommand).
+
+use super::message::rcode_name;
+use super::resolver::{create_authoritative_resolver, create_resolver};
+use super::{AnswerRecord, ResolveResult};
+use crate::config::Config;
+use crate::error::Error;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::proto::rr::RecordType;
+use hickory_resolver::proto::ProtoErrorKind;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Send a single query to `server` and return the full answer section
+///
+/// The resolver is set up exactly as for a benchmark run: the configured
+/// protocol, timeout, socket options, and `--authoritative` RD bit, with
+/// caching disabled. `record_type` is a type name such as `MX`.
+pub async fn resolve_once(
+    server: SocketAddr,
+    name: &str,
+    record_type: &str,
+    config: &Config,
+) -> Result<ResolveResult, Error> {
+    let record_type = RecordType::from_str(&record_type.to_uppercase())
+        .map_err(|_| Error::InvalidArgument(format!("Unsupported record type: {record_type}")))?;
+    let create = if config.authoritative {
+        create_authoritative_resolver
+    } else {
+        create_resolver
+    };
+    let resolver = create(
+        server,
+        config.protocol.try_into()?,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    );
+
+    let name = format!("{}.", name.trim_end_matches('.'));
+    let start = Instant::now();
+    let lookup = resolver.lookup(name.as_str(), record_type).await;
+    let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let mut result = ResolveResult {
+        server,
+        protocol: config.protocol,
+        name,
+        record_type: record_type.to_string(),
+        duration_ms,
+        rcode: None,
+        answers: Vec::new(),
+        error: None,
+    };
+    match lookup {
+        Ok(lookup) => {
+            result.rcode = Some(rcode_name(ResponseCode::NoError));
+            result.answers = lookup
+                .records()
+                .iter()
+                .map(|r| AnswerRecord {
+                    name: r.name().to_ascii(),
+                    ttl: r.ttl(),
+                    record_type: r.record_type().to_string(),
+                    data: r.data().to_string(),
+                })
+                .collect();
+        }
+        Err(e) => match e.proto().map(|p| p.kind()) {
+            Some(ProtoErrorKind::NoRecordsFound { response_code, .. }) => {
+                result.rcode = Some(rcode_name(*response_code));
+            }
+            _ => result.error = Some(e.to_string()),
+        },
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::{ChaosRates, ChaosServer, MOCK_ANSWER_V4};
+
+    #[tokio::test]
+    async fn test_resolve_once() {
+        // The mock answers every A query without injecting faults
+        let server = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        let config = Config::builder().timeout(2).build();
+
+        let result = resolve_once(server.addr(), "example.com", "a", &config).await.unwrap();
+        assert_eq!(result.rcode.as_deref(), Some("NOERROR"));
+        assert_eq!(result.name, "example.com.");
+        assert_eq!(result.answers.len(), 1);
+        assert_eq!(result.answers[0].record_type, "A");
+        assert_eq!(result.answers[0].data, MOCK_ANSWER_V4.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_once_invalid_type() {
+        let config = Config::default();
+        let addr: SocketAddr = "127.0.0.1:53".parse().unwrap();
+        assert!(resolve_once(addr, "example.com", "BOGUS", &config).await.is_err());
+    }
+}
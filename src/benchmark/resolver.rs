@@ -7,9 +7,11 @@ use hickory_resolver::config::{
     ResolverConfig,
     ResolverOpts,
 };
-use hickory_resolver::name_server::TokioConnectionProvider;
+use super::socket::{BenchResolver, SocketRuntimeProvider};
+use crate::config::SocketOptions;
+use hickory_resolver::name_server::GenericConnector;
+use hickory_resolver::proto::rr::Name;
 use hickory_resolver::proto::xfer::Protocol;
-use hickory_resolver::TokioResolver;
 use std::net::SocketAddr;
 use std::time::Duration;
 
@@ -19,11 +21,53 @@ pub fn create_resolver(
     protocol: Protocol,
     timeout_ms: u64,
     lookup_strategy: LookupIpStrategy,
-) -> TokioResolver {
+    socket: &SocketOptions,
+) -> BenchResolver {
+    build_resolver(addr, protocol, timeout_ms, lookup_strategy, socket, true, &[])
+}
+
+/// Create a resolver that applies a DNS search list to names with no dots, like the system resolver
+pub fn create_search_resolver(
+    addr: SocketAddr,
+    protocol: Protocol,
+    timeout_ms: u64,
+    lookup_strategy: LookupIpStrategy,
+    socket: &SocketOptions,
+    search: &[String],
+) -> BenchResolver {
+    build_resolver(addr, protocol, timeout_ms, lookup_strategy, socket, true, search)
+}
+
+/// Create a resolver that clears the RD bit, for querying authoritative servers
+pub fn create_authoritative_resolver(
+    addr: SocketAddr,
+    protocol: Protocol,
+    timeout_ms: u64,
+    lookup_strategy: LookupIpStrategy,
+    socket: &SocketOptions,
+) -> BenchResolver {
+    build_resolver(addr, protocol, timeout_ms, lookup_strategy, socket, false, &[])
+}
+
+fn build_resolver(
+    addr: SocketAddr,
+    protocol: Protocol,
+    timeout_ms: u64,
+    lookup_strategy: LookupIpStrategy,
+    socket: &SocketOptions,
+    recursion_desired: bool,
+    search: &[String],
+) -> BenchResolver {
+    tracing::trace!(%addr, ?protocol, timeout_ms, ?lookup_strategy, recursion_desired, ?search, "Creating resolver");
     let mut config = ResolverConfig::new();
     let mut name_server = NameServerConfig::new(addr, protocol);
     name_server.trust_negative_responses = false;
     config.add_name_server(name_server);
+    for domain in search {
+        if let Ok(name) = Name::from_ascii(domain.trim_end_matches('.')) {
+            config.add_search(name);
+        }
+    }
 
     let mut opts = ResolverOpts::default();
     opts.attempts = 1;
@@ -31,8 +75,56 @@ pub fn create_resolver(
     opts.ip_strategy = lookup_strategy;
     opts.cache_size = 0; // Disable caching for accurate benchmarking
     opts.use_hosts_file = ResolveHosts::Never;
+    opts.recursion_desired = recursion_desired;
 
-    TokioResolver::builder_with_config(config, TokioConnectionProvider::default())
+    let provider = SocketRuntimeProvider::new(socket.clone());
+    BenchResolver::builder_with_config(config, GenericConnector::new(provider))
         .with_options(opts)
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::Message;
+    use tokio::net::UdpSocket;
+
+    #[tokio::test]
+    async fn test_authoritative_resolver_clears_rd() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let resolver = create_authoritative_resolver(
+            server.local_addr().unwrap(),
+            Protocol::Udp,
+            200,
+            LookupIpStrategy::Ipv4Only,
+            &SocketOptions::default(),
+        );
+        let lookup = tokio::spawn(async move { resolver.lookup_ip("example.com.").await });
+
+        let mut buf = [0u8; 512];
+        let (len, _) = server.recv_from(&mut buf).await.unwrap();
+        let query = Message::from_vec(&buf[..len]).unwrap();
+        assert!(!query.recursion_desired());
+        lookup.abort();
+    }
+
+    #[tokio::test]
+    async fn test_search_resolver_applies_search_list() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let resolver = create_search_resolver(
+            server.local_addr().unwrap(),
+            Protocol::Udp,
+            200,
+            LookupIpStrategy::Ipv4Only,
+            &SocketOptions::default(),
+            &["corp.example".to_string()],
+        );
+        let lookup = tokio::spawn(async move { resolver.lookup_ip("intranet").await });
+
+        let mut buf = [0u8; 512];
+        let (len, _) = server.recv_from(&mut buf).await.unwrap();
+        let query = Message::from_vec(&buf[..len]).unwrap();
+        assert_eq!(query.queries()[0].name().to_ascii(), "intranet.corp.example.");
+        lookup.abort();
+    }
+}
@@ -1,36 +1,234 @@
 //! Benchmark result types and statistics.
 
-use crate::dns::{DnsServer, ServerSource};
+use super::{NetworkFingerprint, TimingCalibration};
+use crate::config::{Config, RankMetric, Ranking, SortKey};
+use crate::dns::{DnsServer, Protocol, ProviderInfo, ServerSource};
+use crate::error::Error;
+use crate::probe::{warnings_for, ProbeResult};
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
+/// Serde helpers writing durations as fractional milliseconds, like the JSON output
+mod millis {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    fn to_ms(d: &Duration) -> f64 {
+        d.as_secs_f64() * 1000.0
+    }
+
+    fn from_ms<E: Error>(ms: f64) -> Result<Duration, E> {
+        Duration::try_from_secs_f64(ms / 1000.0).map_err(E::custom)
+    }
+
+    pub fn serialize<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(to_ms(d))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        from_ms(f64::deserialize(deserializer)?)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(d: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+            d.as_ref().map(to_ms).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+            Option::<f64>::deserialize(deserializer)?.map(from_ms).transpose()
+        }
+    }
+
+    pub mod vec {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(d: &[Duration], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(d.iter().map(to_ms))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Duration>, D::Error> {
+            Vec::<f64>::deserialize(deserializer)?.into_iter().map(from_ms).collect()
+        }
+    }
+
+    pub mod map {
+        use super::*;
+
+        type Times<K> = Option<BTreeMap<K, Duration>>;
+
+        pub fn serialize<K, S>(times: &Times<K>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            K: Ord + Serialize,
+            S: Serializer,
+        {
+            times
+                .as_ref()
+                .map(|times| times.iter().map(|(key, d)| (key, to_ms(d))).collect::<BTreeMap<_, _>>())
+                .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, K, D>(deserializer: D) -> Result<Times<K>, D::Error>
+        where
+            K: Ord + Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            Option::<BTreeMap<K, f64>>::deserialize(deserializer)?
+                .map(|times| times.into_iter().map(|(key, ms)| Ok((key, from_ms(ms)?))).collect())
+                .transpose()
+        }
+    }
+}
+
+/// Port of plain DNS, which results stored without a port were measured on
+fn plain_dns_port() -> u16 {
+    Protocol::Udp.default_port()
+}
+
 /// Result of benchmarking a single DNS server
-#[derive(Debug, Clone)]
+///
+/// Serializes with durations as fractional milliseconds (`avg_ms`), like the
+/// JSON output, and with unmeasured extras left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerResult {
     /// Server name
     pub name: String,
     /// Server IP address
     pub ip: IpAddr,
+    /// Server port, as the server list gave it (53 unless set)
+    #[serde(default = "plain_dns_port")]
+    pub port: u16,
     /// Server source
     pub source: ServerSource,
+    /// Protocol the server was queried over
+    pub protocol: Protocol,
+    /// Provider metadata, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderInfo>,
     /// Last successfully resolved IP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub resolved_ip: Option<IpAddr>,
     /// Total number of requests made
     pub total_requests: u32,
     /// Number of successful requests
     pub successful_requests: u32,
     /// Minimum response time
+    #[serde(rename = "min_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
     pub min_time: Option<Duration>,
     /// Maximum response time
+    #[serde(rename = "max_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
     pub max_time: Option<Duration>,
     /// Average response time
+    #[serde(rename = "avg_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
     pub avg_time: Option<Duration>,
+    /// Times of the successful queries, in the order they were sent
+    #[serde(rename = "latencies_ms", default, with = "millis::vec")]
+    pub latencies: Vec<Duration>,
     /// Last error message if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+    /// Simulated browser page loads, if enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_load: Option<PageLoadResult>,
+    /// DNSSEC on/off comparison, if enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec_ab: Option<DnssecAbResult>,
+    /// NXDOMAIN latency before and after caching, if enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_cache: Option<NegativeCacheResult>,
+    /// EDNS Client Subnet handling, if queries carried the option
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecs: Option<EcsResult>,
+    /// UDP answers that came back truncated (TC bit), if tracked
+    ///
+    /// Only hand-built queries (`--edns-payload` or `--ecs`) are tracked; the
+    /// resolver retries truncated answers over TCP without reporting it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<u32>,
+    /// Average time the TCP retry of a truncated answer added, failed retries included, if any was retried
+    #[serde(rename = "tcp_fallback_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub tcp_fallback: Option<Duration>,
+    /// Failed queries by cause, if the engine classified them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failures: Option<FailureBreakdown>,
+    /// Responses received, counted by RCODE (`NOERROR`, `NXDOMAIN`, `SERVFAIL`, ...)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rcodes: BTreeMap<String, u32>,
+    /// Average time of the answered queries by record type, if a workload mixed types
+    #[serde(rename = "query_types_ms", default, with = "millis::map", skip_serializing_if = "Option::is_none")]
+    pub query_types: Option<BTreeMap<String, Duration>>,
+    /// Answers compared to a reference resolver, if verification was enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correctness: Option<Correctness>,
+    /// Internal-only names the server answered, if any were given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_horizon: Option<SplitHorizonResult>,
+    /// Interface queries to the server leave through, if a VPN was checked for leaks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vpn_route: Option<VpnRouteResult>,
+    /// Answer TTLs, if TTL checking was enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<TtlResult>,
+    /// Anycast nodes that answered, if node identification was enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<AnycastNodes>,
+    /// Capability probe results keyed by probe name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub probes: BTreeMap<String, ProbeResult>,
+    /// GeoIP annotation of the server address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoInfo>,
+    /// GeoIP annotation of the resolved answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_geo: Option<GeoInfo>,
 }
 
 impl ServerResult {
+    /// Create an empty result for a server that hasn't been queried
+    pub fn new(server: &DnsServer) -> Self {
+        Self {
+            name: server.name.clone(),
+            ip: server.ip(),
+            port: server.addr.port(),
+            source: server.source,
+            protocol: server.protocol.unwrap_or_default(),
+            provider: server.provider.clone(),
+            resolved_ip: None,
+            total_requests: 0,
+            successful_requests: 0,
+            min_time: None,
+            max_time: None,
+            avg_time: None,
+            latencies: Vec::new(),
+            last_error: None,
+            page_load: None,
+            dnssec_ab: None,
+            negative_cache: None,
+            ecs: None,
+            truncated: None,
+            tcp_fallback: None,
+            failures: None,
+            rcodes: BTreeMap::new(),
+            query_types: None,
+            correctness: None,
+            split_horizon: None,
+            vpn_route: None,
+            ttl: None,
+            nodes: None,
+            probes: BTreeMap::new(),
+            geo: None,
+            resolved_geo: None,
+        }
+    }
+
     /// Create a new server result from measurements
     pub fn from_measurements(server: &DnsServer, measurements: Vec<TimingResult>) -> Self {
         let total = measurements.len() as u32;
@@ -40,13 +238,15 @@ impl ServerResult {
         let mut max_time: Option<Duration> = None;
         let mut resolved_ip: Option<IpAddr> = None;
         let mut last_error: Option<String> = None;
+        let mut latencies = Vec::with_capacity(measurements.len());
 
         for m in &measurements {
             match m {
                 TimingResult::Success { duration, ip } => {
                     successful += 1;
                     total_time += *duration;
-                    resolved_ip = Some(*ip);
+                    resolved_ip = ip.or(resolved_ip);
+                    latencies.push(*duration);
 
                     min_time = Some(min_time.map_or(*duration, |min| min.min(*duration)));
                     max_time = Some(max_time.map_or(*duration, |max| max.max(*duration)));
@@ -64,19 +264,29 @@ impl ServerResult {
         };
 
         Self {
-            name: server.name.clone(),
-            ip: server.ip(),
-            source: server.source,
             resolved_ip,
             total_requests: total,
             successful_requests: successful,
             min_time,
             max_time,
             avg_time,
+            latencies,
             last_error,
+            ..Self::new(server)
         }
     }
 
+    /// Result for a built-in server that answered one query in `ms`
+    #[cfg(test)]
+    pub(crate) fn answered(name: &str, ip: &str, ms: u64) -> Self {
+        let server = DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin);
+        let measurements = vec![TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        }];
+        Self::from_measurements(&server, measurements)
+    }
+
     /// Get success rate as a percentage
     #[inline]
     pub fn success_rate(&self) -> f64 {
@@ -87,6 +297,30 @@ impl ServerResult {
         }
     }
 
+    /// Response time below which `p` percent of successful queries fall (nearest rank)
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut sorted = self.latencies.clone();
+        sorted.sort_unstable();
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+
+    /// Standard deviation of the successful queries' response times
+    ///
+    /// Needs at least two answers; a low value means the server answers consistently.
+    pub fn jitter(&self) -> Option<Duration> {
+        if self.latencies.len() < 2 {
+            return None;
+        }
+        let ms: Vec<f64> = self.latencies.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        let mean = ms.iter().sum::<f64>() / ms.len() as f64;
+        let variance = ms.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / ms.len() as f64;
+        Some(Duration::from_secs_f64(variance.sqrt() / 1000.0))
+    }
+
     /// Check if this server is from system DNS
     #[inline]
     pub fn is_system(&self) -> bool {
@@ -99,6 +333,25 @@ impl ServerResult {
         matches!(self.source, ServerSource::Gateway)
     }
 
+    /// Check if this server came from a DHCP lease
+    #[inline]
+    pub fn is_dhcp(&self) -> bool {
+        matches!(self.source, ServerSource::Dhcp)
+    }
+
+    /// Percentage of answers that came back truncated, if tracked
+    ///
+    /// Every truncated answer is retried over TCP, so this is also the TCP
+    /// fallback rate.
+    pub fn truncation_rate(&self) -> Option<f64> {
+        let truncated = self.truncated?;
+        Some(if self.total_requests == 0 {
+            0.0
+        } else {
+            (truncated as f64 / self.total_requests as f64) * 100.0
+        })
+    }
+
     /// Check if all requests failed
     #[inline]
     pub fn all_failed(&self) -> bool {
@@ -109,6 +362,49 @@ impl ServerResult {
     pub fn sort_key(&self) -> Duration {
         self.avg_time.unwrap_or(Duration::MAX)
     }
+
+    /// Ranking score under `ranking`, lower is better
+    ///
+    /// `None` if the server never answered.
+    pub fn score(&self, ranking: &Ranking) -> Option<f64> {
+        let latency = match ranking.metric {
+            RankMetric::Avg => self.avg_time,
+            RankMetric::Min => self.min_time,
+            RankMetric::P50 => self.percentile(50.0),
+            RankMetric::P95 => self.percentile(95.0),
+            RankMetric::P99 => self.percentile(99.0),
+            RankMetric::Max => self.max_time,
+        }?;
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        Some(
+            ranking.latency * ms(latency)
+                + ranking.jitter * self.jitter().map(ms).unwrap_or_default()
+                + ranking.loss * (100.0 - self.success_rate()),
+        )
+    }
+
+    /// Whether the server answers often enough to be ranked among the rest
+    pub fn is_eligible(&self, ranking: &Ranking) -> bool {
+        ranking.min_success.is_none_or(|min| self.success_rate() >= min)
+    }
+
+    /// Whether queries to the server go around an active VPN tunnel
+    pub fn bypasses_vpn(&self) -> bool {
+        self.vpn_route.as_ref().is_some_and(|route| route.verdict == VpnRoute::Bypass)
+    }
+
+    /// Compare servers by score under `ranking`
+    ///
+    /// Servers below the ranking's minimum success rate come after the
+    /// others, and servers that never answered come last.
+    pub fn cmp_score(&self, other: &Self, ranking: &Ranking) -> Ordering {
+        match (self.score(ranking), other.score(ranking)) {
+            (Some(a), Some(b)) => {
+                other.is_eligible(ranking).cmp(&self.is_eligible(ranking)).then(a.total_cmp(&b))
+            }
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
 }
 
 /// Result of a single timing measurement
@@ -117,7 +413,8 @@ pub enum TimingResult {
     /// Successful resolution
     Success {
         duration: Duration,
-        ip: IpAddr,
+        /// First address answered; `None` for other record types (see [`Workload`](crate::dns::Workload))
+        ip: Option<IpAddr>,
     },
     /// Failed resolution
     Failure {
@@ -138,23 +435,225 @@ impl TimingResult {
     }
 }
 
+/// Why a query failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// No answer within the timeout
+    Timeout,
+    /// The server answered SERVFAIL
+    ServFail,
+    /// The server answered REFUSED, often a sign of rate limiting
+    Refused,
+    /// The query couldn't be sent or the connection failed
+    Network,
+    /// Anything else, like an empty or undecodable answer
+    Other,
+}
+
+/// Failed queries of one server, counted by [`FailureKind`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureBreakdown {
+    /// Queries that timed out
+    pub timeouts: u32,
+    /// Queries answered with SERVFAIL
+    pub servfail: u32,
+    /// Queries answered with REFUSED
+    pub refused: u32,
+    /// Queries lost to send or connection errors
+    pub network: u32,
+    /// Queries that failed otherwise
+    pub other: u32,
+}
+
+impl FailureBreakdown {
+    /// Count one failed query
+    pub fn record(&mut self, kind: FailureKind) {
+        match kind {
+            FailureKind::Timeout => self.timeouts += 1,
+            FailureKind::ServFail => self.servfail += 1,
+            FailureKind::Refused => self.refused += 1,
+            FailureKind::Network => self.network += 1,
+            FailureKind::Other => self.other += 1,
+        }
+    }
+
+    /// Number of failed queries
+    pub fn total(&self) -> u32 {
+        self.timeouts + self.servfail + self.refused + self.network + self.other
+    }
+}
+
+/// Non-zero counts, e.g. `3 timeout, 1 refused`; `-` without failures
+impl fmt::Display for FailureBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            (self.timeouts, "timeout"),
+            (self.servfail, "servfail"),
+            (self.refused, "refused"),
+            (self.network, "network"),
+            (self.other, "other"),
+        ];
+        let parts: Vec<_> = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, kind)| format!("{count} {kind}"))
+            .collect();
+        if parts.is_empty() { write!(f, "-") } else { write!(f, "{}", parts.join(", ")) }
+    }
+}
+
 /// Complete benchmark results
-#[derive(Debug, Clone)]
+///
+/// Serializes like [`ServerResult`]; [`BenchmarkResult::from_json_reader`]
+/// loads it back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BenchmarkResult {
     /// Results for each server, sorted by average time
     pub servers: Vec<ServerResult>,
     /// Total benchmark duration
+    #[serde(rename = "duration_ms", with = "millis")]
     pub duration: Duration,
     /// Domain that was tested
     pub domain: String,
     /// Number of requests per server
     pub requests_per_server: u32,
+    /// IP of the reference server used for normalization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<IpAddr>,
+    /// Network the benchmark was run on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<NetworkFingerprint>,
+    /// Timing precision of the host, if calibrated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<TimingCalibration>,
+    /// TTL served by the domain's authoritative server, if TTLs were checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authoritative_ttl: Option<u32>,
+    /// Time spent in each phase of the run
+    #[serde(default, skip_serializing_if = "PhaseTimings::is_empty")]
+    pub phases: PhaseTimings,
+    /// Average time of each server in the previous run of a `--repeat` series
+    #[serde(rename = "previous_ms", default, with = "millis::map", skip_serializing_if = "Option::is_none")]
+    pub previous: Option<BTreeMap<IpAddr, Duration>>,
+    /// Weights the fastest server and speed ranks are determined by
+    #[serde(default)]
+    pub ranking: Ranking,
+    /// Seed the servers were shuffled with, if they were
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
 }
 
 impl BenchmarkResult {
-    /// Get the fastest server (lowest average time)
+    /// Result of a run over `config` that took `duration`, with `servers` ranked best first
+    ///
+    /// What only some runs detect (the network fingerprint, timing calibration,
+    /// phase timings, and so on) is left empty for the caller to fill in.
+    pub fn ranked(config: &Config, mut servers: Vec<ServerResult>, duration: Duration) -> Self {
+        servers.sort_by(|a, b| a.cmp_score(b, &config.ranking));
+        Self {
+            servers,
+            duration,
+            domain: config.domain.clone(),
+            requests_per_server: config.requests as u32,
+            reference: config.reference,
+            fingerprint: None,
+            calibration: None,
+            authoritative_ttl: None,
+            phases: PhaseTimings::default(),
+            previous: None,
+            ranking: config.ranking.clone(),
+            seed: None,
+        }
+    }
+
+    /// One-second run of 10 requests per server to example.com, as test outputs are built from
+    #[cfg(test)]
+    pub(crate) fn for_test(servers: Vec<ServerResult>) -> Self {
+        Self {
+            servers,
+            duration: Duration::from_secs(1),
+            domain: "example.com".to_string(),
+            requests_per_server: 10,
+            reference: None,
+            fingerprint: None,
+            calibration: None,
+            authoritative_ttl: None,
+            phases: PhaseTimings::default(),
+            previous: None,
+            ranking: Ranking::default(),
+            seed: None,
+        }
+    }
+
+    /// Load a result serialized as JSON, e.g. with `serde_json::to_writer`
+    ///
+    /// This is the full result, not the `--format json` output, which
+    /// [`StoredRun`](super::StoredRun) reads.
+    pub fn from_json_reader(reader: impl Read) -> Result<Self, Error> {
+        serde_json::from_reader(reader)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid benchmark result: {e}")))
+    }
+
+    /// Get the fastest server (best score, by default lowest average time), whatever order the servers are in
+    ///
+    /// Servers below the ranking's minimum success rate, and servers that
+    /// bypass an active VPN tunnel, are never the fastest.
     pub fn fastest(&self) -> Option<&ServerResult> {
-        self.servers.first()
+        self.servers
+            .iter()
+            .filter(|s| s.is_eligible(&self.ranking) && !s.bypasses_vpn())
+            .min_by(|a, b| a.cmp_score(b, &self.ranking))
+    }
+
+    /// Position of a server when ranked by score, starting at 1
+    pub fn speed_rank(&self, server: &ServerResult) -> usize {
+        self.servers
+            .iter()
+            .filter(|s| s.cmp_score(server, &self.ranking) == Ordering::Less)
+            .count()
+            + 1
+    }
+
+    /// Drop all but the `n` fastest servers, keeping their current order
+    pub fn keep_fastest(&mut self, n: usize) {
+        let mut by_speed: Vec<usize> = (0..self.servers.len()).collect();
+        by_speed.sort_by(|&a, &b| self.servers[a].cmp_score(&self.servers[b], &self.ranking));
+        let mut keep = vec![false; self.servers.len()];
+        for i in by_speed.into_iter().take(n) {
+            keep[i] = true;
+        }
+        let mut keep = keep.into_iter();
+        self.servers.retain(|_| keep.next().unwrap_or(false));
+    }
+
+    /// Reorder servers by `key`, best first, or worst first with `reverse`
+    ///
+    /// Ties keep their current order. When ranking by score or a latency,
+    /// servers without one (they never answered) stay last even when reversed.
+    pub fn sort_servers(&mut self, key: SortKey, reverse: bool) {
+        let latency = |s: &ServerResult| match key {
+            SortKey::Avg => s.avg_time,
+            SortKey::Min => s.min_time,
+            SortKey::Max => s.max_time,
+            SortKey::P95 => s.percentile(95.0),
+            SortKey::Score | SortKey::Success | SortKey::Name => None,
+        };
+        let ranking = &self.ranking;
+        self.servers.sort_by(|a, b| {
+            let order = match key {
+                SortKey::Score => match (a.score(ranking), b.score(ranking)) {
+                    (Some(_), Some(_)) => a.cmp_score(b, ranking),
+                    (a, b) => return a.is_none().cmp(&b.is_none()),
+                },
+                SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortKey::Success => b.success_rate().total_cmp(&a.success_rate()),
+                _ => match (latency(a), latency(b)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (a, b) => return a.is_none().cmp(&b.is_none()),
+                },
+            };
+            if reverse { order.reverse() } else { order }
+        });
     }
 
     /// Get servers that had 100% success rate
@@ -166,6 +665,318 @@ impl BenchmarkResult {
     pub fn completely_failed(&self) -> impl Iterator<Item = &ServerResult> {
         self.servers.iter().filter(|s| s.all_failed())
     }
+
+    /// Servers that answered, but less often than the ranking's minimum success rate
+    pub fn ineligible(&self) -> impl Iterator<Item = &ServerResult> {
+        self.servers.iter().filter(|s| !s.all_failed() && !s.is_eligible(&self.ranking))
+    }
+
+    /// Get the result of the reference server, if one was configured
+    pub fn reference_result(&self) -> Option<&ServerResult> {
+        let reference = self.reference?;
+        self.servers.iter().find(|s| s.ip == reference)
+    }
+
+    /// Express a server's average latency relative to the reference server
+    ///
+    /// Returns `None` when no reference is configured or either server has no
+    /// successful measurements.
+    pub fn normalized(&self, server: &ServerResult) -> Option<NormalizedLatency> {
+        let reference = self.reference_result()?.avg_time?.as_secs_f64() * 1000.0;
+        let avg = server.avg_time?.as_secs_f64() * 1000.0;
+
+        if reference <= 0.0 {
+            return None;
+        }
+
+        Some(NormalizedLatency {
+            ratio: avg / reference,
+            delta_ms: avg - reference,
+        })
+    }
+
+    /// Remember the average times of `previous` to show each server's trend
+    pub fn set_previous(&mut self, previous: &BenchmarkResult) {
+        self.previous = Some(
+            previous
+                .servers
+                .iter()
+                .filter_map(|s| Some((s.ip, s.avg_time?)))
+                .collect(),
+        );
+    }
+
+    /// Change of a server's average latency since the previous run, in ms
+    ///
+    /// Returns `None` on the first run, or when the server had no successful
+    /// measurements in either run.
+    pub fn trend_ms(&self, server: &ServerResult) -> Option<f64> {
+        let previous = self.previous.as_ref()?.get(&server.ip)?;
+        Some((server.avg_time?.as_secs_f64() - previous.as_secs_f64()) * 1000.0)
+    }
+
+    /// Group results by provider name, sorted by best average time
+    pub fn by_provider(&self) -> Vec<ProviderResult> {
+        let mut providers = aggregate_providers(self.servers.iter());
+        providers.sort_by_key(|p| p.sort_key());
+        providers
+    }
+
+    /// Pair each provider's IPv4 and IPv6 endpoints, sorted by IPv4 best time
+    ///
+    /// Only providers with at least one server of each family are included.
+    pub fn dual_stack(&self) -> Vec<DualStackComparison> {
+        let v4 = aggregate_providers(self.servers.iter().filter(|s| s.ip.is_ipv4()));
+        let mut v6 = aggregate_providers(self.servers.iter().filter(|s| s.ip.is_ipv6()));
+
+        let mut pairs: Vec<DualStackComparison> = v4
+            .into_iter()
+            .filter_map(|v4| {
+                let idx = v6.iter().position(|p| p.name == v4.name)?;
+                let v6 = v6.swap_remove(idx);
+                Some(DualStackComparison { name: v4.name.clone(), v4, v6 })
+            })
+            .collect();
+
+        pairs.sort_by_key(|p| p.v4.sort_key());
+        pairs
+    }
+
+    /// Group results of servers measured over several protocols, in ranking order
+    ///
+    /// Servers are matched by name and IP; those measured over a single
+    /// protocol are left out.
+    pub fn by_protocol(&self) -> Vec<ProtocolComparison> {
+        let mut comparisons: Vec<ProtocolComparison> = Vec::new();
+        for server in &self.servers {
+            match comparisons.iter_mut().find(|c| c.name == server.name && c.ip == server.ip) {
+                Some(comparison) => comparison.results.push(server.clone()),
+                None => comparisons.push(ProtocolComparison {
+                    name: server.name.clone(),
+                    ip: server.ip,
+                    results: vec![server.clone()],
+                }),
+            }
+        }
+        comparisons.retain(|c| c.results.len() > 1);
+        comparisons
+    }
+}
+
+/// Wall-clock time spent in each phase of a run
+///
+/// Phases that didn't run, or weren't timed, are `None`. Detection of the
+/// network fingerprint overlaps with benchmarking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    /// Loading the server list (built-in, custom, remote, hostname entries)
+    pub collection: Option<Duration>,
+    /// Detecting system and gateway resolvers and the network fingerprint
+    pub detection: Option<Duration>,
+    /// Measuring the servers, including extra measurements like page loads
+    pub benchmarking: Option<Duration>,
+    /// Running capability probes
+    pub probing: Option<Duration>,
+    /// Writing the output; only known after the output is written
+    pub formatting: Option<Duration>,
+}
+
+impl PhaseTimings {
+    /// Timed phases in run order, by name
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Duration)> {
+        [
+            ("collection", self.collection),
+            ("detection", self.detection),
+            ("benchmarking", self.benchmarking),
+            ("probing", self.probing),
+            ("formatting", self.formatting),
+        ]
+        .into_iter()
+        .filter_map(|(name, d)| Some((name, d?)))
+    }
+
+    /// Check if no phase was timed
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, d)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{name} {:.2}s", d.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as `{"collection_ms": 12.3, ...}` with untimed phases left out
+impl Serialize for PhaseTimings {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        for (name, d) in self.iter() {
+            map.serialize_entry(&format!("{name}_ms"), &(d.as_secs_f64() * 1000.0))?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for PhaseTimings {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = BTreeMap::<String, f64>::deserialize(deserializer)?;
+        let phase = |name: &str| {
+            map.get(&format!("{name}_ms"))
+                .map(|ms| Duration::try_from_secs_f64(ms / 1000.0).map_err(serde::de::Error::custom))
+                .transpose()
+        };
+        Ok(Self {
+            collection: phase("collection")?,
+            detection: phase("detection")?,
+            benchmarking: phase("benchmarking")?,
+            probing: phase("probing")?,
+            formatting: phase("formatting")?,
+        })
+    }
+}
+
+/// Latency of a server relative to the reference server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizedLatency {
+    /// Server average divided by reference average (1.0 = same speed)
+    pub ratio: f64,
+    /// Server average minus reference average in milliseconds
+    pub delta_ms: f64,
+}
+
+/// Group server results by name, preserving first-seen order
+fn aggregate_providers<'a>(servers: impl Iterator<Item = &'a ServerResult>) -> Vec<ProviderResult> {
+    let mut providers: Vec<ProviderResult> = Vec::new();
+
+    for server in servers {
+        match providers.iter_mut().find(|p| p.name == server.name) {
+            Some(provider) => provider.add(server),
+            None => providers.push(ProviderResult::new(server)),
+        }
+    }
+
+    providers
+}
+
+/// IPv4 vs IPv6 comparison for a single provider
+#[derive(Debug, Clone)]
+pub struct DualStackComparison {
+    /// Provider name
+    pub name: String,
+    /// Aggregated results for the provider's IPv4 servers
+    pub v4: ProviderResult,
+    /// Aggregated results for the provider's IPv6 servers
+    pub v6: ProviderResult,
+}
+
+impl DualStackComparison {
+    /// IPv6 average minus IPv4 average in milliseconds (positive = IPv6 slower)
+    pub fn delta_ms(&self) -> Option<f64> {
+        let v4 = self.v4.avg_time?.as_secs_f64() * 1000.0;
+        let v6 = self.v6.avg_time?.as_secs_f64() * 1000.0;
+        Some(v6 - v4)
+    }
+}
+
+/// A server's results over each protocol it was measured with
+#[derive(Debug, Clone)]
+pub struct ProtocolComparison {
+    /// Server name
+    pub name: String,
+    /// Server IP address
+    pub ip: IpAddr,
+    /// One result per protocol, in ranking order
+    pub results: Vec<ServerResult>,
+}
+
+impl ProtocolComparison {
+    /// Result over `protocol`, if the server was measured with it
+    pub fn get(&self, protocol: Protocol) -> Option<&ServerResult> {
+        self.results.iter().find(|r| r.protocol == protocol)
+    }
+
+    /// Protocol with the lowest average latency
+    pub fn fastest(&self) -> Option<Protocol> {
+        self.results
+            .iter()
+            .filter_map(|r| Some((r.avg_time?, r.protocol)))
+            .min_by_key(|(avg, _)| *avg)
+            .map(|(_, protocol)| protocol)
+    }
+}
+
+/// Aggregated results for all servers of a single provider
+#[derive(Debug, Clone)]
+pub struct ProviderResult {
+    /// Provider name
+    pub name: String,
+    /// Server IP addresses belonging to this provider
+    pub ips: Vec<IpAddr>,
+    /// Total number of requests made across all servers
+    pub total_requests: u32,
+    /// Number of successful requests across all servers
+    pub successful_requests: u32,
+    /// Lowest average response time of any single server
+    pub best_time: Option<Duration>,
+    /// Average response time across all successful requests
+    pub avg_time: Option<Duration>,
+}
+
+impl ProviderResult {
+    fn new(server: &ServerResult) -> Self {
+        Self {
+            name: server.name.clone(),
+            ips: vec![server.ip],
+            total_requests: server.total_requests,
+            successful_requests: server.successful_requests,
+            best_time: server.avg_time,
+            avg_time: server.avg_time,
+        }
+    }
+
+    fn add(&mut self, server: &ServerResult) {
+        // Weight each server's average by its number of successful requests
+        let weighted = |avg: Option<Duration>, n: u32| avg.map(|d| d * n).unwrap_or_default();
+        let total_time = weighted(self.avg_time, self.successful_requests)
+            + weighted(server.avg_time, server.successful_requests);
+
+        self.ips.push(server.ip);
+        self.total_requests += server.total_requests;
+        self.successful_requests += server.successful_requests;
+        self.avg_time = if self.successful_requests > 0 {
+            Some(total_time / self.successful_requests)
+        } else {
+            None
+        };
+        self.best_time = match (self.best_time, server.avg_time) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Get success rate as a percentage
+    #[inline]
+    pub fn success_rate(&self) -> f64 {
+        if self.total_requests == 0 {
+            0.0
+        } else {
+            (self.successful_requests as f64 / self.total_requests as f64) * 100.0
+        }
+    }
+
+    /// Get the sort key (best time or max duration for failures)
+    pub fn sort_key(&self) -> Duration {
+        self.best_time.unwrap_or(Duration::MAX)
+    }
 }
 
 /// Serializable result entry for output formatters
@@ -173,6 +984,15 @@ impl BenchmarkResult {
 pub struct SerializableResult {
     pub name: String,
     pub ip: String,
+    /// Server port, if not 53
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Server source, if requested with the `source` column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Query protocol, if requested with the `protocol` column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Protocol>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolved_ip: Option<String>,
     pub total_requests: u32,
@@ -186,61 +1006,899 @@ pub struct SerializableResult {
     pub avg_ms: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_delta_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_load_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_load_success_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec_on_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec_off_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec_overhead_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nx_first_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nx_cached_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_fallback_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failures: Option<FailureBreakdown>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rcodes: BTreeMap<String, u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_types_ms: Option<BTreeMap<String, f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecs: Option<EcsSupport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecs_scope: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correctness: Option<Correctness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_answered: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_horizon: Option<SplitHorizon>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vpn_interface: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vpn_route: Option<VpnRoute>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_ttl: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_verdict: Option<TtlVerdict>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_changes: Option<u32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub probes: BTreeMap<String, ProbeResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_geo: Option<GeoInfo>,
 }
 
-impl From<&ServerResult> for SerializableResult {
-    fn from(r: &ServerResult) -> Self {
-        Self {
-            name: r.name.clone(),
-            ip: r.ip.to_string(),
-            resolved_ip: r.resolved_ip.map(|ip| ip.to_string()),
-            total_requests: r.total_requests,
-            successful_requests: r.successful_requests,
-            success_rate: r.success_rate(),
-            min_ms: r.min_time.map(|d| d.as_secs_f64() * 1000.0),
-            max_ms: r.max_time.map(|d| d.as_secs_f64() * 1000.0),
-            avg_ms: r.avg_time.map(|d| d.as_secs_f64() * 1000.0),
-            error: if r.all_failed() { r.last_error.clone() } else { None },
+impl SerializableResult {
+    /// Build an entry including reference normalization from the full result
+    pub fn from_benchmark(r: &ServerResult, result: &BenchmarkResult) -> Self {
+        let mut entry = Self::from(r);
+        if let Some(n) = result.normalized(r) {
+            entry.ref_ratio = Some(n.ratio);
+            entry.ref_delta_ms = Some(n.delta_ms);
         }
+        entry
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::net::Ipv4Addr;
+/// Country and network of an IP address from a GeoIP database
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeoInfo {
+    /// ISO 3166 country code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Autonomous system number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    /// Autonomous system organization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_org: Option<String>,
+}
 
-    fn make_server() -> DnsServer {
-        DnsServer::from_ip("Test", IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)), ServerSource::Builtin)
+impl GeoInfo {
+    /// Check if nothing is known
+    pub fn is_empty(&self) -> bool {
+        self.country.is_none() && self.asn.is_none() && self.as_org.is_none()
     }
 
-    #[test]
-    fn test_server_result_all_success() {
-        let server = make_server();
-        let measurements = vec![
-            TimingResult::Success {
-                duration: Duration::from_millis(10),
-                ip: "1.2.3.4".parse().unwrap(),
-            },
-            TimingResult::Success {
-                duration: Duration::from_millis(20),
-                ip: "1.2.3.4".parse().unwrap(),
-            },
-        ];
-
-        let result = ServerResult::from_measurements(&server, measurements);
+    /// Fill in fields that are still unknown from another lookup
+    pub fn merge(&mut self, other: Self) {
+        self.country = self.country.take().or(other.country);
+        self.asn = self.asn.or(other.asn);
+        self.as_org = self.as_org.take().or(other.as_org);
+    }
+}
 
-        assert_eq!(result.total_requests, 2);
-        assert_eq!(result.successful_requests, 2);
-        assert_eq!(result.success_rate(), 100.0);
-        assert_eq!(result.min_time, Some(Duration::from_millis(10)));
-        assert_eq!(result.max_time, Some(Duration::from_millis(20)));
-        assert_eq!(result.avg_time, Some(Duration::from_millis(15)));
-        assert!(result.resolved_ip.is_some());
-        assert!(!result.all_failed());
+impl fmt::Display for GeoInfo {
+    /// Compact form, e.g. `AS15169 US`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.asn, &self.country) {
+            (Some(asn), Some(cc)) => write!(f, "AS{asn} {cc}"),
+            (Some(asn), None) => write!(f, "AS{asn}"),
+            (None, Some(cc)) => write!(f, "{cc}"),
+            (None, None) => write!(f, "-"),
+        }
     }
+}
 
-    #[test]
+/// Outcome of simulated browser page loads against one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageLoadResult {
+    /// Number of simulated page loads
+    pub runs: u32,
+    /// Page loads in which every domain resolved
+    pub successful: u32,
+    /// Average wall time of successful page loads
+    #[serde(rename = "avg_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub avg_time: Option<Duration>,
+    /// Slowest successful page load
+    #[serde(rename = "max_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub max_time: Option<Duration>,
+}
+
+impl PageLoadResult {
+    /// Summarize the wall times of successful page loads out of `runs`
+    pub fn from_timings(runs: u32, times: &[Duration]) -> Self {
+        let avg_time = (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32);
+        Self {
+            runs,
+            successful: times.len() as u32,
+            avg_time,
+            max_time: times.iter().max().copied(),
+        }
+    }
+
+    /// Percentage of page loads that fully resolved
+    pub fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            (self.successful as f64 / self.runs as f64) * 100.0
+        }
+    }
+}
+
+/// Latency with and without DNSSEC validation against one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnssecAbResult {
+    /// Number of query pairs sent
+    pub runs: u32,
+    /// Average time of answered queries with validation (DO set)
+    #[serde(rename = "validated_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub validated: Option<Duration>,
+    /// Average time of answered queries without validation (CD set)
+    #[serde(rename = "unvalidated_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub unvalidated: Option<Duration>,
+    /// Validated queries that were answered
+    pub validated_successful: u32,
+    /// Unvalidated queries that were answered
+    pub unvalidated_successful: u32,
+}
+
+impl DnssecAbResult {
+    /// Summarize answered query times for both modes out of `runs` pairs
+    pub fn from_timings(runs: u32, validated: &[Duration], unvalidated: &[Duration]) -> Self {
+        let avg = |times: &[Duration]| (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32);
+        Self {
+            runs,
+            validated: avg(validated),
+            unvalidated: avg(unvalidated),
+            validated_successful: validated.len() as u32,
+            unvalidated_successful: unvalidated.len() as u32,
+        }
+    }
+
+    /// Extra latency of validation in milliseconds (validated - unvalidated)
+    pub fn overhead_ms(&self) -> Option<f64> {
+        let validated = self.validated?.as_secs_f64() * 1000.0;
+        let unvalidated = self.unvalidated?.as_secs_f64() * 1000.0;
+        Some(validated - unvalidated)
+    }
+}
+
+/// NXDOMAIN latency of one server before and after the answer is cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegativeCacheResult {
+    /// Repeated queries sent after the first one
+    pub runs: u32,
+    /// Time of the first NXDOMAIN answer, which had to be fetched
+    #[serde(rename = "first_miss_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub first_miss: Option<Duration>,
+    /// Average time of repeated NXDOMAIN answers, normally from the negative cache
+    #[serde(rename = "cached_ms", default, with = "millis::option", skip_serializing_if = "Option::is_none")]
+    pub cached: Option<Duration>,
+    /// Repeated queries answered with NXDOMAIN
+    pub cached_successful: u32,
+}
+
+impl NegativeCacheResult {
+    /// Summarize the first-miss time and the repeated answer times out of `runs`
+    pub fn from_timings(runs: u32, first_miss: Option<Duration>, cached: &[Duration]) -> Self {
+        Self {
+            runs,
+            first_miss,
+            cached: (!cached.is_empty()).then(|| cached.iter().sum::<Duration>() / cached.len() as u32),
+            cached_successful: cached.len() as u32,
+        }
+    }
+
+    /// Time saved by the negative cache in milliseconds (first miss - cached)
+    pub fn savings_ms(&self) -> Option<f64> {
+        let first = self.first_miss?.as_secs_f64() * 1000.0;
+        let cached = self.cached?.as_secs_f64() * 1000.0;
+        Some(first - cached)
+    }
+}
+
+/// How a server treated the EDNS Client Subnet option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EcsSupport {
+    /// Answers were tailored to the subnet (non-zero scope prefix)
+    Honored,
+    /// The option was echoed back, but answers cover all clients (scope 0)
+    Echoed,
+    /// The option was dropped from responses
+    Ignored,
+}
+
+impl fmt::Display for EcsSupport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Honored => write!(f, "honored"),
+            Self::Echoed => write!(f, "echoed"),
+            Self::Ignored => write!(f, "ignored"),
+        }
+    }
+}
+
+/// EDNS Client Subnet option in the responses of one server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EcsResult {
+    /// Responses to queries carrying the option
+    pub responses: u32,
+    /// Responses that echoed the option back
+    pub echoed: u32,
+    /// Largest scope prefix length in echoed options
+    pub max_scope: u8,
+}
+
+impl EcsResult {
+    /// Record one response, with the scope prefix length if the option was echoed
+    pub fn record(&mut self, echoed_scope: Option<u8>) {
+        self.responses += 1;
+        if let Some(scope) = echoed_scope {
+            self.echoed += 1;
+            self.max_scope = self.max_scope.max(scope);
+        }
+    }
+
+    /// Overall verdict, if any response was received
+    pub fn support(&self) -> Option<EcsSupport> {
+        if self.responses == 0 {
+            None
+        } else if self.max_scope > 0 {
+            Some(EcsSupport::Honored)
+        } else if self.echoed > 0 {
+            Some(EcsSupport::Echoed)
+        } else {
+            Some(EcsSupport::Ignored)
+        }
+    }
+
+    /// Largest scope prefix length, if the option was ever echoed
+    pub fn echoed_scope(&self) -> Option<u8> {
+        (self.echoed > 0).then_some(self.max_scope)
+    }
+}
+
+/// How a server's answer TTLs compare to the authoritative TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtlVerdict {
+    /// TTLs count down from the authoritative value
+    Preserved,
+    /// TTLs stay far below the authoritative value (maximum TTL clamp)
+    Lowered,
+    /// TTLs exceed the authoritative value (minimum TTL clamp)
+    Raised,
+}
+
+impl fmt::Display for TtlVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preserved => write!(f, "preserved"),
+            Self::Lowered => write!(f, "lowered"),
+            Self::Raised => write!(f, "raised"),
+        }
+    }
+}
+
+/// Answer TTLs reported by one server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TtlResult {
+    /// Answers with at least one address record
+    pub samples: u32,
+    /// Lowest TTL seen
+    pub min: u32,
+    /// Highest TTL seen
+    pub max: u32,
+    /// Sum of all TTLs, for the average
+    pub total: u64,
+    /// TTL served by the authoritative server, if it could be queried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authoritative: Option<u32>,
+}
+
+impl TtlResult {
+    /// Record the TTL of one answer
+    pub fn record(&mut self, ttl: u32) {
+        self.min = if self.samples == 0 { ttl } else { self.min.min(ttl) };
+        self.max = self.max.max(ttl);
+        self.total += u64::from(ttl);
+        self.samples += 1;
+    }
+
+    /// Average TTL in seconds, if any answer was recorded
+    pub fn avg(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.total as f64 / self.samples as f64)
+    }
+
+    /// Compare to the authoritative TTL, if both are known
+    ///
+    /// Cached answers count down from the authoritative TTL, so only the
+    /// highest TTL seen is meaningful: above the authoritative value means the
+    /// server raised it, below half of it across every answer means the
+    /// server caps it.
+    pub fn verdict(&self) -> Option<TtlVerdict> {
+        let authoritative = self.authoritative?;
+        if self.samples == 0 {
+            None
+        } else if self.max > authoritative {
+            Some(TtlVerdict::Raised)
+        } else if u64::from(self.max) * 2 < u64::from(authoritative) {
+            Some(TtlVerdict::Lowered)
+        } else {
+            Some(TtlVerdict::Preserved)
+        }
+    }
+}
+
+/// Anycast node identifiers reported by one server over the run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnycastNodes {
+    /// Identification queries sent
+    pub queries: u32,
+    /// Identifiers in the order they were received
+    pub samples: Vec<String>,
+}
+
+impl AnycastNodes {
+    /// Record one identification query, with the identifier if the server gave one
+    pub fn record(&mut self, node: Option<String>) {
+        self.queries += 1;
+        self.samples.extend(node);
+    }
+
+    /// Distinct identifiers in the order first seen
+    pub fn distinct(&self) -> impl Iterator<Item = &str> {
+        self.samples
+            .iter()
+            .enumerate()
+            .filter(|(i, node)| !self.samples[..*i].contains(node))
+            .map(|(_, node)| node.as_str())
+    }
+
+    /// Number of times the identifier differed from the previous one
+    pub fn changes(&self) -> u32 {
+        self.samples.windows(2).filter(|w| w[0] != w[1]).count() as u32
+    }
+}
+
+/// Internal-only names resolved through one server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitHorizonResult {
+    /// Number of internal names checked
+    pub checked: u32,
+    /// Internal names that resolved to an address
+    pub answered: Vec<String>,
+}
+
+impl SplitHorizonResult {
+    /// Judge the answers by where the server comes from
+    ///
+    /// System, gateway, and DHCP resolvers are the network's own and should
+    /// answer every internal name; built-in servers are public and should
+    /// answer none. Custom servers can be either, like a company's own resolver, so
+    /// their answers aren't judged.
+    pub fn verdict(&self, source: ServerSource) -> SplitHorizon {
+        let answered = self.answered.len() as u32;
+        match source {
+            ServerSource::System | ServerSource::Gateway | ServerSource::Dhcp if answered < self.checked => {
+                SplitHorizon::Missing
+            }
+            ServerSource::System | ServerSource::Gateway | ServerSource::Dhcp => SplitHorizon::Correct,
+            ServerSource::Builtin if answered > 0 => SplitHorizon::Leak,
+            ServerSource::Builtin => SplitHorizon::Correct,
+            ServerSource::Custom => SplitHorizon::Unknown,
+        }
+    }
+}
+
+/// Split-horizon verdict for one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitHorizon {
+    /// Internal resolvers answer internal names and public ones don't
+    #[serde(rename = "ok")]
+    Correct,
+    /// A public resolver answered an internal name (exposed zone or NXDOMAIN rewriting)
+    Leak,
+    /// An internal resolver failed to answer an internal name
+    Missing,
+    /// A custom server, which may be internal or public
+    Unknown,
+}
+
+impl fmt::Display for SplitHorizon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "ok"),
+            Self::Leak => write!(f, "leak"),
+            Self::Missing => write!(f, "missing"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Interface queries to one server leave through while a VPN is up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VpnRouteResult {
+    /// Interface name, like `tun0` or `en0`
+    pub interface: String,
+    pub verdict: VpnRoute,
+}
+
+/// VPN leak verdict for one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VpnRoute {
+    /// Queries go through the tunnel
+    Tunnel,
+    /// The server runs on this host (a local stub or cache), which forwards queries its own way
+    Local,
+    /// Queries leave outside the tunnel, showing the sites visited to the local network
+    Bypass,
+}
+
+impl fmt::Display for VpnRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tunnel => write!(f, "tunnel"),
+            Self::Local => write!(f, "local"),
+            Self::Bypass => write!(f, "bypass"),
+        }
+    }
+}
+
+/// How a server's answers compare to those of a reference resolver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Correctness {
+    /// At least one address is in the same network as a reference answer
+    Consistent,
+    /// No address is near any reference answer (CDN steering or tampering)
+    Divergent,
+    /// Private, loopback, or otherwise unroutable addresses the reference
+    /// didn't return, a typical sign of poisoning or a block page
+    Bogus,
+}
+
+impl Correctness {
+    /// Compare a server's answer set to the reference's
+    ///
+    /// Addresses count as matching when they share a /24 (IPv4) or /48 (IPv6),
+    /// since CDN-backed domains rotate addresses within their networks.
+    /// Returns `None` when either side has no answers.
+    pub fn compare(answers: &[IpAddr], reference: &[IpAddr]) -> Option<Self> {
+        if answers.is_empty() || reference.is_empty() {
+            return None;
+        }
+        if answers.iter().any(|ip| is_bogon(ip) && !reference.contains(ip)) {
+            return Some(Self::Bogus);
+        }
+        let consistent = answers
+            .iter()
+            .any(|a| reference.iter().any(|r| same_network(a, r)));
+        Some(if consistent { Self::Consistent } else { Self::Divergent })
+    }
+}
+
+impl fmt::Display for Correctness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Consistent => write!(f, "consistent"),
+            Self::Divergent => write!(f, "divergent"),
+            Self::Bogus => write!(f, "bogus"),
+        }
+    }
+}
+
+/// Check if an address is never a legitimate public answer
+pub(crate) fn is_bogon(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_unspecified()
+                || v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_unspecified() || v6.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Check if two addresses share a /24 (IPv4) or /48 (IPv6)
+fn same_network(a: &IpAddr, b: &IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => a.octets()[..3] == b.octets()[..3],
+        (IpAddr::V6(a), IpAddr::V6(b)) => a.segments()[..3] == b.segments()[..3],
+        _ => false,
+    }
+}
+
+/// Single timed query made by the `resolve` subcommand
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolveResult {
+    /// Server the query was sent to
+    pub server: SocketAddr,
+    /// Transport used
+    pub protocol: Protocol,
+    /// Name that was queried
+    pub name: String,
+    /// Record type that was queried
+    pub record_type: String,
+    /// Time until the answer (or the error) in milliseconds
+    pub duration_ms: f64,
+    /// Response code (`NOERROR`, `NXDOMAIN`, ...); `None` without a response
+    pub rcode: Option<String>,
+    /// Answer section, including any CNAME chain
+    pub answers: Vec<AnswerRecord>,
+    /// Why no response was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One record of an answer section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnswerRecord {
+    /// Owner name
+    pub name: String,
+    /// Time to live in seconds
+    pub ttl: u32,
+    /// Record type
+    pub record_type: String,
+    /// Record data in presentation format
+    pub data: String,
+}
+
+/// Iterative resolution of one name, from the root down to its zone
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceResult {
+    /// Name that was traced
+    pub domain: String,
+    /// Record type that was queried
+    pub record_type: String,
+    /// Queries in the order they were sent, including failed attempts
+    pub hops: Vec<TraceHop>,
+    /// Wall time of the whole trace in milliseconds, glue lookups included
+    pub total_ms: f64,
+}
+
+impl TraceResult {
+    /// Check if the trace ended with an authoritative answer, positive or negative
+    pub fn completed(&self) -> bool {
+        matches!(
+            self.hops.last().map(|h| &h.outcome),
+            Some(TraceOutcome::Answer { .. } | TraceOutcome::Negative { .. })
+        )
+    }
+
+    /// Time spent in each zone, summing failed attempts with the successful one
+    pub fn zone_latencies(&self) -> Vec<(&str, f64)> {
+        let mut zones: Vec<(&str, f64)> = Vec::new();
+        for hop in &self.hops {
+            match zones.last_mut() {
+                Some((zone, ms)) if *zone == hop.zone => *ms += hop.latency_ms,
+                _ => zones.push((&hop.zone, hop.latency_ms)),
+            }
+        }
+        zones
+    }
+}
+
+/// One query of a trace
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceHop {
+    /// Zone the server was asked about ("." for the root)
+    pub zone: String,
+    /// Name of the queried server
+    pub server: String,
+    /// Address the query was sent to
+    pub ip: IpAddr,
+    /// Round trip in milliseconds, or time until giving up for failures
+    pub latency_ms: f64,
+    /// What the server answered
+    pub outcome: TraceOutcome,
+}
+
+/// What a server answered during a trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TraceOutcome {
+    /// Delegation to the servers of a child zone
+    Referral { zone: String, servers: Vec<String> },
+    /// Records for the traced name
+    Answer { records: Vec<String> },
+    /// Authoritative NXDOMAIN or NODATA
+    Negative { rcode: String },
+    /// No usable response; the next server of the zone is tried
+    Failed { error: String },
+}
+
+impl fmt::Display for TraceOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Referral { zone, servers } => write!(f, "referral to {zone} ({} servers)", servers.len()),
+            Self::Answer { records } => write!(f, "{}", records.join(", ")),
+            Self::Negative { rcode } => write!(f, "{rcode}"),
+            Self::Failed { error } => write!(f, "failed: {error}"),
+        }
+    }
+}
+
+/// Serializable provider entry for output formatters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializableProvider {
+    pub name: String,
+    pub ips: Vec<String>,
+    pub total_requests: u32,
+    pub successful_requests: u32,
+    pub success_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_ms: Option<f64>,
+}
+
+impl From<&ProviderResult> for SerializableProvider {
+    fn from(p: &ProviderResult) -> Self {
+        Self {
+            name: p.name.clone(),
+            ips: p.ips.iter().map(|ip| ip.to_string()).collect(),
+            total_requests: p.total_requests,
+            successful_requests: p.successful_requests,
+            success_rate: p.success_rate(),
+            best_ms: p.best_time.map(|d| d.as_secs_f64() * 1000.0),
+            avg_ms: p.avg_time.map(|d| d.as_secs_f64() * 1000.0),
+        }
+    }
+}
+
+/// Serializable dual-stack comparison entry for output formatters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializableDualStack {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v4_avg_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v6_avg_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta_ms: Option<f64>,
+}
+
+impl From<&DualStackComparison> for SerializableDualStack {
+    fn from(c: &DualStackComparison) -> Self {
+        Self {
+            name: c.name.clone(),
+            v4_avg_ms: c.v4.avg_time.map(|d| d.as_secs_f64() * 1000.0),
+            v6_avg_ms: c.v6.avg_time.map(|d| d.as_secs_f64() * 1000.0),
+            delta_ms: c.delta_ms(),
+        }
+    }
+}
+
+/// Serializable protocol comparison entry for output formatters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializableProtocolComparison {
+    pub name: String,
+    pub ip: String,
+    /// Average latency by protocol, for protocols with any answer
+    pub avg_ms: BTreeMap<Protocol, f64>,
+    /// Success rate by protocol
+    pub success_rate: BTreeMap<Protocol, f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fastest: Option<Protocol>,
+}
+
+impl From<&ProtocolComparison> for SerializableProtocolComparison {
+    fn from(c: &ProtocolComparison) -> Self {
+        Self {
+            name: c.name.clone(),
+            ip: c.ip.to_string(),
+            avg_ms: c
+                .results
+                .iter()
+                .filter_map(|r| Some((r.protocol, r.avg_time?.as_secs_f64() * 1000.0)))
+                .collect(),
+            success_rate: c.results.iter().map(|r| (r.protocol, r.success_rate())).collect(),
+            fastest: c.fastest(),
+        }
+    }
+}
+
+impl From<&ServerResult> for SerializableResult {
+    fn from(r: &ServerResult) -> Self {
+        Self {
+            name: r.name.clone(),
+            ip: r.ip.to_string(),
+            port: (r.port != plain_dns_port()).then_some(r.port),
+            source: None,
+            protocol: None,
+            resolved_ip: r.resolved_ip.map(|ip| ip.to_string()),
+            total_requests: r.total_requests,
+            successful_requests: r.successful_requests,
+            success_rate: r.success_rate(),
+            min_ms: r.min_time.map(|d| d.as_secs_f64() * 1000.0),
+            max_ms: r.max_time.map(|d| d.as_secs_f64() * 1000.0),
+            avg_ms: r.avg_time.map(|d| d.as_secs_f64() * 1000.0),
+            error: if r.all_failed() { r.last_error.clone() } else { None },
+            ref_ratio: None,
+            ref_delta_ms: None,
+            page_load_ms: r.page_load.and_then(|p| p.avg_time).map(|d| d.as_secs_f64() * 1000.0),
+            page_load_success_rate: r.page_load.map(|p| p.success_rate()),
+            dnssec_on_ms: r.dnssec_ab.and_then(|d| d.validated).map(|d| d.as_secs_f64() * 1000.0),
+            dnssec_off_ms: r.dnssec_ab.and_then(|d| d.unvalidated).map(|d| d.as_secs_f64() * 1000.0),
+            dnssec_overhead_ms: r.dnssec_ab.and_then(|d| d.overhead_ms()),
+            nx_first_ms: r.negative_cache.and_then(|n| n.first_miss).map(|d| d.as_secs_f64() * 1000.0),
+            nx_cached_ms: r.negative_cache.and_then(|n| n.cached).map(|d| d.as_secs_f64() * 1000.0),
+            truncated: r.truncated,
+            failures: r.failures.filter(|f| f.total() > 0),
+            rcodes: r.rcodes.clone(),
+            query_types_ms: r
+                .query_types
+                .as_ref()
+                .map(|types| types.iter().map(|(t, d)| (t.clone(), d.as_secs_f64() * 1000.0)).collect()),
+            truncation_rate: r.truncation_rate(),
+            tcp_fallback_ms: r.tcp_fallback.map(|d| d.as_secs_f64() * 1000.0),
+            ecs: r.ecs.and_then(|e| e.support()),
+            ecs_scope: r.ecs.and_then(|e| e.echoed_scope()),
+            correctness: r.correctness,
+            internal_answered: r.split_horizon.as_ref().map(|s| s.answered.len() as u32),
+            split_horizon: r.split_horizon.as_ref().map(|s| s.verdict(r.source)),
+            vpn_interface: r.vpn_route.as_ref().map(|v| v.interface.clone()),
+            vpn_route: r.vpn_route.as_ref().map(|v| v.verdict),
+            avg_ttl: r.ttl.and_then(|t| t.avg()),
+            ttl_verdict: r.ttl.and_then(|t| t.verdict()),
+            nodes: r.nodes.as_ref().map(|n| n.distinct().map(String::from).collect()).unwrap_or_default(),
+            node_changes: r.nodes.as_ref().map(|n| n.changes()),
+            probes: r.probes.clone(),
+            warnings: warnings_for(&r.probes).map(String::from).collect(),
+            provider: r.provider.clone(),
+            geo: r.geo.clone(),
+            resolved_geo: r.resolved_geo.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn make_server() -> DnsServer {
+        DnsServer::from_ip("Test", IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)), ServerSource::Builtin)
+    }
+
+    #[test]
+    fn test_failure_breakdown() {
+        let mut failures = FailureBreakdown::default();
+        assert_eq!(failures.to_string(), "-");
+        for kind in [FailureKind::Timeout, FailureKind::Timeout, FailureKind::Refused, FailureKind::Network] {
+            failures.record(kind);
+        }
+        assert_eq!(failures.total(), 4);
+        assert_eq!(failures.to_string(), "2 timeout, 1 refused, 1 network");
+
+        let mut server = ServerResult::from_measurements(&make_server(), Vec::new());
+        server.failures = Some(FailureBreakdown::default());
+        assert_eq!(SerializableResult::from(&server).failures, None);
+        server.failures = Some(failures);
+        let json = serde_json::to_string(&SerializableResult::from(&server)).unwrap();
+        assert!(json.contains(r#""failures":{"timeouts":2,"servfail":0,"refused":1,"network":1,"other":0}"#));
+    }
+
+    #[test]
+    fn test_result_round_trip() {
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let mut server = ServerResult::from_measurements(&make_server(), vec![success(10), success(14)]);
+        server.source = ServerSource::System;
+        server.page_load = Some(PageLoadResult::from_timings(2, &[Duration::from_millis(40)]));
+        server.probes.insert("tcp".to_string(), ProbeResult::pass("ok"));
+        server.rcodes.insert("NOERROR".to_string(), 2);
+        server.query_types = Some(BTreeMap::from([("MX".to_string(), Duration::from_millis(15))]));
+        let result = BenchmarkResult {
+            duration: Duration::from_millis(1500),
+            requests_per_server: 2,
+            phases: PhaseTimings {
+                benchmarking: Some(Duration::from_millis(1200)),
+                ..Default::default()
+            },
+            previous: Some(BTreeMap::from([("8.8.8.8".parse().unwrap(), Duration::from_millis(11))])),
+            seed: Some(42),
+            ..BenchmarkResult::for_test(vec![server])
+        };
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""duration_ms":1500.0"#), "{json}");
+        assert!(json.contains(r#""source":"system""#), "{json}");
+        assert!(json.contains(r#""latencies_ms":[10.0,14.0]"#), "{json}");
+        assert!(json.contains(r#""rcodes":{"NOERROR":2}"#), "{json}");
+        assert!(json.contains(r#""query_types_ms":{"MX":15.0}"#), "{json}");
+        assert!(json.contains(r#""previous_ms":{"8.8.8.8":11.0}"#), "{json}");
+        assert!(!json.contains("dnssec_ab"), "{json}");
+
+        let loaded = BenchmarkResult::from_json_reader(json.as_bytes()).unwrap();
+        assert_eq!(loaded.duration, result.duration);
+        assert_eq!(loaded.phases, result.phases);
+        assert_eq!(loaded.previous, result.previous);
+        assert_eq!(loaded.seed, Some(42));
+        let (server, original) = (&loaded.servers[0], &result.servers[0]);
+        assert_eq!(server.source, ServerSource::System);
+        assert_eq!(server.avg_time, Some(Duration::from_millis(12)));
+        assert_eq!(server.latencies, original.latencies);
+        assert_eq!(server.page_load, original.page_load);
+        assert_eq!(server.probes, original.probes);
+        assert_eq!(server.query_types, original.query_types);
+
+        assert!(BenchmarkResult::from_json_reader(&b"{}"[..]).is_err());
+    }
+
+    #[test]
+    fn test_server_result_all_success() {
+        let server = make_server();
+        let measurements = vec![
+            TimingResult::Success {
+                duration: Duration::from_millis(10),
+                ip: Some("1.2.3.4".parse().unwrap()),
+            },
+            TimingResult::Success {
+                duration: Duration::from_millis(20),
+                ip: Some("1.2.3.4".parse().unwrap()),
+            },
+        ];
+
+        let result = ServerResult::from_measurements(&server, measurements);
+
+        assert_eq!(result.total_requests, 2);
+        assert_eq!(result.successful_requests, 2);
+        assert_eq!(result.success_rate(), 100.0);
+        assert_eq!(result.min_time, Some(Duration::from_millis(10)));
+        assert_eq!(result.max_time, Some(Duration::from_millis(20)));
+        assert_eq!(result.avg_time, Some(Duration::from_millis(15)));
+        assert!(result.resolved_ip.is_some());
+        assert!(!result.all_failed());
+    }
+
+    #[test]
     fn test_server_result_all_failed() {
         let server = make_server();
         let measurements = vec![
@@ -256,6 +1914,456 @@ mod tests {
         assert!(result.min_time.is_none());
         assert!(result.avg_time.is_none());
         assert!(result.all_failed());
+        assert!(result.percentile(95.0).is_none());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let server = make_server();
+        let mut measurements: Vec<_> = [40, 10, 30, 20]
+            .into_iter()
+            .map(|ms| TimingResult::Success {
+                duration: Duration::from_millis(ms),
+                ip: Some("1.2.3.4".parse().unwrap()),
+            })
+            .collect();
+        measurements.push(TimingResult::Failure { error: "timeout".to_string() });
+
+        let result = ServerResult::from_measurements(&server, measurements);
+
+        assert_eq!(result.latencies.len(), 4);
+        assert_eq!(result.percentile(0.0), Some(Duration::from_millis(10)));
+        assert_eq!(result.percentile(50.0), Some(Duration::from_millis(20)));
+        assert_eq!(result.percentile(75.0), Some(Duration::from_millis(30)));
+        assert_eq!(result.percentile(95.0), Some(Duration::from_millis(40)));
+        assert_eq!(result.percentile(100.0), Some(Duration::from_millis(40)));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let server = make_server();
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("1.2.3.4".parse().unwrap()),
+        };
+
+        let steady = ServerResult::from_measurements(&server, vec![success(20), success(20), success(20)]);
+        assert_eq!(steady.jitter(), Some(Duration::ZERO));
+
+        let uneven = ServerResult::from_measurements(&server, vec![success(10), success(20), success(30), success(40)]);
+        assert_eq!(uneven.jitter().map(|d| d.as_micros()), Some(11180));
+
+        let single = ServerResult::from_measurements(&server, vec![success(20)]);
+        assert_eq!(single.jitter(), None);
+    }
+
+    #[test]
+    fn test_ecs_result() {
+        let mut ecs = EcsResult::default();
+        assert_eq!(ecs.support(), None);
+
+        ecs.record(None);
+        assert_eq!(ecs.support(), Some(EcsSupport::Ignored));
+        assert_eq!(ecs.echoed_scope(), None);
+
+        ecs.record(Some(0));
+        assert_eq!(ecs.support(), Some(EcsSupport::Echoed));
+        assert_eq!(ecs.echoed_scope(), Some(0));
+
+        ecs.record(Some(24));
+        assert_eq!(ecs.support(), Some(EcsSupport::Honored));
+        assert_eq!(ecs.echoed_scope(), Some(24));
+        assert_eq!(ecs.responses, 3);
+    }
+
+    #[test]
+    fn test_trace_zone_latencies() {
+        let hop = |zone: &str, latency_ms: f64, outcome: TraceOutcome| TraceHop {
+            zone: zone.to_string(),
+            server: "ns.example.".to_string(),
+            ip: "192.0.2.53".parse().unwrap(),
+            latency_ms,
+            outcome,
+        };
+        let referral = |zone: &str| TraceOutcome::Referral {
+            zone: zone.to_string(),
+            servers: vec!["ns.example.".to_string()],
+        };
+        let mut trace = TraceResult {
+            domain: "example.com.".to_string(),
+            record_type: "A".to_string(),
+            hops: vec![
+                hop(".", 1000.0, TraceOutcome::Failed { error: "Timeout".to_string() }),
+                hop(".", 15.0, referral("com.")),
+                hop("com.", 25.0, referral("example.com.")),
+            ],
+            total_ms: 1050.0,
+        };
+
+        // A timed-out root server counts against the root's share
+        assert_eq!(trace.zone_latencies(), [(".", 1015.0), ("com.", 25.0)]);
+        assert!(!trace.completed());
+
+        trace.hops.push(hop("example.com.", 30.0, TraceOutcome::Answer { records: vec!["A 192.0.2.1".to_string()] }));
+        assert!(trace.completed());
+    }
+
+    #[test]
+    fn test_phase_timings() {
+        let phases = PhaseTimings {
+            detection: Some(Duration::from_millis(1500)),
+            benchmarking: Some(Duration::from_millis(8250)),
+            ..Default::default()
+        };
+        assert!(!phases.is_empty());
+        assert_eq!(phases.to_string(), "detection 1.50s, benchmarking 8.25s");
+        assert_eq!(
+            serde_json::to_string(&phases).unwrap(),
+            r#"{"detection_ms":1500.0,"benchmarking_ms":8250.0}"#
+        );
+        assert!(PhaseTimings::default().is_empty());
+    }
+
+    #[test]
+    fn test_split_horizon_verdict() {
+        let none = SplitHorizonResult { checked: 2, answered: vec![] };
+        let one = SplitHorizonResult { checked: 2, answered: vec!["intranet.corp.example".into()] };
+        let all = SplitHorizonResult {
+            checked: 2,
+            answered: vec!["intranet.corp.example".into(), "wiki.corp.example".into()],
+        };
+
+        assert_eq!(all.verdict(ServerSource::System), SplitHorizon::Correct);
+        assert_eq!(one.verdict(ServerSource::Gateway), SplitHorizon::Missing);
+        assert_eq!(all.verdict(ServerSource::Dhcp), SplitHorizon::Correct);
+        assert_eq!(one.verdict(ServerSource::Dhcp), SplitHorizon::Missing);
+        assert_eq!(none.verdict(ServerSource::System), SplitHorizon::Missing);
+        assert_eq!(none.verdict(ServerSource::Builtin), SplitHorizon::Correct);
+        assert_eq!(one.verdict(ServerSource::Builtin), SplitHorizon::Leak);
+        assert_eq!(one.verdict(ServerSource::Custom), SplitHorizon::Unknown);
+        assert_eq!(all.verdict(ServerSource::Custom), SplitHorizon::Unknown);
+    }
+
+    #[test]
+    fn test_correctness_compare() {
+        let ips = |list: &[&str]| list.iter().map(|ip| ip.parse().unwrap()).collect::<Vec<IpAddr>>();
+        let reference = ips(&["142.250.74.46", "2a00:1450:4001:830::200e"]);
+
+        assert_eq!(Correctness::compare(&ips(&["142.250.74.78"]), &reference), Some(Correctness::Consistent));
+        assert_eq!(Correctness::compare(&ips(&["2a00:1450:4001:81c::200e"]), &reference), Some(Correctness::Consistent));
+        assert_eq!(Correctness::compare(&ips(&["93.184.215.14"]), &reference), Some(Correctness::Divergent));
+        assert_eq!(Correctness::compare(&ips(&["142.250.74.78", "10.0.0.1"]), &reference), Some(Correctness::Bogus));
+        assert_eq!(Correctness::compare(&ips(&["0.0.0.0"]), &reference), Some(Correctness::Bogus));
+        assert_eq!(Correctness::compare(&[], &reference), None);
+
+        // Internal names legitimately resolve to private addresses everywhere
+        let internal = ips(&["10.0.0.1"]);
+        assert_eq!(Correctness::compare(&internal, &internal), Some(Correctness::Consistent));
+    }
+
+    #[test]
+    fn test_anycast_nodes() {
+        let mut nodes = AnycastNodes::default();
+        for node in ["fra08", "fra08", "ams01", "fra08"] {
+            nodes.record(Some(node.to_string()));
+        }
+        nodes.record(None);
+
+        assert_eq!(nodes.queries, 5);
+        assert_eq!(nodes.distinct().collect::<Vec<_>>(), ["fra08", "ams01"]);
+        assert_eq!(nodes.changes(), 2);
+        assert_eq!(AnycastNodes::default().changes(), 0);
+    }
+
+    #[test]
+    fn test_ttl_verdict() {
+        let ttls = |samples: &[u32], authoritative: Option<u32>| {
+            let mut result = TtlResult { authoritative, ..Default::default() };
+            samples.iter().for_each(|&ttl| result.record(ttl));
+            result
+        };
+
+        let preserved = ttls(&[300, 212, 41], Some(300));
+        assert_eq!((preserved.min, preserved.max), (41, 300));
+        assert_eq!(preserved.avg(), Some(184.33333333333334));
+        assert_eq!(preserved.verdict(), Some(TtlVerdict::Preserved));
+        assert_eq!(ttls(&[30, 30], Some(5)).verdict(), Some(TtlVerdict::Raised));
+        assert_eq!(ttls(&[3600, 3598], Some(86400)).verdict(), Some(TtlVerdict::Lowered));
+        assert_eq!(ttls(&[300], None).verdict(), None);
+        assert_eq!(ttls(&[], Some(300)).verdict(), None);
+        assert_eq!(ttls(&[], Some(300)).avg(), None);
+    }
+
+    #[test]
+    fn test_by_protocol() {
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let over = |protocol: Protocol, ms: u64| {
+            let mut server = make_server();
+            server.protocol = Some(protocol);
+            ServerResult::from_measurements(&server, vec![success(ms)])
+        };
+        let other = ServerResult::from_measurements(
+            &DnsServer::from_ip("Other", IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), ServerSource::Builtin),
+            vec![success(5)],
+        );
+        let result = BenchmarkResult {
+            duration: Duration::ZERO,
+            requests_per_server: 1,
+            ..BenchmarkResult::for_test(vec![
+                other,
+                over(Protocol::Udp, 10),
+                over(Protocol::Dot, 30),
+                over(Protocol::Tcp, 20),
+            ])
+        };
+
+        let comparisons = result.by_protocol();
+        assert_eq!(comparisons.len(), 1);
+        let comparison = &comparisons[0];
+        assert_eq!(comparison.name, "Test");
+        assert_eq!(comparison.results.len(), 3);
+        assert_eq!(comparison.get(Protocol::Dot).unwrap().avg_time, Some(Duration::from_millis(30)));
+        assert!(comparison.get(Protocol::Doh).is_none());
+        assert_eq!(comparison.fastest(), Some(Protocol::Udp));
+
+        let serialized = SerializableProtocolComparison::from(comparison);
+        assert_eq!(serialized.avg_ms[&Protocol::Tcp], 20.0);
+        assert_eq!(serialized.fastest, Some(Protocol::Udp));
+    }
+
+    #[test]
+    fn test_dnssec_ab_result() {
+        let on = [Duration::from_millis(30), Duration::from_millis(34)];
+        let off = [Duration::from_millis(20)];
+        let ab = DnssecAbResult::from_timings(2, &on, &off);
+
+        assert_eq!(ab.validated, Some(Duration::from_millis(32)));
+        assert_eq!(ab.unvalidated_successful, 1);
+        assert!((ab.overhead_ms().unwrap() - 12.0).abs() < 1e-9);
+        assert_eq!(DnssecAbResult::from_timings(2, &on, &[]).overhead_ms(), None);
+    }
+
+    #[test]
+    fn test_negative_cache_result() {
+        let cached = [Duration::from_millis(2), Duration::from_millis(4)];
+        let nx = NegativeCacheResult::from_timings(3, Some(Duration::from_millis(45)), &cached);
+
+        assert_eq!(nx.cached, Some(Duration::from_millis(3)));
+        assert_eq!(nx.cached_successful, 2);
+        assert!((nx.savings_ms().unwrap() - 42.0).abs() < 1e-9);
+        assert_eq!(NegativeCacheResult::from_timings(3, None, &cached).savings_ms(), None);
+    }
+
+    #[test]
+    fn test_page_load_result() {
+        let times = [Duration::from_millis(40), Duration::from_millis(60)];
+        let page_load = PageLoadResult::from_timings(4, &times);
+
+        assert_eq!(page_load.successful, 2);
+        assert_eq!(page_load.success_rate(), 50.0);
+        assert_eq!(page_load.avg_time, Some(Duration::from_millis(50)));
+        assert_eq!(page_load.max_time, Some(Duration::from_millis(60)));
+        assert_eq!(PageLoadResult::from_timings(3, &[]).avg_time, None);
+    }
+
+    fn make_result(name: &str, ip: &str, ok: u32, avg_ms: Option<u64>) -> ServerResult {
+        ServerResult {
+            total_requests: 10,
+            successful_requests: ok,
+            avg_time: avg_ms.map(Duration::from_millis),
+            ..ServerResult::new(&DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin))
+        }
+    }
+
+    fn make_benchmark(servers: Vec<ServerResult>) -> BenchmarkResult {
+        BenchmarkResult {
+            domain: "google.com".to_string(),
+            ..BenchmarkResult::for_test(servers)
+        }
+    }
+
+    #[test]
+    fn test_sort_servers() {
+        let names = |r: &BenchmarkResult| r.servers.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        let mut result = make_benchmark(vec![
+            make_result("Quad9", "9.9.9.9", 10, Some(12)),
+            make_result("Google", "8.8.8.8", 6, Some(20)),
+            make_result("Dead", "10.0.0.1", 0, None),
+            make_result("cloudflare", "1.1.1.1", 9, Some(10)),
+        ]);
+
+        result.sort_servers(SortKey::Avg, false);
+        assert_eq!(names(&result), ["cloudflare", "Quad9", "Google", "Dead"]);
+        result.sort_servers(SortKey::Avg, true);
+        assert_eq!(names(&result), ["Google", "Quad9", "cloudflare", "Dead"]);
+        result.sort_servers(SortKey::Success, false);
+        assert_eq!(names(&result), ["Quad9", "cloudflare", "Google", "Dead"]);
+        result.sort_servers(SortKey::Name, false);
+        assert_eq!(names(&result), ["cloudflare", "Dead", "Google", "Quad9"]);
+
+        // Ranking by speed doesn't depend on the order shown
+        assert_eq!(result.fastest().unwrap().name, "cloudflare");
+        assert_eq!(result.speed_rank(&result.servers[2]), 3);
+        assert_eq!(result.speed_rank(&result.servers[1]), 4);
+
+        result.keep_fastest(2);
+        assert_eq!(names(&result), ["cloudflare", "Quad9"]);
+    }
+
+    #[test]
+    fn test_ranking_weights() {
+        let names = |r: &BenchmarkResult| r.servers.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        let server = |name: &str, times: &[u64], failed: usize| {
+            let mut measurements: Vec<TimingResult> = times
+                .iter()
+                .map(|&ms| TimingResult::Success {
+                    duration: Duration::from_millis(ms),
+                    ip: Some("192.0.2.10".parse().unwrap()),
+                })
+                .collect();
+            measurements.extend((0..failed).map(|_| TimingResult::Failure { error: "timed out".to_string() }));
+            let server = DnsServer::from_ip(name, "10.0.0.1".parse().unwrap(), ServerSource::Builtin);
+            ServerResult::from_measurements(&server, measurements)
+        };
+        // Fastest on average but lossy, fast with a slow tail, slow but steady
+        let mut result = make_benchmark(vec![
+            server("Lossy", &[10, 10, 10], 1),
+            server("Spiky", &[5, 5, 5, 45], 0),
+            server("Steady", &[20, 20, 20, 20], 0),
+            server("Dead", &[], 4),
+        ]);
+        assert_eq!(result.fastest().unwrap().name, "Lossy");
+
+        result.ranking = Ranking { metric: RankMetric::P99, ..Ranking::default() };
+        result.sort_servers(SortKey::Score, false);
+        assert_eq!(names(&result), ["Lossy", "Steady", "Spiky", "Dead"]);
+
+        result.ranking = Ranking { jitter: 1.0, ..Ranking::default() };
+        result.sort_servers(SortKey::Score, true);
+        assert_eq!(names(&result), ["Spiky", "Steady", "Lossy", "Dead"]);
+
+        result.ranking = Ranking { loss: 1000.0, ..Ranking::default() };
+        assert_eq!(result.fastest().unwrap().name, "Spiky");
+        assert_eq!(result.speed_rank(&result.servers[2]), 3);
+        assert_eq!(result.servers[0].score(&result.ranking).map(f64::round), Some(15.0));
+        assert_eq!(result.servers[3].score(&result.ranking), None);
+
+        // Below the minimum success rate, a server is ranked behind the rest
+        result.ranking = Ranking { min_success: Some(99.0), ..Ranking::default() };
+        assert_eq!(result.fastest().unwrap().name, "Spiky");
+        result.sort_servers(SortKey::Score, false);
+        assert_eq!(names(&result), ["Spiky", "Steady", "Lossy", "Dead"]);
+        assert_eq!(result.speed_rank(&result.servers[2]), 3);
+        assert_eq!(result.ineligible().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["Lossy"]);
+    }
+
+    #[test]
+    fn test_vpn_bypass_not_fastest() {
+        let route = |interface: &str, verdict| {
+            Some(VpnRouteResult {
+                interface: interface.to_string(),
+                verdict,
+            })
+        };
+        let mut result = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(10)),
+            make_result("VPN DNS (tun0)", "10.8.0.1", 10, Some(30)),
+            make_result("System DNS (Primary)", "127.0.0.53", 10, Some(20)),
+        ]);
+        assert_eq!(result.fastest().unwrap().name, "Cloudflare");
+
+        result.servers[0].vpn_route = route("wlan0", VpnRoute::Bypass);
+        result.servers[1].vpn_route = route("tun0", VpnRoute::Tunnel);
+        result.servers[2].vpn_route = route("lo", VpnRoute::Local);
+        assert!(result.servers[0].bypasses_vpn() && !result.servers[2].bypasses_vpn());
+        assert_eq!(result.fastest().unwrap().name, "System DNS (Primary)");
+
+        let serialized = SerializableResult::from(&result.servers[0]);
+        assert_eq!(serialized.vpn_interface.as_deref(), Some("wlan0"));
+        assert_eq!(serialized.vpn_route, Some(VpnRoute::Bypass));
+    }
+
+    #[test]
+    fn test_trend() {
+        let first = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(10)),
+            make_result("Dead", "10.0.0.1", 0, None),
+        ]);
+        let mut second = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(14)),
+            make_result("Dead", "10.0.0.1", 10, Some(30)),
+            make_result("Google", "8.8.8.8", 10, Some(20)),
+        ]);
+        assert!(second.trend_ms(&second.servers[0]).is_none());
+
+        second.set_previous(&first);
+        assert!((second.trend_ms(&second.servers[0]).unwrap() - 4.0).abs() < 1e-9);
+        assert!(second.trend_ms(&second.servers[1]).is_none());
+        assert!(second.trend_ms(&second.servers[2]).is_none());
+    }
+
+    #[test]
+    fn test_normalized() {
+        let mut result = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(10)),
+            make_result("Google", "8.8.8.8", 10, Some(25)),
+            make_result("Dead", "10.0.0.1", 0, None),
+        ]);
+        assert!(result.normalized(&result.servers[1]).is_none());
+
+        result.reference = Some("1.1.1.1".parse().unwrap());
+        let google = result.normalized(&result.servers[1]).unwrap();
+        assert!((google.ratio - 2.5).abs() < 1e-9);
+        assert!((google.delta_ms - 15.0).abs() < 1e-9);
+
+        let own = result.normalized(&result.servers[0]).unwrap();
+        assert!((own.ratio - 1.0).abs() < 1e-9);
+        assert!(result.normalized(&result.servers[2]).is_none());
+    }
+
+    #[test]
+    fn test_by_provider() {
+        let result = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(10)),
+            make_result("Google", "8.8.8.8", 10, Some(20)),
+            make_result("Cloudflare", "1.0.0.1", 5, Some(40)),
+            make_result("Google", "8.8.4.4", 0, None),
+        ]);
+
+        let providers = result.by_provider();
+        assert_eq!(providers.len(), 2);
+
+        assert_eq!(providers[0].name, "Cloudflare");
+        assert_eq!(providers[0].ips.len(), 2);
+        assert_eq!(providers[0].best_time, Some(Duration::from_millis(10)));
+        assert_eq!(providers[0].avg_time, Some(Duration::from_millis(20)));
+        assert_eq!(providers[0].success_rate(), 75.0);
+
+        assert_eq!(providers[1].name, "Google");
+        assert_eq!(providers[1].best_time, Some(Duration::from_millis(20)));
+        assert_eq!(providers[1].avg_time, Some(Duration::from_millis(20)));
+        assert_eq!(providers[1].success_rate(), 50.0);
+    }
+
+    #[test]
+    fn test_dual_stack() {
+        let result = make_benchmark(vec![
+            make_result("Cloudflare", "1.1.1.1", 10, Some(10)),
+            make_result("Cloudflare", "2606:4700:4700::1111", 10, Some(14)),
+            make_result("Google", "8.8.8.8", 10, Some(20)),
+            make_result("Google", "2001:4860:4860::8888", 10, Some(15)),
+            make_result("Quad9", "9.9.9.9", 10, Some(30)),
+        ]);
+
+        let pairs = result.dual_stack();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].name, "Cloudflare");
+        assert!((pairs[0].delta_ms().unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(pairs[1].name, "Google");
+        assert!((pairs[1].delta_ms().unwrap() + 5.0).abs() < 1e-9);
     }
 
     #[test]
@@ -264,7 +2372,7 @@ mod tests {
         let other = TimingResult::Failure { error: "network error".to_string() };
         let success = TimingResult::Success {
             duration: Duration::from_millis(10),
-            ip: "1.2.3.4".parse().unwrap(),
+            ip: Some("1.2.3.4".parse().unwrap()),
         };
 
         assert!(timeout.is_timeout());
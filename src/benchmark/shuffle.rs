@@ -0,0 +1,65 @@
+//! Seeded shuffling of the order servers are measured in.
+//!
+//! Servers sharing an upstream warm each other's caches, so whichever is
+//! queried first can look slower than it is. Shuffling spreads that over the
+//! servers, and the seed makes any shuffled order reproducible.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Random seed for runs that shuffle without one given
+pub fn random_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// SplitMix64 generator: small, fast, and the same sequence on every platform
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Generator starting from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Shuffle `items` in place (Fisher-Yates)
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_shuffle() {
+        let shuffled = |seed| {
+            let mut items: Vec<u32> = (0..10).collect();
+            SeededRng::new(seed).shuffle(&mut items);
+            items
+        };
+
+        // Same seed, same order, on every platform
+        assert_eq!(shuffled(42), shuffled(42));
+        assert_eq!(shuffled(42), [0, 9, 5, 8, 6, 4, 7, 2, 1, 3]);
+        assert_ne!(shuffled(42), shuffled(43));
+
+        let mut sorted = shuffled(random_seed());
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+}
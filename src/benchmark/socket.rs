@@ -0,0 +1,219 @@
+//! OS socket options from the `[socket]` config section.
+//!
+//! Every socket used for queries is created here, both by the hickory
+//! resolvers (through [`SocketRuntimeProvider`]) and by the raw query pool.
+
+use crate::config::SocketOptions;
+use crate::error::ConfigError;
+use hickory_resolver::Resolver;
+use hickory_resolver::name_server::GenericConnector;
+use hickory_resolver::proto::runtime::iocompat::AsyncIoTokioAsStd;
+use hickory_resolver::proto::runtime::{RuntimeProvider, TokioHandle, TokioTime};
+use socket2::{Domain, Socket, Type};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpSocket, TcpStream, UdpSocket};
+
+/// TCP connect timeout when the resolver doesn't pass one (hickory's default)
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolver whose sockets carry the configured options
+pub type BenchResolver = Resolver<GenericConnector<SocketRuntimeProvider>>;
+
+/// Tokio runtime provider that applies [`SocketOptions`] to new sockets
+#[derive(Clone, Default)]
+pub struct SocketRuntimeProvider {
+    handle: TokioHandle,
+    options: Arc<SocketOptions>,
+}
+
+impl SocketRuntimeProvider {
+    pub fn new(options: SocketOptions) -> Self {
+        Self {
+            handle: TokioHandle::default(),
+            options: Arc::new(options),
+        }
+    }
+}
+
+impl RuntimeProvider for SocketRuntimeProvider {
+    type Handle = TokioHandle;
+    type Timer = TokioTime;
+    type Udp = UdpSocket;
+    type Tcp = AsyncIoTokioAsStd<TcpStream>;
+
+    fn create_handle(&self) -> Self::Handle {
+        self.handle.clone()
+    }
+
+    fn connect_tcp(
+        &self,
+        server_addr: SocketAddr,
+        bind_addr: Option<SocketAddr>,
+        timeout: Option<Duration>,
+    ) -> Pin<Box<dyn Send + Future<Output = io::Result<Self::Tcp>>>> {
+        let options = Arc::clone(&self.options);
+        Box::pin(async move {
+            let stream = connect_tcp(
+                server_addr,
+                bind_addr,
+                timeout.unwrap_or(CONNECT_TIMEOUT),
+                &options,
+            )
+            .await?;
+            Ok(AsyncIoTokioAsStd(stream))
+        })
+    }
+
+    fn bind_udp(
+        &self,
+        local_addr: SocketAddr,
+        _server_addr: SocketAddr,
+    ) -> Pin<Box<dyn Send + Future<Output = io::Result<Self::Udp>>>> {
+        let result = bind_udp(local_addr, &self.options);
+        Box::pin(async move { result })
+    }
+}
+
+/// Bind a UDP socket with the configured options
+pub fn bind_udp(local_addr: SocketAddr, options: &SocketOptions) -> io::Result<UdpSocket> {
+    let socket = udp_socket(local_addr, options)?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// Create and bind a blocking UDP socket with the configured options
+fn udp_socket(local_addr: SocketAddr, options: &SocketOptions) -> io::Result<Socket> {
+    let socket = Socket::new(Domain::for_address(local_addr), Type::DGRAM, None)?;
+    apply(&socket, local_addr, options)?;
+    socket.bind(&local_addr.into())?;
+    Ok(socket)
+}
+
+/// Open a TCP connection with the configured options
+pub async fn connect_tcp(
+    server_addr: SocketAddr,
+    bind_addr: Option<SocketAddr>,
+    timeout: Duration,
+    options: &SocketOptions,
+) -> io::Result<TcpStream> {
+    let socket = Socket::new(Domain::for_address(server_addr), Type::STREAM, None)?;
+    apply(&socket, server_addr, options)?;
+    socket.set_nonblocking(true)?;
+    let socket = TcpSocket::from_std_stream(socket.into());
+
+    if let Some(bind_addr) = bind_addr {
+        socket.bind(bind_addr)?;
+    }
+    socket.set_nodelay(true)?;
+
+    match tokio::time::timeout(timeout, socket.connect(server_addr)).await {
+        Ok(result) => result,
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("connection to {server_addr} timed out after {timeout:?}"),
+        )),
+    }
+}
+
+/// Apply the options to a socket of the family of `addr`
+fn apply(socket: &Socket, addr: SocketAddr, options: &SocketOptions) -> io::Result<()> {
+    if let Some(size) = options.recv_buffer {
+        socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = options.send_buffer {
+        socket.set_send_buffer_size(size)?;
+    }
+    if let Some(tos) = options.tos {
+        set_tos(socket, addr, tos)?;
+    }
+    if let Some(ref device) = options.bind_device {
+        bind_device(socket, device)?;
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux", target_os = "macos"))]
+fn set_tos(socket: &Socket, addr: SocketAddr, tos: u8) -> io::Result<()> {
+    if addr.is_ipv4() {
+        socket.set_tos_v4(u32::from(tos))
+    } else {
+        socket.set_tclass_v6(u32::from(tos))
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux", target_os = "macos")))]
+fn set_tos(socket: &Socket, addr: SocketAddr, tos: u8) -> io::Result<()> {
+    if addr.is_ipv4() {
+        socket.set_tos_v4(u32::from(tos))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "tos is not supported for IPv6 on this platform",
+        ))
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn bind_device(socket: &Socket, device: &str) -> io::Result<()> {
+    socket.bind_device(Some(device.as_bytes()))
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn bind_device(_socket: &Socket, _device: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "bind_device is only supported on Linux",
+    ))
+}
+
+/// Check that the OS accepts the options before starting a run
+///
+/// Binding to a device usually needs elevated privileges, and some options
+/// are platform-specific; failing here beats every query failing later.
+pub fn check_socket_options(options: &SocketOptions) -> Result<(), ConfigError> {
+    if options.is_default() {
+        return Ok(());
+    }
+    udp_socket(([0, 0, 0, 0], 0).into(), options)
+        .map(drop)
+        .map_err(|e| ConfigError::InvalidValue {
+            key: "socket".to_string(),
+            message: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socket2::SockRef;
+
+    #[tokio::test]
+    async fn test_bind_udp_applies_buffer_sizes() {
+        let options = SocketOptions {
+            recv_buffer: Some(256 * 1024),
+            send_buffer: Some(128 * 1024),
+            ..Default::default()
+        };
+        let socket = bind_udp(([127, 0, 0, 1], 0).into(), &options).unwrap();
+
+        // The kernel may round or double the requested size
+        let sock = SockRef::from(&socket);
+        assert!(sock.recv_buffer_size().unwrap() >= 256 * 1024);
+        assert!(sock.send_buffer_size().unwrap() >= 128 * 1024);
+    }
+
+    #[test]
+    fn test_check_socket_options_rejects_unknown_device() {
+        let options = SocketOptions {
+            bind_device: Some("no-such-if0".to_string()),
+            ..Default::default()
+        };
+        assert!(check_socket_options(&options).is_err());
+        assert!(check_socket_options(&SocketOptions::default()).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+//! Split-horizon check of internal-only names.
+
+use super::resolver::create_resolver;
+use super::result::SplitHorizonResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+
+/// Resolve each of `config.internal_domains` once through a server
+///
+/// A name counts as answered when the server returns at least one address.
+/// Whether answering is right depends on the server, see
+/// [`SplitHorizonResult::verdict`]. DoT and DoH servers are skipped
+/// (`None`), as the resolver can't query them.
+pub async fn check_internal_domains(
+    server: &DnsServer,
+    config: &Config,
+    on_query: &(dyn Fn() + Sync),
+) -> Option<SplitHorizonResult> {
+    let Ok(protocol) = server.protocol.unwrap_or(config.protocol).try_into() else {
+        config.internal_domains.iter().for_each(|_| on_query());
+        return None;
+    };
+    let resolver = create_resolver(
+        server.addr,
+        protocol,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    );
+
+    let mut answered = Vec::new();
+    for name in &config.internal_domains {
+        // Fully qualified, so a search list can't turn a miss into an answer
+        let fqdn = format!("{}.", name.trim_end_matches('.'));
+        if resolver.lookup_ip(fqdn.as_str()).await.is_ok_and(|lookup| lookup.iter().next().is_some()) {
+            answered.push(name.clone());
+        }
+
+        on_query();
+    }
+
+    Some(SplitHorizonResult {
+        checked: config.internal_domains.len() as u32,
+        answered,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::{ChaosRates, ChaosServer};
+    use crate::dns::{Protocol, ServerSource};
+
+    #[tokio::test]
+    async fn test_check_internal_domains() {
+        // The mock answers every name, like a resolver that knows the internal zone
+        let chaos = ChaosServer::start(None, ChaosRates::NONE).await.unwrap();
+        let server = DnsServer::new("Mock".to_string(), chaos.addr(), ServerSource::System);
+        let config = Config::builder()
+            .timeout(2)
+            .internal_domains(vec!["intranet.corp.example".into(), "wiki.corp.example".into()])
+            .build();
+
+        let result = check_internal_domains(&server, &config, &|| {}).await.unwrap();
+        assert_eq!(result.checked, 2);
+        assert_eq!(result.answered, config.internal_domains);
+
+        // The resolver can't query encrypted servers
+        let dot = DnsServer {
+            protocol: Some(Protocol::Dot),
+            ..server
+        };
+        assert!(check_internal_domains(&dot, &config, &|| {}).await.is_none());
+    }
+}
@@ -0,0 +1,335 @@
+//! Hand-built queries over DNS-over-TLS and DNS-over-HTTPS.
+//!
+//! The resolver is built without TLS support, so encrypted queries are sent
+//! here instead: a [`RawQuery`] goes out over a fresh TLS connection, either
+//! length-prefixed as on plain TCP (DoT, RFC 7858) or as the body of an
+//! HTTP/1.1 POST to `/dns-query` (DoH, RFC 8484). Like plain TCP queries,
+//! every query opens its own connection, so the elapsed time includes the
+//! TCP and TLS handshakes a client pays when it has no connection to reuse.
+//!
+//! rustls is driven by hand over a Tokio stream rather than through an async
+//! TLS crate; DNS exchanges are small enough that buffering whole records is
+//! fine.
+
+use super::message::{RawQuery, RawResponse, DNS_MESSAGE};
+use super::raw::random_id;
+use super::socket::connect_tcp;
+use crate::config::SocketOptions;
+use crate::dns::{DnsServer, Protocol};
+use crate::error::DnsError;
+use hickory_resolver::proto::op::Message;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// TLS settings for DoT; no ALPN protocol, since some servers reject `dot`
+static DOT_CONFIG: LazyLock<Arc<ClientConfig>> = LazyLock::new(|| client_config(None));
+
+/// TLS settings for DoH, advertising HTTP/1.1
+static DOH_CONFIG: LazyLock<Arc<ClientConfig>> = LazyLock::new(|| client_config(Some(b"http/1.1")));
+
+/// Address an encrypted query to `server` goes to
+///
+/// Server lists give plain DNS addresses, so port 53 is swapped for the
+/// protocol's own port; any other port is kept.
+pub fn encrypted_addr(server: &DnsServer, protocol: Protocol) -> SocketAddr {
+    match server.addr.port() {
+        53 => SocketAddr::new(server.ip(), protocol.default_port()),
+        _ => server.addr,
+    }
+}
+
+/// Send a query over DNS-over-TLS on a fresh connection
+///
+/// The certificate is checked against `server.tls_name`, or the server's IP
+/// address when it has none.
+pub async fn raw_dot_query(
+    server: &DnsServer,
+    query: RawQuery<'_>,
+    timeout: Duration,
+    options: &SocketOptions,
+) -> Result<RawResponse, DnsError> {
+    let id = random_id();
+    let bytes = encode(query, id)?;
+    let addr = encrypted_addr(server, Protocol::Dot);
+    let name = server_name(server)?;
+
+    let start = Instant::now();
+    let exchange = async {
+        let mut stream = TlsStream::connect(addr, name, &DOT_CONFIG, timeout, options).await?;
+
+        // Messages are prefixed with their length, as over plain TCP
+        let mut framed = Vec::with_capacity(bytes.len() + 2);
+        framed.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        framed.extend_from_slice(&bytes);
+        stream.write_all(&framed).await?;
+
+        let mut len = [0u8; 2];
+        stream.read_exact(&mut len).await?;
+        let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+        stream.read_exact(&mut buf).await?;
+        Ok::<_, io::Error>(buf)
+    };
+    let buf = run_exchange(exchange, timeout).await?;
+    decode(&buf, id, start.elapsed())
+}
+
+/// Send a query over DNS-over-HTTPS on a fresh connection
+///
+/// The request goes to `https://<tls name or IP>/dns-query`, with the
+/// certificate checked like for DoT.
+pub async fn raw_doh_query(
+    server: &DnsServer,
+    query: RawQuery<'_>,
+    timeout: Duration,
+    options: &SocketOptions,
+) -> Result<RawResponse, DnsError> {
+    let id = random_id();
+    let bytes = encode(query, id)?;
+    let addr = encrypted_addr(server, Protocol::Doh);
+    let name = server_name(server)?;
+
+    let host = match (&server.tls_name, addr.ip()) {
+        (Some(name), _) => name.clone(),
+        (None, IpAddr::V4(ip)) => ip.to_string(),
+        (None, IpAddr::V6(ip)) => format!("[{ip}]"),
+    };
+    let host = match addr.port() {
+        443 => host,
+        port => format!("{host}:{port}"),
+    };
+    let mut request = format!(
+        "POST /dns-query HTTP/1.1\r\nHost: {host}\r\nAccept: {DNS_MESSAGE}\r\nContent-Type: {DNS_MESSAGE}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        bytes.len()
+    )
+    .into_bytes();
+    request.extend_from_slice(&bytes);
+
+    let start = Instant::now();
+    let exchange = async {
+        let mut stream = TlsStream::connect(addr, name, &DOH_CONFIG, timeout, options).await?;
+        stream.write_all(&request).await?;
+        stream.read_http_body().await
+    };
+    let buf = run_exchange(exchange, timeout).await?;
+    decode(&buf, id, start.elapsed())
+}
+
+/// Build the TLS settings shared by every connection of a protocol
+fn client_config(alpn: Option<&[u8]>) -> Arc<ClientConfig> {
+    let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let mut config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = alpn.into_iter().map(<[u8]>::to_vec).collect();
+    Arc::new(config)
+}
+
+/// Name the server's certificate must be valid for
+fn server_name(server: &DnsServer) -> Result<ServerName<'static>, DnsError> {
+    match server.tls_name {
+        Some(ref name) => ServerName::try_from(name.clone())
+            .map_err(|_| DnsError::InvalidAddress(format!("invalid TLS name: {name}"))),
+        None => Ok(ServerName::IpAddress(server.ip().into())),
+    }
+}
+
+fn encode(query: RawQuery<'_>, id: u16) -> Result<Vec<u8>, DnsError> {
+    query
+        .to_message(id)?
+        .to_vec()
+        .map_err(|e| DnsError::ResolutionFailed(e.to_string()))
+}
+
+fn decode(buf: &[u8], id: u16, elapsed: Duration) -> Result<RawResponse, DnsError> {
+    let message = Message::from_vec(buf).map_err(|e| DnsError::ResolutionFailed(e.to_string()))?;
+    if message.id() != id {
+        return Err(DnsError::ResolutionFailed("response ID does not match the query".to_string()));
+    }
+    Ok(RawResponse { message, elapsed })
+}
+
+async fn run_exchange(
+    exchange: impl Future<Output = io::Result<Vec<u8>>>,
+    timeout: Duration,
+) -> Result<Vec<u8>, DnsError> {
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| DnsError::Timeout)?
+        .map_err(|e| DnsError::ResolutionFailed(e.to_string()))
+}
+
+/// Client TLS session over a TCP connection
+struct TlsStream {
+    tcp: TcpStream,
+    tls: ClientConnection,
+}
+
+impl TlsStream {
+    /// Connect and complete the handshake
+    async fn connect(
+        addr: SocketAddr,
+        name: ServerName<'static>,
+        config: &Arc<ClientConfig>,
+        timeout: Duration,
+        options: &SocketOptions,
+    ) -> io::Result<Self> {
+        let tcp = connect_tcp(addr, None, timeout, options).await?;
+        let tls = ClientConnection::new(Arc::clone(config), name).map_err(io::Error::other)?;
+        let mut stream = Self { tcp, tls };
+        while stream.tls.is_handshaking() {
+            stream.flush().await?;
+            if stream.tls.is_handshaking() {
+                stream.fill().await?;
+            }
+        }
+        Ok(stream)
+    }
+
+    /// Send pending TLS records
+    async fn flush(&mut self) -> io::Result<()> {
+        let mut records = Vec::new();
+        while self.tls.wants_write() {
+            self.tls.write_tls(&mut records)?;
+        }
+        self.tcp.write_all(&records).await
+    }
+
+    /// Receive TLS records; fails once the peer closes the connection
+    async fn fill(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 16 * 1024];
+        let n = self.tcp.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut records = &buf[..n];
+        while !records.is_empty() {
+            self.tls.read_tls(&mut records)?;
+            self.tls.process_new_packets().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.tls.writer().write_all(data)?;
+        self.flush().await
+    }
+
+    /// Read some plaintext into `buf`, returning how much; 0 once the peer is done
+    async fn read_some(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.tls.reader().read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => match self.fill().await {
+                    // Servers often close without a TLS close_notify
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                    result => result?,
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.read_some(&mut buf[filled..]).await? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => filled += n,
+            }
+        }
+        Ok(())
+    }
+
+    /// Read an HTTP/1.1 response and return its body, failing unless the status is 200
+    async fn read_http_body(&mut self) -> io::Result<Vec<u8>> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let mut done = false;
+        let header_end = loop {
+            if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if done {
+                return Err(invalid("incomplete HTTP response"));
+            }
+            let n = self.read_some(&mut buf).await?;
+            data.extend_from_slice(&buf[..n]);
+            done = n == 0;
+        };
+
+        let head = String::from_utf8_lossy(&data[..header_end]).to_ascii_lowercase();
+        let status = head.lines().next().and_then(|l| l.split_whitespace().nth(1)).unwrap_or_default();
+        if status != "200" {
+            return Err(invalid(&format!("HTTP status {status}")));
+        }
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|l| l.strip_prefix(name)?.strip_prefix(':').map(str::trim))
+                .map(str::to_string)
+        };
+        let length = header("content-length").and_then(|l| l.parse::<usize>().ok());
+        let chunked = header("transfer-encoding").is_some_and(|t| t.contains("chunked"));
+
+        // Read until the body is complete, or the server closes the connection
+        while !done && length.is_none_or(|length| data.len() < header_end + length) {
+            let n = self.read_some(&mut buf).await?;
+            data.extend_from_slice(&buf[..n]);
+            done = n == 0;
+        }
+
+        let body = &data[header_end..];
+        match length {
+            Some(length) if body.len() < length => Err(invalid("truncated HTTP response")),
+            Some(length) => Ok(body[..length].to_vec()),
+            None if chunked => dechunk(body).ok_or_else(|| invalid("invalid chunked HTTP response")),
+            None => Ok(body.to_vec()),
+        }
+    }
+}
+
+/// Join the chunks of a chunked HTTP body
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut joined = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(joined);
+        }
+        let chunk = body.get(line_end + 2..line_end + 2 + size)?;
+        joined.extend_from_slice(chunk);
+        body = body.get(line_end + 4 + size..)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::ServerSource;
+
+    #[test]
+    fn test_encrypted_addr() {
+        let mut server = DnsServer::from_ip("Cloudflare", "1.1.1.1".parse().unwrap(), ServerSource::Builtin);
+        assert_eq!(encrypted_addr(&server, Protocol::Dot).port(), 853);
+        assert_eq!(encrypted_addr(&server, Protocol::Doh).port(), 443);
+        server.addr.set_port(8853);
+        assert_eq!(encrypted_addr(&server, Protocol::Dot).port(), 8853);
+    }
+
+    #[test]
+    fn test_dechunk() {
+        assert_eq!(dechunk(b"4\r\nabcd\r\n2;x=y\r\nef\r\n0\r\n\r\n").as_deref(), Some(&b"abcdef"[..]));
+        assert_eq!(dechunk(b"4\r\nab"), None);
+    }
+}
@@ -0,0 +1,363 @@
+//! Iterative resolution from the root (`trace` subcommand).
+//!
+//! Like `dig +trace`, the domain is queried without recursion at a root
+//! server, then at the servers of each zone the answer delegates to, until
+//! a server answers authoritatively. Every query is timed, so the result
+//! shows where resolution time goes for a cold cache.
+
+use super::authoritative::lookup_resolver;
+use super::message::RawQuery;
+use super::raw::{raw_query, raw_tcp_query, SocketPool};
+use super::socket::BenchResolver;
+use super::{TraceHop, TraceOutcome, TraceResult};
+use crate::config::Config;
+use crate::dns::IpVersion;
+use crate::error::{DnsError, Error};
+use hickory_resolver::proto::op::{Message, ResponseCode};
+use hickory_resolver::proto::rr::{Name, RData, RecordType};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// Root server names and addresses, from the IANA root hints
+const ROOT_SERVERS: [(&str, Ipv4Addr, Ipv6Addr); 13] = [
+    ("a.root-servers.net.", Ipv4Addr::new(198, 41, 0, 4), Ipv6Addr::new(0x2001, 0x503, 0xba3e, 0, 0, 0, 0x2, 0x30)),
+    ("b.root-servers.net.", Ipv4Addr::new(170, 247, 170, 2), Ipv6Addr::new(0x2801, 0x1b8, 0x10, 0, 0, 0, 0, 0xb)),
+    ("c.root-servers.net.", Ipv4Addr::new(192, 33, 4, 12), Ipv6Addr::new(0x2001, 0x500, 0x2, 0, 0, 0, 0, 0xc)),
+    ("d.root-servers.net.", Ipv4Addr::new(199, 7, 91, 13), Ipv6Addr::new(0x2001, 0x500, 0x2d, 0, 0, 0, 0, 0xd)),
+    ("e.root-servers.net.", Ipv4Addr::new(192, 203, 230, 10), Ipv6Addr::new(0x2001, 0x500, 0xa8, 0, 0, 0, 0, 0xe)),
+    ("f.root-servers.net.", Ipv4Addr::new(192, 5, 5, 241), Ipv6Addr::new(0x2001, 0x500, 0x2f, 0, 0, 0, 0, 0xf)),
+    ("g.root-servers.net.", Ipv4Addr::new(192, 112, 36, 4), Ipv6Addr::new(0x2001, 0x500, 0x12, 0, 0, 0, 0, 0xd0d)),
+    ("h.root-servers.net.", Ipv4Addr::new(198, 97, 190, 53), Ipv6Addr::new(0x2001, 0x500, 0x1, 0, 0, 0, 0, 0x53)),
+    ("i.root-servers.net.", Ipv4Addr::new(192, 36, 148, 17), Ipv6Addr::new(0x2001, 0x7fe, 0, 0, 0, 0, 0, 0x53)),
+    ("j.root-servers.net.", Ipv4Addr::new(192, 58, 128, 30), Ipv6Addr::new(0x2001, 0x503, 0xc27, 0, 0, 0, 0x2, 0x30)),
+    ("k.root-servers.net.", Ipv4Addr::new(193, 0, 14, 129), Ipv6Addr::new(0x2001, 0x7fd, 0, 0, 0, 0, 0, 0x1)),
+    ("l.root-servers.net.", Ipv4Addr::new(199, 7, 83, 42), Ipv6Addr::new(0x2001, 0x500, 0x9f, 0, 0, 0, 0, 0x42)),
+    ("m.root-servers.net.", Ipv4Addr::new(202, 12, 27, 33), Ipv6Addr::new(0x2001, 0xdc3, 0, 0, 0, 0, 0, 0x35)),
+];
+
+/// Servers tried per zone before the trace gives up
+const MAX_ATTEMPTS: usize = 3;
+
+/// Delegations followed before the trace is considered a referral loop
+const MAX_DEPTH: usize = 16;
+
+/// A name server of the zone being queried
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Candidate {
+    name: String,
+    /// Addresses from glue records; empty for out-of-zone servers
+    ips: Vec<IpAddr>,
+}
+
+/// Trace `config.domain` from the root down to its authoritative servers
+///
+/// The A record is traced, or AAAA with `--ns-ip v6`, which also
+/// selects the address family servers are queried over. Glueless name
+/// servers are resolved through `config.bootstrap` or the system resolver.
+/// A trace that runs out of servers to try is returned incomplete; see
+/// [`TraceResult::completed`].
+pub async fn run_trace(config: &Config) -> Result<TraceResult, Error> {
+    let start = Instant::now();
+    let domain = format!("{}.", config.domain.trim_end_matches('.'));
+    let qname = Name::from_ascii(&domain)
+        .map_err(|e| Error::InvalidArgument(format!("Invalid domain '{}': {e}", config.domain)))?;
+    let record_type = match config.name_server_ip {
+        IpVersion::V6 => RecordType::AAAA,
+        IpVersion::V4 | IpVersion::Both => RecordType::A,
+    };
+    let timeout = Duration::from_millis(config.timeout_ms());
+    let pool = SocketPool::new(1, config.socket.clone());
+
+    let mut hops = Vec::new();
+    let mut glue_resolver = None;
+    let mut zone = Name::root();
+    let mut candidates = root_candidates(config.name_server_ip);
+
+    'zones: for _ in 0..MAX_DEPTH {
+        let offset = random_index(candidates.len());
+        candidates.rotate_left(offset);
+
+        let mut attempts = 0;
+        let mut delegation = None;
+        for candidate in &candidates {
+            if attempts == MAX_ATTEMPTS {
+                break;
+            }
+            let ip = match candidate.ips.first() {
+                Some(&ip) => ip,
+                None => {
+                    if glue_resolver.is_none() {
+                        glue_resolver = Some(lookup_resolver(config).await?);
+                    }
+                    let resolver = glue_resolver.as_ref().expect("created above");
+                    match resolve_name_server(resolver, &candidate.name, config.name_server_ip).await {
+                        Some(ip) => ip,
+                        None => continue,
+                    }
+                }
+            };
+            attempts += 1;
+
+            let (latency, response) = query(&pool, ip, &domain, record_type, timeout, config).await;
+            let (outcome, next) = match response {
+                Ok(message) => classify(&message, &zone, &qname, config.name_server_ip),
+                Err(e) => (TraceOutcome::Failed { error: e.to_string() }, None),
+            };
+            let done = matches!(outcome, TraceOutcome::Answer { .. } | TraceOutcome::Negative { .. });
+            hops.push(TraceHop {
+                zone: zone.to_ascii(),
+                server: candidate.name.clone(),
+                ip,
+                latency_ms: latency.as_secs_f64() * 1000.0,
+                outcome,
+            });
+
+            if done {
+                break 'zones;
+            }
+            if next.is_some() {
+                delegation = next;
+                break;
+            }
+        }
+
+        match delegation {
+            Some((child, servers)) => {
+                zone = child;
+                candidates = servers;
+            }
+            None => break,
+        }
+    }
+
+    Ok(TraceResult {
+        domain,
+        record_type: record_type.to_string(),
+        hops,
+        total_ms: start.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+/// Root servers reachable over the requested address family
+fn root_candidates(ip_version: IpVersion) -> Vec<Candidate> {
+    ROOT_SERVERS
+        .iter()
+        .map(|&(name, v4, v6)| {
+            let ips = match ip_version {
+                IpVersion::V4 => vec![IpAddr::V4(v4)],
+                IpVersion::V6 => vec![IpAddr::V6(v6)],
+                IpVersion::Both => vec![IpAddr::V4(v4), IpAddr::V6(v6)],
+            };
+            Candidate {
+                name: name.to_string(),
+                ips,
+            }
+        })
+        .collect()
+}
+
+/// Send one non-recursive query, retrying over TCP if the answer was truncated
+async fn query(
+    pool: &SocketPool,
+    ip: IpAddr,
+    domain: &str,
+    record_type: RecordType,
+    timeout: Duration,
+    config: &Config,
+) -> (Duration, Result<Message, DnsError>) {
+    let addr = SocketAddr::new(ip, 53);
+    let query = RawQuery {
+        record_type,
+        recursion_desired: false,
+        ..RawQuery::new(domain)
+    };
+
+    let start = Instant::now();
+    let response = match raw_query(pool, addr, query, timeout).await {
+        Ok(udp) if udp.message.truncated() => raw_tcp_query(addr, query, timeout, &config.socket)
+            .await
+            .map(|tcp| (udp.elapsed + tcp.elapsed, tcp.message)),
+        Ok(udp) => Ok((udp.elapsed, udp.message)),
+        Err(e) => Err(e),
+    };
+    match response {
+        Ok((elapsed, message)) => (elapsed, Ok(message)),
+        Err(e) => (start.elapsed(), Err(e)),
+    }
+}
+
+/// Interpret a response to a query for `qname` sent to a server of `zone`
+///
+/// Referrals must delegate to a zone below `zone` that contains `qname`;
+/// anything else would loop or leave the name's tree. The returned
+/// candidates are the child zone's name servers with their glue addresses.
+fn classify(
+    message: &Message,
+    zone: &Name,
+    qname: &Name,
+    ip_version: IpVersion,
+) -> (TraceOutcome, Option<(Name, Vec<Candidate>)>) {
+    match message.response_code() {
+        ResponseCode::NoError => {}
+        ResponseCode::NXDomain => return (TraceOutcome::Negative { rcode: "NXDOMAIN".to_string() }, None),
+        rcode => return (TraceOutcome::Failed { error: rcode.to_string() }, None),
+    }
+
+    if !message.answers().is_empty() {
+        let records = message
+            .answers()
+            .iter()
+            .map(|r| format!("{} {}", r.record_type(), r.data()))
+            .collect();
+        return (TraceOutcome::Answer { records }, None);
+    }
+
+    let delegations: Vec<(&Name, &Name)> = message
+        .name_servers()
+        .iter()
+        .filter_map(|r| match r.data() {
+            RData::NS(ns) => Some((r.name(), &ns.0)),
+            _ => None,
+        })
+        .collect();
+    let Some(&(child, _)) = delegations.first() else {
+        return (TraceOutcome::Negative { rcode: "NODATA".to_string() }, None);
+    };
+    if child == zone || !zone.zone_of(child) || !child.zone_of(qname) {
+        let error = format!("referral to {} is not below {}", child.to_ascii(), zone.to_ascii());
+        return (TraceOutcome::Failed { error }, None);
+    }
+
+    let candidates: Vec<Candidate> = delegations
+        .iter()
+        .filter(|(owner, _)| *owner == child)
+        .map(|(_, ns)| Candidate {
+            name: ns.to_ascii(),
+            ips: message
+                .additionals()
+                .iter()
+                .filter(|r| r.name() == *ns)
+                .filter_map(|r| r.data().ip_addr())
+                .filter(|ip| matches_ip_version(ip, ip_version))
+                .collect(),
+        })
+        .collect();
+    let outcome = TraceOutcome::Referral {
+        zone: child.to_ascii(),
+        servers: candidates.iter().map(|c| c.name.clone()).collect(),
+    };
+    (outcome, Some((child.clone(), candidates)))
+}
+
+/// Resolve an out-of-zone name server through the lookup resolver
+async fn resolve_name_server(resolver: &BenchResolver, name: &str, ip_version: IpVersion) -> Option<IpAddr> {
+    let lookup = resolver.lookup_ip(name).await.ok()?;
+    lookup.iter().find(|ip| matches_ip_version(ip, ip_version))
+}
+
+fn matches_ip_version(ip: &IpAddr, version: IpVersion) -> bool {
+    match version {
+        IpVersion::V4 => ip.is_ipv4(),
+        IpVersion::V6 => ip.is_ipv6(),
+        IpVersion::Both => true,
+    }
+}
+
+/// Random index below `len`, spreading traces over a zone's servers
+fn random_index(len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    RandomState::new().build_hasher().finish() as usize % len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::{MessageType, Query};
+    use hickory_resolver::proto::rr::rdata::{A, NS, SOA};
+    use hickory_resolver::proto::rr::Record;
+
+    fn name(s: &str) -> Name {
+        Name::from_ascii(s).unwrap()
+    }
+
+    fn response(qname: &Name) -> Message {
+        let mut message = Message::new();
+        message
+            .set_message_type(MessageType::Response)
+            .add_query(Query::query(qname.clone(), RecordType::A));
+        message
+    }
+
+    #[test]
+    fn test_classify_referral() {
+        let qname = name("www.example.com.");
+        let mut message = response(&qname);
+        for ns in ["a.gtld-servers.net.", "b.gtld-servers.net."] {
+            message.add_name_server(Record::from_rdata(name("com."), 172_800, RData::NS(NS(name(ns)))));
+        }
+        message.add_additional(Record::from_rdata(
+            name("a.gtld-servers.net."),
+            172_800,
+            RData::A(A::new(192, 5, 6, 30)),
+        ));
+
+        let (outcome, next) = classify(&message, &Name::root(), &qname, IpVersion::V4);
+        assert_eq!(
+            outcome,
+            TraceOutcome::Referral {
+                zone: "com.".to_string(),
+                servers: vec!["a.gtld-servers.net.".to_string(), "b.gtld-servers.net.".to_string()],
+            }
+        );
+        let (child, candidates) = next.unwrap();
+        assert_eq!(child, name("com."));
+        assert_eq!(candidates[0].ips, ["192.5.6.30".parse::<IpAddr>().unwrap()]);
+        // Glueless servers are resolved when they're tried
+        assert!(candidates[1].ips.is_empty());
+
+        // The same referral from a server of `com.` would loop
+        let (outcome, next) = classify(&message, &name("com."), &qname, IpVersion::V4);
+        assert!(matches!(outcome, TraceOutcome::Failed { .. }));
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_classify_answers() {
+        let qname = name("www.example.com.");
+        let zone = name("example.com.");
+
+        let mut answer = response(&qname);
+        answer.add_answer(Record::from_rdata(qname.clone(), 300, RData::A(A::new(93, 184, 215, 14))));
+        let (outcome, next) = classify(&answer, &zone, &qname, IpVersion::V4);
+        assert_eq!(outcome.to_string(), "A 93.184.215.14");
+        assert!(next.is_none());
+
+        let mut nodata = response(&qname);
+        let soa = SOA::new(name("ns.example.com."), name("admin.example.com."), 1, 7200, 3600, 1_209_600, 300);
+        nodata.add_name_server(Record::from_rdata(zone.clone(), 300, RData::SOA(soa)));
+        let (outcome, _) = classify(&nodata, &zone, &qname, IpVersion::V4);
+        assert_eq!(outcome, TraceOutcome::Negative { rcode: "NODATA".to_string() });
+
+        let mut nxdomain = response(&qname);
+        nxdomain.set_response_code(ResponseCode::NXDomain);
+        let (outcome, _) = classify(&nxdomain, &zone, &qname, IpVersion::V4);
+        assert_eq!(outcome.to_string(), "NXDOMAIN");
+
+        let mut refused = response(&qname);
+        refused.set_response_code(ResponseCode::Refused);
+        let (outcome, _) = classify(&refused, &zone, &qname, IpVersion::V4);
+        assert!(matches!(outcome, TraceOutcome::Failed { .. }));
+    }
+
+    #[test]
+    fn test_root_candidates() {
+        let v6 = root_candidates(IpVersion::V6);
+        assert_eq!(v6.len(), ROOT_SERVERS.len());
+        assert!(v6.iter().all(|c| c.ips.len() == 1 && c.ips[0].is_ipv6()));
+        assert_eq!(root_candidates(IpVersion::Both)[0].ips.len(), 2);
+    }
+}
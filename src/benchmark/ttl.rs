@@ -0,0 +1,57 @@
+//! Authoritative TTL lookup for TTL rewrite detection.
+
+use super::authoritative::zone_name_servers;
+use super::message::RawQuery;
+use super::raw::{raw_query, SocketPool};
+use super::resolver::create_resolver;
+use crate::config::Config;
+use crate::dns::LookupStrategy;
+use hickory_resolver::proto::rr::RecordType;
+use hickory_resolver::proto::xfer::Protocol;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Name servers tried before giving up
+const MAX_NAME_SERVERS: usize = 3;
+
+/// Ask the benchmark domain's authoritative server for its TTL
+///
+/// The zone's name servers are found through `resolver_addr`, walking up
+/// from the domain until a name with NS records is found. Each is then
+/// queried without recursion. Returns `None` if none answered with an
+/// address record, e.g. because the domain is a CNAME into another zone.
+pub async fn authoritative_ttl(config: &Config, resolver_addr: SocketAddr, pool: &SocketPool) -> Option<u32> {
+    let resolver = create_resolver(
+        resolver_addr,
+        Protocol::Udp,
+        config.timeout_ms(),
+        LookupStrategy::from(config.name_server_ip).into(),
+        &config.socket,
+    );
+
+    let domain = config.domain.trim_end_matches('.');
+    let name = format!("{domain}.");
+    let record_type = match config.lookup_ip {
+        LookupStrategy::V6 | LookupStrategy::PreferV6 => RecordType::AAAA,
+        _ => RecordType::A,
+    };
+    let query = RawQuery {
+        record_type,
+        recursion_desired: false,
+        ..RawQuery::new(&name)
+    };
+    let timeout = Duration::from_millis(config.timeout_ms());
+
+    for ns in zone_name_servers(&resolver, domain).await?.into_iter().take(MAX_NAME_SERVERS) {
+        let Some(ip) = resolver.lookup_ip(ns.as_str()).await.ok().and_then(|l| l.iter().next()) else {
+            continue;
+        };
+        if let Ok(response) = raw_query(pool, SocketAddr::new(ip, 53), query, timeout).await
+            && let Some(ttl) = response.answer_ttl()
+        {
+            return Some(ttl);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,38 @@
+//! Answer cross-check against a reference resolver.
+
+use super::resolver::create_resolver;
+use super::result::Correctness;
+use crate::config::Config;
+use crate::dns::{DnsServer, Protocol};
+use std::net::{IpAddr, SocketAddr};
+
+/// Resolve the benchmark domain through the `--verify-against` resolver
+///
+/// Returns `None` when verification is disabled or the reference didn't
+/// answer with any address.
+pub async fn reference_answers(config: &Config) -> Option<Vec<IpAddr>> {
+    let ip = config.verify_against?;
+    resolve_answers(SocketAddr::new(ip, 53), config.protocol, config).await
+}
+
+/// Compare a server's answers for the benchmark domain to the reference's
+pub async fn verify_answers(server: &DnsServer, config: &Config, reference: &[IpAddr]) -> Option<Correctness> {
+    let answers = resolve_answers(server.addr, server.protocol.unwrap_or(config.protocol), config).await?;
+    Correctness::compare(&answers, reference)
+}
+
+/// Resolve the benchmark domain through one server and collect every address
+async fn resolve_answers(addr: SocketAddr, protocol: Protocol, config: &Config) -> Option<Vec<IpAddr>> {
+    // The resolver can't query DoT and DoH servers, which stay unverified
+    let resolver = create_resolver(
+        addr,
+        protocol.try_into().ok()?,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    );
+
+    let lookup = resolver.lookup_ip(config.domain.as_str()).await.ok()?;
+    let answers: Vec<_> = lookup.iter().collect();
+    (!answers.is_empty()).then_some(answers)
+}
@@ -0,0 +1,294 @@
+//! DNS-over-HTTPS benchmark for browsers.
+//!
+//! A web page can't send plain DNS packets, so this benchmark sends RFC 8484
+//! GET requests through the page's (or worker's) `fetch()` instead. Timing
+//! uses `performance.now()`, and timeouts and probe budgets use JS timers, so
+//! it needs neither Tokio nor the filesystem and builds for
+//! `wasm32-unknown-unknown`. Queries are built and answers judged by the
+//! engine's own code, so a server's result means the same either way.
+
+use super::message::{query_plan, raw_query_for, rcode_name, RawResponse, DNS_MESSAGE};
+use super::result::{BenchmarkResult, FailureBreakdown, FailureKind, ServerResult, TimingResult};
+use crate::config::Config;
+use crate::dns::{DnsServer, Protocol};
+use crate::error::DnsError;
+use crate::probe::{Probe, ProbeFuture, ProbeRegistry, ProbeResult};
+use data_encoding::BASE64URL_NOPAD;
+use hickory_proto::op::Message;
+use hickory_proto::rr::RecordType;
+use js_sys::{Promise, Uint8Array};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortSignal, Headers, Performance, RequestCache, RequestInit, Response, Window, WorkerGlobalScope};
+
+/// DoH endpoint of a server: `https://<tls name or IP>/dns-query`
+///
+/// The port is kept only when it isn't the plain DNS default (53), since
+/// server lists rarely give HTTPS ports.
+pub fn doh_url(server: &DnsServer) -> String {
+    let host = match (&server.tls_name, server.ip()) {
+        (Some(name), _) => name.clone(),
+        (None, IpAddr::V4(ip)) => ip.to_string(),
+        (None, IpAddr::V6(ip)) => format!("[{ip}]"),
+    };
+    match server.addr.port() {
+        53 | 443 => format!("https://{host}/dns-query"),
+        port => format!("https://{host}:{port}/dns-query"),
+    }
+}
+
+/// Benchmark `servers` over DNS-over-HTTPS from a browser
+///
+/// Each server gets `config.requests` queries, for the same domains and
+/// record types the engine would send, one at a time in rounds over all
+/// servers so network hiccups hit every server alike. Probes named in
+/// `config.probes` are taken from `probes` and run once measuring is done.
+/// Options that need raw sockets (page loads, DNSSEC and cache checks) are
+/// ignored. Servers must allow cross-origin requests, as the public DoH
+/// services do.
+pub async fn run_doh_benchmark(config: &Config, servers: &[DnsServer], probes: &ProbeRegistry) -> BenchmarkResult {
+    let urls: Vec<_> = servers.iter().map(doh_url).collect();
+    let plan = query_plan(config);
+    let clock = performance();
+    let start = clock.as_ref().map(Performance::now);
+
+    let mut measured: Vec<Measurements> = servers.iter().map(|_| Measurements::default()).collect();
+    for round in 0..config.requests as usize {
+        let (domain, record_type) = match plan.get(round) {
+            Some((domain, record_type)) => (domain.as_str(), *record_type),
+            None => (config.domain.as_str(), None),
+        };
+        for (url, measurements) in urls.iter().zip(&mut measured) {
+            let timing = match clock {
+                Some(ref clock) => measurements.query(clock, url, config, domain, record_type).await,
+                None => TimingResult::Failure {
+                    error: "fetch() is only available in a window or worker".to_string(),
+                },
+            };
+            measurements.timings.push(timing);
+        }
+    }
+
+    let mut results: Vec<_> = servers
+        .iter()
+        .zip(measured)
+        .map(|(server, measurements)| measurements.finish(server))
+        .collect();
+    let probes = probes.select_known(&config.probes);
+    if !probes.is_empty() {
+        for (server, result) in servers.iter().zip(&mut results) {
+            result.probes = run_probes(server, &probes, config).await;
+        }
+    }
+
+    let duration = match (clock, start) {
+        (Some(clock), Some(start)) => millis(clock.now() - start),
+        _ => Duration::ZERO,
+    };
+    BenchmarkResult::ranked(config, results, duration)
+}
+
+/// Queries sent to one server so far
+#[derive(Default)]
+struct Measurements {
+    timings: Vec<TimingResult>,
+    failures: FailureBreakdown,
+    /// Responses by RCODE name
+    rcodes: BTreeMap<String, u32>,
+}
+
+impl Measurements {
+    /// Send one query and time it up to the decoded answer
+    async fn query(
+        &mut self,
+        clock: &Performance,
+        url: &str,
+        config: &Config,
+        domain: &str,
+        record_type: Option<RecordType>,
+    ) -> TimingResult {
+        let name = format!("{}.", domain.trim_end_matches('.'));
+        // The ID is 0, as RFC 8484 recommends for GET requests
+        let query = raw_query_for(config, &name, record_type)
+            .to_message(0)
+            .and_then(|message| message.to_vec().map_err(|e| DnsError::ResolutionFailed(e.to_string())));
+        let url = match query {
+            Ok(query) => format!("{url}?dns={}", BASE64URL_NOPAD.encode(&query)),
+            Err(e) => return self.failure(FailureKind::Other, e.to_string()),
+        };
+        let timeout_ms = u32::try_from(config.timeout_ms()).unwrap_or(u32::MAX);
+
+        let started = clock.now();
+        let body = match fetch_message(&url, timeout_ms).await {
+            Ok(body) => body,
+            Err((kind, error)) => return self.failure(kind, error),
+        };
+        let response = match Message::from_vec(&body) {
+            Ok(message) => RawResponse {
+                message,
+                elapsed: millis(clock.now() - started),
+            },
+            Err(e) => return self.failure(FailureKind::Other, format!("Undecodable DNS response: {e}")),
+        };
+
+        *self.rcodes.entry(rcode_name(response.message.response_code())).or_insert(0) += 1;
+        match response.outcome(record_type) {
+            Ok(ip) => TimingResult::Success {
+                duration: response.elapsed,
+                ip,
+            },
+            Err((kind, error)) => self.failure(kind, error),
+        }
+    }
+
+    /// Count a failed query
+    fn failure(&mut self, kind: FailureKind, error: String) -> TimingResult {
+        self.failures.record(kind);
+        TimingResult::Failure { error }
+    }
+
+    /// Build the server's result
+    fn finish(self, server: &DnsServer) -> ServerResult {
+        let mut result = ServerResult::from_measurements(server, self.timings);
+        result.protocol = Protocol::Doh;
+        result.failures = Some(self.failures);
+        result.rcodes = self.rcodes;
+        result
+    }
+}
+
+/// GET a DNS message
+///
+/// `Accept` is a CORS-safelisted header, so the request needs no preflight
+/// that would add a round trip to the timing. The HTTP cache is bypassed so
+/// every query reaches the server.
+async fn fetch_message(url: &str, timeout_ms: u32) -> Result<Vec<u8>, (FailureKind, String)> {
+    let headers = Headers::new().map_err(js_error)?;
+    headers.set("Accept", DNS_MESSAGE).map_err(js_error)?;
+    let init = RequestInit::new();
+    init.set_method("GET");
+    init.set_headers(&headers);
+    init.set_cache(RequestCache::NoStore);
+    init.set_signal(Some(&AbortSignal::timeout_with_u32(timeout_ms)));
+
+    let global = js_sys::global();
+    let promise: Promise = if let Some(window) = global.dyn_ref::<Window>() {
+        window.fetch_with_str_and_init(url, &init)
+    } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+        worker.fetch_with_str_and_init(url, &init)
+    } else {
+        return Err((FailureKind::Network, "fetch() is only available in a window or worker".to_string()));
+    };
+
+    let response: Response = JsFuture::from(promise).await.map_err(js_error)?.unchecked_into();
+    if !response.ok() {
+        return Err((FailureKind::Network, format!("HTTP status {}", response.status())));
+    }
+    let body = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    Ok(Uint8Array::new(&body).to_vec())
+}
+
+/// Run every probe against a server in turn, each within `config.probe_timeout`
+///
+/// Runs that exceed it are reported as inconclusive, as in the engine.
+async fn run_probes(server: &DnsServer, probes: &[Arc<dyn Probe>], config: &Config) -> BTreeMap<String, ProbeResult> {
+    let budget_ms = i32::try_from(config.probe_timeout.saturating_mul(1000)).unwrap_or(i32::MAX);
+    let mut results = BTreeMap::new();
+    for probe in probes {
+        let result = within(budget_ms, probe.run(server, config))
+            .await
+            .unwrap_or_else(|| ProbeResult::inconclusive(format!("timed out after {}s", config.probe_timeout)));
+        results.insert(probe.name().to_string(), result);
+    }
+    results
+}
+
+/// Wait for `run`, giving up once `timeout_ms` have passed
+async fn within(timeout_ms: i32, mut run: ProbeFuture<'_>) -> Option<ProbeResult> {
+    let mut timer = JsFuture::from(sleep(timeout_ms));
+    std::future::poll_fn(|cx| {
+        if let Poll::Ready(result) = run.as_mut().poll(cx) {
+            return Poll::Ready(Some(result));
+        }
+        Pin::new(&mut timer).poll(cx).map(|_| None)
+    })
+    .await
+}
+
+/// Promise resolved after `ms` milliseconds, on the window's or worker's timer
+fn sleep(ms: i32) -> Promise {
+    Promise::new(&mut |resolve, _| {
+        let global = js_sys::global();
+        let scheduled = if let Some(window) = global.dyn_ref::<Window>() {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+        } else if let Some(worker) = global.dyn_ref::<WorkerGlobalScope>() {
+            worker.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+        } else {
+            Ok(0)
+        };
+        // Without a timer the wait just never ends, like the run it bounds
+        drop(scheduled);
+    })
+}
+
+/// High-resolution clock of the window or worker
+fn performance() -> Option<Performance> {
+    let global = js_sys::global();
+    if let Some(window) = global.dyn_ref::<Window>() {
+        window.performance()
+    } else {
+        global.dyn_ref::<WorkerGlobalScope>().and_then(|worker| worker.performance())
+    }
+}
+
+fn millis(ms: f64) -> Duration {
+    Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+}
+
+/// Describe a rejected promise; aborts from the timeout signal count as timeouts
+fn js_error(value: JsValue) -> (FailureKind, String) {
+    if let Some(error) = value.dyn_ref::<js_sys::Error>() {
+        let name = String::from(error.name());
+        if name == "TimeoutError" || name == "AbortError" {
+            return (FailureKind::Timeout, "Request timed out".to_string());
+        }
+        return (FailureKind::Network, format!("{name}: {}", String::from(error.message())));
+    }
+    (FailureKind::Network, value.as_string().unwrap_or_else(|| format!("{value:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::ServerSource;
+
+    #[test]
+    fn test_doh_url() {
+        let mut server = DnsServer::from_ip("Google", "2001:4860:4860::8888".parse().unwrap(), ServerSource::Builtin);
+        assert_eq!(doh_url(&server), "https://[2001:4860:4860::8888]/dns-query");
+        server.tls_name = Some("dns.google".to_string());
+        assert_eq!(doh_url(&server), "https://dns.google/dns-query");
+        server.addr.set_port(8443);
+        assert_eq!(doh_url(&server), "https://dns.google:8443/dns-query");
+    }
+
+    #[test]
+    fn test_query_encoding() {
+        // The example query of RFC 8484, section 4.1.1, which has no EDNS record
+        let config = Config::builder().domain("www.example.com").build();
+        let mut message = raw_query_for(&config, "www.example.com.", None).to_message(0).unwrap();
+        *message.extensions_mut() = None;
+        assert_eq!(
+            BASE64URL_NOPAD.encode(&message.to_vec().unwrap()),
+            "AAABAAABAAAAAAAAA3d3dwdleGFtcGxlA2NvbQAAAQAB"
+        );
+    }
+}
@@ -1,11 +1,18 @@
 //! Command-line interface definitions.
 
-use crate::config::{ConfigOverrides, TableStyle};
-use crate::dns::{IpVersion, Protocol};
-use crate::output::OutputFormat;
+use crate::benchmark::RegressionScope;
+use crate::config::{ConfigOverrides, ExtraColumn, SortKey, TableStyle};
+use crate::dns::{DomainList, IpVersion, LookupStrategy, Protocol, ServersFormat, Workload};
+use crate::i18n::Lang;
+use crate::output::{ColorMode, ExportTarget, OutputFormat, OutputTarget};
+use crate::scenario::RECORD_TYPES;
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use clap::builder::PossibleValuesParser;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const ABOUT: &str = r#"
 🌐 DNS Benchmark - Find the fastest DNS servers for your location
@@ -20,8 +27,20 @@ EXAMPLES:
     dns-benchmark --requests 100            # Run 100 requests per server
     dns-benchmark --format json             # Output as JSON
     dns-benchmark --custom-servers dns.txt  # Use custom server list
+    dns-benchmark --exclude AdGuard         # Skip servers by name, IP, or CIDR
+    dns-benchmark --repeat 6 --every 5m     # Watch latency drift over half an hour
     dns-benchmark config init               # Create config file
     dns-benchmark config set --workers 8    # Update config
+    dns-benchmark compare old.json new.json # Compare two JSON runs
+    dns-benchmark history list              # List past runs
+    dns-benchmark serve --interval 5m       # Expose Prometheus metrics on :9353
+    dns-benchmark scenario checks.txt       # Run a scripted test scenario
+    dns-benchmark template export eu.toml   # Share your setup as a template
+    dns-benchmark template import eu.toml   # Use a shared template
+    dns-benchmark --probes dnssec,tcp       # Run capability probes
+    dns-benchmark list                      # List built-in servers
+    dns-benchmark probes                    # List available probes
+    dns-benchmark update-servers -c US,DE   # Download public resolvers
 "#;
 
 /// DNS Benchmark CLI
@@ -38,6 +57,18 @@ pub struct Cli {
     #[command(flatten)]
     pub options: BenchOptions,
 
+    /// When to color output (NO_COLOR in the environment counts as never for auto)
+    #[arg(long, value_enum, value_name = "WHEN", global = true)]
+    pub color: Option<CliColor>,
+
+    /// Never color output, same as --color never
+    #[arg(long, global = true, conflicts_with = "color")]
+    pub no_color: bool,
+
+    /// Print diagnostics to stderr: -v resolver setup and detection steps, -vv every query, -vvv library traces
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -61,42 +92,284 @@ pub struct BenchOptions {
     #[arg(short, long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..=60))]
     pub timeout: Option<u64>,
 
+    /// Queries sent to a server per scheduling turn (servers take turns)
+    #[arg(long, value_name = "NUM", value_parser = clap::value_parser!(u16).range(1..=1000))]
+    pub slice: Option<u16>,
+
+    /// Simulate browser page loads (bursts of unique domains) per server
+    #[arg(long, value_name = "NUM", value_parser = clap::value_parser!(u16).range(0..=100))]
+    pub page_loads: Option<u16>,
+
+    /// Also measure each server with and without DNSSEC validation and report the overhead
+    #[arg(long)]
+    pub dnssec_ab: bool,
+
+    /// Also measure NXDOMAIN latency: first miss vs cached negative answers
+    #[arg(long)]
+    pub negative_cache: bool,
+
+    /// Measure timer overhead and scheduling jitter first and report a noise floor
+    #[arg(long)]
+    pub calibrate: bool,
+
+    /// Advertised EDNS UDP payload size; truncated answers are counted and retried over TCP (UDP only)
+    #[arg(long, value_name = "BYTES", value_parser = clap::value_parser!(u16).range(512..=4096))]
+    pub edns_payload: Option<u16>,
+
+    /// Attach an EDNS Client Subnet option to queries (e.g. 203.0.113.0/24); UDP only
+    #[arg(long, value_name = "PREFIX")]
+    pub ecs: Option<IpNet>,
+
+    /// Mix of queries: 'web', weights like A=60,AAAA=30,MX=10, or a TOML/JSON workload file
+    #[arg(long, value_name = "MIX|FILE", value_parser = parse_workload)]
+    pub workload: Option<Workload>,
+
+    /// Domains to take turns querying: popular:N for the top N of a bundled list, or names
+    #[arg(long, value_name = "LIST", conflicts_with = "authoritative")]
+    pub domains: Option<DomainList>,
+
+    /// Measure servers in a random order instead of the order listed
+    #[arg(long)]
+    pub shuffle_servers: bool,
+
+    /// Seed of the server shuffle, to repeat the order of an earlier run (implies --shuffle-servers)
+    #[arg(long, value_name = "NUM")]
+    pub seed: Option<u64>,
+
+    /// Capability probes to run against each server (comma-separated, or 'all')
+    #[arg(long, value_name = "PROBE", value_delimiter = ',')]
+    pub probes: Vec<String>,
+
+    /// Time budget in seconds for each probe run
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..=300))]
+    pub probe_timeout: Option<u64>,
+
     /// DNS protocol to use
     #[arg(short, long, value_enum)]
     pub protocol: Option<CliProtocol>,
 
+    /// Benchmark every server over each of these protocols and compare them (comma-separated)
+    #[arg(long, value_enum, value_name = "PROTOCOL", value_delimiter = ',', conflicts_with = "protocol")]
+    pub protocols: Vec<CliProtocol>,
+
     /// IP version for name servers
     #[arg(long = "ns-ip", value_enum)]
     pub name_server_ip: Option<CliIpVersion>,
 
-    /// IP version for lookups
+    /// Address families requested for lookups
     #[arg(long = "lookup-ip", value_enum)]
-    pub lookup_ip: Option<CliIpVersion>,
+    pub lookup_ip: Option<CliLookupStrategy>,
 
-    /// Output format
-    #[arg(short, long, value_enum)]
-    pub format: Option<CliFormat>,
+    /// Output format: table, json, xml, csv, influx, ndjson, html, markdown, or junit (see `formats`)
+    #[arg(short, long, value_name = "FORMAT")]
+    pub format: Option<String>,
+
+    /// Write results as FORMAT, to FILE if given, e.g. `--output table --output json=results.json` (repeatable)
+    #[arg(long, value_name = "FORMAT[=FILE]", conflicts_with = "format")]
+    pub output: Vec<OutputTarget>,
+
+    /// Write results to this file instead of standard output
+    #[arg(short, long, value_name = "FILE", conflicts_with = "output")]
+    pub output_file: Option<PathBuf>,
 
     /// Table style (for table output)
     #[arg(short, long, value_enum)]
     pub style: Option<CliStyle>,
 
-    /// Path to custom DNS server list file
-    #[arg(long, value_name = "FILE")]
+    /// Rank results by this column
+    #[arg(long, value_enum)]
+    pub sort_by: Option<CliSortKey>,
+
+    /// Rank worst first; servers that never answered stay last
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Only output the N fastest servers
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub top: Option<u32>,
+
+    /// Leave servers that failed every query out of the table
+    #[arg(long)]
+    pub hide_failed: bool,
+
+    /// Redraw the ranked table as servers finish instead of only showing a progress bar
+    #[arg(long)]
+    pub live: bool,
+
+    /// Never pick a server that answers fewer queries than this (e.g. 99%) as the fastest
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+    pub min_success: Option<f64>,
+
+    /// Optional columns to add to the output (comma-separated; sparkline is table only)
+    #[arg(long, value_enum, value_name = "COLUMN", value_delimiter = ',')]
+    pub columns: Vec<CliColumn>,
+
+    /// With --format junit, fail servers whose average latency is above this (ms)
+    #[arg(long, value_name = "MS")]
+    pub junit_max_avg: Option<f64>,
+
+    /// With --format junit, fail servers that answer fewer queries than this (e.g. 99%)
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+    pub junit_min_success: Option<f64>,
+
+    /// Language of table headers, summaries, and error messages
+    #[arg(long, value_enum)]
+    pub lang: Option<CliLang>,
+
+    /// Screen-reader-friendly output: aligned text without box drawing, emoji, spinners, or color
+    #[arg(long)]
+    pub plain: bool,
+
+    /// Print only the results: no config summary, progress bars, or decorative lines
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Path or https:// URL of a custom DNS server list
+    #[arg(long, value_name = "FILE|URL")]
     pub custom_servers: Option<PathBuf>,
 
+    /// Format of the custom server list file
+    #[arg(long, value_enum)]
+    pub servers_format: Option<CliServersFormat>,
+
+    /// Resolver for hostname entries in the custom server list (default: system resolver)
+    #[arg(long, value_name = "IP")]
+    pub bootstrap: Option<IpAddr>,
+
     /// Skip system DNS detection
     #[arg(long)]
     pub skip_system: bool,
 
+    /// Take system DNS servers only from this network adapter (repeatable)
+    #[arg(long, value_name = "NAME", value_delimiter = ',', conflicts_with = "skip_system")]
+    pub adapter: Vec<String>,
+
+    /// Skip the system DNS servers of this network adapter (repeatable)
+    #[arg(long, value_name = "NAME", value_delimiter = ',', conflicts_with = "skip_system")]
+    pub skip_adapter: Vec<String>,
+
+    /// Benchmark the system DNS servers and gateway of every network interface, not just the primary
+    #[arg(long)]
+    pub all_interfaces: bool,
+
+    /// Also benchmark the DNS servers DHCP handed out, even if the system was set to others
+    #[arg(long)]
+    pub dhcp_dns: bool,
+
+    /// Check whether queries to each server would bypass an active VPN tunnel, and benchmark the VPN's DNS servers
+    #[arg(long)]
+    pub vpn_check: bool,
+
     /// Skip gateway DNS detection
     #[arg(long)]
     pub skip_gateway: bool,
 
+    /// Don't record the network fingerprint (gateway, Wi-Fi, public IP prefix)
+    #[arg(long)]
+    pub skip_fingerprint: bool,
+
+    /// Detect system DNS and gateway again instead of reusing a detection from the last few minutes
+    #[arg(long)]
+    pub no_detect_cache: bool,
+
+    /// Don't store this run in the history database
+    #[arg(long)]
+    pub no_history: bool,
+
     /// Disable adaptive timeout optimization
     #[arg(long)]
     pub no_adaptive_timeout: bool,
 
+    /// Only benchmark servers matching a name, IP, or CIDR (repeatable)
+    #[arg(long, value_name = "PATTERN", value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Exclude servers matching a name, IP, or CIDR (repeatable)
+    #[arg(long, value_name = "PATTERN", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+
+    /// Also show results aggregated by provider
+    #[arg(long)]
+    pub by_provider: bool,
+
+    /// Express latencies relative to this server (benchmarked alongside the others)
+    #[arg(long, value_name = "IP")]
+    pub reference: Option<IpAddr>,
+
+    /// Check each server's answers against this resolver and flag divergent or bogus ones
+    #[arg(long, value_name = "IP")]
+    pub verify_against: Option<IpAddr>,
+
+    /// Benchmark the domain's authoritative name servers with non-recursive queries
+    #[arg(long)]
+    pub authoritative: bool,
+
+    /// Resolve the domain as a short name through the system's DNS search list
+    #[arg(long, conflicts_with_all = ["ecs", "edns_payload", "authoritative", "workload", "domains"])]
+    pub search: bool,
+
+    /// Search domain to apply instead of the system's (repeatable, implies --search)
+    #[arg(
+        long,
+        value_name = "DOMAIN",
+        value_delimiter = ',',
+        conflicts_with_all = ["ecs", "edns_payload", "authoritative", "workload", "domains"]
+    )]
+    pub search_domain: Vec<String>,
+
+    /// Internal-only name that public resolvers should fail and system ones answer (repeatable)
+    #[arg(long, value_name = "NAME", value_delimiter = ',')]
+    pub internal_domain: Vec<String>,
+
+    /// Record answer TTLs and flag servers that rewrite the authoritative TTL
+    #[arg(long)]
+    pub ttl_check: bool,
+
+    /// Identify the anycast node answering each server (CHAOS TXT id.server / NSID)
+    #[arg(long)]
+    pub node_id: bool,
+
+    /// Annotate servers and answers with ASN/country from MaxMind DB files (repeatable)
+    #[arg(long, value_name = "MMDB", value_delimiter = ',')]
+    pub geoip: Vec<PathBuf>,
+
+    /// Write the run summary and threshold violations to the Windows Application event log
+    #[arg(long)]
+    pub event_log: bool,
+
+    /// After the run, upload anonymized results to the share endpoint (asks for consent first)
+    #[arg(long)]
+    pub share: bool,
+
+    /// Community endpoint for --share (https:// only)
+    #[arg(long, value_name = "URL")]
+    pub share_endpoint: Option<String>,
+
+    /// Benchmark a local fault-injecting server instead of real resolvers, for test environments
+    /// (answers itself, or forwards to the given lab resolver)
+    #[arg(long, value_name = "UPSTREAM", num_args = 0..=1)]
+    pub chaos: Option<Option<IpAddr>>,
+
+    /// Run the benchmark N times, showing each server's change since the previous run
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub repeat: Option<u32>,
+
+    /// Time to wait between repeated runs, e.g. 30s, 5m, or 1h
+    #[arg(long, value_name = "INTERVAL", requires = "repeat", value_parser = parse_interval)]
+    pub every: Option<Duration>,
+
+    /// Result file (JSON output) to check this run against for regressions
+    #[arg(long, value_name = "FILE", requires = "fail_on_regression")]
+    pub baseline: Option<PathBuf>,
+
+    /// Exit non-zero if a server is more than this much slower than in --baseline (e.g. 20%)
+    #[arg(long, value_name = "PERCENT", requires = "baseline", value_parser = parse_percent)]
+    pub fail_on_regression: Option<f64>,
+
+    /// Servers the regression check covers
+    #[arg(long, value_enum, default_value = "any", requires = "baseline")]
+    pub regression_scope: CliRegressionScope,
+
     /// Save current options to config file
     #[arg(long)]
     pub save_config: bool,
@@ -110,25 +383,425 @@ impl BenchOptions {
             workers: self.workers,
             requests: self.requests,
             timeout: self.timeout,
+            slice: self.slice,
+            page_loads: self.page_loads,
+            dnssec_ab: self.dnssec_ab,
+            negative_cache: self.negative_cache,
+            calibrate: self.calibrate,
+            edns_payload: self.edns_payload,
+            ecs: self.ecs,
+            workload: self.workload.clone(),
+            domains: self.domains.clone(),
+            shuffle_servers: self.shuffle_servers || self.seed.is_some(),
+            seed: self.seed,
+            probes: self.probes.clone(),
+            probe_timeout: self.probe_timeout,
             protocol: self.protocol.map(Into::into),
+            protocols: self.protocols.iter().copied().map(Into::into).collect(),
             name_server_ip: self.name_server_ip.map(Into::into),
             lookup_ip: self.lookup_ip.map(Into::into),
-            format: self.format.map(Into::into),
+            // The output on standard output decides what else is shown there (progress, summary)
+            format: self
+                .format
+                .as_deref()
+                .map(OutputFormat::named)
+                .or_else(|| self.output.iter().find(|t| t.path.is_none()).map(OutputTarget::builtin)),
             style: self.style.map(Into::into),
+            sort_by: self.sort_by.map(Into::into),
+            reverse: self.reverse,
+            top: self.top,
+            hide_failed: self.hide_failed,
+            live: self.live,
+            min_success: self.min_success,
+            columns: self.columns.iter().copied().map(Into::into).collect(),
+            junit_max_avg_ms: self.junit_max_avg,
+            junit_min_success: self.junit_min_success,
+            lang: self.lang.map(Into::into),
+            plain: self.plain,
+            quiet: self.quiet,
             custom_servers: self.custom_servers.clone(),
+            servers_format: self.servers_format.map(Into::into),
+            bootstrap: self.bootstrap,
             skip_system: self.skip_system,
             skip_gateway: self.skip_gateway,
+            skip_fingerprint: self.skip_fingerprint,
+            no_detect_cache: self.no_detect_cache,
+            no_history: self.no_history,
             disable_adaptive_timeout: self.no_adaptive_timeout,
+            only: self.only.clone(),
+            exclude: self.exclude.clone(),
+            by_provider: self.by_provider,
+            reference: self.reference,
+            verify_against: self.verify_against,
+            authoritative: self.authoritative,
+            search: self.search || !self.search_domain.is_empty(),
+            search_domains: self.search_domain.clone(),
+            adapters: self.adapter.clone(),
+            skip_adapters: self.skip_adapter.clone(),
+            all_interfaces: self.all_interfaces,
+            dhcp_dns: self.dhcp_dns,
+            vpn_check: self.vpn_check,
+            internal_domains: self.internal_domain.clone(),
+            ttl_check: self.ttl_check,
+            node_id: self.node_id,
+            geoip: self.geoip.clone(),
+            event_log: self.event_log,
+            share_endpoint: self.share_endpoint.clone(),
         }
     }
 }
 
 /// Subcommands
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once at startup
 pub enum Command {
     /// Configuration management
     #[command(subcommand)]
     Config(ConfigCommand),
+
+    /// Compare two JSON result files
+    Compare(CompareArgs),
+
+    /// Browse past runs stored in the history database
+    #[command(subcommand)]
+    History(HistoryCommand),
+
+    /// Print DNS configuration for the fastest servers of the latest run
+    Export(ExportArgs),
+
+    /// Set the system's DNS servers to the fastest of the latest run (needs root or Administrator)
+    Apply(ApplyArgs),
+
+    /// Run a scripted test scenario against each server
+    Scenario(ScenarioArgs),
+
+    /// Resolve the domain iteratively from the root, timing each delegation hop
+    Trace(TraceArgs),
+
+    /// Send a single timed query and print the answer section
+    Resolve(ResolveArgs),
+
+    /// Benchmark periodically and expose the results as Prometheus metrics
+    Serve(ServeArgs),
+
+    /// Share benchmark setups as template files
+    #[command(subcommand)]
+    Template(TemplateCommand),
+
+    /// List built-in DNS servers with provider metadata
+    List(ListArgs),
+
+    /// List available capability probes
+    Probes,
+
+    /// List available output formats
+    Formats,
+
+    /// Download a public resolver list into the custom servers format
+    UpdateServers(UpdateServersArgs),
+}
+
+/// History subcommands
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// List recent runs, newest first
+    List(HistoryListArgs),
+
+    /// Show the per-server results of a run
+    Show(HistoryShowArgs),
+}
+
+/// Arguments for history list command
+#[derive(Debug, Args)]
+pub struct HistoryListArgs {
+    /// Number of runs to list
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: u32,
+
+    /// Output format (table or json)
+    #[arg(short, long, value_enum)]
+    pub format: Option<CliFormat>,
+
+    /// Table style
+    #[arg(short, long, value_enum)]
+    pub style: Option<CliStyle>,
+}
+
+/// Arguments for history show command
+#[derive(Debug, Args)]
+pub struct HistoryShowArgs {
+    /// Run ID, as printed by `history list`
+    #[arg(value_name = "ID")]
+    pub id: i64,
+
+    /// Output format (table or json; JSON can be passed to `compare`)
+    #[arg(short, long, value_enum)]
+    pub format: Option<CliFormat>,
+
+    /// Table style
+    #[arg(short, long, value_enum)]
+    pub style: Option<CliStyle>,
+}
+
+/// Arguments for export command
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Configuration format to generate
+    #[arg(long, value_enum)]
+    pub target: CliExportTarget,
+
+    /// Number of servers to include, fastest first
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub top: u32,
+
+    /// Run to take the servers from, as printed by `history list` (default: the latest)
+    #[arg(long, value_name = "ID")]
+    pub run: Option<i64>,
+}
+
+/// Arguments for apply command
+#[derive(Debug, Args)]
+pub struct ApplyArgs {
+    /// Number of servers to set, fastest first
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    pub top: u32,
+
+    /// Run to take the servers from, as printed by `history list` (default: the latest)
+    #[arg(long, value_name = "ID", conflicts_with = "revert")]
+    pub run: Option<i64>,
+
+    /// Interface to change (network service on macOS; default on Linux: the one with the default route)
+    #[arg(long, value_name = "NAME")]
+    pub interface: Option<String>,
+
+    /// Print the commands without running them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Restore the DNS settings from before the first apply
+    #[arg(long, conflicts_with = "interface")]
+    pub revert: bool,
+}
+
+/// Arguments for scenario command
+#[derive(Debug, Args)]
+pub struct ScenarioArgs {
+    /// Scenario file (one step per line: query, sleep, burst)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    #[command(flatten)]
+    pub options: BenchOptions,
+}
+
+/// Arguments for trace command
+#[derive(Debug, Args)]
+pub struct TraceArgs {
+    #[command(flatten)]
+    pub options: BenchOptions,
+}
+
+/// Arguments for resolve command
+#[derive(Debug, Args)]
+pub struct ResolveArgs {
+    /// Name to look up
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Server to query as IP or IP:port (defaults to the system resolver)
+    #[arg(long, value_name = "ADDR", value_parser = parse_server_addr)]
+    pub server: Option<SocketAddr>,
+
+    /// Record type to query
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        default_value = "A",
+        ignore_case = true,
+        value_parser = PossibleValuesParser::new(RECORD_TYPES)
+    )]
+    pub record_type: String,
+
+    #[command(flatten)]
+    pub options: BenchOptions,
+}
+
+/// Arguments for serve command
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address to serve metrics on (GET /metrics)
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:9353")]
+    pub listen: SocketAddr,
+
+    /// Time to wait between runs, e.g. 30s, 5m, or 1h
+    #[arg(long, value_name = "INTERVAL", default_value = "5m", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    #[command(flatten)]
+    pub options: BenchOptions,
+}
+
+/// Parse `9.9.9.9`, `9.9.9.9:5353`, `2620:fe::fe`, or `[2620:fe::fe]:5353`
+fn parse_server_addr(s: &str) -> Result<SocketAddr, String> {
+    s.parse::<SocketAddr>()
+        .or_else(|_| s.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| format!("invalid server address '{s}' (expected IP or IP:port)"))
+}
+
+/// Parse `90`, `90s`, `5m`, or `1h` into a duration (plain numbers are seconds)
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid interval '{s}' (expected e.g. 30s, 5m, or 1h)"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("invalid interval unit in '{s}' (expected s, m, or h)")),
+    };
+    let secs = value.checked_mul(unit_secs).ok_or_else(|| format!("interval '{s}' is too long"))?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// Load a workload file, or parse `web` or an inline mix like `A=60,AAAA=40`
+fn parse_workload(s: &str) -> Result<Workload, String> {
+    let path = Path::new(s);
+    let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    if path.is_file() || matches!(extension.as_deref(), Some("toml" | "json")) {
+        Workload::load(path)
+    } else {
+        s.parse()
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Parse `20%` or `20` into a percentage
+fn parse_percent(s: &str) -> Result<f64, String> {
+    let value: f64 = s
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("invalid percentage '{s}' (expected e.g. 20%)"))?;
+    if value.is_finite() && value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("invalid percentage '{s}' (must not be negative)"))
+    }
+}
+
+/// Template subcommands
+#[derive(Debug, Subcommand)]
+pub enum TemplateCommand {
+    /// Bundle the configuration, server list, domain, and probes into a template file
+    Export(Box<TemplateExportArgs>),
+
+    /// Replace the configuration with a template's
+    Import(TemplateImportArgs),
+}
+
+/// Arguments for template export command
+#[derive(Debug, Args)]
+pub struct TemplateExportArgs {
+    /// Template file to write
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// What the template measures, shown on import
+    #[arg(long, value_name = "TEXT")]
+    pub description: Option<String>,
+
+    #[command(flatten)]
+    pub options: BenchOptions,
+}
+
+/// Arguments for template import command
+#[derive(Debug, Args)]
+pub struct TemplateImportArgs {
+    /// Template file to import
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Replace an existing config file
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for list command
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// IP version of the servers to list
+    #[arg(long = "ns-ip", value_enum)]
+    pub name_server_ip: Option<CliIpVersion>,
+
+    /// Output format (table or json)
+    #[arg(short, long, value_enum)]
+    pub format: Option<CliFormat>,
+
+    /// Table style
+    #[arg(short, long, value_enum)]
+    pub style: Option<CliStyle>,
+}
+
+/// Arguments for update-servers command
+#[derive(Debug, Args)]
+pub struct UpdateServersArgs {
+    /// Only keep resolvers in these countries (ISO codes, comma-separated)
+    #[arg(short, long, value_name = "CC", value_delimiter = ',')]
+    pub country: Vec<String>,
+
+    /// Minimum reliability reported by the list (0.0 - 1.0)
+    #[arg(long, value_name = "RATIO", default_value_t = 0.9)]
+    pub min_reliability: f64,
+
+    /// Keep at most this many resolvers (most reliable first)
+    #[arg(long, value_name = "NUM")]
+    pub limit: Option<usize>,
+
+    /// Download a public-dns.info style CSV from this https:// URL instead
+    #[arg(long, value_name = "URL")]
+    pub source: Option<String>,
+
+    /// Output file (default: ~/.dns-benchmark/public-servers.txt)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for compare command
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+    /// Baseline result file (JSON output), or the run to check with --against-region
+    #[arg(value_name = "BASELINE")]
+    pub baseline: PathBuf,
+
+    /// Result file to compare against the baseline
+    #[arg(value_name = "CURRENT", required_unless_present = "against_region")]
+    pub current: Option<PathBuf>,
+
+    /// Compare the run to community statistics for a country instead of another run
+    /// (ISO 3166 code; looked up from the run's public IP prefix with the config's GeoIP databases if omitted)
+    #[arg(long, value_name = "COUNTRY", num_args = 0..=1, default_missing_value = "", conflicts_with = "current")]
+    pub against_region: Option<String>,
+
+    /// Community statistics endpoint for --against-region (https:// only)
+    #[arg(long, value_name = "URL")]
+    pub stats_endpoint: Option<String>,
+
+    /// Compare even if the runs were taken on different networks
+    #[arg(long)]
+    pub force: bool,
+
+    /// Table style
+    #[arg(short, long, value_enum)]
+    pub style: Option<CliStyle>,
 }
 
 /// Config subcommands
@@ -141,7 +814,7 @@ pub enum ConfigCommand {
     Show,
 
     /// Update configuration values
-    Set(ConfigSetArgs),
+    Set(Box<ConfigSetArgs>),
 
     /// Reset configuration to defaults
     Reset,
@@ -166,6 +839,8 @@ pub struct ConfigSetArgs {
 pub enum CliProtocol {
     Udp,
     Tcp,
+    Dot,
+    Doh,
 }
 
 impl From<CliProtocol> for Protocol {
@@ -173,6 +848,8 @@ impl From<CliProtocol> for Protocol {
         match p {
             CliProtocol::Udp => Protocol::Udp,
             CliProtocol::Tcp => Protocol::Tcp,
+            CliProtocol::Dot => Protocol::Dot,
+            CliProtocol::Doh => Protocol::Doh,
         }
     }
 }
@@ -181,6 +858,7 @@ impl From<CliProtocol> for Protocol {
 pub enum CliIpVersion {
     V4,
     V6,
+    Both,
 }
 
 impl From<CliIpVersion> for IpVersion {
@@ -188,6 +866,33 @@ impl From<CliIpVersion> for IpVersion {
         match v {
             CliIpVersion::V4 => IpVersion::V4,
             CliIpVersion::V6 => IpVersion::V6,
+            CliIpVersion::Both => IpVersion::Both,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliLookupStrategy {
+    /// A records only
+    V4,
+    /// AAAA records only
+    V6,
+    /// A and AAAA records in parallel
+    Both,
+    /// A records, falling back to AAAA
+    PreferV4,
+    /// AAAA records, falling back to A
+    PreferV6,
+}
+
+impl From<CliLookupStrategy> for LookupStrategy {
+    fn from(s: CliLookupStrategy) -> Self {
+        match s {
+            CliLookupStrategy::V4 => LookupStrategy::V4,
+            CliLookupStrategy::V6 => LookupStrategy::V6,
+            CliLookupStrategy::Both => LookupStrategy::Both,
+            CliLookupStrategy::PreferV4 => LookupStrategy::PreferV4,
+            CliLookupStrategy::PreferV6 => LookupStrategy::PreferV6,
         }
     }
 }
@@ -198,6 +903,11 @@ pub enum CliFormat {
     Json,
     Xml,
     Csv,
+    Influx,
+    Ndjson,
+    Html,
+    Markdown,
+    Junit,
 }
 
 impl From<CliFormat> for OutputFormat {
@@ -207,6 +917,135 @@ impl From<CliFormat> for OutputFormat {
             CliFormat::Json => OutputFormat::Json,
             CliFormat::Xml => OutputFormat::Xml,
             CliFormat::Csv => OutputFormat::Csv,
+            CliFormat::Influx => OutputFormat::Influx,
+            CliFormat::Ndjson => OutputFormat::Ndjson,
+            CliFormat::Html => OutputFormat::Html,
+            CliFormat::Markdown => OutputFormat::Markdown,
+            CliFormat::Junit => OutputFormat::Junit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliRegressionScope {
+    /// Fail if any server present in both runs regressed
+    Any,
+    /// Fail only if the fastest server regressed
+    Fastest,
+}
+
+impl From<CliRegressionScope> for RegressionScope {
+    fn from(s: CliRegressionScope) -> Self {
+        match s {
+            CliRegressionScope::Any => RegressionScope::Any,
+            CliRegressionScope::Fastest => RegressionScope::Fastest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliServersFormat {
+    Auto,
+    Text,
+    Json,
+    Yaml,
+}
+
+impl From<CliServersFormat> for ServersFormat {
+    fn from(f: CliServersFormat) -> Self {
+        match f {
+            CliServersFormat::Auto => ServersFormat::Auto,
+            CliServersFormat::Text => ServersFormat::Text,
+            CliServersFormat::Json => ServersFormat::Json,
+            CliServersFormat::Yaml => ServersFormat::Yaml,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliSortKey {
+    Score,
+    Avg,
+    Min,
+    Max,
+    P95,
+    Success,
+    Name,
+}
+
+impl From<CliSortKey> for SortKey {
+    fn from(k: CliSortKey) -> Self {
+        match k {
+            CliSortKey::Score => SortKey::Score,
+            CliSortKey::Avg => SortKey::Avg,
+            CliSortKey::Min => SortKey::Min,
+            CliSortKey::Max => SortKey::Max,
+            CliSortKey::P95 => SortKey::P95,
+            CliSortKey::Success => SortKey::Success,
+            CliSortKey::Name => SortKey::Name,
+        }
+    }
+}
+
+impl Cli {
+    /// Color mode requested on the command line, if any
+    pub fn color_mode(&self) -> Option<ColorMode> {
+        if self.no_color { Some(ColorMode::Never) } else { self.color.map(Into::into) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliColor {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<CliColor> for ColorMode {
+    fn from(c: CliColor) -> Self {
+        match c {
+            CliColor::Auto => ColorMode::Auto,
+            CliColor::Always => ColorMode::Always,
+            CliColor::Never => ColorMode::Never,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliExportTarget {
+    #[value(name = "resolv.conf")]
+    ResolvConf,
+    Dnsmasq,
+    Unbound,
+    SystemdResolved,
+}
+
+impl From<CliExportTarget> for ExportTarget {
+    fn from(t: CliExportTarget) -> Self {
+        match t {
+            CliExportTarget::ResolvConf => ExportTarget::ResolvConf,
+            CliExportTarget::Dnsmasq => ExportTarget::Dnsmasq,
+            CliExportTarget::Unbound => ExportTarget::Unbound,
+            CliExportTarget::SystemdResolved => ExportTarget::SystemdResolved,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliColumn {
+    Source,
+    Protocol,
+    Sparkline,
+    Failures,
+}
+
+impl From<CliColumn> for ExtraColumn {
+    fn from(c: CliColumn) -> Self {
+        match c {
+            CliColumn::Source => ExtraColumn::Source,
+            CliColumn::Protocol => ExtraColumn::Protocol,
+            CliColumn::Sparkline => ExtraColumn::Sparkline,
+            CliColumn::Failures => ExtraColumn::Failures,
         }
     }
 }
@@ -247,3 +1086,20 @@ impl From<CliStyle> for TableStyle {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CliLang {
+    En,
+    Es,
+    De,
+}
+
+impl From<CliLang> for Lang {
+    fn from(l: CliLang) -> Self {
+        match l {
+            CliLang::En => Lang::En,
+            CliLang::Es => Lang::Es,
+            CliLang::De => Lang::De,
+        }
+    }
+}
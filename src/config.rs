@@ -1,13 +1,18 @@
 //! Configuration management.
 
-use crate::dns::{IpVersion, Protocol};
+use crate::dns::{DomainList, IpVersion, LookupStrategy, Protocol, ServersFormat, Workload};
 use crate::error::{ConfigError, Error};
+use crate::i18n::Lang;
 use crate::output::OutputFormat;
-use crate::{DEFAULT_DOMAIN, DEFAULT_REQUESTS, DEFAULT_TIMEOUT_SECS, DEFAULT_WORKERS};
+use crate::{
+    DEFAULT_DOMAIN, DEFAULT_PROBE_TIMEOUT_SECS, DEFAULT_REQUESTS, DEFAULT_SLICE, DEFAULT_TIMEOUT_SECS, DEFAULT_WORKERS,
+};
 use directories::UserDirs;
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fs;
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 
 /// Configuration directory name
@@ -16,6 +21,24 @@ const CONFIG_DIR: &str = ".dns-benchmark";
 /// Configuration file name
 const CONFIG_FILE: &str = "config.toml";
 
+/// Cache directory name (inside the configuration directory)
+const CACHE_DIR: &str = "cache";
+
+/// Run history database (inside the configuration directory)
+const HISTORY_FILE: &str = "history.db";
+
+/// DNS settings saved by `apply` for `apply --revert` (inside the configuration directory)
+const DNS_BACKUP_FILE: &str = "dns-backup.json";
+
+/// Salt the network fingerprint is hashed with (inside the configuration directory)
+const FINGERPRINT_SALT_FILE: &str = "fingerprint-salt";
+
+/// Server list written by `update-servers` (inside the configuration directory)
+const PUBLIC_SERVERS_FILE: &str = "public-servers.txt";
+
+/// Server list installed by `template import`, without extension (inside the configuration directory)
+const TEMPLATE_SERVERS_FILE: &str = "template-servers";
+
 /// Application configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
@@ -32,14 +55,68 @@ pub struct Config {
     /// Timeout in seconds
     pub timeout: u64,
 
-    /// DNS protocol (UDP or TCP)
+    /// Queries sent to a server per scheduling turn
+    pub slice: u16,
+
+    /// Simulated browser page loads per server (0 to disable)
+    #[serde(default)]
+    pub page_loads: u16,
+
+    /// Also measure each server with and without DNSSEC validation
+    #[serde(default)]
+    pub dnssec_ab: bool,
+
+    /// Also measure NXDOMAIN latency before and after negative caching
+    #[serde(default)]
+    pub negative_cache: bool,
+
+    /// Calibrate host timing precision before the run
+    #[serde(default)]
+    pub calibrate: bool,
+
+    /// Advertised EDNS UDP payload size (tracks truncation when set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edns_payload: Option<u16>,
+
+    /// EDNS Client Subnet attached to queries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecs: Option<IpNet>,
+
+    /// Mix of record types to query instead of the lookup strategy's A/AAAA
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workload: Option<Workload>,
+
+    /// Domains to take turns querying instead of the benchmark domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domains: Option<DomainList>,
+
+    /// Measure servers in a random order instead of the order listed
+    #[serde(default)]
+    pub shuffle_servers: bool,
+
+    /// Seed of the server shuffle, to repeat an order; random if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Capability probes to run against each server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub probes: Vec<String>,
+
+    /// Time budget in seconds for each probe run
+    pub probe_timeout: u64,
+
+    /// DNS protocol (UDP, TCP, DoT, or DoH)
     pub protocol: Protocol,
 
+    /// Protocols to compare; with two or more, every server is benchmarked over each
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protocols: Vec<Protocol>,
+
     /// IP version for name servers
     pub name_server_ip: IpVersion,
 
-    /// IP version for lookups
-    pub lookup_ip: IpVersion,
+    /// Address families requested for lookups
+    pub lookup_ip: LookupStrategy,
 
     /// Output format
     pub format: OutputFormat,
@@ -47,10 +124,59 @@ pub struct Config {
     /// Table style (for human-readable output)
     pub style: TableStyle,
 
+    /// Column results are ranked by
+    pub sort_by: SortKey,
+
+    /// Rank worst first
+    pub reverse: bool,
+
+    /// Only output this many of the fastest servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top: Option<u32>,
+
+    /// Leave servers that failed every query out of the table
+    pub hide_failed: bool,
+
+    /// Redraw the ranked table as servers finish, above the progress bar
+    #[serde(default)]
+    pub live: bool,
+
+    /// Optional columns added to the output
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ExtraColumn>,
+
+    /// JUnit output fails servers with a higher average latency (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub junit_max_avg_ms: Option<f64>,
+
+    /// JUnit output fails servers with a lower success rate (percent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub junit_min_success: Option<f64>,
+
+    /// Language of table headers, summaries, and error messages
+    #[serde(default)]
+    pub lang: Lang,
+
+    /// Screen-reader-friendly output: no box drawing, emoji, spinners, or color
+    #[serde(default)]
+    pub plain: bool,
+
+    /// Print only the formatted results: no config summary, progress, or decorative lines
+    #[serde(default)]
+    pub quiet: bool,
+
     /// Path to custom servers file
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_servers: Option<PathBuf>,
 
+    /// Format of the custom servers file
+    #[serde(default)]
+    pub servers_format: ServersFormat,
+
+    /// Resolver used to look up hostname entries (system resolver if unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<IpAddr>,
+
     /// Skip system DNS detection
     #[serde(default)]
     pub skip_system: bool,
@@ -59,9 +185,109 @@ pub struct Config {
     #[serde(default)]
     pub skip_gateway: bool,
 
+    /// Skip recording the network fingerprint
+    #[serde(default)]
+    pub skip_fingerprint: bool,
+
+    /// Detect system DNS and gateway on every run instead of reusing a recent detection
+    #[serde(default)]
+    pub no_detect_cache: bool,
+
+    /// Don't store runs in the history database
+    #[serde(default)]
+    pub no_history: bool,
+
     /// Disable adaptive timeout
     #[serde(default)]
     pub disable_adaptive_timeout: bool,
+
+    /// Only benchmark servers matching these patterns (name, IP, or CIDR)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+
+    /// Exclude servers matching these patterns (name, IP, or CIDR)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// Show results aggregated by provider
+    #[serde(default)]
+    pub by_provider: bool,
+
+    /// Reference server to normalize latencies against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<IpAddr>,
+
+    /// Reference resolver whose answers each server's answers are checked against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_against: Option<IpAddr>,
+
+    /// Benchmark the domain's authoritative name servers with non-recursive queries
+    #[serde(default)]
+    pub authoritative: bool,
+
+    /// Resolve the domain as a short name through the DNS search list
+    #[serde(default)]
+    pub search: bool,
+
+    /// Search list to apply with `search`; empty uses the system's search domains
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_domains: Vec<String>,
+
+    /// Network adapters to take system DNS servers from (empty for all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<String>,
+
+    /// Network adapters whose system DNS servers are skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_adapters: Vec<String>,
+
+    /// Benchmark the system DNS servers and gateway of every network interface
+    #[serde(default)]
+    pub all_interfaces: bool,
+
+    /// Benchmark the DNS servers DHCP handed out, even if the system was set to others
+    #[serde(default)]
+    pub dhcp_dns: bool,
+
+    /// Check whether queries to each server would bypass an active VPN tunnel
+    #[serde(default)]
+    pub vpn_check: bool,
+
+    /// Names that only the network's own resolvers should answer (split-horizon check)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub internal_domains: Vec<String>,
+
+    /// Record answer TTLs and compare them to the authoritative TTL
+    #[serde(default)]
+    pub ttl_check: bool,
+
+    /// Identify the anycast node behind each server with CHAOS TXT and NSID queries
+    #[serde(default)]
+    pub node_id: bool,
+
+    /// MaxMind DB files used to annotate servers and answers with ASN/country
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geoip: Vec<PathBuf>,
+
+    /// Write run summaries and threshold violations to the Windows event log
+    #[serde(default)]
+    pub event_log: bool,
+
+    /// Endpoint `--share` uploads anonymized results to (https:// only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_endpoint: Option<String>,
+
+    /// Endpoint `compare --against-region` downloads community statistics from (https:// only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats_endpoint: Option<String>,
+
+    /// Weights servers are ranked by (`[ranking]` section)
+    #[serde(default, skip_serializing_if = "Ranking::is_default")]
+    pub ranking: Ranking,
+
+    /// Advanced OS socket options (`[socket]` section)
+    #[serde(default, skip_serializing_if = "SocketOptions::is_default")]
+    pub socket: SocketOptions,
 }
 
 impl Default for Config {
@@ -71,15 +297,67 @@ impl Default for Config {
             workers: DEFAULT_WORKERS,
             requests: DEFAULT_REQUESTS,
             timeout: DEFAULT_TIMEOUT_SECS,
+            slice: DEFAULT_SLICE,
+            page_loads: 0,
+            dnssec_ab: false,
+            negative_cache: false,
+            calibrate: false,
+            edns_payload: None,
+            ecs: None,
+            workload: None,
+            domains: None,
+            shuffle_servers: false,
+            seed: None,
+            probes: Vec::new(),
+            probe_timeout: DEFAULT_PROBE_TIMEOUT_SECS,
             protocol: Protocol::default(),
+            protocols: Vec::new(),
             name_server_ip: IpVersion::default(),
-            lookup_ip: IpVersion::default(),
+            lookup_ip: LookupStrategy::default(),
             format: OutputFormat::default(),
             style: TableStyle::default(),
+            sort_by: SortKey::default(),
+            reverse: false,
+            top: None,
+            hide_failed: false,
+            live: false,
+            columns: Vec::new(),
+            junit_max_avg_ms: None,
+            junit_min_success: None,
+            lang: Lang::default(),
+            plain: false,
+            quiet: false,
             custom_servers: None,
+            servers_format: ServersFormat::default(),
+            bootstrap: None,
             skip_system: false,
             skip_gateway: false,
+            skip_fingerprint: false,
+            no_detect_cache: false,
+            no_history: false,
             disable_adaptive_timeout: false,
+            only: Vec::new(),
+            exclude: Vec::new(),
+            by_provider: false,
+            reference: None,
+            verify_against: None,
+            authoritative: false,
+            search: false,
+            search_domains: Vec::new(),
+            adapters: Vec::new(),
+            skip_adapters: Vec::new(),
+            all_interfaces: false,
+            dhcp_dns: false,
+            vpn_check: false,
+            internal_domains: Vec::new(),
+            ttl_check: false,
+            node_id: false,
+            geoip: Vec::new(),
+            event_log: false,
+            share_endpoint: None,
+            stats_endpoint: None,
+            ranking: Ranking::default(),
+            socket: SocketOptions::default(),
         }
     }
 }
@@ -96,6 +374,63 @@ impl Config {
         Ok(user_dirs.home_dir().join(CONFIG_DIR).join(CONFIG_FILE))
     }
 
+    /// Get the directory for cached data (e.g. downloaded server lists)
+    pub fn cache_dir() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(CACHE_DIR))
+    }
+
+    /// Get the directory where detected system DNS and gateway addresses are cached
+    ///
+    /// `None` if the cache is disabled with `no_detect_cache` or there's no home directory.
+    pub fn detect_cache_dir(&self) -> Option<PathBuf> {
+        if self.no_detect_cache {
+            return None;
+        }
+        Self::cache_dir().ok()
+    }
+
+    /// Adapters to take system DNS servers from
+    #[cfg(feature = "engine")]
+    pub fn adapter_filter(&self) -> crate::platform::AdapterFilter {
+        let filter = crate::platform::AdapterFilter::new(&self.adapters, &self.skip_adapters);
+        if self.all_interfaces {
+            filter.all_adapters()
+        } else {
+            filter
+        }
+    }
+
+    /// Get the path of the run history database
+    pub fn history_path() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(HISTORY_FILE))
+    }
+
+    /// Get the path of the DNS settings `apply` replaced, kept until `apply --revert`
+    pub fn dns_backup_path() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(DNS_BACKUP_FILE))
+    }
+
+    /// Get the path of the salt the network fingerprint is hashed with
+    pub fn fingerprint_salt_path() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(FINGERPRINT_SALT_FILE))
+    }
+
+    /// Get the default path of the list written by `update-servers`
+    pub fn public_servers_path() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(PUBLIC_SERVERS_FILE))
+    }
+
+    /// Get the path of the server list installed by `template import`, without extension
+    pub fn template_servers_path() -> Result<PathBuf, ConfigError> {
+        let user_dirs = UserDirs::new().ok_or(ConfigError::NoHomeDirectory)?;
+        Ok(user_dirs.home_dir().join(CONFIG_DIR).join(TEMPLATE_SERVERS_FILE))
+    }
+
     /// Check if config file exists
     pub fn exists() -> Result<bool, ConfigError> {
         Ok(Self::path()?.exists())
@@ -127,6 +462,18 @@ impl Config {
         Self::load().unwrap_or_default()
     }
 
+    /// Load config, or return default if there is no config file
+    ///
+    /// Unlike [`load_or_default`](Self::load_or_default), a config file that
+    /// can't be read or holds invalid values (like a negative ranking weight)
+    /// is an error.
+    pub fn try_load_or_default() -> Result<Self, Error> {
+        match Self::load() {
+            Err(Error::Config(ConfigError::NotFound(_))) => Ok(Self::default()),
+            result => result,
+        }
+    }
+
     /// Save config to default path
     pub fn save(&self) -> Result<(), Error> {
         let path = Self::path()?;
@@ -179,9 +526,51 @@ impl Config {
         if let Some(timeout) = other.timeout {
             self.timeout = timeout;
         }
+        if let Some(slice) = other.slice {
+            self.slice = slice;
+        }
+        if let Some(page_loads) = other.page_loads {
+            self.page_loads = page_loads;
+        }
+        if other.dnssec_ab {
+            self.dnssec_ab = true;
+        }
+        if other.negative_cache {
+            self.negative_cache = true;
+        }
+        if other.calibrate {
+            self.calibrate = true;
+        }
+        if let Some(payload) = other.edns_payload {
+            self.edns_payload = Some(payload);
+        }
+        if let Some(subnet) = other.ecs {
+            self.ecs = Some(subnet);
+        }
+        if let Some(ref workload) = other.workload {
+            self.workload = Some(workload.clone());
+        }
+        if let Some(ref domains) = other.domains {
+            self.domains = Some(domains.clone());
+        }
+        if other.shuffle_servers {
+            self.shuffle_servers = true;
+        }
+        if let Some(seed) = other.seed {
+            self.seed = Some(seed);
+        }
+        if !other.probes.is_empty() {
+            self.probes.clone_from(&other.probes);
+        }
+        if let Some(timeout) = other.probe_timeout {
+            self.probe_timeout = timeout;
+        }
         if let Some(protocol) = other.protocol {
             self.protocol = protocol;
         }
+        if !other.protocols.is_empty() {
+            self.protocols.clone_from(&other.protocols);
+        }
         if let Some(ip) = other.name_server_ip {
             self.name_server_ip = ip;
         }
@@ -194,18 +583,188 @@ impl Config {
         if let Some(style) = other.style {
             self.style = style;
         }
+        if let Some(key) = other.sort_by {
+            self.sort_by = key;
+        }
+        if other.reverse {
+            self.reverse = true;
+        }
+        if let Some(n) = other.top {
+            self.top = Some(n);
+        }
+        if other.hide_failed {
+            self.hide_failed = true;
+        }
+        if other.live {
+            self.live = true;
+        }
+        if let Some(rate) = other.min_success {
+            self.ranking.min_success = Some(rate);
+        }
+        if !other.columns.is_empty() {
+            self.columns.clone_from(&other.columns);
+        }
+        if let Some(ms) = other.junit_max_avg_ms {
+            self.junit_max_avg_ms = Some(ms);
+        }
+        if let Some(rate) = other.junit_min_success {
+            self.junit_min_success = Some(rate);
+        }
+        if let Some(lang) = other.lang {
+            self.lang = lang;
+        }
+        if other.plain {
+            self.plain = true;
+        }
+        if other.quiet {
+            self.quiet = true;
+        }
         if let Some(ref path) = other.custom_servers {
             self.custom_servers = Some(path.clone());
         }
+        if let Some(format) = other.servers_format {
+            self.servers_format = format;
+        }
+        if let Some(ip) = other.bootstrap {
+            self.bootstrap = Some(ip);
+        }
         if other.skip_system {
             self.skip_system = true;
         }
         if other.skip_gateway {
             self.skip_gateway = true;
         }
+        if other.skip_fingerprint {
+            self.skip_fingerprint = true;
+        }
+        if other.no_detect_cache {
+            self.no_detect_cache = true;
+        }
+        if other.no_history {
+            self.no_history = true;
+        }
         if other.disable_adaptive_timeout {
             self.disable_adaptive_timeout = true;
         }
+        if !other.only.is_empty() {
+            self.only.clone_from(&other.only);
+        }
+        if !other.exclude.is_empty() {
+            self.exclude.clone_from(&other.exclude);
+        }
+        if other.by_provider {
+            self.by_provider = true;
+        }
+        if let Some(ip) = other.reference {
+            self.reference = Some(ip);
+        }
+        if let Some(ip) = other.verify_against {
+            self.verify_against = Some(ip);
+        }
+        if other.authoritative {
+            self.authoritative = true;
+        }
+        if other.search {
+            self.search = true;
+        }
+        if !other.search_domains.is_empty() {
+            self.search_domains.clone_from(&other.search_domains);
+        }
+        if !other.adapters.is_empty() {
+            self.adapters.clone_from(&other.adapters);
+        }
+        if !other.skip_adapters.is_empty() {
+            self.skip_adapters.clone_from(&other.skip_adapters);
+        }
+        if other.all_interfaces {
+            self.all_interfaces = true;
+        }
+        if other.dhcp_dns {
+            self.dhcp_dns = true;
+        }
+        if other.vpn_check {
+            self.vpn_check = true;
+        }
+        if !other.internal_domains.is_empty() {
+            self.internal_domains.clone_from(&other.internal_domains);
+        }
+        if other.ttl_check {
+            self.ttl_check = true;
+        }
+        if other.node_id {
+            self.node_id = true;
+        }
+        if !other.geoip.is_empty() {
+            self.geoip.clone_from(&other.geoip);
+        }
+        if other.event_log {
+            self.event_log = true;
+        }
+        if let Some(ref url) = other.share_endpoint {
+            self.share_endpoint = Some(url.clone());
+        }
+    }
+
+    /// Table style to render with; plain output uses borderless aligned columns
+    pub fn table_style(&self) -> TableStyle {
+        if self.plain { TableStyle::Blank } else { self.style }
+    }
+
+    /// Check if progress bars and spinners should be drawn
+    pub fn show_progress(&self) -> bool {
+        self.format == OutputFormat::Table && !self.plain && !self.quiet
+    }
+
+    /// Check if every server is benchmarked over several protocols
+    pub fn compares_protocols(&self) -> bool {
+        self.compared_protocols().len() > 1
+    }
+
+    /// Protocols every server is benchmarked over, in the order given, without repeats
+    pub fn compared_protocols(&self) -> Vec<Protocol> {
+        let mut protocols = Vec::with_capacity(self.protocols.len());
+        for &protocol in &self.protocols {
+            if !protocols.contains(&protocol) {
+                protocols.push(protocol);
+            }
+        }
+        protocols
+    }
+
+    /// Protocols servers are measured over unless their entry names one
+    pub fn measured_protocols(&self) -> Vec<Protocol> {
+        let protocols = self.compared_protocols();
+        if protocols.is_empty() { vec![self.protocol] } else { protocols }
+    }
+
+    /// Check that EDNS options, which only hand-built UDP queries carry, aren't set for other protocols
+    pub fn check_udp_only(&self) -> Result<(), ConfigError> {
+        let Some(protocol) = self.measured_protocols().into_iter().find(|&p| p != Protocol::Udp) else {
+            return Ok(());
+        };
+        if self.ecs.is_some() {
+            return Err(ConfigError::InvalidValue {
+                key: "ecs".to_string(),
+                message: format!("the client subnet is only sent over udp, not {protocol}"),
+            });
+        }
+        if self.edns_payload.is_some() {
+            return Err(ConfigError::InvalidValue {
+                key: "edns_payload".to_string(),
+                message: format!("truncation is only measured over udp, not {protocol}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check if the ranked table should be redrawn while the benchmark runs
+    pub fn show_live_table(&self) -> bool {
+        self.live && self.show_progress()
+    }
+
+    /// Check if the config summary, run banners, and other decorative lines should be printed
+    pub fn show_banners(&self) -> bool {
+        self.format == OutputFormat::Table && !self.quiet
     }
 
     /// Get timeout in milliseconds
@@ -221,16 +780,135 @@ impl fmt::Display for Config {
         writeln!(f, "workers: {}", self.workers)?;
         writeln!(f, "requests: {}", self.requests)?;
         writeln!(f, "timeout: {}s", self.timeout)?;
+        writeln!(f, "slice: {}", self.slice)?;
+        writeln!(f, "page_loads: {}", self.page_loads)?;
+        writeln!(f, "dnssec_ab: {}", self.dnssec_ab)?;
+        writeln!(f, "negative_cache: {}", self.negative_cache)?;
+        writeln!(f, "calibrate: {}", self.calibrate)?;
+        if let Some(payload) = self.edns_payload {
+            writeln!(f, "edns_payload: {}", payload)?;
+        }
+        if let Some(subnet) = self.ecs {
+            writeln!(f, "ecs: {}", subnet)?;
+        }
+        if let Some(ref workload) = self.workload {
+            writeln!(f, "workload: {}", workload)?;
+        }
+        if let Some(ref domains) = self.domains {
+            writeln!(f, "domains: {}", domains)?;
+        }
+        writeln!(f, "shuffle_servers: {}", self.shuffle_servers)?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed: {}", seed)?;
+        }
+        if !self.probes.is_empty() {
+            writeln!(f, "probes: {}", self.probes.join(", "))?;
+        }
+        writeln!(f, "probe_timeout: {}s", self.probe_timeout)?;
         writeln!(f, "protocol: {}", self.protocol)?;
+        if !self.protocols.is_empty() {
+            let protocols: Vec<_> = self.protocols.iter().map(ToString::to_string).collect();
+            writeln!(f, "protocols: {}", protocols.join(", "))?;
+        }
         writeln!(f, "name_server_ip: {}", self.name_server_ip)?;
         writeln!(f, "lookup_ip: {}", self.lookup_ip)?;
         writeln!(f, "format: {}", self.format)?;
         writeln!(f, "style: {}", self.style)?;
+        writeln!(f, "sort_by: {}", self.sort_by)?;
+        writeln!(f, "reverse: {}", self.reverse)?;
+        if let Some(n) = self.top {
+            writeln!(f, "top: {}", n)?;
+        }
+        writeln!(f, "hide_failed: {}", self.hide_failed)?;
+        writeln!(f, "live: {}", self.live)?;
+        if !self.columns.is_empty() {
+            let columns: Vec<_> = self.columns.iter().map(ToString::to_string).collect();
+            writeln!(f, "columns: {}", columns.join(", "))?;
+        }
+        if let Some(ms) = self.junit_max_avg_ms {
+            writeln!(f, "junit_max_avg_ms: {}", ms)?;
+        }
+        if let Some(rate) = self.junit_min_success {
+            writeln!(f, "junit_min_success: {}%", rate)?;
+        }
+        writeln!(f, "lang: {}", self.lang)?;
+        writeln!(f, "plain: {}", self.plain)?;
+        writeln!(f, "quiet: {}", self.quiet)?;
         if let Some(ref path) = self.custom_servers {
             writeln!(f, "custom_servers: {}", path.display())?;
         }
+        writeln!(f, "servers_format: {}", self.servers_format)?;
+        if let Some(ip) = self.bootstrap {
+            writeln!(f, "bootstrap: {}", ip)?;
+        }
         writeln!(f, "skip_system: {}", self.skip_system)?;
         writeln!(f, "skip_gateway: {}", self.skip_gateway)?;
+        writeln!(f, "skip_fingerprint: {}", self.skip_fingerprint)?;
+        writeln!(f, "no_detect_cache: {}", self.no_detect_cache)?;
+        writeln!(f, "no_history: {}", self.no_history)?;
+        if !self.only.is_empty() {
+            writeln!(f, "only: {}", self.only.join(", "))?;
+        }
+        if !self.exclude.is_empty() {
+            writeln!(f, "exclude: {}", self.exclude.join(", "))?;
+        }
+        writeln!(f, "by_provider: {}", self.by_provider)?;
+        if let Some(ip) = self.reference {
+            writeln!(f, "reference: {}", ip)?;
+        }
+        if let Some(ip) = self.verify_against {
+            writeln!(f, "verify_against: {}", ip)?;
+        }
+        writeln!(f, "authoritative: {}", self.authoritative)?;
+        writeln!(f, "search: {}", self.search)?;
+        if !self.search_domains.is_empty() {
+            writeln!(f, "search_domains: {}", self.search_domains.join(", "))?;
+        }
+        if !self.adapters.is_empty() {
+            writeln!(f, "adapters: {}", self.adapters.join(", "))?;
+        }
+        if !self.skip_adapters.is_empty() {
+            writeln!(f, "skip_adapters: {}", self.skip_adapters.join(", "))?;
+        }
+        writeln!(f, "all_interfaces: {}", self.all_interfaces)?;
+        writeln!(f, "dhcp_dns: {}", self.dhcp_dns)?;
+        writeln!(f, "vpn_check: {}", self.vpn_check)?;
+        if !self.internal_domains.is_empty() {
+            writeln!(f, "internal_domains: {}", self.internal_domains.join(", "))?;
+        }
+        writeln!(f, "ttl_check: {}", self.ttl_check)?;
+        writeln!(f, "node_id: {}", self.node_id)?;
+        for path in &self.geoip {
+            writeln!(f, "geoip: {}", path.display())?;
+        }
+        writeln!(f, "event_log: {}", self.event_log)?;
+        if let Some(ref url) = self.share_endpoint {
+            writeln!(f, "share_endpoint: {}", url)?;
+        }
+        if let Some(ref url) = self.stats_endpoint {
+            writeln!(f, "stats_endpoint: {}", url)?;
+        }
+        if !self.ranking.is_default() {
+            writeln!(f, "ranking.metric: {}", self.ranking.metric)?;
+            writeln!(f, "ranking.latency: {}", self.ranking.latency)?;
+            writeln!(f, "ranking.jitter: {}", self.ranking.jitter)?;
+            writeln!(f, "ranking.loss: {}", self.ranking.loss)?;
+            if let Some(rate) = self.ranking.min_success {
+                writeln!(f, "ranking.min_success: {}%", rate)?;
+            }
+        }
+        if let Some(size) = self.socket.recv_buffer {
+            writeln!(f, "socket.recv_buffer: {}", size)?;
+        }
+        if let Some(size) = self.socket.send_buffer {
+            writeln!(f, "socket.send_buffer: {}", size)?;
+        }
+        if let Some(tos) = self.socket.tos {
+            writeln!(f, "socket.tos: {:#04x}", tos)?;
+        }
+        if let Some(ref device) = self.socket.bind_device {
+            writeln!(f, "socket.bind_device: {}", device)?;
+        }
         write!(f, "disable_adaptive_timeout: {}", self.disable_adaptive_timeout)
     }
 }
@@ -242,15 +920,65 @@ pub struct ConfigOverrides {
     pub workers: Option<u16>,
     pub requests: Option<u16>,
     pub timeout: Option<u64>,
+    pub slice: Option<u16>,
+    pub page_loads: Option<u16>,
+    pub dnssec_ab: bool,
+    pub negative_cache: bool,
+    pub calibrate: bool,
+    pub edns_payload: Option<u16>,
+    pub ecs: Option<IpNet>,
+    pub workload: Option<Workload>,
+    pub domains: Option<DomainList>,
+    pub shuffle_servers: bool,
+    pub seed: Option<u64>,
+    pub probes: Vec<String>,
+    pub probe_timeout: Option<u64>,
     pub protocol: Option<Protocol>,
+    pub protocols: Vec<Protocol>,
     pub name_server_ip: Option<IpVersion>,
-    pub lookup_ip: Option<IpVersion>,
+    pub lookup_ip: Option<LookupStrategy>,
     pub format: Option<OutputFormat>,
     pub style: Option<TableStyle>,
+    pub sort_by: Option<SortKey>,
+    pub reverse: bool,
+    pub top: Option<u32>,
+    pub hide_failed: bool,
+    pub live: bool,
+    pub min_success: Option<f64>,
+    pub columns: Vec<ExtraColumn>,
+    pub junit_max_avg_ms: Option<f64>,
+    pub junit_min_success: Option<f64>,
+    pub lang: Option<Lang>,
+    pub plain: bool,
+    pub quiet: bool,
     pub custom_servers: Option<PathBuf>,
+    pub servers_format: Option<ServersFormat>,
+    pub bootstrap: Option<IpAddr>,
     pub skip_system: bool,
     pub skip_gateway: bool,
+    pub skip_fingerprint: bool,
+    pub no_detect_cache: bool,
+    pub no_history: bool,
     pub disable_adaptive_timeout: bool,
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+    pub by_provider: bool,
+    pub reference: Option<IpAddr>,
+    pub verify_against: Option<IpAddr>,
+    pub authoritative: bool,
+    pub search: bool,
+    pub search_domains: Vec<String>,
+    pub adapters: Vec<String>,
+    pub skip_adapters: Vec<String>,
+    pub all_interfaces: bool,
+    pub dhcp_dns: bool,
+    pub vpn_check: bool,
+    pub internal_domains: Vec<String>,
+    pub ttl_check: bool,
+    pub node_id: bool,
+    pub geoip: Vec<PathBuf>,
+    pub event_log: bool,
+    pub share_endpoint: Option<String>,
 }
 
 /// Builder for creating Config
@@ -280,18 +1008,88 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn slice(mut self, slice: u16) -> Self {
+        self.config.slice = slice;
+        self
+    }
+
+    pub fn page_loads(mut self, page_loads: u16) -> Self {
+        self.config.page_loads = page_loads;
+        self
+    }
+
+    pub fn dnssec_ab(mut self, enabled: bool) -> Self {
+        self.config.dnssec_ab = enabled;
+        self
+    }
+
+    pub fn negative_cache(mut self, enabled: bool) -> Self {
+        self.config.negative_cache = enabled;
+        self
+    }
+
+    pub fn calibrate(mut self, enabled: bool) -> Self {
+        self.config.calibrate = enabled;
+        self
+    }
+
+    pub fn edns_payload(mut self, payload: u16) -> Self {
+        self.config.edns_payload = Some(payload);
+        self
+    }
+
+    pub fn ecs(mut self, subnet: IpNet) -> Self {
+        self.config.ecs = Some(subnet);
+        self
+    }
+
+    pub fn workload(mut self, workload: Workload) -> Self {
+        self.config.workload = Some(workload);
+        self
+    }
+
+    pub fn domains(mut self, domains: DomainList) -> Self {
+        self.config.domains = Some(domains);
+        self
+    }
+
+    pub fn shuffle_servers(mut self, enabled: bool) -> Self {
+        self.config.shuffle_servers = enabled;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn probes(mut self, probes: Vec<String>) -> Self {
+        self.config.probes = probes;
+        self
+    }
+
+    pub fn probe_timeout(mut self, timeout: u64) -> Self {
+        self.config.probe_timeout = timeout;
+        self
+    }
+
     pub fn protocol(mut self, protocol: Protocol) -> Self {
         self.config.protocol = protocol;
         self
     }
 
+    pub fn protocols(mut self, protocols: Vec<Protocol>) -> Self {
+        self.config.protocols = protocols;
+        self
+    }
+
     pub fn name_server_ip(mut self, ip: IpVersion) -> Self {
         self.config.name_server_ip = ip;
         self
     }
 
-    pub fn lookup_ip(mut self, ip: IpVersion) -> Self {
-        self.config.lookup_ip = ip;
+    pub fn lookup_ip(mut self, strategy: LookupStrategy) -> Self {
+        self.config.lookup_ip = strategy;
         self
     }
 
@@ -305,11 +1103,81 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn sort_by(mut self, key: SortKey) -> Self {
+        self.config.sort_by = key;
+        self
+    }
+
+    pub fn reverse(mut self, enabled: bool) -> Self {
+        self.config.reverse = enabled;
+        self
+    }
+
+    pub fn top(mut self, n: u32) -> Self {
+        self.config.top = Some(n);
+        self
+    }
+
+    pub fn hide_failed(mut self, enabled: bool) -> Self {
+        self.config.hide_failed = enabled;
+        self
+    }
+
+    pub fn live(mut self, enabled: bool) -> Self {
+        self.config.live = enabled;
+        self
+    }
+
+    pub fn min_success(mut self, rate: f64) -> Self {
+        self.config.ranking.min_success = Some(rate);
+        self
+    }
+
+    pub fn columns(mut self, columns: Vec<ExtraColumn>) -> Self {
+        self.config.columns = columns;
+        self
+    }
+
+    pub fn junit_max_avg_ms(mut self, ms: f64) -> Self {
+        self.config.junit_max_avg_ms = Some(ms);
+        self
+    }
+
+    pub fn junit_min_success(mut self, rate: f64) -> Self {
+        self.config.junit_min_success = Some(rate);
+        self
+    }
+
+    pub fn lang(mut self, lang: Lang) -> Self {
+        self.config.lang = lang;
+        self
+    }
+
+    pub fn plain(mut self, enabled: bool) -> Self {
+        self.config.plain = enabled;
+        self
+    }
+
+    pub fn quiet(mut self, enabled: bool) -> Self {
+        self.config.quiet = enabled;
+        self
+    }
+
     pub fn custom_servers(mut self, path: PathBuf) -> Self {
         self.config.custom_servers = Some(path);
         self
     }
 
+    pub fn servers_format(mut self, format: ServersFormat) -> Self {
+        self.config.servers_format = format;
+        self
+    }
+
+    pub fn bootstrap(mut self, ip: IpAddr) -> Self {
+        self.config.bootstrap = Some(ip);
+        self
+    }
+
     pub fn skip_system(mut self, skip: bool) -> Self {
         self.config.skip_system = skip;
         self
@@ -320,16 +1188,263 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn skip_fingerprint(mut self, skip: bool) -> Self {
+        self.config.skip_fingerprint = skip;
+        self
+    }
+
+    pub fn no_detect_cache(mut self, disable: bool) -> Self {
+        self.config.no_detect_cache = disable;
+        self
+    }
+
+    pub fn no_history(mut self, disable: bool) -> Self {
+        self.config.no_history = disable;
+        self
+    }
+
     pub fn disable_adaptive_timeout(mut self, disable: bool) -> Self {
         self.config.disable_adaptive_timeout = disable;
         self
     }
 
+    pub fn only(mut self, patterns: Vec<String>) -> Self {
+        self.config.only = patterns;
+        self
+    }
+
+    pub fn exclude(mut self, patterns: Vec<String>) -> Self {
+        self.config.exclude = patterns;
+        self
+    }
+
+    pub fn by_provider(mut self, enabled: bool) -> Self {
+        self.config.by_provider = enabled;
+        self
+    }
+
+    pub fn reference(mut self, ip: IpAddr) -> Self {
+        self.config.reference = Some(ip);
+        self
+    }
+
+    pub fn verify_against(mut self, ip: IpAddr) -> Self {
+        self.config.verify_against = Some(ip);
+        self
+    }
+
+    pub fn authoritative(mut self, enabled: bool) -> Self {
+        self.config.authoritative = enabled;
+        self
+    }
+
+    pub fn search(mut self, enabled: bool) -> Self {
+        self.config.search = enabled;
+        self
+    }
+
+    pub fn search_domains(mut self, domains: Vec<String>) -> Self {
+        self.config.search_domains = domains;
+        self
+    }
+
+    pub fn adapters(mut self, adapters: Vec<String>) -> Self {
+        self.config.adapters = adapters;
+        self
+    }
+
+    pub fn skip_adapters(mut self, adapters: Vec<String>) -> Self {
+        self.config.skip_adapters = adapters;
+        self
+    }
+
+    pub fn all_interfaces(mut self, enabled: bool) -> Self {
+        self.config.all_interfaces = enabled;
+        self
+    }
+
+    pub fn dhcp_dns(mut self, enabled: bool) -> Self {
+        self.config.dhcp_dns = enabled;
+        self
+    }
+
+    pub fn vpn_check(mut self, enabled: bool) -> Self {
+        self.config.vpn_check = enabled;
+        self
+    }
+
+    pub fn internal_domains(mut self, domains: Vec<String>) -> Self {
+        self.config.internal_domains = domains;
+        self
+    }
+
+    pub fn ttl_check(mut self, enabled: bool) -> Self {
+        self.config.ttl_check = enabled;
+        self
+    }
+
+    pub fn node_id(mut self, enabled: bool) -> Self {
+        self.config.node_id = enabled;
+        self
+    }
+
+    pub fn geoip(mut self, paths: Vec<PathBuf>) -> Self {
+        self.config.geoip = paths;
+        self
+    }
+
+    pub fn event_log(mut self, enabled: bool) -> Self {
+        self.config.event_log = enabled;
+        self
+    }
+
+    pub fn share_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.config.share_endpoint = Some(url.into());
+        self
+    }
+
+    pub fn stats_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.config.stats_endpoint = Some(url.into());
+        self
+    }
+
+    pub fn ranking(mut self, ranking: Ranking) -> Self {
+        self.config.ranking = ranking;
+        self
+    }
+
+    pub fn socket(mut self, options: SocketOptions) -> Self {
+        self.config.socket = options;
+        self
+    }
+
     pub fn build(self) -> Config {
         self.config
     }
 }
 
+/// OS socket options applied to every socket opened for queries
+///
+/// These are only settable from the config file, as they are rarely needed
+/// outside of router-class devices and high-QPS throughput runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocketOptions {
+    /// Receive buffer size in bytes (`SO_RCVBUF`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recv_buffer: Option<usize>,
+
+    /// Send buffer size in bytes (`SO_SNDBUF`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_buffer: Option<usize>,
+
+    /// Type-of-service byte (`IP_TOS`, or `IPV6_TCLASS` for IPv6)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tos: Option<u8>,
+
+    /// Network interface to send queries through (`SO_BINDTODEVICE`, Linux only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_device: Option<String>,
+}
+
+impl SocketOptions {
+    /// Whether no option is set (sockets keep the OS defaults)
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Weights of the score servers are ranked by
+///
+/// A server scores `latency` times its `metric` response time in ms, plus
+/// `jitter` times its jitter in ms, plus `loss` times the percentage of its
+/// queries that failed. The lowest score is the fastest server and ranks
+/// first. The defaults rank by average time alone; `metric = "p99"` ranks by
+/// tail latency, and a large `loss` buries any server that dropped a query.
+///
+/// Servers below `min_success` rank behind every other server that answered
+/// and are never the fastest, however quick their successful queries were.
+///
+/// Like [`SocketOptions`], the weights are only settable from the config
+/// file; `min_success` is also set by `--min-success`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ranking {
+    /// Response time statistic `latency` applies to
+    pub metric: RankMetric,
+
+    /// Weight of the response time, per ms
+    #[serde(deserialize_with = "weight")]
+    pub latency: f64,
+
+    /// Weight of the jitter (standard deviation of response times), per ms
+    #[serde(deserialize_with = "weight")]
+    pub jitter: f64,
+
+    /// Penalty per percentage point of failed queries, in ms
+    #[serde(deserialize_with = "weight")]
+    pub loss: f64,
+
+    /// Success rate (0 to 100) a server needs to be ranked among the rest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_success: Option<f64>,
+}
+
+impl Default for Ranking {
+    fn default() -> Self {
+        Self {
+            metric: RankMetric::default(),
+            latency: 1.0,
+            jitter: 0.0,
+            loss: 0.0,
+            min_success: None,
+        }
+    }
+}
+
+impl Ranking {
+    /// Whether servers are ranked by average time alone, with none excluded
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Read a ranking weight, rejecting negative and non-finite ones, which would scramble the order
+fn weight<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let value = f64::deserialize(deserializer)?;
+    if value.is_finite() && value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(serde::de::Error::custom(format!("invalid weight {value} (must be a number of at least 0)")))
+    }
+}
+
+/// Response time statistic servers are ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankMetric {
+    #[default]
+    Avg,
+    Min,
+    P50,
+    P95,
+    P99,
+    Max,
+}
+
+impl fmt::Display for RankMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Avg => write!(f, "avg"),
+            Self::Min => write!(f, "min"),
+            Self::P50 => write!(f, "p50"),
+            Self::P95 => write!(f, "p95"),
+            Self::P99 => write!(f, "p99"),
+            Self::Max => write!(f, "max"),
+        }
+    }
+}
+
 /// Table output styles
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -393,6 +1508,97 @@ impl std::str::FromStr for TableStyle {
     }
 }
 
+/// Columns results can be ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Ranking score (see [`Ranking`]), best first
+    #[default]
+    Score,
+    /// Average response time, fastest first
+    Avg,
+    /// Fastest response, fastest first
+    Min,
+    /// Slowest response, fastest first
+    Max,
+    /// 95th percentile response time, fastest first
+    P95,
+    /// Success rate, highest first
+    Success,
+    /// Server name, alphabetically
+    Name,
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Score => write!(f, "score"),
+            Self::Avg => write!(f, "avg"),
+            Self::Min => write!(f, "min"),
+            Self::Max => write!(f, "max"),
+            Self::P95 => write!(f, "p95"),
+            Self::Success => write!(f, "success"),
+            Self::Name => write!(f, "name"),
+        }
+    }
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "score" => Ok(Self::Score),
+            "avg" | "average" => Ok(Self::Avg),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "p95" => Ok(Self::P95),
+            "success" => Ok(Self::Success),
+            "name" => Ok(Self::Name),
+            _ => Err(Error::InvalidArgument(format!("Invalid sort key: {s}"))),
+        }
+    }
+}
+
+/// Optional output columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtraColumn {
+    /// Where the server came from (builtin, custom, system, gateway)
+    Source,
+    /// Protocol the server was queried over
+    Protocol,
+    /// Shape of the response times over the run (table only)
+    Sparkline,
+    /// Failed queries by cause: timeout, SERVFAIL, REFUSED, network (table only)
+    Failures,
+}
+
+impl fmt::Display for ExtraColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source => write!(f, "source"),
+            Self::Protocol => write!(f, "protocol"),
+            Self::Sparkline => write!(f, "sparkline"),
+            Self::Failures => write!(f, "failures"),
+        }
+    }
+}
+
+impl std::str::FromStr for ExtraColumn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "source" => Ok(Self::Source),
+            "protocol" => Ok(Self::Protocol),
+            "sparkline" => Ok(Self::Sparkline),
+            "failures" => Ok(Self::Failures),
+            _ => Err(Error::InvalidArgument(format!("Invalid column: {s}"))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,6 +1646,36 @@ mod tests {
         assert_eq!(config.requests, DEFAULT_REQUESTS); // Unchanged
     }
 
+    #[test]
+    fn test_plain_output() {
+        let config = Config::builder().style(TableStyle::Modern).build();
+        assert_eq!(config.table_style(), TableStyle::Modern);
+        assert!(config.show_progress());
+
+        let plain = Config::builder().style(TableStyle::Modern).plain(true).build();
+        assert_eq!(plain.table_style(), TableStyle::Blank);
+        assert!(!plain.show_progress());
+    }
+
+    #[test]
+    fn test_quiet_output() {
+        let config = Config::default();
+        assert!(config.show_progress());
+        assert!(config.show_banners());
+
+        let quiet = Config::builder().quiet(true).build();
+        assert!(!quiet.show_progress());
+        assert!(!quiet.show_banners());
+    }
+
+    #[test]
+    fn test_live_table() {
+        assert!(!Config::default().show_live_table());
+        assert!(Config::builder().live(true).build().show_live_table());
+        assert!(!Config::builder().live(true).quiet(true).build().show_live_table());
+        assert!(!Config::builder().live(true).format(OutputFormat::Json).build().show_live_table());
+    }
+
     #[test]
     fn test_table_style_parsing() {
         assert_eq!(TableStyle::from_str("rounded").unwrap(), TableStyle::Rounded);
@@ -447,6 +1683,123 @@ mod tests {
         assert!(TableStyle::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_config_lookup_strategy() {
+        let config: Config = toml::from_str("lookup_ip = \"prefer-v6\"").unwrap();
+        assert_eq!(config.lookup_ip, LookupStrategy::PreferV6);
+
+        let config: Config = toml::from_str("lookup_ip = \"v4\"").unwrap();
+        assert_eq!(config.lookup_ip, LookupStrategy::V4);
+    }
+
+    #[test]
+    fn test_config_socket_section() {
+        let config: Config = toml::from_str(
+            "domain = \"example.com\"\n\n[socket]\nrecv_buffer = 4194304\ntos = 0xb8\nbind_device = \"eth1\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.socket.recv_buffer, Some(4_194_304));
+        assert_eq!(config.socket.send_buffer, None);
+        assert_eq!(config.socket.tos, Some(0xb8));
+        assert_eq!(config.socket.bind_device.as_deref(), Some("eth1"));
+
+        let toml = toml::to_string_pretty(&config).unwrap();
+        assert!(toml.contains("[socket]"));
+        assert_eq!(toml::from_str::<Config>(&toml).unwrap(), config);
+
+        let toml = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!toml.contains("[socket]"));
+    }
+
+    #[test]
+    fn test_config_ranking_section() {
+        let config: Config = toml::from_str("[ranking]\nmetric = \"p99\"\nloss = 50.0\n").unwrap();
+        assert_eq!(config.ranking.metric, RankMetric::P99);
+        assert_eq!(config.ranking.latency, 1.0);
+        assert_eq!(config.ranking.loss, 50.0);
+
+        let toml = toml::to_string_pretty(&config).unwrap();
+        assert!(toml.contains("[ranking]"));
+        assert_eq!(toml::from_str::<Config>(&toml).unwrap(), config);
+        assert!(!toml::to_string_pretty(&Config::default()).unwrap().contains("[ranking]"));
+
+        assert!(toml::from_str::<Config>("[ranking]\njitter = -1.0\n").is_err());
+        assert!(toml::from_str::<Config>("[ranking]\nloss = nan\n").is_err());
+        assert!(serde_json::from_str::<Ranking>(r#"{"latency": -0.5}"#).is_err());
+    }
+
+    #[test]
+    fn test_config_compared_protocols() {
+        let config = Config::builder().protocols(vec![Protocol::Doh, Protocol::Udp, Protocol::Doh]).build();
+        assert_eq!(config.compared_protocols(), [Protocol::Doh, Protocol::Udp]);
+        assert!(config.compares_protocols());
+        assert!(!Config::builder().protocols(vec![Protocol::Tcp, Protocol::Tcp]).build().compares_protocols());
+        assert_eq!(config.measured_protocols(), [Protocol::Doh, Protocol::Udp]);
+        assert_eq!(Config::builder().protocol(Protocol::Dot).build().measured_protocols(), [Protocol::Dot]);
+    }
+
+    #[test]
+    fn test_config_udp_only() {
+        let subnet = "203.0.113.0/24".parse().unwrap();
+        assert!(Config::builder().ecs(subnet).build().check_udp_only().is_ok());
+        assert!(Config::builder().ecs(subnet).protocol(Protocol::Tcp).build().check_udp_only().is_err());
+        let compared = Config::builder().ecs(subnet).protocols(vec![Protocol::Udp, Protocol::Doh]).build();
+        assert!(compared.check_udp_only().is_err());
+        assert!(Config::builder().protocol(Protocol::Tcp).build().check_udp_only().is_ok());
+        assert!(Config::builder().edns_payload(512).build().check_udp_only().is_ok());
+        assert!(Config::builder().edns_payload(512).protocol(Protocol::Dot).build().check_udp_only().is_err());
+    }
+
+    #[test]
+    fn test_config_workload() {
+        let config: Config = toml::from_str("workload = \"web\"").unwrap();
+        assert_eq!(config.workload, Some(Workload::web()));
+
+        let toml = toml::to_string(&config).unwrap();
+        assert!(toml.contains("workload = \"A=70,AAAA=25,MX=3,TXT=2\""), "{toml}");
+        assert_eq!(toml::from_str::<Config>(&toml).unwrap(), config);
+        assert!(toml::from_str::<Config>("workload = \"A=x\"").is_err());
+    }
+
+    #[test]
+    fn test_config_shuffle() {
+        let config: Config = toml::from_str("shuffle_servers = true\nseed = 7").unwrap();
+        assert_eq!((config.shuffle_servers, config.seed), (true, Some(7)));
+        assert!(config.to_string().contains("shuffle_servers: true\nseed: 7\n"));
+        assert!(!toml::to_string(&Config::default()).unwrap().contains("seed"));
+    }
+
+    #[test]
+    fn test_config_domains() {
+        let config: Config = toml::from_str("domains = \"popular:50\"").unwrap();
+        assert_eq!(config.domains, Some(DomainList::Popular(50)));
+        assert!(config.to_string().contains("domains: popular:50"));
+
+        let mut config = Config::default();
+        config.merge(&ConfigOverrides {
+            domains: Some("example.com,example.org".parse().unwrap()),
+            ..Default::default()
+        });
+        let toml = toml::to_string(&config).unwrap();
+        assert!(toml.contains("domains = \"example.com,example.org\""), "{toml}");
+        assert!(toml::from_str::<Config>("domains = \"popular:0\"").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "engine")]
+    fn test_config_all_interfaces() {
+        let mut config = Config::default();
+        assert!(config.adapter_filter().is_empty());
+        config.merge(&ConfigOverrides {
+            all_interfaces: true,
+            skip_adapters: vec!["VPN".to_string()],
+            ..Default::default()
+        });
+        assert!(config.to_string().contains("all_interfaces: true"));
+        let filter = config.adapter_filter();
+        assert!(!filter.is_empty() && filter.allows("Wi-Fi") && !filter.allows("Corp VPN"));
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config::default();
@@ -0,0 +1,230 @@
+//! Structured (JSON/YAML) custom server files.
+
+use super::{
+    parse_hostname, parse_server_addr, CustomServers, DnsServer, HostnameServer, IpVersion, Protocol, ServerSource,
+};
+use crate::error::{DnsError, Error};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Custom servers file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServersFormat {
+    /// Detect from the file extension, falling back to the content
+    #[default]
+    Auto,
+    /// `name;ip[:port]` per line
+    Text,
+    /// JSON list of server entries
+    Json,
+    /// YAML list of server entries
+    Yaml,
+}
+
+impl ServersFormat {
+    /// Resolve `Auto` into a concrete format for the given file
+    pub fn detect(self, path: &Path, content: &str) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+
+        let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        match ext.as_deref() {
+            Some("json") => Self::Json,
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("txt" | "list" | "conf") => Self::Text,
+            _ => match content.trim_start().chars().next() {
+                Some('[' | '{') => Self::Json,
+                _ if content.trim_start().starts_with("servers:") => Self::Yaml,
+                _ if content.trim_start().starts_with("- ") => Self::Yaml,
+                _ => Self::Text,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ServersFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+            Self::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+impl FromStr for ServersFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(Error::InvalidArgument(format!("Invalid servers format: {s}"))),
+        }
+    }
+}
+
+/// A single entry in a structured servers file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServerEntry {
+    name: String,
+    address: String,
+    #[serde(default)]
+    protocol: Option<Protocol>,
+    #[serde(default)]
+    tls_name: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Structured servers file: either a bare list or `{ servers: [...] }`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ServerFile {
+    List(Vec<ServerEntry>),
+    Wrapped { servers: Vec<ServerEntry> },
+}
+
+impl ServerFile {
+    fn into_entries(self) -> Vec<ServerEntry> {
+        match self {
+            Self::List(entries) | Self::Wrapped { servers: entries } => entries,
+        }
+    }
+}
+
+/// Parse custom servers from JSON or YAML content
+pub fn parse_structured_servers(
+    content: &str,
+    format: ServersFormat,
+    ip_version: IpVersion,
+    path: &Path,
+) -> Result<CustomServers, Error> {
+    let file_error = |message: String| {
+        Error::Dns(DnsError::CustomFileError {
+            path: path.to_path_buf(),
+            message,
+        })
+    };
+
+    let file: ServerFile = match format {
+        ServersFormat::Json => serde_json::from_str(content).map_err(|e| file_error(e.to_string()))?,
+        ServersFormat::Yaml => serde_yaml::from_str(content).map_err(|e| file_error(e.to_string()))?,
+        ServersFormat::Auto | ServersFormat::Text => {
+            return Err(file_error(format!("'{format}' is not a structured format")));
+        }
+    };
+
+    let mut custom = CustomServers::default();
+
+    for (i, entry) in file.into_entries().into_iter().enumerate() {
+        let default_port = entry.protocol.unwrap_or_default().default_port();
+
+        if let Some(addr) = parse_server_addr(&entry.address, default_port) {
+            let mut server = DnsServer::new(entry.name, addr, ServerSource::Custom);
+            server.protocol = entry.protocol;
+            server.tls_name = entry.tls_name;
+            server.tags = entry.tags;
+
+            if server.matches_ip_version(ip_version) {
+                custom.servers.push(server);
+            }
+        } else if let Some((host, port)) = parse_hostname(&entry.address, default_port) {
+            custom.hostnames.push(HostnameServer {
+                name: entry.name,
+                host,
+                port,
+                protocol: entry.protocol,
+                tls_name: entry.tls_name,
+                tags: entry.tags,
+            });
+        } else {
+            return Err(file_error(format!("Invalid address in entry {}: {}", i + 1, entry.address)));
+        }
+    }
+
+    Ok(custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_detection() {
+        let auto = ServersFormat::Auto;
+        assert_eq!(auto.detect(Path::new("s.json"), ""), ServersFormat::Json);
+        assert_eq!(auto.detect(Path::new("s.YML"), ""), ServersFormat::Yaml);
+        assert_eq!(auto.detect(Path::new("s.txt"), "[]"), ServersFormat::Text);
+        assert_eq!(auto.detect(Path::new("servers"), " [{}]"), ServersFormat::Json);
+        assert_eq!(auto.detect(Path::new("servers"), "servers:\n"), ServersFormat::Yaml);
+        assert_eq!(auto.detect(Path::new("servers"), "Google;8.8.8.8:53"), ServersFormat::Text);
+        assert_eq!(ServersFormat::Yaml.detect(Path::new("s.json"), ""), ServersFormat::Yaml);
+    }
+
+    #[test]
+    fn test_parse_json_servers() {
+        let content = r#"[
+            {"name": "Cloudflare", "address": "1.1.1.1", "tags": ["anycast"]},
+            {"name": "Quad9", "address": "9.9.9.9:53", "protocol": "tcp", "tls_name": "dns.quad9.net"}
+        ]"#;
+        let servers =
+            parse_structured_servers(content, ServersFormat::Json, IpVersion::V4, Path::new("s.json")).unwrap().servers;
+
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "Cloudflare");
+        assert_eq!(servers[0].addr.port(), 53);
+        assert_eq!(servers[0].tags, vec!["anycast".to_string()]);
+        assert_eq!(servers[0].protocol, None);
+        assert_eq!(servers[1].protocol, Some(Protocol::Tcp));
+        assert_eq!(servers[1].tls_name.as_deref(), Some("dns.quad9.net"));
+        assert_eq!(servers[1].source, ServerSource::Custom);
+    }
+
+    #[test]
+    fn test_parse_yaml_servers() {
+        let content = r#"
+servers:
+  - name: Google
+    address: "8.8.8.8:53"
+  - name: Google
+    address: "[2001:4860:4860::8888]:53"
+"#;
+        let path = Path::new("s.yaml");
+        let v4 = parse_structured_servers(content, ServersFormat::Yaml, IpVersion::V4, path).unwrap();
+        let both = parse_structured_servers(content, ServersFormat::Yaml, IpVersion::Both, path).unwrap();
+
+        assert_eq!(v4.servers.len(), 1);
+        assert_eq!(both.servers.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_structured_hostname() {
+        let content = r#"[{"name": "NextDNS", "address": "dns.nextdns.io", "protocol": "tcp", "tags": ["filtering"]}]"#;
+        let custom =
+            parse_structured_servers(content, ServersFormat::Json, IpVersion::V4, Path::new("s.json")).unwrap();
+
+        assert!(custom.servers.is_empty());
+        assert_eq!(custom.hostnames[0].host, "dns.nextdns.io");
+        assert_eq!(custom.hostnames[0].protocol, Some(Protocol::Tcp));
+        assert_eq!(custom.hostnames[0].tags, vec!["filtering".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_structured_errors() {
+        let path = Path::new("s.json");
+        let bad_addr = r#"[{"name": "X", "address": "not an ip"}]"#;
+        let unknown = r#"[{"name": "X", "address": "1.1.1.1:53", "colour": "red"}]"#;
+
+        assert!(parse_structured_servers(bad_addr, ServersFormat::Json, IpVersion::V4, path).is_err());
+        assert!(parse_structured_servers(unknown, ServersFormat::Json, IpVersion::V4, path).is_err());
+    }
+}
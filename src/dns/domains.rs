@@ -0,0 +1,150 @@
+//! Sets of domains to spread queries over.
+//!
+//! One name measures one cache entry. Spreading the queries over many names,
+//! like the bundled list of popular domains, gives a picture closer to what
+//! a resolver serves day to day without having to build a list first.
+
+use super::parse_hostname;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Bundled list of popular domains, most popular first
+static POPULAR_DOMAINS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    include_str!("popular_domains.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+});
+
+/// Number of domains taken from the bundled list by a bare `popular`
+pub const DEFAULT_POPULAR_DOMAINS: usize = 100;
+
+/// Most popular `count` domains of the bundled list (all of them if it is shorter)
+pub fn popular_domains(count: usize) -> &'static [&'static str] {
+    &POPULAR_DOMAINS[..count.min(POPULAR_DOMAINS.len())]
+}
+
+/// Domains to spread queries over
+///
+/// Parsed from `popular:N`, the top `N` of the bundled popular domains (100
+/// for a bare `popular`), or a comma-separated list of names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DomainList {
+    /// Most popular domains of the bundled list
+    Popular(usize),
+    /// Names given explicitly
+    Names(Vec<String>),
+}
+
+impl DomainList {
+    /// Domains in query order
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Self::Popular(count) => popular_domains(*count).to_vec(),
+            Self::Names(names) => names.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Parse a comma-separated list of names, lower-cased and without duplicates
+    fn from_names(s: &str) -> Result<Self, String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match parse_hostname(name, 0) {
+                Some((name, 0)) if !names.contains(&name) => names.push(name),
+                Some((name, 0)) => return Err(format!("{name} is listed twice")),
+                _ => return Err(format!("invalid domain '{name}'")),
+            }
+        }
+        if names.is_empty() {
+            return Err("no domains".to_string());
+        }
+        Ok(Self::Names(names))
+    }
+}
+
+impl fmt::Display for DomainList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Popular(count) => write!(f, "popular:{count}"),
+            Self::Names(names) => write!(f, "{}", names.join(",")),
+        }
+    }
+}
+
+impl FromStr for DomainList {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let error = |message: String| Error::InvalidArgument(format!("Invalid domain list: {message}"));
+
+        if let Some(count) = s.strip_prefix("popular").map(str::trim) {
+            let count = match count.strip_prefix(':') {
+                Some(count) => count.trim().parse().map_err(|_| error(format!("invalid count in '{s}'")))?,
+                None if count.is_empty() => DEFAULT_POPULAR_DOMAINS,
+                None => return Self::from_names(s).map_err(error),
+            };
+            let available = POPULAR_DOMAINS.len();
+            if count == 0 || count > available {
+                return Err(error(format!("popular list count must be between 1 and {available}")));
+            }
+            return Ok(Self::Popular(count));
+        }
+        Self::from_names(s).map_err(error)
+    }
+}
+
+impl TryFrom<String> for DomainList {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DomainList> for String {
+    fn from(domains: DomainList) -> Self {
+        domains.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popular_domains() {
+        assert!(POPULAR_DOMAINS.len() >= 200);
+        assert_eq!(popular_domains(2), ["google.com", "facebook.com"]);
+        for (i, domain) in POPULAR_DOMAINS.iter().enumerate() {
+            assert!(parse_hostname(domain, 53).is_some(), "{domain}");
+            assert!(!POPULAR_DOMAINS[..i].contains(domain), "{domain} is listed twice");
+        }
+    }
+
+    #[test]
+    fn test_domain_list_parsing() {
+        let popular: DomainList = "popular:25".parse().unwrap();
+        assert_eq!(popular, DomainList::Popular(25));
+        assert_eq!(popular.names().len(), 25);
+        assert_eq!("popular".parse::<DomainList>().unwrap(), DomainList::Popular(DEFAULT_POPULAR_DOMAINS));
+        assert!("popular:0".parse::<DomainList>().is_err());
+        assert!("popular:100000".parse::<DomainList>().is_err());
+        assert!("popular:many".parse::<DomainList>().is_err());
+
+        let names: DomainList = "Example.com, example.org.".parse().unwrap();
+        assert_eq!(names.names(), ["example.com", "example.org"]);
+        assert_eq!(names.to_string().parse::<DomainList>().unwrap(), names);
+        // Not the bundled list, just a name starting with it
+        assert_eq!("popularsite.com".parse::<DomainList>().unwrap().names(), ["popularsite.com"]);
+
+        assert!("example.com,example.com".parse::<DomainList>().is_err());
+        assert!("example.com:53".parse::<DomainList>().is_err());
+        assert!(" , ".parse::<DomainList>().is_err());
+    }
+}
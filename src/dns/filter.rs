@@ -0,0 +1,134 @@
+//! Include/exclude filtering for server selection.
+
+use super::DnsServer;
+use crate::error::Error;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A single server selection pattern
+///
+/// Patterns containing a `/` are parsed as CIDR ranges, patterns that parse as
+/// an IP address match that exact address, and anything else is matched
+/// case-insensitively against the server name (substring match).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerPattern {
+    /// Match servers whose name contains this text (lowercased)
+    Name(String),
+    /// Match a single IP address
+    Ip(IpAddr),
+    /// Match any IP address within a network
+    Cidr(IpNet),
+}
+
+impl ServerPattern {
+    /// Check if a server matches this pattern
+    pub fn matches(&self, server: &DnsServer) -> bool {
+        match self {
+            Self::Name(name) => server.name.to_lowercase().contains(name),
+            Self::Ip(ip) => server.ip() == *ip,
+            Self::Cidr(net) => net.contains(&server.ip()),
+        }
+    }
+}
+
+impl FromStr for ServerPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(Error::InvalidArgument("Empty server pattern".into()));
+        }
+
+        if s.contains('/') {
+            return IpNet::from_str(s)
+                .map(Self::Cidr)
+                .map_err(|_| Error::InvalidArgument(format!("Invalid CIDR pattern: {s}")));
+        }
+
+        match IpAddr::from_str(s) {
+            Ok(ip) => Ok(Self::Ip(ip)),
+            Err(_) => Ok(Self::Name(s.to_lowercase())),
+        }
+    }
+}
+
+/// Server filter built from `--only` and `--exclude` patterns
+#[derive(Debug, Clone, Default)]
+pub struct ServerFilter {
+    only: Vec<ServerPattern>,
+    exclude: Vec<ServerPattern>,
+}
+
+impl ServerFilter {
+    /// Build a filter from raw pattern strings
+    pub fn new(only: &[String], exclude: &[String]) -> Result<Self, Error> {
+        Ok(Self {
+            only: only.iter().map(|p| p.parse()).collect::<Result<_, _>>()?,
+            exclude: exclude.iter().map(|p| p.parse()).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Check if a server should be kept
+    ///
+    /// A server is kept when it matches at least one `only` pattern (or no
+    /// `only` patterns were given) and matches none of the `exclude` patterns.
+    pub fn allows(&self, server: &DnsServer) -> bool {
+        let included = self.only.is_empty() || self.only.iter().any(|p| p.matches(server));
+        included && !self.exclude.iter().any(|p| p.matches(server))
+    }
+
+    /// Check if this filter has no patterns
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::ServerSource;
+
+    fn server(name: &str, ip: &str) -> DnsServer {
+        DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin)
+    }
+
+    #[test]
+    fn test_pattern_parsing() {
+        assert_eq!(
+            ServerPattern::from_str("Cloudflare").unwrap(),
+            ServerPattern::Name("cloudflare".into())
+        );
+        assert_eq!(
+            ServerPattern::from_str("1.1.1.1").unwrap(),
+            ServerPattern::Ip("1.1.1.1".parse().unwrap())
+        );
+        assert!(matches!(ServerPattern::from_str("9.9.9.0/24").unwrap(), ServerPattern::Cidr(_)));
+        assert!(ServerPattern::from_str("1.1.1.1/99").is_err());
+        assert!(ServerPattern::from_str("  ").is_err());
+    }
+
+    #[test]
+    fn test_filter_only() {
+        let filter = ServerFilter::new(&["cloudflare".into(), "quad9".into()], &[]).unwrap();
+        assert!(filter.allows(&server("Cloudflare", "1.1.1.1")));
+        assert!(filter.allows(&server("Quad9", "9.9.9.9")));
+        assert!(!filter.allows(&server("Google", "8.8.8.8")));
+    }
+
+    #[test]
+    fn test_filter_exclude() {
+        let filter = ServerFilter::new(&[], &["AdGuard".into(), "8.8.4.4".into()]).unwrap();
+        assert!(!filter.allows(&server("AdGuard", "94.140.14.14")));
+        assert!(!filter.allows(&server("Google", "8.8.4.4")));
+        assert!(filter.allows(&server("Google", "8.8.8.8")));
+    }
+
+    #[test]
+    fn test_filter_cidr() {
+        let filter = ServerFilter::new(&["2606:4700::/32".into()], &[]).unwrap();
+        assert!(filter.allows(&server("Cloudflare", "2606:4700:4700::1111")));
+        assert!(!filter.allows(&server("Google", "2001:4860:4860::8888")));
+    }
+}
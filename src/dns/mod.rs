@@ -1,9 +1,26 @@
 //! DNS server definitions and types.
 
+mod custom;
+mod domains;
+mod filter;
+mod public_list;
+#[cfg(feature = "engine")]
+mod remote;
 mod servers;
-
+mod workload;
+
+pub use custom::{parse_structured_servers, ServersFormat};
+pub use domains::{popular_domains, DomainList, DEFAULT_POPULAR_DOMAINS};
+pub use filter::{ServerFilter, ServerPattern};
+pub use public_list::{
+    parse_public_dns_csv, public_dns_info_url, write_public_servers, PublicListFilter, PublicResolver,
+};
+#[cfg(feature = "engine")]
+pub use remote::{download_text, fetch_server_list, REMOTE_CACHE_TTL, REMOTE_FETCH_TIMEOUT};
 pub use servers::BUILTIN_SERVERS_V4;
 pub use servers::BUILTIN_SERVERS_V6;
+pub use servers::{builtin_provider, Filtering, LoggingPolicy, ProviderInfo, BUILTIN_PROVIDERS};
+pub use workload::{Workload, WorkloadQuery};
 
 use crate::error::{DnsError, Error};
 use serde::{Deserialize, Serialize};
@@ -21,13 +38,29 @@ pub struct DnsServer {
     pub addr: SocketAddr,
     /// Source of this server entry
     pub source: ServerSource,
+    /// Protocol override for this server (defaults to the configured protocol)
+    pub protocol: Option<Protocol>,
+    /// TLS server name for encrypted transports
+    pub tls_name: Option<String>,
+    /// Free-form tags from custom server files
+    pub tags: Vec<String>,
+    /// Provider metadata (known for built-in servers)
+    pub provider: Option<ProviderInfo>,
 }
 
 impl DnsServer {
     /// Create a new DNS server entry
     #[inline]
     pub const fn new(name: String, addr: SocketAddr, source: ServerSource) -> Self {
-        Self { name, addr, source }
+        Self {
+            name,
+            addr,
+            source,
+            protocol: None,
+            tls_name: None,
+            tags: Vec::new(),
+            provider: None,
+        }
     }
 
     /// Create from IP address with default DNS port (53)
@@ -59,10 +92,48 @@ impl DnsServer {
         match version {
             IpVersion::V4 => self.is_ipv4(),
             IpVersion::V6 => self.is_ipv6(),
+            IpVersion::Both => true,
         }
     }
 }
 
+/// Custom server entry addressed by hostname, resolved at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostnameServer {
+    /// Human-readable name
+    pub name: String,
+    /// Hostname to resolve
+    pub host: String,
+    /// Server port
+    pub port: u16,
+    /// Protocol override for this server
+    pub protocol: Option<Protocol>,
+    /// TLS server name (defaults to the hostname)
+    pub tls_name: Option<String>,
+    /// Free-form tags from custom server files
+    pub tags: Vec<String>,
+}
+
+impl HostnameServer {
+    /// Create a server entry for one resolved address of this host
+    pub fn to_server(&self, ip: IpAddr) -> DnsServer {
+        let mut server = DnsServer::new(self.name.clone(), SocketAddr::new(ip, self.port), ServerSource::Custom);
+        server.protocol = self.protocol;
+        server.tls_name = Some(self.tls_name.clone().unwrap_or_else(|| self.host.clone()));
+        server.tags = self.tags.clone();
+        server
+    }
+}
+
+/// Servers loaded from a custom servers file
+#[derive(Debug, Clone, Default)]
+pub struct CustomServers {
+    /// Entries with a literal IP address
+    pub servers: Vec<DnsServer>,
+    /// Entries with a hostname that still need resolving
+    pub hostnames: Vec<HostnameServer>,
+}
+
 impl fmt::Display for DnsServer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} ({})", self.name, self.addr.ip())
@@ -70,7 +141,8 @@ impl fmt::Display for DnsServer {
 }
 
 /// Source of a DNS server entry
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ServerSource {
     /// Built-in server list
     #[default]
@@ -81,6 +153,8 @@ pub enum ServerSource {
     System,
     /// Network gateway/router
     Gateway,
+    /// DNS servers of a DHCP lease
+    Dhcp,
 }
 
 impl fmt::Display for ServerSource {
@@ -90,12 +164,13 @@ impl fmt::Display for ServerSource {
             Self::Custom => write!(f, "custom"),
             Self::System => write!(f, "system"),
             Self::Gateway => write!(f, "gateway"),
+            Self::Dhcp => write!(f, "dhcp"),
         }
     }
 }
 
 /// DNS protocol to use
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Protocol {
     /// UDP (faster, less reliable)
@@ -103,6 +178,10 @@ pub enum Protocol {
     Udp,
     /// TCP (more reliable, slightly slower)
     Tcp,
+    /// DNS-over-TLS (RFC 7858)
+    Dot,
+    /// DNS-over-HTTPS (RFC 8484)
+    Doh,
 }
 
 impl fmt::Display for Protocol {
@@ -110,6 +189,8 @@ impl fmt::Display for Protocol {
         match self {
             Self::Udp => write!(f, "udp"),
             Self::Tcp => write!(f, "tcp"),
+            Self::Dot => write!(f, "dot"),
+            Self::Doh => write!(f, "doh"),
         }
     }
 }
@@ -121,16 +202,38 @@ impl FromStr for Protocol {
         match s.to_lowercase().as_str() {
             "udp" => Ok(Self::Udp),
             "tcp" => Ok(Self::Tcp),
+            "dot" => Ok(Self::Dot),
+            "doh" => Ok(Self::Doh),
             _ => Err(Error::InvalidArgument(format!("Invalid protocol: {s}"))),
         }
     }
 }
 
-impl From<Protocol> for hickory_resolver::proto::xfer::Protocol {
-    fn from(p: Protocol) -> Self {
+impl Protocol {
+    /// Default server port for this protocol
+    #[inline]
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::Udp | Self::Tcp => 53,
+            Self::Dot => 853,
+            Self::Doh => 443,
+        }
+    }
+}
+
+/// The resolver is built without TLS support, so it can't send encrypted
+/// queries; those go through `benchmark::tls` instead.
+#[cfg(feature = "engine")]
+impl TryFrom<Protocol> for hickory_resolver::proto::xfer::Protocol {
+    type Error = Error;
+
+    fn try_from(p: Protocol) -> Result<Self, Self::Error> {
         match p {
-            Protocol::Udp => Self::Udp,
-            Protocol::Tcp => Self::Tcp,
+            Protocol::Udp => Ok(Self::Udp),
+            Protocol::Tcp => Ok(Self::Tcp),
+            Protocol::Dot | Protocol::Doh => {
+                Err(Error::InvalidArgument(format!("The resolver doesn't support {p}; use udp or tcp")))
+            }
         }
     }
 }
@@ -144,6 +247,8 @@ pub enum IpVersion {
     V4,
     /// IPv6 only
     V6,
+    /// Both IPv4 and IPv6
+    Both,
 }
 
 impl fmt::Display for IpVersion {
@@ -151,6 +256,7 @@ impl fmt::Display for IpVersion {
         match self {
             Self::V4 => write!(f, "v4"),
             Self::V6 => write!(f, "v6"),
+            Self::Both => write!(f, "both"),
         }
     }
 }
@@ -162,24 +268,88 @@ impl FromStr for IpVersion {
         match s.to_lowercase().as_str() {
             "v4" | "ipv4" | "4" => Ok(Self::V4),
             "v6" | "ipv6" | "6" => Ok(Self::V6),
+            "both" | "dual" | "any" => Ok(Self::Both),
             _ => Err(Error::InvalidArgument(format!("Invalid IP version: {s}"))),
         }
     }
 }
 
-impl From<IpVersion> for hickory_resolver::config::LookupIpStrategy {
+/// Address families requested when resolving the test domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LookupStrategy {
+    /// A records only
+    #[default]
+    V4,
+    /// AAAA records only
+    V6,
+    /// A and AAAA records in parallel
+    Both,
+    /// A records, falling back to AAAA
+    PreferV4,
+    /// AAAA records, falling back to A
+    PreferV6,
+}
+
+impl fmt::Display for LookupStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V4 => write!(f, "v4"),
+            Self::V6 => write!(f, "v6"),
+            Self::Both => write!(f, "both"),
+            Self::PreferV4 => write!(f, "prefer-v4"),
+            Self::PreferV6 => write!(f, "prefer-v6"),
+        }
+    }
+}
+
+impl FromStr for LookupStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v4" | "ipv4" | "4" => Ok(Self::V4),
+            "v6" | "ipv6" | "6" => Ok(Self::V6),
+            "both" | "dual" | "any" => Ok(Self::Both),
+            "prefer-v4" | "v4-then-v6" => Ok(Self::PreferV4),
+            "prefer-v6" | "v6-then-v4" => Ok(Self::PreferV6),
+            _ => Err(Error::InvalidArgument(format!("Invalid lookup strategy: {s}"))),
+        }
+    }
+}
+
+impl From<IpVersion> for LookupStrategy {
     fn from(v: IpVersion) -> Self {
         match v {
-            IpVersion::V4 => Self::Ipv4Only,
-            IpVersion::V6 => Self::Ipv6Only,
+            IpVersion::V4 => Self::V4,
+            IpVersion::V6 => Self::V6,
+            IpVersion::Both => Self::Both,
+        }
+    }
+}
+
+#[cfg(feature = "engine")]
+impl From<LookupStrategy> for hickory_resolver::config::LookupIpStrategy {
+    fn from(s: LookupStrategy) -> Self {
+        match s {
+            LookupStrategy::V4 => Self::Ipv4Only,
+            LookupStrategy::V6 => Self::Ipv6Only,
+            LookupStrategy::Both => Self::Ipv4AndIpv6,
+            LookupStrategy::PreferV4 => Self::Ipv4thenIpv6,
+            LookupStrategy::PreferV6 => Self::Ipv6thenIpv4,
         }
     }
 }
 
 /// Load custom DNS servers from a file
 ///
-/// Expected format: `name;ip:port` per line
-pub fn load_custom_servers(path: &Path, ip_version: IpVersion) -> Result<Vec<DnsServer>, Error> {
+/// Supports the `name;ip[:port]` line format as well as JSON and YAML entry
+/// lists; `ServersFormat::Auto` picks one based on extension and content.
+pub fn load_custom_servers(
+    path: &Path,
+    ip_version: IpVersion,
+    format: ServersFormat,
+) -> Result<CustomServers, Error> {
     let content = std::fs::read_to_string(path).map_err(|e| {
         Error::Dns(DnsError::CustomFileError {
             path: path.to_path_buf(),
@@ -187,16 +357,79 @@ pub fn load_custom_servers(path: &Path, ip_version: IpVersion) -> Result<Vec<Dns
         })
     })?;
 
-    parse_custom_servers(&content, ip_version, path)
+    parse_server_list(&content, path, ip_version, format)
+}
+
+/// Parse a custom server list in any supported format
+///
+/// `path` is used for format detection and error messages only.
+pub fn parse_server_list(
+    content: &str,
+    path: &Path,
+    ip_version: IpVersion,
+    format: ServersFormat,
+) -> Result<CustomServers, Error> {
+    match format.detect(path, content) {
+        ServersFormat::Text | ServersFormat::Auto => parse_custom_servers(content, ip_version, path),
+        structured => parse_structured_servers(content, structured, ip_version, path),
+    }
+}
+
+/// Check if a custom servers location is a URL rather than a local path
+pub fn is_remote_list(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|s| s.starts_with("https://") || s.starts_with("http://"))
+}
+
+/// Parse a server address, falling back to `default_port` when none is given
+///
+/// Accepts `ip:port`, `[ipv6]:port`, a bare IPv4/IPv6 address, or `[ipv6]`.
+pub fn parse_server_addr(s: &str, default_port: u16) -> Option<SocketAddr> {
+    let s = s.trim();
+    if let Ok(addr) = s.parse::<SocketAddr>() {
+        return Some(addr);
+    }
+
+    let ip = s.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).unwrap_or(s);
+    ip.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, default_port))
+}
+
+/// Parse a `host[:port]` hostname address, falling back to `default_port`
+///
+/// Returns `None` if `s` is not a syntactically valid hostname.
+pub fn parse_hostname(s: &str, default_port: u16) -> Option<(String, u16)> {
+    let s = s.trim();
+    let (host, port) = match s.split_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (s, default_port),
+    };
+    let host = host.strip_suffix('.').unwrap_or(host);
+
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid_label = |l: &&str| {
+        !l.is_empty()
+            && l.len() <= 63
+            && !l.starts_with('-')
+            && !l.ends_with('-')
+            && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let tld_has_letter = labels.last().is_some_and(|l| l.chars().any(|c| c.is_ascii_alphabetic()));
+
+    (labels.iter().all(valid_label) && tld_has_letter).then(|| (host.to_lowercase(), port))
 }
 
 /// Parse custom servers from string content
+///
+/// The port may be omitted, in which case the default DNS port is used.
+/// Entries may also name a host (`NextDNS;dns.nextdns.io`), which is returned
+/// unresolved in [`CustomServers::hostnames`].
 pub fn parse_custom_servers(
     content: &str,
     ip_version: IpVersion,
     path: &Path,
-) -> Result<Vec<DnsServer>, Error> {
-    let mut servers = Vec::new();
+) -> Result<CustomServers, Error> {
+    let mut custom = CustomServers::default();
+    let default_port = Protocol::default().default_port();
 
     for (line_num, line) in content.lines().enumerate() {
         let line = line.trim();
@@ -212,39 +445,47 @@ pub fn parse_custom_servers(
         let name = parts[0].trim().to_string();
         let addr_str = parts[1].trim();
 
-        let addr: SocketAddr = addr_str.parse().map_err(|_| {
-            Error::Dns(DnsError::CustomFileError {
+        if let Some(addr) = parse_server_addr(addr_str, default_port) {
+            let server = DnsServer::new(name, addr, ServerSource::Custom);
+
+            // Filter by IP version
+            if server.matches_ip_version(ip_version) {
+                custom.servers.push(server);
+            }
+        } else if let Some((host, port)) = parse_hostname(addr_str, default_port) {
+            custom.hostnames.push(HostnameServer {
+                name,
+                host,
+                port,
+                protocol: None,
+                tls_name: None,
+                tags: Vec::new(),
+            });
+        } else {
+            return Err(Error::Dns(DnsError::CustomFileError {
                 path: path.to_path_buf(),
                 message: format!("Invalid address at line {}: {}", line_num + 1, addr_str),
-            })
-        })?;
-
-        let server = DnsServer::new(name, addr, ServerSource::Custom);
-
-        // Filter by IP version
-        if server.matches_ip_version(ip_version) {
-            servers.push(server);
+            }));
         }
     }
 
-    Ok(servers)
+    Ok(custom)
 }
 
 /// Get the builtin DNS server list for the given IP version
 pub fn get_builtin_servers(ip_version: IpVersion) -> Vec<DnsServer> {
+    let builtin = |name: &str, ip: IpAddr| {
+        let mut server = DnsServer::from_ip(name, ip, ServerSource::Builtin);
+        server.provider = builtin_provider(name).cloned();
+        server
+    };
+    let v4 = BUILTIN_SERVERS_V4.iter().map(|(name, ip)| builtin(name, IpAddr::V4(*ip)));
+    let v6 = BUILTIN_SERVERS_V6.iter().map(|(name, ip)| builtin(name, IpAddr::V6(*ip)));
+
     match ip_version {
-        IpVersion::V4 => BUILTIN_SERVERS_V4
-            .iter()
-            .map(|(name, ip)| {
-                DnsServer::from_ip(*name, IpAddr::V4(*ip), ServerSource::Builtin)
-            })
-            .collect(),
-        IpVersion::V6 => BUILTIN_SERVERS_V6
-            .iter()
-            .map(|(name, ip)| {
-                DnsServer::from_ip(*name, IpAddr::V6(*ip), ServerSource::Builtin)
-            })
-            .collect(),
+        IpVersion::V4 => v4.collect(),
+        IpVersion::V6 => v6.collect(),
+        IpVersion::Both => v4.chain(v6).collect(),
     }
 }
 
@@ -270,14 +511,33 @@ mod tests {
         assert!(Protocol::from_str("invalid").is_err());
     }
 
+    #[test]
+    #[cfg(feature = "engine")]
+    fn test_protocol_resolver_transport() {
+        use hickory_resolver::proto::xfer::Protocol as Transport;
+        assert_eq!(Transport::try_from(Protocol::Tcp).unwrap(), Transport::Tcp);
+        assert!(Transport::try_from(Protocol::Dot).is_err());
+        assert!(Transport::try_from(Protocol::Doh).is_err());
+    }
+
     #[test]
     fn test_ip_version_parsing() {
         assert_eq!(IpVersion::from_str("v4").unwrap(), IpVersion::V4);
         assert_eq!(IpVersion::from_str("ipv6").unwrap(), IpVersion::V6);
         assert_eq!(IpVersion::from_str("4").unwrap(), IpVersion::V4);
+        assert_eq!(IpVersion::from_str("both").unwrap(), IpVersion::Both);
         assert!(IpVersion::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_lookup_strategy_parsing() {
+        assert_eq!(LookupStrategy::from_str("v4").unwrap(), LookupStrategy::V4);
+        assert_eq!(LookupStrategy::from_str("both").unwrap(), LookupStrategy::Both);
+        assert_eq!(LookupStrategy::from_str("prefer-v6").unwrap(), LookupStrategy::PreferV6);
+        assert!(LookupStrategy::from_str("invalid").is_err());
+        assert_eq!(LookupStrategy::PreferV4.to_string(), "prefer-v4");
+    }
+
     #[test]
     fn test_parse_custom_servers() {
         let content = r#"
@@ -286,12 +546,26 @@ Google;8.8.8.8:53
 Cloudflare;1.1.1.1:53
 "#;
         let path = Path::new("test.txt");
-        let servers = parse_custom_servers(content, IpVersion::V4, path).unwrap();
+        let servers = parse_custom_servers(content, IpVersion::V4, path).unwrap().servers;
         assert_eq!(servers.len(), 2);
         assert_eq!(servers[0].name, "Google");
         assert_eq!(servers[1].name, "Cloudflare");
     }
 
+    #[test]
+    fn test_parse_custom_servers_default_port() {
+        let content = "Cloudflare;1.1.1.1\nAlt;1.0.0.1:5353\nGoogle;2001:4860:4860::8888\nQuad9;[2620:fe::fe]\n";
+        let path = Path::new("test.txt");
+        let servers = parse_custom_servers(content, IpVersion::Both, path).unwrap().servers;
+
+        assert_eq!(servers.len(), 4);
+        assert_eq!(servers[0].addr, "1.1.1.1:53".parse().unwrap());
+        assert_eq!(servers[1].addr.port(), 5353);
+        assert_eq!(servers[2].addr, "[2001:4860:4860::8888]:53".parse().unwrap());
+        assert_eq!(servers[3].addr, "[2620:fe::fe]:53".parse().unwrap());
+        assert!(parse_custom_servers("Bad;1.1.1", IpVersion::V4, path).is_err());
+    }
+
     #[test]
     fn test_builtin_servers() {
         let v4_servers = get_builtin_servers(IpVersion::V4);
@@ -306,5 +580,44 @@ Cloudflare;1.1.1.1:53
         for server in &v6_servers {
             assert!(server.is_ipv6());
         }
+
+        let both = get_builtin_servers(IpVersion::Both);
+        assert_eq!(both.len(), v4_servers.len() + v6_servers.len());
+        assert!(both.iter().all(|s| s.matches_ip_version(IpVersion::Both)));
+    }
+
+    #[test]
+    fn test_parse_custom_servers_both() {
+        let content = "Google;8.8.8.8:53\nGoogle;[2001:4860:4860::8888]:53\n";
+        let path = Path::new("test.txt");
+        assert_eq!(parse_custom_servers(content, IpVersion::V4, path).unwrap().servers.len(), 1);
+        assert_eq!(parse_custom_servers(content, IpVersion::Both, path).unwrap().servers.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_custom_servers_hostnames() {
+        let content = "NextDNS;dns.nextdns.io\nMullvad;dns.mullvad.net:5353\nGoogle;8.8.8.8\n";
+        let custom = parse_custom_servers(content, IpVersion::V4, Path::new("test.txt")).unwrap();
+
+        assert_eq!(custom.servers.len(), 1);
+        assert_eq!(custom.hostnames.len(), 2);
+        assert_eq!(custom.hostnames[0].host, "dns.nextdns.io");
+        assert_eq!(custom.hostnames[0].port, 53);
+        assert_eq!(custom.hostnames[1].port, 5353);
+
+        let server = custom.hostnames[0].to_server("45.90.28.0".parse().unwrap());
+        assert_eq!(server.name, "NextDNS");
+        assert_eq!(server.addr.port(), 53);
+        assert_eq!(server.tls_name.as_deref(), Some("dns.nextdns.io"));
+    }
+
+    #[test]
+    fn test_parse_hostname() {
+        assert_eq!(parse_hostname("dns.google.", 53), Some(("dns.google".into(), 53)));
+        assert_eq!(parse_hostname("One.One.One.One:853", 53), Some(("one.one.one.one".into(), 853)));
+        assert_eq!(parse_hostname("1.1.1", 53), None);
+        assert_eq!(parse_hostname("bad host", 53), None);
+        assert_eq!(parse_hostname("-bad.example", 53), None);
+        assert_eq!(parse_hostname("dns.google:http", 53), None);
     }
 }
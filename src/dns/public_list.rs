@@ -0,0 +1,170 @@
+//! Converting public resolver lists into the custom servers format.
+//!
+//! The list format is the CSV published by public-dns.info
+//! (`ip_address,name,as_org,country_code,error,reliability,...`); extra
+//! columns are ignored.
+
+use crate::error::{DnsError, Error};
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// Base URL of the public-dns.info lists
+const PUBLIC_DNS_INFO_BASE: &str = "https://public-dns.info";
+
+/// Get the public-dns.info list URL, optionally for a single country
+pub fn public_dns_info_url(country: Option<&str>) -> String {
+    match country {
+        Some(cc) => format!("{PUBLIC_DNS_INFO_BASE}/nameserver/{}.csv", cc.to_lowercase()),
+        None => format!("{PUBLIC_DNS_INFO_BASE}/nameservers.csv"),
+    }
+}
+
+/// A resolver entry from a public list
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PublicResolver {
+    /// Resolver address
+    #[serde(rename = "ip_address")]
+    pub ip: IpAddr,
+    /// Reverse DNS name, if any
+    #[serde(default)]
+    pub name: String,
+    /// Owning organization (from the AS registry)
+    #[serde(default)]
+    pub as_org: String,
+    /// ISO 3166 country code
+    #[serde(default, rename = "country_code")]
+    pub country: String,
+    /// Last check error (empty if the resolver is working)
+    #[serde(default)]
+    pub error: String,
+    /// Fraction of checks the resolver passed (0.0 - 1.0)
+    #[serde(default)]
+    pub reliability: f64,
+}
+
+impl PublicResolver {
+    /// Human-readable name for the custom servers file
+    pub fn display_name(&self) -> String {
+        let name = self.name.trim().trim_end_matches('.');
+        let base = if !name.is_empty() {
+            name
+        } else if !self.as_org.trim().is_empty() {
+            self.as_org.trim()
+        } else {
+            return self.ip.to_string();
+        };
+
+        // ';' separates fields in the custom servers format
+        let base = base.replace(';', ",");
+        if self.country.is_empty() {
+            base
+        } else {
+            format!("{base} ({})", self.country.to_uppercase())
+        }
+    }
+}
+
+/// Selection applied to a public resolver list
+#[derive(Debug, Clone, Default)]
+pub struct PublicListFilter {
+    /// Only keep resolvers in these countries (all if empty)
+    pub countries: Vec<String>,
+    /// Minimum reliability (0.0 - 1.0)
+    pub min_reliability: f64,
+    /// Keep at most this many resolvers (most reliable first)
+    pub limit: Option<usize>,
+}
+
+impl PublicListFilter {
+    /// Filter and order resolvers, most reliable first
+    pub fn apply(&self, resolvers: Vec<PublicResolver>) -> Vec<PublicResolver> {
+        let mut kept: Vec<_> = resolvers
+            .into_iter()
+            .filter(|r| r.error.trim().is_empty() && r.reliability >= self.min_reliability)
+            .filter(|r| {
+                self.countries.is_empty() || self.countries.iter().any(|c| c.eq_ignore_ascii_case(&r.country))
+            })
+            .collect();
+
+        kept.sort_by(|a, b| b.reliability.total_cmp(&a.reliability));
+        if let Some(limit) = self.limit {
+            kept.truncate(limit);
+        }
+        kept
+    }
+}
+
+/// Parse a public-dns.info CSV list
+pub fn parse_public_dns_csv(content: &str, source: &str) -> Result<Vec<PublicResolver>, Error> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    reader
+        .deserialize()
+        .collect::<Result<Vec<PublicResolver>, _>>()
+        .map_err(|e| {
+            Error::Dns(DnsError::CustomFileError {
+                path: PathBuf::from(source),
+                message: e.to_string(),
+            })
+        })
+}
+
+/// Write resolvers in the `name;address` custom servers format
+pub fn write_public_servers(resolvers: &[PublicResolver], source: &str, writer: &mut dyn Write) -> io::Result<()> {
+    writeln!(writer, "# Public DNS resolvers from {source}")?;
+    writeln!(writer, "# Generated by dns-benchmark update-servers ({} servers)", resolvers.len())?;
+    for r in resolvers {
+        writeln!(writer, "{};{}", r.display_name(), r.ip)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::{parse_custom_servers, IpVersion};
+    use crate::load_test_fixture;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_and_filter_public_list() {
+        let content = load_test_fixture!("/public_dns/nameservers.csv");
+        let resolvers = parse_public_dns_csv(content, "nameservers.csv").unwrap();
+        assert_eq!(resolvers.len(), 5);
+
+        let filter = PublicListFilter {
+            countries: vec!["us".into()],
+            min_reliability: 0.9,
+            limit: None,
+        };
+        let kept = filter.apply(resolvers.clone());
+        let ips: Vec<_> = kept.iter().map(|r| r.ip.to_string()).collect();
+        assert_eq!(ips, vec!["8.8.8.8", "2001:4860:4860::8888"]);
+        assert_eq!(kept[0].display_name(), "dns.google (US)");
+
+        let all = PublicListFilter { limit: Some(2), ..Default::default() }.apply(resolvers);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].reliability, 1.0);
+    }
+
+    #[test]
+    fn test_written_list_loads_as_custom_servers() {
+        let content = load_test_fixture!("/public_dns/nameservers.csv");
+        let resolvers = PublicListFilter::default().apply(parse_public_dns_csv(content, "test").unwrap());
+
+        let mut output = Vec::new();
+        write_public_servers(&resolvers, "test", &mut output).unwrap();
+
+        let text = String::from_utf8(output).unwrap();
+        let custom = parse_custom_servers(&text, IpVersion::Both, Path::new("test")).unwrap();
+        assert_eq!(custom.servers.len(), resolvers.len());
+        assert!(custom.servers.iter().any(|s| s.name == "Example, Networks (DE)"));
+    }
+
+    #[test]
+    fn test_public_dns_info_url() {
+        assert_eq!(public_dns_info_url(Some("DE")), "https://public-dns.info/nameserver/de.csv");
+        assert_eq!(public_dns_info_url(None), "https://public-dns.info/nameservers.csv");
+    }
+}
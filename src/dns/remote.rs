@@ -0,0 +1,107 @@
+//! Fetching custom server lists over HTTPS.
+
+use crate::benchmark::stable_hash;
+use crate::error::{DnsError, Error};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long a downloaded server list is reused before fetching it again
+pub const REMOTE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Timeout for downloading a server list
+pub const REMOTE_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum accepted size of a server list
+const MAX_LIST_BYTES: u64 = 1024 * 1024;
+
+/// Fetch a server list from an `https://` URL
+///
+/// A copy is cached in `cache_dir` and reused for [`REMOTE_CACHE_TTL`]. If the
+/// download fails, a stale cached copy is used with a warning. This call blocks.
+pub fn fetch_server_list(url: &str, cache_dir: Option<&Path>) -> Result<String, Error> {
+    let url_error = |message: String| {
+        Error::Dns(DnsError::CustomFileError {
+            path: PathBuf::from(url),
+            message,
+        })
+    };
+
+    if !url.starts_with("https://") {
+        return Err(url_error("only https:// URLs are supported".into()));
+    }
+
+    let cache_path = cache_dir.map(|dir| dir.join(format!("servers-{}.list", stable_hash(url))));
+
+    if let Some(ref path) = cache_path
+        && is_fresh(path)
+        && let Ok(content) = fs::read_to_string(path)
+    {
+        return Ok(content);
+    }
+
+    match download_text(url, MAX_LIST_BYTES) {
+        Ok(content) => {
+            if let Some(ref path) = cache_path {
+                let _ = fs::create_dir_all(path.parent().unwrap_or(Path::new(".")));
+                let _ = fs::write(path, &content);
+            }
+            Ok(content)
+        }
+        Err(e) => match cache_path.and_then(|path| fs::read_to_string(path).ok()) {
+            Some(content) => {
+                tracing::warn!("Failed to fetch {url} ({e}); using cached copy");
+                Ok(content)
+            }
+            None => Err(url_error(e)),
+        },
+    }
+}
+
+/// Download a URL as text, reading at most `max_bytes`
+///
+/// This call blocks.
+pub fn download_text(url: &str, max_bytes: u64) -> Result<String, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(REMOTE_FETCH_TIMEOUT))
+        .build()
+        .into();
+
+    let mut response = agent.get(url).call().map_err(|e| e.to_string())?;
+    response
+        .body_mut()
+        .with_config()
+        .limit(max_bytes)
+        .read_to_string()
+        .map_err(|e| e.to_string())
+}
+
+/// Check if a cached file is younger than the cache TTL
+fn is_fresh(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < REMOTE_CACHE_TTL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_uses_fresh_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://example.invalid/servers.txt";
+        let cached = dir.path().join(format!("servers-{}.list", stable_hash(url)));
+        fs::write(&cached, "Cached;1.1.1.1\n").unwrap();
+
+        let content = fetch_server_list(url, Some(dir.path())).unwrap();
+        assert_eq!(content, "Cached;1.1.1.1\n");
+    }
+
+    #[test]
+    fn test_fetch_rejects_plain_http() {
+        assert!(fetch_server_list("http://example.com/servers.txt", None).is_err());
+    }
+}
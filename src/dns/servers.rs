@@ -1,5 +1,8 @@
 //! Built-in DNS server lists.
 
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
 use std::net::{Ipv4Addr, Ipv6Addr};
 
 /// Built-in IPv4 DNS servers: (name, ip)
@@ -40,6 +43,102 @@ pub static BUILTIN_SERVERS_V6: &[(&str, Ipv6Addr)] = &[
     ("AdGuard", Ipv6Addr::new(0x2a10, 0x50c0, 0, 0, 0, 0, 0x0ad2, 0x00ff)),
 ];
 
+/// What a resolver blocks by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filtering {
+    /// Answers every query as-is
+    None,
+    /// Blocks known malware and phishing domains
+    Security,
+    /// Blocks ads and trackers (and usually malware)
+    Ads,
+}
+
+impl fmt::Display for Filtering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Security => write!(f, "security"),
+            Self::Ads => write!(f, "ads"),
+        }
+    }
+}
+
+/// How a provider logs queries, per its published privacy policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingPolicy {
+    /// No query logs kept
+    None,
+    /// Only aggregated or anonymized statistics kept
+    Anonymized,
+    /// Logs with client IPs deleted within a few days
+    Temporary,
+    /// Logs kept long-term
+    Retained,
+}
+
+impl fmt::Display for LoggingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Anonymized => write!(f, "anonymized"),
+            Self::Temporary => write!(f, "temporary"),
+            Self::Retained => write!(f, "retained"),
+        }
+    }
+}
+
+/// Provider metadata for a DNS server
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    /// Operating organization
+    pub operator: Cow<'static, str>,
+    /// ISO 3166 country code of the operator's jurisdiction
+    pub country: Cow<'static, str>,
+    /// Served from many locations via anycast
+    pub anycast: bool,
+    /// Default filtering
+    pub filtering: Filtering,
+    /// Query logging policy
+    pub logging: LoggingPolicy,
+}
+
+impl ProviderInfo {
+    const fn builtin(
+        operator: &'static str,
+        country: &'static str,
+        filtering: Filtering,
+        logging: LoggingPolicy,
+    ) -> Self {
+        Self {
+            operator: Cow::Borrowed(operator),
+            country: Cow::Borrowed(country),
+            anycast: true,
+            filtering,
+            logging,
+        }
+    }
+}
+
+/// Metadata for the providers in the built-in lists: (name, info)
+pub static BUILTIN_PROVIDERS: &[(&str, ProviderInfo)] = &[
+    ("Google", ProviderInfo::builtin("Google LLC", "US", Filtering::None, LoggingPolicy::Temporary)),
+    ("Cloudflare", ProviderInfo::builtin("Cloudflare, Inc.", "US", Filtering::None, LoggingPolicy::Temporary)),
+    ("Quad9", ProviderInfo::builtin("Quad9 Foundation", "CH", Filtering::Security, LoggingPolicy::None)),
+    ("OpenDNS", ProviderInfo::builtin("Cisco Systems, Inc.", "US", Filtering::Security, LoggingPolicy::Retained)),
+    ("AdGuard", ProviderInfo::builtin("AdGuard Software Ltd", "CY", Filtering::Ads, LoggingPolicy::Anonymized)),
+];
+
+/// Look up metadata for a built-in provider by name
+pub fn builtin_provider(name: &str) -> Option<&'static ProviderInfo> {
+    BUILTIN_PROVIDERS
+        .iter()
+        .find(|(provider, _)| *provider == name)
+        .map(|(_, info)| info)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +170,14 @@ mod tests {
             assert!(!ip.is_unspecified());
         }
     }
+
+    #[test]
+    fn test_builtin_servers_have_provider_info() {
+        let names = BUILTIN_SERVERS_V4.iter().map(|(n, _)| n).chain(BUILTIN_SERVERS_V6.iter().map(|(n, _)| n));
+        for name in names {
+            assert!(builtin_provider(name).is_some(), "missing metadata for {name}");
+        }
+        assert_eq!(builtin_provider("Quad9").unwrap().filtering, Filtering::Security);
+        assert!(builtin_provider("Unknown").is_none());
+    }
 }
@@ -0,0 +1,310 @@
+//! Mixed query workloads.
+//!
+//! Real clients don't only ask for the A record of one name: browsers ask
+//! for AAAA (and HTTPS) alongside, mail servers for MX, and many tools for
+//! TXT, across many names. A [`Workload`] gives each query (a record type,
+//! optionally of its own domain) a weight, and every server is sent the same
+//! interleaved sequence of queries in those proportions.
+//!
+//! Workloads are given inline, like `A=60,AAAA=30,mail.example.com/MX=10`,
+//! or loaded from a TOML or JSON file with one entry per query:
+//!
+//! ```toml
+//! [[queries]]
+//! domain = "www.example.com"
+//! type = "AAAA"
+//! weight = 40
+//! ```
+
+use crate::error::Error;
+use crate::scenario::RECORD_TYPES;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// One kind of query in a workload
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkloadQuery {
+    /// Name to query; the benchmark domain if not set
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Record type to query (upper case)
+    #[serde(rename = "type", default = "default_type")]
+    pub record_type: String,
+    /// Relative weight
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_type() -> String {
+    "A".to_string()
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl WorkloadQuery {
+    /// Check the record type, upper-casing it, the domain, and the weight
+    fn validate(mut self) -> Result<Self, String> {
+        let record_type = self.record_type.to_uppercase();
+        if !RECORD_TYPES.contains(&record_type.as_str()) {
+            return Err(format!("unsupported record type '{}'", self.record_type));
+        }
+        self.record_type = record_type;
+        if let Some(ref domain) = self.domain
+            && (domain.is_empty() || domain.contains(['/', '=', ',', ' ']))
+        {
+            return Err(format!("invalid domain '{domain}'"));
+        }
+        if self.weight == 0 {
+            return Err(format!("weight of {self} must be positive"));
+        }
+        Ok(self)
+    }
+}
+
+impl fmt::Display for WorkloadQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.domain {
+            Some(ref domain) => write!(f, "{domain}/{}", self.record_type),
+            None => write!(f, "{}", self.record_type),
+        }
+    }
+}
+
+/// Workload file: a list of queries
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkloadFile {
+    queries: Vec<WorkloadQuery>,
+}
+
+/// Weighted mix of queries
+///
+/// Parsed from a preset name (`web`) or a comma-separated list of
+/// `[DOMAIN/]TYPE[=WEIGHT]` entries, like `A=60,AAAA=30,TXT=10`. Weights are
+/// relative and an entry without one weighs 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Workload {
+    /// Queries in the order given
+    queries: Vec<WorkloadQuery>,
+}
+
+impl Workload {
+    /// Browsing traffic: mostly A, a quarter AAAA, and a few MX and TXT queries
+    pub fn web() -> Self {
+        let query = |record_type: &str, weight| WorkloadQuery {
+            domain: None,
+            record_type: record_type.to_string(),
+            weight,
+        };
+        Self {
+            queries: vec![query("A", 70), query("AAAA", 25), query("MX", 3), query("TXT", 2)],
+        }
+    }
+
+    /// Load a workload file, TOML or JSON (by extension, falling back to the content)
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path).map_err(|e| Error::Workload {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&content, path)
+    }
+
+    /// Parse a workload file's content
+    ///
+    /// `path` picks the format and is used for error messages.
+    pub fn parse(content: &str, path: &Path) -> Result<Self, Error> {
+        let error = |message: String| Error::Workload {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let json = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => ext.eq_ignore_ascii_case("json"),
+            None => content.trim_start().starts_with('{'),
+        };
+        let file: WorkloadFile = if json {
+            serde_json::from_str(content).map_err(|e| error(e.to_string()))?
+        } else {
+            toml::from_str(content).map_err(|e| error(e.to_string()))?
+        };
+
+        let queries = file
+            .queries
+            .into_iter()
+            .map(WorkloadQuery::validate)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        Self::new(queries).map_err(error)
+    }
+
+    /// Build a workload, rejecting empty lists and repeated entries
+    fn new(queries: Vec<WorkloadQuery>) -> Result<Self, String> {
+        if queries.is_empty() {
+            return Err("no queries".to_string());
+        }
+        for (i, query) in queries.iter().enumerate() {
+            if queries[..i].iter().any(|q| q.domain == query.domain && q.record_type == query.record_type) {
+                return Err(format!("{query} is listed twice"));
+            }
+        }
+        Ok(Self { queries })
+    }
+
+    /// Queries and their share of the total, in percent
+    pub fn shares(&self) -> impl Iterator<Item = (&WorkloadQuery, f64)> {
+        // Summed as f64, as large weights would overflow a u32
+        let total: f64 = self.queries.iter().map(|q| f64::from(q.weight)).sum();
+        self.queries
+            .iter()
+            .map(move |query| (query, f64::from(query.weight) * 100.0 / total))
+    }
+
+    /// `len` queries, interleaved in proportion to their weights
+    ///
+    /// Uses smooth weighted round-robin, so any run of queries is as close to
+    /// the mix as its length allows and every server gets the same sequence.
+    pub fn schedule(&self, len: usize) -> Vec<&WorkloadQuery> {
+        let total: i64 = self.queries.iter().map(|q| i64::from(q.weight)).sum();
+        let mut current = vec![0i64; self.queries.len()];
+        (0..len)
+            .map(|_| {
+                for (credit, query) in current.iter_mut().zip(&self.queries) {
+                    *credit += i64::from(query.weight);
+                }
+                // First of the highest credits, so earlier queries win ties
+                let (next, _) = current
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .max_by_key(|&(_, credit)| credit)
+                    .expect("Workloads have at least one query");
+                current[next] -= total;
+                &self.queries[next]
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, query) in self.queries.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{query}={}", query.weight)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Workload {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("web") {
+            return Ok(Self::web());
+        }
+
+        let error = |message: String| Error::InvalidArgument(format!("Invalid workload: {message}"));
+        let mut queries = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (query, weight) = match part.split_once('=') {
+                Some((query, weight)) => {
+                    let weight = weight.trim().parse().map_err(|_| error(format!("invalid weight in '{part}'")))?;
+                    (query.trim(), weight)
+                }
+                None => (part, 1),
+            };
+            let (domain, record_type) = match query.rsplit_once('/') {
+                Some((domain, record_type)) => (Some(domain.to_string()), record_type),
+                None => (None, query),
+            };
+            let query = WorkloadQuery {
+                domain,
+                record_type: record_type.to_string(),
+                weight,
+            };
+            queries.push(query.validate().map_err(error)?);
+        }
+        Self::new(queries).map_err(error)
+    }
+}
+
+impl TryFrom<String> for Workload {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Workload> for String {
+    fn from(workload: Workload) -> Self {
+        workload.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workload_parsing() {
+        assert_eq!("WEB".parse::<Workload>().unwrap(), Workload::web());
+        let workload: Workload = "a=3, mail.example.com/mx".parse().unwrap();
+        assert_eq!(workload.to_string(), "A=3,mail.example.com/MX=1");
+        let shares: Vec<_> = workload.shares().map(|(q, share)| (q.to_string(), share)).collect();
+        assert_eq!(shares, [("A".to_string(), 75.0), ("mail.example.com/MX".to_string(), 25.0)]);
+        assert_eq!(workload.to_string().parse::<Workload>().unwrap(), workload);
+
+        assert!("A=0".parse::<Workload>().is_err());
+        assert!("A=1,A=2".parse::<Workload>().is_err());
+        assert!("NAPTR=1".parse::<Workload>().is_err());
+        assert!("/A".parse::<Workload>().is_err());
+        assert!(" , ".parse::<Workload>().is_err());
+
+        let heavy: Workload = format!("A={0},MX={0}", u32::MAX).parse().unwrap();
+        assert!(heavy.shares().all(|(_, share)| share == 50.0));
+        assert_eq!(heavy.schedule(4).len(), 4);
+    }
+
+    #[test]
+    fn test_workload_schedule() {
+        let web = Workload::web();
+        let schedule: Vec<_> = web.schedule(100).iter().map(|q| q.record_type.as_str()).collect();
+        let count = |t| schedule.iter().filter(|&&s| s == t).count();
+        assert_eq!([count("A"), count("AAAA"), count("MX"), count("TXT")], [70, 25, 3, 2]);
+
+        // Queries are interleaved rather than sent in blocks
+        let workload: Workload = "A=2,MX=1".parse().unwrap();
+        let schedule: Vec<_> = workload.schedule(6).iter().map(|q| q.record_type.as_str()).collect();
+        assert_eq!(schedule, ["A", "MX", "A", "A", "MX", "A"]);
+    }
+
+    #[test]
+    fn test_workload_file() {
+        let toml = "[[queries]]\ndomain = \"www.example.com\"\nweight = 8\n\n\
+                    [[queries]]\ndomain = \"example.com\"\ntype = \"mx\"\n";
+        let workload = Workload::parse(toml, Path::new("traffic.toml")).unwrap();
+        assert_eq!(workload.to_string(), "www.example.com/A=8,example.com/MX=1");
+
+        let json = r#"{"queries": [
+            {"domain": "www.example.com", "weight": 8},
+            {"domain": "example.com", "type": "MX"}
+        ]}"#;
+        assert_eq!(Workload::parse(json, Path::new("traffic")).unwrap(), workload);
+
+        let error = Workload::parse("[[queries]]\ntype = \"BOGUS\"\n", Path::new("bad.toml")).unwrap_err();
+        assert!(error.to_string().contains("bad.toml: unsupported record type 'BOGUS'"), "{error}");
+        assert!(Workload::parse("queries = []\n", Path::new("empty.toml")).is_err());
+        assert!(Workload::parse("[[queries]]\nname = \"x\"\n", Path::new("typo.toml")).is_err());
+    }
+}
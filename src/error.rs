@@ -2,6 +2,7 @@
 
 use std::io;
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Main error type for the library
@@ -30,6 +31,34 @@ pub enum Error {
     /// Invalid argument
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+
+    /// Invalid scenario file
+    #[error("Invalid scenario file {path} at line {line}: {message}")]
+    Scenario { path: PathBuf, line: usize, message: String },
+
+    /// Unreadable GeoIP database
+    #[error("Failed to open GeoIP database at {path}: {message}")]
+    GeoIp { path: PathBuf, message: String },
+
+    /// Unreadable or unsupported benchmark template
+    #[error("Invalid template {path}: {message}")]
+    Template { path: PathBuf, message: String },
+
+    /// Unreadable or invalid workload file
+    #[error("Invalid workload file {path}: {message}")]
+    Workload { path: PathBuf, message: String },
+
+    /// Unreadable or unwritable run history database
+    #[error("Run history error at {path}: {message}")]
+    History { path: PathBuf, message: String },
+
+    /// Failed result submission
+    #[error("Failed to share results with {endpoint}: {message}")]
+    Share { endpoint: String, message: String },
+
+    /// Failed download of community statistics
+    #[error("Failed to fetch community statistics from {endpoint}: {message}")]
+    CommunityStats { endpoint: String, message: String },
 }
 
 /// Configuration-related errors
@@ -87,6 +116,10 @@ pub enum DnsError {
     #[error("DNS resolution failed: {0}")]
     ResolutionFailed(String),
 
+    /// No zone with NS records contains the domain
+    #[error("No authoritative name servers found for {0}")]
+    NoNameServers(String),
+
     /// Invalid server address
     #[error("Invalid DNS server address: {0}")]
     InvalidAddress(String),
@@ -96,7 +129,7 @@ pub enum DnsError {
     CustomFileError { path: PathBuf, message: String },
 
     /// Invalid line in custom servers file
-    #[error("Invalid line format at line {line}: expected 'name;address:port'")]
+    #[error("Invalid line format at line {line}: expected 'name;address[:port]'")]
     InvalidLineFormat { line: usize },
 }
 
@@ -135,6 +168,10 @@ pub enum PlatformError {
     #[error("Failed to detect gateway: {0}")]
     GatewayDetection(String),
 
+    /// Listing the network interfaces failed
+    #[error("Failed to list network interfaces: {0}")]
+    InterfaceDetection(String),
+
     /// Unsupported platform
     #[error("Unsupported platform")]
     UnsupportedPlatform,
@@ -143,6 +180,10 @@ pub enum PlatformError {
     #[error("Failed to execute command '{command}': {message}")]
     CommandFailed { command: String, message: String },
 
+    /// Command ran past its timeout and was killed
+    #[error("Command '{command}' timed out after {}s", timeout.as_secs_f64())]
+    CommandTimeout { command: String, timeout: Duration },
+
     /// Parse error
     #[error("Failed to parse output: {0}")]
     ParseError(String),
@@ -151,6 +192,7 @@ pub enum PlatformError {
 /// Result type alias using our Error
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(feature = "engine")]
 impl From<hickory_resolver::ResolveError> for DnsError {
     fn from(e: hickory_resolver::ResolveError) -> Self {
         let msg = e.to_string().to_lowercase();
@@ -164,6 +206,7 @@ impl From<hickory_resolver::ResolveError> for DnsError {
     }
 }
 
+#[cfg(feature = "engine")]
 impl From<hickory_resolver::ResolveError> for Error {
     fn from(e: hickory_resolver::ResolveError) -> Self {
         Error::Dns(e.into())
@@ -0,0 +1,356 @@
+//! Persistent run history in a local SQLite database.
+//!
+//! Every benchmark run is stored with a snapshot of its configuration and
+//! one row per server, so results can be analyzed over weeks or months with
+//! `history list`, `history show`, or any SQLite client.
+
+use crate::benchmark::{BenchmarkResult, NetworkFingerprint, SerializableResult};
+use crate::config::{Config, SortKey};
+use crate::error::Error;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Database schema; tables are created on first use
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at INTEGER NOT NULL,
+    domain TEXT NOT NULL,
+    requests_per_server INTEGER NOT NULL,
+    duration_ms REAL NOT NULL,
+    config TEXT NOT NULL,
+    fingerprint TEXT
+);
+CREATE TABLE IF NOT EXISTS server_results (
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    success_rate REAL NOT NULL,
+    min_ms REAL,
+    max_ms REAL,
+    avg_ms REAL,
+    result TEXT NOT NULL,
+    PRIMARY KEY (run_id, position)
+);
+CREATE INDEX IF NOT EXISTS server_results_ip ON server_results (ip);
+";
+
+/// One line of `history list`
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Run ID, as taken by `history show`
+    pub id: i64,
+    /// Local start time (`YYYY-MM-DD HH:MM:SS`)
+    pub started_at: String,
+    /// Domain that was tested
+    pub domain: String,
+    /// Number of servers benchmarked
+    pub servers: u32,
+    /// Name of the server with the lowest average time, whatever order the run was sorted in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fastest: Option<String>,
+    /// Fastest server's average time (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fastest_ms: Option<f64>,
+}
+
+/// A stored run with its per-server results
+///
+/// Serializes like JSON output (`meta` and `results`), so a run written with
+/// `history show --format json` can be passed to `compare`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryRun {
+    /// Run metadata
+    pub meta: HistoryMeta,
+    /// Per-server results, fastest first
+    pub results: Vec<SerializableResult>,
+}
+
+impl HistoryRun {
+    /// Up to `n` servers that answered, fastest first
+    ///
+    /// Runs sorted by score (the default) are stored in ranking order, which
+    /// reflects the run's ranking weights; other runs are re-ranked by
+    /// average time. Servers below the run's minimum success rate are left out.
+    pub fn fastest(&self, n: usize) -> Vec<&SerializableResult> {
+        let config = &self.meta.config;
+        let mut servers: Vec<_> = self
+            .results
+            .iter()
+            .filter(|s| s.avg_ms.is_some())
+            .filter(|s| config.ranking.min_success.is_none_or(|min| s.success_rate >= min))
+            .collect();
+        if config.sort_by != SortKey::Score || config.reverse {
+            servers.sort_by(|a, b| a.avg_ms.unwrap_or_default().total_cmp(&b.avg_ms.unwrap_or_default()));
+        }
+        servers.truncate(n);
+        servers
+    }
+}
+
+/// Metadata of a stored run
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryMeta {
+    /// Run ID
+    pub id: i64,
+    /// Local start time (`YYYY-MM-DD HH:MM:SS`)
+    pub started_at: String,
+    /// Domain that was tested
+    pub domain: String,
+    /// Number of requests per server
+    pub requests_per_server: u32,
+    /// Total run duration (ms)
+    pub duration_ms: f64,
+    /// Network the run was taken on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<NetworkFingerprint>,
+    /// Configuration the run used
+    pub config: Config,
+}
+
+/// Run history database
+pub struct History {
+    conn: Connection,
+    path: PathBuf,
+}
+
+impl History {
+    /// Open the history database at `path`, creating it if needed
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path).map_err(|e| history_error(path, e))?;
+        conn.execute_batch(SCHEMA).map_err(|e| history_error(path, e))?;
+        Ok(Self {
+            conn,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Store a finished run and return its ID
+    pub fn record(&mut self, result: &BenchmarkResult, config: &Config) -> Result<i64, Error> {
+        let started_at = SystemTime::now()
+            .checked_sub(result.duration)
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let config_json = serde_json::to_string(config).map_err(|e| self.error(e))?;
+        let fingerprint = result
+            .fingerprint
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| self.error(e))?;
+
+        let tx = self.conn.transaction().map_err(|e| history_error(&self.path, e))?;
+        tx.execute(
+            "INSERT INTO runs (started_at, domain, requests_per_server, duration_ms, config, fingerprint)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                started_at,
+                result.domain,
+                result.requests_per_server,
+                result.duration.as_secs_f64() * 1000.0,
+                config_json,
+                fingerprint
+            ],
+        )
+        .map_err(|e| history_error(&self.path, e))?;
+        let id = tx.last_insert_rowid();
+
+        for (position, server) in result.servers.iter().enumerate() {
+            let row = SerializableResult::from_benchmark(server, result);
+            let json = serde_json::to_string(&row).map_err(|e| history_error(&self.path, e))?;
+            tx.execute(
+                "INSERT INTO server_results (run_id, position, name, ip, success_rate, min_ms, max_ms, avg_ms, result)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    id,
+                    position as i64,
+                    row.name,
+                    row.ip,
+                    row.success_rate,
+                    row.min_ms,
+                    row.max_ms,
+                    row.avg_ms,
+                    json
+                ],
+            )
+            .map_err(|e| history_error(&self.path, e))?;
+        }
+
+        tx.commit().map_err(|e| history_error(&self.path, e))?;
+        Ok(id)
+    }
+
+    /// List the most recent runs, newest first
+    pub fn list(&self, limit: u32) -> Result<Vec<RunSummary>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT r.id, datetime(r.started_at, 'unixepoch', 'localtime'), r.domain,
+                        (SELECT COUNT(*) FROM server_results s WHERE s.run_id = r.id),
+                        f.name, f.avg_ms
+                 FROM runs r
+                 LEFT JOIN server_results f ON f.rowid = (
+                     SELECT s.rowid FROM server_results s
+                     WHERE s.run_id = r.id AND s.avg_ms IS NOT NULL
+                     ORDER BY s.avg_ms, s.position
+                     LIMIT 1
+                 )
+                 ORDER BY r.id DESC
+                 LIMIT ?1",
+            )
+            .map_err(|e| self.error(e))?;
+        let rows = stmt
+            .query_map([limit], |row| {
+                Ok(RunSummary {
+                    id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    domain: row.get(2)?,
+                    servers: row.get(3)?,
+                    fastest: row.get(4)?,
+                    fastest_ms: row.get(5)?,
+                })
+            })
+            .map_err(|e| self.error(e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| self.error(e))
+    }
+
+    /// Load a stored run, or `None` if there's no run with this ID
+    pub fn get(&self, id: i64) -> Result<Option<HistoryRun>, Error> {
+        let run = self
+            .conn
+            .query_row(
+                "SELECT datetime(started_at, 'unixepoch', 'localtime'), domain, requests_per_server, duration_ms,
+                        config, fingerprint
+                 FROM runs WHERE id = ?1",
+                [id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, u32>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                    ))
+                },
+            )
+            .optional()
+            .map_err(|e| self.error(e))?;
+        let Some((started_at, domain, requests_per_server, duration_ms, config, fingerprint)) = run else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT result FROM server_results WHERE run_id = ?1 ORDER BY position")
+            .map_err(|e| self.error(e))?;
+        let results = stmt
+            .query_map([id], |row| row.get::<_, String>(0))
+            .map_err(|e| self.error(e))?
+            .map(|json| {
+                let json = json.map_err(|e| self.error(e))?;
+                serde_json::from_str(&json).map_err(|e| self.error(e))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Some(HistoryRun {
+            meta: HistoryMeta {
+                id,
+                started_at,
+                domain,
+                requests_per_server,
+                duration_ms,
+                // Snapshots from older versions may lack newer fields; those take their defaults
+                fingerprint: fingerprint.and_then(|f| serde_json::from_str(&f).ok()),
+                config: serde_json::from_str(&config).map_err(|e| self.error(e))?,
+            },
+            results,
+        }))
+    }
+
+    fn error(&self, e: impl std::fmt::Display) -> Error {
+        history_error(&self.path, e)
+    }
+}
+
+fn history_error(path: &Path, e: impl std::fmt::Display) -> Error {
+    Error::History {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::ServerResult;
+    use std::time::Duration;
+
+    fn result() -> BenchmarkResult {
+        BenchmarkResult {
+            duration: Duration::from_secs(3),
+            requests_per_server: 1,
+            ..BenchmarkResult::for_test(vec![
+                ServerResult::answered("Cloudflare", "1.1.1.1", 12),
+                ServerResult::answered("Google", "8.8.8.8", 20),
+            ])
+        }
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::open(&dir.path().join("history.db")).unwrap();
+        let config = Config::builder().requests(1).build();
+
+        let first = history.record(&result(), &config).unwrap();
+        let second = history.record(&result(), &config).unwrap();
+        assert!(second > first);
+
+        let runs = history.list(10).unwrap();
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![second, first]);
+        assert_eq!(runs[0].servers, 2);
+        assert_eq!(runs[0].fastest.as_deref(), Some("Cloudflare"));
+        assert_eq!(runs[0].fastest_ms, Some(12.0));
+        assert_eq!(history.list(1).unwrap().len(), 1);
+
+        let run = history.get(first).unwrap().unwrap();
+        assert_eq!(run.meta.domain, "example.com");
+        assert_eq!(run.meta.config, config);
+        assert_eq!(run.results.len(), 2);
+        assert_eq!(run.results[1].ip, "8.8.8.8");
+        assert_eq!(run.results[1].avg_ms, Some(20.0));
+
+        assert!(history.get(second + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_history_run_fastest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::open(&dir.path().join("history.db")).unwrap();
+        let mut reversed = result();
+        reversed.servers.reverse();
+        let names = |run: &HistoryRun, n: usize| run.fastest(n).iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+
+        // Runs ranked by score keep their stored order
+        let id = history.record(&reversed, &Config::default()).unwrap();
+        assert_eq!(names(&history.get(id).unwrap().unwrap(), 1), ["Google"]);
+
+        // Runs sorted otherwise are re-ranked by average time
+        let id = history.record(&reversed, &Config::builder().sort_by(SortKey::Name).build()).unwrap();
+        assert_eq!(names(&history.get(id).unwrap().unwrap(), 5), ["Cloudflare", "Google"]);
+
+        // The run list picks the fastest by average time too
+        let runs = history.list(10).unwrap();
+        assert!(runs.iter().all(|run| run.fastest.as_deref() == Some("Cloudflare")));
+        assert!(runs.iter().all(|run| run.fastest_ms == Some(12.0)));
+    }
+}
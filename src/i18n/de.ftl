@@ -0,0 +1,133 @@
+## Errors
+error-prefix = Fehler:
+error-no-servers = Keine DNS-Server zum Testen
+
+## Run summary printed before the benchmark
+start-title = DNS-Benchmark wird gestartet
+start-domain = Domain:
+start-scope = Umfang:
+start-scope-value = { $servers } Server × { $requests } Anfragen = { $total } gesamt
+start-browser = Browser:
+start-browser-value = { $loads } Seitenaufrufe × { $domains } Domains pro Server
+start-dnssec = DNSSEC:
+start-dnssec-value = { $pairs } Anfragepaare pro Server mit und ohne Validierung
+start-negative-cache = NXDOMAIN:
+start-negative-cache-value = 1 + { $repeats } Anfragen pro Server nach einem nicht existierenden Namen, erster Fehltreffer vs. Cache
+start-edns = EDNS:
+start-edns-value = { $payload } Byte UDP-Nutzlast angekündigt, gekürzte Antworten per TCP wiederholt
+start-verify = Prüfung:
+start-verify-value = Antworten verglichen mit { $ip }
+start-authoritative = Modus:
+start-authoritative-value = autoritative Nameserver, nicht-rekursive Anfragen
+start-search = Suche:
+start-search-value = { $domains } an kurze Namen angehängt
+start-split-horizon = Split-DNS:
+start-split-horizon-value = { $names } sollte nur über System-, Gateway- und DHCP-Resolver auflösen
+start-vpn = VPN:
+start-vpn-value = Route zu jedem Server mit dem VPN-Tunnel verglichen
+start-ttl = TTL:
+start-ttl-value = Antwort-TTLs verglichen mit dem autoritativen Server
+start-node-id = Knoten:
+start-node-id-value = Anycast-Knoten alle { $slice } Anfragen ermittelt
+start-ecs = ECS:
+start-ecs-value = Client-Subnetz { $subnet } an UDP-Anfragen angehängt
+start-workload = Last:
+start-workload-value = { $mix } der Anfragen
+start-domains = Domains:
+start-domains-popular = die { $count } beliebtesten Domains, abwechselnd
+start-domains-names = { $names }, abwechselnd
+start-order = Reihenfolge:
+start-order-value = Server gemischt mit Seed { $seed }
+start-protocols = Protokolle:
+start-protocols-value = jeder Server gemessen über { $protocols }
+start-probes = Tests:
+start-probes-value = { $probes } (je { $budget } s Zeitbudget, nach der Latenzmessung)
+start-config = Konfiguration:
+start-config-value = { $workers } Worker, { $slice } Anfragen pro Runde, { $timeout } s Timeout, { $protocol }
+progress-benchmarking = { $servers } Server werden getestet
+progress-live-more = … und { $count } weitere
+
+## Result table headers
+col-server = Server
+col-ip = IP-Adresse
+col-source = Quelle
+col-protocol = Protokoll
+col-resolved-ip = Aufgelöste IP
+col-success-rate = Erfolgsquote
+col-min = Min
+col-max = Max
+col-avg-sorted = Mittel ↑
+col-jitter = ±Jitter
+col-sparkline = Latenzverlauf
+col-failures = Fehler
+col-trend = Trend
+col-reference = vs. Ref
+col-page-load = Seitenaufruf
+col-dnssec-cost = DNSSEC-Kosten
+col-nxdomain = NXDOMAIN Fehltreffer → Cache
+col-truncated = Gekürzt
+col-ecs = ECS
+col-answers = Antworten
+col-internal = Intern
+col-vpn = VPN-Route
+col-avg-ttl = Ø TTL
+col-node = Knoten
+col-filtering = Filterung
+col-probes = Tests
+col-warnings = Warnungen
+col-server-as = Server-AS
+col-answer-as = Antwort-AS
+col-provider = Anbieter
+col-servers = Server
+col-best-sorted = Bestwert ↑
+col-avg = Mittel
+col-ipv4-avg = IPv4-Mittel
+col-ipv6-avg = IPv6-Mittel
+col-dual-stack-delta = Δ (v6 − v4)
+col-fastest = Schnellstes
+protocols-handshake-note = * Jede Abfrage öffnet eine neue Verbindung, die Zeiten enthalten also den TCP- und TLS-Handshake
+
+## Summary printed after the results
+legend = Legende:
+summary-completed = Benchmark abgeschlossen in { $duration }
+summary-phases = Zeitaufwand: { $phases }
+phase-collection = Serverlisten
+phase-detection = Erkennung
+phase-benchmarking = Messung
+phase-probing = Prüfungen
+phase-formatting = Ausgabe
+summary-fastest = Am schnellsten: { $name } ({ $ip }) - { $time }
+summary-all-failed = { $count ->
+    [one] { $count } Server hat keine Anfrage beantwortet
+   *[other] { $count } Server haben keine Anfrage beantwortet
+}
+summary-all-failed-hidden = { $count ->
+    [one] { $count } Server hat keine Anfrage beantwortet und ist ausgeblendet
+   *[other] { $count } Server haben keine Anfrage beantwortet und sind ausgeblendet
+}
+summary-ineligible = { $count ->
+    [one] { $count } Server hat weniger als { $rate }% der Anfragen beantwortet und gilt nicht als schnellster
+   *[other] { $count } Server haben weniger als { $rate }% der Anfragen beantwortet und gelten nicht als schnellste
+}
+summary-reference = Referenz: { $name } ({ $ip }) - { $time }
+summary-noise-floor = Rauschgrenze: { $floor } (Loopback { $loopback } µs, Jitter { $jitter } µs) - kleinere Unterschiede sind nicht aussagekräftig
+summary-authoritative-ttl = Autoritative TTL: { $ttl } s
+summary-split-horizon-ok = Split-DNS: interne Namen lösen nur über System-, Gateway- und DHCP-Resolver auf
+summary-split-horizon-issues = Split-DNS: { $leaks } öffentliche Resolver beantworten interne Namen, { $missing } System-, Gateway- oder DHCP-Resolver lösen sie nicht auf
+summary-vpn-ok = VPN: alle Server werden über den Tunnel erreicht oder laufen auf diesem Rechner
+summary-vpn-leaks = { $count ->
+    [one] VPN: { $count } Server umgeht den Tunnel und gilt nicht als schnellster
+   *[other] VPN: { $count } Server umgehen den Tunnel und gelten nicht als schnellste
+}
+
+## Markdown report
+report-title = DNS-Benchmark: { $domain }
+report-summary = Zusammenfassung
+report-results = Ergebnisse
+col-p95 = P95
+report-failures = Fehler
+report-scope = { $servers } Server, je { $requests } Anfragen
+report-system = System-DNS: { $name } ({ $ip }) auf Platz { $rank } von { $total } - { $time }
+report-answered = { $successful } von { $total } Anfragen beantwortet
+report-last-error = letzter Fehler: { $error }
+report-no-failures = Alle Server haben alle Anfragen beantwortet.
@@ -0,0 +1,133 @@
+## Errors
+error-prefix = Error:
+error-no-servers = No DNS servers to benchmark
+
+## Run summary printed before the benchmark
+start-title = Starting DNS benchmark
+start-domain = Domain:
+start-scope = Scope:
+start-scope-value = { $servers } servers × { $requests } requests = { $total } total
+start-browser = Browser:
+start-browser-value = { $loads } page loads × { $domains } domains per server
+start-dnssec = DNSSEC:
+start-dnssec-value = { $pairs } query pairs per server with and without validation
+start-negative-cache = NXDOMAIN:
+start-negative-cache-value = 1 + { $repeats } queries per server for a nonexistent name, first miss vs cached
+start-edns = EDNS:
+start-edns-value = { $payload } byte UDP payload advertised, truncated answers retried over TCP
+start-verify = Verify:
+start-verify-value = answers checked against { $ip }
+start-authoritative = Mode:
+start-authoritative-value = authoritative name servers, non-recursive queries
+start-search = Search:
+start-search-value = { $domains } appended to short names
+start-split-horizon = Split DNS:
+start-split-horizon-value = { $names } should resolve only through system, gateway, and DHCP resolvers
+start-vpn = VPN:
+start-vpn-value = route to each server checked against the VPN tunnel
+start-ttl = TTL:
+start-ttl-value = answer TTLs compared to the authoritative server
+start-node-id = Nodes:
+start-node-id-value = anycast node identified every { $slice } queries
+start-ecs = ECS:
+start-ecs-value = client subnet { $subnet } attached to UDP queries
+start-workload = Workload:
+start-workload-value = { $mix } of queries
+start-domains = Domains:
+start-domains-popular = top { $count } popular domains, in turn
+start-domains-names = { $names }, in turn
+start-order = Order:
+start-order-value = servers shuffled with seed { $seed }
+start-protocols = Protocols:
+start-protocols-value = every server measured over { $protocols }
+start-probes = Probes:
+start-probes-value = { $probes } ({ $budget }s budget each, after latency measurement)
+start-config = Config:
+start-config-value = { $workers } workers, { $slice } queries per turn, { $timeout }s timeout, { $protocol }
+progress-benchmarking = Benchmarking { $servers } servers
+progress-live-more = … and { $count } more
+
+## Result table headers
+col-server = Server
+col-ip = IP Address
+col-source = Source
+col-protocol = Protocol
+col-resolved-ip = Resolved IP
+col-success-rate = Success Rate
+col-min = Min
+col-max = Max
+col-avg-sorted = Avg ↑
+col-jitter = ±Jitter
+col-sparkline = Latency Over Time
+col-failures = Failures
+col-trend = Trend
+col-reference = vs Ref
+col-page-load = Page Load
+col-dnssec-cost = DNSSEC Cost
+col-nxdomain = NXDOMAIN Miss → Cached
+col-truncated = Truncated
+col-ecs = ECS
+col-answers = Answers
+col-internal = Internal
+col-vpn = VPN Route
+col-avg-ttl = Avg TTL
+col-node = Node
+col-filtering = Filtering
+col-probes = Probes
+col-warnings = Warnings
+col-server-as = Server AS
+col-answer-as = Answer AS
+col-provider = Provider
+col-servers = Servers
+col-best-sorted = Best ↑
+col-avg = Avg
+col-ipv4-avg = IPv4 Avg
+col-ipv6-avg = IPv6 Avg
+col-dual-stack-delta = Δ (v6 − v4)
+col-fastest = Fastest
+protocols-handshake-note = * Every query opens a new connection, so these times include the TCP and TLS handshakes
+
+## Summary printed after the results
+legend = Legend:
+summary-completed = Benchmark completed in { $duration }
+summary-phases = Time spent: { $phases }
+phase-collection = server lists
+phase-detection = detection
+phase-benchmarking = benchmarking
+phase-probing = probes
+phase-formatting = output
+summary-fastest = Fastest: { $name } ({ $ip }) - { $time }
+summary-all-failed = { $count ->
+    [one] { $count } server failed all requests
+   *[other] { $count } servers failed all requests
+}
+summary-all-failed-hidden = { $count ->
+    [one] { $count } server failed all requests and is hidden
+   *[other] { $count } servers failed all requests and are hidden
+}
+summary-ineligible = { $count ->
+    [one] { $count } server answered less than { $rate }% of queries and was not considered fastest
+   *[other] { $count } servers answered less than { $rate }% of queries and were not considered fastest
+}
+summary-reference = Reference: { $name } ({ $ip }) - { $time }
+summary-noise-floor = Noise floor: { $floor } (loopback { $loopback }µs, jitter { $jitter }µs) - smaller differences are not meaningful
+summary-authoritative-ttl = Authoritative TTL: { $ttl }s
+summary-split-horizon-ok = Split DNS: internal names resolve only through system, gateway, and DHCP resolvers
+summary-split-horizon-issues = Split DNS: { $leaks } public resolvers answer internal names, { $missing } system, gateway, or DHCP resolvers fail them
+summary-vpn-ok = VPN: every server is reached through the tunnel or runs on this host
+summary-vpn-leaks = { $count ->
+    [one] VPN: { $count } server bypasses the tunnel and was not considered fastest
+   *[other] VPN: { $count } servers bypass the tunnel and were not considered fastest
+}
+
+## Markdown report
+report-title = DNS Benchmark: { $domain }
+report-summary = Summary
+report-results = Results
+col-p95 = P95
+report-failures = Failures
+report-scope = { $servers } servers, { $requests } requests each
+report-system = System DNS: { $name } ({ $ip }) ranks { $rank } of { $total } - { $time }
+report-answered = answered { $successful } of { $total } queries
+report-last-error = last error: { $error }
+report-no-failures = Every server answered every query.
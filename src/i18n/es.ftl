@@ -0,0 +1,133 @@
+## Errors
+error-prefix = Error:
+error-no-servers = No hay servidores DNS que evaluar
+
+## Run summary printed before the benchmark
+start-title = Iniciando la prueba de DNS
+start-domain = Dominio:
+start-scope = Alcance:
+start-scope-value = { $servers } servidores × { $requests } consultas = { $total } en total
+start-browser = Navegador:
+start-browser-value = { $loads } cargas de página × { $domains } dominios por servidor
+start-dnssec = DNSSEC:
+start-dnssec-value = { $pairs } pares de consultas por servidor con y sin validación
+start-negative-cache = NXDOMAIN:
+start-negative-cache-value = 1 + { $repeats } consultas por servidor a un nombre inexistente, primer fallo frente a caché
+start-edns = EDNS:
+start-edns-value = carga UDP anunciada de { $payload } bytes, respuestas truncadas reintentadas por TCP
+start-verify = Verificar:
+start-verify-value = respuestas comparadas con { $ip }
+start-authoritative = Modo:
+start-authoritative-value = servidores de nombres autoritativos, consultas no recursivas
+start-search = Búsqueda:
+start-search-value = { $domains } añadido a nombres cortos
+start-split-horizon = DNS dividido:
+start-split-horizon-value = { $names } solo debería resolverse mediante los resolvedores del sistema, del router y de DHCP
+start-vpn = VPN:
+start-vpn-value = ruta a cada servidor comparada con el túnel VPN
+start-ttl = TTL:
+start-ttl-value = TTL de las respuestas comparado con el servidor autoritativo
+start-node-id = Nodos:
+start-node-id-value = nodo anycast identificado cada { $slice } consultas
+start-ecs = ECS:
+start-ecs-value = subred de cliente { $subnet } añadida a las consultas UDP
+start-workload = Carga:
+start-workload-value = { $mix } de las consultas
+start-domains = Dominios:
+start-domains-popular = los { $count } dominios más populares, por turnos
+start-domains-names = { $names }, por turnos
+start-order = Orden:
+start-order-value = servidores barajados con la semilla { $seed }
+start-protocols = Protocolos:
+start-protocols-value = cada servidor medido por { $protocols }
+start-probes = Sondas:
+start-probes-value = { $probes } ({ $budget } s de límite cada una, tras medir la latencia)
+start-config = Configuración:
+start-config-value = { $workers } trabajadores, { $slice } consultas por turno, { $timeout } s de espera, { $protocol }
+progress-benchmarking = Evaluando { $servers } servidores
+progress-live-more = … y { $count } más
+
+## Result table headers
+col-server = Servidor
+col-ip = Dirección IP
+col-source = Origen
+col-protocol = Protocolo
+col-resolved-ip = IP resuelta
+col-success-rate = Tasa de éxito
+col-min = Mín
+col-max = Máx
+col-avg-sorted = Media ↑
+col-jitter = ±Variación
+col-sparkline = Latencia en el tiempo
+col-failures = Fallos
+col-trend = Tendencia
+col-reference = vs Ref
+col-page-load = Carga de página
+col-dnssec-cost = Coste DNSSEC
+col-nxdomain = NXDOMAIN fallo → caché
+col-truncated = Truncadas
+col-ecs = ECS
+col-answers = Respuestas
+col-internal = Internos
+col-vpn = Ruta VPN
+col-avg-ttl = TTL medio
+col-node = Nodo
+col-filtering = Filtrado
+col-probes = Sondas
+col-warnings = Avisos
+col-server-as = AS del servidor
+col-answer-as = AS de la respuesta
+col-provider = Proveedor
+col-servers = Servidores
+col-best-sorted = Mejor ↑
+col-avg = Media
+col-ipv4-avg = Media IPv4
+col-ipv6-avg = Media IPv6
+col-dual-stack-delta = Δ (v6 − v4)
+col-fastest = Más rápido
+protocols-handshake-note = * Cada consulta abre una conexión nueva, así que estos tiempos incluyen los handshakes TCP y TLS
+
+## Summary printed after the results
+legend = Leyenda:
+summary-completed = Prueba completada en { $duration }
+summary-phases = Tiempo empleado: { $phases }
+phase-collection = listas de servidores
+phase-detection = detección
+phase-benchmarking = medición
+phase-probing = sondas
+phase-formatting = salida
+summary-fastest = Más rápido: { $name } ({ $ip }) - { $time }
+summary-all-failed = { $count ->
+    [one] { $count } servidor falló en todas las consultas
+   *[other] { $count } servidores fallaron en todas las consultas
+}
+summary-all-failed-hidden = { $count ->
+    [one] { $count } servidor falló en todas las consultas y está oculto
+   *[other] { $count } servidores fallaron en todas las consultas y están ocultos
+}
+summary-ineligible = { $count ->
+    [one] { $count } servidor respondió menos del { $rate }% de las consultas y no se consideró el más rápido
+   *[other] { $count } servidores respondieron menos del { $rate }% de las consultas y no se consideraron los más rápidos
+}
+summary-reference = Referencia: { $name } ({ $ip }) - { $time }
+summary-noise-floor = Umbral de ruido: { $floor } (loopback { $loopback } µs, variación { $jitter } µs) - las diferencias menores no son significativas
+summary-authoritative-ttl = TTL autoritativo: { $ttl } s
+summary-split-horizon-ok = DNS dividido: los nombres internos solo se resuelven mediante los resolvedores del sistema, del router y de DHCP
+summary-split-horizon-issues = DNS dividido: { $leaks } resolvedores públicos responden nombres internos, { $missing } resolvedores del sistema, del router o de DHCP no los resuelven
+summary-vpn-ok = VPN: todos los servidores se alcanzan a través del túnel o se ejecutan en este equipo
+summary-vpn-leaks = { $count ->
+    [one] VPN: { $count } servidor evita el túnel y no se consideró el más rápido
+   *[other] VPN: { $count } servidores evitan el túnel y no se consideraron los más rápidos
+}
+
+## Markdown report
+report-title = Prueba de DNS: { $domain }
+report-summary = Resumen
+report-results = Resultados
+col-p95 = P95
+report-failures = Fallos
+report-scope = { $servers } servidores, { $requests } consultas cada uno
+report-system = DNS del sistema: { $name } ({ $ip }) ocupa el puesto { $rank } de { $total } - { $time }
+report-answered = respondió { $successful } de { $total } consultas
+report-last-error = último error: { $error }
+report-no-failures = Todos los servidores respondieron todas las consultas.
@@ -0,0 +1,154 @@
+//! Localized output strings.
+//!
+//! Messages live in one [Fluent](https://projectfluent.org) file per
+//! language, embedded at build time. English is complete and used as the
+//! fallback for any message a translation is missing.
+
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use unic_langid::LanguageIdentifier;
+
+/// Output language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+    De,
+}
+
+impl Lang {
+    /// All supported languages
+    pub const ALL: &'static [Self] = &[Self::En, Self::Es, Self::De];
+
+    /// Fluent source for the language
+    fn source(self) -> &'static str {
+        match self {
+            Self::En => include_str!("en.ftl"),
+            Self::Es => include_str!("es.ftl"),
+            Self::De => include_str!("de.ftl"),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::En => write!(f, "en"),
+            Self::Es => write!(f, "es"),
+            Self::De => write!(f, "de"),
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = crate::Error;
+
+    /// Parse a language code, ignoring any region or encoding (`de_AT.UTF-8`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.split(['-', '_', '.']).next().unwrap_or_default();
+        match code.to_lowercase().as_str() {
+            "en" => Ok(Self::En),
+            "es" => Ok(Self::Es),
+            "de" => Ok(Self::De),
+            _ => Err(crate::Error::InvalidArgument(format!("Unsupported language: {s}"))),
+        }
+    }
+}
+
+/// Looks up messages in one language, falling back to English
+pub struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    /// Load the messages for a language
+    pub fn new(lang: Lang) -> Self {
+        Self {
+            bundle: bundle(lang),
+            fallback: (lang != Lang::En).then(|| bundle(Lang::En)),
+        }
+    }
+
+    /// Get a message without arguments
+    pub fn tr(&self, id: &str) -> String {
+        self.tr_args(id, [])
+    }
+
+    /// Get a message, filling in its `{ $name }` placeholders
+    ///
+    /// Returns the message ID itself if no language has the message.
+    pub fn tr_args<'a>(&self, id: &str, args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>) -> String {
+        let args: FluentArgs = args.into_iter().collect();
+        [Some(&self.bundle), self.fallback.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                Some(bundle.format_pattern(pattern, Some(&args), &mut errors).into_owned())
+            })
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::new(Lang::En)
+    }
+}
+
+/// Build the bundle for a language from its embedded source
+fn bundle(lang: Lang) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = lang.to_string().parse().expect("valid language identifier");
+    let resource = FluentResource::try_new(lang.source().to_string()).expect("valid Fluent source");
+    let mut bundle = FluentBundle::new(vec![langid]);
+    // Unicode isolation marks show up as garbage in many terminals
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).expect("no duplicate message IDs");
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_from_str() {
+        assert_eq!("de".parse::<Lang>().unwrap(), Lang::De);
+        assert_eq!("es-MX".parse::<Lang>().unwrap(), Lang::Es);
+        assert_eq!("en_US.UTF-8".parse::<Lang>().unwrap(), Lang::En);
+        assert!("xx".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn test_translations_are_complete() {
+        let ids: Vec<_> = Lang::En
+            .source()
+            .lines()
+            .filter_map(|line| line.split_once(" = ").map(|(id, _)| id))
+            .collect();
+        assert!(!ids.is_empty());
+        for &lang in Lang::ALL {
+            let bundle = bundle(lang);
+            for id in &ids {
+                assert!(bundle.has_message(id), "{lang} is missing {id}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_tr_args() {
+        let l = Localizer::new(Lang::De);
+        assert_eq!(l.tr("col-success-rate"), "Erfolgsquote");
+        assert_eq!(
+            l.tr_args("summary-completed", [("duration", "1.20s".into())]),
+            "Benchmark abgeschlossen in 1.20s"
+        );
+        assert_eq!(l.tr("no-such-message"), "no-such-message");
+    }
+}
@@ -9,11 +9,20 @@
 //! ## Features
 //!
 //! - **Async-first design** - Leverages Tokio for efficient concurrent benchmarking
-//! - **Multiple output formats** - Table, JSON, XML, CSV
+//! - **Multiple output formats** - Table, JSON, XML, CSV, NDJSON, InfluxDB line protocol, JUnit, Markdown, HTML
 //! - **Cross-platform** - Works on Linux, macOS, and Windows
 //! - **Configurable** - Extensive CLI options with persistent configuration
 //! - **Smart detection** - Auto-detects system DNS and gateway servers
 //!
+//! ## Cargo Features
+//!
+//! - `engine` *(default)* - The async benchmark engine, DNS resolution, and
+//!   platform detection. Build with `--no-default-features` to get only the
+//!   result model and output formatters, e.g. for `wasm32` targets.
+//! - `web` - A DNS-over-HTTPS benchmark for web pages
+//!   (`benchmark::run_doh_benchmark`), sending queries through `fetch()`.
+//!   Build it for `wasm32-unknown-unknown` with `--no-default-features`.
+//!
 //! ## Author
 //!
 //! Mohammad Miadh Angkad <MAngkad.BSDSBA2027@aim.edu>
@@ -23,15 +32,28 @@ pub mod cli;
 pub mod config;
 pub mod dns;
 pub mod error;
+#[cfg(feature = "engine")]
+pub mod history;
+pub mod i18n;
+pub mod logging;
 pub mod output;
+#[cfg(feature = "engine")]
 pub mod platform;
+pub mod probe;
+pub mod scenario;
+#[cfg(feature = "engine")]
+pub mod serve;
+pub mod share;
+pub mod template;
 
 // Re-exports for convenience
-pub use benchmark::{BenchmarkEngine, BenchmarkResult, ServerResult};
+#[cfg(feature = "engine")]
+pub use benchmark::{BenchmarkEngine, BenchmarkEvent};
+pub use benchmark::{BenchmarkResult, ServerResult};
 pub use config::Config;
-pub use dns::{DnsServer, IpVersion, Protocol};
+pub use dns::{DnsServer, IpVersion, LookupStrategy, Protocol};
 pub use error::{Error, Result};
-pub use output::{OutputFormat, OutputFormatter};
+pub use output::{FormatterRegistry, OutputFormat, OutputFormatter};
 
 /// Crate version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -48,6 +70,12 @@ pub const DEFAULT_REQUESTS: u16 = 50;
 /// Default timeout in seconds
 pub const DEFAULT_TIMEOUT_SECS: u64 = 2;
 
+/// Default number of queries sent to a server per scheduling turn
+pub const DEFAULT_SLICE: u16 = 5;
+
+/// Default time budget in seconds for each probe run
+pub const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 10;
+
 #[cfg(test)]
 mod tests {
     /// Load test fixture files
@@ -0,0 +1,149 @@
+//! Diagnostic messages on standard error, controlled by `-v`.
+//!
+//! The library reports warnings and debugging detail through `tracing`. This
+//! is a minimal subscriber for the command line; applications embedding the
+//! library can install their own instead.
+
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Target prefix of this crate's events; other crates are only shown at `-vvv`
+const CRATE_TARGET: &str = "dns_benchmark";
+
+/// Install the stderr logger for the given number of `-v` flags
+///
+/// Without `-v` only warnings and errors are printed, as `Warning: ...`
+/// lines. `-v` adds resolver configuration and platform detection steps,
+/// `-vv` the outcome of every query, and `-vvv` trace output of the DNS
+/// library as well. Does nothing if a subscriber is already installed.
+pub fn init(verbosity: u8) {
+    let _ = tracing::subscriber::set_global_default(StderrLogger::new(verbosity));
+}
+
+/// Subscriber printing one line per event; spans are ignored
+struct StderrLogger {
+    /// Most verbose level shown for this crate
+    own: Level,
+    /// Most verbose level shown for dependencies
+    others: LevelFilter,
+    /// Prefix lines with level and target instead of `Warning:`
+    verbose: bool,
+}
+
+impl StderrLogger {
+    fn new(verbosity: u8) -> Self {
+        let own = match verbosity {
+            0 => Level::WARN,
+            1 => Level::INFO,
+            2 => Level::DEBUG,
+            _ => Level::TRACE,
+        };
+        Self {
+            own,
+            others: if verbosity >= 3 { LevelFilter::TRACE } else { LevelFilter::OFF },
+            verbose: verbosity > 0,
+        }
+    }
+
+    fn line(&self, metadata: &Metadata<'_>, fields: &Fields) -> String {
+        if self.verbose {
+            format!("{:>5} {}: {fields}", metadata.level(), metadata.target())
+        } else if *metadata.level() == Level::ERROR {
+            format!("Error: {fields}")
+        } else {
+            format!("Warning: {fields}")
+        }
+    }
+}
+
+impl Subscriber for StderrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if metadata.target().starts_with(CRATE_TARGET) {
+            *metadata.level() <= self.own
+        } else {
+            *metadata.level() <= self.others
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.own).max(self.others))
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let line = self.line(event.metadata(), &fields);
+        let _ = writeln!(std::io::stderr().lock(), "{line}");
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// An event's message followed by its other fields as `key=value`
+#[derive(Default)]
+struct Fields {
+    message: String,
+    extra: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.extra, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.extra, " {}={value:?}", field.name());
+        }
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.message, self.extra)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_levels() {
+        let enabled = |verbosity: u8| {
+            tracing::subscriber::with_default(StderrLogger::new(verbosity), || {
+                [
+                    tracing::enabled!(target: "dns_benchmark::platform", Level::WARN),
+                    tracing::enabled!(target: "dns_benchmark::platform", Level::INFO),
+                    tracing::enabled!(target: "dns_benchmark::benchmark::engine", Level::DEBUG),
+                    tracing::enabled!(target: "hickory_proto::udp", Level::WARN),
+                    tracing::enabled!(target: "hickory_proto::udp", Level::TRACE),
+                ]
+            })
+        };
+        assert_eq!(enabled(0), [true, false, false, false, false]);
+        assert_eq!(enabled(1), [true, true, false, false, false]);
+        assert_eq!(enabled(2), [true, true, true, false, false]);
+        assert_eq!(enabled(3), [true, true, true, true, true]);
+    }
+}
@@ -1,32 +1,90 @@
 //! DNS Benchmark - High-performance DNS benchmarking tool
 
+use anyhow::Context;
 use clap::Parser;
 use console::style;
-use dns_benchmark::benchmark::{collect_servers, BenchmarkEngine};
-use dns_benchmark::cli::{Cli, Command, ConfigCommand};
+use dns_benchmark::benchmark::{
+    check_socket_options, collect_servers, collect_servers_timed, resolve_once, run_trace, BenchmarkEngine,
+    BenchmarkResult, ChaosRates, ChaosServer, GeoIpDatabase, NetworkFingerprint, NetworkMatch, PhaseTimings,
+    RegressionScope, RunComparison, SerializableResult, StoredRun,
+};
+use dns_benchmark::cli::{
+    ApplyArgs, Cli, Command, CompareArgs, ConfigCommand, ExportArgs, HistoryCommand, ListArgs, ResolveArgs,
+    ScenarioArgs, ServeArgs, TemplateCommand, TraceArgs, UpdateServersArgs,
+};
 use dns_benchmark::config::Config;
-use dns_benchmark::output::{get_formatter, OutputFormat};
-use dns_benchmark::platform::get_system_dns_servers;
-use std::io;
+use dns_benchmark::dns::{
+    download_text, get_builtin_servers, parse_public_dns_csv, public_dns_info_url, write_public_servers, DnsServer,
+    PublicListFilter, ServerSource,
+};
+use dns_benchmark::history::{History, HistoryRun};
+use dns_benchmark::i18n::Localizer;
+use dns_benchmark::logging;
+use dns_benchmark::output::{
+    plain_dns_servers, result_line, set_color_mode, write_comparison, write_export, write_history_list,
+    write_history_run, write_prometheus, write_region_comparison, write_resolve, write_scenario_report,
+    write_server_list, write_trace, write_trace_csv, write_trace_xml, FormatterRegistry, NdjsonFormatter, OutputFormat,
+    OutputTarget,
+};
+use dns_benchmark::error::PlatformError;
+use dns_benchmark::platform::{
+    check_event_log, detect_dns_settings, get_system_dns_servers, run_dns_commands, write_event_log, DnsCommand,
+    DnsSettings,
+};
+use dns_benchmark::probe::ProbeRegistry;
+use dns_benchmark::scenario::{run_scenario, Scenario};
+use dns_benchmark::serve::MetricsServer;
+use dns_benchmark::share::{fetch_region_stats, upload, RegionComparison, SharePayload};
+use dns_benchmark::template::{local_settings, Template};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::process::ExitCode;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    match run().await {
+    let cli = Cli::parse();
+    set_color_mode(cli.color_mode().unwrap_or_default());
+    logging::init(cli.verbose);
+    let lang = cli.options.lang.map(Into::into).unwrap_or_else(|| Config::load_or_default().lang);
+
+    match run(cli).await {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("{} {}", style("Error:").red().bold(), e);
+            eprintln!("{} {}", style(Localizer::new(lang).tr("error-prefix")).red().bold(), e);
             ExitCode::FAILURE
         }
     }
 }
 
-async fn run() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // Formats `--format` and `--output` can name, and `formats` lists
+    let formatters = FormatterRegistry::builtin();
 
     match cli.command {
         Some(Command::Config(cmd)) => handle_config_command(cmd),
-        None => run_benchmark(cli).await,
+        Some(Command::Compare(args)) => handle_compare_command(args),
+        Some(Command::History(cmd)) => handle_history_command(cmd),
+        Some(Command::Export(args)) => handle_export_command(args),
+        Some(Command::Apply(args)) => handle_apply_command(args).await,
+        Some(Command::Scenario(args)) => run_scenario_command(args).await,
+        Some(Command::Trace(args)) => run_trace_command(args).await,
+        Some(Command::Resolve(args)) => run_resolve_command(args).await,
+        Some(Command::Serve(args)) => run_serve_command(args).await,
+        Some(Command::Template(cmd)) => handle_template_command(cmd),
+        Some(Command::List(args)) => list_servers(args),
+        Some(Command::Probes) => {
+            list_probes();
+            Ok(())
+        }
+        Some(Command::Formats) => {
+            list_formats(&formatters);
+            Ok(())
+        }
+        Some(Command::UpdateServers(args)) => update_servers(args).await,
+        None => run_benchmark(cli, &formatters).await,
     }
 }
 
@@ -49,7 +107,7 @@ fn handle_config_command(cmd: ConfigCommand) -> anyhow::Result<()> {
                 println!("  Using default values. Run 'dns-benchmark config init' to create one.");
                 println!();
             }
-            let config = Config::load_or_default();
+            let config = Config::try_load_or_default()?;
             println!("{}", style("Current Configuration:").cyan().bold());
             println!("{}", config);
         }
@@ -59,7 +117,7 @@ fn handle_config_command(cmd: ConfigCommand) -> anyhow::Result<()> {
                 anyhow::bail!("Config file does not exist. Run 'dns-benchmark config init' first.");
             }
 
-            let mut config = Config::load_or_default();
+            let mut config = Config::try_load_or_default()?;
             config.merge(&args.options.to_overrides());
             config.save()?;
             println!("{} Configuration updated.", style("✓").green());
@@ -92,44 +150,946 @@ fn handle_config_command(cmd: ConfigCommand) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Handle template subcommands
+fn handle_template_command(cmd: TemplateCommand) -> anyhow::Result<()> {
+    match cmd {
+        TemplateCommand::Export(args) => {
+            let mut config = Config::try_load_or_default()?;
+            config.merge(&args.options.to_overrides());
+
+            let template = Template::from_config(&config, args.description)?;
+            template.save(&args.file)?;
+            println!("{} Template written to {}", style("✓").green(), args.file.display());
+            let left_out = local_settings(&config);
+            if !left_out.is_empty() {
+                println!("  Machine-specific settings were left out: {}", left_out.join(", "));
+            }
+        }
+
+        TemplateCommand::Import(args) => {
+            if Config::exists()? && !args.force {
+                anyhow::bail!("Config file already exists. Use --force to replace it with the template.");
+            }
+
+            let template = Template::load(&args.file)?;
+            if let Some(ref description) = template.description {
+                println!("{} {}", style("Template:").cyan().bold(), description);
+            }
+            let ignored = template.ignored_settings();
+            if !ignored.is_empty() {
+                println!(
+                    "{} Ignoring machine-specific settings in the template: {}",
+                    style("⚠").yellow(),
+                    ignored.join(", ")
+                );
+            }
+            let config = template.install(&Config::template_servers_path()?)?;
+            config.save()?;
+
+            println!("{} Template imported into {}", style("✓").green(), Config::path()?.display());
+            if let Some(ref path) = config.custom_servers {
+                println!("  Server list: {}", path.display());
+            }
+            println!("  Run it with: dns-benchmark");
+        }
+    }
+
+    Ok(())
+}
+
+/// Compare two stored runs
+fn handle_compare_command(args: CompareArgs) -> anyhow::Result<()> {
+    let baseline = StoredRun::load(&args.baseline)?;
+    let Some(ref current_path) = args.current else {
+        return compare_against_region(&baseline, &args);
+    };
+    let current = StoredRun::load(current_path)?;
+    let comparison = RunComparison::new(&baseline, &current);
+
+    match comparison.network {
+        NetworkMatch::Same => {}
+        NetworkMatch::Different(ref fields) if !args.force => {
+            anyhow::bail!(
+                "Runs were taken on different networks (mismatched {}). Use --force to compare anyway.",
+                fields.join(", ")
+            );
+        }
+        NetworkMatch::Different(ref fields) => {
+            eprintln!(
+                "{} Runs were taken on different networks (mismatched {}); results may not be comparable.",
+                style("⚠").yellow(),
+                fields.join(", ")
+            );
+        }
+        NetworkMatch::Unknown => {
+            eprintln!(
+                "{} Could not verify that both runs were taken on the same network.",
+                style("⚠").yellow()
+            );
+        }
+    }
+
+    if baseline.meta.domain != current.meta.domain {
+        eprintln!(
+            "{} Runs tested different domains ({} vs {}).",
+            style("⚠").yellow(),
+            baseline.meta.domain,
+            current.meta.domain
+        );
+    }
+
+    let style = match args.style {
+        Some(style) => style.into(),
+        None => Config::try_load_or_default()?.style,
+    };
+    let mut stdout = io::stdout().lock();
+    write_comparison(&comparison, style, &mut stdout)?;
+
+    Ok(())
+}
+
+fn handle_history_command(cmd: HistoryCommand) -> anyhow::Result<()> {
+    let config = Config::try_load_or_default()?;
+    let path = Config::history_path()?;
+    if !path.exists() {
+        anyhow::bail!("No run history yet at {}", path.display());
+    }
+    let history = History::open(&path)?;
+    let mut stdout = io::stdout().lock();
+
+    match cmd {
+        HistoryCommand::List(args) => {
+            let runs = history.list(args.limit)?;
+            match args.format.map(Into::into).unwrap_or(OutputFormat::Table) {
+                OutputFormat::Table => {
+                    let style = args.style.map(Into::into).unwrap_or(config.style);
+                    write_history_list(&runs, style, &mut stdout)?;
+                }
+                OutputFormat::Json => {
+                    serde_json::to_writer_pretty(&mut stdout, &runs)?;
+                    println!();
+                }
+                other => anyhow::bail!("Output format '{other}' is not supported for the run history"),
+            }
+        }
+        HistoryCommand::Show(args) => {
+            let run = history.get(args.id)?.with_context(|| format!("No run with ID {} in the history", args.id))?;
+            match args.format.map(Into::into).unwrap_or(OutputFormat::Table) {
+                OutputFormat::Table => {
+                    let style = args.style.map(Into::into).unwrap_or(config.style);
+                    write_history_run(&run, style, &mut stdout)?;
+                }
+                OutputFormat::Json => {
+                    serde_json::to_writer_pretty(&mut stdout, &run)?;
+                    println!();
+                }
+                other => anyhow::bail!("Output format '{other}' is not supported for the run history"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a DNS configuration snippet for the fastest servers of a stored run
+fn handle_export_command(args: ExportArgs) -> anyhow::Result<()> {
+    let run = stored_run(args.run)?;
+    let servers = fastest_of_run(&run, args.top, false)?;
+    let origin = format!("run {} of {} ({})", run.meta.id, run.meta.domain, run.meta.started_at);
+    write_export(args.target.into(), &servers, run.meta.config.protocol, &origin, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+/// Load a run from the history, the latest if `id` is `None`
+fn stored_run(id: Option<i64>) -> anyhow::Result<HistoryRun> {
+    let path = Config::history_path()?;
+    if !path.exists() {
+        anyhow::bail!("No run history yet at {}; run a benchmark first", path.display());
+    }
+    let history = History::open(&path)?;
+    let id = match id {
+        Some(id) => id,
+        None => history.list(1)?.first().map(|r| r.id).context("The run history is empty; run a benchmark first")?,
+    };
+    history.get(id)?.with_context(|| format!("No run with ID {id} in the history"))
+}
+
+/// The `top` fastest plain DNS servers of a run, failing if none qualifies
+///
+/// Each address counts once, and servers measured only over DoT or DoH are
+/// left out. `port_53_only` also leaves out servers on other ports.
+fn fastest_of_run(run: &HistoryRun, top: u32, port_53_only: bool) -> anyhow::Result<Vec<&SerializableResult>> {
+    let mut servers = plain_dns_servers(&run.fastest(usize::MAX), run.meta.config.protocol);
+    if port_53_only {
+        servers.retain(|s| s.port.is_none());
+    }
+    servers.truncate(top as usize);
+    if servers.is_empty() {
+        anyhow::bail!(
+            "No server qualifies in run {}: none answered, none met its minimum success rate, \
+             or none can be set as plain DNS",
+            run.meta.id
+        );
+    }
+    Ok(servers)
+}
+
+/// Set the system's DNS servers to the fastest of a stored run, or restore the previous ones
+async fn handle_apply_command(args: ApplyArgs) -> anyhow::Result<()> {
+    let backup_path = Config::dns_backup_path()?;
+    let backup: Option<DnsSettings> = match fs::read_to_string(&backup_path) {
+        Ok(json) => Some(
+            serde_json::from_str(&json)
+                .map_err(|e| anyhow::anyhow!("Failed to read saved DNS settings {}: {e}", backup_path.display()))?,
+        ),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => anyhow::bail!("Failed to read saved DNS settings {}: {e}", backup_path.display()),
+    };
+
+    if args.revert {
+        let Some(previous) = backup else {
+            anyhow::bail!("Nothing to revert: `apply` has not changed the DNS settings");
+        };
+        let restored = if previous.servers.is_empty() {
+            "automatic DNS servers".to_string()
+        } else {
+            join_ips(&previous.servers)
+        };
+        println!("Restore {restored} on {} ({}):", previous.target, previous.backend);
+        let commands = previous.revert_commands();
+        if !confirm_dns_commands(&commands, &args)? {
+            return Ok(());
+        }
+        run_dns_commands(&commands).await.map_err(apply_failed)?;
+        fs::remove_file(&backup_path)
+            .map_err(|e| anyhow::anyhow!("Failed to remove {}: {e}", backup_path.display()))?;
+        eprintln!("{} DNS settings of {} restored.", style("✓").green(), previous.target);
+        return Ok(());
+    }
+
+    let run = stored_run(args.run)?;
+    // System DNS settings take no port
+    let servers = fastest_of_run(&run, args.top, true)?;
+    let ips: Vec<IpAddr> = servers.iter().filter_map(|s| s.ip.parse().ok()).collect();
+    let current = detect_dns_settings(args.interface.as_deref()).await?;
+    // A second apply keeps the settings from before the first, so --revert goes all the way back
+    if let Some(ref previous) = backup
+        && (previous.backend, &previous.target) != (current.backend, &current.target)
+    {
+        anyhow::bail!(
+            "DNS settings of {} ({}) were changed by an earlier apply; run `dns-benchmark apply --revert` first",
+            previous.target,
+            previous.backend
+        );
+    }
+
+    println!(
+        "Set the DNS servers of {} ({}) to the fastest of run {} ({}):",
+        current.target, current.backend, run.meta.id, run.meta.domain
+    );
+    for (rank, server) in servers.iter().enumerate() {
+        let avg = server.avg_ms.map(|ms| format!("{ms:.1}ms")).unwrap_or_else(|| "-".to_string());
+        println!("  {}. {} ({}): {avg} avg", rank + 1, server.name, server.ip);
+    }
+    println!("Commands:");
+    let commands = current.apply_commands(&ips);
+    if !confirm_dns_commands(&commands, &args)? {
+        return Ok(());
+    }
+
+    if backup.is_none() {
+        if let Some(parent) = backup_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&backup_path, serde_json::to_string_pretty(&current)?)
+            .map_err(|e| anyhow::anyhow!("Failed to save DNS settings to {}: {e}", backup_path.display()))?;
+    }
+    run_dns_commands(&commands).await.map_err(apply_failed)?;
+    eprintln!(
+        "{} DNS servers of {} set to {}. Undo with `dns-benchmark apply --revert`.",
+        style("✓").green(),
+        current.target,
+        join_ips(&ips)
+    );
+    Ok(())
+}
+
+/// Print the commands, then ask whether to run them unless `--yes` or `--dry-run` decide
+fn confirm_dns_commands(commands: &[DnsCommand], args: &ApplyArgs) -> anyhow::Result<bool> {
+    for command in commands {
+        println!("  {command}");
+    }
+    if args.dry_run {
+        eprintln!("Dry run, nothing was changed.");
+        return Ok(false);
+    }
+    if args.yes {
+        return Ok(true);
+    }
+
+    eprint!("Run these commands? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        eprintln!("Nothing was changed.");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn apply_failed(e: PlatformError) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{e}\n  Changing DNS settings usually needs root or Administrator rights; \
+         `dns-benchmark apply --revert` restores the previous settings"
+    )
+}
+
+fn join_ips(ips: &[IpAddr]) -> String {
+    ips.iter().map(IpAddr::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Compare a stored run to the community statistics for its region
+fn compare_against_region(run: &StoredRun, args: &CompareArgs) -> anyhow::Result<()> {
+    let config = Config::try_load_or_default()?;
+    let Some(endpoint) = args.stats_endpoint.clone().or(config.stats_endpoint.clone()) else {
+        anyhow::bail!(
+            "--against-region needs an endpoint: pass --stats-endpoint or set stats_endpoint in the config file"
+        );
+    };
+
+    let country = match args.against_region.as_deref() {
+        Some(code) if !code.is_empty() => code.to_uppercase(),
+        _ => {
+            let geoip = (!config.geoip.is_empty())
+                .then(|| GeoIpDatabase::open(&config.geoip))
+                .transpose()?;
+            network_country(run.meta.fingerprint.as_ref(), geoip.as_ref()).context(
+                "Could not determine the run's country; pass it to --against-region (e.g. --against-region DE)",
+            )?
+        }
+    };
+
+    let stats = fetch_region_stats(&endpoint, &country)?;
+    let comparison = RegionComparison::new(run, &stats);
+    if comparison.servers.is_empty() {
+        anyhow::bail!("No community statistics for the servers in this run in {}", stats.country);
+    }
+
+    let style = args.style.map(Into::into).unwrap_or(config.style);
+    let mut stdout = io::stdout().lock();
+    write_region_comparison(&comparison, style, &mut stdout)?;
+
+    Ok(())
+}
+
+/// Look up the country of the network a run was taken on
+fn network_country(fingerprint: Option<&NetworkFingerprint>, geoip: Option<&GeoIpDatabase>) -> Option<String> {
+    fingerprint
+        .and_then(|fp| fp.public_prefix.as_deref())
+        .and_then(|prefix| prefix.parse::<ipnet::IpNet>().ok())
+        .zip(geoip)
+        .and_then(|(net, geoip)| geoip.lookup(net.network()))
+        .and_then(|geo| geo.country)
+}
+
+/// Print the built-in servers with provider metadata
+fn list_servers(args: ListArgs) -> anyhow::Result<()> {
+    let config = Config::try_load_or_default()?;
+    let ip_version = args.name_server_ip.map(Into::into).unwrap_or(config.name_server_ip);
+    let servers = get_builtin_servers(ip_version);
+
+    let mut stdout = io::stdout().lock();
+    match args.format.map(Into::into).unwrap_or(OutputFormat::Table) {
+        OutputFormat::Table => {
+            let style = args.style.map(Into::into).unwrap_or(config.style);
+            write_server_list(&servers, style, &mut stdout)?;
+        }
+        OutputFormat::Json => {
+            let entries: Vec<_> = servers
+                .iter()
+                .map(|s| serde_json::json!({ "name": s.name, "ip": s.ip(), "provider": s.provider }))
+                .collect();
+            serde_json::to_writer_pretty(&mut stdout, &entries)?;
+            println!();
+        }
+        other => anyhow::bail!("Output format '{other}' is not supported for the server list"),
+    }
+
+    Ok(())
+}
+
+/// Print the available capability probes
+fn list_probes() {
+    for probe in ProbeRegistry::builtin().iter() {
+        println!("{:<10} {}", style(probe.name()).cyan(), probe.description());
+    }
+}
+
+/// Print the available output formats
+fn list_formats(formatters: &FormatterRegistry) {
+    for format in formatters.iter() {
+        println!("{:<10} {}", style(format.name).cyan(), format.description);
+    }
+}
+
+/// Maximum size of a downloaded public resolver list
+const MAX_PUBLIC_LIST_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Download a public resolver list and write it as a custom servers file
+async fn update_servers(args: UpdateServersArgs) -> anyhow::Result<()> {
+    if !(0.0..=1.0).contains(&args.min_reliability) {
+        anyhow::bail!("--min-reliability must be between 0.0 and 1.0");
+    }
+
+    // public-dns.info publishes one file per country, which is much smaller
+    // than the full list
+    let urls: Vec<String> = match args.source {
+        Some(url) => vec![url],
+        None if args.country.is_empty() => vec![public_dns_info_url(None)],
+        None => args.country.iter().map(|cc| public_dns_info_url(Some(cc))).collect(),
+    };
+
+    let mut resolvers = Vec::new();
+    for url in &urls {
+        if !url.starts_with("https://") {
+            anyhow::bail!("Only https:// URLs are supported: {url}");
+        }
+        eprintln!("{} {}", style("Downloading").cyan().bold(), url);
+        let fetch_url = url.clone();
+        let content = tokio::task::spawn_blocking(move || download_text(&fetch_url, MAX_PUBLIC_LIST_BYTES))
+            .await?
+            .map_err(|e| anyhow::anyhow!("Failed to download {url}: {e}"))?;
+        resolvers.extend(parse_public_dns_csv(&content, url)?);
+    }
+
+    let filter = PublicListFilter {
+        countries: args.country,
+        min_reliability: args.min_reliability,
+        limit: args.limit,
+    };
+    let resolvers = filter.apply(resolvers);
+    if resolvers.is_empty() {
+        anyhow::bail!("No resolvers matched the filters");
+    }
+
+    let path = match args.output {
+        Some(path) => path,
+        None => Config::public_servers_path()?,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = io::BufWriter::new(fs::File::create(&path)?);
+    write_public_servers(&resolvers, &urls.join(", "), &mut file)?;
+    file.flush()?;
+
+    println!(
+        "{} Wrote {} servers to {}",
+        style("✓").green(),
+        resolvers.len(),
+        path.display()
+    );
+    println!("  Use them with: dns-benchmark --custom-servers {}", path.display());
+
+    Ok(())
+}
+
+/// Run a scenario file against the selected servers
+async fn run_scenario_command(args: ScenarioArgs) -> anyhow::Result<()> {
+    let mut config = Config::try_load_or_default()?;
+    config.merge(&args.options.to_overrides());
+
+    let scenario = Scenario::load(&args.file)?;
+    let servers = collect_servers(&config).await?;
+
+    if servers.is_empty() {
+        anyhow::bail!("No DNS servers to test");
+    }
+
+    if config.show_banners() {
+        println!(
+            "\n{} scenario '{}' ({} steps) against {} servers\n",
+            style("Running").cyan().bold(),
+            scenario.name,
+            scenario.steps.len(),
+            servers.len()
+        );
+    }
+
+    let report = run_scenario(&scenario, &servers, &config).await;
+
+    let mut stdout = io::stdout().lock();
+    match config.format {
+        OutputFormat::Table => write_scenario_report(&report, config.style, &mut stdout)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &report)?;
+            println!();
+        }
+        other => anyhow::bail!("Output format '{other}' is not supported for scenarios"),
+    }
+
+    let failed = report.failed_steps();
+    if failed > 0 {
+        anyhow::bail!("{failed} scenario step(s) failed");
+    }
+
+    Ok(())
+}
+
+async fn run_trace_command(args: TraceArgs) -> anyhow::Result<()> {
+    let mut config = Config::try_load_or_default()?;
+    config.merge(&args.options.to_overrides());
+
+    if config.show_banners() {
+        println!("\n{} {} from the root servers\n", style("Tracing").cyan().bold(), config.domain);
+    }
+
+    let trace = run_trace(&config).await?;
+
+    let mut stdout = io::stdout().lock();
+    match config.format {
+        OutputFormat::Table => write_trace(&trace, config.style, &mut stdout)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &trace)?;
+            println!();
+        }
+        OutputFormat::Xml => write_trace_xml(&trace, &mut stdout)?,
+        OutputFormat::Csv => write_trace_csv(&trace, &mut stdout)?,
+        other => anyhow::bail!("Output format '{other}' is not supported for traces"),
+    }
+
+    if !trace.completed() {
+        anyhow::bail!("Trace of {} ended without an authoritative answer", trace.domain);
+    }
+
+    Ok(())
+}
+
+async fn run_resolve_command(args: ResolveArgs) -> anyhow::Result<()> {
+    let mut config = Config::try_load_or_default()?;
+    config.merge(&args.options.to_overrides());
+
+    let server = match args.server {
+        Some(addr) => addr,
+        None => get_system_dns_servers(
+            config.name_server_ip,
+            config.detect_cache_dir().as_deref(),
+            &config.adapter_filter(),
+        )
+        .await?
+        .first()
+        .map(|s| s.addr)
+        .context("No system resolver found; pass --server")?,
+    };
+    let result = resolve_once(server, &args.name, &args.record_type, &config).await?;
+
+    let mut stdout = io::stdout().lock();
+    match config.format {
+        OutputFormat::Table => write_resolve(&result, config.style, &mut stdout)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &result)?;
+            println!();
+        }
+        other => anyhow::bail!("Output format '{other}' is not supported for resolve"),
+    }
+
+    if let Some(error) = result.error {
+        anyhow::bail!("No response from {server}: {error}");
+    }
+
+    Ok(())
+}
+
+/// Show the anonymized payload and upload it only if the user agrees
+async fn share_results(payload: &SharePayload, endpoint: &str) -> anyhow::Result<()> {
+    eprintln!();
+    eprintln!("The following anonymized results will be sent to {endpoint}:");
+    eprintln!("{}", serde_json::to_string_pretty(payload)?);
+    eprint!("Nothing else is sent. Share these results? [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        eprintln!("Results not shared.");
+        return Ok(());
+    }
+
+    let payload = payload.clone();
+    let endpoint = endpoint.to_string();
+    tokio::task::spawn_blocking(move || upload(&payload, &endpoint)).await??;
+    eprintln!("{} Results shared. Thank you!", style("✓").green());
+    Ok(())
+}
+
+/// Benchmark on an interval and serve the latest run's metrics
+async fn run_serve_command(args: ServeArgs) -> anyhow::Result<()> {
+    let mut config = Config::try_load_or_default()?;
+    config.merge(&args.options.to_overrides());
+    // Runs are reported through the endpoint only; this keeps the progress bar and summary quiet
+    config.format = OutputFormat::Json;
+
+    let probes = ProbeRegistry::builtin().select(&config.probes)?;
+    check_socket_options(&config.socket)?;
+    config.check_udp_only()?;
+    let geoip = (!config.geoip.is_empty())
+        .then(|| GeoIpDatabase::open(&config.geoip))
+        .transpose()?;
+
+    let server = MetricsServer::start(args.listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", args.listen))?;
+    eprintln!(
+        "{} Serving metrics on http://{}/metrics, benchmarking every {}s",
+        style("ℹ").blue(),
+        server.addr(),
+        args.interval.as_secs()
+    );
+
+    loop {
+        // Servers are collected on every run, so a changed system resolver is picked up
+        match collect_servers(&config).await {
+            Ok(servers) if servers.is_empty() => {
+                eprintln!("{} {}", style("⚠").yellow(), Localizer::new(config.lang).tr("error-no-servers"));
+            }
+            Ok(servers) => {
+                let mut result = BenchmarkEngine::new(config.clone(), servers)
+                    .with_probes(probes.clone())
+                    .run()
+                    .await;
+                if let Some(ref geoip) = geoip {
+                    geoip.annotate(&mut result);
+                }
+
+                let mut metrics = Vec::new();
+                write_prometheus(&result, &mut metrics)?;
+                server.update(String::from_utf8_lossy(&metrics).into_owned());
+                eprintln!("{}", result_line(&result, &[]));
+            }
+            Err(e) => eprintln!("{} Failed to collect servers: {e}", style("⚠").yellow()),
+        }
+
+        tokio::time::sleep(args.interval).await;
+    }
+}
+
 /// Run the DNS benchmark
-async fn run_benchmark(cli: Cli) -> anyhow::Result<()> {
+async fn run_benchmark(cli: Cli, formatters: &FormatterRegistry) -> anyhow::Result<()> {
     // Load config and apply CLI overrides
-    let mut config = Config::load_or_default();
+    let mut config = Config::try_load_or_default()?;
     config.merge(&cli.options.to_overrides());
+    if config.plain {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
 
     // Save config if requested
     if cli.options.save_config {
         config.save()?;
-        if config.format == OutputFormat::Table {
+        if config.plain {
+            println!("Configuration saved.");
+        } else if config.format == OutputFormat::Table {
             println!("{} Configuration saved.", style("✓").green());
         }
     }
 
-    // Collect DNS servers to benchmark
-    let servers = collect_servers(&config)?;
+    // Resolve requested probes and socket options before doing any network work
+    let probes = ProbeRegistry::builtin().select(&config.probes)?;
+    check_socket_options(&config.socket)?;
+    config.check_udp_only()?;
+    if config.event_log {
+        check_event_log().context("--event-log is only available on Windows")?;
+    }
+    if cli.options.share {
+        if config.share_endpoint.is_none() {
+            anyhow::bail!("--share needs an endpoint: pass --share-endpoint or set share_endpoint in the config file");
+        }
+        if !io::stdin().is_terminal() {
+            anyhow::bail!("--share asks for consent before uploading and needs an interactive terminal");
+        }
+    }
+    let geoip = (!config.geoip.is_empty())
+        .then(|| GeoIpDatabase::open(&config.geoip))
+        .transpose()?
+        .map(Arc::new);
+    let baseline = cli.options.baseline.as_deref().map(StoredRun::load).transpose()?;
+
+    // Collect DNS servers to benchmark, or stand up the chaos server in their place
+    let mut phases = PhaseTimings::default();
+    let mut chaos = None;
+    let servers = match cli.options.chaos {
+        Some(upstream) => {
+            let server = ChaosServer::start(upstream, ChaosRates::default())
+                .await
+                .context("Failed to start the chaos server")?;
+            let name = match upstream {
+                Some(ip) => format!("Chaos → {ip}"),
+                None => "Chaos (mock)".to_string(),
+            };
+            let servers = vec![DnsServer::new(name, server.addr(), ServerSource::Custom)];
+            chaos = Some(server);
+            servers
+        }
+        None => collect_servers_timed(&config, &mut phases).await?,
+    };
 
     if servers.is_empty() {
-        anyhow::bail!("No DNS servers to benchmark");
+        anyhow::bail!(Localizer::new(config.lang).tr("error-no-servers"));
     }
 
     // Get system DNS IPs for highlighting
     let system_ips: Vec<_> = if config.skip_system {
         vec![]
     } else {
-        get_system_dns_servers(config.name_server_ip)
-            .map(|s| s.into_iter().map(|ds| ds.ip()).collect())
-            .unwrap_or_default()
+        let start = Instant::now();
+        let ips = get_system_dns_servers(
+            config.name_server_ip,
+            config.detect_cache_dir().as_deref(),
+            &config.adapter_filter(),
+        )
+        .await
+        .map(|s| s.into_iter().map(|ds| ds.ip()).collect())
+        .unwrap_or_default();
+        phases.detection = Some(phases.detection.unwrap_or_default() + start.elapsed());
+        ips
     };
 
-    // Run benchmark
-    let engine = BenchmarkEngine::new(config.clone(), servers);
-    let result = engine.run().await;
+    // Runs against the chaos server aren't real measurements, so they aren't kept
+    let mut history = if config.no_history || chaos.is_some() {
+        None
+    } else {
+        match Config::history_path().map_err(Into::into).and_then(|path| History::open(&path)) {
+            Ok(history) => Some(history),
+            Err(e) => {
+                eprintln!("{} Run history disabled: {e}", style("⚠").yellow());
+                None
+            }
+        }
+    };
 
-    // Output results
-    let formatter = get_formatter(config.format);
-    let mut stdout = io::stdout().lock();
-    formatter.write(&result, &config, &system_ips, &mut stdout)?;
+    // A registered format is only known by the name it was given on the command line
+    let format = cli.options.format.clone().unwrap_or_else(|| config.format.to_string());
+    let outputs = if let Some(ref path) = cli.options.output_file {
+        vec![OutputTarget { format, path: Some(path.clone()) }]
+    } else if cli.options.output.is_empty() {
+        vec![OutputTarget::stdout(format)]
+    } else {
+        cli.options.output.clone()
+    };
+    for target in &outputs {
+        formatters.select(&target.format)?;
+    }
+    if outputs.iter().filter(|t| t.path.is_none()).count() > 1 {
+        anyhow::bail!("Only one --output can go to standard output; write the others to files, e.g. json=results.json");
+    }
+    // With --repeat, each run writes its own files
+    let runs = cli.options.repeat.unwrap_or(1);
+    let run_outputs = |run: u32| -> Vec<OutputTarget> {
+        if runs > 1 {
+            outputs.iter().map(|t| t.for_run(run)).collect()
+        } else {
+            outputs.clone()
+        }
+    };
+    // Fail now rather than after a long run if an output file can't be written
+    for target in run_outputs(1) {
+        let Some(ref path) = target.path else {
+            continue;
+        };
+        fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to write results to {}: {e}", path.display()))?;
+    }
+
+    // NDJSON lines are written as servers finish rather than after the run, all runs to the same streams.
+    // One writer task does the writing, so slow disks or pipes don't hold up the workers measuring servers.
+    let mut streams = open_ndjson_streams(&outputs)?;
+    let ndjson = (!streams.is_empty()).then(|| {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let geoip = geoip.clone();
+        let writer = tokio::task::spawn_blocking(move || {
+            while let Some(mut server) = receiver.blocking_recv() {
+                if let Some(ref geoip) = geoip {
+                    geoip.annotate_server(&mut server);
+                }
+                for stream in streams.iter_mut() {
+                    if let Err(e) = NdjsonFormatter::write_server(&server, stream) {
+                        eprintln!("{} Failed to write {}: {e}", style("⚠").yellow(), server.name);
+                    }
+                }
+            }
+        });
+        (sender, writer)
+    });
+
+    // Run the benchmark, repeatedly with --repeat, comparing each run to the one before
+    let mut previous: Option<BenchmarkResult> = None;
+    let mut regressed = 0;
+    for run in 1..=runs {
+        if run > 1 {
+            if let Some(interval) = cli.options.every {
+                tokio::time::sleep(interval).await;
+            }
+            if config.show_banners() {
+                println!();
+                println!("{} Run {run}/{runs}", style("↻").cyan().bold());
+            }
+        }
+
+        let mut engine = BenchmarkEngine::new(config.clone(), servers.clone())
+            .with_probes(probes.clone())
+            .with_phases(if run == 1 { phases } else { PhaseTimings::default() });
+        if let Some((ref sender, _)) = ndjson {
+            let sender = sender.clone();
+            engine = engine.with_on_result(move |server| {
+                // Fails only if the writer task is gone
+                let _ = sender.send(server.clone());
+            });
+        }
+        let mut result = engine.run().await;
+        if let Some(ref geoip) = geoip {
+            geoip.annotate(&mut result);
+        }
+        if let Some(ref previous) = previous {
+            result.set_previous(previous);
+        }
+        result.sort_servers(config.sort_by, config.reverse);
+
+        // Output results
+        let format_start = Instant::now();
+        // --top trims only what is written; history and the verdict line keep every server
+        let top = config.top.map(|n| {
+            let mut top = result.clone();
+            top.keep_fastest(n as usize);
+            top
+        });
+        write_outputs(&run_outputs(run), formatters, top.as_ref().unwrap_or(&result), &config, &system_ips)?;
+        result.phases.formatting = Some(format_start.elapsed());
 
+        if config.event_log {
+            write_event_log(&result).context("Failed to write to the Windows event log")?;
+        }
+
+        if let Some(ref mut history) = history
+            && let Err(e) = history.record(&result, &config)
+        {
+            eprintln!("{} Failed to store the run in the history: {e}", style("⚠").yellow());
+        }
+
+        if let Some(ref baseline) = baseline
+            && let Some(threshold) = cli.options.fail_on_regression
+        {
+            regressed += check_regressions(baseline, &result, threshold, cli.options.regression_scope.into());
+        }
+
+        // Machine-readable verdict, always last and on stderr
+        if !config.quiet {
+            eprintln!("{}", result_line(&result, &system_ips));
+        }
+        previous = Some(result);
+    }
+    let result = previous.expect("at least one run");
+
+    // Every line is written before the process goes on to exit
+    if let Some((sender, writer)) = ndjson {
+        drop(sender);
+        writer.await.context("NDJSON writer failed")?;
+    }
+
+    if let Some(ref chaos) = chaos {
+        eprintln!("{} Chaos server injected faults into {}", style("ℹ").blue(), chaos.stats());
+    }
+
+    if cli.options.share
+        && let Some(ref endpoint) = config.share_endpoint
+    {
+        let country = network_country(result.fingerprint.as_ref(), geoip.as_deref());
+        let payload = SharePayload::from_result(&result, config.protocol, country);
+        share_results(&payload, endpoint).await?;
+    }
+
+    if regressed > 0 {
+        anyhow::bail!(
+            "{regressed} server(s) regressed more than {}% against the baseline",
+            cli.options.fail_on_regression.unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// Write a finished run in every requested format, to standard output or files
+///
+/// NDJSON outputs are skipped; they were streamed while the run went on.
+fn write_outputs(
+    outputs: &[OutputTarget],
+    formatters: &FormatterRegistry,
+    result: &BenchmarkResult,
+    config: &Config,
+    system_ips: &[IpAddr],
+) -> anyhow::Result<()> {
+    for target in outputs.iter().filter(|t| t.builtin() != OutputFormat::Ndjson) {
+        let formatter = formatters.select(&target.format)?;
+        match target.path {
+            None => {
+                let mut stdout = io::stdout().lock();
+                formatter.write(result, config, system_ips, &mut stdout)?;
+                stdout.flush()?;
+            }
+            Some(ref path) => {
+                let mut buffer = Vec::new();
+                formatter.write(result, config, system_ips, &mut buffer)?;
+                // Colors are for terminals; a table in a file is read with other tools
+                if target.builtin() == OutputFormat::Table {
+                    buffer = console::strip_ansi_codes(&String::from_utf8_lossy(&buffer)).into_owned().into_bytes();
+                }
+                fs::write(path, buffer)
+                    .map_err(|e| anyhow::anyhow!("Failed to write results to {}: {e}", path.display()))?;
+            }
+        }
+    }
     Ok(())
 }
+
+/// Open the destination of every NDJSON output, truncating files
+fn open_ndjson_streams(outputs: &[OutputTarget]) -> anyhow::Result<Vec<Box<dyn Write + Send>>> {
+    outputs
+        .iter()
+        .filter(|t| t.builtin() == OutputFormat::Ndjson)
+        .map(|target| -> anyhow::Result<Box<dyn Write + Send>> {
+            match target.path {
+                None => Ok(Box::new(io::stdout())),
+                Some(ref path) => {
+                    let file = fs::File::create(path)
+                        .map_err(|e| anyhow::anyhow!("Failed to write results to {}: {e}", path.display()))?;
+                    Ok(Box::new(file))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Report servers that got slower than the baseline allows and return how many
+fn check_regressions(baseline: &StoredRun, result: &BenchmarkResult, threshold: f64, scope: RegressionScope) -> usize {
+    let comparison = RunComparison::new(baseline, &StoredRun::from_result(result));
+    if let NetworkMatch::Different(ref fields) = comparison.network {
+        eprintln!(
+            "{} The baseline was taken on a different network (mismatched {}); regressions may not be meaningful.",
+            style("⚠").yellow(),
+            fields.join(", ")
+        );
+    }
+
+    let regressions = comparison.regressions(threshold, scope);
+    for delta in &regressions {
+        let change = match (delta.base_ms, delta.current_ms, delta.change_percent()) {
+            (Some(base), Some(current), Some(change)) => format!("{base:.1}ms → {current:.1}ms (+{change:.1}%)"),
+            _ => "stopped answering".to_string(),
+        };
+        eprintln!("{} Regression: {} ({}) {change}", style("✗").red().bold(), delta.name, delta.ip);
+    }
+    regressions.len()
+}
@@ -1,12 +1,14 @@
 //! CSV output formatter.
 
 use super::OutputFormatter;
-use crate::benchmark::BenchmarkResult;
-use crate::config::Config;
+use crate::benchmark::{BenchmarkResult, ServerResult, TraceOutcome, TraceResult};
+use crate::config::{Config, ExtraColumn};
 use crate::error::OutputError;
+use crate::probe::warnings_for;
 use serde::Serialize;
 use std::io::Write;
 use std::net::IpAddr;
+use std::time::Duration;
 
 /// CSV output formatter
 pub struct CsvFormatter;
@@ -15,30 +17,35 @@ impl OutputFormatter for CsvFormatter {
     fn write(
         &self,
         result: &BenchmarkResult,
-        _config: &Config,
+        config: &Config,
         _system_ips: &[IpAddr],
         writer: &mut dyn Write,
     ) -> Result<(), OutputError> {
         let mut csv_writer = csv::Writer::from_writer(writer);
 
+        // A CSV file holds a single row shape, so the aggregated view replaces server rows
+        if config.by_provider {
+            for provider in result.by_provider() {
+                csv_writer.serialize(ProviderCsvRow {
+                    provider: provider.name.clone(),
+                    servers: provider.ips.len(),
+                    total_requests: provider.total_requests,
+                    successful_requests: provider.successful_requests,
+                    success_rate: provider.success_rate(),
+                    best_ms: provider.best_time.map(|d| d.as_secs_f64() * 1000.0),
+                    avg_ms: provider.avg_time.map(|d| d.as_secs_f64() * 1000.0),
+                })?;
+            }
+
+            csv_writer.flush()?;
+            return Ok(());
+        }
+
+        let columns = Column::for_config(config);
+        csv_writer.write_record(columns.iter().map(|c| c.header()))?;
+
         for server in &result.servers {
-            let row = CsvRow {
-                name: server.name.clone(),
-                ip: server.ip.to_string(),
-                resolved_ip: server.resolved_ip.map(|ip| ip.to_string()),
-                total_requests: server.total_requests,
-                successful_requests: server.successful_requests,
-                success_rate: server.success_rate(),
-                min_ms: server.min_time.map(|d| d.as_secs_f64() * 1000.0),
-                max_ms: server.max_time.map(|d| d.as_secs_f64() * 1000.0),
-                avg_ms: server.avg_time.map(|d| d.as_secs_f64() * 1000.0),
-                error: if server.all_failed() {
-                    server.last_error.clone()
-                } else {
-                    None
-                },
-            };
-            csv_writer.serialize(row)?;
+            csv_writer.write_record(columns.iter().map(|c| c.value(server, result)))?;
         }
 
         csv_writer.flush()?;
@@ -46,50 +53,287 @@ impl OutputFormatter for CsvFormatter {
     }
 }
 
-/// CSV row structure
+/// Columns of the per-server CSV output
+///
+/// Rows are written field by field so every record has the same number of
+/// columns; missing values are left empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Name,
+    Ip,
+    Source,
+    Protocol,
+    ResolvedIp,
+    TotalRequests,
+    SuccessfulRequests,
+    SuccessRate,
+    MinMs,
+    MaxMs,
+    AvgMs,
+    Error,
+    RefRatio,
+    RefDeltaMs,
+    PageLoadMs,
+    PageLoadSuccessRate,
+    DnssecOnMs,
+    DnssecOffMs,
+    DnssecOverheadMs,
+    NxFirstMs,
+    NxCachedMs,
+    Truncated,
+    TruncationRate,
+    TcpFallbackMs,
+    Ecs,
+    EcsScope,
+    Correctness,
+    InternalAnswered,
+    SplitHorizon,
+    VpnInterface,
+    VpnRoute,
+    AvgTtl,
+    TtlVerdict,
+    Nodes,
+    NodeChanges,
+    Probes,
+    Warnings,
+}
+
+impl Column {
+    /// Get the columns to write for the given configuration
+    fn for_config(config: &Config) -> Vec<Self> {
+        let mut columns = vec![Self::Name, Self::Ip];
+        if config.columns.contains(&ExtraColumn::Source) {
+            columns.push(Self::Source);
+        }
+        if config.columns.contains(&ExtraColumn::Protocol) || config.compares_protocols() {
+            columns.push(Self::Protocol);
+        }
+        columns.extend([
+            Self::ResolvedIp,
+            Self::TotalRequests,
+            Self::SuccessfulRequests,
+            Self::SuccessRate,
+            Self::MinMs,
+            Self::MaxMs,
+            Self::AvgMs,
+            Self::Error,
+        ]);
+        if config.reference.is_some() {
+            columns.extend([Self::RefRatio, Self::RefDeltaMs]);
+        }
+        if config.page_loads > 0 {
+            columns.extend([Self::PageLoadMs, Self::PageLoadSuccessRate]);
+        }
+        if config.dnssec_ab {
+            columns.extend([Self::DnssecOnMs, Self::DnssecOffMs, Self::DnssecOverheadMs]);
+        }
+        if config.negative_cache {
+            columns.extend([Self::NxFirstMs, Self::NxCachedMs]);
+        }
+        if config.edns_payload.is_some() {
+            columns.extend([Self::Truncated, Self::TruncationRate, Self::TcpFallbackMs]);
+        }
+        if config.ecs.is_some() {
+            columns.extend([Self::Ecs, Self::EcsScope]);
+        }
+        if config.verify_against.is_some() {
+            columns.push(Self::Correctness);
+        }
+        if !config.internal_domains.is_empty() {
+            columns.extend([Self::InternalAnswered, Self::SplitHorizon]);
+        }
+        if config.vpn_check {
+            columns.extend([Self::VpnInterface, Self::VpnRoute]);
+        }
+        if config.ttl_check {
+            columns.extend([Self::AvgTtl, Self::TtlVerdict]);
+        }
+        if config.node_id {
+            columns.extend([Self::Nodes, Self::NodeChanges]);
+        }
+        if !config.probes.is_empty() {
+            columns.extend([Self::Probes, Self::Warnings]);
+        }
+        columns
+    }
+
+    /// Header name
+    fn header(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Ip => "ip",
+            Self::Source => "source",
+            Self::Protocol => "protocol",
+            Self::ResolvedIp => "resolved_ip",
+            Self::TotalRequests => "total_requests",
+            Self::SuccessfulRequests => "successful_requests",
+            Self::SuccessRate => "success_rate",
+            Self::MinMs => "min_ms",
+            Self::MaxMs => "max_ms",
+            Self::AvgMs => "avg_ms",
+            Self::Error => "error",
+            Self::RefRatio => "ref_ratio",
+            Self::RefDeltaMs => "ref_delta_ms",
+            Self::PageLoadMs => "page_load_ms",
+            Self::PageLoadSuccessRate => "page_load_success_rate",
+            Self::DnssecOnMs => "dnssec_on_ms",
+            Self::DnssecOffMs => "dnssec_off_ms",
+            Self::DnssecOverheadMs => "dnssec_overhead_ms",
+            Self::NxFirstMs => "nx_first_ms",
+            Self::NxCachedMs => "nx_cached_ms",
+            Self::Truncated => "truncated",
+            Self::TruncationRate => "truncation_rate",
+            Self::TcpFallbackMs => "tcp_fallback_ms",
+            Self::Ecs => "ecs",
+            Self::EcsScope => "ecs_scope",
+            Self::Correctness => "correctness",
+            Self::InternalAnswered => "internal_answered",
+            Self::SplitHorizon => "split_horizon",
+            Self::VpnInterface => "vpn_interface",
+            Self::VpnRoute => "vpn_route",
+            Self::AvgTtl => "avg_ttl",
+            Self::TtlVerdict => "ttl_verdict",
+            Self::Nodes => "nodes",
+            Self::NodeChanges => "node_changes",
+            Self::Probes => "probes",
+            Self::Warnings => "warnings",
+        }
+    }
+
+    /// Field value for a server
+    fn value(self, r: &ServerResult, result: &BenchmarkResult) -> String {
+        let ms = |d: Option<Duration>| format_float(d.map(|d| d.as_secs_f64() * 1000.0));
+
+        match self {
+            Self::Name => r.name.clone(),
+            Self::Ip => r.ip.to_string(),
+            Self::Source => r.source.to_string(),
+            Self::Protocol => r.protocol.to_string(),
+            Self::ResolvedIp => r.resolved_ip.map(|ip| ip.to_string()).unwrap_or_default(),
+            Self::TotalRequests => r.total_requests.to_string(),
+            Self::SuccessfulRequests => r.successful_requests.to_string(),
+            Self::SuccessRate => format_float(Some(r.success_rate())),
+            Self::MinMs => ms(r.min_time),
+            Self::MaxMs => ms(r.max_time),
+            Self::AvgMs => ms(r.avg_time),
+            Self::Error => if r.all_failed() { r.last_error.clone().unwrap_or_default() } else { String::new() },
+            Self::RefRatio => format_float(result.normalized(r).map(|n| n.ratio)),
+            Self::RefDeltaMs => format_float(result.normalized(r).map(|n| n.delta_ms)),
+            Self::PageLoadMs => ms(r.page_load.and_then(|p| p.avg_time)),
+            Self::PageLoadSuccessRate => format_float(r.page_load.map(|p| p.success_rate())),
+            Self::DnssecOnMs => ms(r.dnssec_ab.and_then(|d| d.validated)),
+            Self::DnssecOffMs => ms(r.dnssec_ab.and_then(|d| d.unvalidated)),
+            Self::DnssecOverheadMs => format_float(r.dnssec_ab.and_then(|d| d.overhead_ms())),
+            Self::NxFirstMs => ms(r.negative_cache.and_then(|n| n.first_miss)),
+            Self::NxCachedMs => ms(r.negative_cache.and_then(|n| n.cached)),
+            Self::Truncated => r.truncated.map(|n| n.to_string()).unwrap_or_default(),
+            Self::TruncationRate => format_float(r.truncation_rate()),
+            Self::TcpFallbackMs => ms(r.tcp_fallback),
+            Self::Ecs => r.ecs.and_then(|e| e.support()).map(|s| s.to_string()).unwrap_or_default(),
+            Self::EcsScope => r.ecs.and_then(|e| e.echoed_scope()).map(|s| s.to_string()).unwrap_or_default(),
+            Self::Correctness => r.correctness.map(|c| c.to_string()).unwrap_or_default(),
+            Self::InternalAnswered => r
+                .split_horizon
+                .as_ref()
+                .map(|s| s.answered.len().to_string())
+                .unwrap_or_default(),
+            Self::SplitHorizon => r
+                .split_horizon
+                .as_ref()
+                .map(|s| s.verdict(r.source).to_string())
+                .unwrap_or_default(),
+            Self::VpnInterface => r.vpn_route.as_ref().map(|v| v.interface.clone()).unwrap_or_default(),
+            Self::VpnRoute => r.vpn_route.as_ref().map(|v| v.verdict.to_string()).unwrap_or_default(),
+            Self::AvgTtl => format_float(r.ttl.and_then(|t| t.avg())),
+            Self::TtlVerdict => r.ttl.and_then(|t| t.verdict()).map(|v| v.to_string()).unwrap_or_default(),
+            Self::Nodes => r.nodes.as_ref().map(|n| n.distinct().collect::<Vec<_>>().join(";")).unwrap_or_default(),
+            Self::NodeChanges => r.nodes.as_ref().map(|n| n.changes().to_string()).unwrap_or_default(),
+            Self::Probes => r
+                .probes
+                .iter()
+                .map(|(name, p)| format!("{name}={}", p.status))
+                .collect::<Vec<_>>()
+                .join(";"),
+            Self::Warnings => warnings_for(&r.probes).collect::<Vec<_>>().join(";"),
+        }
+    }
+}
+
+/// Format an optional float the way serde writes it (shortest round-trip form)
+fn format_float(value: Option<f64>) -> String {
+    value.map(|v| format!("{v:?}")).unwrap_or_default()
+}
+
+/// CSV row structure for the provider aggregation
 #[derive(Debug, Serialize)]
-struct CsvRow {
-    name: String,
-    ip: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    resolved_ip: Option<String>,
+struct ProviderCsvRow {
+    provider: String,
+    servers: usize,
     total_requests: u32,
     successful_requests: u32,
     success_rate: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    min_ms: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    max_ms: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    best_ms: Option<f64>,
     avg_ms: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
+}
+
+/// Write a trace as one row per query
+pub fn write_trace_csv(trace: &TraceResult, writer: &mut dyn Write) -> Result<(), OutputError> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    for (i, hop) in trace.hops.iter().enumerate() {
+        let (result, detail) = match &hop.outcome {
+            TraceOutcome::Referral { zone, .. } => ("referral", zone.clone()),
+            TraceOutcome::Answer { records } => ("answer", records.join(" | ")),
+            TraceOutcome::Negative { rcode } => ("negative", rcode.clone()),
+            TraceOutcome::Failed { error } => ("failed", error.clone()),
+        };
+        csv_writer.serialize(TraceCsvRow {
+            hop: i + 1,
+            zone: &hop.zone,
+            server: &hop.server,
+            ip: hop.ip,
+            latency_ms: hop.latency_ms,
+            result,
+            detail,
+        })?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// CSV row structure for a trace hop
+#[derive(Debug, Serialize)]
+struct TraceCsvRow<'a> {
+    hop: usize,
+    zone: &'a str,
+    server: &'a str,
+    ip: IpAddr,
+    latency_ms: f64,
+    result: &'static str,
+    detail: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::benchmark::ServerResult;
-    use crate::dns::ServerSource;
-    use std::time::Duration;
+    use crate::benchmark::{
+        AnycastNodes, Correctness, EcsResult, PageLoadResult, SplitHorizonResult, TraceHop, TtlResult,
+    };
+    use crate::dns::{DnsServer, Protocol, ServerSource};
+    use crate::probe::ProbeResult;
 
     fn make_test_result() -> BenchmarkResult {
         BenchmarkResult {
-            servers: vec![ServerResult {
-                name: "Test".to_string(),
-                ip: "8.8.8.8".parse().unwrap(),
-                source: ServerSource::Builtin,
+            domain: "google.com".to_string(),
+            ..BenchmarkResult::for_test(vec![ServerResult {
                 resolved_ip: Some("1.2.3.4".parse().unwrap()),
                 total_requests: 10,
                 successful_requests: 9,
                 min_time: Some(Duration::from_millis(5)),
                 max_time: Some(Duration::from_millis(50)),
                 avg_time: Some(Duration::from_millis(20)),
-                last_error: None,
-            }],
-            duration: Duration::from_secs(1),
-            domain: "google.com".to_string(),
-            requests_per_server: 10,
+                ..ServerResult::new(&DnsServer::from_ip("Test", "8.8.8.8".parse().unwrap(), ServerSource::Builtin))
+            }])
         }
     }
 
@@ -104,5 +348,244 @@ mod tests {
         let csv_str = String::from_utf8(output).unwrap();
         assert!(csv_str.contains("name,ip"));
         assert!(csv_str.contains("Test,8.8.8.8"));
+        assert!(!csv_str.contains("ref_ratio"));
+    }
+
+    #[test]
+    fn test_csv_output_consistent_columns() {
+        let mut result = make_test_result();
+        let mut failed = result.servers[0].clone();
+        failed.successful_requests = 0;
+        failed.resolved_ip = None;
+        failed.min_time = None;
+        failed.max_time = None;
+        failed.avg_time = None;
+        failed.last_error = Some("timed out".to_string());
+        result.servers.push(failed);
+        result.reference = Some("8.8.8.8".parse().unwrap());
+
+        let config = Config::builder().reference("8.8.8.8".parse().unwrap()).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("error,ref_ratio,ref_delta_ms"));
+        assert!(lines[1].ends_with(",20.0,,1.0,0.0"));
+        assert!(lines[2].ends_with(",,timed out,,"));
+        assert!(lines.iter().all(|l| l.matches(',').count() == 11));
+    }
+
+    #[test]
+    fn test_csv_output_page_load() {
+        let mut result = make_test_result();
+        result.servers[0].page_load = Some(PageLoadResult::from_timings(4, &[Duration::from_millis(80); 3]));
+
+        let config = Config::builder().page_loads(4).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,page_load_ms,page_load_success_rate"));
+        assert!(lines[1].ends_with(",80.0,75.0"));
+    }
+
+    #[test]
+    fn test_csv_output_extra_columns() {
+        let mut result = make_test_result();
+        result.servers[0].protocol = Protocol::Tcp;
+        let config = Config::builder()
+            .columns(vec![ExtraColumn::Protocol, ExtraColumn::Source])
+            .build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].starts_with("name,ip,source,protocol,resolved_ip,"));
+        assert!(lines[1].starts_with("Test,8.8.8.8,builtin,tcp,1.2.3.4,"));
+    }
+
+    #[test]
+    fn test_csv_output_probes() {
+        let mut result = make_test_result();
+        result.servers[0].probes.insert("tcp".into(), ProbeResult::pass("answered in 5.0ms"));
+        result.servers[0].probes.insert("dnssec".into(), ProbeResult::fail("resolves domains with invalid signatures"));
+
+        let config = Config::builder().probes(vec!["dnssec".into(), "tcp".into()]).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,probes,warnings"));
+        assert!(lines[1].ends_with(",dnssec=fail;tcp=pass,"));
+    }
+
+    #[test]
+    fn test_csv_output_ecs() {
+        let mut result = make_test_result();
+        result.servers[0].ecs = Some(EcsResult {
+            responses: 10,
+            echoed: 10,
+            max_scope: 24,
+        });
+
+        let config = Config::builder().ecs("203.0.113.0/24".parse().unwrap()).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,ecs,ecs_scope"));
+        assert!(lines[1].ends_with(",honored,24"));
+    }
+
+    #[test]
+    fn test_csv_output_correctness() {
+        let mut result = make_test_result();
+        result.servers[0].correctness = Some(Correctness::Bogus);
+
+        let config = Config::builder().verify_against("1.1.1.1".parse().unwrap()).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,correctness"));
+        assert!(lines[1].ends_with(",bogus"));
+    }
+
+    #[test]
+    fn test_csv_output_split_horizon() {
+        let mut result = make_test_result();
+        result.servers[0].split_horizon = Some(SplitHorizonResult {
+            checked: 1,
+            answered: vec!["intranet.corp.example".into()],
+        });
+
+        let config = Config::builder().internal_domains(vec!["intranet.corp.example".into()]).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,internal_answered,split_horizon"));
+        assert!(lines[1].ends_with(",1,leak"));
+    }
+
+    #[test]
+    fn test_csv_output_ttl() {
+        let mut result = make_test_result();
+        let mut ttl = TtlResult { authoritative: Some(300), ..Default::default() };
+        ttl.record(30);
+        ttl.record(60);
+        result.servers[0].ttl = Some(ttl);
+
+        let config = Config::builder().ttl_check(true).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,avg_ttl,ttl_verdict"));
+        assert!(lines[1].ends_with(",45.0,lowered"));
+    }
+
+    #[test]
+    fn test_csv_output_nodes() {
+        let mut result = make_test_result();
+        let mut nodes = AnycastNodes::default();
+        for node in ["fra08", "ams01", "ams01"] {
+            nodes.record(Some(node.to_string()));
+        }
+        result.servers[0].nodes = Some(nodes);
+
+        let config = Config::builder().node_id(true).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,nodes,node_changes"));
+        assert!(lines[1].ends_with(",fra08;ams01,1"));
+    }
+
+    #[test]
+    fn test_csv_output_truncation() {
+        let mut result = make_test_result();
+        result.servers[0].truncated = Some(2);
+        result.servers[0].tcp_fallback = Some(Duration::from_millis(12));
+
+        let config = Config::builder().edns_payload(512).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert!(lines[0].ends_with("error,truncated,truncation_rate,tcp_fallback_ms"));
+        assert!(lines[1].ends_with(",2,20.0,12.0"));
+    }
+
+    #[test]
+    fn test_csv_output_by_provider() {
+        let result = make_test_result();
+        let config = Config::builder().by_provider(true).build();
+        let mut output = Vec::new();
+
+        CsvFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        assert!(csv_str.starts_with("provider,servers,"));
+        assert!(csv_str.contains("Test,1,10,9,90.0,20.0,20.0"));
+    }
+
+    #[test]
+    fn test_csv_trace() {
+        let trace = TraceResult {
+            domain: "example.com.".to_string(),
+            record_type: "A".to_string(),
+            hops: vec![
+                TraceHop {
+                    zone: ".".to_string(),
+                    server: "a.root-servers.net.".to_string(),
+                    ip: "198.41.0.4".parse().unwrap(),
+                    latency_ms: 12.5,
+                    outcome: TraceOutcome::Referral {
+                        zone: "com.".to_string(),
+                        servers: vec!["a.gtld-servers.net.".to_string()],
+                    },
+                },
+                TraceHop {
+                    zone: "com.".to_string(),
+                    server: "a.gtld-servers.net.".to_string(),
+                    ip: "192.5.6.30".parse().unwrap(),
+                    latency_ms: 20.0,
+                    outcome: TraceOutcome::Negative { rcode: "NXDOMAIN".to_string() },
+                },
+            ],
+            total_ms: 33.0,
+        };
+        let mut output = Vec::new();
+
+        write_trace_csv(&trace, &mut output).unwrap();
+
+        let csv_str = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = csv_str.lines().collect();
+        assert_eq!(lines[0], "hop,zone,server,ip,latency_ms,result,detail");
+        assert_eq!(lines[1], "1,.,a.root-servers.net.,198.41.0.4,12.5,referral,com.");
+        assert_eq!(lines[2], "2,com.,a.gtld-servers.net.,192.5.6.30,20.0,negative,NXDOMAIN");
     }
 }
@@ -0,0 +1,228 @@
+//! DNS configuration snippets for the fastest servers of a run.
+
+use super::format_duration_ms;
+use crate::benchmark::SerializableResult;
+use crate::dns::Protocol;
+use crate::error::OutputError;
+use std::fmt;
+use std::io::Write;
+
+/// Most nameservers the glibc resolver reads from resolv.conf (`MAXNS`)
+const RESOLV_CONF_MAX_SERVERS: usize = 3;
+
+/// Configuration formats `export` can generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTarget {
+    /// `nameserver` lines for /etc/resolv.conf
+    ResolvConf,
+    /// `server=` lines for dnsmasq
+    Dnsmasq,
+    /// A forward zone for Unbound
+    Unbound,
+    /// A drop-in for /etc/systemd/resolved.conf.d/
+    SystemdResolved,
+}
+
+impl fmt::Display for ExportTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ResolvConf => write!(f, "resolv.conf"),
+            Self::Dnsmasq => write!(f, "dnsmasq"),
+            Self::Unbound => write!(f, "unbound"),
+            Self::SystemdResolved => write!(f, "systemd-resolved"),
+        }
+    }
+}
+
+/// Servers a plain DNS client can use, in the order given
+///
+/// Servers measured over several protocols are kept once, and servers only
+/// measured over DoT or DoH are left out, as the snippets configure plain
+/// DNS. `protocol` is the run's protocol, for results that don't name one.
+pub fn plain_dns_servers<'a>(servers: &[&'a SerializableResult], protocol: Protocol) -> Vec<&'a SerializableResult> {
+    let mut plain: Vec<&SerializableResult> = Vec::new();
+    for &server in servers {
+        let encrypted = matches!(server.protocol.unwrap_or(protocol), Protocol::Dot | Protocol::Doh);
+        if !encrypted && !plain.iter().any(|p| (&p.ip, p.port) == (&server.ip, server.port)) {
+            plain.push(server);
+        }
+    }
+    plain
+}
+
+/// Write a configuration snippet using `servers`, in order of preference
+///
+/// The snippet starts with comments naming `origin` (the run the servers
+/// come from) and each server's average time, so it documents itself when
+/// pasted into a config file. Only [plain DNS servers](plain_dns_servers)
+/// are configured; the others are named in a comment.
+pub fn write_export(
+    target: ExportTarget,
+    servers: &[&SerializableResult],
+    protocol: Protocol,
+    origin: &str,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let mut plain = plain_dns_servers(servers, protocol);
+    let mut left_out: Vec<(&SerializableResult, &str)> = servers
+        .iter()
+        .filter(|s| !plain.iter().any(|p| (&p.ip, p.port) == (&s.ip, s.port)))
+        .map(|&s| (s, "measured only over DoT or DoH"))
+        .collect();
+    // resolv.conf has no way to give a port
+    if target == ExportTarget::ResolvConf {
+        left_out.extend(plain.iter().filter(|s| s.port.is_some()).map(|&s| (s, "not on port 53")));
+        plain.retain(|s| s.port.is_none());
+    }
+
+    writeln!(writer, "# {target} configuration generated by dns-benchmark from {origin}")?;
+    writeln!(writer, "# Servers, fastest first:")?;
+    for (rank, server) in plain.iter().enumerate() {
+        let avg = server.avg_ms.map(format_duration_ms).unwrap_or_else(|| "-".to_string());
+        writeln!(
+            writer,
+            "#   {}. {} ({}): {avg} avg, {:.1}% answered",
+            rank + 1,
+            server.name.replace('\n', " "),
+            address(server, ":"),
+            server.success_rate
+        )?;
+    }
+    for (server, reason) in &left_out {
+        writeln!(writer, "# Left out, {reason}: {} ({})", server.name.replace('\n', " "), address(server, ":"))?;
+    }
+
+    match target {
+        ExportTarget::ResolvConf => {
+            if plain.len() > RESOLV_CONF_MAX_SERVERS {
+                writeln!(writer, "# Only the first {RESOLV_CONF_MAX_SERVERS} nameservers are used by most resolvers")?;
+            }
+            for server in &plain {
+                writeln!(writer, "nameserver {}", server.ip)?;
+            }
+        }
+        ExportTarget::Dnsmasq => {
+            writeln!(writer, "# Ignore the upstream servers in /etc/resolv.conf")?;
+            writeln!(writer, "no-resolv")?;
+            for server in &plain {
+                writeln!(writer, "server={}", address(server, "#"))?;
+            }
+        }
+        ExportTarget::Unbound => {
+            writeln!(writer, "forward-zone:")?;
+            writeln!(writer, "    name: \".\"")?;
+            for server in &plain {
+                writeln!(writer, "    forward-addr: {}", address(server, "@"))?;
+            }
+        }
+        ExportTarget::SystemdResolved => {
+            let addrs: Vec<_> = plain.iter().map(|s| address(s, ":")).collect();
+            writeln!(writer, "[Resolve]")?;
+            writeln!(writer, "DNS={}", addrs.join(" "))?;
+            writeln!(writer, "# Use these servers for all domains, not just as a fallback")?;
+            writeln!(writer, "Domains=~.")?;
+        }
+    }
+    Ok(())
+}
+
+/// A server's address, with its port after `separator` unless it's 53
+///
+/// IPv6 addresses are bracketed before a `:` separator, as in `[2001:db8::1]:5353`.
+fn address(server: &SerializableResult, separator: &str) -> String {
+    match server.port {
+        None => server.ip.clone(),
+        Some(port) if separator == ":" && server.ip.contains(':') => format!("[{}]:{port}", server.ip),
+        Some(port) => format!("{}{separator}{port}", server.ip),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::{ServerResult, TimingResult};
+    use crate::dns::{DnsServer, ServerSource};
+    use std::time::Duration;
+
+    fn export(target: ExportTarget, servers: &[&SerializableResult]) -> String {
+        let mut output = Vec::new();
+        write_export(target, servers, Protocol::Udp, "run 7", &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_export_targets() {
+        let server = |name: &str, ip: &str, ms: u64| SerializableResult::from(&ServerResult::answered(name, ip, ms));
+        let cloudflare = server("Cloudflare", "1.1.1.1", 12);
+        let google = server("Google", "2001:4860:4860::8888", 20);
+        let servers = [&cloudflare, &google];
+
+        let resolv = export(ExportTarget::ResolvConf, &servers);
+        assert!(resolv.starts_with("# resolv.conf configuration generated by dns-benchmark from run 7\n"));
+        assert!(resolv.contains("#   1. Cloudflare (1.1.1.1): 12.0ms avg, 100.0% answered\n"));
+        assert!(resolv.ends_with("nameserver 1.1.1.1\nnameserver 2001:4860:4860::8888\n"));
+        assert!(!resolv.contains("Only the first"));
+
+        let dnsmasq = export(ExportTarget::Dnsmasq, &servers);
+        assert!(dnsmasq.ends_with("no-resolv\nserver=1.1.1.1\nserver=2001:4860:4860::8888\n"));
+
+        let unbound = export(ExportTarget::Unbound, &servers);
+        assert!(unbound.ends_with(
+            "forward-zone:\n    name: \".\"\n    forward-addr: 1.1.1.1\n    forward-addr: 2001:4860:4860::8888\n"
+        ));
+
+        let resolved = export(ExportTarget::SystemdResolved, &servers);
+        assert!(resolved.contains("[Resolve]\nDNS=1.1.1.1 2001:4860:4860::8888\n"));
+        assert!(resolved.ends_with("Domains=~.\n"));
+
+        let quad9 = server("Quad9", "9.9.9.9", 15);
+        let opendns = server("OpenDNS", "208.67.222.222", 18);
+        let many = export(ExportTarget::ResolvConf, &[&cloudflare, &google, &quad9, &opendns]);
+        assert!(many.contains("# Only the first 3 nameservers are used by most resolvers\n"));
+        // Each address once
+        let repeated = export(ExportTarget::ResolvConf, &[&cloudflare, &google, &cloudflare, &google]);
+        assert_eq!(repeated.matches("nameserver 1.1.1.1").count(), 1);
+        assert!(!repeated.contains("Only the first"));
+    }
+
+    #[test]
+    fn test_export_ports_and_protocols() {
+        let server = |ip: &str, port: Option<u16>, protocol: Protocol| {
+            let server = DnsServer::from_ip("Local", ip.parse().unwrap(), ServerSource::Custom);
+            let measurements = vec![TimingResult::Success {
+                duration: Duration::from_millis(5),
+                ip: None,
+            }];
+            let mut result = SerializableResult::from(&ServerResult::from_measurements(&server, measurements));
+            result.port = port;
+            result.protocol = Some(protocol);
+            result
+        };
+        let dot = server("1.1.1.1", None, Protocol::Dot);
+        let udp = server("1.1.1.1", None, Protocol::Udp);
+        let tcp = server("1.1.1.1", None, Protocol::Tcp);
+        let v4 = server("127.0.0.1", Some(5353), Protocol::Udp);
+        let v6 = server("::1", Some(5353), Protocol::Udp);
+        let quad9 = server("9.9.9.9", None, Protocol::Doh);
+        let servers = [&dot, &udp, &tcp, &v4, &v6, &quad9];
+
+        // One entry per address, none for servers only measured encrypted
+        assert_eq!(plain_dns_servers(&servers, Protocol::Udp).len(), 3);
+        assert!(plain_dns_servers(&[&udp], Protocol::Udp)[0].protocol == Some(Protocol::Udp));
+        assert!(plain_dns_servers(&[&server("1.1.1.1", None, Protocol::Udp)], Protocol::Dot).len() == 1);
+
+        let dnsmasq = export(ExportTarget::Dnsmasq, &servers);
+        assert!(dnsmasq.ends_with("server=1.1.1.1\nserver=127.0.0.1#5353\nserver=::1#5353\n"));
+        assert!(dnsmasq.contains("# Left out, measured only over DoT or DoH: Local (9.9.9.9)\n"));
+
+        let unbound = export(ExportTarget::Unbound, &servers);
+        assert!(unbound.ends_with("forward-addr: 127.0.0.1@5353\n    forward-addr: ::1@5353\n"));
+
+        let resolved = export(ExportTarget::SystemdResolved, &servers);
+        assert!(resolved.contains("DNS=1.1.1.1 127.0.0.1:5353 [::1]:5353\n"));
+
+        let resolv = export(ExportTarget::ResolvConf, &servers);
+        assert!(resolv.ends_with("nameserver 1.1.1.1\n"));
+        assert!(resolv.contains("# Left out, not on port 53: Local ([::1]:5353)\n"));
+    }
+}
@@ -0,0 +1,343 @@
+//! Self-contained HTML report.
+
+use super::{format_duration_ms, OutputFormatter};
+use crate::benchmark::{BenchmarkResult, ServerResult};
+use crate::config::Config;
+use crate::error::OutputError;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Width of the chart drawing area, excluding labels (px)
+const CHART_WIDTH: f64 = 560.0;
+
+/// Width reserved for server names left of each chart (px)
+const LABEL_WIDTH: f64 = 200.0;
+
+/// Height of one chart row (px)
+const ROW_HEIGHT: f64 = 24.0;
+
+/// Report styles, inlined so the file renders anywhere
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 2rem auto; max-width: 60rem; color: #222; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 1.1rem; margin-top: 2rem; }
+.meta { color: #666; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { padding: 0.35rem 0.6rem; border-bottom: 1px solid #e5e5e5; text-align: left; }
+th { cursor: pointer; user-select: none; background: #f6f6f6; }
+th.sorted-asc::after { content: " ▲"; }
+th.sorted-desc::after { content: " ▼"; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+tr.system td:nth-child(2)::after { content: " (system)"; color: #2a7ae2; }
+tr.failed { color: #999; }
+.fast { color: #1a7f37; } .medium { color: #9a6700; } .slow { color: #cf222e; }
+svg text { font-size: 12px; fill: #333; }
+svg .axis { stroke: #ccc; }
+svg .bar.fast, svg .box.fast { fill: #4ac26b; } svg .bar.medium, svg .box.medium { fill: #e3b341; }
+svg .bar.slow, svg .box.slow { fill: #f85149; }
+svg .whisker { stroke: #555; }
+"#;
+
+/// Click a column header to sort by it; numeric cells sort by `data-value`
+const SCRIPT: &str = r#"
+document.querySelectorAll("th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const body = th.closest("table").tBodies[0];
+    const asc = !th.classList.contains("sorted-asc");
+    th.parentNode.querySelectorAll("th").forEach((h) => h.classList.remove("sorted-asc", "sorted-desc"));
+    th.classList.add(asc ? "sorted-asc" : "sorted-desc");
+    const key = (row) => {
+      const cell = row.cells[column];
+      const value = cell.dataset.value;
+      return value === undefined ? cell.textContent : value === "" ? Infinity : parseFloat(value);
+    };
+    [...body.rows]
+      .sort((a, b) => {
+        const [x, y] = [key(a), key(b)];
+        const order = typeof x === "number" ? x - y : x.localeCompare(y);
+        return asc ? order : -order;
+      })
+      .forEach((row) => body.appendChild(row));
+  });
+});
+"#;
+
+/// HTML report formatter
+///
+/// Writes a single standalone page with the ranked results table, a bar chart
+/// of average latency, and a box chart of each server's latency spread.
+/// Styles and the small table-sorting script are inlined, and the charts are
+/// SVG, so the file can be mailed or uploaded without any other assets.
+pub struct HtmlFormatter;
+
+impl OutputFormatter for HtmlFormatter {
+    fn write(
+        &self,
+        result: &BenchmarkResult,
+        _config: &Config,
+        system_ips: &[IpAddr],
+        writer: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        let answered: Vec<_> = result.servers.iter().filter(|s| !s.all_failed()).collect();
+        let title = format!("DNS Benchmark: {}", escape(&result.domain));
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        let _ = writeln!(
+            html,
+            "<p class=\"meta\">{} servers, {} requests each, finished in {:.1}s. Generated by dns-benchmark {}.</p>",
+            result.servers.len(),
+            result.requests_per_server,
+            result.duration.as_secs_f64(),
+            crate::VERSION
+        );
+
+        write_table(&mut html, result, system_ips);
+        if !answered.is_empty() {
+            html.push_str("<h2>Average latency</h2>\n");
+            write_bar_chart(&mut html, &answered);
+            html.push_str("<h2>Latency spread (min, 25th, median, 75th, max)</h2>\n");
+            write_box_chart(&mut html, &answered);
+        }
+
+        let _ = write!(html, "<script>{SCRIPT}</script>\n</body>\n</html>\n");
+        writer.write_all(html.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn write_table(html: &mut String, result: &BenchmarkResult, system_ips: &[IpAddr]) {
+    html.push_str(
+        "<table>\n<thead><tr><th>#</th><th>Server</th><th>IP</th><th>Provider</th><th>Avg</th><th>P95</th>\
+         <th>Min</th><th>Max</th><th>Success</th></tr></thead>\n<tbody>\n",
+    );
+    for (rank, server) in result.servers.iter().enumerate() {
+        let class = match (server.all_failed(), system_ips.contains(&server.ip)) {
+            (true, _) => " class=\"failed\"",
+            (false, true) => " class=\"system\"",
+            (false, false) => "",
+        };
+        let provider = server.provider.as_ref().map(|p| p.operator.as_ref()).unwrap_or("-");
+        let _ = write!(
+            html,
+            "<tr{class}><td class=\"num\" data-value=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td>",
+            rank + 1,
+            rank + 1,
+            escape(&server.name),
+            server.ip,
+            escape(provider)
+        );
+        for time in [server.avg_time, server.percentile(95.0), server.min_time, server.max_time] {
+            write_time_cell(html, time);
+        }
+        let _ = writeln!(
+            html,
+            "<td class=\"num\" data-value=\"{0}\">{0:.1}%</td></tr>",
+            server.success_rate()
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+fn write_time_cell(html: &mut String, time: Option<Duration>) {
+    match time.map(ms) {
+        Some(ms) => {
+            let _ = write!(
+                html,
+                "<td class=\"num {}\" data-value=\"{ms}\">{}</td>",
+                speed_class(ms),
+                format_duration_ms(ms)
+            );
+        }
+        None => html.push_str("<td class=\"num\" data-value=\"\">-</td>"),
+    }
+}
+
+fn write_bar_chart(html: &mut String, servers: &[&ServerResult]) {
+    let max = servers.iter().filter_map(|s| s.avg_time).map(ms).fold(0.0, f64::max);
+    open_chart(html, servers.len(), max);
+    for (row, server) in servers.iter().enumerate() {
+        let Some(avg) = server.avg_time.map(ms) else {
+            continue;
+        };
+        let y = row as f64 * ROW_HEIGHT;
+        write_label(html, y, &server.name);
+        let _ = writeln!(
+            html,
+            "<rect class=\"bar {}\" x=\"{LABEL_WIDTH}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\">\
+             <title>{}: {}</title></rect>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            speed_class(avg),
+            y + 4.0,
+            scale(avg, max).max(1.0),
+            ROW_HEIGHT - 8.0,
+            escape(&server.name),
+            format_duration_ms(avg),
+            LABEL_WIDTH + scale(avg, max) + 6.0,
+            y + ROW_HEIGHT / 2.0 + 4.0,
+            format_duration_ms(avg)
+        );
+    }
+    html.push_str("</svg>\n");
+}
+
+fn write_box_chart(html: &mut String, servers: &[&ServerResult]) {
+    let max = servers.iter().filter_map(|s| s.max_time).map(ms).fold(0.0, f64::max);
+    open_chart(html, servers.len(), max);
+    for (row, server) in servers.iter().enumerate() {
+        let [Some(min), Some(q1), Some(median), Some(q3), Some(top)] =
+            [0.0, 25.0, 50.0, 75.0, 100.0].map(|p| server.percentile(p).map(ms))
+        else {
+            continue;
+        };
+        let y = row as f64 * ROW_HEIGHT;
+        let middle = y + ROW_HEIGHT / 2.0;
+        let x = |value: f64| LABEL_WIDTH + scale(value, max);
+        write_label(html, y, &server.name);
+        let _ = writeln!(
+            html,
+            "<g><title>{}: min {}, median {}, max {}</title>\
+             <line class=\"whisker\" x1=\"{:.1}\" y1=\"{middle:.1}\" x2=\"{:.1}\" y2=\"{middle:.1}\"/>\
+             <rect class=\"box {}\" x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"/>\
+             <line class=\"whisker\" x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/></g>",
+            escape(&server.name),
+            format_duration_ms(min),
+            format_duration_ms(median),
+            format_duration_ms(top),
+            x(min),
+            x(top),
+            speed_class(median),
+            x(q1),
+            y + 5.0,
+            (x(q3) - x(q1)).max(1.0),
+            ROW_HEIGHT - 10.0,
+            x(median),
+            y + 3.0,
+            x(median),
+            y + ROW_HEIGHT - 3.0
+        );
+    }
+    html.push_str("</svg>\n");
+}
+
+/// Start an SVG with one row per server and an axis labelled with `max`
+fn open_chart(html: &mut String, rows: usize, max: f64) {
+    let height = rows as f64 * ROW_HEIGHT;
+    let _ = writeln!(
+        html,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100%\" viewBox=\"0 0 {} {}\" role=\"img\">\n\
+         <line class=\"axis\" x1=\"{LABEL_WIDTH}\" y1=\"0\" x2=\"{LABEL_WIDTH}\" y2=\"{height}\"/>\
+         <text x=\"{LABEL_WIDTH}\" y=\"{}\">0</text>\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+        LABEL_WIDTH + CHART_WIDTH + 80.0,
+        height + 20.0,
+        height + 16.0,
+        LABEL_WIDTH + CHART_WIDTH,
+        height + 16.0,
+        format_duration_ms(max)
+    );
+}
+
+fn write_label(html: &mut String, y: f64, name: &str) {
+    let _ = write!(
+        html,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+        LABEL_WIDTH - 8.0,
+        y + ROW_HEIGHT / 2.0 + 4.0,
+        escape(name)
+    );
+}
+
+fn scale(value: f64, max: f64) -> f64 {
+    if max > 0.0 {
+        value / max * CHART_WIDTH
+    } else {
+        0.0
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// CSS class matching the table's latency colors
+fn speed_class(ms: f64) -> &'static str {
+    match super::get_time_color(ms) {
+        console::Color::Green => "fast",
+        console::Color::Yellow => "medium",
+        _ => "slow",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::{DnsServer, ServerSource};
+
+    #[test]
+    fn test_html_output() {
+        let server = |name: &str, ip: &str, times: &[u64]| {
+            let server = DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin);
+            let measurements = times
+                .iter()
+                .map(|&ms| TimingResult::Success {
+                    duration: Duration::from_millis(ms),
+                    ip: Some("192.0.2.10".parse().unwrap()),
+                })
+                .collect();
+            ServerResult::from_measurements(&server, measurements)
+        };
+        let dead = ServerResult::from_measurements(
+            &DnsServer::from_ip("Dead", "10.0.0.2".parse().unwrap(), ServerSource::Custom),
+            vec![TimingResult::Failure { error: "timeout".to_string() }],
+        );
+        let result = BenchmarkResult {
+            duration: Duration::from_secs(2),
+            requests_per_server: 3,
+            ..BenchmarkResult::for_test(vec![
+                server("Cloudflare", "1.1.1.1", &[10, 12, 14]),
+                server("<Office>", "10.0.0.1", &[40, 90]),
+                dead,
+            ])
+        };
+        let system_ips = ["10.0.0.1".parse().unwrap()];
+
+        let mut output = Vec::new();
+        HtmlFormatter
+            .write(&result, &Config::default(), &system_ips, &mut output)
+            .unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</html>"));
+        assert!(html.contains("<title>DNS Benchmark: example.com</title>"));
+        assert_eq!(html.matches("<tr").count(), 4);
+        assert!(html.contains("<tr class=\"system\">"));
+        assert!(html.contains("<tr class=\"failed\">"));
+        assert!(html.contains("&lt;Office&gt;"));
+        assert!(!html.contains("<Office>"));
+        assert!(html.contains("data-value=\"12\">12.0ms</td>"));
+
+        // Both charts leave out the server without answers
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert_eq!(html.matches("class=\"bar ").count(), 2);
+        assert_eq!(html.matches("class=\"box ").count(), 2);
+        assert!(!html.contains(">Dead</text>"));
+    }
+}
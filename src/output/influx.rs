@@ -0,0 +1,127 @@
+//! InfluxDB line protocol output formatter.
+
+use super::OutputFormatter;
+use crate::benchmark::{BenchmarkResult, ServerResult};
+use crate::config::Config;
+use crate::error::OutputError;
+use std::io::Write;
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Measurement all points are written to
+const MEASUREMENT: &str = "dns_benchmark";
+
+/// InfluxDB line protocol formatter, one point per server
+///
+/// Points are tagged with the server's name, IP, provider, and source, and
+/// carry `avg_ms`, `p95_ms`, and `success_rate` fields. All points of a run
+/// share its end time, in nanoseconds, so the output can be piped into
+/// Telegraf or `influx write` as is.
+pub struct InfluxFormatter;
+
+impl OutputFormatter for InfluxFormatter {
+    fn write(
+        &self,
+        result: &BenchmarkResult,
+        _config: &Config,
+        _system_ips: &[IpAddr],
+        writer: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        write_points(result, timestamp, writer)
+    }
+}
+
+fn write_points(result: &BenchmarkResult, timestamp: u128, writer: &mut dyn Write) -> Result<(), OutputError> {
+    for server in &result.servers {
+        writeln!(writer, "{MEASUREMENT},{} {} {timestamp}", tags(server, result), fields(server))?;
+    }
+    Ok(())
+}
+
+fn tags(server: &ServerResult, result: &BenchmarkResult) -> String {
+    // Empty tag values are invalid, so an unknown provider leaves the tag out
+    let mut tags = format!("server={},ip={}", escape_tag(&server.name), server.ip);
+    if let Some(ref provider) = server.provider {
+        tags.push_str(&format!(",provider={}", escape_tag(&provider.operator)));
+    }
+    tags.push_str(&format!(",source={},domain={}", server.source, escape_tag(&result.domain)));
+    tags
+}
+
+fn fields(server: &ServerResult) -> String {
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    let mut fields = Vec::new();
+    if let Some(avg) = server.avg_time {
+        fields.push(format!("avg_ms={}", ms(avg)));
+    }
+    if let Some(p95) = server.percentile(95.0) {
+        fields.push(format!("p95_ms={}", ms(p95)));
+    }
+    fields.push(format!("success_rate={}", server.success_rate()));
+    fields.push(format!("requests={}i", server.total_requests));
+    fields.join(",")
+}
+
+/// Escape commas, spaces, and equals signs in a tag value, and turn line breaks into spaces
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | ' ' | '=' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // Line protocol has no escape for line breaks
+            '\n' | '\r' => escaped.push_str("\\ "),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::{get_builtin_servers, DnsServer, IpVersion, ServerSource};
+    use std::time::Duration;
+
+    #[test]
+    fn test_influx_output() {
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let cloudflare = get_builtin_servers(IpVersion::V4)
+            .into_iter()
+            .find(|s| s.ip().to_string() == "1.1.1.1")
+            .unwrap();
+        let office = DnsServer::from_ip("Office DNS, 2nd", "10.0.0.1".parse().unwrap(), ServerSource::Custom);
+        let result = BenchmarkResult {
+            requests_per_server: 2,
+            ..BenchmarkResult::for_test(vec![
+                ServerResult::from_measurements(&cloudflare, vec![success(10), success(20)]),
+                ServerResult::from_measurements(&office, vec![TimingResult::Failure { error: "timeout".into() }]),
+            ])
+        };
+
+        let mut output = Vec::new();
+        write_points(&result, 1_700_000_000_000_000_000, &mut output).unwrap();
+        let lines: Vec<_> = std::str::from_utf8(&output).unwrap().lines().map(String::from).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("dns_benchmark,server=Cloudflare,ip=1.1.1.1,provider=Cloudflare"));
+        assert!(lines[0].ends_with(
+            ",source=builtin,domain=example.com avg_ms=15,p95_ms=20,success_rate=100,requests=2i 1700000000000000000"
+        ));
+        assert_eq!(
+            lines[1],
+            "dns_benchmark,server=Office\\ DNS\\,\\ 2nd,ip=10.0.0.1,source=custom,domain=example.com \
+             success_rate=0,requests=1i 1700000000000000000"
+        );
+    }
+}
@@ -1,8 +1,12 @@
 //! JSON output formatter.
 
 use super::OutputFormatter;
-use crate::benchmark::{BenchmarkResult, SerializableResult};
-use crate::config::Config;
+use crate::benchmark::{
+    BenchmarkResult, NetworkFingerprint, PhaseTimings, SerializableDualStack, SerializableProtocolComparison,
+    SerializableProvider, SerializableResult, TimingCalibration,
+};
+use crate::dns::IpVersion;
+use crate::config::{Config, ExtraColumn};
 use crate::error::OutputError;
 use serde::Serialize;
 use std::io::Write;
@@ -15,11 +19,28 @@ impl OutputFormatter for JsonFormatter {
     fn write(
         &self,
         result: &BenchmarkResult,
-        _config: &Config,
+        config: &Config,
         _system_ips: &[IpAddr],
         writer: &mut dyn Write,
     ) -> Result<(), OutputError> {
-        let output = JsonOutput::from(result);
+        let mut output = JsonOutput::from(result);
+        for (entry, server) in output.results.iter_mut().zip(&result.servers) {
+            if config.columns.contains(&ExtraColumn::Source) {
+                entry.source = Some(server.source.to_string());
+            }
+            if config.columns.contains(&ExtraColumn::Protocol) || config.compares_protocols() {
+                entry.protocol = Some(server.protocol);
+            }
+        }
+        if config.by_provider {
+            output.providers = Some(result.by_provider().iter().map(SerializableProvider::from).collect());
+        }
+        if config.name_server_ip == IpVersion::Both {
+            output.dual_stack = Some(result.dual_stack().iter().map(SerializableDualStack::from).collect());
+        }
+        if config.compares_protocols() {
+            output.protocols = Some(result.by_protocol().iter().map(SerializableProtocolComparison::from).collect());
+        }
         let json = serde_json::to_string_pretty(&output)?;
         writeln!(writer, "{}", json)?;
         Ok(())
@@ -33,6 +54,15 @@ struct JsonOutput {
     meta: JsonMeta,
     /// Results for each server
     results: Vec<SerializableResult>,
+    /// Results aggregated by provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    providers: Option<Vec<SerializableProvider>>,
+    /// IPv4 vs IPv6 comparison per provider (dual-stack runs only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dual_stack: Option<Vec<SerializableDualStack>>,
+    /// Latency of each server over each protocol (`--protocols` runs only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocols: Option<Vec<SerializableProtocolComparison>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -41,6 +71,18 @@ struct JsonMeta {
     requests_per_server: u32,
     total_servers: usize,
     duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fingerprint: Option<NetworkFingerprint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calibration: Option<TimingCalibration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authoritative_ttl: Option<u32>,
+    #[serde(skip_serializing_if = "PhaseTimings::is_empty")]
+    phases: PhaseTimings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
 }
 
 impl From<&BenchmarkResult> for JsonOutput {
@@ -51,8 +93,21 @@ impl From<&BenchmarkResult> for JsonOutput {
                 requests_per_server: result.requests_per_server,
                 total_servers: result.servers.len(),
                 duration_ms: result.duration.as_secs_f64() * 1000.0,
+                reference: result.reference.map(|ip| ip.to_string()),
+                fingerprint: result.fingerprint.clone(),
+                calibration: result.calibration,
+                authoritative_ttl: result.authoritative_ttl,
+                phases: result.phases,
+                seed: result.seed,
             },
-            results: result.servers.iter().map(SerializableResult::from).collect(),
+            results: result
+                .servers
+                .iter()
+                .map(|s| SerializableResult::from_benchmark(s, result))
+                .collect(),
+            providers: None,
+            dual_stack: None,
+            protocols: None,
         }
     }
 }
@@ -61,26 +116,21 @@ impl From<&BenchmarkResult> for JsonOutput {
 mod tests {
     use super::*;
     use crate::benchmark::ServerResult;
-    use crate::dns::ServerSource;
+    use crate::dns::{DnsServer, ServerSource};
     use std::time::Duration;
 
     fn make_test_result() -> BenchmarkResult {
         BenchmarkResult {
-            servers: vec![ServerResult {
-                name: "Test".to_string(),
-                ip: "8.8.8.8".parse().unwrap(),
-                source: ServerSource::Builtin,
+            domain: "google.com".to_string(),
+            ..BenchmarkResult::for_test(vec![ServerResult {
                 resolved_ip: Some("1.2.3.4".parse().unwrap()),
                 total_requests: 10,
                 successful_requests: 9,
                 min_time: Some(Duration::from_millis(5)),
                 max_time: Some(Duration::from_millis(50)),
                 avg_time: Some(Duration::from_millis(20)),
-                last_error: None,
-            }],
-            duration: Duration::from_secs(1),
-            domain: "google.com".to_string(),
-            requests_per_server: 10,
+                ..ServerResult::new(&DnsServer::from_ip("Test", "8.8.8.8".parse().unwrap(), ServerSource::Builtin))
+            }])
         }
     }
 
@@ -95,5 +145,79 @@ mod tests {
         let json_str = String::from_utf8(output).unwrap();
         assert!(json_str.contains("\"domain\": \"google.com\""));
         assert!(json_str.contains("\"name\": \"Test\""));
+        assert!(!json_str.contains("\"providers\""));
+    }
+
+    #[test]
+    fn test_json_output_loads_as_stored_run() {
+        let mut result = make_test_result();
+        result.fingerprint = Some(NetworkFingerprint {
+            public_prefix: Some("203.0.113.0/24".into()),
+            ..Default::default()
+        });
+        let mut output = Vec::new();
+
+        JsonFormatter.write(&result, &Config::default(), &[], &mut output).unwrap();
+
+        let run = crate::benchmark::StoredRun::from_json_reader(output.as_slice()).unwrap();
+        assert_eq!(run.meta.fingerprint, result.fingerprint);
+        assert_eq!(run.results[0].ip, "8.8.8.8");
+    }
+
+    #[test]
+    fn test_json_output_provider_info() {
+        let mut result = make_test_result();
+        result.servers[0].provider = crate::dns::builtin_provider("Google").cloned();
+        let mut output = Vec::new();
+
+        JsonFormatter.write(&result, &Config::default(), &[], &mut output).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["results"][0]["provider"]["country"], "US");
+        assert_eq!(json["results"][0]["provider"]["filtering"], "none");
+    }
+
+    #[test]
+    fn test_json_output_extra_columns() {
+        let result = make_test_result();
+        let mut output = Vec::new();
+        JsonFormatter.write(&result, &Config::default(), &[], &mut output).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(json["results"][0].get("source").is_none());
+
+        let config = Config::builder().columns(vec![ExtraColumn::Source, ExtraColumn::Protocol]).build();
+        let mut output = Vec::new();
+        JsonFormatter.write(&result, &config, &[], &mut output).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["results"][0]["source"], "builtin");
+        assert_eq!(json["results"][0]["protocol"], "udp");
+    }
+
+    #[test]
+    fn test_json_output_reference() {
+        let mut result = make_test_result();
+        result.reference = Some("8.8.8.8".parse().unwrap());
+        let config = Config::default();
+        let mut output = Vec::new();
+
+        JsonFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["meta"]["reference"], "8.8.8.8");
+        assert_eq!(json["results"][0]["ref_ratio"], 1.0);
+        assert_eq!(json["results"][0]["ref_delta_ms"], 0.0);
+    }
+
+    #[test]
+    fn test_json_output_by_provider() {
+        let result = make_test_result();
+        let config = Config::builder().by_provider(true).build();
+        let mut output = Vec::new();
+
+        JsonFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["providers"][0]["name"], "Test");
+        assert_eq!(json["providers"][0]["ips"][0], "8.8.8.8");
     }
 }
@@ -0,0 +1,196 @@
+//! JUnit XML output formatter.
+
+use super::{format_duration_ms, OutputFormatter};
+use crate::benchmark::{BenchmarkResult, ServerResult};
+use crate::config::Config;
+use crate::error::OutputError;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::{Cursor, Write};
+use std::net::IpAddr;
+
+/// JUnit XML formatter for CI systems
+///
+/// Each server is a test case in one test suite named after the domain.
+/// A server fails when it answered no queries, or when it misses the
+/// `junit_max_avg_ms` or `junit_min_success` thresholds of the config.
+/// A test case's time is the server's average latency.
+pub struct JunitFormatter;
+
+impl OutputFormatter for JunitFormatter {
+    fn write(
+        &self,
+        result: &BenchmarkResult,
+        config: &Config,
+        _system_ips: &[IpAddr],
+        writer: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        let cases: Vec<_> = result.servers.iter().map(|s| (s, failures(s, config))).collect();
+        let tests = cases.len().to_string();
+        let failed = cases.iter().filter(|(_, f)| !f.is_empty()).count().to_string();
+        let time = format!("{:.3}", result.duration.as_secs_f64());
+
+        let mut buffer = Cursor::new(Vec::new());
+        let mut xml_writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+        write_event(&mut xml_writer, Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+        let suites = BytesStart::new("testsuites").with_attributes([
+            ("name", "dns-benchmark"),
+            ("tests", tests.as_str()),
+            ("failures", failed.as_str()),
+            ("time", time.as_str()),
+        ]);
+        write_event(&mut xml_writer, Event::Start(suites))?;
+        let suite = BytesStart::new("testsuite").with_attributes([
+            ("name", result.domain.as_str()),
+            ("tests", tests.as_str()),
+            ("failures", failed.as_str()),
+            ("errors", "0"),
+            ("skipped", "0"),
+            ("time", time.as_str()),
+        ]);
+        write_event(&mut xml_writer, Event::Start(suite))?;
+
+        for (server, failures) in &cases {
+            let name = format!("{} ({})", server.name, server.ip);
+            let classname = format!("dns-benchmark.{}", server.source);
+            let case_time = format!("{:.6}", server.avg_time.unwrap_or_default().as_secs_f64());
+            let case = BytesStart::new("testcase").with_attributes([
+                ("name", name.as_str()),
+                ("classname", classname.as_str()),
+                ("time", case_time.as_str()),
+            ]);
+            write_event(&mut xml_writer, Event::Start(case))?;
+
+            if !failures.is_empty() {
+                let message = failures.join("; ");
+                let failure = BytesStart::new("failure")
+                    .with_attributes([("message", message.as_str()), ("type", "threshold")]);
+                write_event(&mut xml_writer, Event::Start(failure))?;
+                write_event(&mut xml_writer, Event::Text(BytesText::new(&message)))?;
+                write_event(&mut xml_writer, Event::End(BytesEnd::new("failure")))?;
+            }
+
+            write_event(&mut xml_writer, Event::Start(BytesStart::new("system-out")))?;
+            write_event(&mut xml_writer, Event::Text(BytesText::new(&stats(server))))?;
+            write_event(&mut xml_writer, Event::End(BytesEnd::new("system-out")))?;
+            write_event(&mut xml_writer, Event::End(BytesEnd::new("testcase")))?;
+        }
+
+        write_event(&mut xml_writer, Event::End(BytesEnd::new("testsuite")))?;
+        write_event(&mut xml_writer, Event::End(BytesEnd::new("testsuites")))?;
+
+        writer.write_all(&buffer.into_inner())?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+/// Reasons a server fails, empty if it passes
+fn failures(server: &ServerResult, config: &Config) -> Vec<String> {
+    let mut failures = Vec::new();
+    if server.all_failed() {
+        failures.push(match server.last_error {
+            Some(ref error) => format!("no successful queries (last error: {error})"),
+            None => "no successful queries".to_string(),
+        });
+        return failures;
+    }
+    if let Some(max) = config.junit_max_avg_ms
+        && let Some(avg) = server.avg_time.map(|d| d.as_secs_f64() * 1000.0)
+        && avg > max
+    {
+        failures.push(format!(
+            "average latency {} above {}",
+            format_duration_ms(avg),
+            format_duration_ms(max)
+        ));
+    }
+    if let Some(min) = config.junit_min_success
+        && server.success_rate() < min
+    {
+        failures.push(format!("success rate {:.1}% below {min}%", server.success_rate()));
+    }
+    failures
+}
+
+/// Latency and success summary shown as the test case's output
+fn stats(server: &ServerResult) -> String {
+    let time = |d: Option<std::time::Duration>| {
+        d.map(|d| format_duration_ms(d.as_secs_f64() * 1000.0))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    format!(
+        "avg {}, p95 {}, min {}, max {}, {}/{} queries answered ({:.1}%)",
+        time(server.avg_time),
+        time(server.percentile(95.0)),
+        time(server.min_time),
+        time(server.max_time),
+        server.successful_requests,
+        server.total_requests,
+        server.success_rate()
+    )
+}
+
+fn write_event<W: Write>(writer: &mut Writer<W>, event: Event<'_>) -> Result<(), OutputError> {
+    writer.write_event(event).map_err(|e| OutputError::Xml(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::{DnsServer, ServerSource};
+    use std::time::Duration;
+
+    fn result() -> BenchmarkResult {
+        let server = |name: &str, ip: &str, measurements: Vec<TimingResult>| {
+            let server = DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin);
+            ServerResult::from_measurements(&server, measurements)
+        };
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let failure = || TimingResult::Failure { error: "timed out".to_string() };
+        BenchmarkResult {
+            duration: Duration::from_secs(2),
+            requests_per_server: 2,
+            ..BenchmarkResult::for_test(vec![
+                server("Cloudflare", "1.1.1.1", vec![success(10), success(14)]),
+                server("Slow & Lossy", "10.0.0.1", vec![success(120), failure()]),
+                server("Dead", "10.0.0.2", vec![failure(), failure()]),
+            ])
+        }
+    }
+
+    fn write(config: &Config) -> String {
+        let mut output = Vec::new();
+        JunitFormatter.write(&result(), config, &[], &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_junit_output() {
+        let xml = write(&Config::default());
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains(r#"<testsuite name="example.com" tests="3" failures="1""#));
+        assert!(xml.contains(
+            r#"<testcase name="Cloudflare (1.1.1.1)" classname="dns-benchmark.builtin" time="0.012000">"#
+        ));
+        assert!(xml.contains("Slow &amp; Lossy (10.0.0.1)"));
+        assert!(xml.contains(r#"<failure message="no successful queries (last error: timed out)" type="threshold">"#));
+        assert!(xml.contains("<system-out>avg 12.0ms, p95 14.0ms, min 10.0ms, max 14.0ms, 2/2 queries answered"));
+    }
+
+    #[test]
+    fn test_junit_thresholds() {
+        let config = Config::builder().junit_max_avg_ms(100.0).junit_min_success(99.0).build();
+        let xml = write(&config);
+        assert!(xml.contains(r#"tests="3" failures="2""#));
+        assert!(xml.contains(
+            r#"<failure message="average latency 120ms above 100ms; success rate 50.0% below 99%" type="threshold">"#
+        ));
+        assert_eq!(xml.matches("<failure ").count(), 2);
+    }
+}
@@ -0,0 +1,243 @@
+//! Markdown report output formatter.
+
+use super::{format_duration_ms, OutputFormatter};
+use crate::benchmark::{BenchmarkResult, ServerResult, SplitHorizon};
+use crate::config::Config;
+use crate::error::OutputError;
+use crate::i18n::Localizer;
+use std::io::Write;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Markdown report formatter
+///
+/// Writes a summary, the ranked results table, and notes on every server
+/// that missed queries, as GitHub-flavored Markdown for issues and wikis.
+pub struct MarkdownFormatter;
+
+impl OutputFormatter for MarkdownFormatter {
+    fn write(
+        &self,
+        result: &BenchmarkResult,
+        config: &Config,
+        system_ips: &[IpAddr],
+        writer: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        let l = Localizer::new(config.lang);
+
+        writeln!(writer, "# {}", l.tr_args("report-title", [("domain", escape(&result.domain).into())]))?;
+        writeln!(writer)?;
+        writeln!(writer, "## {}", l.tr("report-summary"))?;
+        writeln!(writer)?;
+        for line in summary(result, system_ips, &l) {
+            writeln!(writer, "- {line}")?;
+        }
+        writeln!(writer)?;
+
+        writeln!(writer, "## {}", l.tr("report-results"))?;
+        writeln!(writer)?;
+        let headers = [
+            "#".to_string(),
+            l.tr("col-server"),
+            l.tr("col-ip"),
+            l.tr("col-provider"),
+            l.tr("col-avg"),
+            l.tr("col-p95"),
+            l.tr("col-min"),
+            l.tr("col-max"),
+            l.tr("col-success-rate"),
+        ];
+        writeln!(writer, "| {} |", headers.join(" | "))?;
+        writeln!(writer, "|---:|---|---|---|---:|---:|---:|---:|---:|")?;
+        for (rank, server) in result.servers.iter().enumerate() {
+            let provider = server.provider.as_ref().map(|p| p.operator.as_ref()).unwrap_or("-");
+            writeln!(
+                writer,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {:.1}% |",
+                rank + 1,
+                escape(&server.name),
+                server.ip,
+                escape(provider),
+                time(server.avg_time),
+                time(server.percentile(95.0)),
+                time(server.min_time),
+                time(server.max_time),
+                server.success_rate()
+            )?;
+        }
+        writeln!(writer)?;
+
+        writeln!(writer, "## {}", l.tr("report-failures"))?;
+        writeln!(writer)?;
+        let failures: Vec<_> = result
+            .servers
+            .iter()
+            .filter(|s| s.successful_requests < s.total_requests)
+            .collect();
+        if failures.is_empty() {
+            writeln!(writer, "{}", l.tr("report-no-failures"))?;
+        }
+        for server in failures {
+            write!(
+                writer,
+                "- **{}** ({}): {}",
+                escape(&server.name),
+                server.ip,
+                l.tr_args(
+                    "report-answered",
+                    [
+                        ("successful", server.successful_requests.into()),
+                        ("total", server.total_requests.into()),
+                    ]
+                )
+            )?;
+            match server.last_error {
+                Some(ref error) => {
+                    let error = format!("`{}`", error.replace('`', "'").replace('\n', " "));
+                    writeln!(writer, ", {}", l.tr_args("report-last-error", [("error", error.into())]))?
+                }
+                None => writeln!(writer)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Summary bullets: run scope, fastest, system and reference servers, split DNS
+fn summary(result: &BenchmarkResult, system_ips: &[IpAddr], l: &Localizer) -> Vec<String> {
+    let mut lines = vec![
+        l.tr_args("summary-completed", [("duration", format!("{:.2?}", result.duration).into())]),
+        l.tr_args(
+            "report-scope",
+            [
+                ("servers", result.servers.len().into()),
+                ("requests", result.requests_per_server.into()),
+            ],
+        ),
+    ];
+
+    let server_line = |id: &str, server: &ServerResult, avg: Duration| {
+        l.tr_args(
+            id,
+            [
+                ("name", format!("**{}**", escape(&server.name)).into()),
+                ("ip", server.ip.to_string().into()),
+                ("time", format_duration_ms(avg.as_secs_f64() * 1000.0).into()),
+            ],
+        )
+    };
+    if let Some(fastest) = result.fastest()
+        && let Some(avg) = fastest.avg_time
+    {
+        lines.push(server_line("summary-fastest", fastest, avg));
+    }
+    if let Some(system) = result.servers.iter().find(|s| system_ips.contains(&s.ip) && !s.all_failed())
+        && let Some(avg) = system.avg_time
+    {
+        lines.push(l.tr_args(
+            "report-system",
+            [
+                ("name", format!("**{}**", escape(&system.name)).into()),
+                ("ip", system.ip.to_string().into()),
+                ("rank", result.speed_rank(system).into()),
+                ("total", result.servers.len().into()),
+                ("time", format_duration_ms(avg.as_secs_f64() * 1000.0).into()),
+            ],
+        ));
+    }
+    if let Some(reference) = result.reference_result()
+        && let Some(avg) = reference.avg_time
+    {
+        lines.push(server_line("summary-reference", reference, avg));
+    }
+
+    let verdicts: Vec<_> = result
+        .servers
+        .iter()
+        .filter_map(|r| r.split_horizon.as_ref().map(|split| split.verdict(r.source)))
+        .filter(|verdict| *verdict != SplitHorizon::Unknown)
+        .collect();
+    if !verdicts.is_empty() {
+        let leaks = verdicts.iter().filter(|v| **v == SplitHorizon::Leak).count();
+        let missing = verdicts.iter().filter(|v| **v == SplitHorizon::Missing).count();
+        lines.push(if leaks == 0 && missing == 0 {
+            l.tr("summary-split-horizon-ok")
+        } else {
+            l.tr_args("summary-split-horizon-issues", [("leaks", leaks.into()), ("missing", missing.into())])
+        });
+    }
+
+    if result.servers.iter().any(|r| r.vpn_route.is_some()) {
+        let bypassing = result.servers.iter().filter(|r| r.bypasses_vpn()).count();
+        lines.push(if bypassing == 0 {
+            l.tr("summary-vpn-ok")
+        } else {
+            l.tr_args("summary-vpn-leaks", [("count", bypassing.into())])
+        });
+    }
+
+    lines
+}
+
+fn time(d: Option<Duration>) -> String {
+    d.map(|d| format_duration_ms(d.as_secs_f64() * 1000.0))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Escape characters Markdown would read as formatting or table cell breaks
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::{DnsServer, ServerSource};
+
+    #[test]
+    fn test_markdown_output() {
+        let server = |name: &str, ip: &str, measurements: Vec<TimingResult>| {
+            let server = DnsServer::from_ip(name, ip.parse().unwrap(), ServerSource::Builtin);
+            ServerResult::from_measurements(&server, measurements)
+        };
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let failure = || TimingResult::Failure { error: "timed out".to_string() };
+        let result = BenchmarkResult {
+            duration: Duration::from_secs(2),
+            requests_per_server: 2,
+            ..BenchmarkResult::for_test(vec![
+                server("Cloudflare", "1.1.1.1", vec![success(10), success(14)]),
+                server("Office | LAN", "10.0.0.1", vec![success(30), failure()]),
+                server("Dead", "10.0.0.2", vec![failure(), failure()]),
+            ])
+        };
+        let system_ips = ["10.0.0.1".parse().unwrap()];
+
+        let mut output = Vec::new();
+        MarkdownFormatter
+            .write(&result, &Config::default(), &system_ips, &mut output)
+            .unwrap();
+        let md = String::from_utf8(output).unwrap();
+
+        assert!(md.starts_with("# DNS Benchmark: example.com\n\n## Summary\n"));
+        assert!(md.contains("- Fastest: **Cloudflare** (1.1.1.1) - 12.0ms\n"));
+        assert!(md.contains("- System DNS: **Office \\| LAN** (10.0.0.1) ranks 2 of 3 - 30.0ms\n"));
+        assert!(md.contains("| 1 | Cloudflare | 1.1.1.1 | - | 12.0ms | 14.0ms | 10.0ms | 14.0ms | 100.0% |\n"));
+        assert!(md.contains("| 3 | Dead | 10.0.0.2 | - | - | - | - | - | 0.0% |\n"));
+        assert!(md.contains("- **Office \\| LAN** (10.0.0.1): answered 1 of 2 queries, last error: `timed out`\n"));
+        assert!(md.contains("- **Dead** (10.0.0.2): answered 0 of 2 queries"));
+        assert!(!md.contains("- **Cloudflare**"));
+    }
+}
@@ -1,14 +1,35 @@
 //! Output formatting for benchmark results.
 
 mod csv;
+mod export;
+mod html;
+mod influx;
 mod json;
+mod junit;
+mod markdown;
+mod ndjson;
+mod prometheus;
+mod registry;
 mod table;
 mod xml;
 
-pub use self::csv::CsvFormatter;
+pub use self::csv::{write_trace_csv, CsvFormatter};
+pub use self::export::{plain_dns_servers, write_export, ExportTarget};
+pub use self::html::HtmlFormatter;
+pub use self::influx::InfluxFormatter;
 pub use self::json::JsonFormatter;
-pub use self::table::TableFormatter;
-pub use self::xml::XmlFormatter;
+pub use self::junit::JunitFormatter;
+pub use self::markdown::MarkdownFormatter;
+pub use self::ndjson::NdjsonFormatter;
+pub use self::prometheus::write_prometheus;
+pub use self::registry::{FormatterRegistry, RegisteredFormatter};
+#[cfg(feature = "engine")]
+pub use self::table::{write_history_list, write_history_run};
+pub use self::table::{
+    live_table, write_comparison, write_region_comparison, write_resolve, write_scenario_report, write_server_list,
+    write_trace, TableFormatter,
+};
+pub use self::xml::{write_trace_xml, XmlFormatter};
 
 use crate::benchmark::BenchmarkResult;
 use crate::config::Config;
@@ -17,7 +38,9 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io::Write;
 use std::net::IpAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
 
 /// Output format selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -32,6 +55,21 @@ pub enum OutputFormat {
     Xml,
     /// CSV format
     Csv,
+    /// InfluxDB line protocol
+    Influx,
+    /// Newline-delimited JSON, one line per server
+    Ndjson,
+    /// Standalone HTML report with charts
+    Html,
+    /// Markdown report
+    Markdown,
+    /// JUnit XML, one test case per server
+    Junit,
+    /// A formatter registered in a [`FormatterRegistry`] rather than built in
+    ///
+    /// Its output is taken to be machine-readable, so progress and the
+    /// summary aren't printed next to it.
+    Custom,
 }
 
 impl fmt::Display for OutputFormat {
@@ -41,10 +79,25 @@ impl fmt::Display for OutputFormat {
             Self::Json => write!(f, "json"),
             Self::Xml => write!(f, "xml"),
             Self::Csv => write!(f, "csv"),
+            Self::Influx => write!(f, "influx"),
+            Self::Ndjson => write!(f, "ndjson"),
+            Self::Html => write!(f, "html"),
+            Self::Markdown => write!(f, "markdown"),
+            Self::Junit => write!(f, "junit"),
+            Self::Custom => write!(f, "custom"),
         }
     }
 }
 
+impl OutputFormat {
+    /// Built-in format a formatter name stands for, aliases included
+    ///
+    /// [`Custom`](Self::Custom) for the name of any other registered formatter.
+    pub fn named(name: &str) -> Self {
+        name.parse().unwrap_or(Self::Custom)
+    }
+}
+
 impl FromStr for OutputFormat {
     type Err = crate::Error;
 
@@ -54,11 +107,87 @@ impl FromStr for OutputFormat {
             "json" => Ok(Self::Json),
             "xml" => Ok(Self::Xml),
             "csv" => Ok(Self::Csv),
+            "influx" | "influxdb" | "line-protocol" => Ok(Self::Influx),
+            "ndjson" | "jsonl" | "json-lines" => Ok(Self::Ndjson),
+            "html" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "junit" => Ok(Self::Junit),
             _ => Err(crate::Error::InvalidArgument(format!("Invalid output format: {s}"))),
         }
     }
 }
 
+/// One requested output: a format, written to a file or standard output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTarget {
+    /// Name of the format, looked up in a [`FormatterRegistry`]
+    pub format: String,
+    /// File to write to; standard output if `None`
+    pub path: Option<PathBuf>,
+}
+
+impl OutputTarget {
+    /// Write `format` to standard output
+    pub fn stdout(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+            path: None,
+        }
+    }
+
+    /// Built-in format the name stands for, or [`OutputFormat::Custom`]
+    pub fn builtin(&self) -> OutputFormat {
+        OutputFormat::named(&self.format)
+    }
+
+    /// Where run `run` of a `--repeat` session is written
+    ///
+    /// Each run gets its own file, numbered before the extension
+    /// (`results-2.json`), as documents can't be appended to one another.
+    /// NDJSON files and standard output take every run in turn.
+    pub fn for_run(&self, run: u32) -> Self {
+        let path = match self.path {
+            Some(ref path) if self.builtin() != OutputFormat::Ndjson => {
+                let mut name = path.file_stem().unwrap_or_default().to_os_string();
+                name.push(format!("-{run}"));
+                if let Some(extension) = path.extension() {
+                    name.push(".");
+                    name.push(extension);
+                }
+                Some(path.with_file_name(name))
+            }
+            ref path => path.clone(),
+        };
+        Self {
+            format: self.format.clone(),
+            path,
+        }
+    }
+}
+
+impl FromStr for OutputTarget {
+    type Err = crate::Error;
+
+    /// Parse `json` (standard output) or `json=results.json`
+    ///
+    /// The format name isn't checked here, as formatters can be registered
+    /// after parsing; [`FormatterRegistry::select`] reports unknown ones.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let target = match s.split_once('=') {
+            Some((_, "")) => return Err(crate::Error::InvalidArgument(format!("Missing file name in output: {s}"))),
+            Some((format, path)) => Self {
+                format: format.to_string(),
+                path: Some(PathBuf::from(path)),
+            },
+            None => Self::stdout(s),
+        };
+        if target.format.is_empty() {
+            return Err(crate::Error::InvalidArgument(format!("Missing format in output: {s}")));
+        }
+        Ok(target)
+    }
+}
+
 /// Trait for output formatters
 pub trait OutputFormatter {
     /// Write benchmark results to the given writer
@@ -71,14 +200,43 @@ pub trait OutputFormatter {
     ) -> Result<(), OutputError>;
 }
 
-/// Get the appropriate formatter for a format
-pub fn get_formatter(format: OutputFormat) -> Box<dyn OutputFormatter> {
-    match format {
-        OutputFormat::Table => Box::new(TableFormatter),
-        OutputFormat::Json => Box::new(JsonFormatter),
-        OutputFormat::Xml => Box::new(XmlFormatter),
-        OutputFormat::Csv => Box::new(CsvFormatter),
-    }
+/// Get the built-in formatter for a format
+///
+/// # Panics
+///
+/// For [`OutputFormat::Custom`], which has no built-in formatter; look
+/// custom formats up by name in their [`FormatterRegistry`] instead.
+pub fn get_formatter(format: OutputFormat) -> Arc<dyn OutputFormatter + Send + Sync> {
+    static BUILTIN: LazyLock<FormatterRegistry> = LazyLock::new(FormatterRegistry::builtin);
+    BUILTIN
+        .get(&format.to_string())
+        .expect("every built-in output format has a formatter")
+}
+
+/// Build the one-line verdict printed to stderr after every run
+///
+/// Shell scripts can grab the result without parsing the chosen output
+/// format, e.g. `RESULT fastest=Cloudflare ip=1.1.1.1 avg_ms=11.8 system_rank=4`.
+/// Unknown values are written as `-`, and values containing spaces are quoted.
+pub fn result_line(result: &BenchmarkResult, system_ips: &[IpAddr]) -> String {
+    let fastest = result.fastest().filter(|s| !s.all_failed());
+    let system_rank = result
+        .servers
+        .iter()
+        .find(|s| system_ips.contains(&s.ip) && !s.all_failed())
+        .map(|s| result.speed_rank(s).to_string());
+
+    let quote = |v: &str| if v.contains(char::is_whitespace) { format!("\"{v}\"") } else { v.to_string() };
+    format!(
+        "RESULT fastest={} ip={} avg_ms={} system_rank={}",
+        fastest.map(|s| quote(&s.name)).unwrap_or_else(|| "-".into()),
+        fastest.map(|s| s.ip.to_string()).unwrap_or_else(|| "-".into()),
+        fastest
+            .and_then(|s| s.avg_time)
+            .map(|t| format!("{:.1}", t.as_secs_f64() * 1000.0))
+            .unwrap_or_else(|| "-".into()),
+        system_rank.as_deref().unwrap_or("-"),
+    )
 }
 
 /// Format a duration in milliseconds with appropriate precision
@@ -94,6 +252,31 @@ pub fn format_duration_ms(ms: f64) -> String {
     }
 }
 
+/// When to color terminal output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Color terminals, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Color even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Turn colors on or off for everything written from now on, tables included
+pub fn set_color_mode(mode: ColorMode) {
+    let enabled = match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) => false,
+        // Leave it to the terminal check (and CLICOLOR / CLICOLOR_FORCE)
+        ColorMode::Auto => return,
+    };
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+}
+
 /// Get color code based on response time
 pub fn get_time_color(ms: f64) -> console::Color {
     if ms <= 30.0 {
@@ -105,6 +288,17 @@ pub fn get_time_color(ms: f64) -> console::Color {
     }
 }
 
+/// Get color code based on response time jitter (ms)
+pub fn get_jitter_color(ms: f64) -> console::Color {
+    if ms <= 5.0 {
+        console::Color::Green
+    } else if ms <= 20.0 {
+        console::Color::Yellow
+    } else {
+        console::Color::Red
+    }
+}
+
 /// Get color code based on success rate
 pub fn get_success_color(rate: f64) -> console::Color {
     if rate >= 100.0 {
@@ -117,3 +311,82 @@ pub fn get_success_color(rate: f64) -> console::Color {
         console::Color::Magenta
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::ServerResult;
+    use crate::dns::{DnsServer, ServerSource};
+    use std::time::Duration;
+
+    fn server(name: &str, ip: [u8; 4], avg_us: Option<u64>) -> ServerResult {
+        let time = avg_us.map(Duration::from_micros);
+        ServerResult {
+            total_requests: 10,
+            successful_requests: if avg_us.is_some() { 10 } else { 0 },
+            min_time: time,
+            max_time: time,
+            avg_time: time,
+            ..ServerResult::new(&DnsServer::from_ip(name, ip.into(), ServerSource::Builtin))
+        }
+    }
+
+    fn result(servers: Vec<ServerResult>) -> BenchmarkResult {
+        BenchmarkResult::for_test(servers)
+    }
+
+    #[test]
+    fn test_result_line() {
+        let result = result(vec![
+            server("Cloudflare", [1, 1, 1, 1], Some(11_840)),
+            server("Google", [8, 8, 8, 8], Some(14_000)),
+            server("System DNS (Primary)", [192, 168, 1, 1], Some(20_000)),
+        ]);
+
+        assert_eq!(
+            result_line(&result, &["192.168.1.1".parse().unwrap()]),
+            "RESULT fastest=Cloudflare ip=1.1.1.1 avg_ms=11.8 system_rank=3"
+        );
+        assert_eq!(
+            result_line(&result, &[]),
+            "RESULT fastest=Cloudflare ip=1.1.1.1 avg_ms=11.8 system_rank=-"
+        );
+    }
+
+    #[test]
+    fn test_result_line_quotes_and_failures() {
+        let quoted = result(vec![server("My Router", [192, 168, 1, 1], Some(2_000))]);
+        assert_eq!(
+            result_line(&quoted, &["192.168.1.1".parse().unwrap()]),
+            "RESULT fastest=\"My Router\" ip=192.168.1.1 avg_ms=2.0 system_rank=1"
+        );
+
+        let failed = result(vec![server("Dead", [192, 0, 2, 1], None)]);
+        assert_eq!(result_line(&failed, &[]), "RESULT fastest=- ip=- avg_ms=- system_rank=-");
+    }
+
+    #[test]
+    fn test_output_target_from_str() {
+        assert_eq!(OutputTarget::from_str("table").unwrap(), OutputTarget::stdout("table"));
+        assert_eq!(
+            OutputTarget::from_str("json=out/results.json").unwrap(),
+            OutputTarget {
+                format: "json".to_string(),
+                path: Some(PathBuf::from("out/results.json")),
+            }
+        );
+        assert!(OutputTarget::from_str("csv=").is_err());
+        assert!(OutputTarget::from_str("=results.csv").is_err());
+
+        // Aliases stand for built-in formats; other names for registered formatters
+        assert_eq!(OutputTarget::from_str("jsonl").unwrap().builtin(), OutputFormat::Ndjson);
+        assert_eq!(OutputTarget::from_str("count=servers.txt").unwrap().builtin(), OutputFormat::Custom);
+
+        let run = |s: &str| OutputTarget::from_str(s).unwrap().for_run(2).path;
+        assert_eq!(run("json=out/results.json"), Some(PathBuf::from("out/results-2.json")));
+        assert_eq!(run("table=results"), Some(PathBuf::from("results-2")));
+        assert_eq!(run("ndjson=results.ndjson"), Some(PathBuf::from("results.ndjson")));
+        assert_eq!(run("json"), None);
+        assert_eq!(run("jsonl=results.jsonl"), Some(PathBuf::from("results.jsonl")));
+    }
+}
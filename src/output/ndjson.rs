@@ -0,0 +1,80 @@
+//! Newline-delimited JSON output formatter.
+
+use super::OutputFormatter;
+use crate::benchmark::{BenchmarkResult, SerializableResult, ServerResult};
+use crate::config::Config;
+use crate::error::OutputError;
+use std::io::Write;
+use std::net::IpAddr;
+
+/// Newline-delimited JSON formatter, one compact object per server
+///
+/// Each line has the same fields as an entry of the JSON output's `results`.
+/// The CLI streams lines with [`NdjsonFormatter::write_server`] as servers
+/// finish; written after the run, lines are ranked like the other formats.
+pub struct NdjsonFormatter;
+
+impl NdjsonFormatter {
+    /// Write one server's result as a single line
+    pub fn write_server(server: &ServerResult, writer: &mut dyn Write) -> Result<(), OutputError> {
+        write_line(&SerializableResult::from(server), writer)
+    }
+}
+
+impl OutputFormatter for NdjsonFormatter {
+    fn write(
+        &self,
+        result: &BenchmarkResult,
+        _config: &Config,
+        _system_ips: &[IpAddr],
+        writer: &mut dyn Write,
+    ) -> Result<(), OutputError> {
+        for server in &result.servers {
+            write_line(&SerializableResult::from_benchmark(server, result), writer)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_line(entry: &SerializableResult, writer: &mut dyn Write) -> Result<(), OutputError> {
+    serde_json::to_writer(&mut *writer, entry)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::ServerResult;
+
+    #[test]
+    fn test_ndjson_output() {
+        let result = BenchmarkResult {
+            requests_per_server: 1,
+            ..BenchmarkResult::for_test(vec![
+                ServerResult::answered("Cloudflare", "1.1.1.1", 12),
+                ServerResult::answered("Google", "8.8.8.8", 20),
+            ])
+        };
+
+        let mut output = Vec::new();
+        NdjsonFormatter
+            .write(&result, &Config::default(), &[], &mut output)
+            .unwrap();
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["name"], "Cloudflare");
+        assert_eq!(lines[1]["avg_ms"], 20.0);
+
+        let mut output = Vec::new();
+        NdjsonFormatter::write_server(&result.servers[1], &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.matches('\n').count(), 1);
+        assert!(text.starts_with(r#"{"name":"Google","ip":"8.8.8.8""#));
+    }
+}
@@ -0,0 +1,125 @@
+//! Prometheus text exposition of benchmark results.
+
+use crate::benchmark::{BenchmarkResult, ServerResult};
+use crate::error::OutputError;
+use std::io::Write;
+
+/// Latency percentiles exposed per server
+const QUANTILES: [f64; 3] = [0.5, 0.95, 0.99];
+
+/// Write a run as Prometheus gauges, in the text exposition format
+///
+/// Every per-server metric is labelled with the server's name, IP, source,
+/// and provider. Latency metrics are left out for servers without a single
+/// successful query; their success ratio is still written as 0.
+pub fn write_prometheus(result: &BenchmarkResult, writer: &mut dyn Write) -> Result<(), OutputError> {
+    let servers = &result.servers;
+
+    header(writer, "dns_benchmark_latency_avg_seconds", "Average response time of successful queries")?;
+    for server in servers {
+        if let Some(avg) = server.avg_time {
+            writeln!(writer, "dns_benchmark_latency_avg_seconds{{{}}} {}", labels(server), avg.as_secs_f64())?;
+        }
+    }
+
+    header(writer, "dns_benchmark_latency_seconds", "Response time percentiles of successful queries")?;
+    for server in servers {
+        for quantile in QUANTILES {
+            if let Some(time) = server.percentile(quantile * 100.0) {
+                writeln!(
+                    writer,
+                    "dns_benchmark_latency_seconds{{{},quantile=\"{quantile}\"}} {}",
+                    labels(server),
+                    time.as_secs_f64()
+                )?;
+            }
+        }
+    }
+
+    header(writer, "dns_benchmark_success_ratio", "Share of queries that got an answer (0 to 1)")?;
+    for server in servers {
+        writeln!(writer, "dns_benchmark_success_ratio{{{}}} {}", labels(server), server.success_rate() / 100.0)?;
+    }
+
+    header(writer, "dns_benchmark_queries", "Queries sent in the last run")?;
+    for server in servers {
+        writeln!(writer, "dns_benchmark_queries{{{}}} {}", labels(server), server.total_requests)?;
+    }
+
+    header(writer, "dns_benchmark_run_duration_seconds", "Duration of the last run")?;
+    writeln!(writer, "dns_benchmark_run_duration_seconds {}", result.duration.as_secs_f64())?;
+
+    Ok(())
+}
+
+fn header(writer: &mut dyn Write, name: &str, help: &str) -> Result<(), OutputError> {
+    writeln!(writer, "# HELP {name} {help}")?;
+    writeln!(writer, "# TYPE {name} gauge")?;
+    Ok(())
+}
+
+fn labels(server: &ServerResult) -> String {
+    let provider = server.provider.as_ref().map(|p| p.operator.as_ref()).unwrap_or_default();
+    format!(
+        "server=\"{}\",ip=\"{}\",source=\"{}\",provider=\"{}\"",
+        escape(&server.name),
+        server.ip,
+        server.source,
+        escape(provider)
+    )
+}
+
+/// Escape a label value as the exposition format requires
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::{DnsServer, ServerSource};
+    use std::time::Duration;
+
+    #[test]
+    fn test_prometheus_output() {
+        let success = |ms: u64| TimingResult::Success {
+            duration: Duration::from_millis(ms),
+            ip: Some("192.0.2.10".parse().unwrap()),
+        };
+        let failure = || TimingResult::Failure { error: "timeout".to_string() };
+        let server = |name: &str, ip: &str| {
+            let ip: std::net::IpAddr = ip.parse().unwrap();
+            DnsServer::new(name.to_string(), (ip, 53).into(), ServerSource::Builtin)
+        };
+        let result = BenchmarkResult {
+            duration: Duration::from_millis(1500),
+            requests_per_server: 4,
+            ..BenchmarkResult::for_test(vec![
+                ServerResult::from_measurements(
+                    &server("Office \"Main\"", "10.0.0.1"),
+                    vec![success(10), success(20), success(30), failure()],
+                ),
+                ServerResult::from_measurements(&server("Dead", "10.0.0.2"), vec![failure()]),
+            ])
+        };
+
+        let mut output = Vec::new();
+        write_prometheus(&result, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+
+        let main = r#"server="Office \"Main\"",ip="10.0.0.1",source="builtin",provider="""#;
+        assert!(text.contains("# TYPE dns_benchmark_latency_avg_seconds gauge"));
+        assert!(text.contains(&format!("dns_benchmark_latency_avg_seconds{{{main}}} 0.02\n")));
+        assert!(text.contains(&format!("dns_benchmark_latency_seconds{{{main},quantile=\"0.5\"}} 0.02\n")));
+        assert!(text.contains(&format!("dns_benchmark_latency_seconds{{{main},quantile=\"0.99\"}} 0.03\n")));
+        assert!(text.contains(&format!("dns_benchmark_success_ratio{{{main}}} 0.75\n")));
+        assert!(text.contains(&format!("dns_benchmark_queries{{{main}}} 4\n")));
+        assert!(text.contains("dns_benchmark_run_duration_seconds 1.5\n"));
+
+        // A server without answers has a success ratio, but no latency
+        let dead = r#"server="Dead",ip="10.0.0.2",source="builtin",provider="""#;
+        assert!(text.contains(&format!("dns_benchmark_success_ratio{{{dead}}} 0\n")));
+        assert!(!text.contains(r#"dns_benchmark_latency_avg_seconds{server="Dead""#));
+    }
+}
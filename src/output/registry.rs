@@ -0,0 +1,145 @@
+//! Output formatter registry.
+
+use super::{
+    CsvFormatter, HtmlFormatter, InfluxFormatter, JsonFormatter, JunitFormatter, MarkdownFormatter, NdjsonFormatter,
+    OutputFormat, OutputFormatter, TableFormatter, XmlFormatter,
+};
+use crate::error::Error;
+use std::sync::Arc;
+
+/// A formatter registered under a name
+#[derive(Clone)]
+pub struct RegisteredFormatter {
+    /// Name used to select the format
+    pub name: &'static str,
+    /// One-line description for listings
+    pub description: &'static str,
+    /// The formatter itself
+    pub formatter: Arc<dyn OutputFormatter + Send + Sync>,
+}
+
+/// Collection of available output formatters, looked up by name
+///
+/// Library users can register their own [`OutputFormatter`]s next to the
+/// built-in ones, then pick one by name like the built-in formats.
+#[derive(Clone, Default)]
+pub struct FormatterRegistry {
+    formatters: Vec<RegisteredFormatter>,
+}
+
+impl FormatterRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in formatters, named like [`OutputFormat`]
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register("table", "Human-readable table", TableFormatter);
+        registry.register("json", "JSON document", JsonFormatter);
+        registry.register("xml", "XML document", XmlFormatter);
+        registry.register("csv", "CSV, one row per server", CsvFormatter);
+        registry.register("influx", "InfluxDB line protocol", InfluxFormatter);
+        registry.register("ndjson", "Newline-delimited JSON, one line per server", NdjsonFormatter);
+        registry.register("html", "Standalone HTML report with charts", HtmlFormatter);
+        registry.register("markdown", "Markdown report", MarkdownFormatter);
+        registry.register("junit", "JUnit XML, one test case per server", JunitFormatter);
+        registry
+    }
+
+    /// Add a formatter, replacing any existing formatter with the same name
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        description: &'static str,
+        formatter: impl OutputFormatter + Send + Sync + 'static,
+    ) {
+        self.formatters.retain(|f| !f.name.eq_ignore_ascii_case(name));
+        self.formatters.push(RegisteredFormatter {
+            name,
+            description,
+            formatter: Arc::new(formatter),
+        });
+    }
+
+    /// Look up a formatter by name
+    ///
+    /// Aliases of the built-in formats, like `md` or `jsonl`, are accepted too.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn OutputFormatter + Send + Sync>> {
+        let find = |name: &str| self.formatters.iter().find(|f| f.name.eq_ignore_ascii_case(name));
+        find(name)
+            .or_else(|| name.parse::<OutputFormat>().ok().and_then(|format| find(&format.to_string())))
+            .map(|f| Arc::clone(&f.formatter))
+    }
+
+    /// Look up a formatter by name, listing the available ones if it is unknown
+    pub fn select(&self, name: &str) -> Result<Arc<dyn OutputFormatter + Send + Sync>, Error> {
+        self.get(name).ok_or_else(|| {
+            let known: Vec<_> = self.formatters.iter().map(|f| f.name).collect();
+            Error::InvalidArgument(format!("Unknown output format '{name}' (available: {})", known.join(", ")))
+        })
+    }
+
+    /// Iterate over all registered formatters
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredFormatter> {
+        self.formatters.iter()
+    }
+}
+
+impl std::fmt::Debug for FormatterRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.formatters.iter().map(|f| f.name)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::BenchmarkResult;
+    use crate::config::Config;
+    use crate::error::OutputError;
+    use std::io::Write;
+    use std::net::IpAddr;
+
+    struct ServerCount;
+
+    impl OutputFormatter for ServerCount {
+        fn write(
+            &self,
+            result: &BenchmarkResult,
+            _config: &Config,
+            _system_ips: &[IpAddr],
+            writer: &mut dyn Write,
+        ) -> Result<(), OutputError> {
+            writeln!(writer, "{} servers", result.servers.len())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_formatter_registry() {
+        let mut registry = FormatterRegistry::builtin();
+        assert_eq!(registry.iter().count(), 9);
+        assert!(registry.get("MD").is_some());
+        assert!(registry.get("count").is_none());
+
+        registry.register("count", "Number of servers", ServerCount);
+        let result = BenchmarkResult {
+            duration: Default::default(),
+            requests_per_server: 1,
+            ..BenchmarkResult::for_test(Vec::new())
+        };
+        let mut output = Vec::new();
+        registry
+            .select("Count")
+            .unwrap()
+            .write(&result, &Config::default(), &[], &mut output)
+            .unwrap();
+        assert_eq!(output, b"0 servers\n");
+
+        let error = registry.select("yaml").err().unwrap().to_string();
+        assert!(error.contains("available: table, json"), "{error}");
+        assert!(error.ends_with("junit, count)"), "{error}");
+    }
+}
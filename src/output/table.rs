@@ -1,14 +1,26 @@
 //! Table output formatter.
 
-use super::{format_duration_ms, get_success_color, get_time_color, OutputFormatter};
-use crate::benchmark::BenchmarkResult;
-use crate::config::{Config, TableStyle};
+use super::{format_duration_ms, get_jitter_color, get_success_color, get_time_color, OutputFormatter};
+use crate::benchmark::{
+    AnswerRecord, BenchmarkResult, Correctness, DualStackComparison, EcsSupport, ProtocolComparison, ProviderResult,
+    ResolveResult, RunComparison, ServerDelta, ServerResult, SplitHorizon, TraceOutcome, TraceResult, TtlVerdict,
+    VpnRoute,
+};
+use crate::config::{Config, ExtraColumn, TableStyle};
+use crate::dns::{DnsServer, IpVersion, Protocol};
 use crate::error::OutputError;
+#[cfg(feature = "engine")]
+use crate::history::{HistoryRun, RunSummary};
+use crate::i18n::Localizer;
+use crate::probe::{glyphs_for, has_glyph, has_warning, warnings_for, PROBE_GLYPHS};
+use crate::scenario::ScenarioReport;
+use crate::share::RegionComparison;
 use console::{style, Color};
 use std::io::Write;
 use std::net::IpAddr;
 use std::time::Duration;
-use tabled::settings::{object, Alignment, Color as TabledColor, Modify, Style};
+use tabled::builder::Builder;
+use tabled::settings::{object, Alignment, Color as TabledColor, Format, Modify, Style};
 use tabled::{Table, Tabled};
 
 /// Table output formatter
@@ -22,85 +34,1149 @@ impl OutputFormatter for TableFormatter {
         system_ips: &[IpAddr],
         writer: &mut dyn Write,
     ) -> Result<(), OutputError> {
-        let rows: Vec<TableRow> = result
+        let l = Localizer::new(config.lang);
+        let mut columns = Column::for_config(config);
+        if result.previous.is_some() {
+            let avg = columns.iter().position(|c| *c == Column::Avg).unwrap_or(columns.len() - 1);
+            columns.insert(avg + 1, Column::Trend);
+        }
+        let table = server_table(result, &columns, system_ips, config, &l);
+
+        writeln!(writer, "{}", table)?;
+
+        // Explain probe glyphs shown next to server names
+        let legend: Vec<_> = PROBE_GLYPHS
+            .iter()
+            .filter(|g| result.servers.iter().any(|s| glyphs_for(&s.probes).any(|m| m == *g)))
+            .map(|g| format!("{} {}", g.glyph, g.meaning))
+            .collect();
+        if !legend.is_empty() && !config.plain {
+            writeln!(writer, "{} {}", style(l.tr("legend")).dim(), legend.join("  "))?;
+        }
+
+        // Print provider aggregation
+        if config.by_provider {
+            writeln!(writer)?;
+            writeln!(writer, "{}", provider_table(&result.by_provider(), config, &l))?;
+        }
+
+        // Print IPv4 vs IPv6 comparison
+        if config.name_server_ip == IpVersion::Both {
+            let pairs = result.dual_stack();
+            if !pairs.is_empty() {
+                writeln!(writer)?;
+                writeln!(writer, "{}", dual_stack_table(&pairs, config, &l))?;
+            }
+        }
+
+        // Print each server's latency over every protocol
+        if config.compares_protocols() {
+            let comparisons = result.by_protocol();
+            if !comparisons.is_empty() {
+                writeln!(writer)?;
+                let protocols = config.compared_protocols();
+                writeln!(writer, "{}", protocol_table(&comparisons, &protocols, config, &l))?;
+                if protocols.iter().any(|p| *p != Protocol::Udp) {
+                    writeln!(writer, "{}", style(l.tr("protocols-handshake-note")).dim())?;
+                }
+            }
+        }
+
+        // Print summary, with a symbol in front of each line unless plain
+        let mark = |symbol: console::StyledObject<&str>| if config.plain { String::new() } else { format!("{symbol} ") };
+        writeln!(writer)?;
+        if !config.quiet {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("✓").green().bold()),
+                l.tr_args("summary-completed", [("duration", format!("{:.2?}", result.duration).into())])
+            )?;
+        }
+
+        if !result.phases.is_empty() && !config.quiet {
+            let phases: Vec<_> = result
+                .phases
+                .iter()
+                .map(|(name, d)| format!("{} {:.2}s", l.tr(&format!("phase-{name}")), d.as_secs_f64()))
+                .collect();
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("◇").dim().bold()),
+                style(l.tr_args("summary-phases", [("phases", phases.join(", ").into())])).dim()
+            )?;
+        }
+
+        if let Some(fastest) = result.fastest()
+            && let Some(avg) = fastest.avg_time
+        {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("★").yellow().bold()),
+                l.tr_args(
+                    "summary-fastest",
+                    [
+                        ("name", style(&fastest.name).green().to_string().into()),
+                        ("ip", fastest.ip.to_string().into()),
+                        ("time", style(format_duration_ms(avg.as_secs_f64() * 1000.0)).cyan().to_string().into()),
+                    ]
+                )
+            )?;
+        }
+
+        let failed = result.completely_failed().count();
+        if failed > 0 {
+            let id = if config.hide_failed { "summary-all-failed-hidden" } else { "summary-all-failed" };
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("✗").red().bold()),
+                l.tr_args(id, [("count", failed.into())])
+            )?;
+        }
+
+        let ineligible = result.ineligible().count();
+        if ineligible > 0
+            && let Some(rate) = result.ranking.min_success
+        {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("⚠").yellow().bold()),
+                l.tr_args("summary-ineligible", [("count", ineligible.into()), ("rate", rate.into())])
+            )?;
+        }
+
+        if let Some(reference) = result.reference_result()
+            && let Some(avg) = reference.avg_time
+        {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("◆").cyan().bold()),
+                l.tr_args(
+                    "summary-reference",
+                    [
+                        ("name", reference.name.as_str().into()),
+                        ("ip", reference.ip.to_string().into()),
+                        ("time", style(format_duration_ms(avg.as_secs_f64() * 1000.0)).cyan().to_string().into()),
+                    ]
+                )
+            )?;
+        }
+
+        if let Some(cal) = result.calibration {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("◇").dim().bold()),
+                l.tr_args(
+                    "summary-noise-floor",
+                    [
+                        ("floor", style(format_duration_ms(cal.noise_floor_ms())).cyan().to_string().into()),
+                        ("loopback", format!("{:.1}", cal.loopback_rtt_us).into()),
+                        ("jitter", format!("{:.1}", cal.jitter_us).into()),
+                    ]
+                )
+            )?;
+        }
+
+        if let Some(ttl) = result.authoritative_ttl {
+            writeln!(
+                writer,
+                "{}{}",
+                mark(style("◇").dim().bold()),
+                l.tr_args("summary-authoritative-ttl", [("ttl", ttl.into())])
+            )?;
+        }
+
+        // Split-DNS sanity report, of the servers known to be internal or public
+        let verdicts: Vec<_> = result
             .servers
             .iter()
-            .map(|s| TableRow::from_result(s, system_ips))
+            .filter_map(|r| r.split_horizon.as_ref().map(|split| split.verdict(r.source)))
+            .filter(|verdict| *verdict != SplitHorizon::Unknown)
             .collect();
+        if !verdicts.is_empty() {
+            let leaks = verdicts.iter().filter(|v| **v == SplitHorizon::Leak).count();
+            let missing = verdicts.iter().filter(|v| **v == SplitHorizon::Missing).count();
+            if leaks == 0 && missing == 0 {
+                writeln!(writer, "{}{}", mark(style("✓").green().bold()), l.tr("summary-split-horizon-ok"))?;
+            } else {
+                writeln!(
+                    writer,
+                    "{}{}",
+                    mark(style("⚠").yellow().bold()),
+                    l.tr_args(
+                        "summary-split-horizon-issues",
+                        [("leaks", leaks.into()), ("missing", missing.into())]
+                    )
+                )?;
+            }
+        }
 
-        let mut table = Table::new(&rows);
+        // VPN leak report
+        if result.servers.iter().any(|r| r.vpn_route.is_some()) {
+            let bypassing = result.servers.iter().filter(|r| r.bypasses_vpn()).count();
+            if bypassing == 0 {
+                writeln!(writer, "{}{}", mark(style("✓").green().bold()), l.tr("summary-vpn-ok"))?;
+            } else {
+                writeln!(
+                    writer,
+                    "{}{}",
+                    mark(style("⚠").yellow().bold()),
+                    l.tr_args("summary-vpn-leaks", [("count", bypassing.into())])
+                )?;
+            }
+        }
 
-        // Apply style
-        apply_style(&mut table, config.style);
+        Ok(())
+    }
+}
 
-        // Center header
-        table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+/// Latency changes smaller than this (ms) are shown as steady in the trend column
+const TREND_STEADY_MS: f64 = 0.5;
+
+/// Most characters in a sparkline; longer runs are averaged into this many buckets
+const SPARKLINE_WIDTH: usize = 50;
 
-        // Apply colors to data cells
-        for (i, s) in result.servers.iter().enumerate() {
-            let row_idx = i + 1; // Skip header row
+/// Bar heights of a sparkline, lowest first
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 
-            // Success rate color
+/// Columns of the main results table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Server,
+    Ip,
+    Source,
+    Protocol,
+    ResolvedIp,
+    SuccessRate,
+    Min,
+    Max,
+    Avg,
+    Jitter,
+    Sparkline,
+    Failures,
+    Trend,
+    Reference,
+    PageLoad,
+    DnssecAb,
+    NegativeCache,
+    Truncated,
+    Ecs,
+    Correctness,
+    SplitHorizon,
+    VpnRoute,
+    Ttl,
+    Node,
+    Filtering,
+    Probes,
+    Warnings,
+    ServerGeo,
+    AnswerGeo,
+}
+
+impl Column {
+    /// Get the columns to render for the given configuration
+    fn for_config(config: &Config) -> Vec<Self> {
+        // Plain output has no glyphs, so every probe result goes in the Probes column
+        let in_probes_column = |p: &String| config.plain || p.eq_ignore_ascii_case("all") || !has_glyph(p);
+        let mut columns = vec![Self::Server, Self::Ip];
+        if config.columns.contains(&ExtraColumn::Source) {
+            columns.push(Self::Source);
+        }
+        if config.columns.contains(&ExtraColumn::Protocol) || config.compares_protocols() {
+            columns.push(Self::Protocol);
+        }
+        columns.extend([Self::ResolvedIp, Self::SuccessRate, Self::Min, Self::Max, Self::Avg, Self::Jitter]);
+        // Block characters mean nothing to a screen reader
+        if config.columns.contains(&ExtraColumn::Sparkline) && !config.plain {
+            columns.push(Self::Sparkline);
+        }
+        if config.columns.contains(&ExtraColumn::Failures) {
+            columns.push(Self::Failures);
+        }
+        if config.reference.is_some() {
+            columns.push(Self::Reference);
+        }
+        if config.page_loads > 0 {
+            columns.push(Self::PageLoad);
+        }
+        if config.dnssec_ab {
+            columns.push(Self::DnssecAb);
+        }
+        if config.negative_cache {
+            columns.push(Self::NegativeCache);
+        }
+        if config.edns_payload.is_some() {
+            columns.push(Self::Truncated);
+        }
+        if config.ecs.is_some() {
+            columns.push(Self::Ecs);
+        }
+        if config.verify_against.is_some() {
+            columns.push(Self::Correctness);
+        }
+        if !config.internal_domains.is_empty() {
+            columns.push(Self::SplitHorizon);
+        }
+        if config.vpn_check {
+            columns.push(Self::VpnRoute);
+        }
+        if config.ttl_check {
+            columns.push(Self::Ttl);
+        }
+        if config.node_id {
+            columns.push(Self::Node);
+        }
+        if config.probes.iter().any(|p| p.eq_ignore_ascii_case("all") || p.eq_ignore_ascii_case("filtering")) {
+            columns.push(Self::Filtering);
+        }
+        // Probes without a glyph get their own column
+        if config.probes.iter().any(in_probes_column) {
+            columns.push(Self::Probes);
+        }
+        if config.probes.iter().any(|p| p.eq_ignore_ascii_case("all") || has_warning(p)) {
+            columns.push(Self::Warnings);
+        }
+        if !config.geoip.is_empty() {
+            columns.extend([Self::ServerGeo, Self::AnswerGeo]);
+        }
+        columns
+    }
+
+    /// Message ID of the header text
+    fn header(self) -> &'static str {
+        match self {
+            Self::Server => "col-server",
+            Self::Ip => "col-ip",
+            Self::Source => "col-source",
+            Self::Protocol => "col-protocol",
+            Self::ResolvedIp => "col-resolved-ip",
+            Self::SuccessRate => "col-success-rate",
+            Self::Min => "col-min",
+            Self::Max => "col-max",
+            Self::Avg => "col-avg-sorted",
+            Self::Jitter => "col-jitter",
+            Self::Sparkline => "col-sparkline",
+            Self::Failures => "col-failures",
+            Self::Trend => "col-trend",
+            Self::Reference => "col-reference",
+            Self::PageLoad => "col-page-load",
+            Self::DnssecAb => "col-dnssec-cost",
+            Self::NegativeCache => "col-nxdomain",
+            Self::Truncated => "col-truncated",
+            Self::Ecs => "col-ecs",
+            Self::Correctness => "col-answers",
+            Self::SplitHorizon => "col-internal",
+            Self::VpnRoute => "col-vpn",
+            Self::Ttl => "col-avg-ttl",
+            Self::Node => "col-node",
+            Self::Filtering => "col-filtering",
+            Self::Probes => "col-probes",
+            Self::Warnings => "col-warnings",
+            Self::ServerGeo => "col-server-as",
+            Self::AnswerGeo => "col-answer-as",
+        }
+    }
+
+    /// Cell text for a server
+    fn value(self, r: &ServerResult, result: &BenchmarkResult, system_ips: &[IpAddr], plain: bool) -> String {
+        match self {
+            Self::Server if plain => {
+                if system_ips.contains(&r.ip) {
+                    format!("{} (system)", r.name)
+                } else {
+                    r.name.clone()
+                }
+            }
+            Self::Server => {
+                let mut name = if system_ips.contains(&r.ip) {
+                    format!("▸ {}", r.name)
+                } else {
+                    r.name.clone()
+                };
+                for g in glyphs_for(&r.probes) {
+                    name.push(' ');
+                    name.push_str(g.glyph);
+                }
+                name
+            }
+            Self::Ip => r.ip.to_string(),
+            Self::Source => r.source.to_string(),
+            Self::Protocol => r.protocol.to_string(),
+            Self::ResolvedIp => r.resolved_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".into()),
+            Self::SuccessRate => format!(
+                "{}/{} ({:.1}%)",
+                r.successful_requests,
+                r.total_requests,
+                r.success_rate()
+            ),
+            Self::Min => format_time(r.min_time),
+            Self::Max => format_time(r.max_time),
+            Self::Avg => format_time(r.avg_time),
+            Self::Jitter => match r.jitter() {
+                Some(jitter) => format!("±{}", format_time(Some(jitter))),
+                None => "-".into(),
+            },
+            Self::Sparkline if r.latencies.is_empty() => "-".into(),
+            Self::Sparkline => sparkline(&r.latencies, SPARKLINE_WIDTH),
+            Self::Failures => r.failures.map(|f| f.to_string()).unwrap_or_else(|| "-".into()),
+            Self::Trend => match result.trend_ms(r) {
+                Some(delta) if plain => format_delta_ms(delta),
+                Some(delta) if delta.abs() < TREND_STEADY_MS => format!("= {}", format_delta_ms(delta)),
+                Some(delta) if delta > 0.0 => format!("▲ {}", format_delta_ms(delta)),
+                Some(delta) => format!("▼ {}", format_delta_ms(delta)),
+                None => "-".into(),
+            },
+            Self::Reference => match result.normalized(r) {
+                Some(_) if result.reference == Some(r.ip) => "1.00× (ref)".into(),
+                Some(n) => format!("{:.2}× ({})", n.ratio, format_delta_ms(n.delta_ms)),
+                None => "-".into(),
+            },
+            Self::PageLoad => match r.page_load {
+                Some(p) if p.successful < p.runs => {
+                    format!("{} ({}/{})", format_time(p.avg_time), p.successful, p.runs)
+                }
+                Some(p) => format_time(p.avg_time),
+                None => "-".into(),
+            },
+            Self::DnssecAb => match r.dnssec_ab {
+                Some(ab) => match ab.overhead_ms() {
+                    Some(delta) => format!(
+                        "{} ({} vs {})",
+                        format_delta_ms(delta),
+                        format_time(ab.validated),
+                        format_time(ab.unvalidated)
+                    ),
+                    None => "-".into(),
+                },
+                None => "-".into(),
+            },
+            Self::NegativeCache => match r.negative_cache {
+                Some(nx) if nx.first_miss.is_some() || nx.cached.is_some() => {
+                    format!("{} → {}", format_time(nx.first_miss), format_time(nx.cached))
+                }
+                _ => "-".into(),
+            },
+            Self::Truncated => match (r.truncated, r.truncation_rate()) {
+                (Some(count), Some(rate)) => match r.tcp_fallback {
+                    Some(fallback) => format!("{count} ({rate:.1}%, +{})", format_time(Some(fallback))),
+                    None => format!("{count} ({rate:.1}%)"),
+                },
+                _ => "-".into(),
+            },
+            Self::Ecs => match r.ecs.map(|e| (e.support(), e.max_scope)) {
+                Some((Some(EcsSupport::Honored), scope)) => format!("honored /{scope}"),
+                Some((Some(support), _)) => support.to_string(),
+                _ => "-".into(),
+            },
+            Self::Correctness => r.correctness.map(|c| c.to_string()).unwrap_or_else(|| "-".into()),
+            Self::SplitHorizon => match r.split_horizon {
+                Some(ref split) => format!("{}/{} {}", split.answered.len(), split.checked, split.verdict(r.source)),
+                None => "-".into(),
+            },
+            Self::VpnRoute => match r.vpn_route {
+                Some(ref route) => format!("{} ({})", route.interface, route.verdict),
+                None => "-".into(),
+            },
+            Self::Ttl => match r.ttl.and_then(|t| t.avg().map(|avg| (avg, t.verdict()))) {
+                Some((avg, Some(verdict @ (TtlVerdict::Lowered | TtlVerdict::Raised)))) => format!("{avg:.0}s ({verdict})"),
+                Some((avg, _)) => format!("{avg:.0}s"),
+                None => "-".into(),
+            },
+            Self::Node => match r.nodes {
+                Some(ref nodes) if !nodes.samples.is_empty() => {
+                    let distinct: Vec<_> = nodes.distinct().collect();
+                    match nodes.changes() {
+                        0 => distinct.join(", "),
+                        1 => format!("{} (1 change)", distinct.join(", ")),
+                        n => format!("{} ({n} changes)", distinct.join(", ")),
+                    }
+                }
+                _ => "-".into(),
+            },
+            Self::Filtering => r
+                .probes
+                .get("filtering")
+                .map(|p| p.summary.clone())
+                .unwrap_or_else(|| "-".into()),
+            Self::Probes => {
+                let text: Vec<_> = r
+                    .probes
+                    .iter()
+                    .filter(|(name, _)| plain || !has_glyph(name))
+                    .map(|(name, p)| format!("{name}:{}", p.status))
+                    .collect();
+                if text.is_empty() { "-".into() } else { text.join(" ") }
+            }
+            Self::Warnings => {
+                let warnings: Vec<_> = warnings_for(&r.probes).collect();
+                if warnings.is_empty() { "-".into() } else { warnings.join(", ") }
+            }
+            Self::ServerGeo => r.geo.as_ref().map(ToString::to_string).unwrap_or_else(|| "-".into()),
+            Self::AnswerGeo => r.resolved_geo.as_ref().map(ToString::to_string).unwrap_or_else(|| "-".into()),
+        }
+    }
+
+    /// Cell color for a server
+    fn color(self, r: &ServerResult, result: &BenchmarkResult) -> Option<Color> {
+        let time_color = |d: Option<Duration>| d.map(|d| get_time_color(d.as_secs_f64() * 1000.0));
+
+        match self {
+            Self::Server | Self::Ip | Self::Source | Self::Protocol | Self::ResolvedIp | Self::Sparkline => None,
+            Self::Ecs | Self::Filtering | Self::Probes => None,
+            Self::Warnings => warnings_for(&r.probes).next().map(|_| Color::Yellow),
+            // Refusals point at rate limiting rather than an unreachable server
+            Self::Failures => r.failures.filter(|f| f.refused > 0).map(|_| Color::Yellow),
+            Self::Correctness => r.correctness.map(|c| match c {
+                Correctness::Consistent => Color::Green,
+                Correctness::Divergent => Color::Yellow,
+                Correctness::Bogus => Color::Red,
+            }),
+            Self::SplitHorizon => r.split_horizon.as_ref().and_then(|split| match split.verdict(r.source) {
+                SplitHorizon::Correct => Some(Color::Green),
+                SplitHorizon::Leak => Some(Color::Yellow),
+                SplitHorizon::Missing => Some(Color::Red),
+                SplitHorizon::Unknown => None,
+            }),
+            Self::VpnRoute => r.vpn_route.as_ref().and_then(|route| match route.verdict {
+                VpnRoute::Tunnel => Some(Color::Green),
+                VpnRoute::Local => None,
+                VpnRoute::Bypass => Some(Color::Red),
+            }),
+            Self::Node => r.nodes.as_ref().filter(|n| n.changes() > 0).map(|_| Color::Yellow),
+            Self::Ttl => r.ttl.and_then(|t| t.verdict()).map(|v| match v {
+                TtlVerdict::Preserved => Color::Green,
+                TtlVerdict::Lowered | TtlVerdict::Raised => Color::Yellow,
+            }),
+            Self::Truncated => r.truncation_rate().map(|rate| get_success_color(100.0 - rate)),
+            Self::ServerGeo | Self::AnswerGeo => None,
+            Self::SuccessRate => Some(get_success_color(r.success_rate())),
+            Self::Min => time_color(r.min_time),
+            Self::Max => time_color(r.max_time),
+            Self::Avg => time_color(r.avg_time),
+            Self::Jitter => r.jitter().map(|d| get_jitter_color(d.as_secs_f64() * 1000.0)),
+            Self::Trend => result
+                .trend_ms(r)
+                .filter(|delta| delta.abs() >= TREND_STEADY_MS)
+                .map(|delta| if delta > 0.0 { Color::Red } else { Color::Green }),
+            Self::Reference => result.normalized(r).map(|n| get_ratio_color(n.ratio)),
+            Self::PageLoad => time_color(r.page_load.and_then(|p| p.avg_time)),
+            Self::DnssecAb => r.dnssec_ab.and_then(|ab| ab.overhead_ms()).map(get_overhead_color),
+            Self::NegativeCache => time_color(r.negative_cache.and_then(|n| n.cached)),
+        }
+    }
+}
+
+/// Build the main per-server results table
+fn server_table(
+    result: &BenchmarkResult,
+    columns: &[Column],
+    system_ips: &[IpAddr],
+    config: &Config,
+    l: &Localizer,
+) -> Table {
+    let servers: Vec<_> = result
+        .servers
+        .iter()
+        .filter(|s| !(config.hide_failed && s.all_failed()))
+        .collect();
+    let mut builder = Builder::default();
+    builder.push_record(columns.iter().map(|c| header_text(l, c.header(), config.plain)));
+    for s in &servers {
+        builder.push_record(columns.iter().map(|c| c.value(s, result, system_ips, config.plain)));
+    }
+
+    let mut table = builder.build();
+
+    // Apply style
+    apply_style(&mut table, config.table_style());
+
+    // Center header
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    if config.plain {
+        return table;
+    }
+
+    // Apply colors to data cells
+    for (i, s) in servers.iter().enumerate() {
+        let row_idx = i + 1; // Skip header row
+
+        for (col_idx, column) in columns.iter().enumerate() {
+            if let Some(color) = column.color(s, result) {
+                table.with(Modify::new(object::Cell::new(row_idx, col_idx)).with(to_tabled_color(color)));
+            }
+        }
+    }
+
+    table
+}
+
+/// Columns of the live table drawn while the benchmark runs
+const LIVE_COLUMNS: [Column; 7] = [
+    Column::Server,
+    Column::Ip,
+    Column::SuccessRate,
+    Column::Min,
+    Column::Max,
+    Column::Avg,
+    Column::Jitter,
+];
+
+/// Render the servers measured so far, ranked, for redrawing during the run
+///
+/// At most `max_rows` servers are shown, the best first, so the table fits
+/// on screen above the progress bar; a last line counts the rest.
+pub fn live_table(finished: &[&ServerResult], config: &Config, max_rows: usize) -> String {
+    let servers = finished.iter().map(|&s| s.clone()).collect();
+    let mut partial = BenchmarkResult {
+        reference: None,
+        ..BenchmarkResult::ranked(config, servers, Duration::ZERO)
+    };
+    let hidden = partial.servers.len().saturating_sub(max_rows);
+    partial.servers.truncate(max_rows);
+    let l = Localizer::new(config.lang);
+    let mut text = server_table(&partial, &LIVE_COLUMNS, &[], config, &l).to_string();
+    if hidden > 0 {
+        text.push('\n');
+        text.push_str(&style(l.tr_args("progress-live-more", [("count", hidden.into())])).dim().to_string());
+    }
+    text
+}
+
+/// Draw response times in the order they were measured, scaled from fastest to slowest
+fn sparkline(latencies: &[Duration], width: usize) -> String {
+    let ms: Vec<f64> = latencies
+        .chunks(latencies.len().div_ceil(width).max(1))
+        .map(|chunk| chunk.iter().map(|d| d.as_secs_f64() * 1000.0).sum::<f64>() / chunk.len() as f64)
+        .collect();
+    let min = ms.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = ms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top = (SPARKLINE_BARS.len() - 1) as f64;
+    ms.iter()
+        .map(|&m| {
+            let level = if max > min { ((m - min) / (max - min) * top).round() } else { 0.0 };
+            SPARKLINE_BARS[level as usize]
+        })
+        .collect()
+}
+
+/// Localized header text, without the sort arrow in plain output
+fn header_text(l: &Localizer, id: &str, plain: bool) -> String {
+    let text = l.tr(id);
+    if plain { text.trim_end_matches(" ↑").to_string() } else { text }
+}
+
+/// Replace the header row of a derived table with localized messages
+fn localize_header(table: &mut Table, ids: &[&str], l: &Localizer, plain: bool) {
+    for (col, id) in ids.iter().enumerate() {
+        let text = header_text(l, id, plain);
+        table.with(Modify::new(object::Cell::new(0, col)).with(Format::content(move |_| text.clone())));
+    }
+}
+
+/// Format a signed millisecond delta for display
+fn format_delta_ms(delta: f64) -> String {
+    if delta >= 0.0 {
+        format!("+{}", format_duration_ms(delta))
+    } else {
+        format!("-{}", format_duration_ms(-delta))
+    }
+}
+
+/// Get color based on latency ratio to the reference server
+fn get_ratio_color(ratio: f64) -> Color {
+    if ratio <= 1.0 {
+        Color::Green
+    } else if ratio <= 1.5 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+/// Provider table row representation
+#[derive(Debug, Tabled)]
+struct ProviderRow {
+    #[tabled(rename = "Provider")]
+    name: String,
+    #[tabled(rename = "Servers")]
+    servers: usize,
+    #[tabled(rename = "Success Rate")]
+    success_rate: String,
+    #[tabled(rename = "Best ↑")]
+    best: String,
+    #[tabled(rename = "Avg")]
+    avg: String,
+}
+
+impl From<&ProviderResult> for ProviderRow {
+    fn from(p: &ProviderResult) -> Self {
+        Self {
+            name: p.name.clone(),
+            servers: p.ips.len(),
+            success_rate: format!(
+                "{}/{} ({:.1}%)",
+                p.successful_requests,
+                p.total_requests,
+                p.success_rate()
+            ),
+            best: format_time(p.best_time),
+            avg: format_time(p.avg_time),
+        }
+    }
+}
+
+/// Build the per-provider aggregated table
+fn provider_table(providers: &[ProviderResult], config: &Config, l: &Localizer) -> Table {
+    let rows: Vec<ProviderRow> = providers.iter().map(ProviderRow::from).collect();
+    let mut table = Table::new(&rows);
+    let headers = ["col-provider", "col-servers", "col-success-rate", "col-best-sorted", "col-avg"];
+    localize_header(&mut table, &headers, l, config.plain);
+
+    apply_style(&mut table, config.table_style());
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    if config.plain {
+        return table;
+    }
+
+    for (i, p) in providers.iter().enumerate() {
+        let row_idx = i + 1;
+
+        table.with(
+            Modify::new(object::Cell::new(row_idx, 2))
+                .with(to_tabled_color(get_success_color(p.success_rate()))),
+        );
+        if let Some(best) = p.best_time {
             table.with(
                 Modify::new(object::Cell::new(row_idx, 3))
-                    .with(to_tabled_color(get_success_color(s.success_rate()))),
+                    .with(to_tabled_color(get_time_color(best.as_secs_f64() * 1000.0))),
+            );
+        }
+        if let Some(avg) = p.avg_time {
+            table.with(
+                Modify::new(object::Cell::new(row_idx, 4))
+                    .with(to_tabled_color(get_time_color(avg.as_secs_f64() * 1000.0))),
             );
+        }
+    }
 
-            // Time columns color (if we have data)
-            if let Some(min) = s.min_time {
-                let ms = min.as_secs_f64() * 1000.0;
-                table.with(
-                    Modify::new(object::Cell::new(row_idx, 4))
-                        .with(to_tabled_color(get_time_color(ms))),
-                );
-            }
-            if let Some(max) = s.max_time {
-                let ms = max.as_secs_f64() * 1000.0;
-                table.with(
-                    Modify::new(object::Cell::new(row_idx, 5))
-                        .with(to_tabled_color(get_time_color(ms))),
-                );
-            }
-            if let Some(avg) = s.avg_time {
-                let ms = avg.as_secs_f64() * 1000.0;
+    table
+}
+
+/// Dual-stack comparison row representation
+#[derive(Debug, Tabled)]
+struct DualStackRow {
+    #[tabled(rename = "Provider")]
+    name: String,
+    #[tabled(rename = "IPv4 Avg")]
+    v4: String,
+    #[tabled(rename = "IPv6 Avg")]
+    v6: String,
+    #[tabled(rename = "Δ (v6 − v4)")]
+    delta: String,
+}
+
+impl From<&DualStackComparison> for DualStackRow {
+    fn from(c: &DualStackComparison) -> Self {
+        Self {
+            name: c.name.clone(),
+            v4: format_time(c.v4.avg_time),
+            v6: format_time(c.v6.avg_time),
+            delta: c.delta_ms().map(format_delta_ms).unwrap_or_else(|| "-".into()),
+        }
+    }
+}
+
+/// Build the IPv4 vs IPv6 comparison table
+fn dual_stack_table(pairs: &[DualStackComparison], config: &Config, l: &Localizer) -> Table {
+    let rows: Vec<DualStackRow> = pairs.iter().map(DualStackRow::from).collect();
+    let mut table = Table::new(&rows);
+    let headers = ["col-provider", "col-ipv4-avg", "col-ipv6-avg", "col-dual-stack-delta"];
+    localize_header(&mut table, &headers, l, config.plain);
+
+    apply_style(&mut table, config.table_style());
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    if config.plain {
+        return table;
+    }
+
+    for (i, c) in pairs.iter().enumerate() {
+        let row_idx = i + 1;
+
+        if let Some(avg) = c.v4.avg_time {
+            table.with(
+                Modify::new(object::Cell::new(row_idx, 1))
+                    .with(to_tabled_color(get_time_color(avg.as_secs_f64() * 1000.0))),
+            );
+        }
+        if let Some(avg) = c.v6.avg_time {
+            table.with(
+                Modify::new(object::Cell::new(row_idx, 2))
+                    .with(to_tabled_color(get_time_color(avg.as_secs_f64() * 1000.0))),
+            );
+        }
+        if let Some(delta) = c.delta_ms() {
+            // IPv6 faster or equal is good; slower is flagged
+            let color = if delta <= 0.0 { Color::Green } else { Color::Yellow };
+            table.with(Modify::new(object::Cell::new(row_idx, 3)).with(to_tabled_color(color)));
+        }
+    }
+
+    table
+}
+
+/// Build the per-server protocol comparison table
+fn protocol_table(comparisons: &[ProtocolComparison], protocols: &[Protocol], config: &Config, l: &Localizer) -> Table {
+    let cell = |r: Option<&ServerResult>| match r {
+        Some(r) if r.all_failed() => "-".to_string(),
+        Some(r) if r.successful_requests < r.total_requests => {
+            format!("{} ({:.0}%)", format_time(r.avg_time), r.success_rate())
+        }
+        Some(r) => format_time(r.avg_time),
+        None => "-".to_string(),
+    };
+
+    let mut builder = Builder::default();
+    let mut header = vec![header_text(l, "col-server", config.plain), header_text(l, "col-ip", config.plain)];
+    // Marked protocols pay for a new connection on every query, see the note below the table
+    header.extend(protocols.iter().map(|p| match p {
+        Protocol::Udp => protocol_label(*p).to_string(),
+        _ => format!("{}*", protocol_label(*p)),
+    }));
+    header.push(header_text(l, "col-fastest", config.plain));
+    builder.push_record(header);
+    for c in comparisons {
+        let mut row = vec![c.name.clone(), c.ip.to_string()];
+        row.extend(protocols.iter().map(|p| cell(c.get(*p))));
+        row.push(c.fastest().map(|p| protocol_label(p).to_string()).unwrap_or_else(|| "-".into()));
+        builder.push_record(row);
+    }
+    let mut table = builder.build();
+
+    apply_style(&mut table, config.table_style());
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    if config.plain {
+        return table;
+    }
+
+    for (i, c) in comparisons.iter().enumerate() {
+        for (j, protocol) in protocols.iter().enumerate() {
+            if let Some(avg) = c.get(*protocol).and_then(|r| r.avg_time) {
                 table.with(
-                    Modify::new(object::Cell::new(row_idx, 6))
-                        .with(to_tabled_color(get_time_color(ms))),
+                    Modify::new(object::Cell::new(i + 1, j + 2))
+                        .with(to_tabled_color(get_time_color(avg.as_secs_f64() * 1000.0))),
                 );
             }
         }
+    }
 
-        writeln!(writer, "{}", table)?;
+    table
+}
 
-        // Print summary
-        writeln!(writer)?;
+/// Protocol name as usually written
+fn protocol_label(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Udp => "UDP",
+        Protocol::Tcp => "TCP",
+        Protocol::Dot => "DoT",
+        Protocol::Doh => "DoH",
+    }
+}
+
+/// Run comparison row representation
+#[derive(Debug, Tabled)]
+struct ComparisonRow {
+    #[tabled(rename = "Server")]
+    name: String,
+    #[tabled(rename = "IP Address")]
+    ip: String,
+    #[tabled(rename = "Baseline Avg")]
+    base: String,
+    #[tabled(rename = "Current Avg")]
+    current: String,
+    #[tabled(rename = "Δ")]
+    delta: String,
+    #[tabled(rename = "Change")]
+    change: String,
+}
+
+impl From<&ServerDelta> for ComparisonRow {
+    fn from(d: &ServerDelta) -> Self {
+        Self {
+            name: d.name.clone(),
+            ip: d.ip.clone(),
+            base: d.base_ms.map(format_duration_ms).unwrap_or_else(|| "-".into()),
+            current: d.current_ms.map(format_duration_ms).unwrap_or_else(|| "-".into()),
+            delta: d.delta_ms().map(format_delta_ms).unwrap_or_else(|| "-".into()),
+            change: d.change_percent().map(|p| format!("{p:+.1}%")).unwrap_or_else(|| "-".into()),
+        }
+    }
+}
+
+/// Write the comparison of two runs as a table
+pub fn write_comparison(
+    comparison: &RunComparison,
+    style: TableStyle,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let rows: Vec<ComparisonRow> = comparison.servers.iter().map(ComparisonRow::from).collect();
+    let table = delta_table(&rows, &comparison.servers, style);
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+/// Region comparison row representation
+#[derive(Debug, Tabled)]
+struct RegionRow {
+    #[tabled(rename = "Server")]
+    name: String,
+    #[tabled(rename = "IP Address")]
+    ip: String,
+    #[tabled(rename = "Typical Avg")]
+    typical: String,
+    #[tabled(rename = "Your Avg")]
+    yours: String,
+    #[tabled(rename = "Δ")]
+    delta: String,
+    #[tabled(rename = "Change")]
+    change: String,
+}
+
+impl From<&ServerDelta> for RegionRow {
+    fn from(d: &ServerDelta) -> Self {
+        let ComparisonRow { name, ip, base, current, delta, change } = ComparisonRow::from(d);
+        Self { name, ip, typical: base, yours: current, delta, change }
+    }
+}
+
+/// Write a run's comparison to its region's typical latencies, with a verdict
+pub fn write_region_comparison(
+    comparison: &RegionComparison,
+    table_style: TableStyle,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let rows: Vec<RegionRow> = comparison.servers.iter().map(RegionRow::from).collect();
+    let table = delta_table(&rows, &comparison.servers, table_style);
+    writeln!(writer, "Compared to typical values in {}:", comparison.country)?;
+    writeln!(writer, "{}", table)?;
+
+    let slow = comparison.slow_resolvers();
+    if comparison.network_is_slow() {
+        writeln!(
+            writer,
+            "{} Most resolvers are {:.1}× slower than typical in {}: your ISP or local network is the likely bottleneck.",
+            style("⚠").yellow(),
+            comparison.network_ratio().unwrap_or_default(),
+            comparison.country
+        )?;
+    }
+    if !slow.is_empty() {
+        let names: Vec<_> = slow.iter().map(|d| d.name.as_str()).collect();
         writeln!(
             writer,
-            "{} Benchmark completed in {:.2?}",
-            style("✓").green().bold(),
-            result.duration
+            "{} Slower than typical even for your network: {}. The resolver or the route to it is the likely problem.",
+            style("⚠").yellow(),
+            names.join(", ")
         )?;
+    }
+    if !comparison.network_is_slow() && slow.is_empty() {
+        writeln!(
+            writer,
+            "{} Your latencies are in line with typical values in {}.",
+            style("✓").green(),
+            comparison.country
+        )?;
+    }
+    Ok(())
+}
 
-        if let Some(fastest) = result.fastest() {
-            if let Some(avg) = fastest.avg_time {
-                writeln!(
-                    writer,
-                    "{} Fastest: {} ({}) - {}",
-                    style("★").yellow().bold(),
-                    style(&fastest.name).green(),
-                    fastest.ip,
-                    style(format_duration_ms(avg.as_secs_f64() * 1000.0)).cyan()
-                )?;
-            }
+/// Build a comparison table, coloring the delta columns
+fn delta_table<R: Tabled>(rows: &[R], deltas: &[ServerDelta], style: TableStyle) -> Table {
+    let mut table = Table::new(rows);
+
+    apply_style(&mut table, style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+
+    for (i, d) in deltas.iter().enumerate() {
+        let row_idx = i + 1;
+
+        if let Some(delta) = d.delta_ms() {
+            // Faster than baseline is good; slower is flagged
+            let color = if delta <= 0.0 { Color::Green } else { Color::Red };
+            table.with(Modify::new(object::Cell::new(row_idx, 4)).with(to_tabled_color(color)));
+            table.with(Modify::new(object::Cell::new(row_idx, 5)).with(to_tabled_color(color)));
         }
+    }
 
-        Ok(())
+    table
+}
+
+/// Server list row representation
+#[derive(Debug, Tabled)]
+struct ServerListRow {
+    #[tabled(rename = "Server")]
+    name: String,
+    #[tabled(rename = "IP Address")]
+    ip: String,
+    #[tabled(rename = "Operator")]
+    operator: String,
+    #[tabled(rename = "Country")]
+    country: String,
+    #[tabled(rename = "Anycast")]
+    anycast: String,
+    #[tabled(rename = "Filtering")]
+    filtering: String,
+    #[tabled(rename = "Logging")]
+    logging: String,
+}
+
+impl From<&DnsServer> for ServerListRow {
+    fn from(s: &DnsServer) -> Self {
+        let dash = || "-".to_string();
+        let info = s.provider.as_ref();
+        Self {
+            name: s.name.clone(),
+            ip: s.ip().to_string(),
+            operator: info.map(|p| p.operator.to_string()).unwrap_or_else(dash),
+            country: info.map(|p| p.country.to_string()).unwrap_or_else(dash),
+            anycast: info.map(|p| if p.anycast { "yes" } else { "no" }.to_string()).unwrap_or_else(dash),
+            filtering: info.map(|p| p.filtering.to_string()).unwrap_or_else(dash),
+            logging: info.map(|p| p.logging.to_string()).unwrap_or_else(dash),
+        }
     }
 }
 
-/// Table row representation
+/// Write a list of servers with their provider metadata as a table
+pub fn write_server_list(
+    servers: &[DnsServer],
+    table_style: TableStyle,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let rows: Vec<ServerListRow> = servers.iter().map(ServerListRow::from).collect();
+    let mut table = Table::new(&rows);
+    apply_style(&mut table, table_style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+/// Scenario step row representation
 #[derive(Debug, Tabled)]
-struct TableRow {
+struct ScenarioRow {
+    #[tabled(rename = "Server")]
+    server: String,
+    #[tabled(rename = "Step")]
+    step: String,
+    #[tabled(rename = "Result")]
+    result: String,
+    #[tabled(rename = "Time")]
+    time: String,
+    #[tabled(rename = "Detail")]
+    detail: String,
+}
+
+/// Write scenario results as a table with a pass/fail summary
+pub fn write_scenario_report(
+    report: &ScenarioReport,
+    table_style: TableStyle,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let mut rows = Vec::new();
+    let mut passed = Vec::new();
+    for server in &report.servers {
+        for (i, step) in server.steps.iter().enumerate() {
+            rows.push(ScenarioRow {
+                server: if i == 0 { format!("{} ({})", server.name, server.ip) } else { String::new() },
+                step: step.step.clone(),
+                result: if step.passed { "PASS".into() } else { "FAIL".into() },
+                time: format_duration_ms(step.duration_ms),
+                detail: step.detail.clone(),
+            });
+            passed.push(step.passed);
+        }
+    }
+
+    let mut table = Table::new(&rows);
+    apply_style(&mut table, table_style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+
+    for (i, ok) in passed.iter().enumerate() {
+        let color = if *ok { Color::Green } else { Color::Red };
+        table.with(Modify::new(object::Cell::new(i + 1, 2)).with(to_tabled_color(color)));
+    }
+
+    writeln!(writer, "{}", table)?;
+    writeln!(writer)?;
+
+    let servers_passed = report.servers.iter().filter(|s| s.passed()).count();
+    let summary = format!(
+        "Scenario '{}': {}/{} servers passed",
+        report.scenario,
+        servers_passed,
+        report.servers.len()
+    );
+    if servers_passed == report.servers.len() {
+        writeln!(writer, "{} {}", style("✓").green().bold(), summary)?;
+    } else {
+        writeln!(writer, "{} {}", style("✗").red().bold(), summary)?;
+    }
+
+    Ok(())
+}
+
+/// History run row representation
+#[cfg(feature = "engine")]
+#[derive(Debug, Tabled)]
+struct HistoryRow {
+    #[tabled(rename = "ID")]
+    id: i64,
+    #[tabled(rename = "Time")]
+    started_at: String,
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Servers")]
+    servers: u32,
+    #[tabled(rename = "Fastest")]
+    fastest: String,
+    #[tabled(rename = "Avg")]
+    avg: String,
+}
+
+/// Write the runs stored in the history database, newest first
+#[cfg(feature = "engine")]
+pub fn write_history_list(
+    runs: &[RunSummary],
+    table_style: TableStyle,
+    writer: &mut dyn Write,
+) -> Result<(), OutputError> {
+    let rows: Vec<HistoryRow> = runs
+        .iter()
+        .map(|r| HistoryRow {
+            id: r.id,
+            started_at: r.started_at.clone(),
+            domain: r.domain.clone(),
+            servers: r.servers,
+            fastest: r.fastest.clone().unwrap_or_else(|| "-".into()),
+            avg: r.fastest_ms.map(format_duration_ms).unwrap_or_else(|| "-".into()),
+        })
+        .collect();
+    let mut table = Table::new(&rows);
+    apply_style(&mut table, table_style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+/// Stored server result row representation
+#[cfg(feature = "engine")]
+#[derive(Debug, Tabled)]
+struct HistoryResultRow {
     #[tabled(rename = "Server")]
     name: String,
     #[tabled(rename = "IP Address")]
@@ -113,33 +1189,202 @@ struct TableRow {
     min: String,
     #[tabled(rename = "Max")]
     max: String,
-    #[tabled(rename = "Avg ↑")]
+    #[tabled(rename = "Avg")]
     avg: String,
 }
 
-impl TableRow {
-    fn from_result(r: &crate::benchmark::ServerResult, system_ips: &[IpAddr]) -> Self {
-        let name = if system_ips.contains(&r.ip) {
-            format!("▸ {}", r.name)
-        } else {
-            r.name.clone()
-        };
+/// Write a stored run: when and how it ran, then its per-server results
+#[cfg(feature = "engine")]
+pub fn write_history_run(run: &HistoryRun, table_style: TableStyle, writer: &mut dyn Write) -> Result<(), OutputError> {
+    let meta = &run.meta;
+    let ms = |v: Option<f64>| v.map(format_duration_ms).unwrap_or_else(|| "-".into());
+    writeln!(
+        writer,
+        "{} {}, {}",
+        style(format!("Run {}", meta.id)).bold(),
+        meta.started_at,
+        style(&meta.domain).green()
+    )?;
+    writeln!(
+        writer,
+        "  {} {} requests per server, {} workers, {}s timeout, {}",
+        style("Config:").dim(),
+        meta.requests_per_server,
+        meta.config.workers,
+        meta.config.timeout,
+        meta.config.protocol
+    )?;
+    writeln!(writer)?;
+
+    let rows: Vec<HistoryResultRow> = run
+        .results
+        .iter()
+        .map(|r| HistoryResultRow {
+            name: r.name.clone(),
+            ip: r.ip.clone(),
+            resolved_ip: r.resolved_ip.clone().unwrap_or_else(|| "-".into()),
+            success_rate: format!("{}/{} ({:.1}%)", r.successful_requests, r.total_requests, r.success_rate),
+            min: ms(r.min_ms),
+            max: ms(r.max_ms),
+            avg: ms(r.avg_ms),
+        })
+        .collect();
+    let mut table = Table::new(&rows);
+    apply_style(&mut table, table_style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+    for (i, r) in run.results.iter().enumerate() {
+        table.with(Modify::new(object::Cell::new(i + 1, 3)).with(to_tabled_color(get_success_color(r.success_rate))));
+        if let Some(avg) = r.avg_ms {
+            table.with(Modify::new(object::Cell::new(i + 1, 6)).with(to_tabled_color(get_time_color(avg))));
+        }
+    }
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+/// Answer record row representation
+#[derive(Debug, Tabled)]
+struct AnswerRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "TTL")]
+    ttl: u32,
+    #[tabled(rename = "Type")]
+    record_type: String,
+    #[tabled(rename = "Data")]
+    data: String,
+}
 
+impl From<&AnswerRecord> for AnswerRow {
+    fn from(r: &AnswerRecord) -> Self {
         Self {
-            name,
-            ip: r.ip.to_string(),
-            resolved_ip: r.resolved_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".into()),
-            success_rate: format!(
-                "{}/{} ({:.1}%)",
-                r.successful_requests,
-                r.total_requests,
-                r.success_rate()
-            ),
-            min: format_time(r.min_time),
-            max: format_time(r.max_time),
-            avg: format_time(r.avg_time),
+            name: r.name.clone(),
+            ttl: r.ttl,
+            record_type: r.record_type.clone(),
+            data: r.data.clone(),
+        }
+    }
+}
+
+/// Write the answer section of a single query, followed by its status and time
+pub fn write_resolve(result: &ResolveResult, table_style: TableStyle, writer: &mut dyn Write) -> Result<(), OutputError> {
+    if !result.answers.is_empty() {
+        let rows: Vec<AnswerRow> = result.answers.iter().map(AnswerRow::from).collect();
+        let mut table = Table::new(&rows);
+        apply_style(&mut table, table_style);
+        table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+        writeln!(writer, "{}", table)?;
+        writeln!(writer)?;
+    }
+
+    let via = format!("{} ({})", result.server, result.protocol);
+    let time = format_duration_ms(result.duration_ms);
+    match (&result.rcode, &result.error) {
+        (Some(rcode), _) => {
+            let status = if result.answers.is_empty() {
+                style(rcode).yellow().bold()
+            } else {
+                style(rcode).green().bold()
+            };
+            writeln!(
+                writer,
+                "{} {} via {via}: {status}, {} answer(s) in {}",
+                result.name,
+                result.record_type,
+                result.answers.len(),
+                style(time).cyan()
+            )?;
+        }
+        (None, error) => {
+            writeln!(
+                writer,
+                "{} {} {} via {via} failed after {time}: {}",
+                style("✗").red().bold(),
+                result.name,
+                result.record_type,
+                error.as_deref().unwrap_or("no response")
+            )?;
         }
     }
+
+    Ok(())
+}
+
+/// Trace hop row representation
+#[derive(Debug, Tabled)]
+struct TraceRow {
+    #[tabled(rename = "#")]
+    hop: usize,
+    #[tabled(rename = "Zone")]
+    zone: String,
+    #[tabled(rename = "Server")]
+    server: String,
+    #[tabled(rename = "Time")]
+    time: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+/// Write a trace as one row per query, followed by the time spent per zone
+pub fn write_trace(trace: &TraceResult, table_style: TableStyle, writer: &mut dyn Write) -> Result<(), OutputError> {
+    let rows: Vec<TraceRow> = trace
+        .hops
+        .iter()
+        .enumerate()
+        .map(|(i, hop)| TraceRow {
+            hop: i + 1,
+            zone: hop.zone.clone(),
+            server: format!("{} ({})", hop.server.trim_end_matches('.'), hop.ip),
+            time: format_duration_ms(hop.latency_ms),
+            result: hop.outcome.to_string(),
+        })
+        .collect();
+
+    let mut table = Table::new(&rows);
+    apply_style(&mut table, table_style);
+    table.with(Modify::new(object::Rows::first()).with(Alignment::center()));
+
+    for (i, hop) in trace.hops.iter().enumerate() {
+        let color = match hop.outcome {
+            TraceOutcome::Failed { .. } => Color::Red,
+            _ => get_time_color(hop.latency_ms),
+        };
+        table.with(Modify::new(object::Cell::new(i + 1, 3)).with(to_tabled_color(color)));
+    }
+
+    writeln!(writer, "{}", table)?;
+    writeln!(writer)?;
+
+    let zones: Vec<String> = trace
+        .zone_latencies()
+        .iter()
+        .map(|(zone, ms)| format!("{zone} {}", format_duration_ms(*ms)))
+        .collect();
+    writeln!(writer, "Time per zone: {}", zones.join(", "))?;
+
+    let summary = format!(
+        "Traced {} {} in {} queries, {}",
+        trace.domain,
+        trace.record_type,
+        trace.hops.len(),
+        format_duration_ms(trace.total_ms)
+    );
+    if trace.completed() {
+        writeln!(writer, "{} {}", style("✓").green().bold(), summary)?;
+    } else {
+        writeln!(writer, "{} {} without an authoritative answer", style("✗").red().bold(), summary)?;
+    }
+
+    Ok(())
+}
+
+/// Get color for a DNSSEC validation overhead in milliseconds
+fn get_overhead_color(delta_ms: f64) -> Color {
+    match delta_ms {
+        d if d <= 2.0 => Color::Green,
+        d if d <= 10.0 => Color::Yellow,
+        _ => Color::Red,
+    }
 }
 
 /// Format a duration for display
@@ -171,6 +1416,9 @@ fn apply_style(table: &mut Table, style: TableStyle) {
 
 /// Convert console color to tabled color
 fn to_tabled_color(color: Color) -> TabledColor {
+    if !console::colors_enabled() {
+        return TabledColor::default();
+    }
     match color {
         Color::Green => TabledColor::FG_BRIGHT_GREEN,
         Color::Yellow => TabledColor::FG_BRIGHT_YELLOW,
@@ -180,3 +1428,33 @@ fn to_tabled_color(color: Color) -> TabledColor {
         _ => TabledColor::default(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        let ms = |values: &[u64]| values.iter().map(|&v| Duration::from_millis(v)).collect::<Vec<_>>();
+        assert_eq!(sparkline(&ms(&[10, 20, 80, 10]), 50), "▁▂█▁");
+        assert_eq!(sparkline(&ms(&[5, 5, 5]), 50), "▁▁▁");
+        // Averaged into buckets when there are more samples than characters
+        assert_eq!(sparkline(&ms(&[10, 10, 30, 30, 10, 10]), 3), "▁█▁");
+    }
+
+    #[test]
+    fn test_live_table() {
+        let slow = ServerResult::answered("Slow", "10.0.0.1", 90);
+        let fast = ServerResult::answered("Fast", "1.1.1.1", 10);
+        let middle = ServerResult::answered("Middle", "8.8.8.8", 40);
+        let config = Config::builder().plain(true).build();
+
+        let table = live_table(&[&slow, &fast, &middle], &config, 2);
+        let fast_at = table.find("Fast").unwrap();
+        assert!(fast_at < table.find("Middle").unwrap());
+        assert!(!table.contains("Slow"));
+        assert!(table.ends_with("… and 1 more"));
+
+        assert!(!live_table(&[&slow, &fast], &config, 2).contains("more"));
+    }
+}
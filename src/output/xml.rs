@@ -1,8 +1,9 @@
 //! XML output formatter.
 
 use super::OutputFormatter;
-use crate::benchmark::BenchmarkResult;
+use crate::benchmark::{BenchmarkResult, TraceOutcome, TraceResult};
 use crate::config::Config;
+use crate::dns::IpVersion;
 use crate::error::OutputError;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
@@ -16,7 +17,7 @@ impl OutputFormatter for XmlFormatter {
     fn write(
         &self,
         result: &BenchmarkResult,
-        _config: &Config,
+        config: &Config,
         _system_ips: &[IpAddr],
         writer: &mut dyn Write,
     ) -> Result<(), OutputError> {
@@ -39,6 +40,54 @@ impl OutputFormatter for XmlFormatter {
         write_element(&mut xml_writer, "RequestsPerServer", &result.requests_per_server.to_string())?;
         write_element(&mut xml_writer, "TotalServers", &result.servers.len().to_string())?;
         write_element(&mut xml_writer, "DurationMs", &format!("{:.2}", result.duration.as_secs_f64() * 1000.0))?;
+        if let Some(reference) = result.reference {
+            write_element(&mut xml_writer, "Reference", &reference.to_string())?;
+        }
+        if let Some(ref fp) = result.fingerprint {
+            xml_writer
+                .write_event(Event::Start(BytesStart::new("Fingerprint")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+            if let Some(ref mac) = fp.gateway_mac {
+                write_element(&mut xml_writer, "GatewayMac", mac)?;
+            }
+            if let Some(ref ssid) = fp.ssid {
+                write_element(&mut xml_writer, "Ssid", ssid)?;
+            }
+            if let Some(ref prefix) = fp.public_prefix {
+                write_element(&mut xml_writer, "PublicPrefix", prefix)?;
+            }
+            xml_writer
+                .write_event(Event::End(BytesEnd::new("Fingerprint")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+        }
+        if let Some(ttl) = result.authoritative_ttl {
+            write_element(&mut xml_writer, "AuthoritativeTtl", &ttl.to_string())?;
+        }
+        if !result.phases.is_empty() {
+            xml_writer
+                .write_event(Event::Start(BytesStart::new("Phases")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+            for (name, d) in result.phases.iter() {
+                let element = format!("{}{}Ms", name[..1].to_uppercase(), &name[1..]);
+                write_element(&mut xml_writer, &element, &format!("{:.3}", d.as_secs_f64() * 1000.0))?;
+            }
+            xml_writer
+                .write_event(Event::End(BytesEnd::new("Phases")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+        }
+        if let Some(cal) = result.calibration {
+            xml_writer
+                .write_event(Event::Start(BytesStart::new("Calibration")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+            write_element(&mut xml_writer, "TimerOverheadUs", &format!("{:.3}", cal.timer_overhead_us))?;
+            write_element(&mut xml_writer, "LoopbackRttUs", &format!("{:.3}", cal.loopback_rtt_us))?;
+            write_element(&mut xml_writer, "JitterUs", &format!("{:.3}", cal.jitter_us))?;
+            write_element(&mut xml_writer, "NoiseFloorUs", &format!("{:.3}", cal.noise_floor_us))?;
+            write_element(&mut xml_writer, "Samples", &cal.samples.to_string())?;
+            xml_writer
+                .write_event(Event::End(BytesEnd::new("Calibration")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+        }
 
         // Results
         let results_start = BytesStart::new("Results");
@@ -73,10 +122,128 @@ impl OutputFormatter for XmlFormatter {
                 write_element(&mut xml_writer, "AvgMs", &format!("{:.3}", avg.as_secs_f64() * 1000.0))?;
             }
 
-            if server.all_failed() {
-                if let Some(ref error) = server.last_error {
-                    write_element(&mut xml_writer, "Error", error)?;
+            if let Some(n) = result.normalized(server) {
+                write_element(&mut xml_writer, "RefRatio", &format!("{:.3}", n.ratio))?;
+                write_element(&mut xml_writer, "RefDeltaMs", &format!("{:.3}", n.delta_ms))?;
+            }
+
+            if let Some(page_load) = server.page_load {
+                if let Some(avg) = page_load.avg_time {
+                    write_element(&mut xml_writer, "PageLoadMs", &format!("{:.3}", avg.as_secs_f64() * 1000.0))?;
+                }
+                write_element(&mut xml_writer, "PageLoadSuccessRate", &format!("{:.2}", page_load.success_rate()))?;
+            }
+
+            if let Some(ab) = server.dnssec_ab {
+                if let Some(on) = ab.validated {
+                    write_element(&mut xml_writer, "DnssecOnMs", &format!("{:.3}", on.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(off) = ab.unvalidated {
+                    write_element(&mut xml_writer, "DnssecOffMs", &format!("{:.3}", off.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(delta) = ab.overhead_ms() {
+                    write_element(&mut xml_writer, "DnssecOverheadMs", &format!("{:.3}", delta))?;
+                }
+            }
+
+            if let Some(nx) = server.negative_cache {
+                if let Some(first) = nx.first_miss {
+                    write_element(&mut xml_writer, "NxFirstMs", &format!("{:.3}", first.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(cached) = nx.cached {
+                    write_element(&mut xml_writer, "NxCachedMs", &format!("{:.3}", cached.as_secs_f64() * 1000.0))?;
+                }
+            }
+
+            if let (Some(count), Some(rate)) = (server.truncated, server.truncation_rate()) {
+                write_element(&mut xml_writer, "Truncated", &count.to_string())?;
+                write_element(&mut xml_writer, "TruncationRate", &format!("{:.2}", rate))?;
+            }
+            if let Some(fallback) = server.tcp_fallback {
+                write_element(&mut xml_writer, "TcpFallbackMs", &format!("{:.3}", fallback.as_secs_f64() * 1000.0))?;
+            }
+
+            if let Some(ecs) = server.ecs {
+                if let Some(support) = ecs.support() {
+                    write_element(&mut xml_writer, "Ecs", &support.to_string())?;
                 }
+                if let Some(scope) = ecs.echoed_scope() {
+                    write_element(&mut xml_writer, "EcsScope", &scope.to_string())?;
+                }
+            }
+
+            if let Some(correctness) = server.correctness {
+                write_element(&mut xml_writer, "Correctness", &correctness.to_string())?;
+            }
+
+            if let Some(ref split) = server.split_horizon {
+                write_element(&mut xml_writer, "InternalAnswered", &split.answered.len().to_string())?;
+                write_element(&mut xml_writer, "SplitHorizon", &split.verdict(server.source).to_string())?;
+            }
+
+            if let Some(ref route) = server.vpn_route {
+                write_element(&mut xml_writer, "VpnInterface", &route.interface)?;
+                write_element(&mut xml_writer, "VpnRoute", &route.verdict.to_string())?;
+            }
+
+            if let Some(avg) = server.ttl.and_then(|t| t.avg()) {
+                write_element(&mut xml_writer, "AvgTtl", &format!("{avg:.1}"))?;
+            }
+            if let Some(verdict) = server.ttl.and_then(|t| t.verdict()) {
+                write_element(&mut xml_writer, "TtlVerdict", &verdict.to_string())?;
+            }
+
+            if let Some(ref nodes) = server.nodes {
+                xml_writer
+                    .write_event(Event::Start(BytesStart::new("Nodes")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+                for node in nodes.distinct() {
+                    write_element(&mut xml_writer, "Node", node)?;
+                }
+                write_element(&mut xml_writer, "Changes", &nodes.changes().to_string())?;
+                xml_writer
+                    .write_event(Event::End(BytesEnd::new("Nodes")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+            }
+
+            if let Some(ref info) = server.provider {
+                xml_writer
+                    .write_event(Event::Start(BytesStart::new("ProviderInfo")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+                write_element(&mut xml_writer, "Operator", &info.operator)?;
+                write_element(&mut xml_writer, "Country", &info.country)?;
+                write_element(&mut xml_writer, "Anycast", &info.anycast.to_string())?;
+                write_element(&mut xml_writer, "Filtering", &info.filtering.to_string())?;
+                write_element(&mut xml_writer, "Logging", &info.logging.to_string())?;
+                xml_writer
+                    .write_event(Event::End(BytesEnd::new("ProviderInfo")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+            }
+
+            if !server.probes.is_empty() {
+                xml_writer
+                    .write_event(Event::Start(BytesStart::new("Probes")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+                for (name, probe) in &server.probes {
+                    xml_writer
+                        .write_event(Event::Start(BytesStart::new("Probe")))
+                        .map_err(|e| OutputError::Xml(e.to_string()))?;
+                    write_element(&mut xml_writer, "Name", name)?;
+                    write_element(&mut xml_writer, "Status", &probe.status.to_string())?;
+                    write_element(&mut xml_writer, "Summary", &probe.summary)?;
+                    xml_writer
+                        .write_event(Event::End(BytesEnd::new("Probe")))
+                        .map_err(|e| OutputError::Xml(e.to_string()))?;
+                }
+                xml_writer
+                    .write_event(Event::End(BytesEnd::new("Probes")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+            }
+
+            if server.all_failed()
+                && let Some(ref error) = server.last_error
+            {
+                write_element(&mut xml_writer, "Error", error)?;
             }
 
             xml_writer
@@ -88,6 +255,74 @@ impl OutputFormatter for XmlFormatter {
             .write_event(Event::End(BytesEnd::new("Results")))
             .map_err(|e| OutputError::Xml(e.to_string()))?;
 
+        // Provider aggregation
+        if config.by_provider {
+            xml_writer
+                .write_event(Event::Start(BytesStart::new("Providers")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+            for provider in result.by_provider() {
+                xml_writer
+                    .write_event(Event::Start(BytesStart::new("Provider")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+                write_element(&mut xml_writer, "Name", &provider.name)?;
+                for ip in &provider.ips {
+                    write_element(&mut xml_writer, "Ip", &ip.to_string())?;
+                }
+                write_element(&mut xml_writer, "TotalRequests", &provider.total_requests.to_string())?;
+                write_element(&mut xml_writer, "SuccessfulRequests", &provider.successful_requests.to_string())?;
+                write_element(&mut xml_writer, "SuccessRate", &format!("{:.2}", provider.success_rate()))?;
+
+                if let Some(best) = provider.best_time {
+                    write_element(&mut xml_writer, "BestMs", &format!("{:.3}", best.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(avg) = provider.avg_time {
+                    write_element(&mut xml_writer, "AvgMs", &format!("{:.3}", avg.as_secs_f64() * 1000.0))?;
+                }
+
+                xml_writer
+                    .write_event(Event::End(BytesEnd::new("Provider")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+            }
+
+            xml_writer
+                .write_event(Event::End(BytesEnd::new("Providers")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+        }
+
+        // IPv4 vs IPv6 comparison
+        if config.name_server_ip == IpVersion::Both {
+            xml_writer
+                .write_event(Event::Start(BytesStart::new("DualStack")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+            for pair in result.dual_stack() {
+                xml_writer
+                    .write_event(Event::Start(BytesStart::new("Provider")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+                write_element(&mut xml_writer, "Name", &pair.name)?;
+                if let Some(avg) = pair.v4.avg_time {
+                    write_element(&mut xml_writer, "V4AvgMs", &format!("{:.3}", avg.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(avg) = pair.v6.avg_time {
+                    write_element(&mut xml_writer, "V6AvgMs", &format!("{:.3}", avg.as_secs_f64() * 1000.0))?;
+                }
+                if let Some(delta) = pair.delta_ms() {
+                    write_element(&mut xml_writer, "DeltaMs", &format!("{:.3}", delta))?;
+                }
+
+                xml_writer
+                    .write_event(Event::End(BytesEnd::new("Provider")))
+                    .map_err(|e| OutputError::Xml(e.to_string()))?;
+            }
+
+            xml_writer
+                .write_event(Event::End(BytesEnd::new("DualStack")))
+                .map_err(|e| OutputError::Xml(e.to_string()))?;
+        }
+
         xml_writer
             .write_event(Event::End(BytesEnd::new("DnsBenchmarkResults")))
             .map_err(|e| OutputError::Xml(e.to_string()))?;
@@ -99,6 +334,63 @@ impl OutputFormatter for XmlFormatter {
     }
 }
 
+/// Write a trace as a `DnsTrace` document with one `Hop` per query
+pub fn write_trace_xml(trace: &TraceResult, writer: &mut dyn Write) -> Result<(), OutputError> {
+    let mut buffer = Cursor::new(Vec::new());
+    let mut xml_writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+
+    xml_writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .map_err(|e| OutputError::Xml(e.to_string()))?;
+    xml_writer
+        .write_event(Event::Start(BytesStart::new("DnsTrace")))
+        .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+    write_element(&mut xml_writer, "Domain", &trace.domain)?;
+    write_element(&mut xml_writer, "RecordType", &trace.record_type)?;
+    write_element(&mut xml_writer, "TotalMs", &format!("{:.2}", trace.total_ms))?;
+    write_element(&mut xml_writer, "Completed", &trace.completed().to_string())?;
+
+    for hop in &trace.hops {
+        xml_writer
+            .write_event(Event::Start(BytesStart::new("Hop")))
+            .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+        write_element(&mut xml_writer, "Zone", &hop.zone)?;
+        write_element(&mut xml_writer, "Server", &hop.server)?;
+        write_element(&mut xml_writer, "IP", &hop.ip.to_string())?;
+        write_element(&mut xml_writer, "LatencyMs", &format!("{:.2}", hop.latency_ms))?;
+        match &hop.outcome {
+            TraceOutcome::Referral { zone, servers } => {
+                write_element(&mut xml_writer, "Referral", zone)?;
+                for server in servers {
+                    write_element(&mut xml_writer, "NameServer", server)?;
+                }
+            }
+            TraceOutcome::Answer { records } => {
+                for record in records {
+                    write_element(&mut xml_writer, "Answer", record)?;
+                }
+            }
+            TraceOutcome::Negative { rcode } => write_element(&mut xml_writer, "Negative", rcode)?,
+            TraceOutcome::Failed { error } => write_element(&mut xml_writer, "Error", error)?,
+        }
+
+        xml_writer
+            .write_event(Event::End(BytesEnd::new("Hop")))
+            .map_err(|e| OutputError::Xml(e.to_string()))?;
+    }
+
+    xml_writer
+        .write_event(Event::End(BytesEnd::new("DnsTrace")))
+        .map_err(|e| OutputError::Xml(e.to_string()))?;
+
+    let xml_content = String::from_utf8(buffer.into_inner())?;
+    writeln!(writer, "{}", xml_content)?;
+
+    Ok(())
+}
+
 /// Helper to write a simple XML element
 fn write_element<W: Write>(
     writer: &mut Writer<W>,
@@ -125,26 +417,21 @@ fn write_element<W: Write>(
 mod tests {
     use super::*;
     use crate::benchmark::ServerResult;
-    use crate::dns::ServerSource;
+    use crate::dns::{DnsServer, ServerSource};
     use std::time::Duration;
 
     fn make_test_result() -> BenchmarkResult {
         BenchmarkResult {
-            servers: vec![ServerResult {
-                name: "Test".to_string(),
-                ip: "8.8.8.8".parse().unwrap(),
-                source: ServerSource::Builtin,
+            domain: "google.com".to_string(),
+            ..BenchmarkResult::for_test(vec![ServerResult {
                 resolved_ip: Some("1.2.3.4".parse().unwrap()),
                 total_requests: 10,
                 successful_requests: 9,
                 min_time: Some(Duration::from_millis(5)),
                 max_time: Some(Duration::from_millis(50)),
                 avg_time: Some(Duration::from_millis(20)),
-                last_error: None,
-            }],
-            duration: Duration::from_secs(1),
-            domain: "google.com".to_string(),
-            requests_per_server: 10,
+                ..ServerResult::new(&DnsServer::from_ip("Test", "8.8.8.8".parse().unwrap(), ServerSource::Builtin))
+            }])
         }
     }
 
@@ -160,5 +447,37 @@ mod tests {
         assert!(xml_str.contains("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
         assert!(xml_str.contains("<DnsBenchmarkResults>"));
         assert!(xml_str.contains("<Name>Test</Name>"));
+        assert!(!xml_str.contains("<Providers>"));
+    }
+
+    #[test]
+    fn test_xml_output_by_provider() {
+        let result = make_test_result();
+        let config = Config::builder().by_provider(true).build();
+        let mut output = Vec::new();
+
+        XmlFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let xml_str = String::from_utf8(output).unwrap();
+        assert!(xml_str.contains("<Providers>"));
+        assert!(xml_str.contains("<BestMs>20.000</BestMs>"));
+    }
+
+    #[test]
+    fn test_xml_output_dual_stack() {
+        let mut result = make_test_result();
+        let mut v6 = result.servers[0].clone();
+        v6.ip = "2001:4860:4860::8888".parse().unwrap();
+        v6.avg_time = Some(Duration::from_millis(25));
+        result.servers.push(v6);
+
+        let config = Config::builder().name_server_ip(IpVersion::Both).build();
+        let mut output = Vec::new();
+
+        XmlFormatter.write(&result, &config, &[], &mut output).unwrap();
+
+        let xml_str = String::from_utf8(output).unwrap();
+        assert!(xml_str.contains("<DualStack>"));
+        assert!(xml_str.contains("<DeltaMs>5.000</DeltaMs>"));
     }
 }
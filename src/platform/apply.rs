@@ -0,0 +1,575 @@
+//! Setting the system's DNS servers, and restoring them afterwards.
+//!
+//! Changes go through the tool that owns the settings on each platform:
+//! NetworkManager or systemd-resolved (`resolvectl`) on Linux,
+//! `networksetup` on macOS, and `netsh` on Windows. The settings found
+//! before a change are returned as [`DnsSettings`], which callers keep to
+//! undo it with [`DnsSettings::revert_commands`].
+
+use crate::error::PlatformError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Time each command changing DNS settings gets (reconnecting can be slow)
+pub const APPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tool that owns the system's DNS settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DnsBackend {
+    /// systemd-resolved, per interface, until the link is reconfigured
+    Resolvectl,
+    /// NetworkManager, per connection profile
+    NetworkManager,
+    /// macOS, per network service
+    Networksetup,
+    /// Windows, per interface
+    Netsh,
+}
+
+impl fmt::Display for DnsBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Resolvectl => write!(f, "resolvectl"),
+            Self::NetworkManager => write!(f, "NetworkManager"),
+            Self::Networksetup => write!(f, "networksetup"),
+            Self::Netsh => write!(f, "netsh"),
+        }
+    }
+}
+
+/// Where DNS servers are set, and the servers set there by hand
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsSettings {
+    /// Tool that changes the settings
+    pub backend: DnsBackend,
+    /// Interface (resolvectl, netsh), network service (networksetup), or connection (NetworkManager)
+    pub target: String,
+    /// Manually configured servers; empty if they come from DHCP or router advertisements
+    pub servers: Vec<IpAddr>,
+    /// Routing and search domains of the link (resolvectl)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<String>,
+    /// Whether the connection ignores servers from DHCP, for IPv4 and IPv6 (NetworkManager)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_auto_dns: Option<(bool, bool)>,
+    /// Interface the connection is active on, where changes are reapplied (NetworkManager)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+}
+
+impl DnsSettings {
+    #[cfg_attr(not(any(target_os = "linux", target_os = "macos", target_os = "windows")), allow(dead_code))]
+    fn new(backend: DnsBackend, target: String, servers: Vec<IpAddr>) -> Self {
+        Self {
+            backend,
+            target,
+            servers,
+            domains: Vec::new(),
+            ignore_auto_dns: None,
+            interface: None,
+        }
+    }
+
+    /// Commands that make `servers` the only DNS servers of the target, in order
+    pub fn apply_commands(&self, servers: &[IpAddr]) -> Vec<DnsCommand> {
+        let ignore_auto_dns = (servers.iter().any(IpAddr::is_ipv4), servers.iter().any(IpAddr::is_ipv6));
+        self.set_commands(servers, ignore_auto_dns, &[])
+    }
+
+    /// Commands that restore the settings recorded here, or automatic DNS if there were no servers
+    pub fn revert_commands(&self) -> Vec<DnsCommand> {
+        // Backups saved before the flags were recorded only have the servers to go by
+        let ignore_auto_dns = self.ignore_auto_dns.unwrap_or((
+            self.servers.iter().any(IpAddr::is_ipv4),
+            self.servers.iter().any(IpAddr::is_ipv6),
+        ));
+        self.set_commands(&self.servers, ignore_auto_dns, &self.domains)
+    }
+
+    /// Commands that set `servers` on the target; no servers means back to automatic
+    ///
+    /// `domains` are put back on a link after automatic DNS is restored.
+    fn set_commands(&self, servers: &[IpAddr], ignore_auto_dns: (bool, bool), domains: &[String]) -> Vec<DnsCommand> {
+        let target = self.target.as_str();
+        let (v4, v6): (Vec<IpAddr>, Vec<IpAddr>) = servers.iter().partition(|ip| ip.is_ipv4());
+        let join = |ips: &[IpAddr], sep: &str| ips.iter().map(IpAddr::to_string).collect::<Vec<_>>().join(sep);
+        let yes_no = |ignore: bool| if ignore { "yes" } else { "no" };
+
+        match self.backend {
+            DnsBackend::Resolvectl if servers.is_empty() => {
+                let mut commands = vec![DnsCommand::new("resolvectl", ["revert", target])];
+                if !domains.is_empty() {
+                    let mut args = vec!["domain".to_string(), target.to_string()];
+                    args.extend(domains.iter().cloned());
+                    commands.push(DnsCommand::new("resolvectl", args));
+                }
+                commands
+            }
+            DnsBackend::Resolvectl => {
+                let mut dns = vec!["dns".to_string(), target.to_string()];
+                dns.extend(servers.iter().map(IpAddr::to_string));
+                vec![
+                    DnsCommand::new("resolvectl", dns),
+                    // Route every domain to this link, not only its own search domains
+                    DnsCommand::new("resolvectl", ["domain", target, "~."]),
+                ]
+            }
+            DnsBackend::NetworkManager => {
+                let modify = DnsCommand::new(
+                    "nmcli",
+                    [
+                        "connection".to_string(),
+                        "modify".to_string(),
+                        target.to_string(),
+                        "ipv4.dns".to_string(),
+                        join(&v4, ","),
+                        "ipv4.ignore-auto-dns".to_string(),
+                        yes_no(ignore_auto_dns.0).to_string(),
+                        "ipv6.dns".to_string(),
+                        join(&v6, ","),
+                        "ipv6.ignore-auto-dns".to_string(),
+                        yes_no(ignore_auto_dns.1).to_string(),
+                    ],
+                );
+                // Changes to an active connection only take effect once reapplied to its
+                // device, which keeps it up; older backups don't know the device
+                let reapply = match &self.interface {
+                    Some(interface) => DnsCommand::new("nmcli", ["device", "reapply", interface.as_str()]),
+                    None => DnsCommand::new("nmcli", ["connection", "up", target]),
+                };
+                vec![modify, reapply]
+            }
+            DnsBackend::Networksetup => {
+                let mut args = vec!["-setdnsservers".to_string(), target.to_string()];
+                if servers.is_empty() {
+                    args.push("Empty".to_string());
+                } else {
+                    args.extend(servers.iter().map(IpAddr::to_string));
+                }
+                vec![DnsCommand::new("networksetup", args)]
+            }
+            DnsBackend::Netsh => {
+                let name = format!("name={target}");
+                let mut commands = Vec::new();
+                for (family, ips) in [("ipv4", &v4), ("ipv6", &v6)] {
+                    let Some((first, rest)) = ips.split_first() else {
+                        commands.push(DnsCommand::new(
+                            "netsh",
+                            ["interface", family, "set", "dnsservers", name.as_str(), "source=dhcp"],
+                        ));
+                        continue;
+                    };
+                    commands.push(DnsCommand::new(
+                        "netsh",
+                        [
+                            "interface",
+                            family,
+                            "set",
+                            "dnsservers",
+                            name.as_str(),
+                            "source=static",
+                            format!("address={first}").as_str(),
+                            "register=primary",
+                        ],
+                    ));
+                    for (i, ip) in rest.iter().enumerate() {
+                        commands.push(DnsCommand::new(
+                            "netsh",
+                            [
+                                "interface",
+                                family,
+                                "add",
+                                "dnsservers",
+                                name.as_str(),
+                                format!("address={ip}").as_str(),
+                                format!("index={}", i + 2).as_str(),
+                            ],
+                        ));
+                    }
+                }
+                commands
+            }
+        }
+    }
+}
+
+/// A command that changes DNS settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsCommand {
+    pub program: &'static str,
+    pub args: Vec<String>,
+}
+
+impl DnsCommand {
+    fn new(program: &'static str, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            program,
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl fmt::Display for DnsCommand {
+    /// The command line as it would be typed, quoting arguments with spaces
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " \"{arg}\"")?;
+            } else {
+                write!(f, " {arg}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run commands one after another, stopping at the first failure
+pub async fn run_dns_commands(commands: &[DnsCommand]) -> Result<(), PlatformError> {
+    for command in commands {
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        tracing::info!("Running `{command}`");
+        super::run_command(command.program, &args, APPLY_TIMEOUT).await?;
+    }
+    Ok(())
+}
+
+/// Find where DNS servers are set and which are set there now
+///
+/// `interface` is an interface name, or a network service name on macOS.
+/// Linux falls back to the interface of the default route; macOS and
+/// Windows need it.
+pub async fn detect_dns_settings(interface: Option<&str>) -> Result<DnsSettings, PlatformError> {
+    #[cfg(target_os = "linux")]
+    return linux::detect(interface).await;
+
+    #[cfg(target_os = "macos")]
+    return macos::detect(interface).await;
+
+    #[cfg(target_os = "windows")]
+    return windows::detect(interface).await;
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = interface;
+        Err(PlatformError::UnsupportedPlatform)
+    }
+}
+
+/// Parse addresses from whitespace- or comma-separated text, skipping anything else
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn parse_ips(text: &str) -> Vec<IpAddr> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|word| word.parse().ok())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+    use std::fs;
+
+    const PROC_NET_ROUTE: &str = "/proc/net/route";
+
+    pub async fn detect(interface: Option<&str>) -> Result<DnsSettings, PlatformError> {
+        let interface = match interface {
+            Some(name) => name.to_string(),
+            None => {
+                let content = fs::read_to_string(PROC_NET_ROUTE).map_err(|e| {
+                    PlatformError::GatewayDetection(format!("Failed to read {PROC_NET_ROUTE}: {e}"))
+                })?;
+                parse_default_interface(&content)?
+            }
+        };
+
+        let nm_running = run_command("nmcli", &["-t", "-f", "RUNNING", "general"], COMMAND_TIMEOUT)
+            .await
+            .is_ok_and(|text| text.trim() == "running");
+        if !nm_running {
+            tracing::info!("NetworkManager is not running, using resolvectl for {interface}");
+            let text = run_command("resolvectl", &["domain", &interface], COMMAND_TIMEOUT).await?;
+            let mut settings = DnsSettings::new(DnsBackend::Resolvectl, interface, Vec::new());
+            settings.domains = parse_link_domains(&text);
+            return Ok(settings);
+        }
+
+        let text = run_command("nmcli", &["-g", "GENERAL.CONNECTION", "device", "show", &interface], COMMAND_TIMEOUT)
+            .await?;
+        let connection = text.trim().to_string();
+        if connection.is_empty() {
+            return Err(PlatformError::ParseError(format!("{interface} has no active NetworkManager connection")));
+        }
+        let fields = "ipv4.dns,ipv6.dns,ipv4.ignore-auto-dns,ipv6.ignore-auto-dns";
+        let text = run_command("nmcli", &["-g", fields, "connection", "show", &connection], COMMAND_TIMEOUT).await?;
+        tracing::info!("Using NetworkManager connection {connection} of {interface}");
+        let (servers, ignore_auto_dns) = parse_connection_dns(&text);
+        let mut settings = DnsSettings::new(DnsBackend::NetworkManager, connection, servers);
+        settings.ignore_auto_dns = Some(ignore_auto_dns);
+        settings.interface = Some(interface);
+        Ok(settings)
+    }
+
+    /// Servers and `ignore-auto-dns` flags in `nmcli -g` output of the fields above, one per line
+    pub fn parse_connection_dns(text: &str) -> (Vec<IpAddr>, (bool, bool)) {
+        let fields: Vec<&str> = text.lines().map(str::trim).collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or_default();
+        // Terse output escapes the colons of IPv6 addresses
+        let servers = parse_ips(&format!("{}\n{}", field(0), field(1)).replace("\\:", ":"));
+        (servers, (field(2) == "yes", field(3) == "yes"))
+    }
+
+    /// Domains in `resolvectl domain <interface>` output (`Link 2 (eth0): example.com ~corp`)
+    pub fn parse_link_domains(text: &str) -> Vec<String> {
+        text.lines()
+            .filter_map(|line| line.split_once("):"))
+            .flat_map(|(_, domains)| domains.split_whitespace())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Interface of the first default route in /proc/net/route
+    pub fn parse_default_interface(content: &str) -> Result<String, PlatformError> {
+        content
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|cols| cols.len() >= 2 && cols[1] == "00000000")
+            .map(|cols| cols[0].to_string())
+            .ok_or_else(|| PlatformError::GatewayDetection("No default route; pass --interface".into()))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::*;
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+
+    pub async fn detect(service: Option<&str>) -> Result<DnsSettings, PlatformError> {
+        let service = service.ok_or_else(|| {
+            PlatformError::ParseError(
+                "pass the network service with --interface (see `networksetup -listallnetworkservices`)".into(),
+            )
+        })?;
+        let text = run_command("networksetup", &["-getdnsservers", service], COMMAND_TIMEOUT).await?;
+        // "There aren't any DNS Servers set on ..." yields no addresses
+        Ok(DnsSettings::new(DnsBackend::Networksetup, service.to_string(), parse_ips(&text)))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::*;
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+
+    /// Network connections by adapter GUID, each with a `Connection` key holding its name
+    const CONNECTIONS_KEY: &str =
+        r"HKLM\SYSTEM\CurrentControlSet\Control\Network\{4D36E972-E325-11CE-BFC1-08002BE10318}";
+
+    /// TCP/IP settings by adapter GUID, for IPv4 and IPv6
+    const INTERFACE_KEYS: [&str; 2] = [
+        r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip\Parameters\Interfaces",
+        r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip6\Parameters\Interfaces",
+    ];
+
+    /// Read the static servers from the registry, whose `reg query` output
+    /// doesn't depend on the display language like `netsh` output does
+    pub async fn detect(interface: Option<&str>) -> Result<DnsSettings, PlatformError> {
+        let interface = interface.ok_or_else(|| {
+            PlatformError::ParseError("pass the interface with --interface (see `netsh interface show interface`)".into())
+        })?;
+        let text = run_command("reg", &["query", CONNECTIONS_KEY, "/s", "/v", "Name"], COMMAND_TIMEOUT).await?;
+        let guid = parse_interface_guid(&text, interface)
+            .ok_or_else(|| PlatformError::ParseError(format!("No network interface named {interface}")))?;
+
+        let mut servers = Vec::new();
+        for key in INTERFACE_KEYS {
+            let key = format!(r"{key}\{guid}");
+            // Fails when the value is missing, as it is on interfaces never set by hand
+            if let Ok(text) = run_command("reg", &["query", &key, "/v", "NameServer"], COMMAND_TIMEOUT).await {
+                servers.extend(text.lines().filter_map(|line| registry_value(line, "NameServer")).flat_map(parse_ips));
+            }
+        }
+        Ok(DnsSettings::new(DnsBackend::Netsh, interface.to_string(), servers))
+    }
+
+    /// GUID of the adapter named `interface` in `reg query <CONNECTIONS_KEY> /s /v Name` output
+    pub fn parse_interface_guid(text: &str, interface: &str) -> Option<String> {
+        let mut guid = None;
+        for line in text.lines() {
+            if line.starts_with("HKEY_") {
+                // ...\{4D36E972-...}\{GUID}\Connection
+                let mut parts = line.trim_end().rsplit('\\');
+                guid = (parts.next() == Some("Connection")).then(|| parts.next()).flatten();
+            } else if let Some(name) = registry_value(line, "Name")
+                && name.eq_ignore_ascii_case(interface)
+                && let Some(guid) = guid
+            {
+                return Some(guid.to_string());
+            }
+        }
+        None
+    }
+
+    /// Data of a string value line (`    Name    REG_SZ    Wi-Fi 2`) if it's the value `name`
+    pub fn registry_value<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+        let rest = line.trim_start().strip_prefix(name)?.strip_prefix("    ")?;
+        rest.trim_start().strip_prefix("REG_SZ").map(str::trim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(backend: DnsBackend, servers: &[&str]) -> DnsSettings {
+        DnsSettings::new(backend, "Wi-Fi 2".to_string(), servers.iter().map(|ip| ip.parse().unwrap()).collect())
+    }
+
+    fn lines(commands: Vec<DnsCommand>) -> Vec<String> {
+        commands.iter().map(DnsCommand::to_string).collect()
+    }
+
+    #[test]
+    fn test_apply_and_revert_commands() {
+        let servers: Vec<IpAddr> = ["1.1.1.1", "2606:4700:4700::1111", "8.8.8.8"]
+            .iter()
+            .map(|ip| ip.parse().unwrap())
+            .collect();
+
+        let resolved = settings(DnsBackend::Resolvectl, &[]);
+        assert_eq!(
+            lines(resolved.apply_commands(&servers)),
+            [
+                "resolvectl dns \"Wi-Fi 2\" 1.1.1.1 2606:4700:4700::1111 8.8.8.8",
+                "resolvectl domain \"Wi-Fi 2\" ~."
+            ]
+        );
+        assert_eq!(lines(resolved.revert_commands()), ["resolvectl revert \"Wi-Fi 2\""]);
+        let resolved = DnsSettings {
+            domains: vec!["example.com".to_string(), "~corp.example".to_string()],
+            ..resolved
+        };
+        assert_eq!(
+            lines(resolved.revert_commands()),
+            [
+                "resolvectl revert \"Wi-Fi 2\"",
+                "resolvectl domain \"Wi-Fi 2\" example.com ~corp.example"
+            ]
+        );
+
+        let nm = DnsSettings {
+            ignore_auto_dns: Some((false, true)),
+            interface: Some("wlp2s0".to_string()),
+            ..settings(DnsBackend::NetworkManager, &[])
+        };
+        assert_eq!(
+            lines(nm.apply_commands(&servers)),
+            [
+                "nmcli connection modify \"Wi-Fi 2\" ipv4.dns 1.1.1.1,8.8.8.8 ipv4.ignore-auto-dns yes \
+                 ipv6.dns 2606:4700:4700::1111 ipv6.ignore-auto-dns yes",
+                "nmcli device reapply wlp2s0",
+            ]
+        );
+        // The flags are put back as they were, not derived from the servers
+        assert_eq!(
+            lines(nm.revert_commands()),
+            [
+                "nmcli connection modify \"Wi-Fi 2\" ipv4.dns \"\" ipv4.ignore-auto-dns no ipv6.dns \"\" \
+                 ipv6.ignore-auto-dns yes",
+                "nmcli device reapply wlp2s0",
+            ]
+        );
+        // Backups from before the flags and device were recorded
+        let old: DnsSettings =
+            serde_json::from_str(r#"{"backend": "network-manager", "target": "Wi-Fi 2", "servers": ["9.9.9.9"]}"#)
+                .unwrap();
+        assert_eq!(
+            lines(old.revert_commands()),
+            [
+                "nmcli connection modify \"Wi-Fi 2\" ipv4.dns 9.9.9.9 ipv4.ignore-auto-dns yes ipv6.dns \"\" \
+                 ipv6.ignore-auto-dns no",
+                "nmcli connection up \"Wi-Fi 2\"",
+            ]
+        );
+
+        let mac = settings(DnsBackend::Networksetup, &["192.168.1.1"]);
+        assert_eq!(
+            lines(mac.apply_commands(&servers[..1])),
+            ["networksetup -setdnsservers \"Wi-Fi 2\" 1.1.1.1"]
+        );
+        assert_eq!(lines(mac.revert_commands()), ["networksetup -setdnsservers \"Wi-Fi 2\" 192.168.1.1"]);
+        assert_eq!(
+            lines(settings(DnsBackend::Networksetup, &[]).revert_commands()),
+            ["networksetup -setdnsservers \"Wi-Fi 2\" Empty"]
+        );
+
+        let windows = settings(DnsBackend::Netsh, &[]);
+        assert_eq!(
+            lines(windows.apply_commands(&servers)),
+            [
+                "netsh interface ipv4 set dnsservers \"name=Wi-Fi 2\" source=static address=1.1.1.1 register=primary",
+                "netsh interface ipv4 add dnsservers \"name=Wi-Fi 2\" address=8.8.8.8 index=2",
+                "netsh interface ipv6 set dnsservers \"name=Wi-Fi 2\" source=static address=2606:4700:4700::1111 \
+                 register=primary",
+            ]
+        );
+        assert_eq!(
+            lines(windows.revert_commands()),
+            [
+                "netsh interface ipv4 set dnsservers \"name=Wi-Fi 2\" source=dhcp",
+                "netsh interface ipv6 set dnsservers \"name=Wi-Fi 2\" source=dhcp",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ips() {
+        let ips = parse_ips("1.1.1.1,8.8.8.8\n2606:4700:4700::1111\nThere aren't any DNS Servers set");
+        assert_eq!(ips.len(), 3);
+        assert!(parse_ips("There aren't any DNS Servers set on Wi-Fi.").is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_default_interface() {
+        let content = crate::load_test_fixture!("/gateway/linux_proc_net_route.txt");
+        assert_eq!(linux::parse_default_interface(content).unwrap(), "enp1s0");
+        assert!(linux::parse_default_interface("Iface\tDestination\n").is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_linux_dns_settings() {
+        let (servers, ignore_auto_dns) =
+            linux::parse_connection_dns("1.1.1.1,9.9.9.9\n2606\\:4700\\:4700\\:\\:1111\nyes\nno\n");
+        assert_eq!(servers.len(), 3);
+        assert_eq!(ignore_auto_dns, (true, false));
+        assert_eq!(linux::parse_connection_dns("\n\nno\nno\n"), (Vec::new(), (false, false)));
+
+        assert_eq!(
+            linux::parse_link_domains("Link 2 (eth0): example.com ~corp.example\n"),
+            ["example.com", "~corp.example"]
+        );
+        assert!(linux::parse_link_domains("Link 2 (eth0):\n").is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_parse_registry() {
+        let connections = crate::load_test_fixture!("/system/windows_reg_query_connections.txt");
+        assert_eq!(
+            windows::parse_interface_guid(connections, "wi-fi 2").as_deref(),
+            Some("{0A1B2C3D-0000-4000-8000-000000000002}")
+        );
+        assert!(windows::parse_interface_guid(connections, "Wi-Fi").is_none());
+
+        let value = |line| windows::registry_value(line, "NameServer");
+        assert_eq!(value("    NameServer    REG_SZ    1.1.1.1,8.8.8.8\r"), Some("1.1.1.1,8.8.8.8"));
+        assert_eq!(value("    NameServer    REG_SZ    "), Some(""));
+        assert_eq!(windows::registry_value("    NameServer    REG_SZ    1.1.1.1", "Name"), None);
+    }
+}
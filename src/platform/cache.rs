@@ -0,0 +1,191 @@
+//! Detected system DNS and gateway addresses, cached between runs.
+//!
+//! Detection shells out to `scutil`, `ipconfig`, or `route` on some
+//! platforms. Rapid repeated runs reuse the last result for the same network
+//! instead, identified by the local addresses the default routes leave from.
+//! A change of network (new Wi-Fi, VPN up or down) gives a new address and
+//! therefore a fresh detection.
+
+use super::{detect_gateway, detect_system_dns};
+use crate::benchmark::stable_hash;
+use crate::error::PlatformError;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How long detected addresses are reused before detecting again
+pub const DETECT_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Public addresses whose routes identify the network (a.root-servers.net)
+const ROUTE_PROBES: [&str; 2] = ["198.41.0.4:53", "[2001:503:ba3e::2:30]:53"];
+
+/// Held while a cache file is read, changed, and written back
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Addresses detected on one network; `None` parts are detected on next use
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Detected {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    system: Option<(IpAddr, Option<IpAddr>)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gateway: Option<IpAddr>,
+}
+
+/// Detect system DNS, reusing a fresh result for this network from `cache_dir`
+pub async fn cached_system_dns(cache_dir: Option<&Path>) -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+    let path = cache_dir.and_then(cache_path);
+    let detected = path.as_deref().map(load).unwrap_or_default();
+    if let Some(system) = detected.system {
+        tracing::info!(primary = %system.0, secondary = ?system.1, "Using system DNS detected earlier on this network");
+        return Ok(system);
+    }
+
+    let system = detect_system_dns().await?;
+    tracing::info!(primary = %system.0, secondary = ?system.1, "Detected system DNS");
+    if let Some(path) = path {
+        update(&path, |detected| detected.system = Some(system));
+    }
+    Ok(system)
+}
+
+/// Detect the default gateway, reusing a fresh result for this network from `cache_dir`
+pub async fn cached_gateway(cache_dir: Option<&Path>) -> Result<IpAddr, PlatformError> {
+    let path = cache_dir.and_then(cache_path);
+    let detected = path.as_deref().map(load).unwrap_or_default();
+    if let Some(gateway) = detected.gateway {
+        tracing::info!("Using gateway detected earlier on this network: {gateway}");
+        return Ok(gateway);
+    }
+
+    let gateway = detect_gateway().await?;
+    tracing::info!("Detected gateway: {gateway}");
+    if let Some(path) = path {
+        update(&path, |detected| detected.gateway = Some(gateway));
+    }
+    Ok(gateway)
+}
+
+/// Cache file for the current network, or `None` if it has no route
+fn cache_path(cache_dir: &Path) -> Option<PathBuf> {
+    let key = network_key()?;
+    tracing::debug!("Network identified by local addresses {key}");
+    Some(cache_dir.join(format!("detected-{}.json", stable_hash(&key))))
+}
+
+/// Identify the current network by the local address of each default route
+///
+/// Connecting a UDP socket only selects a route; nothing is sent.
+fn network_key() -> Option<String> {
+    let local: Vec<IpAddr> = ROUTE_PROBES
+        .iter()
+        .filter_map(|probe| {
+            let remote: SocketAddr = probe.parse().ok()?;
+            let bind: SocketAddr = if remote.is_ipv4() {
+                ([0, 0, 0, 0], 0).into()
+            } else {
+                ([0u16; 8], 0).into()
+            };
+            let socket = UdpSocket::bind(bind).ok()?;
+            socket.connect(remote).ok()?;
+            Some(socket.local_addr().ok()?.ip())
+        })
+        .collect();
+    network_key_for(&local)
+}
+
+fn network_key_for(local: &[IpAddr]) -> Option<String> {
+    (!local.is_empty()).then(|| local.iter().map(IpAddr::to_string).collect::<Vec<_>>().join(","))
+}
+
+/// Read a cache file, ignoring it if stale or unreadable
+fn load(path: &Path) -> Detected {
+    let fresh = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < DETECT_CACHE_TTL);
+    if !fresh {
+        return Detected::default();
+    }
+
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Record one part of the addresses, keeping what was stored since it was loaded
+///
+/// System DNS and the gateway are detected concurrently, so each reads the
+/// file again under a lock rather than writing back what it loaded earlier.
+fn update(path: &Path, change: impl FnOnce(&mut Detected)) {
+    let _guard = UPDATE_LOCK.lock();
+    let mut detected = load(path);
+    change(&mut detected);
+    store(path, &detected);
+}
+
+/// Write a cache file; the cache is best-effort, so failures are ignored
+///
+/// The file is written aside and renamed into place, so other runs never
+/// read it half-written.
+fn store(path: &Path, detected: &Detected) {
+    if let Ok(content) = serde_json::to_string(detected) {
+        let _ = fs::create_dir_all(path.parent().unwrap_or(Path::new(".")));
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        if fs::write(&temp, content).is_err() || fs::rename(&temp, path).is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("detected-1.json");
+        assert_eq!(load(&path), Detected::default());
+
+        let detected = Detected {
+            system: Some(("192.168.1.1".parse().unwrap(), None)),
+            gateway: None,
+        };
+        store(&path, &detected);
+        assert_eq!(load(&path), detected);
+
+        // Unreadable files are treated as a miss
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(load(&path), Detected::default());
+    }
+
+    #[test]
+    fn test_detect_cache_concurrent_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("detected-1.json");
+        let system = ("192.168.1.1".parse().unwrap(), Some("192.168.1.2".parse().unwrap()));
+        let gateway = "192.168.1.254".parse().unwrap();
+
+        // Both parts survive, whichever is written last
+        for _ in 0..20 {
+            let _ = fs::remove_file(&path);
+            std::thread::scope(|scope| {
+                scope.spawn(|| update(&path, |detected| detected.system = Some(system)));
+                scope.spawn(|| update(&path, |detected| detected.gateway = Some(gateway)));
+            });
+            assert_eq!(load(&path), Detected { system: Some(system), gateway: Some(gateway) });
+        }
+    }
+
+    #[test]
+    fn test_network_key() {
+        assert_eq!(network_key_for(&[]), None);
+        let key = network_key_for(&["192.168.1.20".parse().unwrap(), "2001:db8::20".parse().unwrap()]);
+        assert_eq!(key.as_deref(), Some("192.168.1.20,2001:db8::20"));
+    }
+}
@@ -0,0 +1,92 @@
+//! Running detection commands without blocking the runtime.
+
+use crate::error::PlatformError;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Time each detection command (`scutil`, `route PRINT`, ...) gets to finish
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run a command and return its standard output
+///
+/// The command is killed if it runs longer than `timeout`. A non-zero exit
+/// status is reported as [`PlatformError::CommandFailed`].
+pub async fn run_command(program: &str, args: &[&str], timeout: Duration) -> Result<String, PlatformError> {
+    let command = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+    let failed = |message: String| PlatformError::CommandFailed {
+        command: command.clone(),
+        message,
+    };
+
+    tracing::debug!("Running `{command}`");
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+
+    // Drain stdout concurrently so a chatty command can't fill the pipe and stall
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = tokio::task::spawn_blocking(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PlatformError::CommandTimeout { command, timeout });
+            }
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => return Err(failed(e.to_string())),
+        }
+    };
+
+    if !status.success() {
+        return Err(failed(format!("exited with {status}")));
+    }
+
+    // A detached grandchild may still hold the pipe open; don't wait on it forever
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    match tokio::time::timeout(remaining, reader).await {
+        Ok(Ok(Ok(buf))) => Ok(String::from_utf8_lossy(&buf).into_owned()),
+        Ok(Ok(Err(e))) => Err(failed(e.to_string())),
+        Ok(Err(e)) => Err(failed(e.to_string())),
+        Err(_) => Err(PlatformError::CommandTimeout { command, timeout }),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_command_output() {
+        let output = run_command("echo", &["hello"], COMMAND_TIMEOUT).await.unwrap();
+        assert_eq!(output.trim(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_run_command_timeout() {
+        let start = Instant::now();
+        let err = run_command("sleep", &["5"], Duration::from_millis(100)).await.unwrap_err();
+        assert!(matches!(err, PlatformError::CommandTimeout { ref command, .. } if command == "sleep 5"));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_run_command_failure() {
+        let err = run_command("false", &[], COMMAND_TIMEOUT).await.unwrap_err();
+        assert!(matches!(err, PlatformError::CommandFailed { .. }));
+    }
+}
@@ -0,0 +1,208 @@
+//! Windows Application event log sink for run summaries.
+//!
+//! Events are written with `eventcreate`, which ships with every Windows
+//! version, under the `dns-benchmark` source. Fleet tooling can then collect
+//! them through the usual event forwarding pipeline.
+
+use crate::benchmark::BenchmarkResult;
+use crate::error::PlatformError;
+use crate::probe::warnings_for;
+use std::fmt;
+
+/// Event source shown in the Application log
+pub const EVENT_SOURCE: &str = "dns-benchmark";
+
+/// Event ID of the run summary
+pub const EVENT_RUN_SUMMARY: u16 = 100;
+
+/// Event ID of a server that answered no queries
+pub const EVENT_SERVER_UNREACHABLE: u16 = 101;
+
+/// Event ID of a server flagged by a probe warning
+pub const EVENT_PROBE_WARNING: u16 = 102;
+
+/// Severity of an event log entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    Information,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for EventLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Information => write!(f, "INFORMATION"),
+            Self::Warning => write!(f, "WARNING"),
+            Self::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// A single event log entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvent {
+    pub level: EventLevel,
+    pub id: u16,
+    pub message: String,
+}
+
+/// Build the events for a finished run
+///
+/// The summary always comes first, followed by one event per threshold
+/// violation: servers that answered nothing, and servers a probe warned about.
+pub fn events_for(result: &BenchmarkResult) -> Vec<LogEvent> {
+    let failed = result.completely_failed().count();
+    let fastest = result
+        .fastest()
+        .filter(|s| !s.all_failed())
+        .map(|s| {
+            let avg = s.avg_time.map(|t| t.as_secs_f64() * 1000.0).unwrap_or_default();
+            format!("fastest {} ({}) at {:.2}ms avg", s.name, s.ip, avg)
+        })
+        .unwrap_or_else(|| "no server answered".to_string());
+
+    let mut summary = format!(
+        "DNS benchmark of {} finished in {:.1}s: {} servers, {} unreachable, {}",
+        result.domain,
+        result.duration.as_secs_f64(),
+        result.servers.len(),
+        failed,
+        fastest
+    );
+    if !result.phases.is_empty() {
+        summary.push_str(&format!(" (time spent: {})", result.phases));
+    }
+
+    let mut events = vec![LogEvent {
+        level: if failed > 0 { EventLevel::Warning } else { EventLevel::Information },
+        id: EVENT_RUN_SUMMARY,
+        message: summary,
+    }];
+
+    for server in result.completely_failed() {
+        let error = server.last_error.as_deref().unwrap_or("no response");
+        events.push(LogEvent {
+            level: EventLevel::Error,
+            id: EVENT_SERVER_UNREACHABLE,
+            message: format!("DNS server {} ({}) answered no queries: {}", server.name, server.ip, error),
+        });
+    }
+
+    for server in &result.servers {
+        for warning in warnings_for(&server.probes) {
+            events.push(LogEvent {
+                level: EventLevel::Warning,
+                id: EVENT_PROBE_WARNING,
+                message: format!("DNS server {} ({}) {}", server.name, server.ip, warning),
+            });
+        }
+    }
+
+    events
+}
+
+/// Check that events can be written on this platform
+pub fn check_event_log() -> Result<(), PlatformError> {
+    if cfg!(target_os = "windows") {
+        Ok(())
+    } else {
+        Err(PlatformError::UnsupportedPlatform)
+    }
+}
+
+/// Write the events for a finished run to the Application log
+#[cfg(target_os = "windows")]
+pub fn write_event_log(result: &BenchmarkResult) -> Result<(), PlatformError> {
+    use std::process::Command;
+
+    for event in events_for(result) {
+        let level = event.level.to_string();
+        let id = event.id.to_string();
+        let output = Command::new("eventcreate")
+            .args(["/L", "APPLICATION", "/SO", EVENT_SOURCE])
+            .args(["/T", &level, "/ID", &id, "/D", &event.message])
+            .output()
+            .map_err(|e| PlatformError::CommandFailed {
+                command: "eventcreate".into(),
+                message: e.to_string(),
+            })?;
+
+        if !output.status.success() {
+            return Err(PlatformError::CommandFailed {
+                command: "eventcreate".into(),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the events for a finished run to the Application log
+#[cfg(not(target_os = "windows"))]
+pub fn write_event_log(_result: &BenchmarkResult) -> Result<(), PlatformError> {
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::ServerResult;
+    use crate::dns::{DnsServer, ServerSource};
+    use crate::probe::ProbeResult;
+    use std::time::Duration;
+
+    fn server(name: &str, ip: [u8; 4], successful: u32, avg_ms: Option<u64>) -> ServerResult {
+        ServerResult {
+            total_requests: 10,
+            successful_requests: successful,
+            min_time: avg_ms.map(Duration::from_millis),
+            max_time: avg_ms.map(Duration::from_millis),
+            avg_time: avg_ms.map(Duration::from_millis),
+            last_error: (successful == 0).then(|| "timeout".to_string()),
+            ..ServerResult::new(&DnsServer::from_ip(name, ip.into(), ServerSource::Builtin))
+        }
+    }
+
+    fn result(servers: Vec<ServerResult>) -> BenchmarkResult {
+        BenchmarkResult {
+            duration: Duration::from_millis(4200),
+            ..BenchmarkResult::for_test(servers)
+        }
+    }
+
+    #[test]
+    fn test_events_for_healthy_run() {
+        let events = events_for(&result(vec![server("Cloudflare", [1, 1, 1, 1], 10, Some(12))]));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, EventLevel::Information);
+        assert_eq!(events[0].id, EVENT_RUN_SUMMARY);
+        assert_eq!(
+            events[0].message,
+            "DNS benchmark of example.com finished in 4.2s: 1 servers, 0 unreachable, fastest Cloudflare (1.1.1.1) at 12.00ms avg"
+        );
+    }
+
+    #[test]
+    fn test_events_for_violations() {
+        let mut hijacker = server("ISP", [192, 0, 2, 53], 10, Some(8));
+        hijacker
+            .probes
+            .insert("hijack".to_string(), ProbeResult::fail("answered 192.0.2.1 for a nonexistent domain"));
+        let events = events_for(&result(vec![hijacker, server("Dead", [192, 0, 2, 1], 0, None)]));
+
+        let levels: Vec<_> = events.iter().map(|e| (e.level, e.id)).collect();
+        assert_eq!(
+            levels,
+            vec![
+                (EventLevel::Warning, EVENT_RUN_SUMMARY),
+                (EventLevel::Error, EVENT_SERVER_UNREACHABLE),
+                (EventLevel::Warning, EVENT_PROBE_WARNING),
+            ]
+        );
+        assert_eq!(events[1].message, "DNS server Dead (192.0.2.1) answered no queries: timeout");
+        assert_eq!(events[2].message, "DNS server ISP (192.0.2.53) hijacks NXDOMAIN");
+    }
+}
@@ -0,0 +1,223 @@
+//! Network fingerprint detection (gateway MAC, Wi-Fi SSID, public IP).
+
+use super::command::{run_command, COMMAND_TIMEOUT};
+use super::detect_gateway;
+use crate::benchmark::{create_resolver, hash_identifier, public_prefix, NetworkFingerprint};
+use crate::config::{Config, SocketOptions};
+use crate::dns::LookupStrategy;
+use hickory_resolver::proto::xfer::Protocol;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+
+/// OpenDNS resolver that answers `myip.opendns.com` with the client address
+const OPENDNS_RESOLVER: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(208, 67, 222, 222)), 53);
+
+/// Name resolved to discover the public IP
+const MYIP_NAME: &str = "myip.opendns.com.";
+
+/// Length of the salt local identifiers are hashed with
+const SALT_LEN: usize = 32;
+
+/// Detect the fingerprint of the current network
+///
+/// Every component is best-effort; undetectable parts are left empty.
+pub async fn detect_fingerprint(timeout_ms: u64) -> NetworkFingerprint {
+    let gateway_mac = async {
+        let gateway = detect_gateway().await.ok()?;
+        detect_gateway_mac(gateway).await
+    };
+    let (gateway_mac, ssid, public_ip) = tokio::join!(gateway_mac, detect_ssid(), detect_public_ip(timeout_ms));
+
+    let salt = install_salt();
+    NetworkFingerprint {
+        gateway_mac: gateway_mac.map(|mac| hash_identifier(&mac, &salt)),
+        ssid: ssid.map(|ssid| hash_identifier(&ssid, &salt)),
+        public_prefix: public_ip.map(public_prefix),
+    }
+}
+
+/// Salt of this install, created on first use
+///
+/// It never leaves the machine, so fingerprints of its runs match each other
+/// but not hashes of known MAC addresses or SSIDs. If it can't be stored, a
+/// salt for this run alone is used.
+fn install_salt() -> Vec<u8> {
+    match Config::fingerprint_salt_path() {
+        Ok(path) => load_salt(&path),
+        Err(e) => {
+            tracing::warn!("No place to store the fingerprint salt: {e}");
+            random_salt()
+        }
+    }
+}
+
+/// Read the salt at `path`, or create it there
+fn load_salt(path: &Path) -> Vec<u8> {
+    if let Ok(salt) = fs::read(path)
+        && salt.len() == SALT_LEN
+    {
+        return salt;
+    }
+
+    let salt = random_salt();
+    let stored = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(path, &salt));
+    if let Err(e) = stored {
+        tracing::warn!("Failed to store the fingerprint salt at {}: {e}", path.display());
+    }
+    salt
+}
+
+fn random_salt() -> Vec<u8> {
+    let mut salt = vec![0; SALT_LEN];
+    SystemRandom::new().fill(&mut salt).expect("system random number generator");
+    salt
+}
+
+/// Detect the public IP address via OpenDNS
+async fn detect_public_ip(timeout_ms: u64) -> Option<IpAddr> {
+    let resolver = create_resolver(
+        OPENDNS_RESOLVER,
+        Protocol::Udp,
+        timeout_ms,
+        LookupStrategy::V4.into(),
+        &SocketOptions::default(),
+    );
+    let lookup = resolver.lookup_ip(MYIP_NAME).await.ok()?;
+    lookup.iter().next()
+}
+
+/// Look up the MAC address of the gateway in the ARP cache
+async fn detect_gateway_mac(gateway: IpAddr) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    if let Ok(content) = std::fs::read_to_string("/proc/net/arp")
+        && let Some(mac) = parse_arp_output(&content, gateway)
+    {
+        return Some(mac);
+    }
+
+    let ip = gateway.to_string();
+    let args: &[&str] = if cfg!(target_os = "windows") { &["-a", &ip] } else { &["-n", &ip] };
+    let output = run_command("arp", args, COMMAND_TIMEOUT).await.ok()?;
+    parse_arp_output(&output, gateway)
+}
+
+/// Detect the SSID of the connected Wi-Fi network
+async fn detect_ssid() -> Option<String> {
+    let run = async |cmd: &str, args: &[&str]| run_command(cmd, args, COMMAND_TIMEOUT).await.ok();
+
+    if cfg!(target_os = "linux") {
+        if let Some(ssid) = run("iwgetid", &["-r"]).await.and_then(|text| non_empty(text.trim())) {
+            return Some(ssid);
+        }
+        run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"]).await.and_then(|t| parse_nmcli_ssid(&t))
+    } else if cfg!(target_os = "macos") {
+        run("networksetup", &["-getairportnetwork", "en0"]).await.and_then(|t| parse_networksetup_ssid(&t))
+    } else if cfg!(target_os = "windows") {
+        run("netsh", &["wlan", "show", "interfaces"]).await.and_then(|t| parse_netsh_ssid(&t))
+    } else {
+        None
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Find the MAC address for `ip` in `/proc/net/arp`, `arp -n`, or `arp -a` output
+fn parse_arp_output(text: &str, ip: IpAddr) -> Option<String> {
+    let ip = ip.to_string();
+    let paren = format!("({ip})");
+
+    text.lines()
+        .filter(|line| line.split_whitespace().any(|t| t == ip || t == paren))
+        .flat_map(str::split_whitespace)
+        .find_map(normalize_mac)
+}
+
+/// Normalize a MAC address token to lowercase, colon-separated form
+fn normalize_mac(token: &str) -> Option<String> {
+    let parts: Vec<&str> = token.split([':', '-']).collect();
+    if parts.len() != 6 || parts.iter().any(|p| p.is_empty() || p.len() > 2) {
+        return None;
+    }
+
+    let bytes = parts
+        .iter()
+        .map(|p| u8::from_str_radix(p, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    if bytes.iter().all(|&b| b == 0) {
+        return None; // Incomplete ARP entry
+    }
+
+    Some(bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
+}
+
+fn parse_nmcli_ssid(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("yes:"))
+        .and_then(|ssid| non_empty(ssid.trim()))
+}
+
+fn parse_networksetup_ssid(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("Current Wi-Fi Network:"))
+        .and_then(|ssid| non_empty(ssid.trim()))
+}
+
+fn parse_netsh_ssid(text: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "SSID").then(|| non_empty(value.trim())).flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gateway() -> IpAddr {
+        "192.168.0.1".parse().unwrap()
+    }
+
+    #[test]
+    fn test_load_salt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("fingerprint-salt");
+        let salt = load_salt(&path);
+        assert_eq!(salt.len(), SALT_LEN);
+        // Kept for later runs
+        assert_eq!(load_salt(&path), salt);
+
+        // A damaged salt is replaced
+        fs::write(&path, b"short").unwrap();
+        assert_eq!(load_salt(&path).len(), SALT_LEN);
+        assert_ne!(load_salt(&path), salt);
+    }
+
+    #[test]
+    fn test_parse_arp_output() {
+        let linux = crate::load_test_fixture!("/fingerprint/linux_proc_net_arp.txt");
+        let mac = crate::load_test_fixture!("/fingerprint/mac_arp_n.txt");
+        let windows = crate::load_test_fixture!("/fingerprint/windows_arp_a.txt");
+
+        assert_eq!(parse_arp_output(linux, gateway()).as_deref(), Some("a4:2b:b0:12:34:56"));
+        assert_eq!(parse_arp_output(mac, gateway()).as_deref(), Some("a4:2b:b0:12:34:56"));
+        assert_eq!(parse_arp_output(windows, gateway()).as_deref(), Some("a4:2b:b0:12:34:56"));
+        assert_eq!(parse_arp_output(linux, "10.0.0.1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_parse_ssid() {
+        let netsh = crate::load_test_fixture!("/fingerprint/windows_netsh_wlan.txt");
+
+        assert_eq!(parse_netsh_ssid(netsh).as_deref(), Some("Home Network"));
+        assert_eq!(parse_nmcli_ssid("no:Neighbour\nyes:Home Network\n").as_deref(), Some("Home Network"));
+        assert_eq!(
+            parse_networksetup_ssid("Current Wi-Fi Network: Home Network\n").as_deref(),
+            Some("Home Network")
+        );
+        assert_eq!(parse_networksetup_ssid("You are not associated with an AirPort network.\n"), None);
+    }
+}
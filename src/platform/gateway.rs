@@ -2,65 +2,301 @@
 
 use crate::error::PlatformError;
 use std::net::IpAddr;
-use std::str::FromStr;
 
 /// Detect the default gateway IP address
-pub fn detect_gateway() -> Result<IpAddr, PlatformError> {
+pub async fn detect_gateway() -> Result<IpAddr, PlatformError> {
     #[cfg(target_os = "linux")]
-    return linux::detect();
+    return linux::detect().await;
 
     #[cfg(target_os = "macos")]
-    return macos::detect();
+    return macos::detect().await;
 
     #[cfg(target_os = "windows")]
-    return windows::detect();
+    return windows::detect().await;
 
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     Err(PlatformError::UnsupportedPlatform)
 }
 
+/// Gateway of a default route and the interface it leaves from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayRoute {
+    pub ip: IpAddr,
+    /// Interface name, like `wlan0` or `en0` (its address on Windows)
+    pub interface: String,
+}
+
+/// Detect the gateways of all default routes, preferred first
+///
+/// A host on several networks at once (Wi-Fi, Ethernet, a VPN) has a default
+/// route through each, where [`detect_gateway`] only returns the preferred one.
+pub async fn detect_gateways() -> Result<Vec<GatewayRoute>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    return linux::detect_all().await;
+
+    #[cfg(target_os = "macos")]
+    return macos::detect_all().await;
+
+    #[cfg(target_os = "windows")]
+    return windows::detect_all().await;
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+/// Name of a network interface by index, or the index if it's gone
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn interface_name(index: u32) -> String {
+    let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+    // SAFETY: the buffer holds the IF_NAMESIZE bytes a name can take
+    if unsafe { libc::if_indextoname(index, name.as_mut_ptr()) }.is_null() {
+        return format!("interface {index}");
+    }
+    // SAFETY: if_indextoname wrote a NUL-terminated name
+    unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) }.to_string_lossy().into_owned()
+}
+
 #[cfg(target_os = "linux")]
 mod linux {
     use super::*;
-    use std::fs;
-    use std::net::Ipv4Addr;
-    use std::process::Command;
+    use crate::platform::command::COMMAND_TIMEOUT;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
 
     const PROC_NET_ROUTE: &str = "/proc/net/route";
 
-    pub fn detect() -> Result<IpAddr, PlatformError> {
-        // Try /proc/net/route first (most reliable)
-        if let Ok(content) = fs::read_to_string(PROC_NET_ROUTE) {
-            if let Ok(ip) = parse_proc_net_route(&content) {
-                return Ok(ip);
+    /// Netlink message header length (`NLMSG_HDRLEN`)
+    const NLMSG_HDRLEN: usize = 16;
+
+    /// Routing message length (`struct rtmsg`)
+    const RTMSG_LEN: usize = 12;
+
+    /// Route messages in a dump (`RTM_NEWROUTE`)
+    const RTM_NEWROUTE: u16 = 24;
+
+    pub async fn detect() -> Result<IpAddr, PlatformError> {
+        detect_all()
+            .await?
+            .into_iter()
+            .next()
+            .map(|route| route.ip)
+            .ok_or_else(|| PlatformError::GatewayDetection("No default route with a gateway".into()))
+    }
+
+    pub async fn detect_all() -> Result<Vec<GatewayRoute>, PlatformError> {
+        // Ask the kernel directly, which needs no iproute2 and sees IPv6 routes too.
+        // The socket calls block for up to the netlink timeout.
+        let routes = tokio::task::spawn_blocking(netlink_routes)
+            .await
+            .map_err(|e| PlatformError::GatewayDetection(e.to_string()))?;
+        match routes {
+            Ok(routes) if !routes.is_empty() => {
+                let mut gateways: Vec<GatewayRoute> = Vec::new();
+                for route in select_default_routes(&routes) {
+                    let gateway = GatewayRoute {
+                        ip: route.gateway,
+                        interface: interface_name(route.interface),
+                    };
+                    if !gateways.contains(&gateway) {
+                        gateways.push(gateway);
+                    }
+                }
+                return Ok(gateways);
+            }
+            Ok(_) => tracing::debug!("No default route over netlink, reading {PROC_NET_ROUTE}"),
+            Err(e) => tracing::debug!("No default route over netlink ({e}), reading {PROC_NET_ROUTE}"),
+        }
+
+        // Fallback for sandboxes that block netlink sockets
+        let content = tokio::fs::read_to_string(PROC_NET_ROUTE)
+            .await
+            .map_err(|e| PlatformError::GatewayDetection(format!("Failed to read {PROC_NET_ROUTE}: {e}")))?;
+        parse_proc_net_routes(&content)
+    }
+
+    /// Default routes of the main routing table, from an rtnetlink route dump
+    fn netlink_routes() -> Result<Vec<DefaultRoute>, PlatformError> {
+        let error = |what: &str| {
+            PlatformError::GatewayDetection(format!("{what}: {}", std::io::Error::last_os_error()))
+        };
+
+        // SAFETY: plain socket creation; the descriptor is checked before use
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) };
+        if fd < 0 {
+            return Err(error("Failed to open a netlink socket"));
+        }
+        // SAFETY: `fd` is a fresh descriptor nothing else owns
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // The kernel gets as long to answer as a detection command
+        let timeout = libc::timeval {
+            tv_sec: COMMAND_TIMEOUT.as_secs() as libc::time_t,
+            tv_usec: 0,
+        };
+        // SAFETY: the option value is a `timeval` of the given size
+        let status = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                (&raw const timeout).cast(),
+                size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if status < 0 {
+            return Err(error("Failed to set the netlink timeout"));
+        }
+
+        // Unbound netlink sockets send to the kernel
+        let request = route_dump_request();
+        // SAFETY: the buffer is valid for its length
+        let sent = unsafe { libc::send(socket.as_raw_fd(), request.as_ptr().cast(), request.len(), 0) };
+        if sent < 0 {
+            return Err(error("Failed to send the route dump request"));
+        }
+
+        let mut routes = Vec::new();
+        let mut buffer = vec![0u8; 32 * 1024];
+        loop {
+            // SAFETY: the buffer is valid and writable for its length
+            let received =
+                unsafe { libc::recv(socket.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+            if received < 0 {
+                return Err(error("Failed to read the route dump"));
+            }
+            let received = received as usize;
+            if received == 0 || parse_route_messages(&buffer[..received], &mut routes)? {
+                break;
             }
         }
+        Ok(routes)
+    }
+
+    /// `RTM_GETROUTE` request dumping the routes of all address families
+    fn route_dump_request() -> Vec<u8> {
+        let len = NLMSG_HDRLEN + RTMSG_LEN;
+        let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        let mut request = Vec::with_capacity(len);
+        request.extend_from_slice(&(len as u32).to_ne_bytes());
+        request.extend_from_slice(&libc::RTM_GETROUTE.to_ne_bytes());
+        request.extend_from_slice(&flags.to_ne_bytes());
+        request.extend_from_slice(&1u32.to_ne_bytes()); // sequence number
+        request.extend_from_slice(&0u32.to_ne_bytes()); // port id, filled in by the kernel
+        // `struct rtmsg`, all zero: any family, table, and type
+        request.resize(len, 0);
+        request
+    }
+
+    /// Default route in a route dump
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DefaultRoute {
+        pub gateway: IpAddr,
+        /// Route metric (`RTA_PRIORITY`); lower is preferred
+        pub metric: u32,
+        /// Index of the outgoing interface (`RTA_OIF`)
+        pub interface: u32,
+    }
+
+    /// Collect the default routes of the main table from one read of a route dump
+    ///
+    /// Returns `true` once the dump is done.
+    pub fn parse_route_messages(mut data: &[u8], routes: &mut Vec<DefaultRoute>) -> Result<bool, PlatformError> {
+        let invalid = || PlatformError::ParseError("Truncated netlink message".into());
+        let u16_at = |data: &[u8], at: usize| u16::from_ne_bytes([data[at], data[at + 1]]);
+        let u32_at = |data: &[u8], at: usize| u32::from_ne_bytes(data[at..at + 4].try_into().expect("4 bytes"));
+
+        while data.len() >= NLMSG_HDRLEN {
+            let len = u32_at(data, 0) as usize;
+            if len < NLMSG_HDRLEN || len > data.len() {
+                return Err(invalid());
+            }
+            let message = &data[..len];
+            data = &data[len.next_multiple_of(4).min(data.len())..];
+
+            match u16_at(message, 4) {
+                kind if i32::from(kind) == libc::NLMSG_DONE => return Ok(true),
+                kind if i32::from(kind) == libc::NLMSG_ERROR => {
+                    let code = message.get(NLMSG_HDRLEN..NLMSG_HDRLEN + 4).ok_or_else(invalid)?;
+                    let code = i32::from_ne_bytes(code.try_into().expect("4 bytes"));
+                    return Err(PlatformError::GatewayDetection(format!(
+                        "Route dump failed: {}",
+                        std::io::Error::from_raw_os_error(-code)
+                    )));
+                }
+                RTM_NEWROUTE => {}
+                _ => continue,
+            }
 
-        // Fallback to `ip route`
-        let output = Command::new("ip")
-            .args(["route", "show", "default"])
-            .output()
-            .map_err(|e| PlatformError::CommandFailed {
-                command: "ip route show default".into(),
-                message: e.to_string(),
-            })?;
-
-        if output.status.success() {
-            let text = String::from_utf8_lossy(&output.stdout);
-            parse_ip_route(&text)
-        } else {
-            Err(PlatformError::GatewayDetection("No default gateway found".into()))
+            let route = message.get(NLMSG_HDRLEN..).ok_or_else(invalid)?;
+            if route.len() < RTMSG_LEN {
+                return Err(invalid());
+            }
+            let (family, dst_len, mut table, kind) = (route[0], route[1], u32::from(route[4]), route[7]);
+            if dst_len != 0 || kind != libc::RTN_UNICAST {
+                continue;
+            }
+
+            let mut gateway = None;
+            let (mut metric, mut interface) = (0, 0);
+            let mut attrs = &route[RTMSG_LEN..];
+            while attrs.len() >= 4 {
+                let attr_len = usize::from(u16_at(attrs, 0));
+                if attr_len < 4 || attr_len > attrs.len() {
+                    return Err(invalid());
+                }
+                let value = &attrs[4..attr_len];
+                match (u16_at(attrs, 2), value.len()) {
+                    (libc::RTA_GATEWAY, 4) if i32::from(family) == libc::AF_INET => {
+                        gateway = Some(IpAddr::V4(Ipv4Addr::new(value[0], value[1], value[2], value[3])));
+                    }
+                    (libc::RTA_GATEWAY, 16) if i32::from(family) == libc::AF_INET6 => {
+                        let octets: [u8; 16] = value.try_into().expect("16 bytes");
+                        gateway = Some(IpAddr::V6(Ipv6Addr::from(octets)));
+                    }
+                    (libc::RTA_PRIORITY, 4) => metric = u32_at(value, 0),
+                    (libc::RTA_OIF, 4) => interface = u32_at(value, 0),
+                    (libc::RTA_TABLE, 4) => table = u32_at(value, 0),
+                    _ => {}
+                }
+                attrs = &attrs[attr_len.next_multiple_of(4).min(attrs.len())..];
+            }
+
+            if table == u32::from(libc::RT_TABLE_MAIN)
+                && let Some(gateway) = gateway
+            {
+                routes.push(DefaultRoute {
+                    gateway,
+                    metric,
+                    interface,
+                });
+            }
         }
+        Ok(false)
     }
 
-    pub fn parse_proc_net_route(content: &str) -> Result<IpAddr, PlatformError> {
+    /// Default routes in order of preference: IPv4 over IPv6, then the lowest metric
+    ///
+    /// Link-local IPv6 gateways are left out, as they can't be queried without their zone.
+    pub fn select_default_routes(routes: &[DefaultRoute]) -> Vec<DefaultRoute> {
+        let mut routes: Vec<_> = routes
+            .iter()
+            .filter(|route| !matches!(route.gateway, IpAddr::V6(v6) if v6.is_unicast_link_local()))
+            .copied()
+            .collect();
+        routes.sort_by_key(|route| (route.gateway.is_ipv6(), route.metric));
+        routes
+    }
+
+    /// Default routes in /proc/net/route (IPv4 only), lowest metric first
+    pub fn parse_proc_net_routes(content: &str) -> Result<Vec<GatewayRoute>, PlatformError> {
+        let mut routes = Vec::new();
         for (i, line) in content.lines().enumerate() {
             if i == 0 {
                 continue; // Skip header
             }
 
             let cols: Vec<&str> = line.split_whitespace().collect();
-            if cols.len() < 3 {
+            if cols.len() < 7 {
                 continue;
             }
 
@@ -77,113 +313,220 @@ mod linux {
                 }
 
                 let ip = Ipv4Addr::from(u32::from_le_bytes(bytes));
-                return Ok(IpAddr::V4(ip));
+                let metric = cols[6].parse::<u32>().unwrap_or(u32::MAX);
+                let route = GatewayRoute {
+                    ip: IpAddr::V4(ip),
+                    interface: cols[0].to_string(),
+                };
+                routes.push((metric, route));
             }
         }
 
-        Err(PlatformError::GatewayDetection("No default route in /proc/net/route".into()))
-    }
-
-    pub fn parse_ip_route(text: &str) -> Result<IpAddr, PlatformError> {
-        for line in text.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 && parts[0] == "default" {
-                // Find "via" keyword and get the next token
-                for (i, part) in parts.iter().enumerate() {
-                    if *part == "via" && i + 1 < parts.len() {
-                        return IpAddr::from_str(parts[i + 1])
-                            .map_err(|_| PlatformError::ParseError("Invalid gateway IP".into()));
-                    }
-                }
-            }
+        if routes.is_empty() {
+            return Err(PlatformError::GatewayDetection("No default route in /proc/net/route".into()));
         }
-
-        Err(PlatformError::GatewayDetection("No default gateway in ip route output".into()))
+        routes.sort_by_key(|(metric, _)| *metric);
+        Ok(routes.into_iter().map(|(_, route)| route).collect())
     }
 }
 
 #[cfg(target_os = "macos")]
 mod macos {
     use super::*;
-    use std::process::Command;
-
-    pub fn detect() -> Result<IpAddr, PlatformError> {
-        // Try `route -n get default` first
-        if let Ok(output) = Command::new("route").args(["-n", "get", "default"]).output() {
-            if output.status.success() {
-                let text = String::from_utf8_lossy(&output.stdout);
-                if let Ok(ip) = parse_route_get_default(&text) {
-                    return Ok(ip);
-                }
-            }
-        }
+    use std::net::Ipv4Addr;
+
+    /// Attempts at reading the routing table, which can grow between sizing and reading it
+    const SYSCTL_ATTEMPTS: usize = 3;
 
-        // Fallback to `netstat -rn`
-        let output = Command::new("netstat")
-            .arg("-rn")
-            .output()
-            .map_err(|e| PlatformError::CommandFailed {
-                command: "netstat -rn".into(),
-                message: e.to_string(),
-            })?;
-
-        if output.status.success() {
-            let text = String::from_utf8_lossy(&output.stdout);
-            parse_netstat_rn(&text)
-        } else {
-            Err(PlatformError::GatewayDetection("netstat failed".into()))
+    pub async fn detect() -> Result<IpAddr, PlatformError> {
+        let table = route_table()?;
+        parse_default_routes(&table)
+            .into_iter()
+            .find(|route| !route.scoped)
+            .map(|route| IpAddr::V4(route.gateway))
+            .ok_or_else(|| PlatformError::GatewayDetection("No default route in the routing table".into()))
+    }
+
+    /// The default route, then the default routes scoped to other interfaces
+    pub async fn detect_all() -> Result<Vec<GatewayRoute>, PlatformError> {
+        let mut routes = parse_default_routes(&route_table()?);
+        routes.sort_by_key(|route| route.scoped);
+
+        let mut interfaces = Vec::new();
+        let mut gateways = Vec::new();
+        for route in routes {
+            if !interfaces.contains(&route.interface) {
+                interfaces.push(route.interface);
+                gateways.push(GatewayRoute {
+                    ip: IpAddr::V4(route.gateway),
+                    interface: interface_name(u32::from(route.interface)),
+                });
+            }
         }
+        Ok(gateways)
     }
 
-    pub fn parse_route_get_default(text: &str) -> Result<IpAddr, PlatformError> {
-        for line in text.lines() {
-            let line = line.trim();
-            if line.starts_with("gateway:") {
-                if let Some(ip_str) = line.split_whitespace().nth(1) {
-                    return IpAddr::from_str(ip_str)
-                        .map_err(|_| PlatformError::ParseError("Invalid gateway IP".into()));
-                }
+    /// IPv4 routes through a gateway, as routing messages from the `PF_ROUTE` sysctl
+    ///
+    /// Unlike `route` and `netstat`, this needs no subprocess, so it works in
+    /// sandboxes that don't allow running them.
+    fn route_table() -> Result<Vec<u8>, PlatformError> {
+        let mut mib = [libc::CTL_NET, libc::PF_ROUTE, 0, libc::AF_INET, libc::NET_RT_FLAGS, libc::RTF_GATEWAY];
+        let error = || {
+            PlatformError::GatewayDetection(format!(
+                "Failed to read the routing table: {}",
+                std::io::Error::last_os_error()
+            ))
+        };
+
+        for _ in 0..SYSCTL_ATTEMPTS {
+            let mut len = 0;
+            let null = std::ptr::null_mut();
+            // SAFETY: a null buffer only asks for the size needed
+            let status = unsafe { libc::sysctl(mib.as_mut_ptr(), mib.len() as u32, null, &mut len, null, 0) };
+            if status < 0 {
+                return Err(error());
+            }
+
+            let mut table = vec![0u8; len];
+            // SAFETY: the buffer is valid and writable for `len` bytes
+            let status = unsafe {
+                libc::sysctl(mib.as_mut_ptr(), mib.len() as u32, table.as_mut_ptr().cast(), &mut len, null, 0)
+            };
+            if status == 0 {
+                table.truncate(len);
+                return Ok(table);
+            }
+            // Routes were added since the table was sized
+            if std::io::Error::last_os_error().raw_os_error() != Some(libc::ENOMEM) {
+                return Err(error());
             }
         }
+        Err(PlatformError::GatewayDetection("The routing table kept growing while being read".into()))
+    }
 
-        Err(PlatformError::GatewayDetection("No gateway in route output".into()))
+    /// Default route in the routing table
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DefaultRoute {
+        pub gateway: Ipv4Addr,
+        /// Index of the outgoing interface
+        pub interface: u16,
+        /// Scoped to its interface (`RTF_IFSCOPE`), so only used by sockets bound to it
+        pub scoped: bool,
     }
 
-    pub fn parse_netstat_rn(text: &str) -> Result<IpAddr, PlatformError> {
-        for line in text.lines() {
-            let trimmed = line.trim();
-            if trimmed.starts_with("default") {
-                let cols: Vec<&str> = trimmed.split_whitespace().collect();
-                if cols.len() >= 2 {
-                    if let Ok(ip) = IpAddr::from_str(cols[1]) {
-                        return Ok(ip);
-                    }
+    /// Default routes in a `NET_RT_FLAGS` routing table, in table order
+    ///
+    /// Each message is an `rt_msghdr` followed by the socket addresses its
+    /// `rtm_addrs` bits name, in bit order, each padded to 4 bytes. Default
+    /// routes have destination 0.0.0.0 and an empty netmask. Parsing stops at
+    /// a truncated message.
+    pub fn parse_default_routes(mut table: &[u8]) -> Vec<DefaultRoute> {
+        let header_len = size_of::<libc::rt_msghdr>();
+        let mut routes = Vec::new();
+        while table.len() >= header_len {
+            let len = usize::from(u16::from_ne_bytes([table[0], table[1]]));
+            if len < header_len || len > table.len() {
+                break;
+            }
+            let message = &table[..len];
+            table = &table[len..];
+
+            let interface = u16::from_ne_bytes([message[4], message[5]]);
+            let flags = i32::from_ne_bytes(message[8..12].try_into().expect("4 bytes"));
+            let addrs = i32::from_ne_bytes(message[12..16].try_into().expect("4 bytes"));
+            if flags & libc::RTF_GATEWAY == 0 {
+                continue;
+            }
+
+            let (mut destination, mut gateway, mut netmask) = (None, None, None);
+            let mut sockaddrs = &message[header_len..];
+            for bit in (0..libc::RTAX_MAX).map(|i| 1 << i).filter(|bit| addrs & bit != 0) {
+                let Some(&sa_len) = sockaddrs.first() else {
+                    break;
+                };
+                let sa_len = usize::from(sa_len);
+                let sockaddr = sockaddrs.get(..sa_len).unwrap_or(sockaddrs);
+                match bit {
+                    libc::RTA_DST => destination = sockaddr_ipv4(sockaddr),
+                    libc::RTA_GATEWAY => gateway = sockaddr_ipv4(sockaddr),
+                    // Netmasks are trimmed after their last non-zero byte
+                    libc::RTA_NETMASK => netmask = Some(sockaddr.iter().skip(4).all(|&b| b == 0)),
+                    _ => {}
                 }
+                // Padded to 4 bytes, and an empty address still takes 4
+                let padded = if sa_len == 0 { 4 } else { sa_len.next_multiple_of(4) };
+                sockaddrs = sockaddrs.get(padded..).unwrap_or_default();
+            }
+
+            if destination == Some(Ipv4Addr::UNSPECIFIED)
+                && netmask.unwrap_or(true)
+                && let Some(gateway) = gateway
+            {
+                routes.push(DefaultRoute {
+                    gateway,
+                    interface,
+                    scoped: flags & libc::RTF_IFSCOPE != 0,
+                });
             }
         }
+        routes
+    }
 
-        Err(PlatformError::GatewayDetection("No default route in netstat output".into()))
+    /// Address of an IPv4 `sockaddr_in`, or `None` for other families (like links)
+    fn sockaddr_ipv4(sockaddr: &[u8]) -> Option<Ipv4Addr> {
+        match sockaddr {
+            [_, family, _, _, a, b, c, d, ..] if i32::from(*family) == libc::AF_INET => {
+                Some(Ipv4Addr::new(*a, *b, *c, *d))
+            }
+            _ => None,
+        }
     }
 }
 
 #[cfg(target_os = "windows")]
 mod windows {
     use super::*;
-    use std::process::Command;
-
-    pub fn detect() -> Result<IpAddr, PlatformError> {
-        let output = Command::new("route")
-            .arg("PRINT")
-            .output()
-            .map_err(|e| PlatformError::CommandFailed {
-                command: "route PRINT".into(),
-                message: e.to_string(),
-            })?;
-
-        let text = String::from_utf8_lossy(&output.stdout);
+    use std::str::FromStr;
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+
+    pub async fn detect() -> Result<IpAddr, PlatformError> {
+        let text = run_command("route", &["PRINT"], COMMAND_TIMEOUT).await?;
         parse_route_print(&text)
     }
 
+    pub async fn detect_all() -> Result<Vec<GatewayRoute>, PlatformError> {
+        let text = run_command("route", &["PRINT", "-4"], COMMAND_TIMEOUT).await?;
+        Ok(parse_default_routes(&text))
+    }
+
+    /// IPv4 default routes in `route PRINT` output, lowest metric first
+    ///
+    /// Routes are labeled with the address of their interface, which is what
+    /// the table shows. Persistent routes, which list no interface, are skipped.
+    pub fn parse_default_routes(text: &str) -> Vec<GatewayRoute> {
+        let mut routes: Vec<(u32, GatewayRoute)> = Vec::new();
+        for line in text.lines() {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if let [destination, netmask, gateway, interface, metric, ..] = cols[..]
+                && destination == "0.0.0.0"
+                && netmask == "0.0.0.0"
+                && let Ok(ip) = IpAddr::from_str(gateway)
+                && IpAddr::from_str(interface).is_ok()
+            {
+                let route = GatewayRoute {
+                    ip,
+                    interface: interface.to_string(),
+                };
+                if !routes.iter().any(|(_, r)| *r == route) {
+                    routes.push((metric.parse().unwrap_or(u32::MAX), route));
+                }
+            }
+        }
+        routes.sort_by_key(|(metric, _)| *metric);
+        routes.into_iter().map(|(_, route)| route).collect()
+    }
+
     pub fn parse_route_print(text: &str) -> Result<IpAddr, PlatformError> {
         let mut in_ipv4_section = false;
 
@@ -224,39 +567,133 @@ mod tests {
     #[cfg(target_os = "linux")]
     fn test_parse_proc_net_route() {
         let content = crate::load_test_fixture!("/gateway/linux_proc_net_route.txt");
-        let ip = linux::parse_proc_net_route(content).unwrap();
-        assert_eq!(ip.to_string(), "192.168.0.1");
+        let routes = linux::parse_proc_net_routes(content).unwrap();
+        let routes: Vec<_> = routes.iter().map(|r| (r.ip.to_string(), r.interface.as_str())).collect();
+        // Ethernet and Wi-Fi to the same router, lowest metric first
+        let ip = "192.168.0.1".to_string();
+        assert_eq!(routes, [(ip.clone(), "enp1s0"), (ip, "wlp0s20f3")]);
+        assert!(linux::parse_proc_net_routes("Iface\tDestination\tGateway\n").is_err());
     }
 
     #[test]
     #[cfg(target_os = "linux")]
-    fn test_parse_ip_route() {
-        let content = crate::load_test_fixture!("/gateway/linux_ip_route_default.txt");
-        let ip = linux::parse_ip_route(content).unwrap();
-        assert_eq!(ip.to_string(), "192.168.0.1");
+    fn test_parse_route_messages() {
+        // One RTM_NEWROUTE message: rtmsg, then RTA_GATEWAY, RTA_PRIORITY, RTA_TABLE, and RTA_OIF
+        let route = |family: u8, dst_len: u8, gateway: &[u8], metric: u32, table: u32| {
+            let mut attrs = Vec::new();
+            let (metric, table, interface) = (metric.to_ne_bytes(), table.to_ne_bytes(), 2u32.to_ne_bytes());
+            for (kind, value) in [(5u16, gateway), (6, &metric), (15, &table), (4, &interface)] {
+                attrs.extend_from_slice(&(4 + value.len() as u16).to_ne_bytes());
+                attrs.extend_from_slice(&kind.to_ne_bytes());
+                attrs.extend_from_slice(value);
+            }
+            let mut message = Vec::new();
+            message.extend_from_slice(&(16 + 12 + attrs.len() as u32).to_ne_bytes());
+            message.extend_from_slice(&24u16.to_ne_bytes());
+            message.extend_from_slice(&[0; 10]);
+            message.extend_from_slice(&[family, dst_len, 0, 0, 254, 3, 0, 1, 0, 0, 0, 0]);
+            message.extend_from_slice(&attrs);
+            message
+        };
+        let done = [20u32.to_ne_bytes(), [3, 0, 2, 0], [0; 4], [0; 4], [0; 4]].concat();
+
+        let link_local = [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        let global = [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        let dump = [
+            route(10, 0, &link_local, 100, 254),
+            route(2, 0, &[192, 168, 0, 1], 600, 254),
+            // A VPN's policy routing table and a non-default route
+            route(2, 0, &[10, 8, 0, 1], 50, 200),
+            route(2, 8, &[10, 0, 0, 1], 0, 254),
+            route(2, 0, &[192, 168, 1, 1], 100, 254),
+        ]
+        .concat();
+
+        let mut routes = Vec::new();
+        assert!(!linux::parse_route_messages(&dump, &mut routes).unwrap());
+        assert!(linux::parse_route_messages(&done, &mut routes).unwrap());
+        assert_eq!(routes.len(), 3);
+        assert!(routes.iter().all(|route| route.interface == 2));
+        // IPv4 first, then the lowest metric; the link-local gateway can't be used
+        let gateways = |routes: &[linux::DefaultRoute]| {
+            let selected = linux::select_default_routes(routes);
+            selected.iter().map(|route| route.gateway.to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(gateways(&routes), ["192.168.1.1", "192.168.0.1"]);
+
+        let mut routes = Vec::new();
+        linux::parse_route_messages(&route(10, 0, &link_local, 100, 254), &mut routes).unwrap();
+        assert!(gateways(&routes).is_empty());
+        linux::parse_route_messages(&route(10, 0, &global, 1024, 254), &mut routes).unwrap();
+        assert_eq!(gateways(&routes), ["2001:db8::1"]);
+
+        assert!(linux::parse_route_messages(&dump[..20], &mut routes).is_err());
     }
 
     #[test]
     #[cfg(target_os = "macos")]
-    fn test_parse_route_get_default() {
-        let content = crate::load_test_fixture!("/gateway/mac_route_get_default.txt");
-        let ip = macos::parse_route_get_default(content).unwrap();
-        assert_eq!(ip.to_string(), "192.168.0.1");
+    fn test_parse_default_routes() {
+        // One routing message: rt_msghdr, then each address padded to 4 bytes
+        let route = |interface: u16, flags: i32, sockaddrs: &[&[u8]]| {
+            let mut body = Vec::new();
+            for sockaddr in sockaddrs {
+                body.extend_from_slice(sockaddr);
+                body.resize(body.len() + (4 - sockaddr.len() % 4) % 4, 0);
+                if sockaddr.is_empty() {
+                    body.resize(body.len() + 4, 0);
+                }
+            }
+            let mut message = vec![0u8; size_of::<libc::rt_msghdr>()];
+            let len = (message.len() + body.len()) as u16;
+            message[..2].copy_from_slice(&len.to_ne_bytes());
+            message[4..6].copy_from_slice(&interface.to_ne_bytes());
+            message[8..12].copy_from_slice(&flags.to_ne_bytes());
+            let addrs = libc::RTA_DST | libc::RTA_GATEWAY | libc::RTA_NETMASK;
+            message[12..16].copy_from_slice(&addrs.to_ne_bytes());
+            message.extend_from_slice(&body);
+            message
+        };
+        let sockaddr_in = |ip: [u8; 4]| [&[16, 2, 0, 0][..], &ip, &[0; 8]].concat();
+        // Link-layer gateway of an interface route
+        let link = [20u8, 18, 4, 0, 6, 3, 6, 0, 101, 110, 48, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let gateway = libc::RTF_UP | libc::RTF_GATEWAY;
+
+        let table = [
+            route(4, libc::RTF_UP, &[&sockaddr_in([192, 168, 0, 0]), &link, &[7, 2, 0, 0, 255, 255, 255]]),
+            // The scoped default route of a second interface
+            route(7, gateway | libc::RTF_IFSCOPE, &[&sockaddr_in([0; 4]), &sockaddr_in([10, 0, 0, 1]), &[]]),
+            route(4, gateway, &[&sockaddr_in([10, 0, 0, 0]), &sockaddr_in([10, 8, 0, 1]), &[5, 2, 0, 0, 255]]),
+            route(4, gateway, &[&sockaddr_in([0; 4]), &sockaddr_in([192, 168, 0, 1]), &[]]),
+        ]
+        .concat();
+        let routes = macos::parse_default_routes(&table);
+        let routes: Vec<_> = routes.iter().map(|r| (r.gateway.to_string(), r.interface, r.scoped)).collect();
+        assert_eq!(routes, [("10.0.0.1".to_string(), 7, true), ("192.168.0.1".to_string(), 4, false)]);
+        // A truncated message ends the table
+        assert_eq!(macos::parse_default_routes(&table[..table.len() - 10]).len(), 1);
     }
 
     #[test]
-    #[cfg(target_os = "macos")]
-    fn test_parse_netstat_rn() {
-        let content = crate::load_test_fixture!("/gateway/mac_netstat_rn.txt");
-        let ip = macos::parse_netstat_rn(content).unwrap();
+    #[cfg(target_os = "windows")]
+    fn test_parse_route_print() {
+        let content = crate::load_test_fixture!("/gateway/windows_route_print.txt");
+        let ip = windows::parse_route_print(content).unwrap();
         assert_eq!(ip.to_string(), "192.168.0.1");
     }
 
     #[test]
     #[cfg(target_os = "windows")]
-    fn test_parse_route_print() {
+    fn test_parse_default_routes() {
         let content = crate::load_test_fixture!("/gateway/windows_route_print.txt");
-        let ip = windows::parse_route_print(content).unwrap();
-        assert_eq!(ip.to_string(), "192.168.0.1");
+        let routes = windows::parse_default_routes(content);
+        assert_eq!(routes.len(), 1);
+        assert_eq!((routes[0].ip.to_string(), routes[0].interface.as_str()), ("192.168.0.1".into(), "192.168.1.100"));
+
+        // Wi-Fi and Ethernet, with the persistent route of one of them
+        let text = "0.0.0.0 0.0.0.0 192.168.1.1 192.168.1.20 55\n\
+                    0.0.0.0 0.0.0.0 10.0.0.1 10.0.0.20 25\n\
+                    0.0.0.0 0.0.0.0 10.0.0.1 Default\n";
+        let routes: Vec<_> = windows::parse_default_routes(text).into_iter().map(|r| r.interface).collect();
+        assert_eq!(routes, ["10.0.0.20", "192.168.1.20"]);
     }
 }
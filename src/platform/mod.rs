@@ -1,18 +1,54 @@
-//! Platform-specific detection for system DNS and gateway.
+//! Platform-specific detection for system DNS, gateway, and VPN tunnels, and the Windows event log sink.
 
+mod apply;
+mod cache;
+mod command;
+mod eventlog;
+mod fingerprint;
 mod gateway;
 mod system;
+mod vpn;
 
-pub use gateway::detect_gateway;
-pub use system::detect_system_dns;
+pub use apply::{detect_dns_settings, run_dns_commands, DnsBackend, DnsCommand, DnsSettings, APPLY_TIMEOUT};
+pub use cache::DETECT_CACHE_TTL;
+pub use command::{run_command, COMMAND_TIMEOUT};
+pub use eventlog::{
+    check_event_log, events_for, write_event_log, EventLevel, LogEvent, EVENT_PROBE_WARNING, EVENT_RUN_SUMMARY,
+    EVENT_SERVER_UNREACHABLE, EVENT_SOURCE,
+};
+pub use fingerprint::detect_fingerprint;
+pub use gateway::{detect_gateway, detect_gateways, GatewayRoute};
+pub use system::{
+    detect_adapter_dns, detect_dhcp_dns, detect_search_domains, detect_system_dns, AdapterDns, AdapterFilter,
+};
+pub use vpn::{InterfaceAddr, VpnRoutes};
 
 use crate::dns::{DnsServer, IpVersion, ServerSource};
 use crate::error::PlatformError;
 use std::net::IpAddr;
+use std::path::Path;
 
 /// Detect system DNS servers and return them as DnsServer entries
-pub fn get_system_dns_servers(ip_version: IpVersion) -> Result<Vec<DnsServer>, PlatformError> {
-    let (primary, secondary) = detect_system_dns()?;
+///
+/// With a `cache_dir`, a detection from the last few minutes on the same
+/// network is reused (see [`DETECT_CACHE_TTL`]). A non-empty `adapters`
+/// filter takes every server of the selected adapters instead (see
+/// [`detect_adapter_dns`] for the platforms that have them). A filter that
+/// selects all adapters without naming any falls back to the usual detection
+/// where there are none.
+pub async fn get_system_dns_servers(
+    ip_version: IpVersion,
+    cache_dir: Option<&Path>,
+    adapters: &AdapterFilter,
+) -> Result<Vec<DnsServer>, PlatformError> {
+    if !adapters.is_empty() {
+        match adapter_dns_servers(ip_version, adapters).await {
+            Err(e) if !adapters.names_given() => tracing::info!("Using the system-wide DNS servers: {e}"),
+            result => return result,
+        }
+    }
+
+    let (primary, secondary) = cache::cached_system_dns(cache_dir).await?;
 
     let mut servers = Vec::with_capacity(2);
 
@@ -22,26 +58,130 @@ pub fn get_system_dns_servers(ip_version: IpVersion) -> Result<Vec<DnsServer>, P
     }
 
     // Add secondary if present and matches the IP version
-    if let Some(sec) = secondary {
-        if matches_ip_version(&sec, ip_version) && sec != primary {
-            servers.push(DnsServer::from_ip("System DNS (Secondary)", sec, ServerSource::System));
-        }
+    if let Some(sec) = secondary
+        && matches_ip_version(&sec, ip_version)
+        && sec != primary
+    {
+        servers.push(DnsServer::from_ip("System DNS (Secondary)", sec, ServerSource::System));
     }
 
     Ok(servers)
 }
 
-/// Detect gateway and return as DnsServer if it responds to DNS
-pub fn get_gateway_dns_server(ip_version: IpVersion) -> Result<Option<DnsServer>, PlatformError> {
-    match detect_gateway() {
-        Ok(ip) => {
-            if matches_ip_version(&ip, ip_version) {
-                Ok(Some(DnsServer::from_ip("Gateway (Router)", ip, ServerSource::Gateway)))
-            } else {
-                Ok(None)
+/// DNS servers of the adapters `filter` selects, named after their adapter
+async fn adapter_dns_servers(ip_version: IpVersion, filter: &AdapterFilter) -> Result<Vec<DnsServer>, PlatformError> {
+    let adapters = detect_adapter_dns().await?;
+    tracing::info!(adapters = ?adapters, "Detected DNS servers per adapter");
+
+    let selected: Vec<_> = adapters.iter().filter(|adapter| filter.allows(&adapter.name)).collect();
+    if selected.is_empty() {
+        let names: Vec<_> = adapters.iter().map(|adapter| adapter.name.as_str()).collect();
+        return Err(PlatformError::SystemDnsDetection(format!(
+            "No selected adapter has DNS servers (adapters with DNS servers: {})",
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        )));
+    }
+
+    Ok(named_adapter_servers("System DNS", selected, ip_version, ServerSource::System))
+}
+
+/// Detect the DNS servers DHCP handed out and return them as DnsServer entries
+///
+/// Servers are named after the interface of their lease, and are what the
+/// network intended even where the system's own settings were overridden
+/// (see [`detect_dhcp_dns`]).
+pub async fn get_dhcp_dns_servers(ip_version: IpVersion) -> Result<Vec<DnsServer>, PlatformError> {
+    let leases = detect_dhcp_dns().await?;
+    tracing::info!(leases = ?leases, "Detected DHCP-provided DNS servers");
+    Ok(named_adapter_servers("DHCP DNS", &leases, ip_version, ServerSource::Dhcp))
+}
+
+/// Detect the DNS servers of an active VPN and return them as DnsServer entries
+///
+/// These are the servers of adapters named after a tunnel interface (on
+/// Linux, systemd-resolved names links that way), or reached through one,
+/// like those of a NetworkManager VPN connection. There are none without a
+/// tunnel up.
+pub async fn get_vpn_dns_servers(ip_version: IpVersion) -> Result<Vec<DnsServer>, PlatformError> {
+    let routes = VpnRoutes::detect()?;
+    let tunnels = routes.tunnels();
+    if tunnels.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut vpn_adapters = Vec::new();
+    for AdapterDns { name, servers } in detect_adapter_dns().await? {
+        let servers: Vec<_> = servers
+            .into_iter()
+            .filter(|ip| tunnels.contains(&name.as_str()) || routes.egress(*ip).is_some_and(|addr| addr.tunnel))
+            .collect();
+        if !servers.is_empty() {
+            vpn_adapters.push(AdapterDns { name, servers });
+        }
+    }
+    tracing::info!(tunnels = ?tunnels, adapters = ?vpn_adapters, "Detected VPN DNS servers");
+    Ok(named_adapter_servers("VPN DNS", &vpn_adapters, ip_version, ServerSource::System))
+}
+
+/// Servers of `adapters` in order, without duplicates, named like `System DNS (Wi-Fi #2)`
+fn named_adapter_servers<'a>(
+    prefix: &str,
+    adapters: impl IntoIterator<Item = &'a AdapterDns>,
+    ip_version: IpVersion,
+    source: ServerSource,
+) -> Vec<DnsServer> {
+    let mut servers: Vec<DnsServer> = Vec::new();
+    for adapter in adapters {
+        for (i, ip) in adapter.servers.iter().enumerate() {
+            if !matches_ip_version(ip, ip_version) || servers.iter().any(|s| s.ip() == *ip) {
+                continue;
             }
+            let name = match i {
+                0 => format!("{prefix} ({})", adapter.name),
+                _ => format!("{prefix} ({} #{})", adapter.name, i + 1),
+            };
+            servers.push(DnsServer::from_ip(name, *ip, source));
+        }
+    }
+    servers
+}
+
+/// Detect the gateway and return it as a DnsServer entry
+///
+/// Having no gateway is not an error; a detection command that hung is.
+/// `cache_dir` works as for [`get_system_dns_servers`]. With `all_interfaces`,
+/// the gateway of every default route is returned instead, named after its
+/// interface, and detected afresh.
+pub async fn get_gateway_dns_servers(
+    ip_version: IpVersion,
+    cache_dir: Option<&Path>,
+    all_interfaces: bool,
+) -> Result<Vec<DnsServer>, PlatformError> {
+    if all_interfaces {
+        let routes = match detect_gateways().await {
+            Ok(routes) => routes,
+            Err(e @ PlatformError::CommandTimeout { .. }) => return Err(e),
+            Err(_) => return Ok(Vec::new()),
+        };
+        tracing::info!(routes = ?routes, "Detected default routes");
+
+        let mut servers: Vec<DnsServer> = Vec::new();
+        for route in routes {
+            if matches_ip_version(&route.ip, ip_version) && !servers.iter().any(|s| s.ip() == route.ip) {
+                let name = format!("Gateway ({})", route.interface);
+                servers.push(DnsServer::from_ip(name, route.ip, ServerSource::Gateway));
+            }
+        }
+        return Ok(servers);
+    }
+
+    match cache::cached_gateway(cache_dir).await {
+        Ok(ip) if matches_ip_version(&ip, ip_version) => {
+            Ok(vec![DnsServer::from_ip("Gateway (Router)", ip, ServerSource::Gateway)])
         }
-        Err(_) => Ok(None),
+        Ok(_) => Ok(Vec::new()),
+        Err(e @ PlatformError::CommandTimeout { .. }) => Err(e),
+        Err(_) => Ok(Vec::new()),
     }
 }
 
@@ -51,5 +191,6 @@ fn matches_ip_version(ip: &IpAddr, version: IpVersion) -> bool {
     match version {
         IpVersion::V4 => ip.is_ipv4(),
         IpVersion::V6 => ip.is_ipv6(),
+        IpVersion::Both => true,
     }
 }
@@ -7,20 +7,136 @@ use std::str::FromStr;
 /// Detect the system's configured DNS servers
 ///
 /// Returns (primary, optional_secondary)
-pub fn detect_system_dns() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+pub async fn detect_system_dns() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
     #[cfg(target_os = "linux")]
-    return linux::detect();
+    return linux::detect().await;
 
     #[cfg(target_os = "macos")]
-    return macos::detect();
+    return macos::detect().await;
 
     #[cfg(target_os = "windows")]
-    return windows::detect();
+    return windows::detect().await;
 
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     Err(PlatformError::UnsupportedPlatform)
 }
 
+/// Detect the system's DNS search domains, in the order they're tried
+///
+/// An empty list means no search domains are configured.
+pub async fn detect_search_domains() -> Result<Vec<String>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    return linux::detect_search();
+
+    #[cfg(target_os = "macos")]
+    return macos::detect_search().await;
+
+    #[cfg(target_os = "windows")]
+    return windows::detect_search().await;
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+/// DNS servers configured on one network adapter (a network service on macOS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterDns {
+    /// Adapter name, like `Wi-Fi` or `Ethernet 2`
+    pub name: String,
+    /// DNS servers in the order the adapter lists them
+    pub servers: Vec<IpAddr>,
+}
+
+/// Detect the DNS servers of each network adapter that has any
+///
+/// Windows configures DNS servers per adapter, macOS per network service, and
+/// NetworkManager or systemd-resolved on Linux per connection or link;
+/// elsewhere they're configured system-wide.
+pub async fn detect_adapter_dns() -> Result<Vec<AdapterDns>, PlatformError> {
+    #[cfg(target_os = "linux")]
+    return linux::detect_adapters().await;
+
+    #[cfg(target_os = "macos")]
+    return macos::detect_services().await;
+
+    #[cfg(target_os = "windows")]
+    return windows::detect_adapters().await;
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+/// Detect the DNS servers handed out by DHCP on each interface that has a lease
+///
+/// These are the servers the network intends its clients to use, even where
+/// resolv.conf or an adapter's settings were changed by hand. Linux reads the
+/// lease files of dhclient, NetworkManager, and systemd-networkd; macOS asks
+/// for the lease of each network service.
+pub async fn detect_dhcp_dns() -> Result<Vec<AdapterDns>, PlatformError> {
+    // Lease files are read with blocking calls
+    #[cfg(target_os = "linux")]
+    return tokio::task::spawn_blocking(linux::detect_dhcp)
+        .await
+        .map_err(|e| PlatformError::SystemDnsDetection(e.to_string()))?;
+
+    #[cfg(target_os = "macos")]
+    return macos::detect_dhcp().await;
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+/// Adapters to take system DNS servers from, built from `--adapter` and `--skip-adapter`
+///
+/// Names match case-insensitively as substrings, like server name patterns.
+#[derive(Debug, Clone, Default)]
+pub struct AdapterFilter {
+    only: Vec<String>,
+    skip: Vec<String>,
+    all: bool,
+}
+
+impl AdapterFilter {
+    /// Build a filter from adapter names
+    pub fn new(only: &[String], skip: &[String]) -> Self {
+        let lower = |names: &[String]| names.iter().map(|n| n.trim().to_lowercase()).collect();
+        Self {
+            only: lower(only),
+            skip: lower(skip),
+            all: false,
+        }
+    }
+
+    /// Take the servers of every adapter the filter allows, even with no names given
+    pub fn all_adapters(mut self) -> Self {
+        self.all = true;
+        self
+    }
+
+    /// Check if an adapter's servers should be used
+    pub fn allows(&self, adapter: &str) -> bool {
+        let adapter = adapter.to_lowercase();
+        let matches = |names: &[String]| names.iter().any(|n| adapter.contains(n.as_str()));
+        (self.only.is_empty() || matches(&self.only)) && !matches(&self.skip)
+    }
+
+    /// Check if this filter leaves system DNS to the usual detection of two servers
+    pub fn is_empty(&self) -> bool {
+        !self.all && !self.names_given()
+    }
+
+    /// Check if adapters were named to take or skip
+    pub fn names_given(&self) -> bool {
+        !self.only.is_empty() || !self.skip.is_empty()
+    }
+}
+
+/// Error for a DHCP detection that found no lease with DNS servers
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn no_dhcp_leases() -> PlatformError {
+    PlatformError::SystemDnsDetection("No DHCP lease with DNS servers found".into())
+}
+
 /// Helper to select primary and secondary from a list
 fn select_servers(servers: Vec<IpAddr>) -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
     if servers.is_empty() {
@@ -34,17 +150,382 @@ fn select_servers(servers: Vec<IpAddr>) -> Result<(IpAddr, Option<IpAddr>), Plat
 #[cfg(target_os = "linux")]
 mod linux {
     use super::*;
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
     use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     const RESOLV_CONF: &str = "/etc/resolv.conf";
 
-    pub fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+    /// Directories DHCP clients keep their leases in: dhclient's (which
+    /// depends on the distribution), NetworkManager's for both dhclient and
+    /// its internal client, and systemd-networkd's
+    const LEASE_DIRS: [&str; 4] = [
+        "/var/lib/dhcp",
+        "/var/lib/dhclient",
+        "/var/lib/NetworkManager",
+        NETWORKD_LEASE_DIR,
+    ];
+
+    /// systemd-networkd names its leases after the interface index
+    const NETWORKD_LEASE_DIR: &str = "/run/systemd/netif/leases";
+
+    /// Upstream servers systemd-resolved writes out alongside its stub
+    const RESOLVED_UPSTREAM_CONF: &str = "/run/systemd/resolve/resolv.conf";
+
+    /// Local stub listeners of systemd-resolved
+    const RESOLVED_STUBS: [IpAddr; 2] = [
+        IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 53)),
+        IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 54)),
+    ];
+
+    /// Name servers of the NetworkManager connections, or else from `/etc/resolv.conf`
+    ///
+    /// Benchmarking the systemd-resolved stub only measures its cache, so when
+    /// resolv.conf points at it, the upstream servers of each link are asked
+    /// from `resolvectl`, then from resolved's own resolv.conf.
+    pub async fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+        match detect_connections().await {
+            Ok(connections) if !connections.is_empty() => return select_servers(flatten_adapters(connections)),
+            Ok(_) => tracing::debug!("No NetworkManager connection has DNS servers"),
+            Err(e) => tracing::debug!("No NetworkManager connections: {e}"),
+        }
+
+        tracing::debug!("Reading name servers from {RESOLV_CONF}");
         let content = fs::read_to_string(RESOLV_CONF).map_err(|e| {
             PlatformError::SystemDnsDetection(format!("Failed to read {RESOLV_CONF}: {e}"))
         })?;
 
         let servers = parse_resolv_conf(&content);
-        select_servers(servers)
+        if servers.is_empty() || !servers.iter().all(|ip| RESOLVED_STUBS.contains(ip)) {
+            return select_servers(servers);
+        }
+
+        tracing::debug!("{RESOLV_CONF} points at the systemd-resolved stub, asking for its upstream servers");
+        match detect_resolved_links().await {
+            Ok(links) => return select_servers(flatten_adapters(links)),
+            Err(e) => tracing::debug!("Falling back to {RESOLVED_UPSTREAM_CONF}: {e}"),
+        }
+        match fs::read_to_string(RESOLVED_UPSTREAM_CONF) {
+            Ok(content) => select_servers(parse_resolv_conf(&content)),
+            Err(e) => {
+                tracing::warn!("Failed to read {RESOLVED_UPSTREAM_CONF} ({e}), using the systemd-resolved stub");
+                select_servers(servers)
+            }
+        }
+    }
+
+    /// DNS servers per NetworkManager connection, or else per systemd-resolved link
+    pub async fn detect_adapters() -> Result<Vec<AdapterDns>, PlatformError> {
+        match detect_connections().await {
+            Ok(connections) if !connections.is_empty() => return Ok(connections),
+            Ok(_) => tracing::debug!("No NetworkManager connection has DNS servers, asking systemd-resolved"),
+            Err(e) => tracing::debug!("Asking systemd-resolved instead of NetworkManager: {e}"),
+        }
+        detect_resolved_links().await
+    }
+
+    /// DNS servers of each active NetworkManager connection that has any, VPNs included
+    ///
+    /// Fails if NetworkManager isn't running.
+    pub async fn detect_connections() -> Result<Vec<AdapterDns>, PlatformError> {
+        let fields = "GENERAL.DEVICE,GENERAL.CONNECTION,IP4.DNS,IP6.DNS";
+        let text = run_command("nmcli", &["-t", "-f", fields, "device", "show"], COMMAND_TIMEOUT).await?;
+        Ok(parse_nmcli_device_show(&text))
+    }
+
+    /// DNS servers of each systemd-resolved link that has any
+    pub async fn detect_resolved_links() -> Result<Vec<AdapterDns>, PlatformError> {
+        let text = run_command("resolvectl", &["status", "--no-pager"], COMMAND_TIMEOUT).await?;
+        Ok(parse_resolvectl_status(&text))
+    }
+
+    /// Servers of all adapters in order, without duplicates
+    fn flatten_adapters(adapters: Vec<AdapterDns>) -> Vec<IpAddr> {
+        let mut servers: Vec<IpAddr> = Vec::new();
+        for ip in adapters.into_iter().flat_map(|adapter| adapter.servers) {
+            if !servers.contains(&ip) {
+                servers.push(ip);
+            }
+        }
+        servers
+    }
+
+    /// Per-connection servers from `nmcli -t device show`
+    ///
+    /// Each device starts with a `GENERAL.DEVICE:wlp2s0` line and is named
+    /// after its connection, like `Home Wi-Fi` or a VPN's name, if it has one.
+    /// Terse output escapes the colons of IPv6 addresses and names.
+    pub fn parse_nmcli_device_show(text: &str) -> Vec<AdapterDns> {
+        let mut connections: Vec<AdapterDns> = Vec::new();
+        for line in text.lines() {
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().replace("\\:", ":");
+            if field == "GENERAL.DEVICE" {
+                connections.push(AdapterDns {
+                    name: value,
+                    servers: Vec::new(),
+                });
+            } else if let Some(connection) = connections.last_mut() {
+                if field == "GENERAL.CONNECTION" && !value.is_empty() && value != "--" {
+                    connection.name = value;
+                } else if (field.starts_with("IP4.DNS") || field.starts_with("IP6.DNS"))
+                    && let Some(ip) = parse_server(&value)
+                    && !connection.servers.contains(&ip)
+                {
+                    connection.servers.push(ip);
+                }
+            }
+        }
+        connections.retain(|connection| !connection.servers.is_empty());
+        connections
+    }
+
+    /// Per-link servers from `resolvectl status`
+    ///
+    /// Sections start with an unindented `Global` or `Link 2 (enp0s3)` line
+    /// (keys are right-aligned, so the longest can be unindented too), and a
+    /// `DNS Servers:` list may continue on the following lines. Servers
+    /// configured globally are listed as a `Global` link. Fallback servers are
+    /// left out, as resolved only uses them when nothing else is configured.
+    pub fn parse_resolvectl_status(text: &str) -> Vec<AdapterDns> {
+        let mut links: Vec<AdapterDns> = Vec::new();
+        let mut in_servers = false;
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !line.starts_with(char::is_whitespace) && !line.contains(": ") {
+                // `Link 2 (enp0s3)` is named after its interface
+                let name = line
+                    .split_once('(')
+                    .and_then(|(_, name)| name.strip_suffix(')'))
+                    .unwrap_or(line.trim());
+                links.push(AdapterDns {
+                    name: name.to_string(),
+                    servers: Vec::new(),
+                });
+                in_servers = false;
+                continue;
+            }
+
+            let values = match line.split_once(": ") {
+                Some((key, values)) => {
+                    in_servers = key.trim() == "DNS Servers";
+                    values
+                }
+                None => line,
+            };
+            if let Some(link) = links.last_mut().filter(|_| in_servers) {
+                for ip in values.split_whitespace().filter_map(parse_server) {
+                    if !link.servers.contains(&ip) {
+                        link.servers.push(ip);
+                    }
+                }
+            }
+        }
+        links.retain(|link| !link.servers.is_empty());
+        links
+    }
+
+    /// Parse one server as resolvectl or nmcli show it, like `9.9.9.9` or `[2001:db8::53]:53`
+    ///
+    /// Servers are benchmarked as plain DNS on port 53, so ones reached on
+    /// another port, or given a TLS server name (`1.1.1.1#cloudflare-dns.com`),
+    /// are left out rather than measured somewhere resolved doesn't send
+    /// queries. Link-local IPv6 servers need their zone, so they're left out too.
+    fn parse_server(value: &str) -> Option<IpAddr> {
+        if value.contains('#') {
+            tracing::debug!("Skipping {value}, which has a TLS server name");
+            return None;
+        }
+        let value = value.split('%').next()?;
+        let ip = match IpAddr::from_str(value) {
+            Ok(ip) => ip,
+            Err(_) => {
+                let addr = std::net::SocketAddr::from_str(value).ok()?;
+                if addr.port() != 53 {
+                    tracing::debug!("Skipping {value}, which isn't on port 53");
+                    return None;
+                }
+                addr.ip()
+            }
+        };
+        match ip {
+            IpAddr::V6(v6) if (v6.segments()[0] & 0xffc0) == 0xfe80 => None,
+            _ => Some(ip),
+        }
+    }
+
+    /// DNS servers of the current DHCP lease of each interface
+    ///
+    /// Lease files linger after a network is left, so expired leases are
+    /// skipped and the newest file wins when several have a lease for the
+    /// same interface (NetworkManager keeps one per connection).
+    pub fn detect_dhcp() -> Result<Vec<AdapterDns>, PlatformError> {
+        let now = SystemTime::now();
+        let unix_now = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        let mut leases: Vec<(SystemTime, AdapterDns)> = Vec::new();
+        for dir in LEASE_DIRS {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                let (Ok(content), Ok(modified)) = (
+                    fs::read_to_string(entry.path()),
+                    entry.metadata().and_then(|m| m.modified()),
+                ) else {
+                    continue;
+                };
+
+                let found = if content.contains("lease {") {
+                    parse_dhclient_leases(&content, unix_now)
+                } else {
+                    let interface = if dir == NETWORKD_LEASE_DIR {
+                        Some(interface_by_index(&file_name))
+                    } else {
+                        nm_lease_interface(&file_name)
+                    };
+                    let Some(name) = interface else {
+                        continue;
+                    };
+                    let age = now.duration_since(modified).map_or(0, |d| d.as_secs());
+                    let servers = parse_lease_env(&content, age);
+                    vec![AdapterDns { name, servers }]
+                };
+                tracing::debug!(leases = ?found, "Read DHCP leases from {}", entry.path().display());
+
+                for lease in found {
+                    match leases.iter_mut().find(|(_, l)| l.name == lease.name) {
+                        Some((time, newest)) if *time < modified => (*time, *newest) = (modified, lease),
+                        Some(_) => {}
+                        None => leases.push((modified, lease)),
+                    }
+                }
+            }
+        }
+
+        let mut leases: Vec<_> = leases.into_iter().map(|(_, lease)| lease).collect();
+        leases.retain(|lease| !lease.servers.is_empty());
+        leases.sort_by(|a, b| a.name.cmp(&b.name));
+        if leases.is_empty() {
+            return Err(no_dhcp_leases());
+        }
+        Ok(leases)
+    }
+
+    /// Interface of a NetworkManager internal client lease, `internal-<uuid>-<iface>.lease`
+    fn nm_lease_interface(file_name: &str) -> Option<String> {
+        let name = file_name.strip_prefix("internal-")?.strip_suffix(".lease")?;
+        // Skip the connection UUID and the dash after it
+        name.get(37..).filter(|iface| !iface.is_empty()).map(str::to_string)
+    }
+
+    /// Name of the interface with index `index`, or the index itself if it's gone
+    fn interface_by_index(index: &str) -> String {
+        fs::read_dir("/sys/class/net")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .find(|entry| fs::read_to_string(entry.path().join("ifindex")).is_ok_and(|i| i.trim() == index))
+            .map_or_else(
+                || format!("interface {index}"),
+                |entry| entry.file_name().to_string_lossy().into_owned(),
+            )
+    }
+
+    /// Leases per interface from a dhclient leases file, the last one of each winning
+    ///
+    /// dhclient appends each lease it gets as a `lease { ... }` block, so the
+    /// last block of an interface is its current lease. An interface whose
+    /// current lease expired before `now` (Unix seconds) has no servers.
+    /// DHCPv6 (`lease6`) blocks are skipped.
+    pub fn parse_dhclient_leases(content: &str, now: i64) -> Vec<AdapterDns> {
+        let mut leases: Vec<AdapterDns> = Vec::new();
+        let mut in_lease = false;
+        let mut interface = None;
+        let mut servers = Vec::new();
+        let mut expired = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim().trim_end_matches(';');
+            if line == "lease {" {
+                (in_lease, interface, expired) = (true, None, false);
+                servers.clear();
+            } else if !in_lease {
+                continue;
+            } else if line == "}" {
+                in_lease = false;
+                if let Some(name) = interface.take() {
+                    leases.retain(|lease| lease.name != name);
+                    let servers = if expired { Vec::new() } else { std::mem::take(&mut servers) };
+                    leases.push(AdapterDns { name, servers });
+                }
+            } else if let Some(name) = line.strip_prefix("interface ") {
+                interface = Some(name.trim().trim_matches('"').to_string());
+            } else if let Some(list) = line.strip_prefix("option domain-name-servers ") {
+                servers = list.split(',').filter_map(|ip| IpAddr::from_str(ip.trim()).ok()).collect();
+            } else if let Some(expiry) = line.strip_prefix("expire ") {
+                expired = lease_expiry(expiry).is_some_and(|expiry| expiry <= now);
+            }
+        }
+        leases
+    }
+
+    /// Unix time of a dhclient `expire` value: `4 2024/01/04 18:30:00` (UTC) or `epoch 1704393000`
+    ///
+    /// `never` has none.
+    fn lease_expiry(value: &str) -> Option<i64> {
+        match value.split_whitespace().collect::<Vec<_>>()[..] {
+            ["epoch", seconds, ..] => seconds.parse().ok(),
+            [_weekday, date, time, ..] => {
+                let mut date = date.split('/').map(|part| part.parse::<i64>().ok());
+                let mut time = time.split(':').map(|part| part.parse::<i64>().ok());
+                let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+                let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+                Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+            }
+            _ => None,
+        }
+    }
+
+    /// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        // Count years from March, so the leap day ends the year
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Servers of a `KEY=value` lease, as NetworkManager's internal client and systemd-networkd write them
+    ///
+    /// The lease is rewritten on every renewal, so one older (`age` seconds)
+    /// than its `LIFETIME` has expired and has no servers.
+    pub fn parse_lease_env(content: &str, age: u64) -> Vec<IpAddr> {
+        let value = |key: &str| {
+            content
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+        };
+        if let Some(lifetime) = value("LIFETIME").and_then(|lifetime| lifetime.trim().parse::<u64>().ok())
+            && lifetime < age
+        {
+            return Vec::new();
+        }
+        value("DNS")
+            .map(|list| list.split_whitespace().filter_map(|ip| IpAddr::from_str(ip).ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn detect_search() -> Result<Vec<String>, PlatformError> {
+        let content = fs::read_to_string(RESOLV_CONF).map_err(|e| {
+            PlatformError::SystemDnsDetection(format!("Failed to read {RESOLV_CONF}: {e}"))
+        })?;
+        Ok(parse_resolv_conf_search(&content))
     }
 
     pub fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
@@ -57,27 +538,126 @@ mod linux {
             })
             .collect()
     }
+
+    /// Search list from the last `search` or `domain` line, as the resolver reads it
+    pub fn parse_resolv_conf_search(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                line.strip_prefix("search ").or_else(|| line.strip_prefix("domain "))
+            })
+            .next_back()
+            .map(|list| list.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(target_os = "macos")]
 mod macos {
     use super::*;
-    use std::process::Command;
-
-    pub fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
-        let output = Command::new("scutil")
-            .arg("--dns")
-            .output()
-            .map_err(|e| PlatformError::CommandFailed {
-                command: "scutil --dns".into(),
-                message: e.to_string(),
-            })?;
-
-        let text = String::from_utf8_lossy(&output.stdout);
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+
+    pub async fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+        let text = run_command("scutil", &["--dns"], COMMAND_TIMEOUT).await?;
         let servers = parse_scutil_dns(&text);
         select_servers(servers)
     }
 
+    pub async fn detect_search() -> Result<Vec<String>, PlatformError> {
+        let text = run_command("scutil", &["--dns"], COMMAND_TIMEOUT).await?;
+        Ok(parse_scutil_search(&text))
+    }
+
+    /// DNS servers of each enabled network service, in service order
+    ///
+    /// Servers set by hand on a service come from `networksetup`; a service
+    /// without any uses the servers of its DHCP lease, if it has one.
+    pub async fn detect_services() -> Result<Vec<AdapterDns>, PlatformError> {
+        let text = run_command("networksetup", &["-listnetworkserviceorder"], COMMAND_TIMEOUT).await?;
+        let mut services = Vec::new();
+        for (name, device) in parse_service_order(&text) {
+            let text = run_command("networksetup", &["-getdnsservers", name.as_str()], COMMAND_TIMEOUT).await?;
+            let mut servers = parse_networksetup_dns(&text);
+            if servers.is_empty() && !device.is_empty() {
+                servers = dhcp_servers(&device).await;
+            }
+            if !servers.is_empty() {
+                services.push(AdapterDns { name, servers });
+            }
+        }
+        Ok(services)
+    }
+
+    /// DHCP-provided DNS servers of each enabled network service, in service order
+    pub async fn detect_dhcp() -> Result<Vec<AdapterDns>, PlatformError> {
+        let text = run_command("networksetup", &["-listnetworkserviceorder"], COMMAND_TIMEOUT).await?;
+        let mut services = Vec::new();
+        for (name, device) in parse_service_order(&text) {
+            let servers = if device.is_empty() { Vec::new() } else { dhcp_servers(&device).await };
+            if !servers.is_empty() {
+                services.push(AdapterDns { name, servers });
+            }
+        }
+        if services.is_empty() {
+            return Err(no_dhcp_leases());
+        }
+        Ok(services)
+    }
+
+    /// DNS servers of the DHCP lease on `device`
+    ///
+    /// `ipconfig getoption` only gives the first server, so the whole packet is read.
+    async fn dhcp_servers(device: &str) -> Vec<IpAddr> {
+        // Fails for interfaces without a lease, like a disconnected Ethernet port
+        match run_command("ipconfig", &["getpacket", device], COMMAND_TIMEOUT).await {
+            Ok(text) => parse_dhcp_packet_dns(&text),
+            Err(e) => {
+                tracing::debug!("No DHCP lease on {device}: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Enabled services and their devices from `networksetup -listnetworkserviceorder`
+    ///
+    /// Services are listed as `(1) Wi-Fi` followed by
+    /// `(Hardware Port: Wi-Fi, Device: en0)`; disabled ones are numbered `(*)`.
+    /// VPN services may have no device.
+    pub fn parse_service_order(text: &str) -> Vec<(String, String)> {
+        let mut services = Vec::new();
+        let mut service: Option<&str> = None;
+        for line in text.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("(Hardware Port:") {
+                if let Some(name) = service.take() {
+                    let device = rest.rsplit_once("Device:").map_or("", |(_, device)| device);
+                    services.push((name.to_string(), device.trim_end_matches(')').trim().to_string()));
+                }
+            } else if let Some((number, name)) = line.strip_prefix('(').and_then(|l| l.split_once(") ")) {
+                service = number.parse::<u32>().is_ok().then_some(name.trim());
+            }
+        }
+        services
+    }
+
+    /// Servers from `networksetup -getdnsservers`, one per line
+    ///
+    /// A service without servers of its own gets a sentence instead
+    /// ("There aren't any DNS Servers set on Wi-Fi."), which parses to none.
+    pub fn parse_networksetup_dns(text: &str) -> Vec<IpAddr> {
+        text.lines().filter_map(|line| IpAddr::from_str(line.trim()).ok()).collect()
+    }
+
+    /// Servers from the `domain_name_server (ip_mult): {a, b}` line of `ipconfig getpacket`
+    pub fn parse_dhcp_packet_dns(text: &str) -> Vec<IpAddr> {
+        text.lines()
+            .find_map(|line| line.trim().strip_prefix("domain_name_server"))
+            .and_then(|line| line.split_once('{'))
+            .and_then(|(_, list)| list.split_once('}'))
+            .map(|(list, _)| list.split(',').filter_map(|ip| IpAddr::from_str(ip.trim()).ok()).collect())
+            .unwrap_or_default()
+    }
+
     pub fn parse_scutil_dns(text: &str) -> Vec<IpAddr> {
         text.lines()
             .filter_map(|line| {
@@ -92,55 +672,283 @@ mod macos {
             })
             .collect()
     }
+
+    /// `search domain[N] : <domain>` entries of all resolvers, without duplicates
+    pub fn parse_scutil_search(text: &str) -> Vec<String> {
+        let mut domains: Vec<String> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with("search domain[")
+                && let Some((_, domain)) = line.split_once(':')
+            {
+                let domain = domain.trim().to_string();
+                if !domain.is_empty() && !domains.contains(&domain) {
+                    domains.push(domain);
+                }
+            }
+        }
+        domains
+    }
 }
 
 #[cfg(target_os = "windows")]
 mod windows {
     use super::*;
-    use std::process::Command;
-
-    pub fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
-        let output = Command::new("ipconfig")
-            .arg("/all")
-            .output()
-            .map_err(|e| PlatformError::CommandFailed {
-                command: "ipconfig /all".into(),
-                message: e.to_string(),
-            })?;
-
-        let text = String::from_utf8_lossy(&output.stdout);
-        let servers = parse_ipconfig(&text);
+    use crate::platform::command::{run_command, COMMAND_TIMEOUT};
+
+    pub async fn detect() -> Result<(IpAddr, Option<IpAddr>), PlatformError> {
+        let mut servers: Vec<IpAddr> = Vec::new();
+        for ip in detect_adapters().await?.into_iter().flat_map(|adapter| adapter.servers) {
+            if !servers.contains(&ip) {
+                servers.push(ip);
+            }
+        }
         select_servers(servers)
     }
 
-    pub fn parse_ipconfig(text: &str) -> Vec<IpAddr> {
-        let mut servers = Vec::new();
-        let mut in_dns_section = false;
+    /// DNS servers per adapter from the IP Helper API, or `ipconfig` if that fails
+    pub async fn detect_adapters() -> Result<Vec<AdapterDns>, PlatformError> {
+        match api_adapters() {
+            Ok(adapters) => return Ok(adapters),
+            Err(e) => tracing::debug!("Falling back to ipconfig: {e}"),
+        }
+        let text = run_command("ipconfig", &["/all"], COMMAND_TIMEOUT).await?;
+        Ok(parse_ipconfig(&text))
+    }
+
+    /// DNS servers of each connected adapter, from `GetAdaptersAddresses`
+    ///
+    /// Unlike `ipconfig` output, this doesn't depend on the display language.
+    fn api_adapters() -> Result<Vec<AdapterDns>, PlatformError> {
+        use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+        use windows_sys::Win32::NetworkManagement::IpHelper::{
+            GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_MULTICAST, GAA_FLAG_SKIP_UNICAST,
+            IP_ADAPTER_ADDRESSES_LH,
+        };
+        use windows_sys::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+        use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
+
+        let flags = GAA_FLAG_SKIP_UNICAST | GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST;
+        // Start with the documented 15 KB and grow to the size asked for, as
+        // adapters can come and go between calls
+        let mut size: u32 = 15 * 1024;
+        let mut buffer: Vec<u64> = Vec::new();
+        for _ in 0..3 {
+            // u64 elements keep the structures 8-byte aligned
+            buffer = vec![0; (size as usize).div_ceil(8)];
+            // SAFETY: the buffer holds at least `size` writable bytes
+            let status = unsafe {
+                GetAdaptersAddresses(
+                    u32::from(AF_UNSPEC),
+                    flags,
+                    std::ptr::null(),
+                    buffer.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>(),
+                    &mut size,
+                )
+            };
+            match status {
+                ERROR_SUCCESS => break,
+                ERROR_BUFFER_OVERFLOW => buffer.clear(),
+                error => {
+                    return Err(PlatformError::SystemDnsDetection(format!(
+                        "GetAdaptersAddresses failed with error {error}"
+                    )));
+                }
+            }
+        }
+        if buffer.is_empty() {
+            return Err(PlatformError::SystemDnsDetection(
+                "GetAdaptersAddresses kept asking for a larger buffer".into(),
+            ));
+        }
+
+        let mut adapters = Vec::new();
+        let mut next = buffer.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
+        // SAFETY: on success the buffer starts with a linked list of adapters,
+        // all pointing into the buffer, which outlives the loop
+        while let Some(adapter) = unsafe { next.as_ref() } {
+            next = adapter.Next.cast_const();
+            if adapter.OperStatus != IfOperStatusUp {
+                continue;
+            }
+
+            let mut servers = Vec::new();
+            let mut server = adapter.FirstDnsServerAddress.cast_const();
+            // SAFETY: as above, and each address matches its family
+            while let Some(entry) = unsafe { server.as_ref() } {
+                server = entry.Next.cast_const();
+                if let Some(ip) = unsafe { socket_ip(entry.Address.lpSockaddr) }.and_then(usable_dns_server)
+                    && !servers.contains(&ip)
+                {
+                    servers.push(ip);
+                }
+            }
+            if !servers.is_empty() {
+                adapters.push(AdapterDns {
+                    // SAFETY: friendly names are NUL-terminated UTF-16
+                    name: unsafe { wide_string(adapter.FriendlyName) },
+                    servers,
+                });
+            }
+        }
+        Ok(adapters)
+    }
+
+    /// IP address of a socket address returned by the API
+    ///
+    /// # Safety
+    ///
+    /// `addr` must be null or point to a socket address of the family it states.
+    unsafe fn socket_ip(addr: *const windows_sys::Win32::Networking::WinSock::SOCKADDR) -> Option<IpAddr> {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+        use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6, SOCKADDR_IN, SOCKADDR_IN6};
+
+        // SAFETY: guaranteed by the caller
+        unsafe {
+            match addr.as_ref()?.sa_family {
+                AF_INET => {
+                    let addr = &*addr.cast::<SOCKADDR_IN>();
+                    // Network byte order in memory
+                    Some(Ipv4Addr::from(addr.sin_addr.S_un.S_addr.to_ne_bytes()).into())
+                }
+                AF_INET6 => {
+                    let addr = &*addr.cast::<SOCKADDR_IN6>();
+                    Some(Ipv6Addr::from(addr.sin6_addr.u.Byte).into())
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Copy a NUL-terminated UTF-16 string
+    ///
+    /// # Safety
+    ///
+    /// `s` must be null or point to a NUL-terminated UTF-16 string.
+    unsafe fn wide_string(s: *const u16) -> String {
+        if s.is_null() {
+            return String::new();
+        }
+        // SAFETY: guaranteed by the caller
+        unsafe {
+            let len = (0..).take_while(|&i| *s.add(i) != 0).count();
+            String::from_utf16_lossy(std::slice::from_raw_parts(s, len))
+        }
+    }
+
+    pub async fn detect_search() -> Result<Vec<String>, PlatformError> {
+        let text = run_command("ipconfig", &["/all"], COMMAND_TIMEOUT).await?;
+        Ok(parse_ipconfig_search(&text))
+    }
+
+    /// DNS servers of each adapter in `ipconfig /all` output
+    ///
+    /// Adapters without any are left out. Servers may continue on the lines
+    /// after `DNS Servers`, and IPv6 ones may carry a zone (`fe80::1%12`).
+    pub fn parse_ipconfig(text: &str) -> Vec<AdapterDns> {
+        let mut adapters: Vec<AdapterDns> = Vec::new();
+        let mut in_dns_list = false;
 
         for line in text.lines() {
             let trimmed = line.trim();
+            if trimmed.is_empty() {
+                in_dns_list = false;
+                continue;
+            }
 
-            if trimmed.contains("DNS") && trimmed.contains(':') {
-                // Start of DNS section - extract IP from this line
-                if let Some(ip_str) = trimmed.split(':').nth(1) {
-                    if let Ok(ip) = IpAddr::from_str(ip_str.trim()) {
-                        servers.push(ip);
-                        in_dns_section = true;
-                    }
+            // Section headers start in the first column: `Wireless LAN adapter Wi-Fi:`
+            if !line.starts_with(char::is_whitespace) {
+                in_dns_list = false;
+                if let Some(header) = trimmed.strip_suffix(':') {
+                    adapters.push(AdapterDns {
+                        name: adapter_name(header.trim()).to_string(),
+                        servers: Vec::new(),
+                    });
                 }
-            } else if in_dns_section && !trimmed.is_empty() {
-                // Continuation lines might have more DNS servers
-                if let Ok(ip) = IpAddr::from_str(trimmed) {
-                    servers.push(ip);
-                } else {
-                    in_dns_section = false;
+                continue;
+            }
+
+            let value = match trimmed.split_once(" :") {
+                Some((key, value)) => {
+                    in_dns_list = is_dns_servers_key(key);
+                    value.trim()
                 }
-            } else if trimmed.is_empty() {
-                in_dns_section = false;
+                None => trimmed,
+            };
+            if !in_dns_list {
+                continue;
+            }
+            if let (Some(adapter), Some(ip)) = (adapters.last_mut(), parse_dns_server(value))
+                && !adapter.servers.contains(&ip)
+            {
+                adapter.servers.push(ip);
             }
         }
 
-        servers
+        adapters.retain(|adapter| !adapter.servers.is_empty());
+        adapters
+    }
+
+    /// Adapter name of a section header, like `Wi-Fi` in `Wireless LAN adapter Wi-Fi`
+    ///
+    /// Headers that don't follow the English pattern (or a German
+    /// `Ethernet-Adapter Ethernet`) are used whole.
+    fn adapter_name(header: &str) -> &str {
+        match header.to_ascii_lowercase().find("adapter ") {
+            Some(start) => header[start + "adapter ".len()..].trim(),
+            None => header,
+        }
+    }
+
+    /// Check for the `DNS Servers` key (`DNS-Server`, `Serveurs DNS` in other languages)
+    fn is_dns_servers_key(key: &str) -> bool {
+        let key = key.to_lowercase();
+        key.contains("dns") && key.contains("serv")
+    }
+
+    /// Parse one listed server, dropping its zone
+    fn parse_dns_server(value: &str) -> Option<IpAddr> {
+        usable_dns_server(IpAddr::from_str(value.split('%').next()?.trim()).ok()?)
+    }
+
+    /// Filter out servers that can't be benchmarked
+    ///
+    /// Link-local IPv6 servers can't be queried without their zone, and the
+    /// `fec0:0:0:ffff::1-3` placeholders Windows lists when IPv6 has no DNS
+    /// configured don't exist.
+    fn usable_dns_server(ip: IpAddr) -> Option<IpAddr> {
+        match ip {
+            IpAddr::V6(v6) if (v6.segments()[0] & 0xffc0) == 0xfe80 => None,
+            IpAddr::V6(v6) if v6.segments()[..4] == [0xfec0, 0, 0, 0xffff] => None,
+            _ => Some(ip),
+        }
+    }
+
+    /// Entries of `DNS Suffix Search List` lines and their continuation lines, without duplicates
+    pub fn parse_ipconfig_search(text: &str) -> Vec<String> {
+        let mut domains: Vec<String> = Vec::new();
+        let mut in_list = false;
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            let entry = if trimmed.starts_with("DNS Suffix Search List") {
+                in_list = true;
+                trimmed.split_once(':').map(|(_, domain)| domain.trim())
+            } else if in_list && !trimmed.is_empty() && !trimmed.contains(':') {
+                Some(trimmed)
+            } else {
+                in_list = false;
+                None
+            };
+
+            if let Some(domain) = entry.filter(|d| !d.is_empty())
+                && !domains.iter().any(|d| d == domain)
+            {
+                domains.push(domain.to_string());
+            }
+        }
+
+        domains
     }
 }
 
@@ -158,6 +966,83 @@ mod tests {
         assert_eq!(servers[1].to_string(), "1.1.1.1");
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_resolv_conf_search() {
+        let content = crate::load_test_fixture!("/system/linux_resolv.conf");
+        assert_eq!(linux::parse_resolv_conf_search(content), ["localdomain"]);
+
+        // The last search or domain line wins
+        let content = "domain corp.example\nsearch eng.corp.example corp.example\nnameserver 10.0.0.1\n";
+        assert_eq!(linux::parse_resolv_conf_search(content), ["eng.corp.example", "corp.example"]);
+        assert!(linux::parse_resolv_conf_search("nameserver 10.0.0.1\n").is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_resolvectl_status() {
+        let content = crate::load_test_fixture!("/system/linux_resolvectl_status.txt");
+        let links = linux::parse_resolvectl_status(content);
+        // The link without servers is left out; fallback servers aren't used
+        let names: Vec<_> = links.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Global", "wlp2s0", "tun0"]);
+        let servers = |i: usize| links[i].servers.iter().map(ToString::to_string).collect::<Vec<_>>();
+        // No server with a TLS server name or a port other than 53
+        assert_eq!(servers(0), ["9.9.9.9"]);
+        // Continuation lines, and no link-local server
+        assert_eq!(servers(1), ["192.168.1.1", "9.9.9.9", "2001:db8::53"]);
+        assert_eq!(servers(2), ["10.8.0.1"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_nmcli_device_show() {
+        let content = crate::load_test_fixture!("/system/linux_nmcli_device_show.txt");
+        let connections = linux::parse_nmcli_device_show(content);
+        // Disconnected devices and loopback have no servers
+        let names: Vec<_> = connections.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Home: 5 GHz", "Corp VPN"]);
+        let servers = |i: usize| connections[i].servers.iter().map(ToString::to_string).collect::<Vec<_>>();
+        // Escaped IPv6 colons; the link-local server is left out
+        assert_eq!(servers(0), ["192.168.1.1", "9.9.9.9", "2001:db8::53"]);
+        assert_eq!(servers(1), ["10.8.0.1", "10.8.0.2"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_dhclient_leases() {
+        let content = crate::load_test_fixture!("/system/linux_dhclient.leases");
+        let servers = |leases: &[AdapterDns], name: &str| {
+            let lease = leases.iter().find(|lease| lease.name == name).unwrap();
+            lease.servers.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        // 2024-01-03 12:00 UTC: the last lease of eth0 wins, DHCPv6 is skipped
+        let leases = linux::parse_dhclient_leases(content, 1_704_283_200);
+        assert_eq!(leases.len(), 2);
+        assert_eq!(servers(&leases, "eth0"), ["192.168.1.1", "1.1.1.1"]);
+        assert_eq!(servers(&leases, "wlan0"), ["10.20.0.2", "10.20.0.3"]);
+
+        // 2024-01-04 00:00 UTC: the wlan0 lease expired at 21:00
+        let leases = linux::parse_dhclient_leases(content, 1_704_326_400);
+        assert!(servers(&leases, "wlan0").is_empty());
+        // 2024-01-04 21:30 UTC (given as epoch): so did eth0's
+        let leases = linux::parse_dhclient_leases(content, 1_704_403_800);
+        assert!(servers(&leases, "eth0").is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_lease_env() {
+        let content = "# This is private data. Do not parse.\nADDRESS=192.168.1.23\n\
+                       DNS=192.168.1.1 2001:db8::53\nLIFETIME=86400\n";
+        let servers: Vec<_> = linux::parse_lease_env(content, 600).iter().map(ToString::to_string).collect();
+        assert_eq!(servers, ["192.168.1.1", "2001:db8::53"]);
+        // Not renewed in over a day
+        assert!(linux::parse_lease_env(content, 90_000).is_empty());
+        assert!(linux::parse_lease_env("ADDRESS=192.168.1.23\n", 0).is_empty());
+    }
+
     #[test]
     #[cfg(target_os = "macos")]
     fn test_parse_scutil_dns() {
@@ -169,14 +1054,82 @@ mod tests {
         assert_eq!(servers[2].to_string(), "192.168.1.1");
     }
 
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_parse_scutil_search() {
+        let content = crate::load_test_fixture!("/system/mac_scutil_dns.txt");
+        assert_eq!(macos::parse_scutil_search(content), ["local"]);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_parse_networksetup_services() {
+        let content = crate::load_test_fixture!("/system/mac_networksetup_services.txt");
+        // The disabled Bluetooth PAN service is left out; the VPN has no device
+        let services = macos::parse_service_order(content);
+        let expected = [("Wi-Fi", "en0"), ("USB 10/100/1000 LAN", "en7"), ("Corp VPN", "")];
+        assert_eq!(services.len(), expected.len());
+        for ((name, device), (expected_name, expected_device)) in services.iter().zip(expected) {
+            assert_eq!((name.as_str(), device.as_str()), (expected_name, expected_device));
+        }
+
+        let servers = macos::parse_networksetup_dns("1.1.1.1\n2606:4700:4700::1111\n");
+        assert_eq!(servers, ["1.1.1.1".parse::<IpAddr>().unwrap(), "2606:4700:4700::1111".parse().unwrap()]);
+        assert!(macos::parse_networksetup_dns("There aren't any DNS Servers set on Wi-Fi.\n").is_empty());
+
+        let packet = "op = BOOTREPLY\nrouter (ip_mult): {192.168.1.1}\n\
+                      domain_name_server (ip_mult): {192.168.1.1, 9.9.9.9}\nend (none):\n";
+        let servers = macos::parse_dhcp_packet_dns(packet);
+        assert_eq!(servers, ["192.168.1.1".parse::<IpAddr>().unwrap(), "9.9.9.9".parse().unwrap()]);
+        assert!(macos::parse_dhcp_packet_dns("op = BOOTREPLY\n").is_empty());
+    }
+
     #[test]
     #[cfg(target_os = "windows")]
     fn test_parse_ipconfig() {
         let content = crate::load_test_fixture!("/system/windows_ipconfig_all.txt");
-        let servers = windows::parse_ipconfig(content);
-        assert_eq!(servers.len(), 2);
-        assert_eq!(servers[0].to_string(), "8.8.8.8");
-        assert_eq!(servers[1].to_string(), "1.1.1.1");
+        let adapters = windows::parse_ipconfig(content);
+        assert_eq!(adapters.len(), 1);
+        assert_eq!(adapters[0].name, "Ethernet");
+        assert_eq!(adapters[0].servers.len(), 2);
+        assert_eq!(adapters[0].servers[0].to_string(), "8.8.8.8");
+        assert_eq!(adapters[0].servers[1].to_string(), "1.1.1.1");
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_parse_ipconfig_adapters() {
+        let content = crate::load_test_fixture!("/system/windows_ipconfig_adapters.txt");
+        let adapters = windows::parse_ipconfig(content);
+        // The disconnected adapter and the one with placeholder servers have none
+        let names: Vec<_> = adapters.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Wi-Fi", "VPN"]);
+        // IPv6 and continuation lines; the link-local server is dropped
+        let servers: Vec<_> = adapters[0].servers.iter().map(ToString::to_string).collect();
+        assert_eq!(servers, ["2001:db8:1::1", "192.168.1.1", "9.9.9.9"]);
+        assert_eq!(adapters[1].servers, ["10.8.0.1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_parse_ipconfig_search() {
+        let content = crate::load_test_fixture!("/system/windows_ipconfig_all.txt");
+        assert_eq!(windows::parse_ipconfig_search(content), ["corp.example.com", "example.com"]);
+    }
+
+    #[test]
+    fn test_adapter_filter() {
+        let filter = AdapterFilter::new(&["wi-fi".to_string(), "Ethernet".to_string()], &["WSL".to_string()]);
+        assert!(filter.allows("Wi-Fi"));
+        assert!(filter.allows("Ethernet 2"));
+        assert!(!filter.allows("vEthernet (WSL)"));
+        assert!(!filter.allows("VPN"));
+
+        let skip_only = AdapterFilter::new(&[], &["vpn".to_string()]);
+        assert!(skip_only.allows("Wi-Fi") && !skip_only.allows("VPN"));
+        assert!(AdapterFilter::default().is_empty() && !skip_only.is_empty());
+        let all = AdapterFilter::default().all_adapters();
+        assert!(all.allows("VPN") && !all.is_empty() && !all.names_given());
     }
 
     #[test]
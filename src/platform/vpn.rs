@@ -0,0 +1,190 @@
+//! VPN tunnel detection and the interface each server is reached through.
+
+use crate::benchmark::{VpnRoute, VpnRouteResult};
+use crate::error::PlatformError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+
+/// Address of a network interface that is up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceAddr {
+    /// Interface name, like `wlan0` or `utun4`
+    pub interface: String,
+    pub ip: IpAddr,
+    /// Point-to-point (tun, WireGuard, PPP) or tap interface, as VPNs create
+    pub tunnel: bool,
+    pub loopback: bool,
+}
+
+/// Addresses of the interfaces that are up, to tell which one traffic to a server leaves through
+///
+/// Where traffic goes is read from the source address the system picks for
+/// it, which follows the routing table (and, on Linux, the policy rules
+/// WireGuard sets up) without sending anything.
+#[derive(Debug, Clone, Default)]
+pub struct VpnRoutes {
+    addresses: Vec<InterfaceAddr>,
+}
+
+impl VpnRoutes {
+    /// Read the addresses of the interfaces that are up
+    pub fn detect() -> Result<Self, PlatformError> {
+        let addresses = interface_addresses()?;
+        tracing::debug!(addresses = ?addresses, "Detected interface addresses");
+        Ok(Self::new(addresses))
+    }
+
+    pub fn new(addresses: Vec<InterfaceAddr>) -> Self {
+        Self { addresses }
+    }
+
+    /// Tunnels that are up with a routable address
+    ///
+    /// Tunnels with only link-local addresses, like the `utun` interfaces
+    /// macOS keeps for its own services, aren't VPNs.
+    pub fn tunnels(&self) -> Vec<&str> {
+        let mut tunnels: Vec<&str> = Vec::new();
+        for addr in self.addresses.iter().filter(|addr| addr.tunnel && !is_link_local(addr.ip)) {
+            if !tunnels.contains(&addr.interface.as_str()) {
+                tunnels.push(&addr.interface);
+            }
+        }
+        tunnels
+    }
+
+    /// Interface that traffic to `ip` leaves through, if there's a route to it
+    pub fn egress(&self, ip: IpAddr) -> Option<&InterfaceAddr> {
+        let source = source_address(ip)?;
+        self.interface_of(source)
+    }
+
+    /// Interface that owns a source address
+    pub fn interface_of(&self, source: IpAddr) -> Option<&InterfaceAddr> {
+        self.addresses.iter().find(|addr| addr.ip == source)
+    }
+
+    /// Whether queries to `ip` go through a tunnel, stay on this host, or bypass the tunnel
+    pub fn route(&self, ip: IpAddr) -> Option<VpnRouteResult> {
+        self.egress(ip).map(route_result)
+    }
+}
+
+/// Judge the interface traffic to a server leaves through
+pub fn route_result(addr: &InterfaceAddr) -> VpnRouteResult {
+    let verdict = if addr.tunnel {
+        VpnRoute::Tunnel
+    } else if addr.loopback {
+        VpnRoute::Local
+    } else {
+        VpnRoute::Bypass
+    };
+    VpnRouteResult {
+        interface: addr.interface.clone(),
+        verdict,
+    }
+}
+
+/// Source address the system would send DNS queries to `ip` from
+///
+/// Connecting a UDP socket only looks the route up; nothing is sent.
+fn source_address(ip: IpAddr) -> Option<IpAddr> {
+    let unspecified = match ip {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0)).ok()?;
+    socket.connect(SocketAddr::new(ip, 53)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn is_link_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) == 0xfe80,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn interface_addresses() -> Result<Vec<InterfaceAddr>, PlatformError> {
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs allocates the list, which is freed below
+    if unsafe { libc::getifaddrs(&mut list) } != 0 {
+        return Err(PlatformError::InterfaceDetection(std::io::Error::last_os_error().to_string()));
+    }
+
+    let mut addresses = Vec::new();
+    let mut entry = list;
+    while !entry.is_null() {
+        // SAFETY: entries of the list stay valid until it's freed
+        let ifa = unsafe { &*entry };
+        entry = ifa.ifa_next;
+
+        let flags = ifa.ifa_flags as libc::c_int;
+        if ifa.ifa_addr.is_null() || flags & libc::IFF_UP == 0 {
+            continue;
+        }
+        // SAFETY: the address is non-null and starts with its family
+        let ip = match i32::from(unsafe { (*ifa.ifa_addr).sa_family }) {
+            libc::AF_INET => {
+                // SAFETY: addresses of this family are `sockaddr_in`
+                let sockaddr = unsafe { &*ifa.ifa_addr.cast::<libc::sockaddr_in>() };
+                IpAddr::V4(Ipv4Addr::from(u32::from_be(sockaddr.sin_addr.s_addr)))
+            }
+            libc::AF_INET6 => {
+                // SAFETY: addresses of this family are `sockaddr_in6`
+                let sockaddr = unsafe { &*ifa.ifa_addr.cast::<libc::sockaddr_in6>() };
+                IpAddr::V6(Ipv6Addr::from(sockaddr.sin6_addr.s6_addr))
+            }
+            _ => continue,
+        };
+        // SAFETY: interface names are NUL-terminated
+        let interface = unsafe { std::ffi::CStr::from_ptr(ifa.ifa_name) }.to_string_lossy().into_owned();
+        addresses.push(InterfaceAddr {
+            tunnel: flags & libc::IFF_POINTOPOINT != 0 || interface.starts_with("tap"),
+            loopback: flags & libc::IFF_LOOPBACK != 0,
+            interface,
+            ip,
+        });
+    }
+
+    // SAFETY: the list came from getifaddrs and isn't used after this
+    unsafe { libc::freeifaddrs(list) };
+    Ok(addresses)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn interface_addresses() -> Result<Vec<InterfaceAddr>, PlatformError> {
+    Err(PlatformError::UnsupportedPlatform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vpn_routes() {
+        let addr = |interface: &str, ip: &str, tunnel, loopback| InterfaceAddr {
+            interface: interface.to_string(),
+            ip: ip.parse().unwrap(),
+            tunnel,
+            loopback,
+        };
+        let routes = VpnRoutes::new(vec![
+            addr("lo0", "127.0.0.1", false, true),
+            addr("en0", "192.168.1.23", false, false),
+            // Kept by macOS for its own services
+            addr("utun0", "fe80::1", true, false),
+            addr("utun4", "10.8.0.6", true, false),
+            addr("utun4", "fd00:8::6", true, false),
+        ]);
+        assert_eq!(routes.tunnels(), ["utun4"]);
+
+        let verdict = |source: &str| {
+            let result = route_result(routes.interface_of(source.parse().unwrap()).unwrap());
+            (result.interface, result.verdict)
+        };
+        assert_eq!(verdict("10.8.0.6"), ("utun4".to_string(), VpnRoute::Tunnel));
+        assert_eq!(verdict("192.168.1.23"), ("en0".to_string(), VpnRoute::Bypass));
+        assert_eq!(verdict("127.0.0.1"), ("lo0".to_string(), VpnRoute::Local));
+        assert!(routes.interface_of("10.0.0.1".parse().unwrap()).is_none());
+    }
+}
@@ -0,0 +1,311 @@
+//! Built-in probes.
+
+use super::{Probe, ProbeFuture, ProbeResult};
+use crate::benchmark::{create_resolver, BenchResolver};
+use crate::config::Config;
+use crate::dns::DnsServer;
+use crate::error::Error;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::proto::ProtoErrorKind;
+use hickory_resolver::ResolveError;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Instant;
+
+/// Domain with deliberately broken DNSSEC signatures
+const DNSSEC_BOGUS_DOMAIN: &str = "dnssec-failed.org.";
+
+/// Correctly signed domain used as a control query
+const DNSSEC_SIGNED_DOMAIN: &str = "isc.org.";
+
+/// Resolver for the server's protocol; fails for DoT and DoH, which it can't query
+fn server_resolver(server: &DnsServer, config: &Config) -> Result<BenchResolver, Error> {
+    Ok(create_resolver(
+        server.addr,
+        server.protocol.unwrap_or(config.protocol).try_into()?,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    ))
+}
+
+/// Checks whether a resolver validates DNSSEC
+///
+/// Validating resolvers answer SERVFAIL for a domain with broken signatures
+/// while still resolving a correctly signed domain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DnssecProbe;
+
+impl Probe for DnssecProbe {
+    fn name(&self) -> &'static str {
+        "dnssec"
+    }
+
+    fn description(&self) -> &'static str {
+        "Validates DNSSEC (rejects a domain with broken signatures)"
+    }
+
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let resolver = match server_resolver(server, config) {
+                Ok(resolver) => resolver,
+                Err(e) => return ProbeResult::inconclusive(e.to_string()),
+            };
+
+            if let Err(e) = resolver.lookup_ip(DNSSEC_SIGNED_DOMAIN).await {
+                return ProbeResult::inconclusive(format!("control query failed: {e}"));
+            }
+
+            match resolver.lookup_ip(DNSSEC_BOGUS_DOMAIN).await {
+                Ok(_) => ProbeResult::fail("resolves domains with invalid signatures"),
+                Err(e) if response_code(&e) == Some(ResponseCode::ServFail) => {
+                    ProbeResult::pass("rejects invalid signatures")
+                }
+                Err(e) => ProbeResult::inconclusive(e.to_string()),
+            }
+        })
+    }
+}
+
+/// Well-known name that only has A records (RFC 7050)
+const IPV4ONLY_DOMAIN: &str = "ipv4only.arpa.";
+
+/// Addresses `ipv4only.arpa` resolves to
+const IPV4ONLY_ADDRS: [Ipv4Addr; 2] = [Ipv4Addr::new(192, 0, 0, 170), Ipv4Addr::new(192, 0, 0, 171)];
+
+/// Prefix lengths allowed for NAT64 address synthesis (RFC 6052), most common first
+const NAT64_PREFIX_LENGTHS: [u8; 6] = [96, 64, 56, 48, 40, 32];
+
+/// Checks whether a resolver synthesizes AAAA records (DNS64)
+///
+/// A DNS64 resolver answers AAAA queries for IPv4-only names with addresses
+/// inside its NAT64 prefix, so IPv6-only clients can reach IPv4 hosts.
+/// `ipv4only.arpa` has no AAAA records of its own, so any AAAA answer is
+/// synthesized, and the known IPv4 address embedded in it reveals the prefix.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dns64Probe;
+
+impl Probe for Dns64Probe {
+    fn name(&self) -> &'static str {
+        "dns64"
+    }
+
+    fn description(&self) -> &'static str {
+        "Synthesizes AAAA records for IPv4-only names (DNS64) and reports the NAT64 prefix"
+    }
+
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let resolver = match server_resolver(server, config) {
+                Ok(resolver) => resolver,
+                Err(e) => return ProbeResult::inconclusive(e.to_string()),
+            };
+
+            match resolver.ipv6_lookup(IPV4ONLY_DOMAIN).await {
+                Ok(lookup) => {
+                    let mut prefixes: Vec<_> = lookup
+                        .iter()
+                        .filter_map(|aaaa| nat64_prefix(aaaa.0))
+                        .map(|(prefix, len)| format!("{prefix}/{len}"))
+                        .collect();
+                    prefixes.dedup();
+                    if prefixes.is_empty() {
+                        ProbeResult::pass("synthesizes AAAA records (unknown NAT64 prefix)")
+                    } else {
+                        ProbeResult::pass(format!("NAT64 prefix {}", prefixes.join(", ")))
+                    }
+                }
+                Err(e) if matches!(response_code(&e), Some(ResponseCode::NoError)) => {
+                    ProbeResult::fail("no AAAA synthesis")
+                }
+                Err(e) => ProbeResult::inconclusive(e.to_string()),
+            }
+        })
+    }
+}
+
+/// Test domains for each filtering category, resolvable when unfiltered
+const FILTERING_TEST_DOMAINS: &[(&str, &str)] = &[
+    ("malware", "malware.testcategory.com."),
+    ("adult", "nudity.testcategory.com."),
+    ("ads", "pagead2.googlesyndication.com."),
+];
+
+/// Classifies a resolver's content filtering policy
+///
+/// Each category has a test domain that resolves normally on an unfiltered
+/// resolver. A category counts as blocked when its domain comes back as
+/// NXDOMAIN, REFUSED, or only sinkhole addresses (`0.0.0.0`, loopback). The
+/// configured domain is queried first so an unreachable server isn't mistaken
+/// for one that blocks everything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FilteringProbe;
+
+impl Probe for FilteringProbe {
+    fn name(&self) -> &'static str {
+        "filtering"
+    }
+
+    fn description(&self) -> &'static str {
+        "Filters or blocks domains (malware, adult, and ad test domains)"
+    }
+
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let resolver = match server_resolver(server, config) {
+                Ok(resolver) => resolver,
+                Err(e) => return ProbeResult::inconclusive(e.to_string()),
+            };
+
+            if let Err(e) = resolver.lookup_ip(config.domain.as_str()).await {
+                return ProbeResult::inconclusive(format!("control query failed: {e}"));
+            }
+
+            let mut blocked = Vec::new();
+            let mut unknown = Vec::new();
+            for &(category, domain) in FILTERING_TEST_DOMAINS {
+                match resolver.lookup_ip(domain).await {
+                    Ok(lookup) if lookup.iter().all(|ip| is_sinkhole(&ip)) => blocked.push(category),
+                    Ok(_) => {}
+                    Err(e) if matches!(response_code(&e), Some(ResponseCode::NXDomain | ResponseCode::Refused)) => {
+                        blocked.push(category)
+                    }
+                    Err(_) => unknown.push(category),
+                }
+            }
+
+            if !blocked.is_empty() {
+                ProbeResult::fail(format!("blocks {}", blocked.join(", ")))
+            } else if !unknown.is_empty() {
+                ProbeResult::inconclusive(format!("no answer for {}", unknown.join(", ")))
+            } else {
+                ProbeResult::pass("no filtering")
+            }
+        })
+    }
+}
+
+/// Checks whether a resolver rewrites NXDOMAIN answers
+///
+/// Some ISP resolvers answer queries for nonexistent domains with the address
+/// of an ad or search page. A random name that can't exist must come back as
+/// NXDOMAIN.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HijackProbe;
+
+impl Probe for HijackProbe {
+    fn name(&self) -> &'static str {
+        "hijack"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rewrites NXDOMAIN answers (returns an address for a nonexistent domain)"
+    }
+
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let resolver = match server_resolver(server, config) {
+                Ok(resolver) => resolver,
+                Err(e) => return ProbeResult::inconclusive(e.to_string()),
+            };
+
+            let name = format!("dnsbench-{:016x}.com.", RandomState::new().build_hasher().finish());
+            match resolver.lookup_ip(name.as_str()).await {
+                Ok(lookup) => match lookup.iter().next() {
+                    Some(ip) => ProbeResult::fail(format!("answered {ip} for a nonexistent domain")),
+                    None => ProbeResult::fail("answered a nonexistent domain"),
+                },
+                Err(e) if response_code(&e) == Some(ResponseCode::NXDomain) => ProbeResult::pass("NXDOMAIN preserved"),
+                Err(e) => ProbeResult::inconclusive(e.to_string()),
+            }
+        })
+    }
+}
+
+/// Checks whether a resolver answers over TCP
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpProbe;
+
+impl Probe for TcpProbe {
+    fn name(&self) -> &'static str {
+        "tcp"
+    }
+
+    fn description(&self) -> &'static str {
+        "Answers queries over TCP"
+    }
+
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a> {
+        Box::pin(async move {
+            let resolver = create_resolver(
+                server.addr,
+                hickory_resolver::proto::xfer::Protocol::Tcp,
+                config.timeout_ms(),
+                config.lookup_ip.into(),
+                &config.socket,
+            );
+
+            let start = Instant::now();
+            match resolver.lookup_ip(config.domain.as_str()).await {
+                Ok(_) => ProbeResult::pass(format!(
+                    "answered in {:.1}ms",
+                    start.elapsed().as_secs_f64() * 1000.0
+                )),
+                Err(e) => ProbeResult::fail(e.to_string()),
+            }
+        })
+    }
+}
+
+/// Check if an address is a typical block-page sinkhole
+fn is_sinkhole(ip: &IpAddr) -> bool {
+    ip.is_unspecified() || ip.is_loopback()
+}
+
+/// Find the NAT64 prefix of an address synthesized for `ipv4only.arpa`
+///
+/// Tries each RFC 6052 prefix length, skipping the reserved octet (bits
+/// 64-71) when extracting the embedded IPv4 address. Returns the prefix with
+/// host bits cleared, and its length.
+fn nat64_prefix(addr: Ipv6Addr) -> Option<(Ipv6Addr, u8)> {
+    let octets = addr.octets();
+    NAT64_PREFIX_LENGTHS.into_iter().find_map(|len| {
+        let start = usize::from(len / 8);
+        let embedded: Vec<u8> = (start..16).filter(|&i| i != 8).take(4).map(|i| octets[i]).collect();
+        let ipv4 = Ipv4Addr::new(embedded[0], embedded[1], embedded[2], embedded[3]);
+        if !IPV4ONLY_ADDRS.contains(&ipv4) {
+            return None;
+        }
+        let mask = u128::MAX << (128 - u32::from(len));
+        Some((Ipv6Addr::from(u128::from(addr) & mask), len))
+    })
+}
+
+/// Extract the response code from a negative answer
+fn response_code(e: &ResolveError) -> Option<ResponseCode> {
+    match e.proto()?.kind() {
+        ProtoErrorKind::NoRecordsFound { response_code, .. } => Some(*response_code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nat64_prefix() {
+        let prefix = |addr: &str| nat64_prefix(addr.parse().unwrap()).map(|(p, len)| format!("{p}/{len}"));
+
+        // Well-known prefix
+        assert_eq!(prefix("64:ff9b::c000:aa").as_deref(), Some("64:ff9b::/96"));
+        assert_eq!(prefix("64:ff9b::192.0.0.171").as_deref(), Some("64:ff9b::/96"));
+        // Network-specific /64 prefix, IPv4 address after the reserved octet
+        assert_eq!(prefix("2001:db8:1:2:c0:0:aa00:0").as_deref(), Some("2001:db8:1:2::/64"));
+        // /32 prefix
+        assert_eq!(prefix("2001:db8:c000:aa::").as_deref(), Some("2001:db8::/32"));
+        // A real AAAA record, not synthesized
+        assert_eq!(prefix("2606:4700:4700::1111"), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Capability probes run against each server.
+//!
+//! A probe is a single check (e.g. "does this resolver validate DNSSEC?")
+//! with a structured result. Probes implement [`Probe`] and are looked up by
+//! name in a [`ProbeRegistry`], which library users can extend with their own
+//! checks. The CLI enables probes with `--probes`.
+
+#[cfg(feature = "engine")]
+mod builtin;
+mod registry;
+
+#[cfg(feature = "engine")]
+pub use builtin::{Dns64Probe, DnssecProbe, FilteringProbe, HijackProbe, TcpProbe};
+pub use registry::{Probe, ProbeFuture, ProbeRegistry};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Overall outcome of a probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeStatus {
+    /// The server has the capability / behaves correctly
+    Pass,
+    /// The server lacks the capability / misbehaves
+    Fail,
+    /// The probe could not reach a verdict (timeouts, errors)
+    Inconclusive,
+}
+
+impl fmt::Display for ProbeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Fail => write!(f, "fail"),
+            Self::Inconclusive => write!(f, "inconclusive"),
+        }
+    }
+}
+
+/// Structured result of running a probe against one server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// Overall outcome
+    pub status: ProbeStatus,
+    /// Short human-readable explanation
+    pub summary: String,
+}
+
+impl ProbeResult {
+    /// Create a passing result
+    pub fn pass(summary: impl Into<String>) -> Self {
+        Self { status: ProbeStatus::Pass, summary: summary.into() }
+    }
+
+    /// Create a failing result
+    pub fn fail(summary: impl Into<String>) -> Self {
+        Self { status: ProbeStatus::Fail, summary: summary.into() }
+    }
+
+    /// Create an inconclusive result
+    pub fn inconclusive(summary: impl Into<String>) -> Self {
+        Self { status: ProbeStatus::Inconclusive, summary: summary.into() }
+    }
+}
+
+/// Compact marker shown next to a server name for a notable probe outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeGlyph {
+    /// Probe name
+    pub probe: &'static str,
+    /// Outcome that the glyph marks
+    pub status: ProbeStatus,
+    /// The glyph itself
+    pub glyph: &'static str,
+    /// Legend text
+    pub meaning: &'static str,
+    /// Warning shown in the results when the outcome indicates misbehavior
+    pub warning: Option<&'static str>,
+}
+
+/// Glyphs for probe outcomes, in display order
+pub const PROBE_GLYPHS: &[ProbeGlyph] = &[
+    ProbeGlyph {
+        probe: "dnssec",
+        status: ProbeStatus::Pass,
+        glyph: "🔒",
+        meaning: "validates DNSSEC",
+        warning: None,
+    },
+    ProbeGlyph {
+        probe: "filtering",
+        status: ProbeStatus::Fail,
+        glyph: "🚫",
+        meaning: "filters or blocks domains",
+        warning: None,
+    },
+    ProbeGlyph {
+        probe: "hijack",
+        status: ProbeStatus::Fail,
+        glyph: "⚠",
+        meaning: "rewrites NXDOMAIN answers",
+        warning: Some("hijacks NXDOMAIN"),
+    },
+];
+
+/// Check if a probe's outcomes are shown as glyphs
+pub fn has_glyph(probe: &str) -> bool {
+    PROBE_GLYPHS.iter().any(|g| g.probe.eq_ignore_ascii_case(probe))
+}
+
+/// Get the glyphs matching a server's probe results
+pub fn glyphs_for(results: &BTreeMap<String, ProbeResult>) -> impl Iterator<Item = &'static ProbeGlyph> + '_ {
+    PROBE_GLYPHS
+        .iter()
+        .filter(|g| results.get(g.probe).is_some_and(|r| r.status == g.status))
+}
+
+/// Check if a probe can raise a warning
+pub fn has_warning(probe: &str) -> bool {
+    PROBE_GLYPHS
+        .iter()
+        .any(|g| g.warning.is_some() && g.probe.eq_ignore_ascii_case(probe))
+}
+
+/// Get the warnings raised by a server's probe results
+pub fn warnings_for(results: &BTreeMap<String, ProbeResult>) -> impl Iterator<Item = &'static str> + '_ {
+    glyphs_for(results).filter_map(|g| g.warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyphs_for() {
+        let mut results = BTreeMap::new();
+        results.insert("dnssec".to_string(), ProbeResult::pass("rejects invalid signatures"));
+        results.insert("hijack".to_string(), ProbeResult::pass("NXDOMAIN preserved"));
+        results.insert("tcp".to_string(), ProbeResult::fail("timed out"));
+
+        let glyphs: Vec<_> = glyphs_for(&results).map(|g| g.glyph).collect();
+        assert_eq!(glyphs, vec!["🔒"]);
+        assert!(has_glyph("dnssec"));
+        assert!(!has_glyph("tcp"));
+    }
+
+    #[test]
+    fn test_warnings_for() {
+        let mut results = BTreeMap::new();
+        results.insert("dnssec".to_string(), ProbeResult::fail("resolves domains with invalid signatures"));
+        assert_eq!(warnings_for(&results).count(), 0);
+
+        results.insert("hijack".to_string(), ProbeResult::fail("answered 192.0.2.1 for a nonexistent domain"));
+        assert_eq!(warnings_for(&results).collect::<Vec<_>>(), vec!["hijacks NXDOMAIN"]);
+        assert!(has_warning("hijack"));
+        assert!(!has_warning("dnssec"));
+    }
+}
@@ -0,0 +1,168 @@
+//! The `Probe` trait and probe registry.
+
+#[cfg(feature = "engine")]
+use super::builtin::{Dns64Probe, DnssecProbe, FilteringProbe, HijackProbe, TcpProbe};
+use super::ProbeResult;
+use crate::config::Config;
+use crate::dns::DnsServer;
+use crate::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Future returned by [`Probe::run`]
+#[cfg(not(target_arch = "wasm32"))]
+pub type ProbeFuture<'a> = Pin<Box<dyn Future<Output = ProbeResult> + Send + 'a>>;
+
+/// Future returned by [`Probe::run`]
+///
+/// Browser futures (`fetch()` and timers) aren't `Send`, and pages run
+/// probes on one thread anyway.
+#[cfg(target_arch = "wasm32")]
+pub type ProbeFuture<'a> = Pin<Box<dyn Future<Output = ProbeResult> + 'a>>;
+
+/// A capability check run against a single server
+pub trait Probe: Send + Sync {
+    /// Unique name used with `--probes`
+    fn name(&self) -> &'static str;
+
+    /// One-line description for listings
+    fn description(&self) -> &'static str;
+
+    /// Run the probe against a server
+    fn run<'a>(&'a self, server: &'a DnsServer, config: &'a Config) -> ProbeFuture<'a>;
+}
+
+/// Collection of available probes, looked up by name
+#[derive(Clone, Default)]
+pub struct ProbeRegistry {
+    probes: Vec<Arc<dyn Probe>>,
+}
+
+impl ProbeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in probes
+    #[cfg(feature = "engine")]
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Dns64Probe);
+        registry.register(DnssecProbe);
+        registry.register(FilteringProbe);
+        registry.register(HijackProbe);
+        registry.register(TcpProbe);
+        registry
+    }
+
+    /// Add a probe, replacing any existing probe with the same name
+    pub fn register(&mut self, probe: impl Probe + 'static) {
+        self.probes.retain(|p| p.name() != probe.name());
+        self.probes.push(Arc::new(probe));
+    }
+
+    /// Look up a probe by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Probe>> {
+        self.probes.iter().find(|p| p.name().eq_ignore_ascii_case(name)).cloned()
+    }
+
+    /// Iterate over all registered probes
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Probe>> {
+        self.probes.iter()
+    }
+
+    /// Resolve probe names (or `all`) into probes
+    pub fn select(&self, names: &[String]) -> Result<Vec<Arc<dyn Probe>>, Error> {
+        if names.iter().any(|n| n.eq_ignore_ascii_case("all")) {
+            return Ok(self.probes.clone());
+        }
+
+        let mut selected: Vec<Arc<dyn Probe>> = Vec::with_capacity(names.len());
+        for name in names {
+            let probe = self.get(name).ok_or_else(|| {
+                let known: Vec<_> = self.probes.iter().map(|p| p.name()).collect();
+                Error::InvalidArgument(format!("Unknown probe '{name}' (available: {})", known.join(", ")))
+            })?;
+            if !selected.iter().any(|p| p.name() == probe.name()) {
+                selected.push(probe);
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Resolve probe names (or `all`) into probes, skipping unknown names with a warning
+    pub fn select_known(&self, names: &[String]) -> Vec<Arc<dyn Probe>> {
+        let known: Vec<String> = names
+            .iter()
+            .filter(|name| {
+                let known = name.eq_ignore_ascii_case("all") || self.get(name).is_some();
+                if !known {
+                    tracing::warn!("Unknown probe '{name}', skipping it");
+                }
+                known
+            })
+            .cloned()
+            .collect();
+        self.select(&known).expect("only known probes are selected")
+    }
+}
+
+impl std::fmt::Debug for ProbeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.probes.iter().map(|p| p.name())).finish()
+    }
+}
+
+#[cfg(all(test, feature = "engine"))]
+mod tests {
+    use super::*;
+
+    struct AlwaysPass;
+
+    impl Probe for AlwaysPass {
+        fn name(&self) -> &'static str {
+            "always-pass"
+        }
+
+        fn description(&self) -> &'static str {
+            "Passes without sending any query"
+        }
+
+        fn run<'a>(&'a self, _server: &'a DnsServer, _config: &'a Config) -> ProbeFuture<'a> {
+            Box::pin(async { ProbeResult::pass("ok") })
+        }
+    }
+
+    #[test]
+    fn test_select_probes() {
+        let mut registry = ProbeRegistry::builtin();
+        registry.register(AlwaysPass);
+
+        let selected = registry.select(&["TCP".into(), "always-pass".into(), "tcp".into()]).unwrap();
+        let names: Vec<_> = selected.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["tcp", "always-pass"]);
+
+        assert_eq!(registry.select(&["all".into()]).unwrap().len(), 6);
+        assert!(registry.select(&["bogus".into()]).is_err());
+    }
+
+    #[test]
+    fn test_select_known_probes() {
+        let mut registry = ProbeRegistry::new();
+        registry.register(AlwaysPass);
+
+        let selected = registry.select_known(&["bogus".into(), "always-pass".into()]);
+        let names: Vec<_> = selected.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["always-pass"]);
+        assert!(registry.select_known(&["bogus".into()]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_probe_runs() {
+        let server = DnsServer::from_ip("Test", "127.0.0.1".parse().unwrap(), crate::dns::ServerSource::Custom);
+        let result = AlwaysPass.run(&server, &Config::default()).await;
+        assert_eq!(result, ProbeResult::pass("ok"));
+    }
+}
@@ -0,0 +1,406 @@
+//! Scripted resolver test scenarios.
+//!
+//! A scenario is a text file with one step per line, executed in order
+//! against every server:
+//!
+//! ```text
+//! # Resolve a name and expect an answer
+//! query example.com
+//! query example.com AAAA
+//! # Non-existent names must not be hijacked
+//! query does-not-exist.example.com expect nxdomain
+//! sleep 500ms
+//! # 20 concurrent queries, all must be answered
+//! burst 20 example.com
+//! ```
+
+#[cfg(feature = "engine")]
+mod runner;
+
+#[cfg(feature = "engine")]
+pub use runner::run_scenario;
+
+use crate::error::Error;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Record types accepted in `query` and `burst` steps
+pub const RECORD_TYPES: &[&str] = &[
+    "A", "AAAA", "CAA", "CNAME", "HTTPS", "MX", "NS", "PTR", "SOA", "SRV", "SVCB", "TXT",
+];
+
+/// Maximum number of queries in a single burst
+pub const MAX_BURST: u32 = 1000;
+
+/// Expected outcome of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expect {
+    /// At least one record in the answer
+    #[default]
+    Answer,
+    /// NOERROR, with or without records
+    NoError,
+    /// NOERROR with an empty answer
+    NoData,
+    /// NXDOMAIN
+    NxDomain,
+    /// SERVFAIL
+    ServFail,
+    /// REFUSED
+    Refused,
+    /// No response within the timeout
+    Timeout,
+    /// Any outcome passes (timing only)
+    Any,
+}
+
+impl Expect {
+    /// Check if an outcome satisfies this expectation
+    pub fn matches(self, outcome: &Outcome) -> bool {
+        match self {
+            Self::Answer => matches!(outcome, Outcome::Answer(_)),
+            Self::NoError => matches!(outcome, Outcome::Answer(_) | Outcome::NoData),
+            Self::NoData => matches!(outcome, Outcome::NoData),
+            Self::NxDomain => matches!(outcome, Outcome::NxDomain),
+            Self::ServFail => matches!(outcome, Outcome::ServFail),
+            Self::Refused => matches!(outcome, Outcome::Refused),
+            Self::Timeout => matches!(outcome, Outcome::Timeout),
+            Self::Any => true,
+        }
+    }
+}
+
+impl fmt::Display for Expect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Answer => write!(f, "answer"),
+            Self::NoError => write!(f, "noerror"),
+            Self::NoData => write!(f, "nodata"),
+            Self::NxDomain => write!(f, "nxdomain"),
+            Self::ServFail => write!(f, "servfail"),
+            Self::Refused => write!(f, "refused"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Any => write!(f, "any"),
+        }
+    }
+}
+
+impl FromStr for Expect {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "answer" => Ok(Self::Answer),
+            "noerror" => Ok(Self::NoError),
+            "nodata" => Ok(Self::NoData),
+            "nxdomain" => Ok(Self::NxDomain),
+            "servfail" => Ok(Self::ServFail),
+            "refused" => Ok(Self::Refused),
+            "timeout" => Ok(Self::Timeout),
+            "any" => Ok(Self::Any),
+            _ => Err(Error::InvalidArgument(format!("Invalid expectation: {s}"))),
+        }
+    }
+}
+
+/// Outcome of a single query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// NOERROR with this many records
+    Answer(usize),
+    /// NOERROR without records
+    NoData,
+    /// NXDOMAIN
+    NxDomain,
+    /// SERVFAIL
+    ServFail,
+    /// REFUSED
+    Refused,
+    /// No response
+    Timeout,
+    /// Any other failure
+    Error(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Answer(n) => write!(f, "{n} record{}", if *n == 1 { "" } else { "s" }),
+            Self::NoData => write!(f, "nodata"),
+            Self::NxDomain => write!(f, "nxdomain"),
+            Self::ServFail => write!(f, "servfail"),
+            Self::Refused => write!(f, "refused"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Error(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// A query to send in a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySpec {
+    /// Name to query
+    pub name: String,
+    /// Record type (uppercase)
+    pub record_type: String,
+    /// Expected outcome
+    pub expect: Expect,
+}
+
+impl fmt::Display for QuerySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.record_type)?;
+        if self.expect != Expect::Answer {
+            write!(f, " expect {}", self.expect)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single scenario step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Send one query
+    Query(QuerySpec),
+    /// Wait before the next step
+    Sleep(Duration),
+    /// Send `count` identical queries concurrently
+    Burst { count: u32, query: QuerySpec },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query(q) => write!(f, "query {q}"),
+            Self::Sleep(d) => write!(f, "sleep {}ms", d.as_millis()),
+            Self::Burst { count, query } => write!(f, "burst {count} {query}"),
+        }
+    }
+}
+
+/// A parsed scenario
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    /// Scenario name (file stem)
+    pub name: String,
+    /// Steps in execution order
+    pub steps: Vec<Step>,
+}
+
+impl Scenario {
+    /// Load a scenario from a file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content, path)
+    }
+
+    /// Parse a scenario from string content
+    pub fn parse(content: &str, path: &Path) -> Result<Self, Error> {
+        let mut steps = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let step = parse_step(line).map_err(|message| Error::Scenario {
+                path: path.to_path_buf(),
+                line: line_num + 1,
+                message,
+            })?;
+            steps.push(step);
+        }
+
+        if steps.is_empty() {
+            return Err(Error::Scenario {
+                path: path.to_path_buf(),
+                line: 0,
+                message: "scenario has no steps".into(),
+            });
+        }
+
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "scenario".into());
+
+        Ok(Self { name, steps })
+    }
+}
+
+/// Parse a single non-empty, non-comment line
+fn parse_step(line: &str) -> Result<Step, String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+
+    match tokens.as_slice() {
+        ["query", rest @ ..] => parse_query(rest).map(Step::Query),
+        ["sleep", duration] => parse_duration(duration).map(Step::Sleep),
+        ["burst", count, rest @ ..] => {
+            let count: u32 = count
+                .parse()
+                .ok()
+                .filter(|c| (1..=MAX_BURST).contains(c))
+                .ok_or_else(|| format!("burst count must be between 1 and {MAX_BURST}"))?;
+            Ok(Step::Burst { count, query: parse_query(rest)? })
+        }
+        [keyword, ..] => Err(format!("unknown step '{keyword}' (expected query, sleep, or burst)")),
+        [] => Err("empty step".into()),
+    }
+}
+
+/// Parse `<name> [<type>] [expect <outcome>]`
+fn parse_query(tokens: &[&str]) -> Result<QuerySpec, String> {
+    let (name, mut rest) = tokens.split_first().ok_or("missing query name")?;
+    let mut record_type = "A".to_string();
+    let mut expect = Expect::default();
+
+    if let Some((t, tail)) = rest.split_first()
+        && *t != "expect"
+    {
+        let upper = t.to_uppercase();
+        if !RECORD_TYPES.contains(&upper.as_str()) {
+            return Err(format!("unsupported record type '{t}'"));
+        }
+        record_type = upper;
+        rest = tail;
+    }
+
+    match rest {
+        [] => {}
+        ["expect", outcome] => expect = outcome.parse().map_err(|_| format!("invalid expectation '{outcome}'"))?,
+        _ => return Err(format!("unexpected '{}'", rest.join(" "))),
+    }
+
+    Ok(QuerySpec {
+        name: name.to_string(),
+        record_type,
+        expect,
+    })
+}
+
+/// Parse `500`, `500ms`, or `2s`
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{s}'");
+
+    if let Some(ms) = s.strip_suffix("ms") {
+        ms.parse().map(Duration::from_millis).map_err(|_| invalid())
+    } else if let Some(secs) = s.strip_suffix('s') {
+        secs.parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(invalid)
+    } else {
+        s.parse().map(Duration::from_millis).map_err(|_| invalid())
+    }
+}
+
+/// Result of one step against one server
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    /// Step description
+    pub step: String,
+    /// Whether the outcome matched the expectation
+    pub passed: bool,
+    /// Wall time of the step in milliseconds
+    pub duration_ms: f64,
+    /// Observed outcome
+    pub detail: String,
+}
+
+/// Results of a scenario against one server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerScenarioResult {
+    /// Server name
+    pub name: String,
+    /// Server IP
+    pub ip: String,
+    /// Per-step results
+    pub steps: Vec<StepResult>,
+}
+
+impl ServerScenarioResult {
+    /// Check if every step passed
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|s| s.passed)
+    }
+}
+
+/// Results of a scenario against all servers
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    /// Scenario name
+    pub scenario: String,
+    /// Per-server results, in server order
+    pub servers: Vec<ServerScenarioResult>,
+}
+
+impl ScenarioReport {
+    /// Number of failed steps across all servers
+    pub fn failed_steps(&self) -> usize {
+        self.servers
+            .iter()
+            .flat_map(|s| &s.steps)
+            .filter(|s| !s.passed)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Result<Scenario, Error> {
+        Scenario::parse(content, Path::new("checks.txt"))
+    }
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario = parse(
+            "# checks\nquery example.com\nquery example.com aaaa expect noerror\n\
+             query nope.example.com expect NXDOMAIN\nsleep 1.5s\nburst 20 example.com\n",
+        )
+        .unwrap();
+
+        assert_eq!(scenario.name, "checks");
+        assert_eq!(scenario.steps.len(), 5);
+        assert_eq!(
+            scenario.steps[1],
+            Step::Query(QuerySpec {
+                name: "example.com".into(),
+                record_type: "AAAA".into(),
+                expect: Expect::NoError,
+            })
+        );
+        assert!(matches!(&scenario.steps[2], Step::Query(q) if q.expect == Expect::NxDomain));
+        assert_eq!(scenario.steps[3], Step::Sleep(Duration::from_millis(1500)));
+        assert!(matches!(&scenario.steps[4], Step::Burst { count: 20, query } if query.record_type == "A"));
+        assert_eq!(scenario.steps[4].to_string(), "burst 20 example.com A");
+    }
+
+    #[test]
+    fn test_parse_scenario_errors() {
+        assert!(matches!(parse("query"), Err(Error::Scenario { line: 1, .. })));
+        assert!(matches!(parse("\nquery a.com BOGUS"), Err(Error::Scenario { line: 2, .. })));
+        assert!(parse("query a.com expect maybe").is_err());
+        assert!(parse("burst 0 a.com").is_err());
+        assert!(parse("sleep soon").is_err());
+        assert!(parse("resolve a.com").is_err());
+        assert!(parse("# only comments\n").is_err());
+    }
+
+    #[test]
+    fn test_expect_matches() {
+        assert!(Expect::Answer.matches(&Outcome::Answer(2)));
+        assert!(!Expect::Answer.matches(&Outcome::NoData));
+        assert!(Expect::NoError.matches(&Outcome::NoData));
+        assert!(Expect::NxDomain.matches(&Outcome::NxDomain));
+        assert!(!Expect::NxDomain.matches(&Outcome::Answer(1)));
+        assert!(Expect::Any.matches(&Outcome::Error("boom".into())));
+    }
+}
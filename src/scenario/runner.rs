@@ -0,0 +1,174 @@
+//! Scenario execution against DNS servers.
+
+use super::{Outcome, QuerySpec, Scenario, ScenarioReport, ServerScenarioResult, Step, StepResult};
+use crate::benchmark::{create_resolver, BenchResolver};
+use crate::config::Config;
+use crate::dns::DnsServer;
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::proto::rr::RecordType;
+use hickory_resolver::proto::xfer::Protocol;
+use hickory_resolver::proto::ProtoErrorKind;
+use hickory_resolver::ResolveError;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Run a scenario against every server
+///
+/// Steps run sequentially per server; up to `config.workers` servers run in
+/// parallel.
+pub async fn run_scenario(scenario: &Scenario, servers: &[DnsServer], config: &Config) -> ScenarioReport {
+    let semaphore = Arc::new(Semaphore::new(config.workers as usize));
+    let mut tasks = JoinSet::new();
+
+    for (i, server) in servers.iter().enumerate() {
+        let semaphore = Arc::clone(&semaphore);
+        let server = server.clone();
+        let steps = scenario.steps.clone();
+        let config = config.clone();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire().await.unwrap();
+            (i, run_server(&server, &steps, &config).await)
+        });
+    }
+
+    let mut results = Vec::with_capacity(servers.len());
+    while let Some(Ok(result)) = tasks.join_next().await {
+        results.push(result);
+    }
+    results.sort_by_key(|(i, _)| *i);
+
+    ScenarioReport {
+        scenario: scenario.name.clone(),
+        servers: results.into_iter().map(|(_, r)| r).collect(),
+    }
+}
+
+/// Run all steps against one server
+async fn run_server(server: &DnsServer, steps: &[Step], config: &Config) -> ServerScenarioResult {
+    let protocol = match Protocol::try_from(server.protocol.unwrap_or(config.protocol)) {
+        Ok(protocol) => protocol,
+        // DoT and DoH servers can't be queried, so every step fails
+        Err(e) => {
+            return ServerScenarioResult {
+                name: server.name.clone(),
+                ip: server.ip().to_string(),
+                steps: steps
+                    .iter()
+                    .map(|step| StepResult {
+                        step: step.to_string(),
+                        passed: false,
+                        duration_ms: 0.0,
+                        detail: e.to_string(),
+                    })
+                    .collect(),
+            };
+        }
+    };
+    let resolver = create_resolver(
+        server.addr,
+        protocol,
+        config.timeout_ms(),
+        config.lookup_ip.into(),
+        &config.socket,
+    );
+
+    let mut results = Vec::with_capacity(steps.len());
+    for step in steps {
+        let start = Instant::now();
+        let (passed, detail) = match step {
+            Step::Query(query) => {
+                let outcome = query_once(&resolver, query).await;
+                (query.expect.matches(&outcome), outcome.to_string())
+            }
+            Step::Sleep(duration) => {
+                tokio::time::sleep(*duration).await;
+                (true, "-".to_string())
+            }
+            Step::Burst { count, query } => run_burst(&resolver, *count, query).await,
+        };
+
+        results.push(StepResult {
+            step: step.to_string(),
+            passed,
+            duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+            detail,
+        });
+    }
+
+    ServerScenarioResult {
+        name: server.name.clone(),
+        ip: server.ip().to_string(),
+        steps: results,
+    }
+}
+
+/// Send `count` queries concurrently and check every outcome
+async fn run_burst(resolver: &BenchResolver, count: u32, query: &QuerySpec) -> (bool, String) {
+    let mut tasks = JoinSet::new();
+    for _ in 0..count {
+        let resolver = resolver.clone();
+        let query = query.clone();
+        tasks.spawn(async move {
+            let start = Instant::now();
+            let outcome = query_once(&resolver, &query).await;
+            (query.expect.matches(&outcome), start.elapsed())
+        });
+    }
+
+    let mut matched = 0u32;
+    let mut total_time = Duration::ZERO;
+    while let Some(Ok((ok, elapsed))) = tasks.join_next().await {
+        if ok {
+            matched += 1;
+            total_time += elapsed;
+        }
+    }
+
+    let detail = if matched > 0 {
+        let avg_ms = (total_time / matched).as_secs_f64() * 1000.0;
+        format!("{matched}/{count} matched, avg {avg_ms:.1}ms")
+    } else {
+        format!("0/{count} matched")
+    };
+    (matched == count, detail)
+}
+
+/// Send a single query and classify the outcome
+async fn query_once(resolver: &BenchResolver, query: &QuerySpec) -> Outcome {
+    let Ok(record_type) = RecordType::from_str(&query.record_type) else {
+        return Outcome::Error(format!("unsupported record type {}", query.record_type));
+    };
+
+    match resolver.lookup(query.name.as_str(), record_type).await {
+        Ok(lookup) if lookup.records().is_empty() => Outcome::NoData,
+        Ok(lookup) => Outcome::Answer(lookup.records().len()),
+        Err(e) => classify_error(&e),
+    }
+}
+
+/// Map a resolver error to an outcome
+fn classify_error(e: &ResolveError) -> Outcome {
+    match e.proto().map(|p| p.kind()) {
+        Some(ProtoErrorKind::NoRecordsFound { response_code, .. }) => match *response_code {
+            ResponseCode::NoError => Outcome::NoData,
+            ResponseCode::NXDomain => Outcome::NxDomain,
+            ResponseCode::ServFail => Outcome::ServFail,
+            ResponseCode::Refused => Outcome::Refused,
+            code => Outcome::Error(code.to_string()),
+        },
+        Some(ProtoErrorKind::Timeout) => Outcome::Timeout,
+        _ => {
+            let msg = e.to_string();
+            let lower = msg.to_lowercase();
+            if lower.contains("timeout") || lower.contains("timed out") {
+                Outcome::Timeout
+            } else {
+                Outcome::Error(msg)
+            }
+        }
+    }
+}
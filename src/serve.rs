@@ -0,0 +1,177 @@
+//! HTTP endpoint for the `serve` subcommand.
+//!
+//! A minimal HTTP/1.1 server that answers `GET /metrics` with the metrics of
+//! the latest finished run, in the Prometheus text format. Every request gets
+//! its own connection (`Connection: close`), which is all a scraper needs.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Content type of the Prometheus text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Largest request head accepted, in bytes
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// Drop clients that don't send a full request head in time
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, so a persistent error doesn't spin the loop
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Served until the first run finishes
+const NO_RUN_YET: &str = "# No benchmark run has finished yet\n";
+
+/// Metrics endpoint serving the latest rendered metrics
+pub struct MetricsServer {
+    addr: SocketAddr,
+    metrics: Arc<RwLock<String>>,
+    task: JoinHandle<()>,
+}
+
+impl MetricsServer {
+    /// Bind `addr` and start answering requests
+    pub async fn start(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let metrics = Arc::new(RwLock::new(NO_RUN_YET.to_string()));
+
+        let task = {
+            let metrics = Arc::clone(&metrics);
+            tokio::spawn(async move {
+                loop {
+                    let stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        // Running out of file descriptors or a client resetting
+                        // early is transient; keep serving once it passes
+                        Err(e) => {
+                            tracing::warn!("Failed to accept a metrics connection: {e}");
+                            tokio::time::sleep(ACCEPT_BACKOFF).await;
+                            continue;
+                        }
+                    };
+                    let metrics = Arc::clone(&metrics);
+                    tokio::spawn(async move {
+                        let _ = tokio::time::timeout(REQUEST_TIMEOUT, handle(stream, metrics)).await;
+                    });
+                }
+            })
+        };
+
+        Ok(Self { addr, metrics, task })
+    }
+
+    /// Address the endpoint listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Replace the served metrics with those of a newer run
+    pub fn update(&self, metrics: String) {
+        *self.metrics.write().unwrap_or_else(|e| e.into_inner()) = metrics;
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle(mut stream: TcpStream, metrics: Arc<RwLock<String>>) -> io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_BYTES {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", "").await;
+        }
+        let len = stream.read(&mut buf).await?;
+        if len == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..len]);
+    }
+
+    let request_line = String::from_utf8_lossy(&head);
+    let request_line = request_line.lines().next().unwrap_or_default();
+    let (status, body) = match route(request_line) {
+        Route::Metrics => ("200 OK", metrics.read().unwrap_or_else(|e| e.into_inner()).clone()),
+        Route::NotFound => ("404 Not Found", "Not found, metrics are at /metrics\n".to_string()),
+        Route::MethodNotAllowed => ("405 Method Not Allowed", String::new()),
+    };
+    write_response(&mut stream, status, &body).await
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    Metrics,
+    NotFound,
+    MethodNotAllowed,
+}
+
+/// Route a request line such as `GET /metrics HTTP/1.1`
+fn route(request_line: &str) -> Route {
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    if method != "GET" {
+        return Route::MethodNotAllowed;
+    }
+    // Scrapers may add parameters, e.g. `?name[]=...`; all metrics are served regardless
+    match target.split('?').next() {
+        Some("/metrics") => Route::Metrics,
+        _ => Route::NotFound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET /metrics HTTP/1.1"), Route::Metrics);
+        assert_eq!(route("GET /metrics?name[]=up HTTP/1.1"), Route::Metrics);
+        assert_eq!(route("GET / HTTP/1.1"), Route::NotFound);
+        assert_eq!(route("POST /metrics HTTP/1.1"), Route::MethodNotAllowed);
+        assert_eq!(route(""), Route::MethodNotAllowed);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server() {
+        async fn get(addr: SocketAddr, path: &str) -> String {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        }
+
+        let server = MetricsServer::start("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+        let response = get(server.addr(), "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(NO_RUN_YET));
+
+        server.update("dns_benchmark_run_duration_seconds 1.5\n".to_string());
+        let response = get(server.addr(), "/metrics").await;
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.ends_with("\r\n\r\ndns_benchmark_run_duration_seconds 1.5\n"));
+
+        assert!(get(server.addr(), "/").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Opt-in submission of anonymized results to a community endpoint.
+//!
+//! Only what a resolver performance map needs is sent: which public
+//! resolvers were measured, how fast and reliably they answered, and the
+//! country of the network they were measured from. Everything that could
+//! identify the user is left out:
+//!
+//! - system, gateway, and private-address servers (they reveal the ISP or LAN)
+//! - names given to custom servers
+//! - the tested domain, resolved answers, error messages, and the network
+//!   fingerprint
+//! - the public IP prefix (only its GeoIP country is sent)
+//!
+//! Latencies are rounded to 0.1 ms.
+//!
+//! The same community publishes aggregated statistics per region.
+//! [`RegionComparison`] holds a run up against them to tell a slow network
+//! apart from a slow resolver.
+
+use crate::benchmark::{BenchmarkResult, ServerDelta, ServerResult, StoredRun};
+use crate::dns::{Protocol, ServerSource};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Version of the submitted document layout
+pub const SHARE_SCHEMA_VERSION: u32 = 1;
+
+/// Anonymized results of one run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SharePayload {
+    /// Document layout version
+    pub schema: u32,
+    /// Version of dns-benchmark that measured the results
+    pub client_version: String,
+    /// ISO 3166 country the run was made from, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// Protocol queries were sent over
+    pub protocol: Protocol,
+    /// Queries sent to each server
+    pub requests_per_server: u32,
+    /// Measured public resolvers
+    pub servers: Vec<SharedServer>,
+}
+
+/// Anonymized results of one public resolver
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SharedServer {
+    /// Resolver address
+    pub ip: IpAddr,
+    /// Built-in server name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Operating organization, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    pub success_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ms: Option<f64>,
+}
+
+impl SharePayload {
+    /// Build the anonymized payload for a run
+    ///
+    /// `country` is the GeoIP country of the network the run was made from.
+    pub fn from_result(result: &BenchmarkResult, protocol: Protocol, country: Option<String>) -> Self {
+        Self {
+            schema: SHARE_SCHEMA_VERSION,
+            client_version: crate::VERSION.to_string(),
+            country,
+            protocol,
+            requests_per_server: result.requests_per_server,
+            servers: result
+                .servers
+                .iter()
+                .filter(|s| is_shareable(s))
+                .map(SharedServer::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&ServerResult> for SharedServer {
+    fn from(r: &ServerResult) -> Self {
+        let ms = |d: Option<std::time::Duration>| d.map(|d| round1(d.as_secs_f64() * 1000.0));
+        Self {
+            ip: r.ip,
+            name: matches!(r.source, ServerSource::Builtin).then(|| r.name.clone()),
+            provider: r.provider.as_ref().map(|p| p.operator.to_string()),
+            success_rate: round1(r.success_rate()),
+            min_ms: ms(r.min_time),
+            avg_ms: ms(r.avg_time),
+            max_ms: ms(r.max_time),
+        }
+    }
+}
+
+/// Check if a server is a public resolver rather than one tied to the user's network
+fn is_shareable(r: &ServerResult) -> bool {
+    !r.is_system() && !r.is_gateway() && !r.is_dhcp() && !crate::benchmark::is_bogon(&r.ip)
+}
+
+/// Round to one decimal place
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Ratio to the typical latency above which a resolver or network counts as slow
+pub const SLOW_RATIO: f64 = 1.5;
+
+/// Aggregated community results for one region
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RegionStats {
+    /// ISO 3166 country the statistics cover
+    pub country: String,
+    /// Typical results per resolver
+    pub servers: Vec<RegionServer>,
+}
+
+/// Typical results of one resolver in a region
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RegionServer {
+    /// Resolver address
+    pub ip: IpAddr,
+    /// Median of the shared average latencies
+    pub median_ms: f64,
+    /// Number of shared runs the median is based on
+    #[serde(default)]
+    pub samples: u64,
+}
+
+impl RegionStats {
+    /// Parse the statistics document served by the stats endpoint
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// A run compared to the typical latencies of its region
+#[derive(Debug, Clone)]
+pub struct RegionComparison {
+    /// ISO 3166 country of the statistics
+    pub country: String,
+    /// Servers with regional statistics; the baseline is the regional median
+    pub servers: Vec<ServerDelta>,
+}
+
+impl RegionComparison {
+    /// Match a run's servers to the regional statistics by IP
+    pub fn new(run: &StoredRun, stats: &RegionStats) -> Self {
+        let servers = run
+            .results
+            .iter()
+            .filter_map(|cur| {
+                let ip: IpAddr = cur.ip.parse().ok()?;
+                let typical = stats.servers.iter().find(|s| s.ip == ip)?;
+                Some(ServerDelta {
+                    name: cur.name.clone(),
+                    ip: cur.ip.clone(),
+                    base_ms: Some(typical.median_ms),
+                    current_ms: cur.avg_ms,
+                })
+            })
+            .collect();
+
+        Self {
+            country: stats.country.clone(),
+            servers,
+        }
+    }
+
+    /// How much slower than typical this network is across resolvers
+    ///
+    /// The median of the per-resolver ratios, so a single slow resolver
+    /// doesn't make the whole network look slow.
+    pub fn network_ratio(&self) -> Option<f64> {
+        let mut ratios: Vec<f64> = self.servers.iter().filter_map(ratio).collect();
+        if ratios.is_empty() {
+            return None;
+        }
+        ratios.sort_by(f64::total_cmp);
+        let mid = ratios.len() / 2;
+        Some(if ratios.len().is_multiple_of(2) {
+            (ratios[mid - 1] + ratios[mid]) / 2.0
+        } else {
+            ratios[mid]
+        })
+    }
+
+    /// Check if all resolvers are slow here, pointing at the ISP or local network
+    pub fn network_is_slow(&self) -> bool {
+        self.network_ratio().is_some_and(|r| r > SLOW_RATIO)
+    }
+
+    /// Resolvers that are slow here even after accounting for the network
+    pub fn slow_resolvers(&self) -> Vec<&ServerDelta> {
+        let Some(network) = self.network_ratio() else {
+            return Vec::new();
+        };
+        let limit = network.max(1.0) * SLOW_RATIO;
+        self.servers.iter().filter(|d| ratio(d).is_some_and(|r| r > limit)).collect()
+    }
+}
+
+/// Ratio of the measured to the typical latency
+fn ratio(d: &ServerDelta) -> Option<f64> {
+    let base = d.base_ms?;
+    (base > 0.0).then(|| d.current_ms.map(|cur| cur / base)).flatten()
+}
+
+/// Download the statistics for a country from an `https://` endpoint
+///
+/// The country is passed as the `country` query parameter. This call blocks.
+#[cfg(feature = "engine")]
+pub fn fetch_region_stats(endpoint: &str, country: &str) -> Result<RegionStats, crate::Error> {
+    /// Upper bound for the statistics document
+    const MAX_STATS_BYTES: u64 = 4 * 1024 * 1024;
+
+    let stats_error = |message: String| crate::Error::CommunityStats {
+        endpoint: endpoint.to_string(),
+        message,
+    };
+
+    if !endpoint.starts_with("https://") {
+        return Err(stats_error("only https:// endpoints are supported".into()));
+    }
+
+    let separator = if endpoint.contains('?') { '&' } else { '?' };
+    let url = format!("{endpoint}{separator}country={}", country.to_uppercase());
+    let json = crate::dns::download_text(&url, MAX_STATS_BYTES).map_err(stats_error)?;
+    RegionStats::from_json(&json).map_err(|e| stats_error(e.to_string()))
+}
+
+/// Upload a payload to an `https://` endpoint as JSON
+///
+/// This call blocks.
+#[cfg(feature = "engine")]
+pub fn upload(payload: &SharePayload, endpoint: &str) -> Result<(), crate::Error> {
+    use std::time::Duration;
+
+    let share_error = |message: String| crate::Error::Share {
+        endpoint: endpoint.to_string(),
+        message,
+    };
+
+    if !endpoint.starts_with("https://") {
+        return Err(share_error("only https:// endpoints are supported".into()));
+    }
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(10)))
+        .build()
+        .into();
+    let body = serde_json::to_string(payload).map_err(|e| share_error(e.to_string()))?;
+    agent
+        .post(endpoint)
+        .header("Content-Type", "application/json")
+        .send(body)
+        .map_err(|e| share_error(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::TimingResult;
+    use crate::dns::DnsServer;
+    use std::time::Duration;
+
+    fn server(name: &str, ip: &str, source: ServerSource) -> ServerResult {
+        let server = DnsServer::from_ip(name, ip.parse().unwrap(), source);
+        let measurements = vec![
+            TimingResult::Success {
+                duration: Duration::from_micros(12_340),
+                ip: Some("93.184.215.14".parse().unwrap()),
+            },
+            TimingResult::Failure {
+                error: "timeout for secret.corp.example".to_string(),
+            },
+        ];
+        ServerResult::from_measurements(&server, measurements)
+    }
+
+    #[test]
+    fn test_share_payload_anonymization() {
+        let result = BenchmarkResult {
+            domain: "secret.corp.example".to_string(),
+            requests_per_server: 2,
+            ..BenchmarkResult::for_test(vec![
+                server("Cloudflare", "1.1.1.1", ServerSource::Builtin),
+                server("My ISP", "203.0.113.53", ServerSource::System),
+                server("Router", "192.168.1.1", ServerSource::Gateway),
+                server("DHCP DNS (wlan0)", "198.51.100.53", ServerSource::Dhcp),
+                server("Home Pi", "10.0.0.2", ServerSource::Custom),
+                server("Office", "9.9.9.9", ServerSource::Custom),
+            ])
+        };
+
+        let payload = SharePayload::from_result(&result, Protocol::Udp, Some("DE".to_string()));
+
+        let ips: Vec<_> = payload.servers.iter().map(|s| s.ip.to_string()).collect();
+        assert_eq!(ips, ["1.1.1.1", "9.9.9.9"]);
+        assert_eq!(payload.servers[0].name.as_deref(), Some("Cloudflare"));
+        assert_eq!(payload.servers[1].name, None);
+        assert_eq!(payload.servers[0].avg_ms, Some(12.3));
+        assert_eq!(payload.servers[0].success_rate, 50.0);
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert!(!json.contains("secret.corp.example"));
+        assert!(!json.contains("93.184.215.14"));
+        assert!(!json.contains("Office"));
+    }
+
+    #[test]
+    fn test_region_comparison() {
+        let stats = RegionStats::from_json(crate::load_test_fixture!("/share/region-de.json")).unwrap();
+        assert_eq!(stats.country, "DE");
+
+        let run = |results: &str| {
+            let json = format!(r#"{{"meta": {{"domain": "google.com"}}, "results": [{results}]}}"#);
+            StoredRun::from_json_reader(json.as_bytes()).unwrap()
+        };
+        let row = |name: &str, ip: &str, avg: f64| {
+            format!(
+                r#"{{"name": "{name}", "ip": "{ip}", "total_requests": 10, "successful_requests": 10,
+                    "success_rate": 100.0, "avg_ms": {avg}}}"#
+            )
+        };
+
+        // Everything about twice as slow: the network is the bottleneck
+        let slow_network = run(&[
+            row("Cloudflare", "1.1.1.1", 20.0),
+            row("Google", "8.8.8.8", 24.0),
+            row("Quad9", "9.9.9.9", 30.0),
+            row("Home Pi", "10.0.0.2", 1.0),
+        ]
+        .join(","));
+        let comparison = RegionComparison::new(&slow_network, &stats);
+        assert_eq!(comparison.servers.len(), 3);
+        assert_eq!(comparison.network_ratio(), Some(2.0));
+        assert!(comparison.network_is_slow());
+        assert!(comparison.slow_resolvers().is_empty());
+
+        // Typical network, one resolver far off
+        let slow_resolver = run(&[
+            row("Cloudflare", "1.1.1.1", 10.0),
+            row("Google", "8.8.8.8", 12.0),
+            row("Quad9", "9.9.9.9", 60.0),
+        ]
+        .join(","));
+        let comparison = RegionComparison::new(&slow_resolver, &stats);
+        assert!(!comparison.network_is_slow());
+        let slow: Vec<_> = comparison.slow_resolvers().iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(slow, ["Quad9"]);
+    }
+}
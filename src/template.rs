@@ -0,0 +1,313 @@
+//! Shareable benchmark templates.
+//!
+//! A template bundles everything that defines a run into one TOML file: the
+//! settings (domain, probe selection, and every other option) and the custom
+//! server list, inlined. Anyone can then reproduce the run with
+//! `template import` and post their results.
+//!
+//! ```toml
+//! version = 1
+//! description = "European resolvers with DNSSEC probes"
+//!
+//! [servers]
+//! format = "text"
+//! content = """
+//! Quad9;9.9.9.9
+//! DNS4EU;86.54.11.1
+//! """
+//!
+//! [config]
+//! domain = "example.org"
+//! probes = ["dnssec"]
+//! ```
+
+use crate::config::Config;
+use crate::dns::{is_remote_list, parse_server_list, ServersFormat};
+use crate::error::{ConfigError, DnsError, Error};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Template format version written by this build
+pub const TEMPLATE_VERSION: u32 = 1;
+
+/// A benchmark setup that can be shared as a single file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// Format version the template was written with
+    pub version: u32,
+    /// What the template measures, shown on import
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Inlined custom server list; `None` runs the built-in list or a remote one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servers: Option<TemplateServers>,
+    /// Benchmark settings
+    #[serde(default)]
+    pub config: Config,
+}
+
+/// Custom server list carried in a template
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateServers {
+    /// Format of `content`
+    pub format: ServersFormat,
+    /// The server list file contents
+    pub content: String,
+}
+
+impl Template {
+    /// Bundle a configuration with the server list it points to
+    ///
+    /// Local server lists are inlined; remote lists stay URLs. Local settings
+    /// (see [`local_settings`]) are dropped.
+    pub fn from_config(config: &Config, description: Option<String>) -> Result<Self, Error> {
+        let mut config = config.clone();
+        strip_local_settings(&mut config);
+
+        let servers = match config.custom_servers.take() {
+            Some(path) if is_remote_list(&path) => {
+                config.custom_servers = Some(path);
+                None
+            }
+            Some(path) => {
+                let content = fs::read_to_string(&path).map_err(|e| {
+                    Error::Dns(DnsError::CustomFileError {
+                        path: path.clone(),
+                        message: e.to_string(),
+                    })
+                })?;
+                let format = config.servers_format.detect(&path, &content);
+                config.servers_format = ServersFormat::Auto;
+                Some(TemplateServers { format, content })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            version: TEMPLATE_VERSION,
+            description,
+            servers,
+            config,
+        })
+    }
+
+    /// Load a template file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path).map_err(|e| Error::Template {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&content, path)
+    }
+
+    /// Parse a template, rejecting versions newer than this build understands
+    ///
+    /// `path` is used for error messages only.
+    pub fn parse(content: &str, path: &Path) -> Result<Self, Error> {
+        let error = |message: String| Error::Template {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let template: Self = toml::from_str(content).map_err(|e| error(e.to_string()))?;
+        if template.version > TEMPLATE_VERSION {
+            return Err(error(format!(
+                "version {} is newer than the supported version {TEMPLATE_VERSION}; update dns-benchmark",
+                template.version
+            )));
+        }
+        Ok(template)
+    }
+
+    /// Write the template to a file
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = toml::to_string_pretty(self).map_err(ConfigError::SerializeError)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Local settings the template carries, which [`install`](Self::install) ignores
+    pub fn ignored_settings(&self) -> Vec<&'static str> {
+        local_settings(&self.config)
+    }
+
+    /// Turn the template into a configuration
+    ///
+    /// The server list, if any, is checked and written to `servers_path` with
+    /// an extension matching its format, and the configuration points to it.
+    /// Local settings (see [`local_settings`]) are ignored, whoever wrote the
+    /// template.
+    pub fn install(self, servers_path: &Path) -> Result<Config, Error> {
+        let mut config = self.config;
+        strip_local_settings(&mut config);
+        if let Some(servers) = self.servers {
+            let extension = match servers.format {
+                ServersFormat::Json => "json",
+                ServersFormat::Yaml => "yaml",
+                ServersFormat::Text | ServersFormat::Auto => "txt",
+            };
+            let path = servers_path.with_extension(extension);
+            parse_server_list(&servers.content, &path, config.name_server_ip, servers.format)?;
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, servers.content)?;
+            config.custom_servers = Some(path);
+            config.servers_format = servers.format;
+        }
+        Ok(config)
+    }
+}
+
+/// Settings of `config` that belong to one machine or decide where data goes
+///
+/// These are GeoIP database paths, the Windows event log, the share and
+/// statistics endpoints, the bootstrap resolver, the bound network device,
+/// adapter selection, and whether history and the detection cache are used.
+/// A template never carries them, so importing one can't send `--share`
+/// uploads to someone else's endpoint.
+pub fn local_settings(config: &Config) -> Vec<&'static str> {
+    strip_local_settings(&mut config.clone())
+}
+
+/// Reset the local settings of `config` to their defaults, returning the names of those that were set
+fn strip_local_settings(config: &mut Config) -> Vec<&'static str> {
+    let mut stripped = Vec::new();
+    if !config.geoip.is_empty() {
+        config.geoip.clear();
+        stripped.push("geoip");
+    }
+    if config.event_log {
+        config.event_log = false;
+        stripped.push("event_log");
+    }
+    if config.share_endpoint.take().is_some() {
+        stripped.push("share_endpoint");
+    }
+    if config.stats_endpoint.take().is_some() {
+        stripped.push("stats_endpoint");
+    }
+    if config.bootstrap.take().is_some() {
+        stripped.push("bootstrap");
+    }
+    if config.socket.bind_device.take().is_some() {
+        stripped.push("socket.bind_device");
+    }
+    if !config.adapters.is_empty() {
+        config.adapters.clear();
+        stripped.push("adapters");
+    }
+    if !config.skip_adapters.is_empty() {
+        config.skip_adapters.clear();
+        stripped.push("skip_adapters");
+    }
+    if config.no_history {
+        config.no_history = false;
+        stripped.push("no_history");
+    }
+    if config.no_detect_cache {
+        config.no_detect_cache = false;
+        stripped.push("no_detect_cache");
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_template_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let list = dir.path().join("servers.txt");
+        fs::write(&list, "Quad9;9.9.9.9\n").unwrap();
+        let config = Config::builder()
+            .domain("example.org")
+            .probes(vec!["dnssec".to_string()])
+            .custom_servers(list)
+            .geoip(vec![PathBuf::from("/usr/share/GeoIP/GeoLite2-ASN.mmdb")])
+            .build();
+
+        let template = Template::from_config(&config, Some("Quad9 only".to_string())).unwrap();
+        assert!(template.config.geoip.is_empty());
+        assert_eq!(template.config.custom_servers, None);
+        assert_eq!(template.servers.as_ref().unwrap().format, ServersFormat::Text);
+
+        let path = dir.path().join("quad9.toml");
+        template.save(&path).unwrap();
+        let loaded = Template::load(&path).unwrap();
+        assert_eq!(loaded, template);
+
+        let installed = loaded.install(&dir.path().join("template-servers")).unwrap();
+        let installed_list = dir.path().join("template-servers.txt");
+        assert_eq!(installed.custom_servers.as_deref(), Some(installed_list.as_path()));
+        assert_eq!(fs::read_to_string(installed_list).unwrap(), "Quad9;9.9.9.9\n");
+        assert_eq!(installed.domain, "example.org");
+        assert_eq!(installed.probes, ["dnssec"]);
+    }
+
+    #[test]
+    fn test_template_fixture() {
+        let content = crate::load_test_fixture!("/template/eu-resolvers.toml");
+        let template = Template::parse(content, Path::new("eu-resolvers.toml")).unwrap();
+
+        assert_eq!(template.description.as_deref(), Some("European resolvers with DNSSEC probes"));
+        assert_eq!(template.config.domain, "example.org");
+        assert_eq!(template.config.requests, 100);
+        assert_eq!(template.config.probes, ["dnssec", "hijack"]);
+        // Unset options keep their defaults
+        assert_eq!(template.config.workers, crate::DEFAULT_WORKERS);
+
+        let servers = template.servers.unwrap();
+        assert_eq!(servers.format, ServersFormat::Text);
+        assert_eq!(servers.content.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_template_local_settings_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "version = 1\n\n[config]\ndomain = \"example.org\"\n\
+                       share_endpoint = \"https://results.attacker.example/upload\"\n\
+                       adapters = [\"Ethernet\"]\n\n[config.socket]\nbind_device = \"eth1\"\n";
+        let template = Template::parse(content, Path::new("shared.toml")).unwrap();
+        assert_eq!(template.ignored_settings(), ["share_endpoint", "socket.bind_device", "adapters"]);
+
+        let installed = template.install(&dir.path().join("template-servers")).unwrap();
+        assert_eq!(installed.share_endpoint, None);
+        assert_eq!(installed.socket.bind_device, None);
+        assert!(installed.adapters.is_empty());
+        assert_eq!(installed.domain, "example.org");
+
+        // Exports leave them out in the first place
+        let config = Config::builder().stats_endpoint("https://stats.example").bootstrap([9, 9, 9, 9].into()).build();
+        assert_eq!(local_settings(&config), ["stats_endpoint", "bootstrap"]);
+        let template = Template::from_config(&config, None).unwrap();
+        assert!(template.ignored_settings().is_empty());
+    }
+
+    #[test]
+    fn test_template_newer_version() {
+        let err = Template::parse("version = 2\n", Path::new("future.toml")).unwrap_err();
+        assert!(err.to_string().contains("newer than the supported version 1"));
+    }
+
+    #[test]
+    fn test_template_invalid_servers() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = Template {
+            version: TEMPLATE_VERSION,
+            description: None,
+            servers: Some(TemplateServers {
+                format: ServersFormat::Text,
+                content: "not a server list".to_string(),
+            }),
+            config: Config::default(),
+        };
+
+        assert!(template.install(&dir.path().join("template-servers")).is_err());
+        assert!(!dir.path().join("template-servers.txt").exists());
+    }
+}